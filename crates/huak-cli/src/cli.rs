@@ -1,58 +1,185 @@
-use crate::error::{CliResult, Error};
+use crate::{
+    complete, defaults,
+    error::{CliResult, Error, ErrorStyle},
+};
 use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::{self, Shell};
 use huak_home::huak_home_dir;
 use huak_package_manager::ops::{
-    self, install as install_op, AddOptions, BuildOptions, CleanOptions, FormatOptions,
-    LintOptions, PublishOptions, RemoveOptions, TestOptions, UpdateOptions,
+    self, install as install_op, ActivateOptions, ActivationShell, AddOptions, BuildOptions,
+    CheckOptions, CleanOptions, FileFilter, FixOptions, FormatOptions, HistoryOptions,
+    KernelInstallOptions, LintOptions, ListFormat, ListOptions, ListPythonOptions, OutputFormat,
+    ProjectTemplate, PublishOptions, RemoveOptions, RenameOptions, ReportFormat, ReportOptions,
+    ReportSection, SbomFormat, SbomOptions, SetuptoolsConversionOptions, ShardBy, TestOptions,
+    TestShard, UpdateOptions, UsePythonOptions, VendorSyncOptions, VersionOptions,
 };
 use huak_package_manager::{
-    Config, Error as HuakError, HuakResult, InstallOptions, TerminalOptions, Verbosity,
+    check_huak_min_version, in_container, is_root, pep440_version, CommandPlan, Config,
+    Error as HuakError, HuakResult, InstallOptions, TerminalOptions, VenvOptions, Verbosity,
     WorkspaceOptions,
 };
 use huak_python_manager::RequestedVersion;
 use huak_toolchain::{Channel, LocalTool};
 use huak_workspace::{resolve_root, PathMarker};
 use pep508_rs::Requirement;
-use std::{env::current_dir, path::PathBuf, process::ExitCode, str::FromStr};
+use std::{
+    collections::HashMap,
+    env::current_dir,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
 use termcolor::ColorChoice;
 use url::Url;
 
 /// A Python package manager written in Rust inspired by Cargo.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Parser)]
 #[command(version, author, about, arg_required_else_help = true)]
 pub struct Cli {
     #[command(subcommand)]
-    command: Commands,
+    pub(crate) command: Commands,
     #[arg(short, long, global = true)]
-    quiet: bool,
+    pub(crate) quiet: bool,
+    /// Print more about what Huak is doing, like the programs `huak run` resolves.
+    #[arg(short, long, global = true, conflicts_with = "quiet")]
+    pub(crate) verbose: bool,
     #[arg(long, global = true)]
     no_color: bool,
+    /// Print errors for people, or as JSON with a stable code for scripts (human, json).
+    #[arg(long, global = true, value_name = "FORMAT", default_value = "human")]
+    error_format: OutputFormat,
+    /// Kill subprocesses that run longer than <SECS> seconds.
+    #[arg(long, global = true, value_name = "SECS")]
+    timeout: Option<u64>,
+    /// Print what would change without changing anything.
+    #[arg(long, global = true)]
+    dry_run: bool,
+    /// Print how long huak's steps and the commands it runs take.
+    #[arg(long, global = true)]
+    timings: bool,
+    /// Use the defaults configured in [tool.huak.profile.<NAME>] (`HUAK_PROFILE`).
+    #[arg(long, global = true, value_name = "NAME")]
+    pub(crate) profile: Option<String>,
+    /// Use the Python interpreter instead of a virtual environment (`HUAK_SYSTEM_PYTHON=1`).
+    #[arg(long, global = true)]
+    system: bool,
+    /// Use the environment configured in [tool.huak.envs.<NAME>] instead of the project's .venv.
+    #[arg(
+        long = "env",
+        global = true,
+        value_name = "NAME",
+        conflicts_with = "system"
+    )]
+    env_name: Option<String>,
+    /// Allow --system to change an externally managed Python (PEP 668).
+    #[arg(long, global = true)]
+    break_system_packages: bool,
+    /// Allow commands that change files to run as root (`HUAK_ALLOW_ROOT=1`).
+    #[arg(long, global = true)]
+    allow_root: bool,
+    /// Fail commands that print warnings (`[tool.huak] strict = true`).
+    #[arg(long, global = true)]
+    strict: bool,
+    /// Warnings for the deprecated flags and `[tool.huak]` keys the command was parsed with.
+    #[arg(skip)]
+    pub(crate) deprecations: Vec<String>,
 }
 
 // List of commands.
 #[derive(Subcommand)]
 #[clap(rename_all = "kebab-case")]
-enum Commands {
+pub(crate) enum Commands {
     /// Activate the virtual environment.
-    Activate,
+    Activate {
+        /// Print shell code to activate the virtual environment with `eval` instead.
+        #[arg(long, conflicts_with_all = ["print_deactivate", "print_source"])]
+        print: bool,
+        /// Print shell code to deactivate a virtual environment activated with --print.
+        #[arg(long, conflicts_with = "print_source")]
+        print_deactivate: bool,
+        /// Print the command that sources the virtual environment's activation script instead.
+        #[arg(long)]
+        print_source: bool,
+        /// The shell to print code for (bash, zsh, fish, csh, powershell, nu) [default: detected].
+        #[arg(long, value_name = "SHELL")]
+        shell: Option<ActivationShell>,
+        /// Create the virtual environment if it doesn't exist.
+        #[arg(long, conflicts_with = "print_deactivate")]
+        create: bool,
+    },
     /// Add dependencies to the project.
     Add {
         #[arg(num_args = 1.., required = true)]
         dependencies: Vec<Dependency>,
-        /// Adds an optional dependency group.
+        /// Add to an optional dependency group (repeat to add to several).
         #[arg(long)]
-        group: Option<String>,
+        group: Vec<String>,
+        /// Add the dependencies to a script's inline metadata instead.
+        #[arg(long, value_name = "PATH", conflicts_with = "group")]
+        script: Option<PathBuf>,
+        /// Only require the dependencies on Python versions matching a constraint, like "<3.10".
+        #[arg(long, value_name = "CONSTRAINT", conflicts_with = "script")]
+        python_constraint: Option<String>,
+        /// Add the dependencies even if they conflict with the project's other requirements.
+        #[arg(long, conflicts_with = "script")]
+        allow_conflicts: bool,
+        /// Only install wheels for these packages (comma-separated, or :all:).
+        #[arg(long, value_name = "PACKAGES")]
+        only_binary: Option<String>,
+        /// Never install wheels for these packages (comma-separated, or :all:).
+        #[arg(long, value_name = "PACKAGES")]
+        no_binary: Option<String>,
+        /// Install only from the vendor directory (see `huak vendor sync`).
+        #[arg(long)]
+        offline: bool,
+        /// Print the resolver's decisions while installing.
+        #[arg(long)]
+        verbose_resolution: bool,
         /// Pass trailing arguments with `--`.
         #[arg(last = true)]
         trailing: Option<Vec<String>>,
     },
     /// Build tarball and wheel for the project.
     Build {
+        /// Build wheels for each platform and supported Python version with cibuildwheel.
+        #[arg(long)]
+        cibuildwheel: bool,
+        /// Report the built artifacts as human-readable messages or JSON (human, json).
+        #[arg(long, value_name = "FORMAT", default_value = "human")]
+        format: OutputFormat,
+        /// Write the artifacts' sha256 digests to SHA256SUMS and a .sha256 file next to each.
+        #[arg(long)]
+        checksums: bool,
+        /// Check that the artifacts contain the project's package and [tool.huak.build] include.
+        #[arg(long)]
+        verify_contents: bool,
+        /// Build from a temporary checkout of the committed files, leaving out uncommitted ones.
+        #[arg(long, conflicts_with = "cibuildwheel")]
+        from_clean: bool,
+        /// Build from a clean checkout even if the working tree has uncommitted changes.
+        #[arg(long, requires = "from_clean")]
+        allow_dirty: bool,
         /// Pass trailing arguments with `--`.
         #[arg(last = true)]
         trailing: Option<Vec<String>>,
     },
+    /// Manage Huak's cache.
+    Cache {
+        #[command(subcommand)]
+        command: Cache,
+    },
+    /// Read the project's CHANGELOG.md.
+    Changelog {
+        #[command(subcommand)]
+        command: Changelog,
+    },
+    /// Check the project's metadata and configured policies.
+    Check {
+        /// Enforce the dependency policies configured in [tool.huak.policy].
+        #[arg(long, required = false)]
+        policies: bool,
+    },
     /// Remove tarball and wheel from the built project.
     Clean {
         #[arg(long, required = false)]
@@ -61,14 +188,47 @@ enum Commands {
         #[arg(long, required = false)]
         /// Remove all __pycache__ directories.
         include_pycache: bool,
+        /// Remove the dependency history (.huak/history.jsonl).
+        #[arg(long)]
+        include_history: bool,
+        /// The directory build artifacts were written to [default: dist].
+        #[arg(long, value_name = "PATH")]
+        out_dir: Option<PathBuf>,
+        /// Only remove build artifacts older than each package's <N> latest versions.
+        #[arg(long, value_name = "N")]
+        keep_latest: Option<usize>,
+        /// Remove files git tracks too (they're kept by default).
+        #[arg(long)]
+        include_tracked: bool,
     },
     /// Generates a shell completion script for supported shells.
     Completion {
         #[arg(short, long, value_name = "shell")]
         shell: Option<Shell>,
     },
+    /// Show the defaults Huak runs commands with.
+    Config {
+        #[command(subcommand)]
+        command: Configuration,
+    },
+    /// Install local checkouts in place of the project's dependencies.
+    Develop {
+        #[command(subcommand)]
+        command: Develop,
+    },
+    /// Manage the project's virtual environment.
+    Env {
+        #[command(subcommand)]
+        command: Env,
+    },
     /// Auto-fix fixable lint conflicts
     Fix {
+        /// Print the changes the fixes would make without making them.
+        #[arg(long)]
+        diff: bool,
+        /// Fix files with uncommitted changes.
+        #[arg(long)]
+        allow_dirty: bool,
         /// Pass trailing arguments with `--`.
         #[arg(last = true)]
         trailing: Option<Vec<String>>,
@@ -78,24 +238,62 @@ enum Commands {
         /// Check if Python code is formatted.
         #[arg(long)]
         check: bool,
+        /// Only format files matching the glob (relative to the project root).
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+        /// Don't format files matching the glob, even if they're included.
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+        /// Format every file, even those unchanged since they were last formatted.
+        #[arg(long)]
+        no_cache: bool,
         /// Pass trailing arguments with `--`.
         #[arg(last = true)]
         trailing: Option<Vec<String>>,
     },
+    /// Show the changes huak has made to the project's dependencies.
+    History {
+        /// Only show changes made on or after a date (YYYY-MM-DD) or UTC timestamp.
+        #[arg(long, value_name = "DATE")]
+        since: Option<String>,
+        /// Only show changes to a package.
+        #[arg(long, value_name = "NAME")]
+        package: Option<String>,
+        /// Print the raw JSON records.
+        #[arg(long)]
+        json: bool,
+    },
     /// Initialize the current project.
     Init {
         /// Use an application template.
-        #[arg(long, conflicts_with = "lib")]
+        #[arg(long, conflicts_with_all = ["lib", "bare", "cli"])]
         app: bool,
+        /// Use a command-line application template.
+        #[arg(long, conflicts_with_all = ["app", "lib", "bare"])]
+        cli: bool,
         /// Use a library template [default].
-        #[arg(long, conflicts_with = "app")]
+        #[arg(long, conflicts_with_all = ["app", "bare", "cli"])]
         lib: bool,
+        /// Only generate the manifest file.
+        #[arg(long, conflicts_with_all = ["app", "lib", "cli"])]
+        bare: bool,
         /// Don't initialize VCS in the project
         #[arg(long)]
         no_vcs: bool,
+        /// Commit the generated files to the new git repository [default message: "Initial commit"].
+        // Allow `option_option` because it's how clap takes a flag with an optional value.
+        #[allow(clippy::option_option)]
+        #[arg(long, value_name = "MESSAGE", require_equals = true, conflicts_with_all = ["no_vcs", "from_setup_py"])]
+        initial_commit: Option<Option<String>>,
         /// Initialize with a project manifest.
         #[arg(long)]
         manifest: Option<PathBuf>,
+        /// Convert the metadata in setup.py and setup.cfg to pyproject.toml.
+        #[arg(long, conflicts_with_all = ["app", "cli", "lib", "bare", "manifest"])]
+        from_setup_py: bool,
+        /// Remove setup.py and setup.cfg once the converted project installs.
+        #[arg(long, requires = "from_setup_py", conflicts_with = "no_env")]
+        remove_legacy: bool,
         // TODO(cnpryer): https://github.com/cnpryer/huak/issues/853
         // /// Initialize with requirements files.
         // #[arg(short, long)]
@@ -105,10 +303,25 @@ enum Commands {
         /// Initialize without setting up a Python environment.
         #[arg(long)]
         no_env: bool,
-        /// Optional dependency groups to install.
+        /// Give the virtual environment access to the system site-packages.
         #[arg(long)]
-        optional_dependencies: Option<Vec<String>>,
-        /// Force the initialization.
+        system_site_packages: bool,
+        /// The prompt shown while the virtual environment is activated.
+        #[arg(long)]
+        prompt: Option<String>,
+        /// Copy the Python executable into the virtual environment instead of symlinking it.
+        #[arg(long, conflicts_with = "symlinks")]
+        copies: bool,
+        /// Symlink the Python executable into the virtual environment instead of copying it.
+        #[arg(long)]
+        symlinks: bool,
+        /// Optional dependency groups to install.
+        #[arg(long, alias = "optional-dependencies")]
+        groups: Option<Vec<String>>,
+        /// Don't install the groups in [tool.huak] default-groups or `HUAK_DEFAULT_GROUPS`.
+        #[arg(long, conflicts_with = "groups")]
+        no_default_groups: bool,
+        /// Overwrite existing files (backing each up to <name>.bak) and the Python environment.
         #[arg(short, long)]
         force: bool,
         /// Pass trailing arguments with `--`.
@@ -117,9 +330,9 @@ enum Commands {
     },
     /// Install a Python package (defaults to $HOME/.huak/bin).
     Install {
-        /// The Python package to install.
-        #[arg(required = true)]
-        package: Requirement,
+        /// The Python package to install. With `--target`, defaults to the project's dependencies.
+        #[arg(required_unless_present = "target")]
+        package: Option<Requirement>,
         /// The Python version to use.  TODO(cnpryer): https://github.com/cnpryer/huak/issues/850
         #[arg(long, alias = "py", required = false)]
         python_version: Option<RequestedVersion>,
@@ -131,6 +344,30 @@ enum Commands {
             required = false
         )] // TODO(cnpryer): Names
         package_index_url: Url,
+        /// Only install wheels for these packages (comma-separated, or :all:).
+        #[arg(long, value_name = "PACKAGES")]
+        only_binary: Option<String>,
+        /// Never install wheels for these packages (comma-separated, or :all:).
+        #[arg(long, value_name = "PACKAGES")]
+        no_binary: Option<String>,
+        /// Install only from the vendor directory (see `huak vendor sync`).
+        #[arg(long)]
+        offline: bool,
+        /// Print the resolver's decisions while installing.
+        #[arg(long)]
+        verbose_resolution: bool,
+        /// Install into a directory instead of an environment, like for bundling a deployment.
+        #[arg(long, value_name = "DIR")]
+        target: Option<PathBuf>,
+        /// Print the commands the install would run, with their environment and working
+        /// directory, without running them.
+        #[arg(long)]
+        explain: bool,
+    },
+    /// Register the project's environment as a Jupyter kernel.
+    Kernel {
+        #[command(subcommand)]
+        command: Kernel,
     },
     /// Lint the project's Python code.
     Lint {
@@ -140,26 +377,97 @@ enum Commands {
         /// Perform type-checking.
         #[arg(long)]
         no_types: bool,
+        /// Enable the comma-separated rule codes (e.g. "E,F401").
+        #[arg(long, value_name = "CODES")]
+        select: Option<String>,
+        /// Disable the comma-separated rule codes (e.g. "E501").
+        #[arg(long, value_name = "CODES")]
+        ignore: Option<String>,
+        /// Only lint files matching the glob (relative to the project root).
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+        /// Don't lint files matching the glob, even if they're included.
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+        /// Only fail on `ruff` violations that aren't recorded in the baseline file.
+        #[arg(long, value_name = "FILE", conflicts_with = "fix")]
+        baseline: Option<PathBuf>,
+        /// Record the current `ruff` violations in the --baseline file.
+        #[arg(long, requires = "baseline")]
+        write_baseline: bool,
+        /// Also write the `ruff` violations to a SARIF file for code scanning.
+        #[arg(long, value_name = "PATH", conflicts_with = "fix")]
+        sarif: Option<PathBuf>,
+        /// Lint every file, even those unchanged since they last passed.
+        #[arg(long)]
+        no_cache: bool,
         /// Pass trailing arguments with `--` to `ruff`.
         #[arg(last = true)]
         trailing: Option<Vec<String>>,
     },
+    /// List the packages installed in the project's environment.
+    List {
+        /// List packages as columns, `name==version` lines, or JSON (columns, freeze, json).
+        #[arg(long, value_name = "FORMAT", default_value = "columns")]
+        format: ListFormat,
+        /// Only list packages with newer versions on the package index.
+        #[arg(long)]
+        outdated: bool,
+    },
+    /// Get or set the project's metadata.
+    Metadata {
+        #[command(subcommand)]
+        command: Metadata,
+    },
+    /// Move the project's dependencies from [tool.poetry] to PEP 621's [project] table.
+    Migrate,
     /// Create a new project at <path>.
     New {
         /// Use an application template.
-        #[arg(long, conflicts_with = "lib")]
+        #[arg(long, conflicts_with_all = ["lib", "bare", "cli"])]
         app: bool,
+        /// Use a command-line application template.
+        #[arg(long, conflicts_with_all = ["app", "lib", "bare"])]
+        cli: bool,
         /// Use a library template [default].
-        #[arg(long, conflicts_with = "app")]
+        #[arg(long, conflicts_with_all = ["app", "bare", "cli"])]
         lib: bool,
+        /// Only generate the manifest file (no package or tests).
+        #[arg(long, conflicts_with_all = ["app", "lib", "cli"])]
+        bare: bool,
         /// Path and name of the python package
         path: String,
         /// Don't initialize VCS in the new project
         #[arg(long)]
         no_vcs: bool,
+        /// Commit the generated files to the new git repository [default message: "Initial commit"].
+        // Allow `option_option` because it's how clap takes a flag with an optional value.
+        #[allow(clippy::option_option)]
+        #[arg(
+            long,
+            value_name = "MESSAGE",
+            require_equals = true,
+            conflicts_with = "no_vcs"
+        )]
+        initial_commit: Option<Option<String>>,
+        /// Use a non-empty directory, skipping files that already exist.
+        #[arg(short, long)]
+        force: bool,
     },
     /// Builds and uploads current project to a registry.
     Publish {
+        /// Publish a version that's already on the index, only uploading new files.
+        #[arg(long)]
+        skip_existing: bool,
+        /// Publish with uncommitted changes in the working tree.
+        #[arg(long)]
+        allow_dirty: bool,
+        /// Publish from a HEAD that isn't tagged with the project's version.
+        #[arg(long)]
+        allow_untagged: bool,
+        /// Publish without verifying the artifacts against their SHA256SUMS.
+        #[arg(long)]
+        no_checksums: bool,
         /// Pass trailing arguments with `--`.
         #[arg(last = true)]
         trailing: Option<Vec<String>>,
@@ -172,19 +480,121 @@ enum Commands {
     },
     /// Remove dependencies from the project.
     Remove {
-        #[arg(num_args = 1.., required = true)]
+        #[arg(num_args = 1.., required_unless_present = "purge_group")]
         dependencies: Vec<String>,
+        /// Remove the dependencies from an optional dependency group.
+        #[arg(long)]
+        group: Option<String>,
+        /// Remove the group and all of its dependencies.
+        #[arg(long, requires = "group", conflicts_with = "dependencies")]
+        purge_group: bool,
+        /// Keep the manifest change if uninstalling the dependencies fails.
+        #[arg(long)]
+        keep_manifest: bool,
         /// Pass trailing arguments with `--`.
         #[arg(last = true)]
         trailing: Option<Vec<String>>,
     },
+    /// Rename the project, its package, and its imports.
+    Rename {
+        /// The project's new name.
+        name: String,
+        /// Reinstall the project if its virtual environment has it installed under the old name.
+        #[arg(long)]
+        reinstall: bool,
+    },
+    /// Write a health report of the project as markdown or JSON.
+    Report {
+        /// The sections to include (deps, python, policy, lint, tests, coverage) [default: all].
+        #[arg(long, value_delimiter = ',', value_name = "SECTIONS")]
+        sections: Vec<ReportSection>,
+        /// Write the report as markdown or JSON (markdown, json).
+        #[arg(long, value_name = "FORMAT", default_value = "markdown")]
+        format: ReportFormat,
+        /// Write the report to a file instead of stdout.
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+        /// An XML report of the last test run (like `huak test -- --junitxml=<PATH>`).
+        #[arg(long, value_name = "PATH")]
+        junit: Option<PathBuf>,
+        /// A Cobertura XML coverage report [default: coverage.xml].
+        #[arg(long, value_name = "PATH")]
+        coverage: Option<PathBuf>,
+        /// A lint baseline written by `huak lint --write-baseline`.
+        #[arg(long, value_name = "PATH")]
+        lint_baseline: Option<PathBuf>,
+    },
     /// Run a command with Huak.
     Run {
+        /// Run a script with the dependencies from its inline metadata.
+        #[arg(long, value_name = "PATH")]
+        script: Option<PathBuf>,
+        /// List the scripts the installed distributions provide.
+        #[arg(long, conflicts_with_all = ["script", "command"])]
+        list_scripts: bool,
+        /// Print the tasks and executables that can be run as JSON.
+        #[arg(long, conflicts_with_all = ["script", "list_scripts", "command"])]
+        json: bool,
+        /// Run the rest of the `--`-separated steps after one fails.
+        #[arg(long, conflicts_with_all = ["script", "list_scripts", "json"])]
+        keep_going: bool,
+        /// A command, or steps separated by `--` to run in order (like `lint -- test`).
         #[arg(trailing_var_arg = true)]
         command: Vec<String>,
     },
+    /// Write a software bill of materials for the project's environment.
+    Sbom {
+        /// The specification to write the SBOM with (cyclonedx-json, spdx-json).
+        #[arg(long, value_name = "FORMAT", default_value = "cyclonedx-json")]
+        format: SbomFormat,
+        /// Write the SBOM to a file instead of stdout.
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
     /// Test the project's Python code.
     Test {
+        /// Only run one shard of the tests, like 2/4 for the second of four.
+        #[arg(long, value_name = "INDEX/TOTAL")]
+        shard: Option<TestShard>,
+        /// Partition tests by a hash of their IDs or by recorded durations (hash, duration).
+        #[arg(
+            long,
+            value_name = "STRATEGY",
+            default_value = "hash",
+            requires = "shard"
+        )]
+        shard_by: ShardBy,
+        /// Run the tests in a random order (with pytest-randomly), optionally with a seed.
+        // Allow `option_option` because it's how clap takes a flag with an optional value.
+        #[allow(clippy::option_option)]
+        #[arg(long, value_name = "SEED")]
+        shuffle: Option<Option<u64>>,
+        /// Rerun the tests until they fail, to hunt for flaky tests.
+        #[arg(long)]
+        until_failure: bool,
+        /// Stop --until-failure after <N> passing runs.
+        #[arg(
+            long,
+            value_name = "N",
+            requires = "until_failure",
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        max_runs: Option<usize>,
+        /// Discover tests in <DIR> (repeatable) instead of the configured test paths.
+        #[arg(long, value_name = "DIR")]
+        test_path: Vec<PathBuf>,
+        /// Install the dependencies in an optional dependency group (repeatable) before testing.
+        #[arg(long, value_name = "GROUP")]
+        only_group: Vec<String>,
+        /// Don't install the project's required dependencies with --only-group.
+        #[arg(long, requires = "only_group")]
+        no_default_deps: bool,
+        /// Show the tests' output as it's printed instead of capturing it.
+        #[arg(short = 's', long)]
+        no_capture: bool,
+        /// Show log records at <LEVEL> or above as they're emitted (like DEBUG or INFO).
+        #[arg(long, value_name = "LEVEL")]
+        log_cli_level: Option<String>,
         /// Pass trailing arguments with `--`.
         #[arg(last = true)]
         trailing: Option<Vec<String>>,
@@ -199,16 +609,159 @@ enum Commands {
     Update {
         #[arg(num_args = 0..)]
         dependencies: Option<Vec<String>>,
+        /// Upgrade past the dependencies' current version specifiers.
+        #[arg(long)]
+        latest: bool,
+        /// Upgrade unbounded dependencies past their installed major version.
+        #[arg(long)]
+        allow_major: bool,
+        /// Install only from the vendor directory (see `huak vendor sync`).
+        #[arg(long)]
+        offline: bool,
+        /// Print the resolver's decisions while installing.
+        #[arg(long)]
+        verbose_resolution: bool,
         /// Pass trailing arguments with `--`.
         #[arg(last = true)]
         trailing: Option<Vec<String>>,
     },
+    /// Manage the project's vendored dependencies.
+    Vendor {
+        #[command(subcommand)]
+        command: Vendor,
+    },
     /// Display the version of the project.
-    Version,
+    Version {
+        /// Write a new PEP 440 version to the project's pyproject.toml.
+        #[arg(long, value_name = "VERSION", conflicts_with_all = ["is_newer_than", "is_prerelease", "format"])]
+        set: Option<String>,
+        /// Exit with 0 if the project's version is newer than VERSION, or 1 if it isn't.
+        #[arg(long, value_name = "VERSION")]
+        is_newer_than: Option<String>,
+        /// Exit with 0 if the project's version is a pre-release, or 1 if it isn't.
+        #[arg(long)]
+        is_prerelease: bool,
+        /// Report the version and checks as human-readable messages or JSON (human, json).
+        #[arg(long, value_name = "FORMAT", default_value = "human")]
+        format: OutputFormat,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Cache {
+    /// List the cached script environments.
+    List,
+    /// Remove everything in the cache.
+    Clean,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Changelog {
+    /// Print the changelog entry for a version (defaults to the project's version).
+    Get { version: Option<String> },
+    /// Check the changelog has an entry for the project's version and no unreleased changes.
+    Verify,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Configuration {
+    /// Print the options configured for each command, merged with the --profile's.
+    Show,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Develop {
+    /// Install a local checkout editable in place of a dependency (without changing pyproject.toml).
+    Add {
+        /// The dependency to override.
+        dependency: String,
+        /// The path of the local checkout.
+        path: PathBuf,
+    },
+    /// List the active dependency overrides.
+    List,
+    /// Remove a dependency override, reinstalling the declared version.
+    Remove {
+        /// The dependency to stop overriding.
+        dependency: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Env {
+    /// Create the project's virtual environment.
+    Create {
+        /// Give the virtual environment access to the system site-packages.
+        #[arg(long)]
+        system_site_packages: bool,
+        /// The prompt shown while the virtual environment is activated.
+        #[arg(long)]
+        prompt: Option<String>,
+        /// Copy the Python executable into the virtual environment instead of symlinking it.
+        #[arg(long, conflicts_with = "symlinks")]
+        copies: bool,
+        /// Symlink the Python executable into the virtual environment instead of copying it.
+        #[arg(long)]
+        symlinks: bool,
+    },
+    /// List the project's environments with the groups they were built with.
+    List,
+    /// Remove one of the project's environments (defaults to --env or the project's .venv).
+    Remove {
+        /// The name of an environment configured in [tool.huak.envs].
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Kernel {
+    /// Register a kernel named after the project, installing ipykernel if it's missing.
+    Install,
+    /// List the kernels huak registered with their projects.
+    List,
+    /// Unregister a kernel huak registered (defaults to the project's).
+    Remove { name: Option<String> },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Metadata {
+    /// Print the value of a pyproject.toml key.
+    Get {
+        /// A dotted key (like project.urls.Homepage).
+        key: String,
+    },
+    /// Set the value of a pyproject.toml key.
+    Set {
+        /// A dotted key (like project.urls.Homepage). End with += to append to an array.
+        key: String,
+        /// The value to set. Values starting with [ or { are parsed as TOML.
+        value: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum Vendor {
+    /// Download the project's dependencies into the vendor directory.
+    Sync {
+        /// A platform to download wheels for (like `manylinux2014_x86_64`) [default: current].
+        #[arg(long = "platform", value_name = "PLATFORM")]
+        platforms: Vec<String>,
+        /// A Python version to download wheels for (like 3.11) [default: current].
+        #[arg(long = "python-version", value_name = "VERSION")]
+        python_versions: Vec<String>,
+    },
+    /// Check the vendor directory has every dependency of the project and nothing else.
+    Verify,
 }
 
 #[derive(Subcommand)]
-enum Python {
+pub(crate) enum Python {
+    /// Check that a Python interpreter is available and print its path.
+    Check {
+        /// The version of Python to check for (major.minor or exact).
+        #[arg(required = true)]
+        version: RequestedVersion,
+    },
     /// Install a Python interpreter.
     Install {
         /// The version of Python to install.
@@ -216,17 +769,24 @@ enum Python {
         version: RequestedVersion,
     },
     /// List available Python interpreters.
-    List,
+    List {
+        /// Print the interpreters as JSON.
+        #[arg(long)]
+        json: bool,
+    },
     /// Use an available Python interpreter.
     Use {
         /// The version of Python to use.
         #[arg(required = true)]
         version: RequestedVersion,
+        /// Recreate an existing virtual environment built with another Python version.
+        #[arg(long)]
+        recreate: bool,
     },
 }
 
 #[derive(Subcommand)]
-enum Toolchain {
+pub(crate) enum Toolchain {
     /// Add a tool to a toolchain.
     Add {
         /// A tool to add.
@@ -286,6 +846,12 @@ enum Toolchain {
         #[arg(long, required = false)]
         channel: Option<Channel>,
     },
+    /// Upgrade the tools pinned in [tool.huak.toolchain-lock] and move their pins forward.
+    Upgrade {
+        /// Only upgrade a tool (like `ruff`).
+        #[arg(required = false)]
+        tool: Option<String>,
+    },
     /// Use an available toolchain.
     Use {
         /// The toolchain channel to use.
@@ -293,50 +859,214 @@ enum Toolchain {
     },
 }
 
+impl Commands {
+    /// Check if the command only reads the project, environments and toolchains.
+    fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Commands::Activate { create: false, .. }
+                | Commands::Cache {
+                    command: Cache::List
+                }
+                | Commands::Changelog { .. }
+                | Commands::Check { .. }
+                | Commands::Completion { .. }
+                | Commands::Config { .. }
+                | Commands::Develop {
+                    command: Develop::List
+                }
+                | Commands::Env { command: Env::List }
+                | Commands::Fix { diff: true, .. }
+                | Commands::History { .. }
+                | Commands::Kernel {
+                    command: Kernel::List
+                }
+                | Commands::List { .. }
+                | Commands::Metadata {
+                    command: Metadata::Get { .. }
+                }
+                | Commands::Python {
+                    command: Python::Check { .. } | Python::List { .. }
+                }
+                | Commands::Report { output: None, .. }
+                | Commands::Run {
+                    list_scripts: true,
+                    ..
+                }
+                | Commands::Run { json: true, .. }
+                | Commands::Sbom { output: None, .. }
+                | Commands::Toolchain {
+                    command: Toolchain::Info { .. } | Toolchain::List
+                }
+                | Commands::Vendor {
+                    command: Vendor::Verify
+                }
+                | Commands::Version { set: None, .. }
+        )
+    }
+}
+
 // Command gating for Huak.
 impl Cli {
-    pub fn run(self) -> CliResult<i32> {
+    /// Get how the `Cli`'s errors are printed.
+    pub fn error_style(&self) -> ErrorStyle {
+        ErrorStyle {
+            format: self.error_format,
+            verbose: self.verbose,
+        }
+    }
+
+    pub fn run(self) -> CliResult<()> {
         let cwd = current_dir()?;
         let mut config = get_config(cwd, &self);
 
-        match exec_command(self.command, &mut config) {
-            Ok(()) => Ok(0),
-            // TODO: Implement our own ExitCode or status handler.
-            Err(HuakError::SubprocessFailure(e)) => Ok(e.code().unwrap_or_default()),
-            Err(e) => Err(Error::new(e, ExitCode::FAILURE)),
+        if !self.dry_run && !self.command.is_read_only() && is_root() {
+            guard_root(&self, &config)?;
+        }
+
+        for it in &self.deprecations {
+            config.terminal().print_warning(it)?;
         }
+
+        check_huak_min_version(&config)?;
+
+        let start = Instant::now();
+        let res = exec_command(self.command, &mut config);
+        config
+            .terminal()
+            .print_profile("finished", start.elapsed())?;
+
+        res.and_then(|()| promote_warnings(&config))
+            .map_err(Error::from)
     }
 }
 
+/// Fail with the warnings printed by a strict `Config`'s terminals.
+fn promote_warnings(config: &Config) -> HuakResult<()> {
+    let warnings = config.terminal_options.promoted_warnings();
+
+    if warnings.is_empty() {
+        return Ok(());
+    }
+
+    Err(HuakError::PromotedWarnings(
+        warnings
+            .iter()
+            .map(|it| format!("  - {it}"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    ))
+}
+
+/// Refuse to run as root unless it's allowed, since files huak creates would be owned by root.
+/// Running as root is expected with the system Python and in containers, so it's only warned
+/// about there.
+fn guard_root(cli: &Cli, config: &Config) -> HuakResult<()> {
+    let allowed = cli.allow_root
+        || std::env::var("HUAK_ALLOW_ROOT").is_ok_and(|it| it == "1")
+        || config.system_python
+        || in_container();
+    if !allowed {
+        return Err(HuakError::RunningAsRoot);
+    }
+
+    config.terminal().print_warning(
+        "huak is running as root; files it creates in the project will be owned by root",
+    )
+}
+
 // TODO(cnpryer): Might be a [lints] bug.
 #[allow(clippy::too_many_lines)]
 fn exec_command(cmd: Commands, config: &mut Config) -> HuakResult<()> {
     match cmd {
-        Commands::Activate => activate(config),
+        Commands::Activate {
+            print,
+            print_deactivate,
+            print_source,
+            shell,
+            create,
+        } => {
+            let options = ActivateOptions {
+                create,
+                print,
+                print_deactivate,
+                print_source,
+                shell,
+            };
+            activate(&options, config)
+        }
         Commands::Add {
             dependencies,
             group,
+            script,
+            python_constraint,
+            allow_conflicts,
+            only_binary,
+            no_binary,
+            offline,
+            verbose_resolution,
             trailing,
         } => {
             let options = AddOptions {
-                install_options: InstallOptions { values: trailing },
+                install_options: InstallOptions {
+                    values: trailing,
+                    only_binary,
+                    no_binary,
+                    offline,
+                    verbose_resolution,
+                    ..Default::default()
+                },
+                python_constraint,
+                allow_conflicts,
             };
-            add(&dependencies, group.as_ref(), &options, config)
+            match script {
+                Some(it) => add_script(&it, &dependencies, config),
+                None => add(&dependencies, &group, &options, config),
+            }
         }
-        Commands::Build { trailing } => {
+        Commands::Build {
+            cibuildwheel,
+            format,
+            checksums,
+            verify_contents,
+            from_clean,
+            allow_dirty,
+            trailing,
+        } => {
             let options = BuildOptions {
                 values: trailing,
-                install_options: InstallOptions { values: None },
+                cibuildwheel,
+                env: HashMap::new(),
+                format,
+                checksums,
+                verify_contents,
+                from_clean,
+                allow_dirty,
+                install_options: InstallOptions::default(),
             };
             build(&options, config)
         }
+        Commands::Cache { command } => cache(&command, config),
+        Commands::Changelog { command } => changelog(&command, config),
+        Commands::Check { policies } => {
+            let options = CheckOptions { policies };
+            check(&options, config)
+        }
         Commands::Clean {
             include_pyc,
             include_pycache,
+            include_history,
+            out_dir,
+            keep_latest,
+            include_tracked,
         } => {
             let options = CleanOptions {
                 include_pycache,
                 include_compiled_bytecode: include_pyc,
+                include_history,
+                dist_dir: out_dir,
+                keep_latest,
+                include_tracked,
             };
             clean(&options, config)
         }
@@ -345,15 +1075,30 @@ fn exec_command(cmd: Commands, config: &mut Config) -> HuakResult<()> {
             completion(&options);
             Ok(())
         }
-        Commands::Fix { trailing } => {
-            let options = LintOptions {
+        Commands::Config { command } => configuration(&command, config),
+        Commands::Develop { command } => develop(&command, config),
+        Commands::Env { command } => env(command, config),
+        Commands::Fix {
+            diff,
+            allow_dirty,
+            trailing,
+        } => {
+            let options = FixOptions {
                 values: trailing,
-                include_types: false,
-                install_options: InstallOptions { values: None },
+                diff,
+                allow_dirty,
+                env: HashMap::new(),
+                install_options: InstallOptions::default(),
             };
             fix(&options, config)
         }
-        Commands::Fmt { check, trailing } => {
+        Commands::Fmt {
+            check,
+            include,
+            exclude,
+            no_cache,
+            trailing,
+        } => {
             let mut args = if check {
                 vec!["--check".to_string()]
             } else {
@@ -364,35 +1109,90 @@ fn exec_command(cmd: Commands, config: &mut Config) -> HuakResult<()> {
             }
             let options = FormatOptions {
                 values: Some(args),
-                install_options: InstallOptions { values: None },
+                files: FileFilter { include, exclude },
+                env: HashMap::new(),
+                no_cache,
+                install_options: InstallOptions::default(),
             };
             fmt(&options, config)
         }
+        Commands::History {
+            since,
+            package,
+            json,
+        } => {
+            let options = HistoryOptions {
+                since,
+                package,
+                json,
+            };
+            history(&options, config)
+        }
         Commands::Init {
             app,
-            lib,
+            cli,
+            lib: _,
+            bare,
             no_vcs,
+            initial_commit,
             manifest,
+            from_setup_py,
+            remove_legacy,
             no_env,
-            optional_dependencies,
+            system_site_packages,
+            prompt,
+            copies,
+            symlinks,
+            groups,
+            no_default_groups,
             trailing,
             force,
         } => {
             config.workspace_root = config.cwd.clone();
+            config.venv_options = VenvOptions {
+                system_site_packages,
+                prompt,
+                copies,
+                symlinks,
+            };
             let workspace_options = WorkspaceOptions {
                 uses_git: !no_vcs,
+                force,
                 values: None,
+                initial_commit: initial_commit_message(initial_commit),
             };
 
-            let install_options = InstallOptions { values: trailing }; // TODO(cnpryer)
+            let install_options = InstallOptions {
+                values: trailing,
+                ..Default::default()
+            }; // TODO(cnpryer)
+
+            if from_setup_py {
+                let options = SetuptoolsConversionOptions {
+                    force,
+                    remove_legacy,
+                };
+                ops::init_from_setup_py(config, &options)?;
+                if no_env {
+                    return Ok(());
+                }
+                return ops::init_python_env(
+                    None,
+                    groups,
+                    no_default_groups,
+                    force,
+                    &install_options,
+                    config,
+                );
+            }
 
             // TODO(cnpryer): Use `WorkspaceOptions` where possible.
             init(
                 manifest,
-                optional_dependencies,
-                app,
+                groups,
+                no_default_groups,
+                project_template(app, cli, bare),
                 force,
-                lib,
                 no_env,
                 &install_options,
                 &workspace_options,
@@ -403,10 +1203,58 @@ fn exec_command(cmd: Commands, config: &mut Config) -> HuakResult<()> {
             package,
             python_version,
             package_index_url,
-        } => install(&package, python_version, &package_index_url, config),
+            only_binary,
+            no_binary,
+            offline,
+            verbose_resolution,
+            target,
+            explain,
+        } => {
+            let options = InstallOptions {
+                values: None,
+                only_binary,
+                no_binary,
+                offline,
+                verbose_resolution,
+                target: None,
+            };
+            if explain {
+                config.dry_run = true;
+                config.plan = Some(CommandPlan::default());
+            }
+            let res = match (package, target) {
+                (package, Some(target)) => {
+                    install_to_target(package.as_ref(), &target, &options, config)
+                }
+                (Some(package), None) => install(
+                    &package,
+                    python_version,
+                    &package_index_url,
+                    &options,
+                    config,
+                ),
+                (None, None) => Err(HuakError::InternalError(
+                    "missing a package to install".to_string(),
+                )),
+            };
+            // The steps planned before a failure are still printed.
+            if let Some(plan) = config.plan.as_ref() {
+                print!("{plan}");
+            }
+            res
+        }
+        Commands::Kernel { command } => kernel(&command, config),
         Commands::Lint {
             fix,
             no_types,
+            select,
+            ignore,
+            include,
+            exclude,
+            baseline,
+            write_baseline,
+            sarif,
+            no_cache,
             trailing,
         } => {
             let mut args = if fix {
@@ -419,60 +1267,189 @@ fn exec_command(cmd: Commands, config: &mut Config) -> HuakResult<()> {
             }
             let options = LintOptions {
                 values: Some(args),
+                select,
+                ignore,
+                files: FileFilter { include, exclude },
                 include_types: !no_types,
-                install_options: InstallOptions { values: None },
+                baseline,
+                write_baseline,
+                sarif,
+                env: HashMap::new(),
+                no_cache,
+                install_options: InstallOptions::default(),
             };
             lint(config, &options)
         }
+        Commands::List { format, outdated } => {
+            let options = ListOptions { format, outdated };
+            ops::list_packages(config, &options)
+        }
+        Commands::Migrate => ops::migrate_poetry_project(config),
         Commands::New {
             path,
             app,
-            lib,
+            cli,
+            lib: _,
+            bare,
             no_vcs,
+            initial_commit,
+            force,
         } => {
             config.workspace_root = PathBuf::from(path);
             let options = WorkspaceOptions {
                 uses_git: !no_vcs,
+                force,
                 values: None,
+                initial_commit: initial_commit_message(initial_commit),
             };
-            new(&options, app, lib, config)
+            new(&options, project_template(app, cli, bare), config)
         }
-        Commands::Publish { trailing } => {
+        Commands::Publish {
+            skip_existing,
+            allow_dirty,
+            allow_untagged,
+            no_checksums,
+            trailing,
+        } => {
             let options = PublishOptions {
                 values: trailing,
-                install_options: InstallOptions { values: None },
+                env: HashMap::new(),
+                install_options: InstallOptions::default(),
+                skip_existing,
+                allow_dirty,
+                allow_untagged,
+                no_checksums,
             };
             publish(&options, config)
         }
+        Commands::Metadata { command } => metadata(command, config),
         Commands::Python { command } => python(command, config),
         Commands::Remove {
             dependencies,
+            group,
+            purge_group,
+            keep_manifest,
             trailing,
         } => {
             let options = RemoveOptions {
-                install_options: InstallOptions { values: trailing },
+                purge_group,
+                keep_manifest,
+                install_options: InstallOptions {
+                    values: trailing,
+                    ..Default::default()
+                },
+            };
+            remove(&dependencies, group.as_deref(), &options, config)
+        }
+        Commands::Rename { name, reinstall } => {
+            let options = RenameOptions {
+                reinstall,
+                install_options: InstallOptions::default(),
+            };
+            ops::rename_project(&name, config, &options)
+        }
+        Commands::Report {
+            sections,
+            format,
+            output,
+            junit,
+            coverage,
+            lint_baseline,
+        } => {
+            let options = ReportOptions {
+                sections,
+                format,
+                output,
+                junit,
+                coverage,
+                lint_baseline,
             };
-            remove(&dependencies, &options, config)
+            ops::generate_report(config, &options)
+        }
+        Commands::Run {
+            script,
+            list_scripts,
+            json,
+            keep_going,
+            command,
+        } => match script {
+            Some(it) => ops::run_script(&it, &command, config),
+            None if list_scripts => ops::list_entry_point_scripts(config),
+            None if json => ops::list_run_targets(config, true),
+            None if keep_going || command.iter().any(|it| it == "--") => {
+                ops::run_command_sequence(&command, keep_going, config)
+            }
+            None => run(&command, config),
+        },
+        Commands::Sbom { format, output } => {
+            ops::generate_sbom(config, &SbomOptions { format, output })
         }
-        Commands::Run { command } => run(&command, config),
-        Commands::Test { trailing } => {
+        Commands::Test {
+            shard,
+            shard_by,
+            shuffle,
+            until_failure,
+            max_runs,
+            test_path,
+            only_group,
+            no_default_deps,
+            no_capture,
+            log_cli_level,
+            trailing,
+        } => {
             let options = TestOptions {
                 values: trailing,
-                install_options: InstallOptions { values: None },
+                env: HashMap::new(),
+                install_options: InstallOptions::default(),
+                groups: only_group,
+                no_default_deps,
+                shard,
+                shard_by,
+                shuffle: shuffle.is_some(),
+                seed: shuffle.flatten(),
+                until_failure,
+                max_runs,
+                paths: test_path,
+                no_capture,
+                log_cli_level,
             };
             test(&options, config)
         }
         Commands::Toolchain { command } => toolchain(command, config),
         Commands::Update {
             dependencies,
+            latest,
+            allow_major,
+            offline,
+            verbose_resolution,
             trailing,
         } => {
             let options = UpdateOptions {
-                install_options: InstallOptions { values: trailing },
+                install_options: InstallOptions {
+                    values: trailing,
+                    offline,
+                    verbose_resolution,
+                    ..Default::default()
+                },
+                latest,
+                allow_major,
             };
             update(dependencies, &options, config)
         }
-        Commands::Version => version(config),
+        Commands::Vendor { command } => vendor(command, config),
+        Commands::Version {
+            set,
+            is_newer_than,
+            is_prerelease,
+            format,
+        } => {
+            let options = VersionOptions {
+                is_newer_than,
+                is_prerelease,
+                format,
+            };
+            version(set.as_deref(), &options, config)
+        }
     }
 }
 
@@ -481,11 +1458,15 @@ fn get_config(cwd: PathBuf, cli: &Cli) -> Config {
     let ws = resolve_root(&cwd, PathMarker::file("pyproject.toml"));
     let verbosity = if cli.quiet {
         Verbosity::Quiet
+    } else if cli.verbose {
+        Verbosity::Verbose
     } else {
         Verbosity::Normal
     };
     let terminal_options = TerminalOptions {
         verbosity,
+        timeout: cli.timeout.map(Duration::from_secs),
+        profile: cli.timings,
         ..Default::default()
     };
     let mut config = Config {
@@ -493,23 +1474,29 @@ fn get_config(cwd: PathBuf, cli: &Cli) -> Config {
         cwd,
         terminal_options,
         home: huak_home_dir(),
+        dry_run: cli.dry_run,
+        system_python: cli.system || std::env::var("HUAK_SYSTEM_PYTHON").is_ok_and(|it| it == "1"),
+        break_system_packages: cli.break_system_packages,
+        venv_options: VenvOptions::default(),
+        env_name: cli.env_name.clone(),
+        profile: cli.profile.clone(),
+        huak_version: pep440_version(env!("CARGO_PKG_VERSION")),
+        plan: None,
     };
     if cli.no_color {
-        config.terminal_options = TerminalOptions {
-            verbosity,
-            color_choice: ColorChoice::Never,
-        };
+        config.terminal_options.color_choice = ColorChoice::Never;
     }
+    config.terminal_options.strict = cli.strict || config.workspace().is_strict();
     config
 }
 
-fn activate(config: &Config) -> HuakResult<()> {
-    ops::activate_python_environment(config)
+fn activate(options: &ActivateOptions, config: &Config) -> HuakResult<()> {
+    ops::activate_python_environment(config, options)
 }
 
 fn add(
     dependencies: &[Dependency],
-    group: Option<&String>,
+    groups: &[String],
     options: &AddOptions,
     config: &Config,
 ) -> HuakResult<()> {
@@ -517,45 +1504,129 @@ fn add(
         .iter()
         .map(std::string::ToString::to_string)
         .collect::<Vec<String>>();
-    match group.as_ref() {
-        Some(it) => ops::add_project_optional_dependencies(&deps, it, config, options),
-        None => ops::add_project_dependencies(&deps, config, options),
+    if groups.is_empty() {
+        ops::add_project_dependencies(&deps, config, options)
+    } else {
+        ops::add_project_optional_dependencies(&deps, groups, config, options)
     }
 }
 
+fn add_script(path: &Path, dependencies: &[Dependency], config: &Config) -> HuakResult<()> {
+    let deps = dependencies
+        .iter()
+        .map(std::string::ToString::to_string)
+        .collect::<Vec<String>>();
+    ops::add_script_dependencies(path, &deps, config)
+}
+
 fn build(options: &BuildOptions, config: &Config) -> HuakResult<()> {
-    ops::build_project(config, options)
+    let report = ops::build_project(config, options)?;
+
+    if options.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    }
+
+    Ok(())
+}
+
+fn cache(command: &Cache, config: &Config) -> HuakResult<()> {
+    match command {
+        Cache::List => ops::list_cache(config),
+        Cache::Clean => ops::clean_cache(config),
+    }
+}
+
+fn changelog(command: &Changelog, config: &Config) -> HuakResult<()> {
+    match command {
+        Changelog::Get { version } => ops::display_changelog_entry(version.as_deref(), config),
+        Changelog::Verify => ops::verify_changelog(config),
+    }
+}
+
+fn check(options: &CheckOptions, config: &Config) -> HuakResult<()> {
+    ops::check_project(config, options)
 }
 
 fn clean(options: &CleanOptions, config: &Config) -> HuakResult<()> {
     ops::clean_project(config, options)
 }
 
-fn fix(options: &LintOptions, config: &Config) -> HuakResult<()> {
-    ops::lint_project(config, options)
+fn configuration(command: &Configuration, config: &Config) -> HuakResult<()> {
+    match command {
+        Configuration::Show => defaults::show_defaults(config),
+    }
+}
+
+fn develop(command: &Develop, config: &Config) -> HuakResult<()> {
+    let options = InstallOptions::default();
+
+    match command {
+        Develop::Add { dependency, path } => {
+            ops::add_dependency_override(dependency, path, &options, config)
+        }
+        Develop::List => ops::list_dependency_overrides(config),
+        Develop::Remove { dependency } => {
+            ops::remove_dependency_override(dependency, &options, config)
+        }
+    }
+}
+
+fn env(command: Env, config: &mut Config) -> HuakResult<()> {
+    match command {
+        Env::Create {
+            system_site_packages,
+            prompt,
+            copies,
+            symlinks,
+        } => {
+            config.venv_options = VenvOptions {
+                system_site_packages,
+                prompt,
+                copies,
+                symlinks,
+            };
+            ops::create_python_environment(config)
+        }
+        Env::List => ops::list_python_environments(config),
+        Env::Remove { name } => {
+            if name.is_some() {
+                config.env_name = name;
+            }
+            ops::remove_python_environment(config)
+        }
+    }
+}
+
+fn fix(options: &FixOptions, config: &Config) -> HuakResult<()> {
+    ops::fix_project(config, options)
 }
 
 fn fmt(options: &FormatOptions, config: &Config) -> HuakResult<()> {
     ops::format_project(config, options)
 }
 
+fn history(options: &HistoryOptions, config: &Config) -> HuakResult<()> {
+    ops::display_history(config, options)
+}
+
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::fn_params_excessive_bools)]
 fn init(
     manifest: Option<PathBuf>,
     optional_dependencies: Option<Vec<String>>,
-    app: bool,
+    no_default_groups: bool,
+    template: ProjectTemplate,
     force: bool,
     no_env: bool,
-    _lib: bool,
     install_options: &InstallOptions,
     workspace_options: &WorkspaceOptions,
     config: &Config,
 ) -> HuakResult<()> {
-    let res = if app {
-        ops::init_app_project(config, workspace_options)
-    } else {
-        ops::init_lib_project(config, workspace_options)
+    let res = match template {
+        ProjectTemplate::App => ops::init_app_project(config, workspace_options),
+        ProjectTemplate::Cli => ops::init_cli_project(config, workspace_options),
+        ProjectTemplate::Lib => ops::init_lib_project(config, workspace_options),
+        ProjectTemplate::Bare => ops::init_bare_project(config, workspace_options),
     };
 
     // If initialization failed because a manifest file already exists and the project
@@ -567,9 +1638,14 @@ fn init(
         .map_or(true, |it| matches!(it, HuakError::ManifestFileFound))
         && !no_env
     {
+        if let Err(e) = res {
+            config.terminal().print_warning(e)?;
+        }
+
         ops::init_python_env(
             manifest,
             optional_dependencies,
+            no_default_groups,
             force,
             install_options,
             config,
@@ -583,37 +1659,109 @@ fn install(
     package: &Requirement,
     python_version: Option<RequestedVersion>,
     package_index_url: &Url,
+    options: &InstallOptions,
     config: &Config,
 ) -> HuakResult<()> {
-    install_op(package, python_version, package_index_url.as_str(), config)
+    install_op(
+        package,
+        python_version,
+        package_index_url.as_str(),
+        options,
+        config,
+    )
+}
+
+fn install_to_target(
+    package: Option<&Requirement>,
+    target: &Path,
+    options: &InstallOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    ops::install_to_target(package, target, options, config)
+}
+
+fn kernel(command: &Kernel, config: &Config) -> HuakResult<()> {
+    match command {
+        Kernel::Install => {
+            let options = KernelInstallOptions {
+                install_options: InstallOptions::default(),
+            };
+            ops::install_kernel(config, &options)
+        }
+        Kernel::List => ops::list_kernels(config),
+        Kernel::Remove { name } => ops::remove_kernel(name.as_deref(), config),
+    }
 }
 
 fn lint(config: &Config, options: &LintOptions) -> HuakResult<()> {
     ops::lint_project(config, options)
 }
 
-fn new(options: &WorkspaceOptions, app: bool, _lib: bool, config: &Config) -> HuakResult<()> {
+fn new(options: &WorkspaceOptions, template: ProjectTemplate, config: &Config) -> HuakResult<()> {
+    match template {
+        ProjectTemplate::App => ops::new_app_project(config, options),
+        ProjectTemplate::Cli => ops::new_cli_project(config, options),
+        ProjectTemplate::Lib => ops::new_lib_project(config, options),
+        ProjectTemplate::Bare => ops::new_bare_project(config, options),
+    }
+}
+
+/// Get the `ProjectTemplate` selected by the `--app`, `--cli`, and `--bare` flags.
+fn project_template(app: bool, cli: bool, bare: bool) -> ProjectTemplate {
     if app {
-        ops::new_app_project(config, options)
+        ProjectTemplate::App
+    } else if cli {
+        ProjectTemplate::Cli
+    } else if bare {
+        ProjectTemplate::Bare
     } else {
-        ops::new_lib_project(config, options)
+        ProjectTemplate::Lib
     }
 }
 
+/// Get the message of the `--initial-commit`, if one was requested.
+// Allow `option_option` because it's how clap takes a flag with an optional value.
+#[allow(clippy::option_option)]
+fn initial_commit_message(initial_commit: Option<Option<String>>) -> Option<String> {
+    initial_commit.map(|it| it.unwrap_or_else(|| "Initial commit".to_string()))
+}
+
 fn publish(options: &PublishOptions, config: &Config) -> HuakResult<()> {
     ops::publish_project(config, options)
 }
 
+fn metadata(command: Metadata, config: &Config) -> HuakResult<()> {
+    match command {
+        Metadata::Get { key } => ops::get_metadata(&key, config),
+        Metadata::Set { key, value } => ops::set_metadata(&key, value.as_deref(), config),
+    }
+}
+
 fn python(command: Python, config: &Config) -> HuakResult<()> {
     match command {
-        Python::List => ops::list_python(config),
-        Python::Use { version } => ops::use_python(&version, config),
-        Python::Install { version } => ops::install_python(version),
+        Python::Check { version } => ops::check_python(&version, config),
+        Python::List { json } => ops::list_python(config, &ListPythonOptions { json }),
+        Python::Use { version, recreate } => {
+            let options = UsePythonOptions {
+                recreate,
+                install_options: InstallOptions::default(),
+            };
+            ops::use_python(&version, config, &options)
+        }
+        Python::Install { version } => ops::install_python(version, config),
     }
 }
 
-fn remove(dependencies: &[String], options: &RemoveOptions, config: &Config) -> HuakResult<()> {
-    ops::remove_project_dependencies(dependencies, config, options)
+fn remove(
+    dependencies: &[String],
+    group: Option<&str>,
+    options: &RemoveOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    match group {
+        Some(it) => ops::remove_project_optional_dependencies(dependencies, it, config, options),
+        None => ops::remove_project_dependencies(dependencies, config, options),
+    }
 }
 
 fn run(command: &[String], config: &Config) -> HuakResult<()> {
@@ -640,6 +1788,9 @@ fn toolchain(command: Toolchain, config: &Config) -> HuakResult<()> {
         Toolchain::Update { tool, channel } => {
             ops::update_toolchain(tool, channel.as_ref(), config)
         }
+        Toolchain::Upgrade { tool } => {
+            ops::upgrade_self_toolchain(tool.as_deref(), &InstallOptions::default(), config)
+        }
         Toolchain::Use { channel } => ops::use_toolchain(&channel, config),
     }
 }
@@ -652,8 +1803,42 @@ fn update(
     ops::update_project_dependencies(dependencies, config, options)
 }
 
-fn version(config: &Config) -> HuakResult<()> {
-    ops::display_project_version(config)
+fn vendor(command: Vendor, config: &Config) -> HuakResult<()> {
+    match command {
+        Vendor::Sync {
+            platforms,
+            python_versions,
+        } => {
+            let options = VendorSyncOptions {
+                platforms,
+                python_versions,
+            };
+            ops::sync_vendor_dir(config, &options)
+        }
+        Vendor::Verify => ops::verify_vendor_dir(config),
+    }
+}
+
+fn version(set: Option<&str>, options: &VersionOptions, config: &Config) -> HuakResult<()> {
+    if let Some(it) = set {
+        return ops::set_project_version(it, config);
+    }
+
+    if options.format == OutputFormat::Human && !options.has_checks() {
+        return ops::display_project_version(config);
+    }
+
+    let report = ops::project_version_report(config, options)?;
+
+    if options.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    }
+
+    if report.passed() {
+        Ok(())
+    } else {
+        Err(HuakError::VersionCheckFailed(report.version))
+    }
 }
 
 fn completion(options: &CompletionOptions) {
@@ -666,12 +1851,12 @@ struct CompletionOptions {
 
 fn generate_shell_completion_script(shell: Option<Shell>) {
     let mut cmd = Cli::command();
-    clap_complete::generate(
-        shell.unwrap_or(Shell::Bash),
-        &mut cmd,
-        "huak",
-        &mut std::io::stdout(),
-    );
+    let shell = shell.unwrap_or(Shell::Bash);
+    clap_complete::generate(shell, &mut cmd, "huak", &mut std::io::stdout());
+
+    if let Some(it) = complete::dynamic_completion_script(shell) {
+        print!("{it}");
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -681,7 +1866,16 @@ impl FromStr for Dependency {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(s.replace('@', "==")))
+        // Parse with the package manager so operators and `@` pins are handled consistently.
+        // The hashes aren't part of the requirement, so they're passed on after it.
+        let dependency = huak_package_manager::Dependency::from_str(s)?;
+        let hashes = dependency.hashes().iter().map(|it| format!(" --hash={it}"));
+
+        Ok(Self(
+            std::iter::once(dependency.to_string())
+                .chain(hashes)
+                .collect(),
+        ))
     }
 }
 
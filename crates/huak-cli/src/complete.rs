@@ -0,0 +1,200 @@
+use clap_complete::Shell;
+use huak_package_manager::{ops, Config};
+use huak_workspace::{resolve_root, PathMarker};
+use std::{env::current_dir, ffi::OsString, process::ExitCode};
+
+/// Global options taking a value, so their value isn't mistaken for the subcommand.
+const GLOBAL_VALUE_OPTIONS: [&str; 1] = ["--timeout"];
+
+/// Glue appended to clap's bash script asking `huak __complete` for values before falling back
+/// to the static completions.
+const BASH_DYNAMIC_COMPLETION: &str = r#"
+_huak_dynamic() {
+    local candidates
+    if candidates="$(huak __complete -- "${COMP_WORDS[@]:0:COMP_CWORD+1}" 2>/dev/null)"; then
+        compopt +o default +o bashdefault 2>/dev/null
+        local IFS=$'\n'
+        COMPREPLY=($(compgen -W "${candidates}" -- "${COMP_WORDS[COMP_CWORD]}"))
+        return 0
+    fi
+    _huak "$@"
+}
+
+complete -F _huak_dynamic -o nosort -o bashdefault -o default huak
+"#;
+
+const ZSH_DYNAMIC_COMPLETION: &str = r#"
+_huak_dynamic() {
+    local output
+    local -a candidates
+    if output="$(huak __complete -- "${(@)words[1,CURRENT]}" 2>/dev/null)"; then
+        candidates=(${(f)output})
+        compadd -a candidates
+        return
+    fi
+    _huak "$@"
+}
+
+compdef _huak_dynamic huak
+"#;
+
+const FISH_DYNAMIC_COMPLETION: &str = r#"
+function __huak_complete
+    huak __complete -- (commandline -opc) (commandline -ct) 2>/dev/null
+end
+
+complete -c huak -n "__huak_complete >/dev/null" -f -a "(__huak_complete)"
+"#;
+
+/// Get the glue for a `Shell`'s completion script to complete dynamic values, if the shell is
+/// supported.
+pub fn dynamic_completion_script(shell: Shell) -> Option<&'static str> {
+    match shell {
+        Shell::Bash => Some(BASH_DYNAMIC_COMPLETION),
+        Shell::Zsh => Some(ZSH_DYNAMIC_COMPLETION),
+        Shell::Fish => Some(FISH_DYNAMIC_COMPLETION),
+        _ => None,
+    }
+}
+
+/// Print the values completing the last of the command line `args` (after a `--`), one per
+/// line. Exits unsuccessfully so completion scripts fall back to the static completions if the
+/// values aren't dynamic.
+pub fn run(args: &[OsString]) -> ExitCode {
+    let args = match args.split_first() {
+        Some((first, rest)) if first == "--" => rest,
+        _ => args,
+    };
+    let words = args
+        .iter()
+        .map(|it| it.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    let Ok(cwd) = current_dir() else {
+        return ExitCode::FAILURE;
+    };
+    let config = Config {
+        workspace_root: resolve_root(&cwd, PathMarker::file("pyproject.toml"))
+            .root()
+            .clone(),
+        cwd,
+        ..Default::default()
+    };
+
+    let Some(candidates) = complete(&words, &config) else {
+        return ExitCode::FAILURE;
+    };
+
+    for it in candidates {
+        println!("{it}");
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Get the values to complete the last of the command line `words` with. The `words` are
+/// `huak` followed by the arguments up to and including the one being completed.
+///
+/// Only values that depend on the project are completed: optional dependency groups, declared
+/// dependencies for `huak remove`, and tasks and executables for `huak run`. `None` means the
+/// static completions apply.
+pub fn complete(words: &[String], config: &Config) -> Option<Vec<String>> {
+    let (current, words) = words.split_last()?;
+    let args = words.get(1..)?;
+
+    let mut takes_value = false;
+    let position = args.iter().position(|it| {
+        let is_subcommand = !takes_value && !it.starts_with('-');
+        takes_value = GLOBAL_VALUE_OPTIONS.contains(&it.as_str());
+        is_subcommand
+    })?;
+    let subcommand = args[position].as_str();
+    let args = &args[position + 1..];
+
+    if current.starts_with('-') || args.iter().any(|it| it == "--") {
+        return None;
+    }
+
+    let candidates = match (subcommand, args.last().map(String::as_str)) {
+        ("add" | "remove", Some("--group"))
+        | ("init", Some("--groups" | "--optional-dependencies"))
+        | ("test", Some("--only-group")) => ops::complete_groups(config),
+        ("remove", _) => ops::complete_dependencies(group(args), config),
+        ("run", None) => ops::complete_run(config),
+        _ => return None,
+    };
+
+    Some(
+        candidates
+            .into_iter()
+            .filter(|it| it.starts_with(current.as_str()))
+            .collect(),
+    )
+}
+
+/// Get the value of a `--group` in the `args`.
+fn group(args: &[String]) -> Option<&str> {
+    args.iter().enumerate().find_map(|(i, it)| {
+        it.strip_prefix("--group=").or_else(|| {
+            (it == "--group")
+                .then(|| args.get(i + 1).map(String::as_str))
+                .flatten()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_complete() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            r#"[project]
+name = "mock-project"
+dependencies = ["requests", "rich"]
+
+[project.optional-dependencies]
+dev = ["ruff"]
+docs = ["mkdocs"]
+
+[tool.huak.task]
+serve = "mkdocs serve"
+"#,
+        )
+        .unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root,
+            ..Default::default()
+        };
+        let complete = |it: &str| {
+            let words = it.split(' ').map(ToString::to_string).collect::<Vec<_>>();
+            complete(&words, &config)
+        };
+
+        assert_eq!(complete("huak add --group d").unwrap(), ["dev", "docs"]);
+        assert_eq!(
+            complete("huak --timeout 5 init --groups ").unwrap(),
+            ["dev", "docs"]
+        );
+        assert_eq!(
+            complete("huak test --only-group ").unwrap(),
+            ["dev", "docs"]
+        );
+        assert_eq!(complete("huak remove r").unwrap(), ["requests", "rich"]);
+        assert_eq!(
+            complete("huak -q remove --group docs ").unwrap(),
+            ["mkdocs"]
+        );
+        assert_eq!(complete("huak remove --group=dev ").unwrap(), ["ruff"]);
+        assert_eq!(complete("huak run s").unwrap(), ["serve"]);
+        assert!(complete("huak run --script ").is_none());
+        assert!(complete("huak remove --").is_none());
+        assert!(complete("huak add ").is_none());
+        assert!(complete("huak ").is_none());
+    }
+}
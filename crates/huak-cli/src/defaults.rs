@@ -0,0 +1,569 @@
+use crate::cli::Cli;
+use clap::{parser::ValueSource, Arg, ArgAction, Command, CommandFactory, FromArgMatches, Parser};
+use huak_package_manager::{
+    deprecated_flags, deprecated_key, CommandDefault, Config, DeprecatedFlag, Error as HuakError,
+    HuakResult,
+};
+use huak_workspace::{resolve_root, PathMarker};
+use std::{env::current_dir, ffi::OsString, io::Write};
+use termcolor::Color;
+
+/// Parse `args` with the defaults configured for their subcommand in the workspace's
+/// `[tool.huak.<command>]`. Options passed on the command line override the defaults.
+///
+/// With `--profile <NAME>` (or `HUAK_PROFILE`) the defaults in `[tool.huak.profile.<NAME>]` are
+/// merged over them: its keys set the global options and its tables set commands' options.
+///
+/// Deprecated flags and keys keep working, and a warning for each is kept in
+/// `Cli::deprecations`. Problems with the command line are returned as `Error::ClapError`s.
+pub fn try_parse(args: &[OsString]) -> HuakResult<Cli> {
+    let matches = Cli::command()
+        .ignore_errors(true)
+        .try_get_matches_from(args)
+        .ok();
+    let name = matches
+        .as_ref()
+        .and_then(|it| it.subcommand_name().map(ToString::to_string));
+    let Some(name) = name else {
+        return Ok(Cli::try_parse_from(args)?);
+    };
+    let profile = matches
+        .as_ref()
+        .and_then(|it| it.get_one::<String>("profile").cloned())
+        .or_else(|| {
+            std::env::var("HUAK_PROFILE")
+                .ok()
+                .filter(|it| !it.is_empty())
+        });
+    let cwd = current_dir()?;
+    let config = Config {
+        workspace_root: resolve_root(&cwd, PathMarker::file("pyproject.toml"))
+            .root()
+            .clone(),
+        cwd,
+        ..Default::default()
+    };
+    let defaults = config
+        .workspace()
+        .command_defaults(&name, profile.as_deref())?;
+    let globals = match profile.as_deref() {
+        Some(it) => {
+            check_profile_commands(
+                &Cli::command(),
+                it,
+                &config.workspace().profile_commands(it)?,
+            )?;
+            config.workspace().profile_defaults(it)?
+        }
+        None => Vec::new(),
+    };
+    let mut deprecations = deprecated_flags(&name, args)
+        .into_iter()
+        .map(DeprecatedFlag::flag_warning)
+        .collect::<Vec<_>>();
+    let defaults = renamed_defaults(&name, defaults, &mut deprecations);
+
+    let mut cli = if defaults.is_empty() && globals.is_empty() {
+        Cli::try_parse_from(args)?
+    } else {
+        parse_with_defaults(args, &name, &defaults, profile.as_deref(), &globals)?
+    };
+    cli.profile = profile;
+    cli.deprecations = deprecations;
+
+    Ok(cli)
+}
+
+/// Replace the deprecated keys in `defaults` with their new names, adding a warning for
+/// each to `deprecations`.
+fn renamed_defaults(
+    name: &str,
+    defaults: Vec<(String, CommandDefault)>,
+    deprecations: &mut Vec<String>,
+) -> Vec<(String, CommandDefault)> {
+    defaults
+        .into_iter()
+        .map(|(key, default)| match deprecated_key(name, &key) {
+            Some(flag) => {
+                deprecations.push(flag.key_warning());
+                (flag.new.to_string(), default)
+            }
+            None => (key, default),
+        })
+        .collect()
+}
+
+/// Parse `args` with the `defaults` of the subcommand `name` and the `globals` a `profile` sets.
+fn parse_with_defaults(
+    args: &[OsString],
+    name: &str,
+    defaults: &[(String, CommandDefault)],
+    profile: Option<&str>,
+    globals: &[(String, CommandDefault)],
+) -> HuakResult<Cli> {
+    let mut cmd = command_with_defaults(Cli::command(), name, defaults)?;
+    if let Some(profile) = profile {
+        cmd = root_with_defaults(cmd, profile, globals)?;
+    }
+    let matches = cmd.try_get_matches_from(args).map_err(|e| {
+        // Report the command line's own problem if it has one, otherwise it's a default's.
+        if let Err(e) = Cli::try_parse_from(args) {
+            return HuakError::ClapError(e);
+        }
+
+        let e = e.to_string();
+        let e = e.lines().next().unwrap_or_default();
+        HuakError::HuakConfigurationError {
+            table: profile.map_or_else(
+                || format!("tool.huak.{name}"),
+                |it| format!("tool.huak.profile.{it}"),
+            ),
+            message: format!(
+                "has an invalid default: {}",
+                e.trim_start_matches("error: ")
+            ),
+        }
+    })?;
+    let mut cli = Cli::from_arg_matches(&matches)?;
+
+    // `--quiet` and `--verbose` conflict, so one passed on the command line turns the other off
+    // when it's a default.
+    let explicit = |id| matches.value_source(id) == Some(ValueSource::CommandLine);
+    if explicit("verbose") && !explicit("quiet") {
+        cli.quiet = false;
+    } else if explicit("quiet") && !explicit("verbose") {
+        cli.verbose = false;
+    }
+
+    Ok(cli)
+}
+
+/// Set the defaults of the subcommand `name`'s arguments. `args` sets the default trailing
+/// arguments, and any other key is an option's long name (with `-` or `_`).
+fn command_with_defaults(
+    cmd: Command,
+    name: &str,
+    defaults: &[(String, CommandDefault)],
+) -> HuakResult<Command> {
+    let Some(subcommand) = cmd.find_subcommand(name) else {
+        return Ok(cmd);
+    };
+    let mut validated = Vec::new();
+
+    for (key, default) in defaults {
+        let id = if key == "args" {
+            "trailing".to_string()
+        } else {
+            key.replace('-', "_")
+        };
+        let Some(arg) = subcommand
+            .get_arguments()
+            .find(|it| *it.get_id() == id && (id == "trailing" || !it.is_positional()))
+        else {
+            return Err(HuakError::HuakConfigurationError {
+                table: format!("tool.huak.{name}"),
+                message: format!("has no option {key}"),
+            });
+        };
+        check_default(arg, key, default, &format!("tool.huak.{name}"))?;
+        validated.push((id, default.clone()));
+    }
+
+    Ok(cmd.mut_subcommand(name, |mut it| {
+        for (id, default) in validated {
+            it = it.mut_arg(id, |arg| with_default(arg, default));
+        }
+        it
+    }))
+}
+
+/// Set the defaults of the global options a `profile` configures. Keys are the options' long
+/// names (with `-` or `_`).
+fn root_with_defaults(
+    mut cmd: Command,
+    profile: &str,
+    defaults: &[(String, CommandDefault)],
+) -> HuakResult<Command> {
+    let table = format!("tool.huak.profile.{profile}");
+
+    for (key, default) in defaults {
+        let long = key.replace('_', "-");
+        let Some(arg) = cmd.get_arguments().find(|it| {
+            it.is_global_set() && it.get_id() != "profile" && it.get_long() == Some(long.as_str())
+        }) else {
+            // A command's option belongs in the profile's table for the command.
+            let is_command_option = long == "args"
+                || cmd.get_subcommands().any(|it| {
+                    it.get_arguments()
+                        .any(|arg| arg.get_long() == Some(long.as_str()))
+                });
+            let message = if is_command_option {
+                format!("has no global option {key} (set it in [{table}.<command>])")
+            } else {
+                format!("has no global option {key}")
+            };
+            return Err(HuakError::HuakConfigurationError { table, message });
+        };
+        check_default(arg, key, default, &table)?;
+
+        let id = arg.get_id().clone();
+        cmd = cmd.mut_arg(id, |arg| with_default(arg, default.clone()));
+    }
+
+    Ok(cmd)
+}
+
+/// Check that each of the `commands` a `profile` has a table for is a subcommand of `cmd`.
+fn check_profile_commands(cmd: &Command, profile: &str, commands: &[String]) -> HuakResult<()> {
+    match commands.iter().find(|name| {
+        !cmd.get_subcommands()
+            .any(|it| it.get_name() == name.as_str())
+    }) {
+        Some(name) => Err(HuakError::HuakConfigurationError {
+            table: format!("tool.huak.profile.{profile}"),
+            message: format!("has no command {name}"),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Check that a `default` configured with the `key` in the `table` suits the `arg`.
+fn check_default(arg: &Arg, key: &str, default: &CommandDefault, table: &str) -> HuakResult<()> {
+    let is_flag = matches!(arg.get_action(), ArgAction::SetTrue);
+
+    match default {
+        CommandDefault::Flag(_) if !is_flag => Err(HuakError::HuakConfigurationError {
+            table: table.to_string(),
+            message: format!("{key} takes a value, not true or false"),
+        }),
+        CommandDefault::Values(_) if is_flag => Err(HuakError::HuakConfigurationError {
+            table: table.to_string(),
+            message: format!("{key} is a flag and must be true or false"),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Print the defaults commands run with, merged with the `Config`'s profile, in the shape of a
+/// `[tool.huak.profile.<name>]` table: the global options' keys, then a table for each command.
+pub fn show_defaults(config: &Config) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let profile = config.profile.as_deref();
+    let mut cmd = Cli::command();
+    let mut sections = Vec::new();
+
+    if let Some(name) = profile {
+        check_profile_commands(&cmd, name, &workspace.profile_commands(name)?)?;
+        let globals = workspace.profile_defaults(name)?;
+        cmd = root_with_defaults(cmd, name, &globals)?;
+        sections.push((None, globals));
+    }
+
+    let names = cmd
+        .get_subcommands()
+        .map(|it| it.get_name().to_string())
+        .collect::<Vec<_>>();
+    for name in names {
+        let defaults = workspace.command_defaults(&name, profile)?;
+        if !defaults.is_empty() {
+            cmd = command_with_defaults(cmd, &name, &defaults)?;
+            sections.push((Some(name), defaults));
+        }
+    }
+
+    sections.retain(|(_, it)| !it.is_empty());
+    if sections.is_empty() {
+        return config.terminal().print_custom(
+            "Defaults",
+            "none are configured",
+            Color::Green,
+            true,
+        );
+    }
+
+    let mut stdout = std::io::stdout();
+    for (i, (name, defaults)) in sections.iter().enumerate() {
+        if i > 0 {
+            writeln!(stdout)?;
+        }
+        if let Some(name) = name {
+            writeln!(stdout, "[{name}]")?;
+        }
+        for (key, it) in defaults {
+            writeln!(stdout, "{key} = {it}")?;
+        }
+    }
+
+    Ok(())
+}
+
+fn with_default(arg: Arg, default: CommandDefault) -> Arg {
+    match default {
+        // A flag can be turned off for one run with `--flag=false`.
+        CommandDefault::Flag(it) => arg
+            .action(ArgAction::Set)
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("true")
+            .default_value(if it { "true" } else { "false" }),
+        CommandDefault::Values(it) => arg.default_values(it),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Commands;
+
+    fn parse(args: &str, name: &str, defaults: &[(&str, CommandDefault)]) -> HuakResult<Cli> {
+        let args = args.split(' ').map(OsString::from).collect::<Vec<_>>();
+        let defaults = defaults
+            .iter()
+            .map(|(key, it)| ((*key).to_string(), it.clone()))
+            .collect::<Vec<_>>();
+
+        parse_with_defaults(&args, name, &defaults, None, &[])
+    }
+
+    fn values(it: &[&str]) -> CommandDefault {
+        CommandDefault::Values(it.iter().map(ToString::to_string).collect())
+    }
+
+    #[test]
+    fn test_command_defaults_override() {
+        let defaults = [
+            ("no-types", CommandDefault::Flag(true)),
+            ("select", values(&["E,F"])),
+            ("args", values(&["--show-fixes"])),
+        ];
+        let lint = |args| match parse(args, "lint", &defaults).unwrap().command {
+            Commands::Lint {
+                no_types,
+                select,
+                trailing,
+                ..
+            } => (no_types, select, trailing),
+            _ => panic!("expected huak lint"),
+        };
+
+        assert_eq!(
+            lint("huak lint"),
+            (
+                true,
+                Some("E,F".to_string()),
+                Some(vec!["--show-fixes".to_string()])
+            )
+        );
+        assert_eq!(
+            lint("huak lint --no-types=false --select E501 -- --statistics"),
+            (
+                false,
+                Some("E501".to_string()),
+                Some(vec!["--statistics".to_string()])
+            )
+        );
+        assert!(lint("huak lint --no-types").0);
+
+        let test = |args| match parse(args, "test", &[("args", values(&["-q"]))])
+            .unwrap()
+            .command
+        {
+            Commands::Test { trailing, .. } => trailing,
+            _ => panic!("expected huak test"),
+        };
+
+        assert_eq!(test("huak test"), Some(vec!["-q".to_string()]));
+        assert_eq!(test("huak test -- -x"), Some(vec!["-x".to_string()]));
+    }
+
+    #[test]
+    fn test_command_defaults_invalid() {
+        let error = |name, defaults: &[(&str, CommandDefault)]| {
+            parse(&format!("huak {name}"), name, defaults)
+                .err()
+                .unwrap()
+                .to_string()
+        };
+
+        assert!(error("lint", &[("no-typos", CommandDefault::Flag(true))])
+            .contains("[tool.huak.lint] has no option no-typos"));
+        assert!(error("lint", &[("select", CommandDefault::Flag(true))])
+            .contains("select takes a value"));
+        assert!(error("lint", &[("fix", values(&["yes"]))]).contains("fix is a flag"));
+        assert!(error("test", &[("shard", values(&["2"]))])
+            .contains("[tool.huak.test] has an invalid default"));
+    }
+
+    #[test]
+    fn test_profile_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("pyproject.toml"),
+            r#"[project]
+name = "mock-project"
+
+[tool.huak.lint]
+select = "E,F"
+no-types = true
+
+[tool.huak.profile.ci]
+quiet = true
+timeout = 600
+
+[tool.huak.profile.ci.lint]
+no_types = false
+args = ["--output-format", "github"]
+
+[tool.huak.profile.local]
+"#,
+        )
+        .unwrap();
+        let config = Config {
+            workspace_root: dir.path().to_path_buf(),
+            cwd: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let workspace = config.workspace();
+
+        // The profile's options are merged over the project's.
+        let defaults = workspace.command_defaults("lint", Some("ci")).unwrap();
+        assert_eq!(
+            defaults,
+            [
+                ("select".to_string(), values(&["E,F"])),
+                ("no_types".to_string(), CommandDefault::Flag(false)),
+                ("args".to_string(), values(&["--output-format", "github"])),
+            ]
+        );
+        let globals = workspace.profile_defaults("ci").unwrap();
+        assert_eq!(
+            globals,
+            [
+                ("quiet".to_string(), CommandDefault::Flag(true)),
+                ("timeout".to_string(), values(&["600"])),
+            ]
+        );
+        assert_eq!(
+            workspace
+                .command_defaults("lint", Some("nightly"))
+                .unwrap_err()
+                .to_string(),
+            "a profile could not be found in [tool.huak.profile]: nightly \
+            (available profiles: ci, local)"
+        );
+
+        let args = |it: &str| it.split(' ').map(OsString::from).collect::<Vec<_>>();
+        let cli = parse_with_defaults(&args("huak lint"), "lint", &defaults, Some("ci"), &globals)
+            .unwrap();
+        assert!(cli.quiet && !cli.verbose);
+        assert!(matches!(
+            cli.command,
+            Commands::Lint {
+                no_types: false,
+                ..
+            }
+        ));
+
+        // Options passed on the command line win over the profile's.
+        let cli = parse_with_defaults(
+            &args("huak lint -v --no-types"),
+            "lint",
+            &defaults,
+            Some("ci"),
+            &globals,
+        )
+        .unwrap();
+        assert!(!cli.quiet && cli.verbose);
+        assert!(matches!(cli.command, Commands::Lint { no_types: true, .. }));
+
+        assert!(parse_with_defaults(
+            &args("huak lint"),
+            "lint",
+            &[],
+            Some("ci"),
+            &[("color".to_string(), CommandDefault::Flag(true))],
+        )
+        .err()
+        .unwrap()
+        .to_string()
+        .contains("[tool.huak.profile.ci] has no global option color"));
+    }
+
+    #[test]
+    fn test_profile_unknown_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("pyproject.toml"),
+            r#"[project]
+name = "mock-project"
+
+[tool.huak.profile.ci]
+quiet = true
+no-types = true
+lint = { select = "E" }
+
+[tool.huak.profile.ci.tset]
+args = ["-x"]
+"#,
+        )
+        .unwrap();
+        let config = Config {
+            workspace_root: dir.path().to_path_buf(),
+            cwd: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let workspace = config.workspace();
+
+        // An inline table is a command's defaults too, not a global option.
+        let commands = workspace.profile_commands("ci").unwrap();
+        assert_eq!(commands, ["lint", "tset"]);
+        assert_eq!(
+            check_profile_commands(&Cli::command(), "ci", &commands)
+                .unwrap_err()
+                .to_string(),
+            "a problem with huak configuration occurred: [tool.huak.profile.ci] has no command tset"
+        );
+        assert!(check_profile_commands(&Cli::command(), "ci", &commands[..1]).is_ok());
+
+        let globals = workspace.profile_defaults("ci").unwrap();
+        assert_eq!(
+            root_with_defaults(Cli::command(), "ci", &globals)
+                .unwrap_err()
+                .to_string(),
+            "a problem with huak configuration occurred: [tool.huak.profile.ci] has no global \
+            option no-types (set it in [tool.huak.profile.ci.<command>])"
+        );
+    }
+
+    #[test]
+    fn test_command_defaults_deprecated_key() {
+        let mut deprecations = Vec::new();
+        let defaults = renamed_defaults(
+            "init",
+            vec![("optional_dependencies".to_string(), values(&["dev"]))],
+            &mut deprecations,
+        );
+        let groups = |args| {
+            let args = format!("huak init {args}");
+            let args = args
+                .split_whitespace()
+                .map(OsString::from)
+                .collect::<Vec<_>>();
+            match parse_with_defaults(&args, "init", &defaults, None, &[])
+                .unwrap()
+                .command
+            {
+                Commands::Init { groups, .. } => groups,
+                _ => panic!("expected huak init"),
+            }
+        };
+
+        assert_eq!(groups(""), Some(vec!["dev".to_string()]));
+        assert_eq!(
+            groups("--optional-dependencies test"),
+            Some(vec!["test".to_string()])
+        );
+        assert_eq!(deprecations.len(), 1);
+        assert!(deprecations[0].starts_with("[tool.huak.init] optional-dependencies"));
+    }
+}
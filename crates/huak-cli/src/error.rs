@@ -1,4 +1,6 @@
-use std::process::ExitCode;
+use colored::Colorize;
+use huak_package_manager::{ops::OutputFormat, Error as HuakError};
+use std::{error::Error as _, ffi::OsString, fmt::Write, process::ExitCode};
 use thiserror::Error as ThisError;
 
 pub type CliResult<T> = Result<T, Error>;
@@ -58,6 +60,139 @@ impl From<std::env::VarError> for Error {
 
 impl From<huak_package_manager::Error> for Error {
     fn from(e: huak_package_manager::Error) -> Error {
-        Error::new(e, ExitCode::FAILURE)
+        let exit_code = ExitCode::from(e.exit_code());
+        Error::new(e, exit_code)
+    }
+}
+
+/// How errors are printed to stderr.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ErrorStyle {
+    pub format: OutputFormat,
+    /// Print the errors that caused the error too.
+    pub verbose: bool,
+}
+
+impl ErrorStyle {
+    /// Get the `ErrorStyle` from `args` that couldn't be parsed into a `Cli`. Anything after
+    /// `--` belongs to another program.
+    pub fn from_args(args: &[OsString]) -> ErrorStyle {
+        let args = args
+            .iter()
+            .skip(1)
+            .take_while(|it| *it != "--")
+            .filter_map(|it| it.to_str())
+            .collect::<Vec<_>>();
+        let json = args.windows(2).any(|it| it == ["--error-format", "json"])
+            || args.contains(&"--error-format=json");
+
+        ErrorStyle {
+            format: if json {
+                OutputFormat::Json
+            } else {
+                OutputFormat::Human
+            },
+            verbose: args.iter().any(|it| *it == "-v" || *it == "--verbose"),
+        }
+    }
+
+    /// Render the `error` to print to stderr, or `None` if it's reported some other way.
+    ///
+    /// JSON errors are always rendered with their code and causes so scripts can branch on them.
+    pub fn render(self, error: &HuakError) -> Option<String> {
+        let causes = causes(error);
+
+        if self.format == OutputFormat::Json {
+            let report = serde_json::json!({
+                "error": {
+                    "code": error.code(),
+                    "message": error.to_string(),
+                    "exit_code": error.exit_code(),
+                    "causes": causes,
+                }
+            });
+            return Some(report.to_string());
+        }
+
+        // Subprocesses report their own failures, so only their exit code is passed on. Failed
+        // version checks are only meant to be branched on, so they're silent too.
+        if matches!(
+            error,
+            HuakError::SubprocessFailure(_) | HuakError::VersionCheckFailed(_)
+        ) {
+            return None;
+        }
+
+        // TODO(cnpryer):
+        //   - Make subprocess hack more clear
+        //   - https://github.com/cnpryer/huak/issues/318
+        let message = error.to_string();
+        if message.is_empty() {
+            return Some(message);
+        }
+
+        let mut rendered = format!("{}{} {message}", "error".red(), ":".bold());
+        if self.verbose {
+            for it in causes {
+                let _ = write!(rendered, "\n  {} {it}", "caused by:".bold());
+            }
+        }
+
+        Some(rendered)
+    }
+}
+
+/// Get the messages of the errors that caused the `error`, leaving out those its message (or
+/// the previous cause's) already includes.
+fn causes(error: &HuakError) -> Vec<String> {
+    let mut causes = Vec::new();
+    let mut previous = error.to_string();
+    let mut source = error.source();
+
+    while let Some(it) = source {
+        let message = it.to_string();
+        if !previous.contains(&message) {
+            causes.push(message.clone());
+        }
+        previous = message;
+        source = it.source();
+    }
+
+    causes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_json() {
+        let style = ErrorStyle {
+            format: OutputFormat::Json,
+            verbose: false,
+        };
+        let rendered = style.render(&HuakError::PythonNotFound).unwrap();
+        let value = serde_json::from_str::<serde_json::Value>(&rendered).unwrap();
+
+        assert_eq!(value["error"]["code"], "python-not-found");
+        assert_eq!(
+            value["error"]["message"],
+            "a python interpreter could not be found"
+        );
+        assert_eq!(value["error"]["exit_code"], 1);
+        assert!(!rendered.contains('\n'));
+    }
+
+    #[test]
+    fn test_from_args() {
+        let args = |it: &[&str]| it.iter().map(OsString::from).collect::<Vec<_>>();
+
+        let style = ErrorStyle::from_args(&args(&["huak", "--error-format", "json", "-v"]));
+        assert_eq!(style.format, OutputFormat::Json);
+        assert!(style.verbose);
+
+        let style = ErrorStyle::from_args(&args(&["huak", "run", "--", "--error-format=json"]));
+        assert_eq!(style.format, OutputFormat::Human);
+        assert!(!style.verbose);
     }
 }
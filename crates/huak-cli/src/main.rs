@@ -3,18 +3,17 @@
 //! Huak implements a cli application with various subcommands.
 
 mod cli;
-use clap::Parser;
-use cli::Cli;
+mod complete;
+mod defaults;
 use colored::Colorize;
+use error::ErrorStyle;
 use huak_home::huak_home_dir;
+use huak_package_manager::Error as HuakError;
 use human_panic::setup_panic;
-use std::{
-    env,
-    fs::create_dir_all,
-    process::{exit, ExitCode},
-};
+use std::{env, fs::create_dir_all, process::ExitCode};
 
 mod error;
+mod suggest;
 
 /// Launch Huak's cli process.
 #[must_use]
@@ -42,19 +41,31 @@ pub fn main() -> ExitCode {
         }
     }
 
-    // Capture and run CLI input.
-    match Cli::parse().run() {
-        Ok(0) => ExitCode::SUCCESS,
-        // Lazy-like exit of a subprocess failure. TODO: https://github.com/cnpryer/huak/issues/631
-        Ok(code) => exit(code),
+    // Completion scripts ask for dynamic values with `huak __complete -- <WORDS>`. It isn't a
+    // subcommand so it stays out of help and the generated scripts, and skips parsing.
+    let args = env::args_os().collect::<Vec<_>>();
+    if args.get(1).is_some_and(|it| it == "__complete") {
+        return complete::run(&args[2..]);
+    }
+
+    // Capture and run CLI input, with the defaults configured for its command.
+    let cli = match defaults::try_parse(&args) {
+        Ok(it) => it,
+        Err(HuakError::ClapError(e)) => suggest::with_suggestions(e, &args).exit(),
+        Err(e) => {
+            if let Some(it) = ErrorStyle::from_args(&args).render(&e) {
+                eprintln!("{it}");
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let style = cli.error_style();
+    match cli.run() {
+        Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
-            // TODO(cnpryer):
-            //   - Make subprocess hack more clear
-            //   - https://github.com/cnpryer/huak/issues/318
-            if e.error.to_string().is_empty() {
-                eprintln!("{}", e.error);
-            } else {
-                eprintln!("{}{} {}", "error".red(), ":".bold(), e.error);
+            if let Some(it) = style.render(&e.error) {
+                eprintln!("{it}");
             }
             e.exit_code
         }
@@ -0,0 +1,200 @@
+use crate::cli::Cli;
+use clap::{
+    builder::StyledStr,
+    error::{ContextKind, ContextValue, ErrorKind},
+    Command, CommandFactory,
+};
+use huak_package_manager::similar_names;
+use std::ffi::OsString;
+
+/// Commands from other tools people reach for and the subcommand that does the same in Huak.
+const COMMON_NAMES: [(&str, &str); 6] = [
+    ("exec", "run"),
+    ("format", "fmt"),
+    ("rm", "remove"),
+    ("shell", "activate"),
+    ("uninstall", "remove"),
+    ("upgrade", "update"),
+];
+
+/// Add "did you mean" suggestions to an `Error` from parsing `args`.
+///
+/// Mistyped subcommands get the closest subcommands by edit distance instead of clap's loose
+/// matches. Mistyped arguments get the closest argument of the subcommand and any other
+/// subcommand or argument with the same name (like `huak lint --fix` for `huak fmt --fix`).
+pub fn with_suggestions(mut error: clap::Error, args: &[OsString]) -> clap::Error {
+    let mut cli = Cli::command();
+    cli.build();
+    let path = command_path(&cli, args);
+    let cmd = path.last().copied().unwrap_or(&cli);
+    let mut suggestions = Vec::new();
+
+    match error.kind() {
+        ErrorKind::InvalidSubcommand => {
+            let Some(ContextValue::String(name)) = error.get(ContextKind::InvalidSubcommand) else {
+                return error;
+            };
+            let name = name.clone();
+
+            let similar = similar_names(&name, subcommand_names(cmd));
+            if !similar.is_empty() {
+                error.insert(
+                    ContextKind::SuggestedSubcommand,
+                    ContextValue::Strings(similar),
+                );
+            }
+
+            if path.len() == 1 {
+                suggestions.extend(
+                    COMMON_NAMES
+                        .iter()
+                        .filter(|(it, _)| *it == name)
+                        .map(|(_, it)| format!("did you mean 'huak {it}'?")),
+                );
+            }
+        }
+        ErrorKind::UnknownArgument => {
+            let Some(ContextValue::String(arg)) = error.get(ContextKind::InvalidArg) else {
+                return error;
+            };
+            let Some(name) = arg.strip_prefix("--").map(|it| {
+                it.split_once('=')
+                    .map_or(it.to_string(), |(it, _)| it.to_string())
+            }) else {
+                return error;
+            };
+
+            if let Some(it) = similar_names(&name, long_names(cmd)).first() {
+                error.insert(
+                    ContextKind::SuggestedArg,
+                    ContextValue::String(format!("--{it}")),
+                );
+            }
+
+            if subcommand_names(&cli).any(|it| it == name) {
+                suggestions.push(format!("did you mean 'huak {name}'?"));
+            }
+
+            let mut others = Vec::new();
+            other_commands(&cli, "huak", cmd, &mut others);
+            suggestions.extend(
+                others
+                    .into_iter()
+                    .filter(|(_, it)| long_names(it).any(|it| it == name))
+                    .map(|(it, _)| format!("did you mean '{it} --{name}'?")),
+            );
+        }
+        _ => return error,
+    }
+
+    if !suggestions.is_empty() {
+        let mut styled = match error.get(ContextKind::Suggested) {
+            Some(ContextValue::StyledStrs(it)) => it.clone(),
+            _ => Vec::new(),
+        };
+        styled.extend(suggestions.into_iter().map(StyledStr::from));
+        error.insert(ContextKind::Suggested, ContextValue::StyledStrs(styled));
+    }
+
+    error
+}
+
+/// Get the commands `args` were parsed into before the error, starting with `huak`.
+fn command_path<'a>(cli: &'a Command, args: &[OsString]) -> Vec<&'a Command> {
+    let mut path = vec![cli];
+
+    for arg in args.iter().skip(1).filter_map(|it| it.to_str()) {
+        if arg == "--" {
+            break;
+        }
+
+        let current = path[path.len() - 1];
+        if let Some(it) = current.find_subcommand(arg) {
+            path.push(it);
+        }
+    }
+
+    path
+}
+
+fn subcommand_names(cmd: &Command) -> impl Iterator<Item = &str> {
+    cmd.get_subcommands()
+        .flat_map(|it| std::iter::once(it.get_name()).chain(it.get_all_aliases()))
+}
+
+fn long_names(cmd: &Command) -> impl Iterator<Item = &str> {
+    cmd.get_arguments()
+        .filter(|it| !it.is_hide_set())
+        .flat_map(|it| it.get_long_and_visible_aliases().unwrap_or_default())
+}
+
+/// Collect every subcommand under `cmd` except `skip`, with the command line that runs it.
+fn other_commands<'a>(
+    cmd: &'a Command,
+    name: &str,
+    skip: &Command,
+    commands: &mut Vec<(String, &'a Command)>,
+) {
+    for it in cmd.get_subcommands() {
+        let name = format!("{name} {}", it.get_name());
+
+        if !std::ptr::eq(it, skip) {
+            commands.push((name.clone(), it));
+        }
+        other_commands(it, &name, skip, commands);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn suggestions(args: &str) -> String {
+        let args = args.split(' ').map(OsString::from).collect::<Vec<_>>();
+        let Err(error) = Cli::try_parse_from(&args) else {
+            panic!("{args:?} should fail to parse");
+        };
+
+        with_suggestions(error, &args).render().to_string()
+    }
+
+    #[test]
+    fn test_typo_suggestions() {
+        let typos = [
+            ("huak installl", "a similar subcommand exists: 'install'"),
+            ("huak fmtt", "a similar subcommand exists: 'fmt'"),
+            ("huak format", "did you mean 'huak fmt'?"),
+            ("huak uninstall requests", "did you mean 'huak remove'?"),
+            (
+                "huak toolchain instal",
+                "a similar subcommand exists: 'install'",
+            ),
+            (
+                "huak add requests --groups dev",
+                "a similar argument exists: '--group'",
+            ),
+            ("huak fmt --fix", "did you mean 'huak fix'?"),
+            ("huak fmt --fix", "did you mean 'huak lint --fix'?"),
+            (
+                "huak test --shards 1/2",
+                "a similar argument exists: '--shard'",
+            ),
+            (
+                "huak add requests --ofline",
+                "a similar argument exists: '--offline'",
+            ),
+        ];
+
+        for (args, expected) in typos {
+            let rendered = suggestions(args);
+            assert!(
+                rendered.contains(expected),
+                "{args}: expected {expected:?} in\n{rendered}"
+            );
+        }
+
+        assert!(!suggestions("huak installl").contains("'init'"));
+        assert!(!suggestions("huak fmt --fix").contains("'huak fmt --fix'"));
+    }
+}
@@ -0,0 +1,245 @@
+//! End-to-end tests driving the compiled `huak` binary against real projects and the Python
+//! interpreter on `PATH`.
+//!
+//! Every command runs with its own home, Huak home, and cache and config directories in a
+//! temporary directory, so the tests can't touch the developer's configuration.
+//!
+//! The smoke tests run by default and don't need network access. The project lifecycle test
+//! installs packages from the package index, so it only runs with the `e2e` feature:
+//!
+//! ```zsh
+//! ❯ cargo test -p huak --features e2e --test e2e
+//! ```
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+use tempfile::TempDir;
+
+/// Environment variables that would make Huak use something other than the test's own
+/// environment.
+const IGNORED_ENV_VARS: [&str; 8] = [
+    "CONDA_PREFIX",
+    "HUAK_DEFAULT_GROUPS",
+    "HUAK_SYSTEM_PYTHON",
+    "HUAK_TOOLCHAIN",
+    "PIP_INDEX_URL",
+    "PIP_REQUIRE_VIRTUALENV",
+    "PYTHONPATH",
+    "VIRTUAL_ENV",
+];
+
+/// An isolated environment to run `huak` in.
+struct TestEnv {
+    dir: TempDir,
+}
+
+impl TestEnv {
+    fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        for it in ["home", "projects"] {
+            std::fs::create_dir(dir.path().join(it)).unwrap();
+        }
+
+        TestEnv { dir }
+    }
+
+    fn home(&self) -> PathBuf {
+        self.dir.path().join("home")
+    }
+
+    /// Get the directory projects are created in.
+    fn projects(&self) -> PathBuf {
+        self.dir.path().join("projects")
+    }
+
+    /// Get the path of the project `name`, created with `huak new`.
+    fn project(&self, name: &str) -> PathBuf {
+        self.projects().join(name)
+    }
+
+    /// Run `huak` with the `args` from `cwd`.
+    fn huak<I, S>(&self, cwd: &Path, args: I) -> Output
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let home = self.home();
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_huak"));
+        for it in IGNORED_ENV_VARS {
+            cmd.env_remove(it);
+        }
+        cmd.arg("--no-color")
+            .args(args)
+            .current_dir(cwd)
+            .env("HOME", &home)
+            .env("USERPROFILE", &home)
+            .env("HUAK_HOME", home.join(".huak"))
+            .env("HUAK_ALLOW_ROOT", "1")
+            .env("XDG_CACHE_HOME", home.join(".cache"))
+            .env("XDG_CONFIG_HOME", home.join(".config"))
+            .env("XDG_DATA_HOME", home.join(".local").join("share"))
+            .env("PIP_CACHE_DIR", home.join(".cache").join("pip"))
+            .env("PIP_CONFIG_FILE", "os.devnull")
+            .env("PIP_DISABLE_PIP_VERSION_CHECK", "1");
+
+        cmd.output().unwrap()
+    }
+
+    /// Run `huak` with the `args` from `cwd` and check that it succeeded.
+    fn huak_ok<I, S>(&self, cwd: &Path, args: I) -> Output
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let args = args
+            .into_iter()
+            .map(|it| it.as_ref().to_os_string())
+            .collect::<Vec<_>>();
+        let output = self.huak(cwd, &args);
+        assert_success(&args, &output);
+
+        output
+    }
+}
+
+fn assert_success<S: AsRef<OsStr>>(args: &[S], output: &Output) {
+    let args = args
+        .iter()
+        .map(|it| it.as_ref().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+
+    assert!(
+        output.status.success(),
+        "`huak {}` failed with {}\nstdout:\n{}\nstderr:\n{}",
+        args.join(" "),
+        output.status,
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Get the path of the Python interpreter in the `project`'s virtual environment.
+fn venv_python(project: &Path) -> PathBuf {
+    if cfg!(windows) {
+        project.join(".venv").join("Scripts").join("python.exe")
+    } else {
+        project.join(".venv").join("bin").join("python")
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn test_new_project_smoke() {
+    let env = TestEnv::new();
+    let project = env.project("smoke");
+
+    env.huak_ok(&env.projects(), ["new", "smoke"]);
+
+    let pyproject = std::fs::read_to_string(project.join("pyproject.toml")).unwrap();
+    assert!(pyproject.contains("name = \"smoke\""));
+    assert!(project
+        .join("src")
+        .join("smoke")
+        .join("__init__.py")
+        .exists());
+    assert!(project.join("tests").join("test_version.py").exists());
+    assert!(project.join(".git").exists());
+    let output = env.huak_ok(&project, ["version"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("version: 0.0.1"));
+}
+
+#[test]
+fn test_env_smoke() {
+    let env = TestEnv::new();
+    let project = env.project("smoke");
+    env.huak_ok(&env.projects(), ["new", "smoke", "--no-vcs"]);
+
+    env.huak_ok(&project, ["env", "create"]);
+    assert!(venv_python(&project).exists());
+
+    // Commands run with the project's environment from any of its directories.
+    std::fs::write(project.join("prefix.py"), "import sys\nprint(sys.prefix)\n").unwrap();
+    let output = env.huak_ok(
+        &project.join("src").join("smoke"),
+        ["run", "python", "../../prefix.py"],
+    );
+    assert_eq!(
+        PathBuf::from(stdout(&output)).canonicalize().unwrap(),
+        project.join(".venv").canonicalize().unwrap()
+    );
+
+    let dist = project.join("dist");
+    std::fs::create_dir(&dist).unwrap();
+    std::fs::write(dist.join("smoke-0.0.1.tar.gz"), "").unwrap();
+    std::fs::write(dist.join("smoke-0.0.1-py3-none-any.whl"), "").unwrap();
+    env.huak_ok(&project, ["clean"]);
+    assert!(!dist.join("smoke-0.0.1.tar.gz").exists());
+    assert!(!dist.join("smoke-0.0.1-py3-none-any.whl").exists());
+}
+
+#[test]
+fn test_add_hashes_smoke() {
+    let env = TestEnv::new();
+    let project = env.project("smoke");
+    env.huak_ok(&env.projects(), ["new", "smoke", "--no-vcs"]);
+    env.huak_ok(&project, ["env", "create"]);
+    let hash = "sha256:8abb2f1d86890a2dfb989f9a77cfcfd3e47c2a354b01111771326f8aa26e0254";
+
+    // The hashes passed with a dependency reach the manifest and the hash-checked install.
+    let output = env.huak_ok(
+        &project,
+        ["--dry-run", "add", &format!("six==1.16.0 --hash={hash}")],
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--require-hashes"));
+    assert!(stderr.contains("[tool.huak.hashes]"));
+    assert!(stderr.contains(&format!("six = [\"{hash}\"]")));
+}
+
+#[cfg(feature = "e2e")]
+#[test]
+fn test_project_lifecycle() {
+    let env = TestEnv::new();
+    let project = env.project("lifecycle");
+    env.huak_ok(&env.projects(), ["new", "lifecycle"]);
+
+    env.huak_ok(&project, ["env", "create"]);
+    env.huak_ok(&project, ["add", "six"]);
+    let pyproject = std::fs::read_to_string(project.join("pyproject.toml")).unwrap();
+    assert!(pyproject.contains("\"six "));
+    let output = Command::new(venv_python(&project))
+        .args(["-c", "import six"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "six should be installed in .venv");
+
+    env.huak_ok(&project, ["lint"]);
+    env.huak_ok(&project, ["fmt", "--check"]);
+    env.huak_ok(&project, ["test"]);
+
+    // An unformatted file fails the check without being changed.
+    let module = project.join("src").join("lifecycle").join("unformatted.py");
+    std::fs::write(&module, "x=[1,2]\n").unwrap();
+    assert!(!env.huak(&project, ["fmt", "--check"]).status.success());
+    assert_eq!(std::fs::read_to_string(&module).unwrap(), "x=[1,2]\n");
+    std::fs::remove_file(&module).unwrap();
+
+    env.huak_ok(&project, ["build"]);
+    let artifacts = std::fs::read_dir(project.join("dist"))
+        .unwrap()
+        .map(|it| it.unwrap().file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    assert!(artifacts.iter().any(|it| it.ends_with(".whl")));
+    assert!(artifacts.iter().any(|it| it.ends_with(".tar.gz")));
+
+    env.huak_ok(&project, ["clean"]);
+    assert!(std::fs::read_dir(project.join("dist"))
+        .map(|it| it.count() == 0)
+        .unwrap_or(true));
+}
@@ -18,6 +18,16 @@ mod tests {
         assert_cmd_snapshot!(Command::new("huak").arg("build").arg("--help"));
     }
 
+    #[test]
+    fn test_cache_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("cache").arg("--help"));
+    }
+
+    #[test]
+    fn test_check_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("check").arg("--help"));
+    }
+
     #[test]
     fn test_clean_help() {
         assert_cmd_snapshot!(Command::new("huak").arg("clean").arg("--help"));
@@ -28,6 +38,21 @@ mod tests {
         assert_cmd_snapshot!(Command::new("huak").arg("completion").arg("--help"));
     }
 
+    #[test]
+    fn test_config_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("config").arg("--help"));
+    }
+
+    #[test]
+    fn test_develop_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("develop").arg("--help"));
+    }
+
+    #[test]
+    fn test_env_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("env").arg("--help"));
+    }
+
     #[test]
     fn test_fix_help() {
         assert_cmd_snapshot!(Command::new("huak").arg("fix").arg("--help"));
@@ -44,6 +69,11 @@ mod tests {
         assert_cmd_snapshot!(Command::new("huak").arg("--help"));
     }
 
+    #[test]
+    fn test_history_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("history").arg("--help"));
+    }
+
     #[test]
     fn test_init_help() {
         assert_cmd_snapshot!(Command::new("huak").arg("init").arg("--help"));
@@ -54,11 +84,26 @@ mod tests {
         assert_cmd_snapshot!(Command::new("huak").arg("install").arg("--help"));
     }
 
+    #[test]
+    fn test_kernel_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("kernel").arg("--help"));
+    }
+
     #[test]
     fn test_lint_help() {
         assert_cmd_snapshot!(Command::new("huak").arg("lint").arg("--help"));
     }
 
+    #[test]
+    fn test_list_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("list").arg("--help"));
+    }
+
+    #[test]
+    fn test_metadata_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("metadata").arg("--help"));
+    }
+
     #[test]
     fn test_new_help() {
         assert_cmd_snapshot!(Command::new("huak").arg("new").arg("--help"));
@@ -79,11 +124,26 @@ mod tests {
         assert_cmd_snapshot!(Command::new("huak").arg("remove").arg("--help"));
     }
 
+    #[test]
+    fn test_rename_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("rename").arg("--help"));
+    }
+
+    #[test]
+    fn test_report_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("report").arg("--help"));
+    }
+
     #[test]
     fn test_run_help() {
         assert_cmd_snapshot!(Command::new("huak").arg("run").arg("--help"));
     }
 
+    #[test]
+    fn test_sbom_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("sbom").arg("--help"));
+    }
+
     #[test]
     fn test_test_help() {
         assert_cmd_snapshot!(Command::new("huak").arg("test").arg("--help"));
@@ -94,6 +154,11 @@ mod tests {
         assert_cmd_snapshot!(Command::new("huak").arg("update").arg("--help"));
     }
 
+    #[test]
+    fn test_vendor_help() {
+        assert_cmd_snapshot!(Command::new("huak").arg("vendor").arg("--help"));
+    }
+
     #[test]
     fn test_version_help() {
         assert_cmd_snapshot!(Command::new("huak").arg("version").arg("--help"));
@@ -0,0 +1,26 @@
+"""This module generates classifiers.rs for the huak_package_manager crate."""
+import subprocess
+from pathlib import Path
+
+from trove_classifiers import sorted_classifiers
+
+
+ROOT = Path(
+    subprocess.check_output(["git", "rev-parse", "--show-toplevel"], text=True).strip()
+)
+CRATE = "huak_package_manager"
+
+
+module = f"""\
+//! This file was generated with `{Path(__file__).name}`.
+
+/// Every trove classifier the package index accepts, sorted.
+#[rustfmt::skip]
+pub(crate) const CLASSIFIERS: &[&str] = &["""
+
+for classifier in sorted(sorted_classifiers):
+    module += f'\n\t"{classifier}",'
+module += "\n];\n"
+
+path = ROOT / "crates" / CRATE.replace("_", "-") / "src" / "classifiers.rs"
+path.write_text(module)
@@ -1,7 +1,16 @@
 use huak_home::huak_home_dir;
-use std::path::PathBuf;
+use pep440_rs::Version;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+use termcolor::Color;
 
-use crate::{sys::Terminal, workspace::Workspace, TerminalOptions};
+use crate::{
+    sys::{command_display, CommandPlan, PlannedCommand, Terminal},
+    workspace::{VenvOptions, Workspace},
+    HuakResult, LocalManifest, ScriptMetadata, TerminalOptions,
+};
 
 /// The main `Config` for Huak.
 ///
@@ -32,6 +41,26 @@ pub struct Config {
     pub terminal_options: TerminalOptions,
     /// Huak's home directory.
     pub home: Option<PathBuf>,
+    /// Report side effects (file writes, removals, and subprocesses that change the system)
+    /// instead of performing them.
+    pub dry_run: bool,
+    /// Use the Python interpreter directly instead of a virtual environment.
+    pub system_python: bool,
+    /// Allow changes to a Python environment that's externally managed (see PEP 668).
+    pub break_system_packages: bool,
+    /// Options for the virtual environments created for the workspace.
+    pub venv_options: VenvOptions,
+    /// Use the environment configured in `[tool.huak.envs.<name>]` instead of the project's
+    /// default virtual environment.
+    pub env_name: Option<String>,
+    /// The profile in `[tool.huak.profile.<name>]` the command's defaults were merged from.
+    pub profile: Option<String>,
+    /// The version of Huak that's running, checked against the versions projects and the files
+    /// Huak writes require. `None` skips the check.
+    pub huak_version: Option<Version>,
+    /// Record the subprocesses that would change the system instead of running them
+    /// (`--explain`).
+    pub plan: Option<CommandPlan>,
 }
 
 impl Config {
@@ -47,6 +76,21 @@ impl Config {
         Terminal::from_options(self.terminal_options.clone())
     }
 
+    /// Get a `Terminal` for running the subprocesses of an operation (ex: "install", "test").
+    ///
+    /// If no timeout was already configured, the operation's timeout is read from the
+    /// workspace's `[tool.huak.timeouts]` table.
+    #[must_use]
+    pub fn operation_terminal(&self, operation: &str) -> Terminal {
+        let mut options = self.terminal_options.clone();
+
+        if options.timeout.is_none() {
+            options.timeout = self.workspace().operation_timeout(operation);
+        }
+
+        Terminal::from_options(options)
+    }
+
     /// Get a `Config` with a new `Terminal`.
     #[must_use]
     pub fn with_terminal(self, terminal_options: TerminalOptions) -> Self {
@@ -54,9 +98,177 @@ impl Config {
             workspace_root: self.workspace_root,
             cwd: self.cwd,
             terminal_options,
+            dry_run: self.dry_run,
+            system_python: self.system_python,
+            break_system_packages: self.break_system_packages,
+            venv_options: self.venv_options,
+            env_name: self.env_name,
+            huak_version: self.huak_version,
+            plan: self.plan,
             ..Default::default()
         }
     }
+
+    /// Run a subprocess for an operation that changes the system (installs, uploads, etc.).
+    ///
+    /// In dry-run mode the command is printed instead. With a `CommandPlan` it's recorded to the
+    /// plan instead.
+    pub fn run_effect(&self, operation: &str, cmd: &mut Command) -> HuakResult<()> {
+        if let Some(plan) = self.plan.as_ref() {
+            plan.record(PlannedCommand::new(operation, cmd));
+            return Ok(());
+        }
+
+        let mut terminal = self.operation_terminal(operation);
+
+        if self.dry_run {
+            return terminal.print_custom(
+                "Would run",
+                format!("`{}`", command_display(cmd)),
+                Color::Yellow,
+                true,
+            );
+        }
+
+        terminal.run_command(cmd)
+    }
+
+    /// Write a `LocalManifest` to its file.
+    ///
+    /// In dry-run mode the changes that would be made to the file are printed instead.
+    pub fn write_manifest(&self, manifest: &LocalManifest) -> HuakResult<()> {
+        if !self.dry_run {
+            return manifest.write_file();
+        }
+
+        self.print_file_diff(manifest.path(), &manifest.manifest_data().to_string())
+    }
+
+    /// Write a script with its `ScriptMetadata` to its file.
+    ///
+    /// In dry-run mode the changes that would be made to the file are printed instead.
+    pub fn write_script(&self, script: &ScriptMetadata) -> HuakResult<()> {
+        if !self.dry_run {
+            return script.write_file();
+        }
+
+        self.print_file_diff(script.path(), &script.contents())
+    }
+
+    /// Write `contents` to the file at `path`.
+    ///
+    /// In dry-run mode the changes that would be made to the file are printed instead.
+    pub fn write_file<T: AsRef<Path>>(&self, path: T, contents: &str) -> HuakResult<()> {
+        let path = path.as_ref();
+
+        if !self.dry_run {
+            return Ok(std::fs::write(path, contents)?);
+        }
+
+        self.print_file_diff(path, contents)
+    }
+
+    /// Move a file or directory from `from` to `to`.
+    ///
+    /// In dry-run mode the paths are printed instead.
+    pub fn move_path<T: AsRef<Path>>(&self, from: T, to: T) -> HuakResult<()> {
+        let (from, to) = (from.as_ref(), to.as_ref());
+
+        if self.dry_run {
+            return self.terminal().print_custom(
+                "Would move",
+                format!("{} -> {}", from.display(), to.display()),
+                Color::Yellow,
+                true,
+            );
+        }
+
+        Ok(std::fs::rename(from, to)?)
+    }
+
+    /// Print the changes writing `after` to the file at `path` would make.
+    fn print_file_diff(&self, path: &Path, after: &str) -> HuakResult<()> {
+        let before = std::fs::read_to_string(path).unwrap_or_default();
+        let mut terminal = self.terminal();
+
+        if before == after {
+            return Ok(());
+        }
+
+        terminal.print_custom("Would write", path.display(), Color::Yellow, true)?;
+        for (tag, line) in line_diff(&before, after) {
+            match tag {
+                '-' => terminal.print_without_status(format!("-{line}"), Color::Red)?,
+                '+' => terminal.print_without_status(format!("+{line}"), Color::Green)?,
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove a file or directory.
+    ///
+    /// In dry-run mode the path is printed instead.
+    pub fn remove_path<T: AsRef<Path>>(&self, path: T) -> HuakResult<()> {
+        let path = path.as_ref();
+
+        if self.dry_run {
+            return self.terminal().print_custom(
+                "Would remove",
+                path.display(),
+                Color::Yellow,
+                true,
+            );
+        }
+
+        if path.is_dir() {
+            std::fs::remove_dir_all(path)?;
+        } else if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Diff two strings line by line. Each line is tagged with '-' (removed), '+' (added),
+/// or ' ' (unchanged).
+fn line_diff<'a>(before: &'a str, after: &'a str) -> Vec<(char, &'a str)> {
+    let a = before.lines().collect::<Vec<_>>();
+    let b = after.lines().collect::<Vec<_>>();
+
+    // Longest common subsequence lengths for every suffix pair.
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::new();
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            diff.push((' ', a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(('-', a[i]));
+            i += 1;
+        } else {
+            diff.push(('+', b[j]));
+            j += 1;
+        }
+    }
+    diff.extend(a[i..].iter().map(|it| ('-', *it)));
+    diff.extend(b[j..].iter().map(|it| ('+', *it)));
+
+    diff
 }
 
 impl Default for Config {
@@ -66,6 +278,103 @@ impl Default for Config {
             cwd: PathBuf::default(),
             terminal_options: TerminalOptions::default(),
             home: huak_home_dir(),
+            dry_run: false,
+            system_python: false,
+            break_system_packages: false,
+            venv_options: VenvOptions::default(),
+            env_name: None,
+            profile: None,
+            huak_version: None,
+            plan: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Verbosity;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff("a\nb\nc", "a\nc\nd");
+
+        assert_eq!(diff, vec![(' ', "a"), ('-', "b"), (' ', "c"), ('+', "d")]);
+    }
+
+    #[test]
+    fn test_dry_run_effects() {
+        let dir = tempdir().unwrap();
+        let config = Config {
+            workspace_root: dir.path().to_path_buf(),
+            cwd: dir.path().to_path_buf(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            dry_run: true,
+            ..Default::default()
+        };
+        let path = dir.path().join("pyproject.toml");
+        let mut manifest = LocalManifest::template(&path);
+        manifest
+            .manifest_data_mut()
+            .set_project_name("mock-project");
+        let file = dir.path().join("file.txt");
+        std::fs::write(&file, "").unwrap();
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "touch created.txt"])
+            .current_dir(dir.path());
+
+        config.write_manifest(&manifest).unwrap();
+        config.remove_path(&file).unwrap();
+        config.run_effect("test", &mut cmd).unwrap();
+
+        assert!(!path.exists());
+        assert!(file.exists());
+        assert!(!dir.path().join("created.txt").exists());
+    }
+
+    #[test]
+    fn test_planned_effects() {
+        let dir = tempdir().unwrap();
+        let plan = CommandPlan::default();
+        let config = Config {
+            workspace_root: dir.path().to_path_buf(),
+            cwd: dir.path().to_path_buf(),
+            dry_run: true,
+            plan: Some(plan.clone()),
+            ..Default::default()
+        };
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "touch created.txt"])
+            .env("PIP_NO_INDEX", "1")
+            .env_remove("PYTHONHOME")
+            .current_dir(dir.path());
+
+        config.run_effect("install", &mut cmd).unwrap();
+        config
+            .run_effect("install", &mut Command::new("/bin/true"))
+            .unwrap();
+
+        let commands = plan.commands();
+
+        assert!(!dir.path().join("created.txt").exists());
+        assert_eq!(commands.len(), 2);
+        assert_eq!(
+            commands[0],
+            PlannedCommand {
+                operation: "install".to_string(),
+                program: "/bin/sh".to_string(),
+                args: vec!["-c".to_string(), "touch created.txt".to_string()],
+                envs: vec![
+                    ("PIP_NO_INDEX".to_string(), Some("1".to_string())),
+                    ("PYTHONHOME".to_string(), None),
+                ],
+                current_dir: Some(dir.path().to_path_buf()),
+            }
+        );
+        assert_eq!(commands[0].command_line(), "/bin/sh -c 'touch created.txt'");
+    }
+}
@@ -1,9 +1,26 @@
-use std::{ffi::OsStr, fmt::Display, str::FromStr};
+use std::{
+    borrow::Cow, collections::HashMap, ffi::OsStr, fmt::Display, io::Read, path::Path, str::FromStr,
+};
 
-use pep440_rs::VersionSpecifiers;
-use pep508_rs::{Requirement, VersionOrUrl};
+use huak_pyproject_toml::canonical_name;
+use pep440_rs::{Operator, Version, VersionSpecifiers};
+use pep508_rs::{MarkerTree, Requirement, VersionOrUrl};
+use url::Url;
 
-use crate::Error;
+use crate::{suggest::edit_distance, Error, HuakResult};
+
+/// Characters used by PEP 440 version specifier operators.
+const VERSION_OPERATOR_CHARS: [char; 5] = ['=', '<', '>', '~', '!'];
+
+/// Extensions of distribution archives that can be added from a local file.
+const SUPPORTED_ARCHIVE_EXTENSIONS: [&str; 3] = [".whl", ".tar.gz", ".zip"];
+
+/// Extensions of archives that look like distributions but can't be added.
+const UNSUPPORTED_ARCHIVE_EXTENSIONS: [&str; 6] =
+    [".egg", ".exe", ".tar", ".tar.bz2", ".tar.xz", ".tgz"];
+
+/// Hash algorithms the installer can verify and the length of their hex digests.
+const HASH_ALGORITHMS: [(&str, usize); 3] = [("sha256", 64), ("sha384", 96), ("sha512", 128)];
 
 /// The `Dependency` is an abstraction for `Package` data used as a cheap alternative
 /// for operations on lots of `Package` data.
@@ -17,18 +34,189 @@ use crate::Error;
 /// let dependency = Dependency::from_str("my-dependency >= 0.1.0, < 0.2.0").unwrap();
 /// ```
 #[derive(Clone, Debug)]
-pub struct Dependency(Requirement);
+pub struct Dependency {
+    requirement: Requirement,
+    /// Hashes (like "sha256:<hex>") the `Dependency`'s distribution must match.
+    hashes: Vec<String>,
+}
 
 impl Dependency {
     /// Get a reference to the wrapped `Requirement`.
     #[must_use]
     pub fn requirement(&self) -> &Requirement {
-        &self.0
+        &self.requirement
     }
 
     /// Get a mutable reference to the wrapped `Requirement`.
     pub fn requirement_mut(&mut self) -> &mut Requirement {
-        &mut self.0
+        &mut self.requirement
+    }
+
+    /// Get the hashes the `Dependency`'s distribution must match.
+    #[must_use]
+    pub fn hashes(&self) -> &[String] {
+        &self.hashes
+    }
+
+    /// Set the hashes the `Dependency`'s distribution must match.
+    ///
+    /// Hashes can only be pinned for a `Dependency` pinned to an exact version.
+    pub fn set_hashes(&mut self, hashes: Vec<String>) -> HuakResult<()> {
+        if let Some(it) = hashes.iter().find(|it| !is_valid_hash(it)) {
+            return Err(Error::InvalidHash(it.clone()));
+        }
+
+        if !hashes.is_empty() && !self.is_pinned() {
+            return Err(Error::UnpinnedHashedDependency {
+                requirement: self.to_string(),
+            });
+        }
+
+        self.hashes = hashes;
+
+        Ok(())
+    }
+
+    /// Restrict the `Dependency` to the Python versions matching `constraint` (like `<3.10`)
+    /// with a `python_version` marker, combined with any marker it already has.
+    pub fn add_python_constraint(&mut self, constraint: &str) -> HuakResult<()> {
+        let invalid = |reason: String| Error::InvalidVersionString {
+            version: constraint.to_string(),
+            reason,
+        };
+        let specifiers =
+            VersionSpecifiers::from_str(constraint).map_err(|e| invalid(e.to_string()))?;
+
+        if specifiers.iter().next().is_none() {
+            return Err(invalid("expected a constraint like <3.10".to_string()));
+        }
+
+        let mut markers = self
+            .requirement
+            .marker
+            .as_ref()
+            .map(|it| vec![format!("({it})")])
+            .unwrap_or_default();
+
+        for it in specifiers.iter() {
+            let specifier = it.to_string();
+            let (operator, version) =
+                specifier.split_at(specifier.find(|c: char| c.is_ascii_digit()).unwrap_or(0));
+            // `python_version` only has the major and minor versions.
+            let key = if version.split('.').filter(|it| *it != "*").count() > 2 {
+                "python_full_version"
+            } else {
+                "python_version"
+            };
+            markers.push(format!("{key} {} '{version}'", operator.trim()));
+        }
+
+        self.requirement.marker = Some(MarkerTree::from_str(&markers.join(" and "))?);
+
+        Ok(())
+    }
+
+    /// Check if the `Dependency` is pinned to an exact version.
+    fn is_pinned(&self) -> bool {
+        self.version_specifiers().is_some_and(|it| {
+            it.len() == 1
+                && it
+                    .iter()
+                    .all(|it| matches!(it.operator(), Operator::Equal | Operator::ExactEqual))
+        })
+    }
+
+    /// Get the version the `Dependency` is pinned to, if it's pinned to an exact version.
+    #[must_use]
+    pub fn pinned_version(&self) -> Option<String> {
+        if !self.is_pinned() {
+            return None;
+        }
+
+        self.version_specifiers()
+            .and_then(|it| it.iter().next())
+            .map(|it| it.version().to_string())
+    }
+
+    /// Check if the `installed` package versions (keyed by canonical name) satisfy the
+    /// `Dependency`.
+    ///
+    /// Dependencies with extras or markers are never considered satisfied, since the installed
+    /// versions alone can't tell if the extras' packages or the right platform's packages are
+    /// installed. Direct references are satisfied by any installed version.
+    #[must_use]
+    pub fn is_satisfied_by(&self, installed: &HashMap<String, Version>) -> bool {
+        if self.requirement.marker.is_some()
+            || self
+                .requirement
+                .extras
+                .as_ref()
+                .is_some_and(|it| !it.is_empty())
+        {
+            return false;
+        }
+
+        let Some(version) = installed.get(&canonical_name(self.name())) else {
+            return false;
+        };
+
+        match self.requirement.version_or_url.as_ref() {
+            Some(VersionOrUrl::VersionSpecifier(it)) => it.contains(version),
+            Some(VersionOrUrl::Url(_)) | None => true,
+        }
+    }
+
+    /// Check if the `Dependency` can't be satisfied together with `other`, another requirement
+    /// of the same package, because no version matches both of their specifiers.
+    ///
+    /// Requirements with different markers may never apply together, so only requirements with
+    /// the same markers are compared. Direct references are never considered conflicting.
+    #[must_use]
+    pub fn conflicts_with(&self, other: &Dependency) -> bool {
+        if canonical_name(self.name()) != canonical_name(other.name())
+            || self.requirement.marker.as_ref().map(ToString::to_string)
+                != other.requirement.marker.as_ref().map(ToString::to_string)
+        {
+            return false;
+        }
+        let (Some(a), Some(b)) = (self.version_specifiers(), other.version_specifiers()) else {
+            return false;
+        };
+
+        let mut lower: Option<(Version, bool)> = None;
+        let mut upper: Option<(Version, bool)> = None;
+        for specifier in a.iter().chain(b.iter()) {
+            let (low, high) = specifier_bounds(*specifier.operator(), specifier.version());
+            if let Some(it) = low {
+                if lower
+                    .as_ref()
+                    .map_or(true, |bound| tighter_lower(&it, bound))
+                {
+                    lower = Some(it);
+                }
+            }
+            if let Some(it) = high {
+                if upper
+                    .as_ref()
+                    .map_or(true, |bound| tighter_upper(&it, bound))
+                {
+                    upper = Some(it);
+                }
+            }
+        }
+
+        match (lower, upper) {
+            (Some((low, low_inclusive)), Some((high, high_inclusive))) => {
+                low > high
+                    || (low == high
+                        // Only one version is left, so `!=` can still exclude it.
+                        && !(low_inclusive
+                            && high_inclusive
+                            && a.contains(&low)
+                            && b.contains(&low)))
+            }
+            _ => false,
+        }
     }
 
     /// Get the `Dependency` name.
@@ -40,7 +228,7 @@ impl Dependency {
     /// Get a reference to the `Dependency`'s `VersionSpecifiers`.
     #[allow(dead_code)]
     fn version_specifiers(&self) -> Option<&VersionSpecifiers> {
-        match self.0.version_or_url.as_ref() {
+        match self.requirement.version_or_url.as_ref() {
             Some(VersionOrUrl::VersionSpecifier(it)) => Some(it),
             _ => None,
         }
@@ -49,23 +237,42 @@ impl Dependency {
 
 impl From<Requirement> for Dependency {
     fn from(value: Requirement) -> Self {
-        Dependency(value)
+        Dependency {
+            requirement: value,
+            hashes: Vec::new(),
+        }
     }
 }
 
 /// Initialize a `Dependency` from a `&str`.
 ///
+/// `@` can be used as shorthand for an exact pin ("my-dependency@0.1.0") as long as no other
+/// version operator is used. Otherwise the full PEP 508 requirement is preserved.
+///
+/// Exact pins can be followed by `--hash=<algorithm>:<hex>` options like in a requirements file.
+///
+/// A path to a local wheel or sdist is parsed as a direct reference to the file, named after the
+/// archive's filename (`my_dependency @ file:///path/to/my_dependency-0.1.0-py3-none-any.whl`).
+///
 /// ```
 /// use huak_package_manager::Dependency;
 ///
 /// let dependency = Dependency::from_str("my-dependency >= 0.1.0, < 0.2.0").unwrap();
+/// let pinned = Dependency::from_str("my-dependency@0.1.0").unwrap();
 /// ```
 impl FromStr for Dependency {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let requirement = Requirement::from_str(s)?;
-        let dependency = Dependency(requirement);
+        if is_archive_path(s) {
+            let (path, marker) = split_marker(s);
+            return archive_dependency(Path::new(path), marker);
+        }
+
+        let (s, hashes) = split_hashes(s);
+        let requirement = Requirement::from_str(&expand_pin_shorthand(&s))?;
+        let mut dependency = Dependency::from(requirement);
+        dependency.set_hashes(hashes)?;
 
         Ok(dependency)
     }
@@ -79,7 +286,7 @@ impl Display for Dependency {
 
 impl From<&Requirement> for Dependency {
     fn from(value: &Requirement) -> Self {
-        Dependency(value.clone())
+        Dependency::from(value.clone())
     }
 }
 
@@ -91,12 +298,173 @@ impl AsRef<OsStr> for Dependency {
 
 impl PartialEq for Dependency {
     fn eq(&self, other: &Self) -> bool {
-        self.name() == other.name()
+        canonical_name(self.name()) == canonical_name(other.name())
     }
 }
 
 impl Eq for Dependency {}
 
+/// Check if a requirement string is a path to an archive rather than a PEP 508 requirement.
+/// A requirement's environment marker can contain anything, so only what's before it is
+/// checked.
+fn is_archive_path(s: &str) -> bool {
+    let (path, _) = split_marker(s);
+
+    !path.contains('@')
+        && (path.contains(['/', '\\'])
+            || SUPPORTED_ARCHIVE_EXTENSIONS
+                .iter()
+                .chain(UNSUPPORTED_ARCHIVE_EXTENSIONS.iter())
+                .any(|it| path.to_lowercase().ends_with(it)))
+}
+
+/// Split a requirement string into what's before its environment marker and the marker.
+fn split_marker(s: &str) -> (&str, Option<&str>) {
+    match s.split_once(';') {
+        Some((it, marker)) => (it.trim(), Some(marker.trim())),
+        None => (s.trim(), None),
+    }
+}
+
+/// Get a `Dependency` referencing a local wheel or sdist `path`.
+///
+/// The archive must exist and its name and version are read from its filename, like
+/// `my_dependency-0.1.0-py3-none-any.whl` or `my-dependency-0.1.0.tar.gz`. An environment
+/// `marker` is kept on the requirement.
+fn archive_dependency(path: &Path, marker: Option<&str>) -> HuakResult<Dependency> {
+    let file_name = path
+        .file_name()
+        .map(|it| it.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let lowercase = file_name.to_lowercase();
+    let Some(extension) = SUPPORTED_ARCHIVE_EXTENSIONS
+        .iter()
+        .find(|it| lowercase.ends_with(*it))
+    else {
+        return Err(Error::UnsupportedArchive {
+            path: path.to_path_buf(),
+        });
+    };
+    let stem = &file_name[..file_name.len() - extension.len()];
+
+    // Wheels are named {name}-{version}(-{build})?-{python}-{abi}-{platform}.whl.
+    let name_and_version = if *extension == ".whl" {
+        let parts = stem.split('-').collect::<Vec<_>>();
+        (parts.len() == 5 || parts.len() == 6).then(|| (parts[0], parts[1]))
+    } else {
+        stem.rsplit_once('-')
+    };
+    let Some((name, _)) = name_and_version
+        .filter(|(name, version)| !name.is_empty() && Version::from_str(version).is_ok())
+    else {
+        return Err(Error::InvalidArchive(format!(
+            "{} (expected a name and version in the filename)",
+            path.display()
+        )));
+    };
+
+    // Verbatim (`\\?\`) paths on Windows make file URLs pip can't read.
+    let path = dunce::canonicalize(std::env::current_dir()?.join(path)).map_err(|_| {
+        Error::ArchiveNotFound {
+            path: path.to_path_buf(),
+        }
+    })?;
+    check_archive_format(&path, extension)?;
+    let url = Url::from_file_path(&path)
+        .map_err(|()| Error::InvalidArchive(path.display().to_string()))?;
+
+    // A space is needed between a URL and its marker.
+    let marker = marker.map(|it| format!(" ; {it}")).unwrap_or_default();
+
+    Ok(Dependency::from(Requirement::from_str(&format!(
+        "{name} @ {url}{marker}"
+    ))?))
+}
+
+/// Check an archive's leading bytes match its format (zip for wheels, gzip for tarballs).
+fn check_archive_format(path: &Path, extension: &str) -> HuakResult<()> {
+    let magic: &[u8] = if extension == ".tar.gz" {
+        &[0x1f, 0x8b]
+    } else {
+        b"PK\x03\x04"
+    };
+    let mut buf = vec![0; magic.len()];
+    let valid = std::fs::File::open(path)?
+        .read_exact(&mut buf)
+        .is_ok_and(|()| buf == magic);
+
+    if !valid {
+        return Err(Error::InvalidArchive(format!(
+            "{} (not a {} file)",
+            path.display(),
+            if extension == ".tar.gz" {
+                "gzip"
+            } else {
+                "zip"
+            }
+        )));
+    }
+
+    Ok(())
+}
+
+/// Split `--hash=<hash>` options from a requirement string.
+fn split_hashes(s: &str) -> (Cow<'_, str>, Vec<String>) {
+    if !s.contains("--hash") {
+        return (Cow::Borrowed(s), Vec::new());
+    }
+
+    let (hashes, requirement): (Vec<_>, Vec<_>) = s
+        .split_whitespace()
+        .partition(|it| it.starts_with("--hash"));
+    let hashes = hashes
+        .into_iter()
+        .map(|it| {
+            it.trim_start_matches("--hash")
+                .trim_start_matches('=')
+                .to_string()
+        })
+        .collect();
+
+    (Cow::Owned(requirement.join(" ")), hashes)
+}
+
+/// Check if a hash is formatted as "<algorithm>:<hex digest>".
+fn is_valid_hash(hash: &str) -> bool {
+    hash.split_once(':').is_some_and(|(algorithm, digest)| {
+        HASH_ALGORITHMS.iter().any(|(it, len)| {
+            *it == algorithm
+                && digest.len() == *len
+                && digest.chars().all(|c| c.is_ascii_hexdigit())
+        })
+    })
+}
+
+/// Expand "name@version" into "name==version".
+///
+/// The `@` is left alone when the requirement already uses a version operator or when it's
+/// followed by a URL (PEP 508's "name @ url" form).
+fn expand_pin_shorthand(s: &str) -> Cow<'_, str> {
+    let (requirement, marker) = match s.split_once(';') {
+        Some((requirement, marker)) => (requirement, Some(marker)),
+        None => (s, None),
+    };
+
+    match requirement.split_once('@') {
+        Some((name, version))
+            if !requirement.contains(VERSION_OPERATOR_CHARS) && !version.contains("://") =>
+        {
+            let mut expanded = format!("{}=={}", name.trim(), version.trim());
+            if let Some(it) = marker {
+                expanded.push(';');
+                expanded.push_str(it);
+            }
+            Cow::Owned(expanded)
+        }
+        _ => Cow::Borrowed(s),
+    }
+}
+
 /// Construct an `Iterator` over an `IntoIterator` of `&str`s.
 ///
 /// ```
@@ -112,6 +480,87 @@ where
         .filter_map(|item| Dependency::from_str(item.as_ref()).ok())
 }
 
+/// Get the `DependencyNotFound` error for a `name` none of the `candidates` match.
+///
+/// The error shows the canonical form of the name and suggests close candidates.
+pub(crate) fn dependency_not_found<I>(name: &str, candidates: I) -> Error
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let canonical = canonical_name(name);
+    let max_distance = (canonical.len() / 3).max(1);
+    let mut suggestions = candidates
+        .into_iter()
+        .map(|it| it.as_ref().to_string())
+        .filter_map(|it| {
+            let distance = edit_distance(&canonical, &canonical_name(&it));
+            (distance <= max_distance).then_some((distance, it))
+        })
+        .collect::<Vec<_>>();
+    suggestions.sort();
+    suggestions.dedup_by(|a, b| a.1 == b.1);
+
+    Error::DependencyNotFound {
+        name: name.to_string(),
+        canonical_name: (canonical != name).then_some(canonical),
+        suggestions: suggestions.into_iter().take(3).map(|(_, it)| it).collect(),
+    }
+}
+
+/// Get the lower and upper bounds of the versions matching a version specifier with the
+/// `operator` and `version`, and whether each bound is inclusive.
+///
+/// `!=` doesn't bound the versions, and `==1.2.*` and `~=1.2` match up to (but excluding) the
+/// next release of their prefix.
+#[allow(clippy::type_complexity)]
+fn specifier_bounds(
+    operator: Operator,
+    version: &Version,
+) -> (Option<(Version, bool)>, Option<(Version, bool)>) {
+    let next_release = |release: &[u64]| {
+        let mut release = release.to_vec();
+        if let Some(it) = release.last_mut() {
+            *it += 1;
+        }
+        Version::from_release(release)
+    };
+
+    match operator {
+        Operator::Equal | Operator::ExactEqual => {
+            (Some((version.clone(), true)), Some((version.clone(), true)))
+        }
+        Operator::EqualStar => (
+            Some((Version::from_release(version.release.clone()), true)),
+            Some((next_release(&version.release), false)),
+        ),
+        Operator::TildeEqual => (
+            Some((version.clone(), true)),
+            (version.release.len() > 1).then(|| {
+                (
+                    next_release(&version.release[..version.release.len() - 1]),
+                    false,
+                )
+            }),
+        ),
+        Operator::LessThan => (None, Some((version.clone(), false))),
+        Operator::LessThanEqual => (None, Some((version.clone(), true))),
+        Operator::GreaterThan => (Some((version.clone(), false)), None),
+        Operator::GreaterThanEqual => (Some((version.clone(), true)), None),
+        Operator::NotEqual | Operator::NotEqualStar => (None, None),
+    }
+}
+
+/// Check if the lower bound `a` excludes more versions than the lower bound `b`.
+fn tighter_lower(a: &(Version, bool), b: &(Version, bool)) -> bool {
+    a.0 > b.0 || (a.0 == b.0 && !a.1)
+}
+
+/// Check if the upper bound `a` excludes more versions than the upper bound `b`.
+fn tighter_upper(a: &(Version, bool), b: &(Version, bool)) -> bool {
+    a.0 < b.0 || (a.0 == b.0 && !a.1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -127,4 +576,279 @@ mod tests {
             pep440_rs::VersionSpecifiers::from_str("==0.0.0").unwrap()
         );
     }
+
+    #[test]
+    fn dependency_from_str_operators() {
+        for (s, specifiers) in [
+            ("django==4.2.0", "==4.2.0"),
+            ("django!=4.2.0", "!=4.2.0"),
+            ("django>=4,<5", ">=4,<5"),
+            ("django > 4", ">4"),
+            ("django<=4.2", "<=4.2"),
+            ("django~=4.2", "~=4.2"),
+            ("django===4.2.0", "===4.2.0"),
+        ] {
+            let dep = Dependency::from_str(s).unwrap();
+
+            assert_eq!(dep.name(), "django");
+            assert_eq!(
+                *dep.version_specifiers().unwrap(),
+                VersionSpecifiers::from_str(specifiers).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn dependency_is_satisfied_by() {
+        let installed = HashMap::from([
+            ("requests".to_string(), Version::from_str("2.31.0").unwrap()),
+            (
+                "typing-extensions".to_string(),
+                Version::from_str("4.9.0").unwrap(),
+            ),
+        ]);
+        let satisfied = |it: &str| {
+            Dependency::from_str(it)
+                .unwrap()
+                .is_satisfied_by(&installed)
+        };
+
+        assert!(satisfied("requests"));
+        assert!(satisfied("requests>=2,<3"));
+        assert!(satisfied("typing_extensions==4.9.0"));
+        assert!(!satisfied("requests>=3"));
+        assert!(!satisfied("requests[socks]"));
+        assert!(!satisfied("requests; python_version >= '3.8'"));
+        assert!(!satisfied("rich"));
+    }
+
+    #[test]
+    fn dependency_from_str_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let wheel = dir.path().join("my_pkg-1.0-py3-none-any.whl");
+        let sdist = dir.path().join("my-pkg-1.0.tar.gz");
+        std::fs::write(&wheel, b"PK\x03\x04").unwrap();
+        std::fs::write(&sdist, [0x1f, 0x8b, 0x08]).unwrap();
+
+        for path in [&wheel, &sdist] {
+            let dep = Dependency::from_str(&path.display().to_string()).unwrap();
+
+            assert_eq!(canonical_name(dep.name()), "my-pkg");
+            assert_eq!(
+                dep.requirement().version_or_url,
+                Some(VersionOrUrl::Url(
+                    Url::from_file_path(dunce::canonicalize(path).unwrap()).unwrap()
+                ))
+            );
+        }
+
+        let invalid = dir.path().join("my_pkg-2.0-py3-none-any.whl");
+        std::fs::write(&invalid, "not a wheel").unwrap();
+
+        assert!(matches!(
+            Dependency::from_str(&invalid.display().to_string()),
+            Err(Error::InvalidArchive(_))
+        ));
+        assert!(matches!(
+            Dependency::from_str(&dir.path().join("my_pkg.whl").display().to_string()),
+            Err(Error::InvalidArchive(_))
+        ));
+        assert!(matches!(
+            Dependency::from_str("./missing-1.0.tar.gz"),
+            Err(Error::ArchiveNotFound { .. })
+        ));
+        assert!(matches!(
+            Dependency::from_str("my_pkg-1.0-py3.11.egg"),
+            Err(Error::UnsupportedArchive { .. })
+        ));
+        assert!(Dependency::from_str("my-pkg @ https://example.com/my_pkg-1.0.tar.gz").is_ok());
+
+        let dep =
+            Dependency::from_str(&format!("{}; python_version >= '3.8'", wheel.display())).unwrap();
+
+        assert_eq!(canonical_name(dep.name()), "my-pkg");
+        assert_eq!(
+            dep.requirement().marker,
+            Some(MarkerTree::from_str("python_version >= '3.8'").unwrap())
+        );
+        assert!(matches!(
+            dep.requirement().version_or_url,
+            Some(VersionOrUrl::Url(_))
+        ));
+    }
+
+    #[test]
+    fn dependency_from_str_archive_non_ascii_nested_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let archives = (0..20).fold(dir.path().join("архивы"), |it, _| {
+            it.join("вложенная-папка")
+        });
+        std::fs::create_dir_all(&archives).unwrap();
+        let wheel = archives.join("my_pkg-1.0-py3-none-any.whl");
+        std::fs::write(&wheel, b"PK\x03\x04").unwrap();
+
+        let dep = Dependency::from_str(&wheel.display().to_string()).unwrap();
+        let Some(VersionOrUrl::Url(url)) = dep.requirement().version_or_url.as_ref() else {
+            panic!("expected a url");
+        };
+
+        assert!(url.as_str().is_ascii());
+        assert_eq!(
+            url.to_file_path().unwrap(),
+            dunce::canonicalize(&wheel).unwrap()
+        );
+    }
+
+    #[test]
+    fn dependency_from_str_pin_shorthand() {
+        let dep = Dependency::from_str("django@4.2.0").unwrap();
+
+        assert_eq!(dep.to_string(), "django ==4.2.0");
+        assert_eq!(
+            *dep.version_specifiers().unwrap(),
+            VersionSpecifiers::from_str("==4.2.0").unwrap()
+        );
+
+        let dep = Dependency::from_str("django[argon2]@4.2.0").unwrap();
+
+        assert_eq!(
+            dep.requirement().extras.as_deref(),
+            Some(&[String::from("argon2")][..])
+        );
+        assert_eq!(
+            *dep.version_specifiers().unwrap(),
+            VersionSpecifiers::from_str("==4.2.0").unwrap()
+        );
+    }
+
+    #[test]
+    fn dependency_from_str_marker() {
+        let dep = Dependency::from_str("uvloop; platform_system != 'Windows'").unwrap();
+
+        assert_eq!(dep.to_string(), "uvloop ; platform_system != 'Windows'");
+
+        let dep = Dependency::from_str("uvloop@0.19.0; sys_platform == 'linux/arm'").unwrap();
+
+        assert_eq!(
+            dep.to_string(),
+            "uvloop ==0.19.0 ; sys_platform == 'linux/arm'"
+        );
+        assert!(matches!(
+            Dependency::from_str("uvloop; platform_system !="),
+            Err(Error::PEP508Error(_))
+        ));
+    }
+
+    #[test]
+    fn dependency_add_python_constraint() {
+        let mut dep = Dependency::from_str("importlib-metadata").unwrap();
+        dep.add_python_constraint("<3.10").unwrap();
+
+        assert_eq!(
+            dep.to_string(),
+            "importlib-metadata ; python_version < '3.10'"
+        );
+
+        let mut dep = Dependency::from_str("tomli>=1").unwrap();
+        dep.add_python_constraint(">=3.8, <3.10.2").unwrap();
+
+        assert_eq!(
+            dep.to_string(),
+            "tomli >=1 ; python_version >= '3.8' and python_full_version < '3.10.2'"
+        );
+
+        let mut dep = Dependency::from_str("uvloop; platform_system != 'Windows'").unwrap();
+        dep.add_python_constraint("<3.12").unwrap();
+        let marker = dep.requirement().marker.as_ref().unwrap().to_string();
+
+        assert!(marker.contains("platform_system != 'Windows'"));
+        assert!(marker.contains("python_version < '3.12'"));
+        assert!(matches!(
+            Dependency::from_str("tomli")
+                .unwrap()
+                .add_python_constraint("<three"),
+            Err(Error::InvalidVersionString { .. })
+        ));
+    }
+
+    #[test]
+    fn dependency_from_str_hashes() {
+        let hash = format!("sha256:{}", "a".repeat(64));
+        let dep = Dependency::from_str(&format!("requests==2.31.0 --hash={hash}")).unwrap();
+
+        assert_eq!(dep.to_string(), "requests ==2.31.0");
+        assert_eq!(dep.hashes(), std::slice::from_ref(&hash));
+        assert!(matches!(
+            Dependency::from_str(&format!("requests>=2.31.0 --hash={hash}")),
+            Err(Error::UnpinnedHashedDependency { .. })
+        ));
+        assert!(matches!(
+            Dependency::from_str(&format!("requests --hash={hash}")),
+            Err(Error::UnpinnedHashedDependency { .. })
+        ));
+        assert!(matches!(
+            Dependency::from_str("requests==2.31.0 --hash=md5:abc"),
+            Err(Error::InvalidHash(_))
+        ));
+    }
+
+    #[test]
+    fn dependency_from_str_url() {
+        let dep = Dependency::from_str("django @ https://example.com/django.tar.gz").unwrap();
+
+        assert!(matches!(
+            dep.requirement().version_or_url,
+            Some(VersionOrUrl::Url(_))
+        ));
+    }
+
+    #[test]
+    fn dependency_not_found_suggestions() {
+        let candidates = ["zope.interface", "Flask_SQLAlchemy", "requests"];
+
+        assert_eq!(
+            dependency_not_found("zope_interfaces", candidates).to_string(),
+            "a dependency could not be found: zope_interfaces (canonical name zope-interfaces); \
+            did you mean zope.interface?"
+        );
+        assert_eq!(
+            dependency_not_found("flask-sqlalchemy2", candidates).to_string(),
+            "a dependency could not be found: flask-sqlalchemy2; did you mean Flask_SQLAlchemy?"
+        );
+        assert_eq!(
+            dependency_not_found("django", candidates).to_string(),
+            "a dependency could not be found: django"
+        );
+    }
+
+    #[test]
+    fn dependency_eq_canonical_name() {
+        assert_eq!(
+            Dependency::from_str("Zope_Interface>=6").unwrap(),
+            Dependency::from_str("zope.interface").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_conflicts_with() {
+        let conflicts = |a: &str, b: &str| {
+            Dependency::from_str(a)
+                .unwrap()
+                .conflicts_with(&Dependency::from_str(b).unwrap())
+        };
+
+        assert!(conflicts("foo>=2", "foo<2"));
+        assert!(conflicts("foo==1.4", "Foo>=2,<3"));
+        assert!(conflicts("foo~=1.4", "foo>=2"));
+        assert!(conflicts("foo==1.*", "foo>=2"));
+        assert!(conflicts("foo>=2,<=2", "foo!=2"));
+        assert!(!conflicts("foo>=2", "foo<=2"));
+        assert!(!conflicts("foo~=1.4", "foo<1.9"));
+        assert!(!conflicts("foo>=2", "bar<2"));
+        assert!(!conflicts("foo>=2", "foo"));
+        assert!(!conflicts(
+            "foo>=2; python_version >= '3.8'",
+            "foo<2; python_version < '3.8'"
+        ));
+    }
 }
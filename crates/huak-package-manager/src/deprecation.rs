@@ -0,0 +1,154 @@
+//! Renamed flags keep working for a release with a warning naming their replacement, and so do
+//! their keys in a command's `[tool.huak.<command>]` defaults.
+//!
+//! To rename a flag, add an alias for its old name to the flag and an entry to
+//! `DEPRECATED_FLAGS`.
+
+use huak_pyproject_toml::PyProjectToml;
+use std::ffi::OsString;
+
+/// A flag that was renamed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeprecatedFlag {
+    /// The subcommand the flag belongs to.
+    pub command: &'static str,
+    /// The flag's old long name.
+    pub old: &'static str,
+    /// The flag's new long name.
+    pub new: &'static str,
+    /// The release the old name is removed in.
+    pub removed_in: &'static str,
+}
+
+/// The renamed flags that still accept their old names.
+pub const DEPRECATED_FLAGS: &[DeprecatedFlag] = &[DeprecatedFlag {
+    command: "init",
+    old: "optional-dependencies",
+    new: "groups",
+    removed_in: "0.1.0",
+}];
+
+impl DeprecatedFlag {
+    /// Get the warning for passing the flag's old name.
+    #[must_use]
+    pub fn flag_warning(&self) -> String {
+        format!(
+            "`huak {} --{}` is deprecated and will be removed in {}; use `--{}` instead",
+            self.command, self.old, self.removed_in, self.new
+        )
+    }
+
+    /// Get the warning for configuring a default for the flag's old name.
+    #[must_use]
+    pub fn key_warning(&self) -> String {
+        format!(
+            "[tool.huak.{}] {} is deprecated and will be removed in {}; use {} instead",
+            self.command, self.old, self.removed_in, self.new
+        )
+    }
+}
+
+/// Get the deprecated flags passed to the `command`. Trailing arguments (after `--`) aren't
+/// huak's, so they're ignored.
+#[must_use]
+pub fn deprecated_flags(command: &str, args: &[OsString]) -> Vec<&'static DeprecatedFlag> {
+    let args = args
+        .iter()
+        .map(|it| it.to_string_lossy())
+        .take_while(|it| it != "--")
+        .collect::<Vec<_>>();
+
+    DEPRECATED_FLAGS
+        .iter()
+        .filter(|it| it.command == command)
+        .filter(|flag| {
+            let name = format!("--{}", flag.old);
+            args.iter()
+                .any(|it| *it == name || it.starts_with(&format!("{name}=")))
+        })
+        .collect()
+}
+
+/// Get the deprecated flag a `[tool.huak.<command>]` key is the old name of. Keys can be
+/// spelled with `-` or `_`.
+#[must_use]
+pub fn deprecated_key(command: &str, key: &str) -> Option<&'static DeprecatedFlag> {
+    DEPRECATED_FLAGS
+        .iter()
+        .find(|it| it.command == command && it.old == key.replace('_', "-"))
+}
+
+/// Get a warning for each deprecated key in the manifest's `[tool.huak.<command>]` tables.
+#[must_use]
+pub fn deprecated_keys(manifest: &PyProjectToml) -> Vec<String> {
+    let Some(huak) = manifest.huak_table() else {
+        return Vec::new();
+    };
+
+    huak.iter()
+        .filter_map(|(command, item)| Some((command, item.as_table_like()?)))
+        .flat_map(|(command, table)| {
+            table
+                .iter()
+                .filter_map(move |(key, _)| deprecated_key(command, key))
+        })
+        .map(DeprecatedFlag::key_warning)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_deprecated_flags() {
+        let args = |it: &str| it.split(' ').map(OsString::from).collect::<Vec<_>>();
+        let init = &DEPRECATED_FLAGS[0];
+
+        assert_eq!(
+            deprecated_flags("init", &args("huak init --optional-dependencies dev")),
+            [init]
+        );
+        assert_eq!(
+            deprecated_flags("init", &args("huak init --optional-dependencies=dev")),
+            [init]
+        );
+        assert!(deprecated_flags("init", &args("huak init --groups dev")).is_empty());
+        assert!(deprecated_flags("init", &args("huak init -- --optional-dependencies")).is_empty());
+        assert!(deprecated_flags("add", &args("huak add --optional-dependencies")).is_empty());
+        assert_eq!(
+            init.flag_warning(),
+            "`huak init --optional-dependencies` is deprecated and will be removed in 0.1.0; use `--groups` instead"
+        );
+    }
+
+    #[test]
+    fn test_deprecated_keys() {
+        let manifest = PyProjectToml::from_str(
+            r#"[project]
+name = "mock-project"
+
+[tool.huak]
+strict = true
+
+[tool.huak.init]
+optional_dependencies = ["dev"]
+
+[tool.huak.lint]
+optional-dependencies = true
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            deprecated_keys(&manifest),
+            ["[tool.huak.init] optional-dependencies is deprecated and will be removed in 0.1.0; use groups instead"]
+        );
+        assert_eq!(
+            deprecated_key("init", "optional_dependencies"),
+            Some(&DEPRECATED_FLAGS[0])
+        );
+        assert_eq!(deprecated_key("init", "groups"), None);
+    }
+}
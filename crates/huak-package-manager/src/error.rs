@@ -8,38 +8,128 @@ pub type HuakResult<T> = Result<T, Error>;
 //   These prompts feel more like application experience than library needs.
 #[derive(ThisError, Debug)]
 pub enum Error {
+    #[error("a distribution archive could not be found: {path}")]
+    ArchiveNotFound { path: PathBuf },
+    #[error("an artifact doesn't match its recorded checksum (rebuild it or remove it): {path}")]
+    ArtifactModified { path: PathBuf },
+    #[error("a changelog entry could not be found for version {version}")]
+    ChangelogEntryNotFound { version: String },
+    #[error("a changelog could not be found: {path}")]
+    ChangelogNotFound { path: PathBuf },
     #[error("a problem with argument parsing occurred: {0}")]
     ClapError(#[from] clap::Error),
-    #[error("a directory already exists: {0}")]
-    DirectoryExists(PathBuf),
+    #[error("a directory already exists: {path}")]
+    DirectoryExists { path: PathBuf },
+    #[error("a directory is not empty (pass --force to only add the files it's missing): {path}")]
+    DirectoryNotEmpty { path: PathBuf },
+    #[error("a file already exists: {path}")]
+    FileExists { path: PathBuf },
+    #[error("a directory is outside the workspace: {path}")]
+    DirectoryOutsideWorkspace { path: PathBuf },
+    #[error("the project failed {0} checks")]
+    ChecksFailed(usize),
+    #[error("a package can't be both --only-binary and --no-binary: {0}")]
+    ConflictingBinaryOptions(String),
+    #[error("a dependency conflicts with the project's other requirements (pass --allow-conflicts to add it anyway): {}", conflicts.join(", "))]
+    DependencyConflict { conflicts: Vec<String> },
+    #[error("a dependency could not be found: {name}{}{}", canonical_name.as_ref().map(|it| format!(" (canonical name {it})")).unwrap_or_default(), did_you_mean(suggestions))]
+    DependencyNotFound {
+        name: String,
+        /// The normalized name, when it differs from `name`.
+        canonical_name: Option<String>,
+        /// Similarly named dependencies, closest first.
+        suggestions: Vec<String>,
+    },
+    #[error("a dependency override could not be found (see `huak develop list`): {name}")]
+    DependencyOverrideNotFound { name: String },
+    #[error("the project version is dynamic and can't be set")]
+    DynamicProjectVersion,
+    #[error("a dynamic project version could not be resolved by the build backend: {0}")]
+    DynamicVersionUnresolved(String),
+    #[error("an environment isn't configured in [tool.huak.envs]: {name}")]
+    EnvironmentNotConfigured { name: String },
     #[error("a problem with the environment occurred: {0}")]
     EnvVarError(#[from] std::env::VarError),
+    #[error("a python environment is externally managed (pass --break-system-packages to change it anyway): {path}")]
+    ExternallyManagedEnvironment { path: PathBuf },
     #[error("a problem with git occurred: {0}")]
     GitError(#[from] git2::Error),
+    #[error("git needs to know who you are to commit (set it with `git config --global user.name \"Your Name\"` and `git config --global user.email you@example.com`)")]
+    GitIdentityNotConfigured,
+    #[error("a git repository could not be found: {path}")]
+    GitRepositoryNotFound { path: PathBuf },
     #[error("a problem occurred with the glob package: {0}")]
     GlobError(#[from] glob::GlobError),
     #[error("a problem occurred with a glob pattern: {0}")]
     GlobPatternError(#[from] glob::PatternError),
-    #[error("a problem with huak configuration occurred: {0}")]
-    HuakConfigurationError(String),
+    #[error("a problem with huak configuration occurred: [{table}] {message}")]
+    HuakConfigurationError { table: String, message: String },
     #[error("a problem occurred resolving huak's home directory")]
     HuakHomeNotFound,
+    #[error("{origin} requires huak {required}, you have {current} (update it with `pip install --upgrade huak`)")]
+    HuakVersionTooOld {
+        origin: String,
+        required: String,
+        current: String,
+    },
+    #[error("the built artifacts are missing {0} of the project's files or directories (list files left out on purpose in [tool.huak.build] exclude)")]
+    IncompleteArtifacts(usize),
     #[error("a toolchain cannot be found")]
     ToolchainNotFound,
     #[error("{0}")] // See TODO note above.
     ToolchainError(#[from] huak_toolchain::Error),
-    #[error("a toolchain already exists: {0}")]
-    LocalToolchainExists(PathBuf),
+    #[error("a toolchain already exists: {path}")]
+    LocalToolchainExists { path: PathBuf },
+    #[error("a jupyter data directory could not be resolved (set JUPYTER_DATA_DIR)")]
+    JupyterDataDirNotFound,
+    #[error("a jupyter kernel could not be found: {name}")]
+    KernelNotFound { name: String },
     #[error("a problem with huak's internals occurred: {0}")]
     InternalError(String),
+    #[error("a distribution archive is invalid: {0}")]
+    InvalidArchive(String),
     #[error("a checksum is invalid: {0}")]
     InvalidChecksum(String),
+    #[error("a classifier is invalid: {0}")]
+    InvalidClassifier(String),
+    #[error("an environment variable value is invalid: {0}")]
+    InvalidEnvironmentValue(String),
+    #[error("a dependency override is invalid: {0}")]
+    InvalidDependencyOverride(String),
+    #[error("a date is invalid (expected YYYY-MM-DD or YYYY-MM-DDTHH:MM:SSZ): {0}")]
+    InvalidDate(String),
+    #[error("an entry point is invalid (not a Python identifier): {0}")]
+    InvalidEntryPoint(String),
+    #[error(
+        "a hash is invalid (expected sha256, sha384, or sha512 followed by a hex digest): {0}"
+    )]
+    InvalidHash(String),
+    #[error("a metadata value is invalid: {0}")]
+    InvalidMetadata(String),
+    #[error("a poetry dependency can't be read: {0}")]
+    InvalidPoetryDependency(String),
     #[error("a program is invalid: {0}")]
     InvalidProgram(String),
+    #[error("an output format is invalid: {0}")]
+    InvalidOutputFormat(String),
+    #[error("a project name is invalid: {0}")]
+    InvalidProjectName(String),
+    #[error("a list of lint rule codes is invalid: {0}")]
+    InvalidRuleCodes(String),
+    #[error("a report section is invalid: {0}")]
+    InvalidReportSection(String),
     #[error("a run command is invalid: {0}")]
     InvalidRunCommand(String),
-    #[error("a version number could not be parsed: {0}")]
-    InvalidVersionString(String),
+    #[error("a script's inline metadata is invalid: {0}")]
+    InvalidScriptMetadata(String),
+    #[error("a setup.py can't be converted: {0}")]
+    InvalidSetupPy(String),
+    #[error("a test shard is invalid: {0}")]
+    InvalidTestShard(String),
+    #[error("a url is invalid: {0}")]
+    InvalidUrl(String),
+    #[error("a version number could not be parsed: {version} ({reason})")]
+    InvalidVersionString { version: String, reason: String },
     #[error("a problem occurred with json deserialization: {0}")]
     JSONSerdeError(#[from] serde_json::Error),
     #[error("a problem with io occurred: {0}")]
@@ -48,34 +138,92 @@ pub enum Error {
     PEP440Error(#[from] pep440_rs::Pep440Error),
     #[error("a problem occurred with PEP508 parsing: {0}")]
     PEP508Error(#[from] pep508_rs::Pep508Error),
-    #[error("a manifest file already exists")]
+    #[error("a setup.py or setup.cfg could not be found in {path}")]
+    LegacySetupNotFound { path: PathBuf },
+    #[error("a lint baseline could not be found (pass --write-baseline to create it): {path}")]
+    LintBaselineNotFound { path: PathBuf },
+    #[error("a manifest file already exists (pass --force to overwrite it)")]
     ManifestFileFound,
     #[error("a manifest file could not be found")]
     ManifestFileNotFound,
-    #[error("a manifest file is not supported: {0}")]
-    ManifestFileNotSupported(PathBuf),
+    #[error("a manifest file is not supported: {path}")]
+    ManifestFileNotSupported { path: PathBuf },
+    #[error("the tools to build the project's native extensions are missing:\n{0}")]
+    NativeBuildToolsMissing(String),
+    #[error("the project has {0} lint violations that aren't in the baseline")]
+    NewLintViolations(usize),
+    #[error("an optional dependency group could not be found: {group}{}{}", requested_from(origin.as_deref(), available), did_you_mean(suggestions))]
+    OptionalDependencyGroupNotFound {
+        group: String,
+        /// Where the group was requested, if it wasn't on the command line.
+        origin: Option<String>,
+        /// The project's groups, listed alongside `origin`.
+        available: Vec<String>,
+        /// Similarly named groups, closest first.
+        suggestions: Vec<String>,
+    },
+    #[error("the package index could not be reached: {0}")]
+    PackageIndexUnavailable(String),
     #[error("a package version could not be found")]
     PackageVersionNotFound,
+    #[error("warnings are errors in strict mode:\n{0}")]
+    PromotedWarnings(String),
+    #[error("the project's dependencies are managed by poetry (run `huak migrate` to move them to [project])")]
+    PoetryManagedDependencies,
     #[error("a project already exists")]
     ProjectFound,
+    #[error(
+        "a profile could not be found in [tool.huak.profile]: {name} ({})",
+        available_profiles(available)
+    )]
+    ProfileNotFound {
+        name: String,
+        /// The profiles that are configured.
+        available: Vec<String>,
+    },
     #[error("{0}")]
     PyProjectTomlError(#[from] huak_pyproject_toml::Error),
     #[error("{0}")]
     PythonManagerError(#[from] huak_python_manager::Error),
-    #[error("a python module could not be found: {0}")]
-    PythonModuleNotFound(String),
+    #[error("a python module could not be found: {module}{}", hint.as_ref().map(|it| format!(" ({it})")).unwrap_or_default())]
+    PythonModuleNotFound {
+        module: String,
+        /// What the module is needed for and how to install it.
+        hint: Option<String>,
+    },
     #[error("a python interpreter could not be found")]
     PythonNotFound,
     #[error("a problem occurred attempting to install python: {0}")]
     PythonInstallError(String),
-    #[error("a python release could not be found: {0}")]
-    PythonReleaseNotFound(String),
+    #[error("a python release could not be found: {version}")]
+    PythonReleaseNotFound { version: String },
+    #[error("a python interpreter could not be found for version {version}")]
+    PythonVersionNotFound { version: String },
+    #[error(
+        "a program could not be found in the virtual environment or PATH: {program}{}",
+        did_you_mean(suggestions)
+    )]
+    ProgramNotFound {
+        program: String,
+        /// Similarly named console scripts and executables, closest first.
+        suggestions: Vec<String>,
+    },
     #[error("a python environment could not be found")]
     PythonEnvironmentNotFound,
+    #[error("huak is running as root, which leaves root-owned files in the project (pass --allow-root or set HUAK_ALLOW_ROOT=1 to run anyway)")]
+    RunningAsRoot,
+    #[error("the project can't be renamed: {0}")]
+    RenameConflict(String),
     #[error("a regex error occurred: {0}")]
     RegexError(#[from] regex::Error),
+    #[error("a problem with the http client occurred: {0}")]
+    ReqwestError(#[from] reqwest::Error),
     #[error("a subprocess exited with {0}")]
     SubprocessFailure(sys::SubprocessError),
+    #[error("a subprocess timed out after {seconds}s: {command}")]
+    SubprocessTimeout { command: String, seconds: u64 },
+    #[error("a test path could not be found: {path}")]
+    TestPathNotFound { path: PathBuf },
     #[error("a problem with toml deserialization occurred: {0}")]
     TOMLDeserializationError(#[from] toml::de::Error),
     #[error("a problem with toml serialization occurred {0}")]
@@ -86,10 +234,238 @@ pub enum Error {
     TOMLEditDeserializationError(#[from] toml_edit::de::Error),
     #[error("a problem with toml serialization occurred {0}")]
     TOMLEditSerializationError(#[from] toml_edit::ser::Error),
+    #[error("an environment variable is undefined: {0}")]
+    UndefinedEnvironmentVariable(String),
+    #[error("the working tree has uncommitted changes a clean build would leave out (pass --allow-dirty to build anyway): {0}")]
+    UncommittedBuild(String),
+    #[error("files with uncommitted changes would be fixed (pass --allow-dirty to fix them anyway): {0}")]
+    UncommittedChanges(String),
+    #[error(
+        "the working tree has uncommitted changes (pass --allow-dirty to publish anyway): {0}"
+    )]
+    UncommittedRelease(String),
+    #[error("a jupyter kernel wasn't registered by huak: {name}")]
+    UnmanagedKernel { name: String },
+    #[error("the changelog's Unreleased section still has changes (move them to the entry for version {version})")]
+    UnreleasedChangelogEntries { version: String },
+    #[error("a dependency must be pinned to an exact version to use hashes: {requirement}")]
+    UnpinnedHashedDependency { requirement: String },
     #[error("a feature is unimplemented: {0}")]
     Unimplemented(String),
+    #[error("a distribution archive is unsupported (expected .whl, .tar.gz, or .zip): {path}")]
+    UnsupportedArchive { path: PathBuf },
+    #[error("HEAD isn't tagged with the project's version (pass --allow-untagged to publish anyway): {0}")]
+    UntaggedRelease(String),
+    #[error("a shell is unsupported for this feature: {0}")]
+    UnsupportedShell(String),
     #[error("a python environment is unsupported for this feature")]
-    UnsupportedPythonEnvironment(PathBuf),
+    UnsupportedPythonEnvironment { path: PathBuf },
     #[error("a problem with utf-8 parsing occurred: {0}")]
     Utf8Error(#[from] std::str::Utf8Error),
+    #[error("a vendor directory could not be found (run `huak vendor sync`): {path}")]
+    VendorDirNotFound { path: PathBuf },
+    #[error("a vendor directory is out of date with the project's dependencies ({0} problems)")]
+    VendorDirOutOfDate(usize),
+    #[error("a version is already published to {repository} (pass --skip-existing to only upload new files): {package} {version}")]
+    VersionAlreadyPublished {
+        package: String,
+        version: String,
+        repository: String,
+    },
+    #[error("the project's version didn't pass its checks: {0}")]
+    VersionCheckFailed(String),
+}
+
+impl Error {
+    /// Get the exit code to exit with because of the `Error`.
+    ///
+    /// Subprocess failures keep the subprocess's exit code so tools like `pytest` can be
+    /// branched on. Timeouts exit with 124 like coreutils' `timeout`. Anything else is 1.
+    #[must_use]
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::SubprocessFailure(it) => it.exit_code(),
+            Error::SubprocessTimeout { .. } => 124,
+            _ => 1,
+        }
+    }
+
+    /// Get a stable code identifying the kind of `Error` for programs handling it.
+    ///
+    /// Codes are kebab-case and don't change when a variant or its message is reworded.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::ArchiveNotFound { .. } => "archive-not-found",
+            Error::ArtifactModified { .. } => "artifact-modified",
+            Error::ChangelogEntryNotFound { .. } => "changelog-entry-not-found",
+            Error::ChangelogNotFound { .. } => "changelog-not-found",
+            Error::ClapError(..) => "invalid-arguments",
+            Error::DirectoryExists { .. } => "directory-exists",
+            Error::DirectoryNotEmpty { .. } => "directory-not-empty",
+            Error::FileExists { .. } => "file-exists",
+            Error::DirectoryOutsideWorkspace { .. } => "directory-outside-workspace",
+            Error::ChecksFailed(..) => "checks-failed",
+            Error::ConflictingBinaryOptions(..) => "conflicting-binary-options",
+            Error::DependencyConflict { .. } => "dependency-conflict",
+            Error::DependencyNotFound { .. } => "dependency-not-found",
+            Error::DependencyOverrideNotFound { .. } => "dependency-override-not-found",
+            Error::DynamicProjectVersion => "dynamic-project-version",
+            Error::DynamicVersionUnresolved(..) => "dynamic-version-unresolved",
+            Error::EnvironmentNotConfigured { .. } => "environment-not-configured",
+            Error::EnvVarError(..) => "env-var-error",
+            Error::ExternallyManagedEnvironment { .. } => "externally-managed-environment",
+            Error::GitError(..) => "git-error",
+            Error::GitIdentityNotConfigured => "git-identity-not-configured",
+            Error::GitRepositoryNotFound { .. } => "git-repository-not-found",
+            Error::GlobError(..) => "glob-error",
+            Error::GlobPatternError(..) => "glob-pattern-error",
+            Error::HuakConfigurationError { .. } => "invalid-configuration",
+            Error::HuakHomeNotFound => "huak-home-not-found",
+            Error::HuakVersionTooOld { .. } => "huak-version-too-old",
+            Error::IncompleteArtifacts(..) => "incomplete-artifacts",
+            Error::ToolchainNotFound => "toolchain-not-found",
+            Error::ToolchainError(..) => "toolchain-error",
+            Error::LocalToolchainExists { .. } => "local-toolchain-exists",
+            Error::JupyterDataDirNotFound => "jupyter-data-dir-not-found",
+            Error::KernelNotFound { .. } => "kernel-not-found",
+            Error::InternalError(..) => "internal-error",
+            Error::InvalidArchive(..) => "invalid-archive",
+            Error::InvalidChecksum(..) => "invalid-checksum",
+            Error::InvalidClassifier(..) => "invalid-classifier",
+            Error::InvalidEnvironmentValue(..) => "invalid-environment-value",
+            Error::InvalidDependencyOverride(..) => "invalid-dependency-override",
+            Error::InvalidDate(..) => "invalid-date",
+            Error::InvalidEntryPoint(..) => "invalid-entry-point",
+            Error::InvalidHash(..) => "invalid-hash",
+            Error::InvalidMetadata(..) => "invalid-metadata",
+            Error::InvalidPoetryDependency(..) => "invalid-poetry-dependency",
+            Error::InvalidProgram(..) => "invalid-program",
+            Error::InvalidSetupPy(..) => "invalid-setup-py",
+            Error::InvalidOutputFormat(..) => "invalid-output-format",
+            Error::InvalidProjectName(..) => "invalid-project-name",
+            Error::InvalidRuleCodes(..) => "invalid-rule-codes",
+            Error::InvalidReportSection(..) => "invalid-report-section",
+            Error::InvalidRunCommand(..) => "invalid-run-command",
+            Error::InvalidScriptMetadata(..) => "invalid-script-metadata",
+            Error::InvalidTestShard(..) => "invalid-test-shard",
+            Error::InvalidUrl(..) => "invalid-url",
+            Error::InvalidVersionString { .. } => "invalid-version-string",
+            Error::JSONSerdeError(..) => "json-error",
+            Error::IOError(..) => "io-error",
+            Error::PEP440Error(..) => "pep440-error",
+            Error::PEP508Error(..) => "pep508-error",
+            Error::LegacySetupNotFound { .. } => "legacy-setup-not-found",
+            Error::LintBaselineNotFound { .. } => "lint-baseline-not-found",
+            Error::ManifestFileFound => "manifest-file-found",
+            Error::ManifestFileNotFound => "manifest-file-not-found",
+            Error::ManifestFileNotSupported { .. } => "manifest-file-not-supported",
+            Error::NativeBuildToolsMissing(..) => "native-build-tools-missing",
+            Error::NewLintViolations(..) => "new-lint-violations",
+            Error::OptionalDependencyGroupNotFound { .. } => "optional-dependency-group-not-found",
+            Error::PackageIndexUnavailable(..) => "package-index-unavailable",
+            Error::PackageVersionNotFound => "package-version-not-found",
+            Error::PromotedWarnings(..) => "promoted-warnings",
+            Error::PoetryManagedDependencies => "poetry-managed-dependencies",
+            Error::ProjectFound => "project-found",
+            Error::ProfileNotFound { .. } => "profile-not-found",
+            Error::PyProjectTomlError(..) => "pyproject-toml-error",
+            Error::PythonManagerError(..) => "python-manager-error",
+            Error::PythonModuleNotFound { .. } => "python-module-not-found",
+            Error::PythonNotFound => "python-not-found",
+            Error::PythonInstallError(..) => "python-install-error",
+            Error::PythonReleaseNotFound { .. } => "python-release-not-found",
+            Error::PythonVersionNotFound { .. } => "python-version-not-found",
+            Error::ProgramNotFound { .. } => "program-not-found",
+            Error::PythonEnvironmentNotFound => "python-environment-not-found",
+            Error::RunningAsRoot => "running-as-root",
+            Error::RenameConflict(..) => "rename-conflict",
+            Error::RegexError(..) => "regex-error",
+            Error::ReqwestError(..) => "http-error",
+            Error::SubprocessFailure(..) => "subprocess-failure",
+            Error::SubprocessTimeout { .. } => "subprocess-timeout",
+            Error::TestPathNotFound { .. } => "test-path-not-found",
+            Error::TOMLDeserializationError(..) => "toml-deserialization-error",
+            Error::TOMLSerializationError(..) => "toml-serialization-error",
+            Error::TOMLEditError(..) => "toml-edit-error",
+            Error::TOMLEditDeserializationError(..) => "toml-edit-deserialization-error",
+            Error::TOMLEditSerializationError(..) => "toml-edit-serialization-error",
+            Error::UndefinedEnvironmentVariable(..) => "undefined-environment-variable",
+            Error::UncommittedBuild(..) => "uncommitted-build",
+            Error::UncommittedChanges(..) => "uncommitted-changes",
+            Error::UncommittedRelease(..) => "uncommitted-release",
+            Error::UnmanagedKernel { .. } => "unmanaged-kernel",
+            Error::UnreleasedChangelogEntries { .. } => "unreleased-changelog-entries",
+            Error::UnpinnedHashedDependency { .. } => "unpinned-hashed-dependency",
+            Error::Unimplemented(..) => "unimplemented",
+            Error::UnsupportedArchive { .. } => "unsupported-archive",
+            Error::UntaggedRelease(..) => "untagged-release",
+            Error::UnsupportedShell(..) => "unsupported-shell",
+            Error::UnsupportedPythonEnvironment { .. } => "unsupported-python-environment",
+            Error::Utf8Error(..) => "utf8-error",
+            Error::VendorDirNotFound { .. } => "vendor-dir-not-found",
+            Error::VendorDirOutOfDate(..) => "vendor-dir-out-of-date",
+            Error::VersionAlreadyPublished { .. } => "version-already-published",
+            Error::VersionCheckFailed(..) => "version-check-failed",
+        }
+    }
+}
+
+/// Display `suggestions` as a "did you mean" hint, or nothing if there are none.
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!("; did you mean {}?", suggestions.join(" or "))
+    }
+}
+
+/// Display where an optional dependency group was requested and the groups that are available.
+fn requested_from(origin: Option<&str>, available: &[String]) -> String {
+    origin
+        .map(|it| format!(" (from {it}; available: {})", available.join(", ")))
+        .unwrap_or_default()
+}
+
+/// Display the profiles that are `available` to choose from.
+fn available_profiles(available: &[String]) -> String {
+    if available.is_empty() {
+        "none are configured".to_string()
+    } else {
+        format!("available profiles: {}", available.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_code() {
+        assert_eq!(Error::PythonNotFound.code(), "python-not-found");
+        assert_eq!(
+            Error::SubprocessTimeout {
+                command: "pytest".to_string(),
+                seconds: 1
+            }
+            .code(),
+            "subprocess-timeout"
+        );
+        assert_eq!(
+            Error::IOError(io::Error::other("denied")).code(),
+            "io-error"
+        );
+    }
+
+    #[test]
+    fn test_source() {
+        let e = Error::IOError(io::Error::new(io::ErrorKind::NotFound, "missing"));
+
+        assert_eq!(
+            e.source().map(ToString::to_string).as_deref(),
+            Some("missing")
+        );
+        assert!(Error::PythonNotFound.source().is_none());
+    }
 }
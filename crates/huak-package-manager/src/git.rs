@@ -1,7 +1,7 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::{error::HuakResult, Error};
-use git2::Repository;
+use git2::{Config, ObjectType, Oid, Repository, Signature, Status, StatusOptions, Tree};
 
 /// From <https://github.com/github/gitignore/blob/main/Python.gitignore>.
 const DEFAULT_PYTHON_GITIGNORE: &str = r"
@@ -88,6 +88,217 @@ pub fn init<T: Into<PathBuf>>(path: T) -> HuakResult<Repository> {
     Repository::init(path.into()).map_err(Error::GitError)
 }
 
+/// Get the `paths` with changes that aren't committed, including untracked files. Nothing is
+/// reported for paths outside a git repository.
+pub(crate) fn uncommitted_paths(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let Some(dir) = paths.first().and_then(|it| it.parent()) else {
+        return Vec::new();
+    };
+    let Ok(repo) = Repository::discover(dir) else {
+        return Vec::new();
+    };
+    let Some(workdir) = repo.workdir().and_then(|it| it.canonicalize().ok()) else {
+        return Vec::new();
+    };
+
+    paths
+        .iter()
+        .filter(|path| {
+            let Some(relative) = path
+                .canonicalize()
+                .ok()
+                .and_then(|it| it.strip_prefix(&workdir).ok().map(Path::to_path_buf))
+            else {
+                return false;
+            };
+
+            repo.status_file(&relative)
+                .is_ok_and(|it| !it.is_empty() && !it.contains(Status::IGNORED))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Get the paths in the repository containing `dir` with changes that aren't committed,
+/// including untracked files. `None` is returned outside a git repository.
+pub(crate) fn working_tree_changes(dir: &Path) -> Option<Vec<PathBuf>> {
+    let repo = Repository::discover(dir).ok()?;
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).include_ignored(false);
+    let statuses = repo.statuses(Some(&mut options)).ok()?;
+
+    Some(
+        statuses
+            .iter()
+            .filter(|it| !it.status().is_empty() && !it.status().contains(Status::IGNORED))
+            .filter_map(|it| it.path().map(PathBuf::from))
+            .collect(),
+    )
+}
+
+/// Get the absolute paths of the files tracked in the repository containing `dir` (what
+/// `git ls-files` lists). `None` is returned outside a git repository.
+pub(crate) fn tracked_files(dir: &Path) -> Option<Vec<PathBuf>> {
+    let repo = Repository::discover(dir).ok()?;
+    let workdir = dunce::canonicalize(repo.workdir()?).ok()?;
+    let index = repo.index().ok()?;
+
+    Some(
+        index
+            .iter()
+            .filter_map(|it| {
+                std::str::from_utf8(&it.path)
+                    .ok()
+                    .map(|it| workdir.join(it))
+            })
+            .collect(),
+    )
+}
+
+/// Get the names of the tags pointing at HEAD in the repository containing `dir`. `None` is
+/// returned outside a git repository.
+pub(crate) fn head_tags(dir: &Path) -> Option<Vec<String>> {
+    let repo = Repository::discover(dir).ok()?;
+    let Some(head) = repo
+        .head()
+        .ok()
+        .and_then(|it| it.peel_to_commit().ok())
+        .map(|it| it.id())
+    else {
+        return Some(Vec::new());
+    };
+    let names = repo.tag_names(None).ok()?;
+
+    Some(
+        names
+            .iter()
+            .flatten()
+            .filter(|name| {
+                repo.revparse_single(&format!("refs/tags/{name}"))
+                    .and_then(|it| it.peel_to_commit())
+                    .is_ok_and(|it| it.id() == head)
+            })
+            .map(ToString::to_string)
+            .collect(),
+    )
+}
+
+/// Get the identity to commit as in a repository at `root` from its git configuration (falling
+/// back to the global configuration if there's no repository yet).
+pub(crate) fn signature(root: &Path) -> HuakResult<Signature<'static>> {
+    let config = match Repository::open(root) {
+        Ok(repo) => repo.config(),
+        Err(_) => Config::open_default(),
+    }?;
+    let (Ok(name), Ok(email)) = (
+        config.get_string("user.name"),
+        config.get_string("user.email"),
+    ) else {
+        return Err(Error::GitIdentityNotConfigured);
+    };
+
+    Ok(Signature::now(&name, &email)?)
+}
+
+/// Stage the `paths` (relative to `root`) in the repository at `root` and commit them with the
+/// `message`. Returns the id of the new commit.
+pub(crate) fn commit_paths(
+    root: &Path,
+    paths: &[PathBuf],
+    message: &str,
+    signature: &Signature,
+) -> HuakResult<Oid> {
+    let repo = Repository::open(root)?;
+    let mut index = repo.index()?;
+    for it in paths {
+        index.add_path(it)?;
+    }
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let parent = repo.head().ok().and_then(|it| it.peel_to_commit().ok());
+    let parents = parent.iter().collect::<Vec<_>>();
+
+    Ok(repo.commit(Some("HEAD"), signature, signature, message, &tree, &parents)?)
+}
+
+/// Write the files committed at HEAD in the repository containing `dir` to `target`, like
+/// `git archive HEAD | tar -x -C <target>`. Returns the path of `dir` in the export.
+pub(crate) fn export_head(dir: &Path, target: &Path) -> HuakResult<PathBuf> {
+    let not_found = || Error::GitRepositoryNotFound {
+        path: dir.to_path_buf(),
+    };
+    let repo = Repository::discover(dir).map_err(|_| not_found())?;
+    let workdir = dunce::canonicalize(repo.workdir().ok_or_else(not_found)?)?;
+    let relative = dunce::canonicalize(dir)?
+        .strip_prefix(&workdir)
+        .map(Path::to_path_buf)
+        .map_err(|_| not_found())?;
+
+    export_tree(&repo, &repo.head()?.peel_to_tree()?, target)?;
+
+    Ok(target.join(relative))
+}
+
+fn export_tree(repo: &Repository, tree: &Tree, target: &Path) -> HuakResult<()> {
+    std::fs::create_dir_all(target)?;
+
+    for entry in tree {
+        let Some(name) = entry.name() else {
+            continue;
+        };
+        let path = target.join(name);
+
+        match entry.kind() {
+            Some(ObjectType::Tree) => export_tree(repo, &repo.find_tree(entry.id())?, &path)?,
+            Some(ObjectType::Blob) => {
+                write_blob(
+                    &path,
+                    repo.find_blob(entry.id())?.content(),
+                    entry.filemode(),
+                )?;
+            }
+            // Submodules are commits in other repositories and aren't exported.
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a blob's `content` to `path` as the file `mode` git recorded (a regular file, an
+/// executable, or a symlink).
+#[cfg(unix)]
+fn write_blob(path: &Path, content: &[u8], mode: i32) -> HuakResult<()> {
+    use std::{
+        ffi::OsStr,
+        os::unix::{
+            ffi::OsStrExt,
+            fs::{symlink, PermissionsExt},
+        },
+    };
+
+    if mode == 0o120_000 {
+        symlink(OsStr::from_bytes(content), path)?;
+        return Ok(());
+    }
+
+    std::fs::write(path, content)?;
+    if mode == 0o100_755 {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(())
+}
+
+/// Write a blob's `content` to `path`. Symlinks are written as files containing their target,
+/// the way git checks them out without symlink support.
+#[cfg(not(unix))]
+fn write_blob(path: &Path, content: &[u8], _mode: i32) -> HuakResult<()> {
+    std::fs::write(path, content)?;
+
+    Ok(())
+}
+
 #[must_use]
 pub fn default_python_gitignore() -> &'static str {
     DEFAULT_PYTHON_GITIGNORE
@@ -104,4 +315,132 @@ mod tests {
         init(dir.path()).unwrap();
         assert!(dir.path().join(".git").is_dir());
     }
+
+    #[test]
+    fn test_uncommitted_paths() {
+        let dir = tempdir().unwrap();
+        let repo = init(dir.path()).unwrap();
+        let committed = dir.path().join("committed.py");
+        let untracked = dir.path().join("untracked.py");
+        std::fs::write(&committed, "x = 1\n").unwrap();
+        std::fs::write(&untracked, "y = 2\n").unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("committed.py")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("huak", "huak@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        let paths = [committed.clone(), untracked.clone()];
+
+        assert_eq!(uncommitted_paths(&paths), [untracked]);
+
+        std::fs::write(&committed, "x = 2\n").unwrap();
+
+        assert_eq!(uncommitted_paths(&paths), paths);
+    }
+
+    #[test]
+    fn test_working_tree_changes_and_head_tags() {
+        let dir = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+
+        assert_eq!(working_tree_changes(outside.path()), None);
+        assert_eq!(head_tags(outside.path()), None);
+
+        let repo = init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "dist/\n").unwrap();
+
+        assert_eq!(head_tags(dir.path()), Some(Vec::new()));
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("pyproject.toml")).unwrap();
+        index.add_path(Path::new(".gitignore")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("huak", "huak@example.com").unwrap();
+        let commit = repo
+            .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        std::fs::create_dir_all(dir.path().join("dist")).unwrap();
+        std::fs::write(dir.path().join("dist").join("pkg-1.0.0.tar.gz"), "").unwrap();
+
+        assert_eq!(working_tree_changes(dir.path()), Some(Vec::new()));
+
+        std::fs::write(dir.path().join("new.py"), "").unwrap();
+
+        assert_eq!(
+            working_tree_changes(dir.path()),
+            Some(vec![PathBuf::from("new.py")])
+        );
+
+        repo.tag_lightweight("v1.0.0", &repo.find_object(commit, None).unwrap(), false)
+            .unwrap();
+
+        assert_eq!(head_tags(dir.path()), Some(vec!["v1.0.0".to_string()]));
+    }
+
+    #[test]
+    fn test_commit_paths() {
+        let dir = tempdir().unwrap();
+        let repo = init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+        let signature = Signature::now("huak", "huak@example.com").unwrap();
+
+        let id = commit_paths(
+            dir.path(),
+            &[PathBuf::from("pyproject.toml")],
+            "Initial commit",
+            &signature,
+        )
+        .unwrap();
+        let commit = repo.find_commit(id).unwrap();
+
+        assert_eq!(commit.message(), Some("Initial commit"));
+        assert_eq!(commit.parent_count(), 0);
+        assert!(commit.tree().unwrap().get_name("pyproject.toml").is_some());
+        assert!(commit.tree().unwrap().get_name("notes.txt").is_none());
+        assert_eq!(
+            working_tree_changes(dir.path()),
+            Some(vec![PathBuf::from("notes.txt")])
+        );
+    }
+
+    #[test]
+    fn test_export_head() {
+        let dir = tempdir().unwrap();
+        init(dir.path()).unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::write(project.join("pyproject.toml"), "").unwrap();
+        std::fs::write(project.join("src").join("mod.py"), "x = 1\n").unwrap();
+        let signature = Signature::now("huak", "huak@example.com").unwrap();
+        commit_paths(
+            dir.path(),
+            &[
+                PathBuf::from("project/pyproject.toml"),
+                PathBuf::from("project/src/mod.py"),
+            ],
+            "Initial commit",
+            &signature,
+        )
+        .unwrap();
+        // Changes that aren't committed are left out.
+        std::fs::write(project.join("src").join("mod.py"), "x = 2\n").unwrap();
+        std::fs::write(project.join("notes.txt"), "").unwrap();
+
+        let target = tempdir().unwrap();
+        let exported = export_head(&project, target.path()).unwrap();
+
+        assert_eq!(exported, target.path().join("project"));
+        assert!(exported.join("pyproject.toml").exists());
+        assert_eq!(
+            std::fs::read_to_string(exported.join("src").join("mod.py")).unwrap(),
+            "x = 1\n"
+        );
+        assert!(!exported.join("notes.txt").exists());
+    }
 }
@@ -0,0 +1,280 @@
+use crate::{
+    schema::{SchemaMarker, HISTORY_SCHEMA},
+    Config, Dependency, HuakResult, LocalManifest, PythonEnvironment,
+};
+use huak_pyproject_toml::{canonical_name, PyProjectToml};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A change to the project's dependencies made by a huak command, recorded as one line of the
+/// workspace's `.huak/history.jsonl`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    #[serde(flatten)]
+    pub(crate) schema: SchemaMarker,
+    /// When the change was made, as a UTC timestamp like `2024-05-01T12:00:00Z`.
+    pub timestamp: String,
+    /// The huak command that made the change (like `add`).
+    pub command: String,
+    /// The user that ran the command.
+    pub user: Option<String>,
+    pub changes: Vec<DependencyChange>,
+}
+
+/// A dependency that was added, removed, or respecified.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyChange {
+    pub name: String,
+    /// The optional dependency group the dependency belongs to.
+    pub group: Option<String>,
+    /// The dependency's specifier before the change, if it was listed.
+    pub before: Option<String>,
+    /// The dependency's specifier after the change, if it's still listed.
+    pub after: Option<String>,
+    /// The version installed after the change.
+    pub installed: Option<String>,
+}
+
+/// The dependency specifiers a manifest lists, keyed by optional dependency group (`None` for
+/// the project's dependencies) and canonical name.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct DependencySnapshot(BTreeMap<(Option<String>, String), String>);
+
+impl DependencySnapshot {
+    pub(crate) fn new(manifest: &PyProjectToml) -> DependencySnapshot {
+        let mut specifiers = BTreeMap::new();
+        let optional = manifest
+            .project_optional_dependencies()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(group, it)| (Some(group), it));
+
+        for (group, deps) in
+            std::iter::once((None, manifest.project_dependencies().unwrap_or_default()))
+                .chain(optional)
+        {
+            for it in deps {
+                if let Ok(dep) = Dependency::from_str(&it) {
+                    specifiers.insert((group.clone(), canonical_name(dep.name())), it);
+                }
+            }
+        }
+
+        DependencySnapshot(specifiers)
+    }
+
+    /// Get the changes from this snapshot to the `after` snapshot.
+    pub(crate) fn changes(&self, after: &DependencySnapshot) -> Vec<DependencyChange> {
+        self.0
+            .keys()
+            .chain(after.0.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|key| {
+                let before = self.0.get(key);
+                let it = after.0.get(key);
+
+                (before != it).then(|| DependencyChange {
+                    name: key.1.clone(),
+                    group: key.0.clone(),
+                    before: before.cloned(),
+                    after: it.cloned(),
+                    installed: None,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Get the path to a workspace's dependency history file.
+#[must_use]
+pub fn history_file_path(root: &Path) -> PathBuf {
+    root.join(".huak").join("history.jsonl")
+}
+
+/// Append the changes between the `before` snapshot and the `manifest` to the workspace's
+/// dependency history, with the versions installed in the `python_env`.
+///
+/// Recording is best-effort: a problem is printed as a warning and never fails the command
+/// that changed the dependencies. Nothing is recorded for dry runs.
+pub(crate) fn record_dependency_changes(
+    command: &str,
+    before: &DependencySnapshot,
+    manifest: &LocalManifest,
+    python_env: Option<&PythonEnvironment>,
+    config: &Config,
+) {
+    if config.dry_run {
+        return;
+    }
+
+    if let Err(e) = append_history_entry(command, before, manifest, python_env, config) {
+        config
+            .terminal()
+            .print_warning(format!("the dependency history couldn't be recorded: {e}"))
+            .ok();
+    }
+}
+
+fn append_history_entry(
+    command: &str,
+    before: &DependencySnapshot,
+    manifest: &LocalManifest,
+    python_env: Option<&PythonEnvironment>,
+    config: &Config,
+) -> HuakResult<()> {
+    let mut changes = before.changes(&DependencySnapshot::new(manifest.manifest_data()));
+
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(python_env) = python_env {
+        let installed = python_env
+            .installed_packages()?
+            .iter()
+            .map(|it| (canonical_name(it.name()), it.version().to_string()))
+            .collect::<HashMap<_, _>>();
+
+        for it in &mut changes {
+            it.installed = installed.get(&it.name).cloned();
+        }
+    }
+
+    let entry = HistoryEntry {
+        schema: HISTORY_SCHEMA.marker(),
+        timestamp: utc_timestamp(SystemTime::now()),
+        command: command.to_string(),
+        user: std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .ok(),
+        changes,
+    };
+    let path = history_file_path(config.workspace().root());
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+
+    Ok(())
+}
+
+/// Read the entries of the workspace's dependency history, each with the line it was read from.
+/// Lines that aren't entries are skipped.
+///
+/// Entries recorded with an older schema are read as they are, since the history is only ever
+/// appended to.
+pub fn read_history(config: &Config) -> HuakResult<Vec<(HistoryEntry, String)>> {
+    let path = history_file_path(config.workspace().root());
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(it) => it,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut entries = Vec::new();
+    for line in contents.lines() {
+        // The schema is checked first since a newer entry might not parse.
+        if let Ok(it) = serde_json::from_str::<SchemaMarker>(line) {
+            HISTORY_SCHEMA.check(&it, &path, config)?;
+        }
+        if let Ok(it) = serde_json::from_str::<HistoryEntry>(line) {
+            entries.push((it, line.to_string()));
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Format a `SystemTime` as a UTC timestamp like `2024-05-01T12:00:00Z`.
+pub(crate) fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |it| it.as_secs());
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+
+    // Convert days since the epoch to a civil date (see Howard Hinnant's `civil_from_days`).
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_dependency_snapshot_changes() {
+        let before = PyProjectToml::from_str(
+            r#"[project]
+name = "mock-project"
+dependencies = ["requests==2.31.0", "Click==8.1.7"]
+
+[project.optional-dependencies]
+dev = ["ruff==0.1.0"]
+"#,
+        )
+        .unwrap();
+        let after = PyProjectToml::from_str(
+            r#"[project]
+name = "mock-project"
+dependencies = ["requests==2.32.0", "rich"]
+
+[project.optional-dependencies]
+dev = ["ruff==0.1.0"]
+"#,
+        )
+        .unwrap();
+
+        let changes = DependencySnapshot::new(&before).changes(&DependencySnapshot::new(&after));
+
+        assert_eq!(
+            changes
+                .iter()
+                .map(|it| (it.name.as_str(), it.before.as_deref(), it.after.as_deref()))
+                .collect::<Vec<_>>(),
+            [
+                ("click", Some("Click==8.1.7"), None),
+                (
+                    "requests",
+                    Some("requests==2.31.0"),
+                    Some("requests==2.32.0")
+                ),
+                ("rich", None, Some("rich")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_utc_timestamp() {
+        let at = |secs| utc_timestamp(UNIX_EPOCH + Duration::from_secs(secs));
+
+        assert_eq!(at(0), "1970-01-01T00:00:00Z");
+        assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(at(1_700_000_000), "2023-11-14T22:13:20Z");
+    }
+}
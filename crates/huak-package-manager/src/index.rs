@@ -0,0 +1,536 @@
+use crate::{Config, Error, HuakResult};
+use huak_pyproject_toml::canonical_name;
+use pep440_rs::Version;
+use reqwest::{
+    blocking::Client,
+    header::{HeaderMap, CACHE_CONTROL, ETAG, IF_NONE_MATCH},
+    Certificate, Proxy, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The package index's JSON API used unless `[tool.huak.network] index-url` is set.
+pub const DEFAULT_INDEX_URL: &str = "https://pypi.org/pypi";
+/// How long a response without a `max-age` is reused before it's revalidated.
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+/// The most requests made to the index at once when asking about many packages.
+const MAX_CONCURRENT_REQUESTS: usize = 8;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Settings for reaching the package index.
+///
+/// ```toml
+/// [tool.huak.network]
+/// index-url = "https://pypi.example.com/pypi"
+/// proxy = "http://proxy.example.com:3128"
+/// cert = "certs/ca.pem"
+/// ```
+///
+/// Without a `proxy` the `HTTPS_PROXY`, `HTTP_PROXY`, and `NO_PROXY` environment variables are
+/// used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkSettings {
+    /// The base URL of the index's JSON API (`<index-url>/<name>/json`).
+    pub index_url: String,
+    pub proxy: Option<String>,
+    /// A PEM file with extra certificates to trust, like a corporate CA.
+    pub cert: Option<PathBuf>,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        NetworkSettings {
+            index_url: DEFAULT_INDEX_URL.to_string(),
+            proxy: None,
+            cert: None,
+        }
+    }
+}
+
+/// A project's metadata from the package index.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProjectMetadata {
+    /// The versions with files that haven't been yanked, oldest first.
+    pub versions: Vec<Version>,
+    pub project_urls: BTreeMap<String, String>,
+}
+
+impl ProjectMetadata {
+    /// Get the newest final release, or the newest pre-release if there are only pre-releases.
+    #[must_use]
+    pub fn latest_version(&self) -> Option<&Version> {
+        self.versions
+            .iter()
+            .rev()
+            .find(|it| !it.any_prerelease())
+            .or_else(|| self.versions.last())
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonProject {
+    info: JsonInfo,
+    #[serde(default)]
+    releases: HashMap<String, Vec<JsonFile>>,
+}
+
+#[derive(Deserialize)]
+struct JsonInfo {
+    project_urls: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Deserialize)]
+struct JsonFile {
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// A response kept in the on-disk cache.
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+    etag: Option<String>,
+    /// When the response should be revalidated, in seconds since the Unix epoch.
+    expires: u64,
+    body: String,
+}
+
+/// The result of fetching a project, shared by the requests waiting on it.
+type ProjectResult = Result<Option<Arc<ProjectMetadata>>, String>;
+
+/// A client for the package index's JSON API, shared by the features that need package
+/// metadata.
+///
+/// Connections are reused, responses are cached on disk until their `max-age` passes (and then
+/// revalidated with their `ETag`), and each project is only requested once per client, even when
+/// several threads ask for it at the same time.
+pub struct IndexClient {
+    http: Client,
+    index_url: String,
+    cache_dir: Option<PathBuf>,
+    projects: Mutex<HashMap<String, Arc<OnceLock<ProjectResult>>>>,
+}
+
+impl IndexClient {
+    /// Create a client with the `settings`, caching responses in the `cache_dir` if there is one.
+    pub fn new(settings: &NetworkSettings, cache_dir: Option<PathBuf>) -> HuakResult<IndexClient> {
+        let mut builder = Client::builder()
+            .user_agent(concat!("huak/", env!("CARGO_PKG_VERSION")))
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT);
+
+        if let Some(it) = settings.proxy.as_ref() {
+            builder = builder.proxy(Proxy::all(it)?);
+        }
+
+        if let Some(it) = settings.cert.as_ref() {
+            builder = builder.add_root_certificate(Certificate::from_pem(&std::fs::read(it)?)?);
+        }
+
+        Ok(IndexClient {
+            http: builder.build()?,
+            index_url: settings.index_url.trim_end_matches('/').to_string(),
+            cache_dir,
+            projects: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Create a client with the workspace's `NetworkSettings`, caching responses in Huak's cache.
+    pub fn from_config(config: &Config) -> HuakResult<IndexClient> {
+        IndexClient::with_settings(&config.workspace().network_settings(), config)
+    }
+
+    /// Create a client with the `settings`, caching responses in Huak's cache.
+    pub fn with_settings(settings: &NetworkSettings, config: &Config) -> HuakResult<IndexClient> {
+        IndexClient::new(
+            settings,
+            config.home.as_ref().map(|it| it.join("cache").join("http")),
+        )
+    }
+
+    /// Get a project's metadata. Projects the index doesn't have are `None`.
+    pub fn project(&self, name: &str) -> HuakResult<Option<Arc<ProjectMetadata>>> {
+        let name = canonical_name(name);
+        let cell = self
+            .projects
+            .lock()
+            .map_err(|e| Error::InternalError(e.to_string()))?
+            .entry(name.clone())
+            .or_default()
+            .clone();
+
+        cell.get_or_init(|| self.fetch_project(&name))
+            .clone()
+            .map_err(Error::PackageIndexUnavailable)
+    }
+
+    /// Get a project's versions, oldest first.
+    pub fn versions(&self, name: &str) -> HuakResult<Vec<Version>> {
+        Ok(self
+            .project(name)?
+            .map(|it| it.versions.clone())
+            .unwrap_or_default())
+    }
+
+    /// Get a project's latest version (see `ProjectMetadata::latest_version`).
+    pub fn latest_version(&self, name: &str) -> HuakResult<Option<Version>> {
+        Ok(self
+            .project(name)?
+            .and_then(|it| it.latest_version().cloned()))
+    }
+
+    /// Get a project's URLs (like its homepage and source) by their labels.
+    pub fn project_urls(&self, name: &str) -> HuakResult<BTreeMap<String, String>> {
+        Ok(self
+            .project(name)?
+            .map(|it| it.project_urls.clone())
+            .unwrap_or_default())
+    }
+
+    /// Get the latest version of each project the index has, by PEP 503 normalized name.
+    /// Projects are requested a few at a time and each is only requested once.
+    pub fn latest_versions<T: AsRef<str>>(
+        &self,
+        names: &[T],
+    ) -> HuakResult<HashMap<String, Version>> {
+        let mut names = names
+            .iter()
+            .map(|it| canonical_name(it.as_ref()))
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::new());
+
+        std::thread::scope(|scope| {
+            for _ in 0..MAX_CONCURRENT_REQUESTS.min(names.len()) {
+                scope.spawn(|| {
+                    while let Some(name) = names.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let result = self.latest_version(name);
+                        if let Ok(mut it) = results.lock() {
+                            it.push((name.clone(), result));
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut latest = HashMap::new();
+        for (name, result) in results
+            .into_inner()
+            .map_err(|e| Error::InternalError(e.to_string()))?
+        {
+            if let Some(version) = result? {
+                latest.insert(name, version);
+            }
+        }
+
+        Ok(latest)
+    }
+
+    fn fetch_project(&self, name: &str) -> ProjectResult {
+        let url = format!("{}/{name}/json", self.index_url);
+        let Some(body) = self.get(&url)? else {
+            return Ok(None);
+        };
+        let project: JsonProject =
+            serde_json::from_str(&body).map_err(|e| format!("{url}: {e}"))?;
+
+        let mut versions = project
+            .releases
+            .iter()
+            .filter(|(_, files)| files.iter().any(|it| !it.yanked))
+            .filter_map(|(version, _)| Version::from_str(version).ok())
+            .collect::<Vec<_>>();
+        versions.sort();
+
+        Ok(Some(Arc::new(ProjectMetadata {
+            versions,
+            project_urls: project.info.project_urls.unwrap_or_default(),
+        })))
+    }
+
+    /// Get the body of a `url`, from the cache while it's fresh. Missing resources are `None`.
+    fn get(&self, url: &str) -> Result<Option<String>, String> {
+        let path = self
+            .cache_dir
+            .as_ref()
+            .map(|it| it.join(format!("{}.json", hex::encode(Sha256::digest(url)))));
+        let cached = path
+            .as_ref()
+            .and_then(|it| std::fs::read_to_string(it).ok())
+            .and_then(|it| serde_json::from_str::<CachedResponse>(&it).ok());
+
+        if let Some(it) = cached.as_ref().filter(|it| unix_now() < it.expires) {
+            return Ok(Some(it.body.clone()));
+        }
+
+        let mut request = self.http.get(url);
+        if let Some(etag) = cached.as_ref().and_then(|it| it.etag.as_ref()) {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        let response = request.send().map_err(|e| e.to_string())?;
+        let status = response.status();
+        let headers = response.headers().clone();
+
+        let (etag, body) = match (status, cached) {
+            (StatusCode::NOT_FOUND, _) => return Ok(None),
+            (StatusCode::NOT_MODIFIED, Some(it)) => (it.etag, it.body),
+            (it, _) if it.is_success() => (
+                headers
+                    .get(ETAG)
+                    .and_then(|it| it.to_str().ok())
+                    .map(ToString::to_string),
+                response.text().map_err(|e| e.to_string())?,
+            ),
+            (it, _) => return Err(format!("{url}: {it}")),
+        };
+
+        if let (Some(path), Some(max_age)) = (path, max_age(&headers)) {
+            let cached = CachedResponse {
+                etag,
+                expires: unix_now() + max_age.as_secs(),
+                body: body.clone(),
+            };
+            // The cache is only an optimization, so failing to write it isn't an error.
+            if let Ok(it) = serde_json::to_string(&cached) {
+                let _ = path
+                    .parent()
+                    .map(std::fs::create_dir_all)
+                    .transpose()
+                    .and_then(|_| std::fs::write(&path, it));
+            }
+        }
+
+        Ok(Some(body))
+    }
+}
+
+/// Get how long a response can be reused from its `Cache-Control` header. Responses that can't be
+/// stored are `None`, and `no-cache` ones are revalidated every time.
+fn max_age(headers: &HeaderMap) -> Option<Duration> {
+    let Some(value) = headers.get(CACHE_CONTROL).and_then(|it| it.to_str().ok()) else {
+        return Some(DEFAULT_MAX_AGE);
+    };
+    let directives = value
+        .split(',')
+        .map(|it| it.trim().to_ascii_lowercase())
+        .collect::<Vec<_>>();
+
+    if directives.iter().any(|it| it == "no-store") {
+        return None;
+    }
+
+    if directives.iter().any(|it| it == "no-cache") {
+        return Some(Duration::ZERO);
+    }
+
+    Some(
+        directives
+            .iter()
+            .find_map(|it| it.strip_prefix("max-age=")?.parse().ok())
+            .map_or(DEFAULT_MAX_AGE, Duration::from_secs),
+    )
+}
+
+/// Get the JSON API for a pip-style (PEP 503) `index_url`, if the index is the
+/// Python Package Index.
+#[must_use]
+pub fn json_api_url(index_url: &str) -> Option<String> {
+    let url = url::Url::parse(index_url).ok()?;
+    if url.path().trim_end_matches('/') != "/simple" {
+        return None;
+    }
+
+    match url.host_str()? {
+        "pypi.org" | "pypi.python.org" => Some(DEFAULT_INDEX_URL.to_string()),
+        "test.pypi.org" => Some("https://test.pypi.org/pypi".to_string()),
+        _ => None,
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|it| it.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+    };
+    use tempfile::tempdir;
+
+    const PROJECT: &str = r#"{
+        "info": {"project_urls": {"Source": "https://github.com/psf/requests"}},
+        "releases": {
+            "2.30.0": [{"yanked": false}],
+            "2.31.0": [{"yanked": false}],
+            "2.32.0": [{"yanked": true}],
+            "3.0.0rc1": [{"yanked": false}],
+            "3.0.0": []
+        }
+    }"#;
+
+    /// Serve the `PROJECT` for `/requests/json` with an `ETag` and `cache_control`, answering
+    /// revalidations with 304 and anything else with 404. Returns the base URL and the number of
+    /// requests served.
+    fn serve(cache_control: &'static str) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let served = requests.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+                let path = lines.next().unwrap().unwrap();
+                let mut revalidating = false;
+                for line in lines.map(Result::unwrap).take_while(|it| !it.is_empty()) {
+                    revalidating |= line.eq_ignore_ascii_case("if-none-match: \"v1\"");
+                }
+                served.fetch_add(1, Ordering::SeqCst);
+
+                let response = if !path.starts_with("GET /requests/json ") {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                } else if revalidating {
+                    format!("HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nCache-Control: {cache_control}\r\nConnection: close\r\n\r\n")
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nCache-Control: {cache_control}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{PROJECT}",
+                        PROJECT.len()
+                    )
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (url, requests)
+    }
+
+    fn client(url: &str, cache_dir: Option<PathBuf>) -> IndexClient {
+        let settings = NetworkSettings {
+            index_url: url.to_string(),
+            ..Default::default()
+        };
+        IndexClient::new(&settings, cache_dir).unwrap()
+    }
+
+    #[test]
+    fn test_project_metadata() {
+        let (url, requests) = serve("max-age=600");
+        let client = client(&url, None);
+
+        assert_eq!(
+            client
+                .versions("Requests")
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["2.30.0", "2.31.0", "3.0.0rc1"]
+        );
+        assert_eq!(
+            client.latest_version("requests").unwrap(),
+            Some(Version::from_str("2.31.0").unwrap())
+        );
+        assert_eq!(
+            client.project_urls("requests").unwrap().get("Source"),
+            Some(&"https://github.com/psf/requests".to_string())
+        );
+        assert_eq!(client.latest_version("missing").unwrap(), None);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_latest_versions_coalesces_requests() {
+        let (url, requests) = serve("max-age=600");
+        let client = client(&url, None);
+        let names = ["requests", "Requests", "missing"]
+            .into_iter()
+            .cycle()
+            .take(300)
+            .collect::<Vec<_>>();
+
+        let latest = client.latest_versions(&names).unwrap();
+
+        assert_eq!(
+            latest,
+            HashMap::from([("requests".to_string(), Version::from_str("2.31.0").unwrap())])
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_response_cache() {
+        let dir = tempdir().unwrap();
+        let (url, requests) = serve("max-age=600");
+
+        client(&url, Some(dir.path().to_path_buf()))
+            .versions("requests")
+            .unwrap();
+        client(&url, Some(dir.path().to_path_buf()))
+            .versions("requests")
+            .unwrap();
+
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        let (url, requests) = serve("no-cache");
+        let dir = tempdir().unwrap();
+
+        client(&url, Some(dir.path().to_path_buf()))
+            .versions("requests")
+            .unwrap();
+        let versions = client(&url, Some(dir.path().to_path_buf()))
+            .versions("requests")
+            .unwrap();
+
+        // The second request revalidated the cached response and got a 304 back.
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        assert_eq!(versions.len(), 3);
+    }
+
+    #[test]
+    fn test_json_api_url() {
+        assert_eq!(
+            json_api_url("https://pypi.python.org/simple").as_deref(),
+            Some(DEFAULT_INDEX_URL)
+        );
+        assert_eq!(
+            json_api_url("https://test.pypi.org/simple/").as_deref(),
+            Some("https://test.pypi.org/pypi")
+        );
+        assert_eq!(json_api_url("https://pypi.org/pypi"), None);
+        assert_eq!(json_api_url("https://pypi.example.com/simple"), None);
+        assert_eq!(json_api_url("file:///srv/simple"), None);
+    }
+
+    #[test]
+    fn test_index_unavailable() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        assert!(matches!(
+            client(&url, None).latest_version("requests"),
+            Err(Error::PackageIndexUnavailable(_))
+        ));
+    }
+}
@@ -13,33 +13,55 @@
 //! 3. Running tests
 //! 4. Distributing the project
 
+mod classifiers;
 mod config;
 mod dependency;
+mod deprecation;
 mod environment;
 mod error;
 mod fs;
 mod git;
+mod history;
+mod index;
 mod manifest;
+mod metadata;
 pub mod ops;
 mod package;
+mod policy;
 mod python_environment;
+mod schema;
+mod script;
+mod suggest;
 mod sys;
 mod workspace;
 
 pub use config::Config;
 pub use dependency::{dependency_iter, Dependency};
+pub use deprecation::{
+    deprecated_flags, deprecated_key, deprecated_keys, DeprecatedFlag, DEPRECATED_FLAGS,
+};
 pub use environment::{env_path_string, env_path_values, Environment};
 pub use error::{Error, HuakResult};
 pub use fs::{copy_dir, last_path_component, CopyDirOptions};
 pub use git::{default_python_gitignore, init as git_init};
+pub use history::{history_file_path, read_history, DependencyChange, HistoryEntry};
+pub use index::{json_api_url, IndexClient, NetworkSettings, ProjectMetadata, DEFAULT_INDEX_URL};
 pub use manifest::{
     default_package_entrypoint_string, default_package_test_file_contents,
     default_pyproject_toml_contents, LocalManifest,
 };
+pub use metadata::{validate_classifier, validate_url};
 pub use package::{importable_package_name, Package};
+pub use policy::{DependencyGraph, DependencyPolicy, PolicyViolation};
 pub use python_environment::{
     active_python_env_path, directory_is_venv, initialize_venv, venv_executables_dir_path,
     InstallOptions, PythonEnvironment,
 };
-pub use sys::{shell_name, shell_path, SubprocessError, TerminalOptions, Verbosity};
-pub use workspace::{Workspace, WorkspaceOptions};
+pub use schema::{check_huak_min_version, pep440_version};
+pub use script::ScriptMetadata;
+pub use suggest::similar_names;
+pub use sys::{
+    in_container, is_root, shell_name, shell_path, CommandPlan, PlannedCommand, SubprocessError,
+    TerminalOptions, Verbosity,
+};
+pub use workspace::{CommandDefault, VenvOptions, Workspace, WorkspaceOptions};
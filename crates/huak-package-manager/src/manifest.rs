@@ -53,6 +53,12 @@ impl LocalManifest {
         &mut self.manifest_data
     }
 
+    /// Get a reference to the path to the `LocalManifest` file.
+    #[must_use]
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
     /// Write the `LocalManifest` file to its path.
     pub fn write_file(&self) -> HuakResult<()> {
         Ok(self.manifest_data.write_toml(&self.path)?)
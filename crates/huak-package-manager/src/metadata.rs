@@ -0,0 +1,170 @@
+use crate::{classifiers::CLASSIFIERS, Error, HuakResult};
+use huak_pyproject_toml::PyProjectToml;
+use pep440_rs::Version;
+use std::str::FromStr;
+use toml_edit::Item;
+use url::Url;
+
+/// Check if a trove classifier is one the package index would accept.
+///
+/// Classifiers are checked against the known list (see <https://pypi.org/classifiers/>), except
+/// `Private :: ` classifiers, which are accepted since they're meant to prevent uploads.
+pub fn validate_classifier(classifier: &str) -> HuakResult<()> {
+    let is_private = classifier.strip_prefix("Private :: ").is_some_and(|rest| {
+        rest.split(" :: ")
+            .all(|it| !it.is_empty() && it.trim() == it && !it.contains("::"))
+    });
+
+    if is_private || CLASSIFIERS.binary_search(&classifier).is_ok() {
+        Ok(())
+    } else {
+        Err(Error::InvalidClassifier(classifier.to_string()))
+    }
+}
+
+/// Check if a project URL is an absolute http(s) URL.
+pub fn validate_url(url: &str) -> HuakResult<()> {
+    match Url::parse(url) {
+        Ok(it) if matches!(it.scheme(), "http" | "https") && it.has_host() => Ok(()),
+        _ => Err(Error::InvalidUrl(url.to_string())),
+    }
+}
+
+/// Get the problems with an `item` stored at the dotted `key` of a pyproject.toml.
+///
+/// Only keys with known constraints are validated.
+pub(crate) fn metadata_problems(key: &[&str], item: &Item) -> Vec<Error> {
+    match key {
+        ["project", "classifiers"] => string_array(key, item).map_or_else(
+            |e| vec![e],
+            |it| {
+                it.into_iter()
+                    .filter_map(|it| validate_classifier(it).err())
+                    .collect()
+            },
+        ),
+        ["project", "keywords"] => string_array(key, item).err().into_iter().collect(),
+        ["project", "urls"] => match item.as_table_like() {
+            Some(table) => table
+                .iter()
+                .flat_map(|(name, it)| metadata_problems(&["project", "urls", name], it))
+                .collect(),
+            None => vec![invalid_type(key, "a table")],
+        },
+        ["project", "urls", _] => match item.as_str() {
+            Some(it) => validate_url(it).err().into_iter().collect(),
+            None => vec![invalid_type(key, "a string")],
+        },
+        ["project", "version"] => match item.as_str() {
+            Some(it) => Version::from_str(it)
+                .err()
+                .map(|e| Error::InvalidVersionString {
+                    version: it.to_string(),
+                    reason: e,
+                })
+                .into_iter()
+                .collect(),
+            None => vec![invalid_type(key, "a string")],
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Get the problems with the project metadata of a pyproject.toml.
+pub(crate) fn manifest_metadata_problems(manifest: &PyProjectToml) -> Vec<Error> {
+    let Some(project) = manifest.project_table() else {
+        return Vec::new();
+    };
+
+    ["classifiers", "keywords", "urls"]
+        .into_iter()
+        .filter_map(|name| project.get(name).map(|it| (name, it)))
+        .flat_map(|(name, it)| metadata_problems(&["project", name], it))
+        .collect()
+}
+
+fn string_array<'a>(key: &[&str], item: &'a Item) -> HuakResult<Vec<&'a str>> {
+    item.as_array()
+        .and_then(|it| it.iter().map(toml_edit::Value::as_str).collect())
+        .ok_or_else(|| invalid_type(key, "an array of strings"))
+}
+
+fn invalid_type(key: &[&str], expected: &str) -> Error {
+    Error::InvalidMetadata(format!("{} must be {expected}", key.join(".")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_classifier() {
+        for it in [
+            "Development Status :: 4 - Beta",
+            "Intended Audience :: Science/Research",
+            "Programming Language :: Python :: 3.12",
+            "License :: OSI Approved :: MIT License",
+            "Private :: Do Not Upload",
+            "Private :: Internal :: Tools",
+            "Framework :: Django :: 4.2",
+            "Topic :: Software Development :: Libraries :: Python Modules",
+            "Typing :: Typed",
+        ] {
+            assert!(validate_classifier(it).is_ok(), "{it}");
+        }
+
+        for it in [
+            "Development Status :: 8 - Done",
+            "Programming Language",
+            "Programming Language :: ",
+            "Programing Language :: Python",
+            "Topic ::Utilities",
+            "License :: Foo",
+            "Programming Language :: Pyhton :: 3",
+            "Framework :: Django :: 9.9",
+            "Private",
+            "Private :: ",
+        ] {
+            assert!(
+                matches!(validate_classifier(it), Err(Error::InvalidClassifier(_))),
+                "{it}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_classifiers_sorted() {
+        assert!(CLASSIFIERS.windows(2).all(|it| it[0] < it[1]));
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://github.com/cnpryer/huak").is_ok());
+        assert!(validate_url("github.com/cnpryer/huak").is_err());
+        assert!(validate_url("ftp://example.com").is_err());
+    }
+
+    #[test]
+    fn test_manifest_metadata_problems() {
+        let manifest = PyProjectToml {
+            doc: r#"
+[project]
+name = "mock-project"
+keywords = ["cli", 1]
+classifiers = ["Typing :: Typed", "Typing :: Untyped"]
+
+[project.urls]
+Homepage = "https://example.com"
+Docs = "example.com"
+"#
+            .parse()
+            .unwrap(),
+        };
+        let problems = manifest_metadata_problems(&manifest);
+
+        assert_eq!(problems.len(), 3);
+        assert!(matches!(&problems[0], Error::InvalidClassifier(it) if it == "Typing :: Untyped"));
+        assert!(matches!(&problems[1], Error::InvalidMetadata(_)));
+        assert!(matches!(&problems[2], Error::InvalidUrl(it) if it == "example.com"));
+    }
+}
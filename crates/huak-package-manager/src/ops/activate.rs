@@ -1,10 +1,251 @@
-use std::process::Command;
+use std::{fmt::Display, io::Write, path::Path, process::Command, str::FromStr};
 
-use crate::{Config, HuakResult};
+use crate::{shell_name, Config, Error, HuakResult, PythonEnvironment, TerminalOptions, Verbosity};
 
-pub fn activate_python_environment(config: &Config) -> HuakResult<()> {
-    let workspace = config.workspace();
-    let python_env = workspace.current_python_environment()?;
+#[allow(clippy::struct_excessive_bools)]
+pub struct ActivateOptions {
+    /// Create the virtual environment if it doesn't exist.
+    pub create: bool,
+    /// Print shell code that activates the virtual environment instead of starting a subshell.
+    pub print: bool,
+    /// Print shell code that deactivates the virtual environment.
+    pub print_deactivate: bool,
+    /// Print the command that runs the virtual environment's own activation script.
+    pub print_source: bool,
+    /// The shell to print code for. The current shell is detected if `None`.
+    pub shell: Option<ActivationShell>,
+}
+
+/// The shells Huak can print activation code for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActivationShell {
+    Bash,
+    Zsh,
+    Fish,
+    Csh,
+    Powershell,
+    Nu,
+}
+
+impl ActivationShell {
+    /// Detect the `ActivationShell` from the current shell.
+    pub fn detect() -> HuakResult<ActivationShell> {
+        let name = shell_name()?;
+
+        match ActivationShell::from_str(name.trim_end_matches(".exe")) {
+            Err(_) if cfg!(windows) => Ok(ActivationShell::Powershell),
+            it => it,
+        }
+    }
+
+    /// Get the command that runs the activation script the environment with executables in `bin`
+    /// ships for the shell, or `None` if it has no script for it.
+    fn source_command(self, bin: &Path) -> Option<String> {
+        let (script, command) = match self {
+            ActivationShell::Bash | ActivationShell::Zsh => ("activate", "source"),
+            ActivationShell::Fish => ("activate.fish", "source"),
+            ActivationShell::Csh => ("activate.csh", "source"),
+            ActivationShell::Powershell => ("Activate.ps1", "&"),
+            ActivationShell::Nu => ("activate.nu", "overlay use"),
+        };
+        let path = bin.join(script);
+        if !path.is_file() {
+            return None;
+        }
+        let path = path.display().to_string();
+        let path = match self {
+            ActivationShell::Bash | ActivationShell::Zsh => posix_quote(&path),
+            ActivationShell::Fish => fish_quote(&path),
+            ActivationShell::Csh => csh_quote(&path),
+            ActivationShell::Powershell => powershell_quote(&path),
+            ActivationShell::Nu => nu_quote(&path),
+        };
+
+        Some(format!("{command} {path}"))
+    }
+
+    /// Get the code that activates the `PythonEnvironment` at `root` in the shell.
+    fn activate_script(self, root: &Path, bin: &Path, prompt: &str) -> String {
+        let (root, bin) = (root.display().to_string(), bin.display().to_string());
+
+        match self {
+            ActivationShell::Bash | ActivationShell::Zsh => {
+                let (root, bin, prompt) =
+                    (posix_quote(&root), posix_quote(&bin), posix_quote(prompt));
+                format!(
+                    r#"export VIRTUAL_ENV={root}
+export _OLD_VIRTUAL_PATH="$PATH"
+export PATH={bin}:"$PATH"
+_OLD_VIRTUAL_PS1="${{PS1-}}"
+PS1={prompt}"${{PS1-}}"
+hash -r 2>/dev/null
+"#
+                )
+            }
+            ActivationShell::Fish => {
+                let (root, bin, prompt) = (fish_quote(&root), fish_quote(&bin), fish_quote(prompt));
+                format!(
+                    r"set -gx VIRTUAL_ENV {root}
+set -gx _OLD_VIRTUAL_PATH $PATH
+set -gx PATH {bin} $PATH
+functions -c fish_prompt _old_fish_prompt
+function fish_prompt
+    printf '%s' {prompt}
+    _old_fish_prompt
+end
+"
+                )
+            }
+            // csh can't `eval` multi-line code, so the environment's own script is sourced.
+            ActivationShell::Csh => format!(
+                "source {}\n",
+                csh_quote(&Path::new(&bin).join("activate.csh").display().to_string())
+            ),
+            ActivationShell::Powershell => {
+                let (root, bin, prompt) = (
+                    powershell_quote(&root),
+                    powershell_quote(&bin),
+                    powershell_quote(prompt),
+                );
+                format!(
+                    r"$env:VIRTUAL_ENV = {root}
+$env:_OLD_VIRTUAL_PATH = $env:PATH
+$env:PATH = {bin} + [IO.Path]::PathSeparator + $env:PATH
+Copy-Item function:prompt function:global:_OLD_VIRTUAL_PROMPT
+function global:prompt {{ Write-Host -NoNewline {prompt}; _OLD_VIRTUAL_PROMPT }}
+"
+                )
+            }
+            ActivationShell::Nu => {
+                let (root, bin, prompt) = (nu_quote(&root), nu_quote(&bin), nu_quote(prompt));
+                format!(
+                    r#"$env.VIRTUAL_ENV = {root}
+$env._OLD_VIRTUAL_PATH = $env.PATH
+$env.PATH = ($env.PATH | prepend {bin})
+$env._OLD_VIRTUAL_PROMPT_COMMAND = ($env.PROMPT_COMMAND? | default "")
+$env.PROMPT_COMMAND = {{|| let old = $env._OLD_VIRTUAL_PROMPT_COMMAND; {prompt} + (if ($old | describe) == "closure" {{ do $old }} else {{ $old }}) }}
+"#
+                )
+            }
+        }
+    }
+
+    /// Get the code that reverts the shell's activation code.
+    fn deactivate_script(self) -> &'static str {
+        match self {
+            ActivationShell::Bash | ActivationShell::Zsh => {
+                r#"if [ -n "${_OLD_VIRTUAL_PATH+x}" ]; then
+    export PATH="$_OLD_VIRTUAL_PATH"
+    unset _OLD_VIRTUAL_PATH
+fi
+if [ -n "${_OLD_VIRTUAL_PS1+x}" ]; then
+    PS1="$_OLD_VIRTUAL_PS1"
+    unset _OLD_VIRTUAL_PS1
+fi
+unset VIRTUAL_ENV
+hash -r 2>/dev/null
+"#
+            }
+            ActivationShell::Fish => {
+                r"if set -q _OLD_VIRTUAL_PATH
+    set -gx PATH $_OLD_VIRTUAL_PATH
+    set -e _OLD_VIRTUAL_PATH
+end
+if functions -q _old_fish_prompt
+    functions -e fish_prompt
+    functions -c _old_fish_prompt fish_prompt
+    functions -e _old_fish_prompt
+end
+set -e VIRTUAL_ENV
+"
+            }
+            // The alias `activate.csh` defines.
+            ActivationShell::Csh => "deactivate\n",
+            ActivationShell::Powershell => {
+                r"if (Test-Path env:_OLD_VIRTUAL_PATH) {
+    $env:PATH = $env:_OLD_VIRTUAL_PATH
+    Remove-Item env:_OLD_VIRTUAL_PATH
+}
+if (Test-Path function:_OLD_VIRTUAL_PROMPT) {
+    Copy-Item function:_OLD_VIRTUAL_PROMPT function:global:prompt
+    Remove-Item function:_OLD_VIRTUAL_PROMPT
+}
+if (Test-Path env:VIRTUAL_ENV) {
+    Remove-Item env:VIRTUAL_ENV
+}
+"
+            }
+            ActivationShell::Nu => {
+                r"if '_OLD_VIRTUAL_PATH' in $env {
+    $env.PATH = $env._OLD_VIRTUAL_PATH
+    $env.PROMPT_COMMAND = $env._OLD_VIRTUAL_PROMPT_COMMAND
+    hide-env _OLD_VIRTUAL_PATH _OLD_VIRTUAL_PROMPT_COMMAND VIRTUAL_ENV
+}
+"
+            }
+        }
+    }
+}
+
+impl Display for ActivationShell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ActivationShell::Bash => "bash",
+            ActivationShell::Zsh => "zsh",
+            ActivationShell::Fish => "fish",
+            ActivationShell::Csh => "csh",
+            ActivationShell::Powershell => "powershell",
+            ActivationShell::Nu => "nu",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for ActivationShell {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" | "sh" | "dash" => Ok(ActivationShell::Bash),
+            "zsh" => Ok(ActivationShell::Zsh),
+            "fish" => Ok(ActivationShell::Fish),
+            "csh" | "tcsh" => Ok(ActivationShell::Csh),
+            "powershell" | "pwsh" => Ok(ActivationShell::Powershell),
+            "nu" => Ok(ActivationShell::Nu),
+            _ => Err(Error::UnsupportedShell(s.to_string())),
+        }
+    }
+}
+
+pub fn activate_python_environment(config: &Config, options: &ActivateOptions) -> HuakResult<()> {
+    if options.print_deactivate {
+        return print_script(shell(options)?.deactivate_script());
+    }
+
+    let python_env = python_environment(config, options)?;
+
+    if options.print_source {
+        let shell = shell(options)?;
+        let Some(command) = shell.source_command(python_env.executables_dir_path()) else {
+            return Err(Error::UnsupportedShell(format!(
+                "{shell} (the environment has no activation script for it, use --print instead)"
+            )));
+        };
+
+        return print_script(&format!("{command}\n"));
+    }
+
+    if options.print {
+        let prompt = format!("({}) ", python_env.name()?);
+        let script = shell(options)?.activate_script(
+            python_env.root(),
+            python_env.executables_dir_path(),
+            &prompt,
+        );
+
+        return print_script(&script);
+    }
 
     if python_env.active() {
         return Ok(());
@@ -39,5 +280,149 @@ pub fn activate_python_environment(config: &Config) -> HuakResult<()> {
         ),
     ]);
 
-    config.terminal().run_command(&mut cmd)
+    // The activated shell is interactive so it's never subject to a timeout.
+    let mut terminal = config.terminal();
+    terminal.options.timeout = None;
+    terminal.run_command(&mut cmd)
+}
+
+fn shell(options: &ActivateOptions) -> HuakResult<ActivationShell> {
+    options.shell.map_or_else(ActivationShell::detect, Ok)
+}
+
+fn python_environment(config: &Config, options: &ActivateOptions) -> HuakResult<PythonEnvironment> {
+    if !options.create {
+        return config.workspace().current_python_environment();
+    }
+
+    // Printed code is meant to be evaluated, so nothing else can be written to stdout.
+    let config = if options.print || options.print_source {
+        config.clone().with_terminal(TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..config.terminal_options.clone()
+        })
+    } else {
+        config.clone()
+    };
+
+    config.workspace().resolve_python_environment()
+}
+
+/// Write shell code to stdout with no status text so it's safe to `eval`.
+fn print_script(script: &str) -> HuakResult<()> {
+    std::io::stdout().write_all(script.as_bytes())?;
+
+    Ok(())
+}
+
+fn posix_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+/// Quote a value for csh, which expands `!` even in single quotes.
+fn csh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''").replace('!', r"\!"))
+}
+
+fn powershell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn nu_quote(value: &str) -> String {
+    format!("r#'{value}'#")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activation_shell_from_str() {
+        assert_eq!(
+            ActivationShell::from_str("zsh").unwrap(),
+            ActivationShell::Zsh
+        );
+        assert_eq!(
+            ActivationShell::from_str("pwsh").unwrap(),
+            ActivationShell::Powershell
+        );
+        assert_eq!(
+            ActivationShell::from_str("tcsh").unwrap(),
+            ActivationShell::Csh
+        );
+        assert!(matches!(
+            ActivationShell::from_str("xonsh"),
+            Err(Error::UnsupportedShell(_))
+        ));
+    }
+
+    #[test]
+    fn test_posix_activate_script() {
+        let script = ActivationShell::Bash.activate_script(
+            Path::new("/it's/.venv"),
+            Path::new("/it's/.venv/bin"),
+            "(.venv) ",
+        );
+
+        assert!(script.starts_with("export VIRTUAL_ENV='/it'\\''s/.venv'\n"));
+        assert!(script.contains("export PATH='/it'\\''s/.venv/bin':\"$PATH\"\n"));
+        assert!(script.contains("PS1='(.venv) '\"${PS1-}\"\n"));
+    }
+
+    #[test]
+    fn test_source_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin = dir.path();
+        for it in ["activate", "activate.fish", "activate.csh", "Activate.ps1"] {
+            std::fs::write(bin.join(it), "").unwrap();
+        }
+        let path = |script: &str| bin.join(script).display().to_string();
+
+        assert_eq!(
+            ActivationShell::Zsh.source_command(bin).unwrap(),
+            format!("source '{}'", path("activate"))
+        );
+        assert_eq!(
+            ActivationShell::Fish.source_command(bin).unwrap(),
+            format!("source '{}'", path("activate.fish"))
+        );
+        assert_eq!(
+            ActivationShell::Csh.source_command(bin).unwrap(),
+            format!("source '{}'", path("activate.csh"))
+        );
+        assert_eq!(
+            ActivationShell::Powershell.source_command(bin).unwrap(),
+            format!("& '{}'", path("Activate.ps1"))
+        );
+        // venv doesn't ship a script for nu.
+        assert_eq!(ActivationShell::Nu.source_command(bin), None);
+        assert_eq!(csh_quote("it's !"), r"'it'\''s \!'");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_posix_scripts_eval() {
+        let activate = ActivationShell::Bash.activate_script(
+            Path::new("/mock/.venv"),
+            Path::new("/mock/.venv/bin"),
+            "(.venv) ",
+        );
+        let deactivate = ActivationShell::Bash.deactivate_script();
+        let output = Command::new("/bin/sh")
+            .arg("-c")
+            .arg(format!(
+                "PATH=/usr/bin; {activate} echo \"$VIRTUAL_ENV $PATH\"; {deactivate} echo \"${{VIRTUAL_ENV-unset}} $PATH\""
+            ))
+            .output()
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "/mock/.venv /mock/.venv/bin:/usr/bin\nunset /usr/bin\n"
+        );
+    }
 }
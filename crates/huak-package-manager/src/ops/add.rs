@@ -1,10 +1,24 @@
-use crate::{dependency_iter, Config, Dependency, HuakResult, InstallOptions};
+use super::{
+    ensure_not_poetry_managed, install_project_dependencies, unsatisfied_dependencies,
+    warn_policy_violations,
+};
+use crate::{
+    dependency_iter,
+    history::{record_dependency_changes, DependencySnapshot},
+    similar_names, Config, Dependency, Error, HuakResult, InstallOptions,
+};
+use huak_pyproject_toml::PyProjectToml;
 use pep440_rs::VersionSpecifiers;
 use pep508_rs::VersionOrUrl;
 use std::str::FromStr;
+use termcolor::Color;
 
 pub struct AddOptions {
     pub install_options: InstallOptions,
+    /// Only require the dependencies on Python versions matching this constraint (like `<3.10`).
+    pub python_constraint: Option<String>,
+    /// Add dependencies even if they conflict with the project's other requirements.
+    pub allow_conflicts: bool,
 }
 
 pub fn add_project_dependencies(
@@ -14,25 +28,50 @@ pub fn add_project_dependencies(
 ) -> HuakResult<()> {
     let workspace = config.workspace();
     let mut manifest = workspace.current_local_manifest()?;
+    ensure_not_poetry_managed(&manifest)?;
+    let before = DependencySnapshot::new(manifest.manifest_data());
 
     // Collect all dependencies that need to be added to the manifest file.
-    let mut deps = dependency_iter(dependencies)
-        .filter(|dep| {
-            !manifest
+    let (declared, mut deps): (Vec<_>, Vec<_>) = requested_dependencies(dependencies, options)?
+        .into_iter()
+        .partition(|dep| {
+            manifest
                 .manifest_data()
                 .contains_project_dependency(dep.name())
-        })
-        .collect::<Vec<_>>();
+        });
+    let listed = manifest
+        .manifest_data()
+        .project_dependencies()
+        .unwrap_or_default();
+    let missing = declared_to_install(&declared, &listed, config)?;
+    check_dependency_conflicts(&deps, manifest.manifest_data(), options, config)?;
 
-    if deps.is_empty() {
+    if deps.is_empty() && missing.is_empty() {
         return Ok(());
     }
 
     let python_env = workspace.resolve_python_environment()?;
+
+    if !missing.is_empty() {
+        install_project_dependencies(
+            &python_env,
+            &missing,
+            &manifest,
+            &options.install_options,
+            config,
+        )?;
+    }
+
+    if deps.is_empty() {
+        return Ok(());
+    }
+
+    python_env.install_hashed_packages(&deps, &options.install_options, config)?;
     python_env.install_packages(&deps, &options.install_options, config)?;
 
     // If there's no version data then get the installed version and add to manifest file.
     let packages = python_env.installed_packages()?; // TODO: Only run if versions weren't provided.
+    let mut pinned = Vec::new();
     for dep in &mut deps {
         if dep.requirement().version_or_url.is_none() {
             // TODO: Optimize this .find
@@ -41,6 +80,7 @@ pub fn add_project_dependencies(
                     VersionSpecifiers::from_str(&format!("=={}", pkg.version()))
                         .expect("package should have a version"),
                 ));
+                pinned.push(dep.clone());
             }
         }
 
@@ -52,42 +92,126 @@ pub fn add_project_dependencies(
                 .manifest_data_mut()
                 .add_project_dependency(&dep.to_string());
         }
+
+        if !dep.hashes().is_empty() {
+            manifest
+                .manifest_data_mut()
+                .set_dependency_hashes(dep.name(), dep.hashes());
+        }
     }
 
+    // The installed versions of unversioned dependencies weren't known until now.
+    check_dependency_conflicts(&pinned, manifest.manifest_data(), options, config)?;
     manifest.manifest_data_mut().formatted();
-    manifest.write_file()?;
+    config.write_manifest(&manifest)?;
+    record_dependency_changes("add", &before, &manifest, Some(&python_env), config);
+
+    warn_policy_violations(&manifest, &python_env, config);
 
     Ok(())
 }
 
+/// Parse the requested `dependencies`, restricting them to the `--python-constraint`.
+fn requested_dependencies(
+    dependencies: &[String],
+    options: &AddOptions,
+) -> HuakResult<Vec<Dependency>> {
+    let mut deps = dependency_iter(dependencies).collect::<Vec<_>>();
+
+    if let Some(constraint) = options.python_constraint.as_deref() {
+        for dep in &mut deps {
+            dep.add_python_constraint(constraint)?;
+        }
+    }
+
+    Ok(deps)
+}
+
+/// Add the `dependencies` to each of the optional dependency `groups`, installing them once.
 pub fn add_project_optional_dependencies(
     dependencies: &[String],
-    group: &str,
+    groups: &[String],
     config: &Config,
     options: &AddOptions,
 ) -> HuakResult<()> {
     let workspace = config.workspace();
     let mut manifest = workspace.current_local_manifest()?;
+    ensure_not_poetry_managed(&manifest)?;
+    let before = DependencySnapshot::new(manifest.manifest_data());
 
-    // Collect all dependencies that need to be added.
-    // TODO(cnpryer): Allow
-    let mut deps = dependency_iter(dependencies)
-        .filter(|dep| {
-            !manifest
-                .manifest_data()
-                .contains_project_optional_dependency(dep.name(), group)
+    let mut targets = Vec::new();
+    for group in groups {
+        if !targets.contains(group) {
+            targets.push(group.clone());
+        }
+    }
+
+    // A new group that's a near miss of an existing one is likely a typo.
+    let existing = manifest
+        .manifest_data()
+        .project_optional_dependency_groups()
+        .unwrap_or_default();
+    for group in targets.iter().filter(|it| !existing.contains(it)) {
+        let similar = similar_names(group, &existing);
+        if !similar.is_empty() {
+            config.terminal().print_warning(format!(
+                "adding a new optional dependency group {group}; did you mean {}?",
+                similar.join(" or ")
+            ))?;
+        }
+    }
+
+    // Collect all dependencies that need to be added to at least one of the groups.
+    let (declared, mut deps): (Vec<_>, Vec<_>) = requested_dependencies(dependencies, options)?
+        .into_iter()
+        .partition(|dep| {
+            targets.iter().all(|group| {
+                manifest
+                    .manifest_data()
+                    .contains_project_optional_dependency(dep.name(), group)
+            })
+        });
+    let listed = manifest
+        .manifest_data()
+        .project_optional_dependencies()
+        .map(|mut groups| {
+            targets
+                .iter()
+                .filter_map(|it| groups.remove(it))
+                .flatten()
+                .collect::<Vec<_>>()
         })
-        .collect::<Vec<Dependency>>();
+        .unwrap_or_default();
+    let missing = declared_to_install(&declared, &listed, config)?;
+    check_dependency_conflicts(&deps, manifest.manifest_data(), options, config)?;
 
-    if deps.is_empty() {
+    if deps.is_empty() && missing.is_empty() {
         return Ok(());
     };
 
     let python_env = workspace.resolve_python_environment()?;
+
+    if !missing.is_empty() {
+        install_project_dependencies(
+            &python_env,
+            &missing,
+            &manifest,
+            &options.install_options,
+            config,
+        )?;
+    }
+
+    if deps.is_empty() {
+        return Ok(());
+    }
+
+    python_env.install_hashed_packages(&deps, &options.install_options, config)?;
     python_env.install_packages(&deps, &options.install_options, config)?;
 
     // If there's no version data then get the installed version and add to manifest file.
     let packages = python_env.installed_packages()?; // TODO: Only run if versions weren't provided.
+    let mut pinned = Vec::new();
+    let mut updated = Vec::new();
     for dep in &mut deps {
         if dep.requirement().version_or_url.is_none() {
             // TODO: Optimize this .find
@@ -96,31 +220,176 @@ pub fn add_project_optional_dependencies(
                     VersionSpecifiers::from_str(&format!("=={}", pkg.version()))
                         .expect("package should have a version"),
                 ));
+                pinned.push(dep.clone());
             }
         }
 
-        if !manifest
-            .manifest_data()
-            .contains_project_optional_dependency(dep.name(), group)
-        {
+        for group in &targets {
+            if !manifest
+                .manifest_data()
+                .contains_project_optional_dependency(dep.name(), group)
+            {
+                manifest
+                    .manifest_data_mut()
+                    .add_project_optional_dependency(&dep.to_string(), group);
+
+                if !updated.contains(group) {
+                    updated.push(group.clone());
+                }
+            }
+        }
+
+        if !dep.hashes().is_empty() {
             manifest
                 .manifest_data_mut()
-                .add_project_optional_dependency(&dep.to_string(), group);
+                .set_dependency_hashes(dep.name(), dep.hashes());
         }
     }
 
+    // The installed versions of unversioned dependencies weren't known until now.
+    check_dependency_conflicts(&pinned, manifest.manifest_data(), options, config)?;
     manifest.manifest_data_mut().formatted();
-    manifest.write_file()?;
+    config.write_manifest(&manifest)?;
+    record_dependency_changes("add", &before, &manifest, Some(&python_env), config);
+
+    if !config.dry_run {
+        config.terminal().print_custom(
+            "Updated",
+            format!("optional dependency groups {}", updated.join(", ")),
+            Color::Green,
+            true,
+        )?;
+    }
+
+    warn_policy_violations(&manifest, &python_env, config);
 
     Ok(())
 }
 
+/// Check the `dependencies` being added against the requirements of the same packages in the
+/// project's dependencies and each of its optional dependency groups.
+///
+/// Conflicts fail the operation unless `--allow-conflicts` is passed, in which case they're only
+/// reported.
+fn check_dependency_conflicts(
+    dependencies: &[Dependency],
+    manifest_data: &PyProjectToml,
+    options: &AddOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    let conflicts = dependency_conflicts(dependencies, manifest_data);
+
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+
+    if !options.allow_conflicts {
+        return Err(Error::DependencyConflict { conflicts });
+    }
+
+    let mut terminal = config.terminal();
+    for it in conflicts {
+        terminal.print_warning(it)?;
+    }
+
+    Ok(())
+}
+
+/// Describe each requirement in the manifest that can't be satisfied together with one of the
+/// `dependencies`, along with where it's listed.
+fn dependency_conflicts(dependencies: &[Dependency], manifest_data: &PyProjectToml) -> Vec<String> {
+    let mut listed = vec![(
+        "the project's dependencies".to_string(),
+        manifest_data.project_dependencies().unwrap_or_default(),
+    )];
+    let mut groups = manifest_data
+        .project_optional_dependencies()
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<Vec<_>>();
+    groups.sort_by(|a, b| a.0.cmp(&b.0));
+    listed.extend(
+        groups
+            .into_iter()
+            .map(|(group, deps)| (format!("optional dependency group {group}"), deps)),
+    );
+
+    let mut conflicts = Vec::new();
+    for dep in dependencies {
+        for (origin, deps) in &listed {
+            for it in dependency_iter(deps).filter(|it| dep.conflicts_with(it)) {
+                conflicts.push(format!("{dep} conflicts with {it} in {origin}"));
+            }
+        }
+    }
+
+    conflicts
+}
+
+/// Check the requested `dependencies` the manifest already lists (as `listed`) against the
+/// project's Python environment without running the installer.
+///
+/// Satisfied dependencies are reported as already present. The listed requirements of the rest
+/// are returned so they can be installed without changing the manifest.
+fn declared_to_install(
+    dependencies: &[Dependency],
+    listed: &[String],
+    config: &Config,
+) -> HuakResult<Vec<String>> {
+    if dependencies.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let listed = dependency_iter(listed).collect::<Vec<_>>();
+    let mut terminal = config.terminal();
+    let mut declared = Vec::new();
+
+    for dep in dependencies {
+        let Some(it) = listed.iter().find(|it| *it == dep) else {
+            continue;
+        };
+
+        // A different specifier would need the manifest to change, which `huak add` doesn't do
+        // for dependencies it already lists.
+        if dep.requirement().version_or_url.is_some()
+            && dep.requirement().version_or_url != it.requirement().version_or_url
+        {
+            terminal.print_warning(format!(
+                "{} is already declared as {it}, nothing to do",
+                dep.name()
+            ))?;
+            continue;
+        }
+
+        declared.push(it.clone());
+    }
+
+    let unsatisfied = match config.workspace().current_python_environment() {
+        Ok(it) => unsatisfied_dependencies(&it, &declared, config)?,
+        Err(Error::PythonEnvironmentNotFound) => declared.clone(),
+        Err(e) => return Err(e),
+    };
+
+    for dep in declared.iter().filter(|it| !unsatisfied.contains(it)) {
+        terminal.print_custom(
+            "Skipped",
+            format!("{} is already present, nothing to do", dep.name()),
+            Color::Green,
+            true,
+        )?;
+    }
+
+    Ok(unsatisfied.iter().map(ToString::to_string).collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{copy_dir, initialize_venv, CopyDirOptions, TerminalOptions, Verbosity};
     use huak_dev::dev_resources_dir;
+    use std::process::Command;
     use tempfile::tempdir;
+    use url::Url;
 
     #[test]
     fn test_add_project_dependencies() {
@@ -146,7 +415,9 @@ mod tests {
         let ws = config.workspace();
         let venv = ws.resolve_python_environment().unwrap();
         let options = AddOptions {
-            install_options: InstallOptions { values: None },
+            install_options: InstallOptions::default(),
+            python_constraint: None,
+            allow_conflicts: false,
         };
 
         add_project_dependencies(&[String::from("ruff")], &config, &options).unwrap();
@@ -169,7 +440,7 @@ mod tests {
             &CopyDirOptions::default(),
         )
         .unwrap();
-        let group = "dev";
+        let groups = ["dev".to_string(), "ci".to_string(), "dev".to_string()];
         let workspace_root = dir.path().join("mock-project");
         let cwd = workspace_root.clone();
         let terminal_options = TerminalOptions {
@@ -186,10 +457,12 @@ mod tests {
         initialize_venv(ws.root().join(".venv"), &ws.environment()).unwrap();
         let venv = ws.resolve_python_environment().unwrap();
         let options = AddOptions {
-            install_options: InstallOptions { values: None },
+            install_options: InstallOptions::default(),
+            python_constraint: None,
+            allow_conflicts: false,
         };
 
-        add_project_optional_dependencies(&[String::from("isort")], group, &config, &options)
+        add_project_optional_dependencies(&[String::from("isort")], &groups, &config, &options)
             .unwrap();
 
         let dep = Dependency::from_str("isort").unwrap();
@@ -199,5 +472,78 @@ mod tests {
         assert!(manifest
             .manifest_data()
             .contains_project_optional_dependency(dep.name(), "dev"));
+        assert!(manifest
+            .manifest_data()
+            .contains_project_optional_dependency(dep.name(), "ci"));
+    }
+
+    #[test]
+    fn test_add_optional_archive_dependency() {
+        let dir = tempdir().unwrap();
+        copy_dir(
+            &dev_resources_dir().join("mock-project"),
+            &dir.path().join("mock-project"),
+            &CopyDirOptions::default(),
+        )
+        .unwrap();
+        let workspace_root = dir.path().join("mock-project");
+        let cwd = workspace_root.clone();
+        let terminal_options = TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+        let config = Config {
+            workspace_root,
+            cwd,
+            terminal_options,
+            ..Default::default()
+        };
+        let ws = config.workspace();
+        let venv = ws.resolve_python_environment().unwrap();
+
+        // A wheel on disk, like one built from another project.
+        let wheel = dir.path().join("my_pkg-0.1.0-py3-none-any.whl");
+        let status = Command::new(venv.python_path())
+            .args([
+                "-c",
+                r#"import sys, zipfile
+with zipfile.ZipFile(sys.argv[1], "w") as it:
+    it.writestr("my_pkg/__init__.py", "")
+    it.writestr("my_pkg-0.1.0.dist-info/METADATA", "Metadata-Version: 2.1\nName: my-pkg\nVersion: 0.1.0\n")
+    it.writestr("my_pkg-0.1.0.dist-info/WHEEL", "Wheel-Version: 1.0\nRoot-Is-Purelib: true\nTag: py3-none-any\n")
+    it.writestr("my_pkg-0.1.0.dist-info/RECORD", "")
+"#,
+            ])
+            .arg(&wheel)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let options = AddOptions {
+            install_options: InstallOptions::default(),
+            python_constraint: None,
+            allow_conflicts: false,
+        };
+
+        add_project_optional_dependencies(
+            &[wheel.display().to_string()],
+            &["dev".to_string()],
+            &config,
+            &options,
+        )
+        .unwrap();
+
+        let manifest = ws.current_local_manifest().unwrap();
+        let dev = manifest
+            .manifest_data()
+            .project_optional_dependencies()
+            .unwrap()
+            .remove("dev")
+            .unwrap();
+
+        assert!(venv.installed_versions().unwrap().contains_key("my-pkg"));
+        assert!(dev.contains(&format!(
+            "my_pkg @ {}",
+            Url::from_file_path(dunce::canonicalize(&wheel).unwrap()).unwrap()
+        )));
     }
 }
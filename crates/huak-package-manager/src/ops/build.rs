@@ -1,24 +1,151 @@
-use super::add_venv_to_command;
-use crate::{Config, Dependency, HuakResult, InstallOptions};
-use std::{process::Command, str::FromStr};
+use super::{
+    add_venv_to_command,
+    checksums::{is_checksum_file, write_checksums},
+    contents::verify_artifact_contents,
+    format_elapsed, install_locked_tool,
+    native::{cibuildwheel_build_selector, NativeBuild},
+    operation_env, option_value, resolve_dynamic_version,
+};
+use crate::{
+    git::{export_head, working_tree_changes},
+    Config, Dependency, Error, HuakResult, InstallOptions, LocalManifest, PythonEnvironment,
+    TerminalOptions, Verbosity,
+};
+use pep440_rs::VersionSpecifiers;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+    time::{Duration, Instant, SystemTime},
+};
+use tempfile::TempDir;
+use termcolor::Color;
 
+/// The directory `huak build --cibuildwheel` writes wheels to, relative to the workspace root.
+pub(crate) const CIBUILDWHEEL_OUTPUT_DIR: &str = "wheelhouse";
+
+#[allow(clippy::struct_excessive_bools)]
 pub struct BuildOptions {
     /// A values vector of build options typically used for passing on arguments.
     pub values: Option<Vec<String>>,
+    /// Build wheels for each supported platform and Python version with `cibuildwheel`.
+    pub cibuildwheel: bool,
+    /// Environment variables for the tool's subprocesses, merged over `[tool.huak.build] env`.
+    pub env: HashMap<String, String>,
+    /// How the built artifacts are reported.
+    pub format: OutputFormat,
+    /// Write the artifacts' sha256 digests to a `SHA256SUMS` file and a `.sha256` file next to
+    /// each.
+    pub checksums: bool,
+    /// Check that the artifacts contain the project's package and the files configured with
+    /// `[tool.huak.build] include`.
+    pub verify_contents: bool,
+    /// Build from a temporary checkout of the committed files instead of the working tree.
+    pub from_clean: bool,
+    /// Build from a clean checkout even if the working tree has uncommitted changes.
+    pub allow_dirty: bool,
     pub install_options: InstallOptions,
 }
 
-pub fn build_project(config: &Config, options: &BuildOptions) -> HuakResult<()> {
+/// How a command's results are printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Status messages and a summary for people.
+    #[default]
+    Human,
+    /// A JSON document on stdout for scripts. Tools' output is silenced so it doesn't mix in.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(OutputFormat::Human),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(Error::InvalidOutputFormat(format!(
+                "{s} (expected human or json)"
+            ))),
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Human => write!(f, "human"),
+            OutputFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// The project a build was for and the artifacts it wrote.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct BuildReport {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub artifacts: Vec<BuildArtifact>,
+}
+
+/// A file written by a build, like a wheel or an sdist.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct BuildArtifact {
+    pub path: PathBuf,
+    /// The file's size in bytes.
+    pub size: u64,
+    /// The file's sha256 digest, if checksums were written.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// Build the project, returning a `BuildReport` of what was built. The artifacts are also
+/// printed for the `Human` `OutputFormat`.
+pub fn build_project(config: &Config, options: &BuildOptions) -> HuakResult<BuildReport> {
+    let quiet;
+    let config = if options.format == OutputFormat::Json {
+        quiet = config.clone().with_terminal(TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..config.terminal_options.clone()
+        });
+        &quiet
+    } else {
+        config
+    };
     let workspace = config.workspace();
-    let mut manifest = workspace.current_local_manifest()?;
+    let manifest = workspace.current_local_manifest()?;
     let python_env = workspace.resolve_python_environment()?;
 
-    // Install the `build` package if it isn't already installed.
-    let build_dep = Dependency::from_str("build")?;
-    if !python_env.contains_module(build_dep.name())? {
-        python_env.install_packages(&[&build_dep], &options.install_options, config)?;
+    // Export the committed files before the manifest is changed below.
+    let checkout = if options.from_clean {
+        Some(clean_checkout(workspace.root(), options, config)?)
+    } else {
+        None
+    };
+
+    // Check the tools for building native extensions upfront instead of failing mid-build.
+    // cibuildwheel sets up its own build environments.
+    if !options.cibuildwheel {
+        let native = NativeBuild::detect(manifest.manifest_data(), workspace.root());
+        if !native.is_empty() {
+            native.check(&python_env)?;
+        }
     }
 
+    // Install the build tool at its pinned version.
+    let build_dep = Dependency::from_str(if options.cibuildwheel {
+        "cibuildwheel"
+    } else {
+        "build"
+    })?;
+    install_locked_tool(&build_dep, &python_env, &options.install_options, config)?;
+
+    // Reread the manifest in case the build tool was just pinned.
+    let mut manifest = workspace.current_local_manifest()?;
+
     // Add the installed `build` package to the manifest file.
     if !manifest
         .manifest_data()
@@ -36,18 +163,251 @@ pub fn build_project(config: &Config, options: &BuildOptions) -> HuakResult<()>
     }
 
     manifest.manifest_data_mut().formatted();
-    manifest.write_file()?;
+    config.write_manifest(&manifest)?;
+
+    // Surface the version a dynamic version (like one derived from VCS tags) resolves to.
+    let name = manifest.manifest_data().project_name();
+    let version = if manifest.manifest_data().is_project_field_dynamic("version") {
+        let version =
+            resolve_dynamic_version(&manifest, &python_env, &options.install_options, config)?;
+        config.operation_terminal("build").print_custom(
+            "Building",
+            format!("{} {version}", name.as_deref().unwrap_or_default()),
+            Color::Green,
+            true,
+        )?;
+        Some(version)
+    } else {
+        manifest.manifest_data().project_version()
+    };
+
+    // Allow for filesystems with coarse modification times when finding the built artifacts.
+    let started = SystemTime::now() - Duration::from_secs(1);
+    let start = Instant::now();
+    let values = options.values.clone().unwrap_or_default();
+
+    if options.cibuildwheel {
+        run_cibuildwheel(&manifest, &python_env, options, config)?;
+
+        let output_dir = workspace
+            .root()
+            .join(option_value(&values, &["--output-dir"]).unwrap_or(CIBUILDWHEEL_OUTPUT_DIR));
+        let artifacts = finish_build(&output_dir, started, start, options, config)?;
+        if options.verify_contents {
+            verify_artifact_contents(&artifacts, &manifest, &python_env, "build", config)?;
+        }
+
+        return Ok(BuildReport {
+            name,
+            version,
+            artifacts,
+        });
+    }
 
-    // Run `build`.
+    // Run `build`, writing to the configured dist directory unless an output directory is passed.
     let mut cmd = Command::new(python_env.python_path());
+    let output_dir = workspace.root().join(
+        option_value(&values, &["-o", "--outdir"])
+            .map_or_else(|| workspace.dist_dir(), PathBuf::from),
+    );
     let mut args = vec!["-m", "build"];
     if let Some(it) = options.values.as_ref() {
         args.extend(it.iter().map(std::string::String::as_str));
     }
+    let has_outdir = args
+        .iter()
+        .any(|it| *it == "-o" || it.starts_with("--outdir"));
     add_venv_to_command(&mut cmd, &python_env)?;
-    cmd.args(args).current_dir(workspace.root());
+    cmd.args(args);
+    // A clean checkout's artifacts are written back to the project's output directory.
+    if !has_outdir || checkout.is_some() {
+        cmd.arg("--outdir").arg(&output_dir);
+    }
+    if checkout.is_some() && manifest.manifest_data().is_project_field_dynamic("version") {
+        if let Some(it) = version.as_ref() {
+            // The checkout doesn't have the VCS metadata tools like setuptools-scm read.
+            cmd.env("SETUPTOOLS_SCM_PRETEND_VERSION", it);
+        }
+    }
+    cmd.envs(operation_env("build", &options.env, config)?)
+        .current_dir(checkout.as_ref().map_or(workspace.root(), |(_, it)| it));
+
+    config.operation_terminal("build").run_command(&mut cmd)?;
+
+    let artifacts = finish_build(&output_dir, started, start, options, config)?;
+    if options.verify_contents {
+        verify_artifact_contents(&artifacts, &manifest, &python_env, "build", config)?;
+    }
+
+    Ok(BuildReport {
+        name,
+        version,
+        artifacts,
+    })
+}
+
+/// Export the files committed in the repository of the project at `root` to a temporary
+/// directory, returning it and the project's root in it.
+///
+/// Uncommitted changes would be left out of the artifacts, so they're an error unless
+/// `--allow-dirty` is passed.
+fn clean_checkout(
+    root: &Path,
+    options: &BuildOptions,
+    config: &Config,
+) -> HuakResult<(TempDir, PathBuf)> {
+    let Some(changes) = working_tree_changes(root) else {
+        return Err(Error::GitRepositoryNotFound {
+            path: root.to_path_buf(),
+        });
+    };
+    let mut terminal = config.operation_terminal("build");
 
-    config.terminal().run_command(&mut cmd)
+    if !changes.is_empty() {
+        let changes = changes
+            .iter()
+            .map(|it| it.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !options.allow_dirty {
+            return Err(Error::UncommittedBuild(changes));
+        }
+        terminal.print_warning(format!(
+            "building without the uncommitted changes to {changes}"
+        ))?;
+    }
+
+    let dir = tempfile::tempdir()?;
+    let project_root = export_head(root, dir.path())?;
+    terminal.print_custom(
+        "Exported",
+        format!("the committed files to {}", dir.path().display()),
+        Color::Green,
+        true,
+    )?;
+
+    Ok((dir, project_root))
+}
+
+/// Get the artifacts written to `output_dir` since `started`, recording their checksums and
+/// printing them for the `Human` `OutputFormat`.
+fn finish_build(
+    output_dir: &Path,
+    started: SystemTime,
+    start: Instant,
+    options: &BuildOptions,
+    config: &Config,
+) -> HuakResult<Vec<BuildArtifact>> {
+    let mut artifacts = build_artifacts(output_dir, started);
+    if options.checksums {
+        write_checksums(output_dir, &mut artifacts, "build", config)?;
+    }
+    if options.format == OutputFormat::Human {
+        print_build_summary(&artifacts, start, config)?;
+    }
+
+    Ok(artifacts)
+}
+
+/// Get the files written to `output_dir` since `started`, other than checksum files.
+fn build_artifacts(output_dir: &Path, started: SystemTime) -> Vec<BuildArtifact> {
+    let mut artifacts = std::fs::read_dir(output_dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter_map(|it| {
+                    let metadata = it.metadata().ok()?;
+                    let modified = metadata.modified().ok()?;
+
+                    (metadata.is_file() && modified >= started && !is_checksum_file(&it.path()))
+                        .then(|| BuildArtifact {
+                            path: it.path(),
+                            size: metadata.len(),
+                            sha256: None,
+                        })
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    artifacts.sort_by(|a, b| a.path.cmp(&b.path));
+
+    artifacts
+}
+
+/// Print the built `artifacts`, followed by a summary of the build.
+fn print_build_summary(
+    artifacts: &[BuildArtifact],
+    start: Instant,
+    config: &Config,
+) -> HuakResult<()> {
+    let root = config.workspace_root.as_path();
+    let mut terminal = config.operation_terminal("build");
+
+    for it in artifacts {
+        terminal.print_custom(
+            "Built",
+            it.path.strip_prefix(root).unwrap_or(&it.path).display(),
+            Color::Green,
+            true,
+        )?;
+    }
+
+    terminal.print_summary(format!(
+        "built {} artifact{} in {}",
+        artifacts.len(),
+        if artifacts.len() == 1 { "" } else { "s" },
+        format_elapsed(start.elapsed())
+    ))
+}
+
+/// Run `cibuildwheel`, building wheels for the Python versions satisfying the project's
+/// `requires-python` unless the versions are already configured.
+fn run_cibuildwheel(
+    manifest: &LocalManifest,
+    python_env: &PythonEnvironment,
+    options: &BuildOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let mut terminal = config.operation_terminal("build");
+    let mut cmd = Command::new(python_env.python_path());
+    let mut args = vec!["-m", "cibuildwheel"];
+    if let Some(it) = options.values.as_ref() {
+        args.extend(it.iter().map(String::as_str));
+    }
+    if !args.iter().any(|it| it.starts_with("--output-dir")) {
+        args.extend(["--output-dir", CIBUILDWHEEL_OUTPUT_DIR]);
+    }
+    let env = operation_env("build", &options.env, config)?;
+    add_venv_to_command(&mut cmd, python_env)?;
+    cmd.args(args).envs(&env).current_dir(workspace.root());
+
+    let configured = std::env::var_os("CIBW_BUILD").is_some()
+        || env.contains_key("CIBW_BUILD")
+        || manifest
+            .manifest_data()
+            .tool_table()
+            .and_then(|it| it.get("cibuildwheel"))
+            .and_then(|it| it.get("build"))
+            .is_some();
+    let requires_python = manifest
+        .manifest_data()
+        .project_table()
+        .and_then(|it| it.get("requires-python"))
+        .and_then(|it| it.as_str())
+        .map(VersionSpecifiers::from_str)
+        .transpose()?;
+
+    if let Some(it) = requires_python
+        .as_ref()
+        .and_then(cibuildwheel_build_selector)
+        .filter(|_| !configured)
+    {
+        terminal.print_custom("Building", format!("wheels for {it}"), Color::Green, true)?;
+        cmd.env("CIBW_BUILD", it);
+    }
+
+    terminal.run_command(&mut cmd)
 }
 
 #[cfg(test)]
@@ -82,9 +442,55 @@ mod tests {
         initialize_venv(ws.root().join(".venv"), &ws.environment()).unwrap();
         let options = BuildOptions {
             values: None,
-            install_options: InstallOptions { values: None },
+            cibuildwheel: false,
+            env: HashMap::new(),
+            format: OutputFormat::default(),
+            checksums: false,
+            verify_contents: false,
+            from_clean: false,
+            allow_dirty: false,
+            install_options: InstallOptions::default(),
         };
 
         build_project(&config, &options).unwrap();
     }
+
+    #[test]
+    fn test_build_artifacts() {
+        let dir = tempdir().unwrap();
+        let started = SystemTime::now() - Duration::from_secs(1);
+        std::fs::write(dir.path().join("pkg-0.1.0.tar.gz"), [0; 4]).unwrap();
+        std::fs::write(dir.path().join("pkg-0.1.0-py3-none-any.whl"), [0; 8]).unwrap();
+        std::fs::write(dir.path().join("SHA256SUMS"), "").unwrap();
+        std::fs::create_dir(dir.path().join("tmp")).unwrap();
+
+        let report = BuildReport {
+            name: Some("pkg".to_string()),
+            version: Some("0.1.0".to_string()),
+            artifacts: build_artifacts(dir.path(), started),
+        };
+
+        assert_eq!(
+            report.artifacts,
+            [
+                BuildArtifact {
+                    path: dir.path().join("pkg-0.1.0-py3-none-any.whl"),
+                    size: 8,
+                    sha256: None,
+                },
+                BuildArtifact {
+                    path: dir.path().join("pkg-0.1.0.tar.gz"),
+                    size: 4,
+                    sha256: None,
+                },
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&report).unwrap()["artifacts"][1]["size"],
+            4
+        );
+        assert!(
+            build_artifacts(dir.path(), SystemTime::now() + Duration::from_secs(60)).is_empty()
+        );
+    }
 }
@@ -0,0 +1,132 @@
+use crate::{Config, Error, HuakResult};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use termcolor::Color;
+
+pub(crate) const SCRIPT_ENVIRONMENT_INFO_FILE_NAME: &str = "script.json";
+
+/// What a cached script environment was created for.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct ScriptEnvironmentInfo {
+    /// The Python interpreter the environment was created with.
+    pub python: PathBuf,
+    #[serde(rename = "requires-python")]
+    pub requires_python: Option<String>,
+    pub dependencies: Vec<String>,
+    /// The scripts that have been run with the environment.
+    pub scripts: Vec<PathBuf>,
+}
+
+/// Get the directory cached script environments are kept in.
+pub(crate) fn script_environments_dir(config: &Config) -> HuakResult<PathBuf> {
+    cache_dir(config).map(|it| it.join("scripts"))
+}
+
+pub(super) fn cache_dir(config: &Config) -> HuakResult<PathBuf> {
+    config
+        .home
+        .as_ref()
+        .map(|it| it.join("cache"))
+        .ok_or(Error::HuakHomeNotFound)
+}
+
+/// List the cached script environments.
+pub fn list_cache(config: &Config) -> HuakResult<()> {
+    let mut terminal = config.terminal();
+    let dir = script_environments_dir(config)?;
+    let mut entries = std::fs::read_dir(&dir)
+        .map(|it| {
+            it.filter_map(Result::ok)
+                .map(|it| it.path())
+                .filter(|it| it.is_dir())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    entries.sort();
+
+    if entries.is_empty() {
+        return terminal.print_custom("Empty", dir.display(), Color::Yellow, true);
+    }
+
+    for path in entries {
+        let key = path
+            .file_name()
+            .map(|it| it.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let info = std::fs::read_to_string(path.join(SCRIPT_ENVIRONMENT_INFO_FILE_NAME))
+            .ok()
+            .and_then(|it| serde_json::from_str::<ScriptEnvironmentInfo>(&it).ok())
+            .unwrap_or_default();
+
+        terminal.print_custom(
+            key,
+            format!(
+                "{} ({} dependencies, {})",
+                info.python.display(),
+                info.dependencies.len(),
+                format_size(dir_size(&path))
+            ),
+            Color::Green,
+            true,
+        )?;
+
+        for script in &info.scripts {
+            terminal.print_without_status(format!("  {}", script.display()), Color::White)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove everything in Huak's cache.
+pub fn clean_cache(config: &Config) -> HuakResult<()> {
+    let dir = cache_dir(config)?;
+
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    config.remove_path(dir)
+}
+
+pub(super) fn dir_size(path: &Path) -> u64 {
+    std::fs::read_dir(path).map_or(0, |it| {
+        it.filter_map(Result::ok)
+            .map(|it| match it.file_type() {
+                Ok(kind) if kind.is_dir() => dir_size(&it.path()),
+                Ok(kind) if kind.is_file() => it.metadata().map_or(0, |it| it.len()),
+                _ => 0,
+            })
+            .sum()
+    })
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub(super) fn format_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
@@ -0,0 +1,224 @@
+use super::project_version;
+use crate::{Config, Error, HuakResult};
+use lazy_static::lazy_static;
+use pep440_rs::Version;
+use regex::Regex;
+use std::{io::Write, path::PathBuf, str::FromStr};
+use termcolor::Color;
+
+const CHANGELOG_FILE_NAME: &str = "CHANGELOG.md";
+
+lazy_static! {
+    /// A release heading like `## [1.2.3] - 2024-01-01`, `## 1.2.3`, or `## [Unreleased]`.
+    static ref HEADING: Regex = Regex::new(r"^##\s+\[?v?([^\]\s]+)\]?(?:\s+-\s+\S.*)?$").unwrap();
+    /// A link reference definition like `[1.2.3]: https://...` at the end of a changelog.
+    static ref LINK_DEFINITION: Regex = Regex::new(r"^\[[^\]]+\]:\s").unwrap();
+}
+
+/// A release's section of a Keep a Changelog style changelog.
+#[derive(Debug, PartialEq, Eq)]
+struct ChangelogEntry<'a> {
+    /// The release's version, or `Unreleased`.
+    title: &'a str,
+    /// The section's contents without its heading or surrounding blank lines.
+    body: String,
+}
+
+impl ChangelogEntry<'_> {
+    fn is_unreleased(&self) -> bool {
+        self.title.eq_ignore_ascii_case("unreleased")
+    }
+
+    /// Check if the entry is for the `version`, comparing PEP 440 versions when both parse
+    /// (so `1.2` matches `1.2.0`).
+    fn is_version(&self, version: &str) -> bool {
+        match (Version::from_str(self.title), Version::from_str(version)) {
+            (Ok(it), Ok(version)) => it == version,
+            _ => self.title == version,
+        }
+    }
+}
+
+/// Print the workspace's CHANGELOG.md entry for the `version` (or the project's version).
+pub fn display_changelog_entry(version: Option<&str>, config: &Config) -> HuakResult<()> {
+    let version = match version {
+        Some(it) => it.to_string(),
+        None => project_version(config)?,
+    };
+    let contents = read_changelog(config)?;
+    let entry = changelog_entries(&contents)
+        .into_iter()
+        .find(|it| it.is_version(&version))
+        .ok_or(Error::ChangelogEntryNotFound { version })?;
+
+    let mut stdout = std::io::stdout();
+    writeln!(stdout, "{}", entry.body)?;
+
+    Ok(())
+}
+
+/// Check the workspace's CHANGELOG.md is ready to release the project's version: it has an
+/// entry for the version and its `Unreleased` section (if it has one) is empty.
+pub fn verify_changelog(config: &Config) -> HuakResult<()> {
+    let version = project_version(config)?;
+    let contents = read_changelog(config)?;
+    let entries = changelog_entries(&contents);
+
+    if entries
+        .iter()
+        .any(|it| it.is_unreleased() && !it.body.is_empty())
+    {
+        return Err(Error::UnreleasedChangelogEntries { version });
+    }
+
+    if !entries
+        .iter()
+        .any(|it| it.is_version(&version) && !it.body.is_empty())
+    {
+        return Err(Error::ChangelogEntryNotFound { version });
+    }
+
+    config.terminal().print_custom(
+        "Verified",
+        format!("{CHANGELOG_FILE_NAME} has an entry for {version}"),
+        Color::Green,
+        true,
+    )
+}
+
+fn read_changelog(config: &Config) -> HuakResult<String> {
+    let path = changelog_path(config);
+
+    std::fs::read_to_string(&path).map_err(|_| Error::ChangelogNotFound { path })
+}
+
+fn changelog_path(config: &Config) -> PathBuf {
+    config.workspace().root().join(CHANGELOG_FILE_NAME)
+}
+
+/// Get the release entries of a Keep a Changelog style changelog, in the order they're listed.
+fn changelog_entries(contents: &str) -> Vec<ChangelogEntry<'_>> {
+    let mut entries = Vec::new();
+    let mut current: Option<(&str, Vec<&str>)> = None;
+
+    for line in contents.lines() {
+        if let Some(captures) = HEADING.captures(line) {
+            entries.extend(current.take().map(changelog_entry));
+            current = Some((captures.get(1).map_or("", |it| it.as_str()), Vec::new()));
+        } else if let Some((.., lines)) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    entries.extend(current.map(changelog_entry));
+
+    entries
+}
+
+fn changelog_entry<'a>((title, lines): (&'a str, Vec<&'a str>)) -> ChangelogEntry<'a> {
+    let body = lines
+        .into_iter()
+        .filter(|it| !LINK_DEFINITION.is_match(it))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    ChangelogEntry {
+        title,
+        body: body.trim_matches('\n').trim_end().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TerminalOptions, Verbosity};
+    use tempfile::tempdir;
+
+    const CHANGELOG: &str = "# Changelog
+
+All notable changes to this project will be documented in this file.
+
+## [Unreleased]
+
+## [1.2.0] - 2024-01-01
+
+### Added
+
+- Shuffled tests.
+
+## 1.1.0
+
+- Fixed the thing.
+
+[unreleased]: https://github.com/org/project/compare/v1.2.0...HEAD
+[1.2.0]: https://github.com/org/project/compare/v1.1.0...v1.2.0
+";
+
+    #[test]
+    fn test_changelog_entries() {
+        let entries = changelog_entries(CHANGELOG);
+
+        assert_eq!(
+            entries,
+            [
+                ChangelogEntry {
+                    title: "Unreleased",
+                    body: String::new(),
+                },
+                ChangelogEntry {
+                    title: "1.2.0",
+                    body: "### Added\n\n- Shuffled tests.".to_string(),
+                },
+                ChangelogEntry {
+                    title: "1.1.0",
+                    body: "- Fixed the thing.".to_string(),
+                },
+            ]
+        );
+        assert!(entries[1].is_version("1.2"));
+        assert!(!entries[1].is_version("1.1.0"));
+    }
+
+    #[test]
+    fn test_verify_changelog() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root.clone(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let write = |version: &str, changelog: &str| {
+            std::fs::write(
+                root.join("pyproject.toml"),
+                format!("[project]\nname = \"mock-project\"\nversion = \"{version}\"\n"),
+            )
+            .unwrap();
+            std::fs::write(root.join(CHANGELOG_FILE_NAME), changelog).unwrap();
+        };
+
+        write("1.2.0", CHANGELOG);
+        assert!(verify_changelog(&config).is_ok());
+
+        write("1.3.0", CHANGELOG);
+        assert!(matches!(
+            verify_changelog(&config),
+            Err(Error::ChangelogEntryNotFound { version: it }) if it == "1.3.0"
+        ));
+
+        write(
+            "1.2.0",
+            &CHANGELOG.replace(
+                "## [Unreleased]\n",
+                "## [Unreleased]\n\n- Not released yet.\n",
+            ),
+        );
+        assert!(matches!(
+            verify_changelog(&config),
+            Err(Error::UnreleasedChangelogEntries { .. })
+        ));
+    }
+}
@@ -0,0 +1,270 @@
+#[cfg(unix)]
+use super::clean::is_skipped_dir;
+use super::{
+    active_overrides_warning, contents::dist_content_problems, environment_drift,
+    project_dependencies, tool_version_drift,
+};
+use crate::{
+    dependency_iter, deprecated_keys, metadata::manifest_metadata_problems, Config, Error,
+    HuakResult, LocalManifest, PolicyViolation, PythonEnvironment,
+};
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+use termcolor::Color;
+
+pub struct CheckOptions {
+    /// Enforce the dependency policies configured with `[tool.huak.policy]`.
+    pub policies: bool,
+}
+
+/// Check the project's metadata, file ownership, pinned tool versions, the groups its
+/// environments were built with, and the contents of its built artifacts, and its dependency
+/// policies if `options.policies` is `true`. Deprecated `[tool.huak]` keys and active dependency
+/// overrides are warned about.
+pub fn check_project(config: &Config, options: &CheckOptions) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let manifest = workspace.current_local_manifest()?;
+    let mut terminal = config.terminal();
+    let mut failures = 0;
+
+    for problem in manifest_metadata_problems(manifest.manifest_data()) {
+        terminal.print_error(problem)?;
+        failures += 1;
+    }
+
+    for warning in deprecated_keys(manifest.manifest_data()) {
+        terminal.print_warning(warning)?;
+    }
+
+    if let Some(warning) = active_overrides_warning(workspace.root())? {
+        terminal.print_warning(warning)?;
+    }
+
+    #[cfg(unix)]
+    if !crate::is_root() {
+        let paths = root_owned_paths(workspace.root());
+
+        if let Some(path) = paths.first() {
+            let owned = if paths.len() == 1 {
+                format!(
+                    "a file in the workspace is owned by root: {}",
+                    path.display()
+                )
+            } else {
+                format!(
+                    "{} files in the workspace are owned by root, like {}",
+                    paths.len(),
+                    path.display()
+                )
+            };
+            terminal.print_error(format!(
+                "{owned} (fix with `sudo chown -R \"$(id -un)\" {}`)",
+                workspace.root().display()
+            ))?;
+            failures += 1;
+        }
+    }
+
+    if !manifest.manifest_data().tool_version_pins().is_empty() {
+        match workspace.current_python_environment() {
+            Ok(python_env) => {
+                for problem in tool_version_drift(&manifest, &python_env)? {
+                    terminal.print_error(problem)?;
+                    failures += 1;
+                }
+            }
+            Err(Error::PythonEnvironmentNotFound) => (),
+            Err(e) => return Err(e),
+        }
+    }
+
+    for problem in environment_drift(config)? {
+        terminal.print_error(problem)?;
+        failures += 1;
+    }
+
+    // Artifacts already built for the current version are checked for missing files.
+    if let Ok(python_env) = workspace.current_python_environment() {
+        for problem in dist_content_problems(&manifest, &python_env, config)? {
+            terminal.print_error(problem)?;
+            failures += 1;
+        }
+    }
+
+    if options.policies {
+        if workspace.dependency_policy()?.is_empty() {
+            terminal
+                .print_warning("no dependency policies are configured in [tool.huak.policy]")?;
+        } else {
+            let python_env = workspace.current_python_environment()?;
+
+            for violation in policy_violations(&manifest, &python_env, config)? {
+                terminal.print_error(violation)?;
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        return Err(Error::ChecksFailed(failures));
+    }
+
+    terminal.print_custom("Success", "all checks passed", Color::Green, true)
+}
+
+/// Print a warning for each dependency policy the project's installed dependencies violate.
+///
+/// Checking is best-effort: it runs after the project has changed, so a policy that can't be
+/// checked is printed as a warning and never fails the command.
+pub(crate) fn warn_policy_violations(
+    manifest: &LocalManifest,
+    python_env: &PythonEnvironment,
+    config: &Config,
+) {
+    let mut terminal = config.terminal();
+
+    match policy_violations(manifest, python_env, config) {
+        Ok(violations) => {
+            for violation in violations {
+                terminal.print_warning(violation).ok();
+            }
+        }
+        Err(e) => {
+            terminal
+                .print_warning(format!("the dependency policies couldn't be checked: {e}"))
+                .ok();
+        }
+    }
+}
+
+/// Get the paths under `dir` owned by root, like the ones left behind by `sudo huak install`.
+/// Symlinks aren't followed, and the directories `huak clean` skips (like `.venv` and
+/// `node_modules`) are reported without walking into them.
+#[cfg(unix)]
+fn root_owned_paths(dir: &Path) -> Vec<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut paths = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+
+            if metadata.uid() == 0 {
+                paths.push(entry.path());
+            }
+
+            if metadata.is_dir() && !is_skipped_dir(&entry.path()) {
+                dirs.push(entry.path());
+            }
+        }
+    }
+
+    paths.sort();
+
+    paths
+}
+
+/// Get the violations of the workspace's dependency policies by the project's installed
+/// dependencies.
+pub(crate) fn policy_violations(
+    manifest: &LocalManifest,
+    python_env: &PythonEnvironment,
+    config: &Config,
+) -> HuakResult<Vec<PolicyViolation>> {
+    let policy = config.workspace().dependency_policy()?;
+
+    if policy.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let deps = project_dependencies(manifest)?;
+    let deps = deps
+        .required
+        .into_iter()
+        .chain(deps.optional.into_values().flatten());
+
+    let direct = dependency_iter(deps)
+        .map(|it| it.name().to_string())
+        .collect::<Vec<_>>();
+
+    Ok(policy.violations(&python_env.dependency_graph()?, &direct))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TerminalOptions, Verbosity};
+    use tempfile::tempdir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_root_owned_paths() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src").join("main.py"), "").unwrap();
+        std::fs::create_dir_all(dir.path().join(".venv").join("lib")).unwrap();
+        std::fs::create_dir_all(dir.path().join("node_modules").join("left-pad")).unwrap();
+
+        // Files are created by the user running the tests.
+        let paths = root_owned_paths(dir.path());
+
+        if crate::is_root() {
+            assert_eq!(
+                paths,
+                [
+                    dir.path().join(".venv"),
+                    dir.path().join("node_modules"),
+                    dir.path().join("src"),
+                    dir.path().join("src").join("main.py")
+                ]
+            );
+        } else {
+            assert!(paths.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_warn_policy_violations_malformed_policy() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::create_dir_all(root.join(".venv")).unwrap();
+        std::fs::write(root.join(".venv").join("pyvenv.cfg"), "version = 3.11.0\n").unwrap();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            r#"[project]
+name = "mock-project"
+dependencies = []
+
+[tool.huak]
+policy = "deny everything"
+"#,
+        )
+        .unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root.clone(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                strict: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let manifest = config.workspace().current_local_manifest().unwrap();
+        let python_env = PythonEnvironment::new(root.join(".venv")).unwrap();
+
+        warn_policy_violations(&manifest, &python_env, &config);
+
+        let warnings = config.terminal_options.promoted_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("the dependency policies couldn't be checked"));
+    }
+}
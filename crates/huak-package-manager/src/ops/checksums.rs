@@ -0,0 +1,215 @@
+use super::BuildArtifact;
+use crate::{Config, Error, HuakResult};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, fs::File, io::ErrorKind, path::Path};
+use termcolor::Color;
+
+/// The file listing the sha256 digests of the artifacts in a directory, in `sha256sum`'s format.
+pub(crate) const SHA256SUMS_FILE_NAME: &str = "SHA256SUMS";
+
+/// The extension of the file written next to each artifact with just its digest.
+const SHA256_EXTENSION: &str = "sha256";
+
+/// Check if a file in an artifact directory is a checksum file rather than an artifact.
+pub(crate) fn is_checksum_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|it| it == SHA256SUMS_FILE_NAME)
+        || path.extension().is_some_and(|it| it == SHA256_EXTENSION)
+}
+
+/// Record the sha256 digests of the built `artifacts` in the `SHA256SUMS` file of their
+/// directory `dir` and a `.sha256` file next to each, then print them.
+///
+/// Artifacts listed in an existing `SHA256SUMS` that weren't rebuilt are verified first, so an
+/// artifact changed since it was built isn't published by accident. Listed files that no
+/// longer exist are dropped.
+pub(crate) fn write_checksums(
+    dir: &Path,
+    artifacts: &mut [BuildArtifact],
+    operation: &str,
+    config: &Config,
+) -> HuakResult<()> {
+    let mut sums = read_checksums(dir)?;
+    sums.retain(|name, _| {
+        dir.join(name).is_file()
+            && !artifacts
+                .iter()
+                .any(|it| it.path.file_name().is_some_and(|it| it == name.as_str()))
+    });
+    verify_checksums(dir, &sums)?;
+
+    let mut terminal = config.operation_terminal(operation);
+    for it in artifacts.iter_mut() {
+        let Some(name) = it
+            .path
+            .file_name()
+            .map(|it| it.to_string_lossy().into_owned())
+        else {
+            continue;
+        };
+        let digest = file_sha256(&it.path)?;
+        let line = checksum_line(&digest, &name);
+
+        config.write_file(dir.join(format!("{name}.{SHA256_EXTENSION}")), &line)?;
+        terminal.print_custom("SHA256", line.trim_end(), Color::Green, true)?;
+        sums.insert(name, digest.clone());
+        it.sha256 = Some(digest);
+    }
+
+    let contents = sums
+        .iter()
+        .map(|(name, digest)| checksum_line(digest, name))
+        .collect::<String>();
+
+    config.write_file(dir.join(SHA256SUMS_FILE_NAME), &contents)
+}
+
+/// Verify the artifacts in `dir` against its `SHA256SUMS` file and record the checksums of the
+/// ones it doesn't list yet. Returns how many artifacts were verified.
+pub(crate) fn check_artifact_checksums(
+    dir: &Path,
+    operation: &str,
+    config: &Config,
+) -> HuakResult<usize> {
+    let mut sums = read_checksums(dir)?;
+    sums.retain(|name, _| dir.join(name).is_file());
+    let mut unlisted = std::fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter_map(|it| {
+            let path = it.path();
+            let metadata = it.metadata().ok()?;
+            let listed = sums.contains_key(it.file_name().to_string_lossy().as_ref());
+
+            (metadata.is_file() && !listed && !is_checksum_file(&path)).then_some(BuildArtifact {
+                path,
+                size: metadata.len(),
+                sha256: None,
+            })
+        })
+        .collect::<Vec<_>>();
+    unlisted.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if unlisted.is_empty() {
+        verify_checksums(dir, &sums)?;
+    } else {
+        write_checksums(dir, &mut unlisted, operation, config)?;
+    }
+
+    Ok(sums.len())
+}
+
+/// Check that each file in `dir` named in the `sums` has its digest.
+fn verify_checksums(dir: &Path, sums: &BTreeMap<String, String>) -> HuakResult<()> {
+    for (name, digest) in sums {
+        let path = dir.join(name);
+        if !file_sha256(&path)?.eq_ignore_ascii_case(digest) {
+            return Err(Error::ArtifactModified { path });
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the digests in the `SHA256SUMS` file of `dir`, keyed by file name.
+fn read_checksums(dir: &Path) -> HuakResult<BTreeMap<String, String>> {
+    let path = dir.join(SHA256SUMS_FILE_NAME);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(it) => it,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    contents
+        .lines()
+        .filter(|it| !it.trim().is_empty())
+        .map(|line| {
+            // `sha256sum` separates the digest and name with a space and a space (text mode)
+            // or `*` (binary mode).
+            let (digest, name) = line
+                .split_once(' ')
+                .filter(|(digest, _)| digest.len() == 64)
+                .ok_or_else(|| Error::InvalidChecksum(format!("{}: {line}", path.display())))?;
+            let name = name.strip_prefix([' ', '*']).unwrap_or(name);
+
+            Ok((name.to_string(), digest.to_string()))
+        })
+        .collect()
+}
+
+/// Format a line of a checksum file the way `sha256sum` writes it.
+fn checksum_line(digest: &str, name: &str) -> String {
+    format!("{digest}  {name}\n")
+}
+
+/// Get the sha256 digest of the file at `path`, reading it in chunks.
+fn file_sha256(path: &Path) -> HuakResult<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TerminalOptions, Verbosity};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_checksums() {
+        let dir = tempdir().unwrap();
+        let dir = dir.path();
+        let config = Config {
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let artifact = |name: &str, contents: &str| {
+            let path = dir.join(name);
+            std::fs::write(&path, contents).unwrap();
+            BuildArtifact {
+                path,
+                size: contents.len() as u64,
+                sha256: None,
+            }
+        };
+
+        let mut artifacts = [artifact("mock-0.1.0.tar.gz", "sdist")];
+        write_checksums(dir, &mut artifacts, "build", &config).unwrap();
+        let digest = artifacts[0].sha256.clone().unwrap();
+        assert_eq!(digest, hex::encode(Sha256::digest(b"sdist")));
+        assert_eq!(
+            std::fs::read_to_string(dir.join("mock-0.1.0.tar.gz.sha256")).unwrap(),
+            format!("{digest}  mock-0.1.0.tar.gz\n")
+        );
+
+        // Rebuilding one artifact keeps the sums of the others.
+        let mut artifacts = [artifact("mock-0.1.0-py3-none-any.whl", "wheel")];
+        write_checksums(dir, &mut artifacts, "build", &config).unwrap();
+        let sums = read_checksums(dir).unwrap();
+        assert_eq!(sums.len(), 2);
+        assert_eq!(
+            check_artifact_checksums(dir, "publish", &config).unwrap(),
+            2
+        );
+
+        // Artifacts that aren't listed yet are added.
+        artifact("mock-0.1.0-cp311-cp311-linux_x86_64.whl", "native wheel");
+        assert_eq!(
+            check_artifact_checksums(dir, "publish", &config).unwrap(),
+            2
+        );
+        assert_eq!(read_checksums(dir).unwrap().len(), 3);
+
+        // An artifact changed after it was built is caught.
+        std::fs::write(dir.join("mock-0.1.0.tar.gz"), "changed").unwrap();
+        assert!(matches!(
+            check_artifact_checksums(dir, "publish", &config),
+            Err(Error::ArtifactModified { .. })
+        ));
+        let mut artifacts = [artifact("mock-0.1.0-py3-none-any.whl", "wheel")];
+        assert!(write_checksums(dir, &mut artifacts, "build", &config).is_err());
+    }
+}
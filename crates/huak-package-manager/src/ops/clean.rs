@@ -1,50 +1,395 @@
-use crate::{Config, HuakResult};
+use super::{
+    cache::{dir_size, format_size},
+    distribution_name_version,
+};
+use crate::{directory_is_venv, git::tracked_files, history_file_path, Config, Error, HuakResult};
+use pep440_rs::Version;
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Condvar, Mutex, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use termcolor::Color;
 
+/// The most threads the workspace is walked with.
+const MAX_WALK_THREADS: usize = 8;
+
+/// How often progress is reported while walking a large workspace.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Directories the workspace walk doesn't descend into: version control metadata, virtual
+/// environments, and other tools' dependency trees.
+const SKIPPED_DIRS: [&str; 7] = [
+    ".git",
+    ".hg",
+    ".svn",
+    ".venv",
+    ".tox",
+    ".nox",
+    "node_modules",
+];
+
+#[allow(clippy::struct_excessive_bools)]
 pub struct CleanOptions {
     pub include_pycache: bool,
     pub include_compiled_bytecode: bool,
+    /// Remove the dependency history (see `huak history`).
+    pub include_history: bool,
+    /// The directory build artifacts were written to. Defaults to the workspace's configured
+    /// dist directory.
+    pub dist_dir: Option<PathBuf>,
+    /// Only remove the build artifacts that aren't for one of their package's N latest versions.
+    pub keep_latest: Option<usize>,
+    /// Remove paths git tracks too. By default they're kept so only build output is removed.
+    pub include_tracked: bool,
 }
 
 pub fn clean_project(config: &Config, options: &CleanOptions) -> HuakResult<()> {
     let workspace = config.workspace();
+    let dist_dir = options
+        .dist_dir
+        .as_ref()
+        .map_or_else(|| workspace.dist_dir(), |it| workspace.root().join(it));
+    let tracked = if options.include_tracked {
+        TrackedFiles::default()
+    } else {
+        TrackedFiles::new(workspace.root())
+    };
 
-    // Remove everything from the dist directory if it exists.
-    if workspace.root().join("dist").exists() {
-        std::fs::read_dir(workspace.root().join("dist"))?
-            .filter_map(|x| x.ok().map(|item| item.path()))
-            .for_each(|item| {
-                if item.is_dir() {
-                    std::fs::remove_dir_all(item).ok();
-                } else if item.is_file() {
-                    std::fs::remove_file(item).ok();
+    // Remove everything (or only outdated artifacts) from the dist directory if it exists. Only
+    // directories within the workspace are cleaned.
+    if dist_dir.exists() {
+        let root = dunce::canonicalize(workspace.root())?;
+        let dist_dir = dunce::canonicalize(dist_dir)?;
+        if dist_dir == root || !dist_dir.starts_with(&root) {
+            return Err(Error::DirectoryOutsideWorkspace { path: dist_dir });
+        }
+
+        if let Some(keep) = options.keep_latest {
+            for it in outdated_artifacts(&dist_dir, keep)? {
+                if !tracked.protects(&it) {
+                    config.remove_path(it)?;
                 }
-            });
+            }
+        } else {
+            std::fs::read_dir(dist_dir)?
+                .filter_map(|x| x.ok().map(|item| item.path()))
+                .filter(|item| !tracked.protects(item))
+                .for_each(|item| {
+                    config.remove_path(item).ok();
+                });
+        }
     }
 
-    // Remove all __pycache__ directories in the workspace if they exist.
-    if options.include_pycache {
-        let pattern = format!(
-            "{}",
-            workspace.root().join("**").join("__pycache__").display()
-        );
-        glob::glob(&pattern)?.for_each(|item| {
-            if let Ok(it) = item {
-                std::fs::remove_dir_all(it).ok();
+    // Remove all __pycache__ directories and .pyc files in the workspace if they exist. The
+    // workspace is walked instead of globbed so a root with glob characters (like `[`) is cleaned.
+    if options.include_pycache || options.include_compiled_bytecode {
+        clean_bytecode(config, options, &tracked)?;
+    }
+
+    // The dependency history is an audit log, so it's only removed when asked for.
+    if options.include_history {
+        let path = history_file_path(workspace.root());
+        if path.exists() && !tracked.protects(&path) {
+            config.remove_path(path)?;
+        }
+    }
+
+    let protected = tracked.protected.get();
+    if protected == 0 {
+        return Ok(());
+    }
+
+    config.terminal().print_custom(
+        "Kept",
+        format!(
+            "{protected} tracked {} (pass --include-tracked to remove them)",
+            if protected == 1 { "file" } else { "files" }
+        ),
+        Color::Yellow,
+        true,
+    )
+}
+
+/// The files git tracks in a workspace's repository, which cleaning keeps.
+#[derive(Debug, Default)]
+struct TrackedFiles {
+    files: HashSet<PathBuf>,
+    /// The number of tracked files under each directory containing one.
+    dirs: HashMap<PathBuf, usize>,
+    /// The number of tracked files kept so far.
+    protected: Cell<usize>,
+}
+
+impl TrackedFiles {
+    /// Get the files tracked in the repository containing `root`. Nothing is tracked outside a
+    /// git repository.
+    fn new(root: &Path) -> TrackedFiles {
+        let files = tracked_files(root)
+            .unwrap_or_default()
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let mut dirs = HashMap::new();
+        for it in &files {
+            for dir in it.ancestors().skip(1) {
+                *dirs.entry(dir.to_path_buf()).or_default() += 1;
             }
-        });
+        }
+
+        TrackedFiles {
+            files,
+            dirs,
+            protected: Cell::new(0),
+        }
     }
 
-    // Remove all .pyc files in the workspace if they exist.
-    if options.include_compiled_bytecode {
-        let pattern = format!("{}", workspace.root().join("**").join("*.pyc").display());
-        glob::glob(&pattern)?.for_each(|item| {
-            if let Ok(it) = item {
-                std::fs::remove_file(it).ok();
+    /// Check if removing `path` would remove tracked files, counting them as kept if it would.
+    fn protects(&self, path: &Path) -> bool {
+        if self.files.is_empty() {
+            return false;
+        }
+        let Ok(path) = dunce::canonicalize(path) else {
+            return false;
+        };
+
+        let count = if self.files.contains(&path) {
+            1
+        } else {
+            self.dirs.get(&path).copied().unwrap_or_default()
+        };
+        self.protected.set(self.protected.get() + count);
+
+        count > 0
+    }
+}
+
+/// Get the artifacts in `dist_dir` that aren't for one of their package's `keep` latest versions,
+/// ordering versions by PEP 440. Files that aren't wheels or source distributions are kept.
+fn outdated_artifacts(dist_dir: &Path, keep: usize) -> HuakResult<Vec<PathBuf>> {
+    let mut packages: HashMap<String, Vec<(Option<Version>, PathBuf)>> = HashMap::new();
+
+    for entry in std::fs::read_dir(dist_dir)?.filter_map(Result::ok) {
+        let path = entry.path();
+        let Some((name, version)) = path
+            .file_name()
+            .and_then(|it| it.to_str())
+            .and_then(distribution_name_version)
+            .filter(|_| path.is_file())
+        else {
+            continue;
+        };
+
+        packages
+            .entry(name)
+            .or_default()
+            .push((Version::from_str(&version).ok(), path));
+    }
+
+    let mut outdated = Vec::new();
+
+    for artifacts in packages.into_values() {
+        let mut versions = artifacts
+            .iter()
+            .map(|(it, _)| it.clone())
+            .collect::<Vec<_>>();
+        versions.sort();
+        versions.dedup();
+        let latest = &versions[versions.len().saturating_sub(keep)..];
+
+        outdated.extend(
+            artifacts
+                .into_iter()
+                .filter(|(it, _)| !latest.contains(it))
+                .map(|(_, path)| path),
+        );
+    }
+    outdated.sort();
+
+    Ok(outdated)
+}
+
+/// Remove the workspace's __pycache__ directories and .pyc files, reporting what was freed.
+///
+/// Each path is removed on its own, so an interrupted clean only leaves some bytecode behind
+/// for Python to use or regenerate.
+fn clean_bytecode(
+    config: &Config,
+    options: &CleanOptions,
+    tracked: &TrackedFiles,
+) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let mut terminal = config.terminal();
+    let mut last_report = Instant::now();
+    let walk = find_paths(
+        workspace.root(),
+        &|path: &Path| {
+            (options.include_pycache && path.file_name().is_some_and(|it| it == "__pycache__"))
+                || (options.include_compiled_bytecode
+                    && path.extension().is_some_and(|it| it == "pyc"))
+        },
+        &mut |scanned, found| {
+            if last_report.elapsed() >= PROGRESS_INTERVAL {
+                last_report = Instant::now();
+                terminal
+                    .print_custom(
+                        "Scanning",
+                        format!("{scanned} directories, {found} to remove"),
+                        Color::Cyan,
+                        true,
+                    )
+                    .ok();
             }
-        });
+        },
+    );
+
+    let mut removed = 0;
+    let mut freed = 0;
+    for it in walk.paths.iter().filter(|it| !tracked.protects(it)) {
+        let size = if it.is_dir() {
+            dir_size(it)
+        } else {
+            it.metadata().map_or(0, |it| it.len())
+        };
+
+        if config.remove_path(it).is_ok() {
+            removed += 1;
+            freed += size;
+        }
+    }
+
+    if removed == 0 {
+        return Ok(());
+    }
+
+    let status = if config.dry_run {
+        "Would free"
+    } else {
+        "Freed"
+    };
+    terminal.print_custom(
+        status,
+        format!(
+            "{} from {removed} paths ({} directories scanned)",
+            format_size(freed),
+            walk.scanned
+        ),
+        Color::Green,
+        true,
+    )
+}
+
+/// The paths a walk found.
+#[derive(Debug, Default)]
+struct Walk {
+    /// The matching paths, sorted.
+    paths: Vec<PathBuf>,
+    /// The number of directories read.
+    scanned: usize,
+}
+
+/// Collect the paths under `dir` that match, without walking into the directories that match.
+///
+/// Workspaces can have millions of files, so directories are read by a bounded pool of threads
+/// sharing a queue. `progress` is called with the directories scanned and the paths found so
+/// far while the walk runs.
+fn find_paths(
+    dir: &Path,
+    matches: &(impl Fn(&Path) -> bool + Sync),
+    progress: &mut impl FnMut(usize, usize),
+) -> Walk {
+    let threads = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(MAX_WALK_THREADS);
+    // The directories left to read and the number being read.
+    let queue = Mutex::new((vec![dir.to_path_buf()], 0usize));
+    let ready = Condvar::new();
+    let found = Mutex::new(Vec::new());
+    let scanned = AtomicUsize::new(0);
+    let found_count = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|_| {
+                scope.spawn(|| loop {
+                    let dir = {
+                        let mut state = queue.lock().unwrap_or_else(PoisonError::into_inner);
+                        loop {
+                            if let Some(it) = state.0.pop() {
+                                state.1 += 1;
+                                break it;
+                            }
+                            // Nothing is queued or being read, so nothing else will be queued.
+                            if state.1 == 0 {
+                                ready.notify_all();
+                                return;
+                            }
+                            state = ready.wait(state).unwrap_or_else(PoisonError::into_inner);
+                        }
+                    };
+
+                    let mut dirs = Vec::new();
+                    let mut paths = Vec::new();
+                    if let Ok(entries) = std::fs::read_dir(&dir) {
+                        for entry in entries.filter_map(Result::ok) {
+                            let path = entry.path();
+
+                            if matches(&path) {
+                                paths.push(path);
+                            } else if entry.file_type().is_ok_and(|it| it.is_dir())
+                                && !is_skipped_dir(&path)
+                            {
+                                dirs.push(path);
+                            }
+                        }
+                    }
+
+                    scanned.fetch_add(1, Ordering::Relaxed);
+                    found_count.fetch_add(paths.len(), Ordering::Relaxed);
+                    found
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .extend(paths);
+
+                    let mut state = queue.lock().unwrap_or_else(PoisonError::into_inner);
+                    state.0.extend(dirs);
+                    state.1 -= 1;
+                    ready.notify_all();
+                })
+            })
+            .collect::<Vec<_>>();
+
+        while !workers.iter().all(thread::ScopedJoinHandle::is_finished) {
+            thread::sleep(Duration::from_millis(50));
+            progress(
+                scanned.load(Ordering::Relaxed),
+                found_count.load(Ordering::Relaxed),
+            );
+        }
+    });
+
+    let mut paths = found.into_inner().unwrap_or_else(PoisonError::into_inner);
+    paths.sort();
+
+    Walk {
+        paths,
+        scanned: scanned.into_inner(),
     }
+}
 
-    Ok(())
+/// Check if the workspace walk skips the directory at `path`. Virtual environments are skipped
+/// whatever they're named.
+pub(super) fn is_skipped_dir(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| SKIPPED_DIRS.iter().any(|it| name == *it))
+        || directory_is_venv(path)
 }
 
 #[cfg(test)]
@@ -78,6 +423,10 @@ mod tests {
         let options = CleanOptions {
             include_pycache: true,
             include_compiled_bytecode: true,
+            include_history: false,
+            dist_dir: None,
+            keep_latest: None,
+            include_tracked: false,
         };
 
         clean_project(&config, &options).unwrap();
@@ -112,4 +461,344 @@ mod tests {
         assert!(pycaches.is_empty());
         assert!(bytecode.is_empty());
     }
+
+    #[test]
+    fn test_clean_project_configured_dist_dir() {
+        let dir = tempdir().unwrap();
+        copy_dir(
+            dev_resources_dir().join("mock-project"),
+            dir.path().join("mock-project"),
+            &CopyDirOptions::default(),
+        )
+        .unwrap();
+        let workspace_root = dir.path().join("mock-project");
+        let cwd = workspace_root.clone();
+        let terminal_options = TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+        let config = Config {
+            workspace_root,
+            cwd,
+            terminal_options,
+            ..Default::default()
+        };
+        let ws = config.workspace();
+        let mut manifest = ws.current_local_manifest().unwrap();
+        manifest.manifest_data_mut().tool_table_mut().unwrap()["huak"]["dist-dir"] =
+            toml_edit::value("out");
+        manifest.write_file().unwrap();
+        let artifact = ws.root().join("out").join("mock_project-0.0.1.tar.gz");
+        std::fs::create_dir_all(artifact.parent().unwrap()).unwrap();
+        std::fs::write(&artifact, "").unwrap();
+        let history = history_file_path(ws.root());
+        std::fs::create_dir_all(history.parent().unwrap()).unwrap();
+        std::fs::write(&history, "").unwrap();
+        let mut options = CleanOptions {
+            include_pycache: false,
+            include_compiled_bytecode: false,
+            include_history: false,
+            dist_dir: None,
+            keep_latest: None,
+            include_tracked: false,
+        };
+
+        clean_project(&config, &options).unwrap();
+
+        assert!(!artifact.exists());
+        assert!(history.exists());
+
+        options.include_history = true;
+        clean_project(&config, &options).unwrap();
+
+        assert!(!history.exists());
+    }
+
+    #[test]
+    fn test_clean_project_outside_workspace() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("mock-project")).unwrap();
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("keep.txt"), "").unwrap();
+        let workspace_root = dir.path().join("mock-project");
+        let cwd = workspace_root.clone();
+        let terminal_options = TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+        let config = Config {
+            workspace_root,
+            cwd,
+            terminal_options,
+            ..Default::default()
+        };
+        let options = CleanOptions {
+            include_pycache: false,
+            include_compiled_bytecode: false,
+            include_history: false,
+            dist_dir: Some(PathBuf::from("../outside")),
+            keep_latest: None,
+            include_tracked: false,
+        };
+
+        assert!(matches!(
+            clean_project(&config, &options),
+            Err(Error::DirectoryOutsideWorkspace { .. })
+        ));
+        assert!(outside.join("keep.txt").exists());
+    }
+
+    #[test]
+    fn test_clean_project_non_ascii_nested_workspace() {
+        let dir = tempdir().unwrap();
+        // A root with non-ASCII and glob characters that's longer than Windows' `MAX_PATH`.
+        let workspace_root = (0..20).fold(dir.path().join("проект [ü]"), |it, _| {
+            it.join("вложенная-папка")
+        });
+        let module = workspace_root.join("src").join("модуль");
+        std::fs::create_dir_all(module.join("__pycache__")).unwrap();
+        std::fs::write(module.join("__pycache__").join("ядро.cpython-311.pyc"), "").unwrap();
+        std::fs::write(module.join("ядро.pyc"), "").unwrap();
+        std::fs::write(module.join("ядро.py"), "").unwrap();
+        std::fs::create_dir_all(workspace_root.join("dist")).unwrap();
+        std::fs::write(workspace_root.join("dist").join("пакет-0.1.0.tar.gz"), "").unwrap();
+        let config = Config {
+            workspace_root: workspace_root.clone(),
+            cwd: workspace_root.clone(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let options = CleanOptions {
+            include_pycache: true,
+            include_compiled_bytecode: true,
+            include_history: false,
+            dist_dir: None,
+            keep_latest: None,
+            include_tracked: false,
+        };
+
+        assert!(workspace_root.as_os_str().len() > 260);
+
+        clean_project(&config, &options).unwrap();
+
+        assert!(!module.join("__pycache__").exists());
+        assert!(!module.join("ядро.pyc").exists());
+        assert!(module.join("ядро.py").exists());
+        assert_eq!(
+            std::fs::read_dir(workspace_root.join("dist"))
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_clean_project_skipped_dirs() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("mock-project");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            "[project]\nname = \"mock-project\"\nversion = \"0.0.1\"\n",
+        )
+        .unwrap();
+        let kept = [
+            root.join(".venv").join("lib").join("__pycache__"),
+            root.join("env").join("lib").join("__pycache__"),
+            root.join("node_modules").join("pkg").join("__pycache__"),
+        ];
+        for it in &kept {
+            std::fs::create_dir_all(it).unwrap();
+        }
+        std::fs::write(root.join("env").join("pyvenv.cfg"), "version = 3.11.0\n").unwrap();
+        std::fs::create_dir_all(root.join("src").join("__pycache__")).unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root.clone(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let options = CleanOptions {
+            include_pycache: true,
+            include_compiled_bytecode: false,
+            include_history: false,
+            dist_dir: None,
+            keep_latest: None,
+            include_tracked: true,
+        };
+
+        clean_project(&config, &options).unwrap();
+
+        assert!(!root.join("src").join("__pycache__").exists());
+        assert!(kept.iter().all(|it| it.exists()));
+    }
+
+    #[test]
+    fn test_clean_project_tracked_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let package = root.join("src").join("my_pkg");
+        std::fs::create_dir_all(package.join("__pycache__")).unwrap();
+        std::fs::write(package.join("__pycache__").join("a.cpython-311.pyc"), "").unwrap();
+        std::fs::write(package.join("vendored.pyc"), "").unwrap();
+        std::fs::write(package.join("stale.pyc"), "").unwrap();
+        let repo = crate::git_init(&root).unwrap();
+        let mut index = repo.index().unwrap();
+        index
+            .add_path(Path::new("src/my_pkg/vendored.pyc"))
+            .unwrap();
+        index.write().unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root,
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut options = CleanOptions {
+            include_pycache: true,
+            include_compiled_bytecode: true,
+            include_history: false,
+            dist_dir: None,
+            keep_latest: None,
+            include_tracked: false,
+        };
+
+        clean_project(&config, &options).unwrap();
+
+        assert!(!package.join("__pycache__").exists());
+        assert!(!package.join("stale.pyc").exists());
+        assert!(package.join("vendored.pyc").exists());
+
+        options.include_tracked = true;
+        clean_project(&config, &options).unwrap();
+
+        assert!(!package.join("vendored.pyc").exists());
+    }
+
+    #[test]
+    fn test_clean_project_keep_latest() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let dist = root.join("dist");
+        std::fs::create_dir_all(&dist).unwrap();
+        let artifacts = [
+            "my_pkg-0.9.0-py3-none-any.whl",
+            "my_pkg-0.10.0-py3-none-any.whl",
+            "my_pkg-0.10.0.tar.gz",
+            "my-pkg-0.2.0.tar.gz",
+            "other-1.0.0rc1.tar.gz",
+            "other-1.0.0.tar.gz",
+            "notes.txt",
+        ];
+        for it in artifacts {
+            std::fs::write(dist.join(it), "").unwrap();
+        }
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root,
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let options = CleanOptions {
+            include_pycache: false,
+            include_compiled_bytecode: false,
+            include_history: false,
+            dist_dir: None,
+            keep_latest: Some(1),
+            include_tracked: false,
+        };
+
+        clean_project(&config, &options).unwrap();
+
+        let mut remaining = std::fs::read_dir(&dist)
+            .unwrap()
+            .map(|it| it.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        remaining.sort();
+
+        assert_eq!(
+            remaining,
+            [
+                "my_pkg-0.10.0-py3-none-any.whl",
+                "my_pkg-0.10.0.tar.gz",
+                "notes.txt",
+                "other-1.0.0.tar.gz",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clean_project_deep_tree() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        // A wide and deep tree with bytecode at every level.
+        let mut expected = 0;
+        for branch in 0..20 {
+            let mut path = root.join(format!("pkg{branch}"));
+            for depth in 0..25 {
+                path = path.join(format!("mod{depth}"));
+                std::fs::create_dir_all(path.join("__pycache__")).unwrap();
+                std::fs::write(path.join("__pycache__").join("a.cpython-311.pyc"), "a").unwrap();
+                std::fs::write(path.join("b.pyc"), "b").unwrap();
+                std::fs::write(path.join("b.py"), "b").unwrap();
+                expected += 2;
+            }
+        }
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root.clone(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let options = CleanOptions {
+            include_pycache: true,
+            include_compiled_bytecode: true,
+            include_history: false,
+            dist_dir: None,
+            keep_latest: None,
+            include_tracked: false,
+        };
+        let matches = |path: &Path| {
+            path.file_name().is_some_and(|it| it == "__pycache__")
+                || path.extension().is_some_and(|it| it == "pyc")
+        };
+
+        let start = Instant::now();
+        let walk = find_paths(&root, &matches, &mut |_, _| {});
+        assert_eq!(walk.paths.len(), expected);
+        assert_eq!(walk.scanned, 1 + 20 + 20 * 25);
+        assert!(walk.paths.windows(2).all(|it| it[0] < it[1]));
+
+        clean_project(&config, &options).unwrap();
+        // Generous, but a walk that stops being parallel or revisits directories would blow it.
+        assert!(start.elapsed() < Duration::from_secs(30));
+
+        assert!(find_paths(&root, &matches, &mut |_, _| {}).paths.is_empty());
+        assert_eq!(
+            find_paths(
+                &root,
+                &|it: &Path| it.extension().is_some_and(|it| it == "py"),
+                &mut |_, _| {}
+            )
+            .paths
+            .len(),
+            20 * 25
+        );
+    }
 }
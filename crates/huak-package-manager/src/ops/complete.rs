@@ -0,0 +1,131 @@
+use super::run::{task_table, venv_executables};
+use crate::{dependency_iter, Config};
+use huak_pyproject_toml::PyProjectToml;
+use std::collections::BTreeSet;
+
+// Completions are requested on every <TAB>, so they fail silently and only read the manifest
+// (and the virtual environment's executables for `huak run`).
+
+/// Get the project's optional dependency groups.
+#[must_use]
+pub fn complete_groups(config: &Config) -> Vec<String> {
+    let Some(manifest) = manifest_data(config) else {
+        return Vec::new();
+    };
+
+    let mut groups = manifest
+        .project_optional_dependency_groups()
+        .unwrap_or_default();
+    groups.sort();
+
+    groups
+}
+
+/// Get the names of the project's declared dependencies, or only the ones in an optional
+/// dependency `group`.
+#[must_use]
+pub fn complete_dependencies(group: Option<&str>, config: &Config) -> Vec<String> {
+    let Some(manifest) = manifest_data(config) else {
+        return Vec::new();
+    };
+
+    let dependencies = match group {
+        Some(it) => manifest
+            .project_optional_dependencies()
+            .and_then(|mut groups| groups.remove(it))
+            .unwrap_or_default(),
+        None => manifest.project_dependencies().unwrap_or_default(),
+    };
+
+    dependency_iter(dependencies)
+        .map(|it| it.name().to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Get the tasks from `[tool.huak.task]` followed by the executables in the project's virtual
+/// environment.
+#[must_use]
+pub fn complete_run(config: &Config) -> Vec<String> {
+    let mut candidates = manifest_data(config)
+        .and_then(|it| {
+            task_table(&it).map(|it| it.iter().map(|(k, _)| k.to_string()).collect::<Vec<_>>())
+        })
+        .unwrap_or_default();
+    let executables = venv_executables(config)
+        .into_iter()
+        .filter(|it| !candidates.contains(it))
+        .collect::<Vec<_>>();
+    candidates.extend(executables);
+
+    candidates
+}
+
+fn manifest_data(config: &Config) -> Option<PyProjectToml> {
+    config
+        .workspace()
+        .current_local_manifest()
+        .ok()
+        .map(|it| it.manifest_data().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::venv_executables_dir_path;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_complete() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let bin = venv_executables_dir_path(root.join(".venv"));
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(root.join(".venv").join("pyvenv.cfg"), "version = 3.11.0\n").unwrap();
+        for it in ["activate", "activate.fish", "pytest", "python"] {
+            std::fs::write(bin.join(it), "").unwrap();
+        }
+        std::fs::write(
+            root.join("pyproject.toml"),
+            r#"[project]
+name = "mock-project"
+dependencies = ["requests>=2", "Click == 8.1.7"]
+
+[project.optional-dependencies]
+test = ["pytest"]
+dev = ["ruff", "mypy[reports]"]
+
+[tool.huak.task]
+lint = "ruff check ."
+pytest = "pytest -x"
+"#,
+        )
+        .unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root,
+            ..Default::default()
+        };
+
+        assert_eq!(complete_groups(&config), ["dev", "test"]);
+        assert_eq!(complete_dependencies(None, &config), ["Click", "requests"]);
+        assert_eq!(
+            complete_dependencies(Some("dev"), &config),
+            ["mypy", "ruff"]
+        );
+        assert!(complete_dependencies(Some("docs"), &config).is_empty());
+        assert_eq!(complete_run(&config), ["lint", "pytest", "python"]);
+
+        let outside = tempdir().unwrap();
+        let config = Config {
+            workspace_root: outside.path().to_path_buf(),
+            cwd: outside.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        assert!(complete_groups(&config).is_empty());
+        assert!(complete_dependencies(None, &config).is_empty());
+        assert!(complete_run(&config).is_empty());
+    }
+}
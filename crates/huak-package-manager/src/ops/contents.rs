@@ -0,0 +1,394 @@
+use super::{
+    files::{matches_any, patterns, source_files},
+    vendor::distribution_name_version,
+    BuildArtifact, FileFilter,
+};
+use crate::{
+    git::tracked_files, importable_package_name, Config, Error, HuakResult, LocalManifest,
+    PythonEnvironment,
+};
+use huak_pyproject_toml::canonical_name;
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use termcolor::Color;
+
+/// Print a JSON list of the files in each archive passed as an argument.
+const LIST_ARCHIVES_SCRIPT: &str = r#"import json
+import sys
+import tarfile
+import zipfile
+
+contents = []
+for path in sys.argv[1:]:
+    if path.endswith((".whl", ".zip")):
+        with zipfile.ZipFile(path) as archive:
+            contents.append([it for it in archive.namelist() if not it.endswith("/")])
+    else:
+        with tarfile.open(path) as archive:
+            contents.append([it.name for it in archive.getmembers() if it.isfile()])
+
+print(json.dumps(contents))
+"#;
+
+/// The files the built artifacts are expected to contain, relative to the workspace root.
+struct ExpectedContents {
+    /// The files expected in the sdist.
+    sdist: Vec<PathBuf>,
+    /// The files expected in a wheel, with the paths they're expected at in it.
+    wheel: Vec<(PathBuf, PathBuf)>,
+}
+
+/// Fail if the built `artifacts` are missing any of the project's files, printing what each one
+/// is missing.
+pub(crate) fn verify_artifact_contents(
+    artifacts: &[BuildArtifact],
+    manifest: &LocalManifest,
+    python_env: &PythonEnvironment,
+    operation: &str,
+    config: &Config,
+) -> HuakResult<()> {
+    let paths = artifacts
+        .iter()
+        .map(|it| it.path.clone())
+        .collect::<Vec<_>>();
+    let problems = missing_artifact_contents(&paths, manifest, python_env, config)?;
+    let mut terminal = config.operation_terminal(operation);
+
+    for it in &problems {
+        terminal.print_error(it)?;
+    }
+    if !problems.is_empty() {
+        return Err(Error::IncompleteArtifacts(problems.len()));
+    }
+
+    terminal.print_custom(
+        "Verified",
+        format!("the contents of {} artifacts", artifacts.len()),
+        Color::Green,
+        true,
+    )
+}
+
+/// Get what the artifacts in the dist directory built for the project's current version are
+/// missing, for `huak check`. Nothing is checked if none have been built.
+pub(crate) fn dist_content_problems(
+    manifest: &LocalManifest,
+    python_env: &PythonEnvironment,
+    config: &Config,
+) -> HuakResult<Vec<String>> {
+    let data = manifest.manifest_data();
+    let (Some(name), Some(version)) = (data.project_name(), data.project_version()) else {
+        return Ok(Vec::new());
+    };
+    let Ok(entries) = std::fs::read_dir(config.workspace().dist_dir()) else {
+        return Ok(Vec::new());
+    };
+    let mut artifacts = entries
+        .filter_map(Result::ok)
+        .map(|it| it.path())
+        .filter(|it| {
+            it.file_name()
+                .and_then(|it| distribution_name_version(&it.to_string_lossy()))
+                .is_some_and(|it| it == (canonical_name(&name), version.clone()))
+        })
+        .collect::<Vec<_>>();
+    artifacts.sort();
+
+    missing_artifact_contents(&artifacts, manifest, python_env, config)
+}
+
+/// Describe each of the project's files (or directories of them) the wheels and sdists in the
+/// `artifacts` are missing.
+fn missing_artifact_contents(
+    artifacts: &[PathBuf],
+    manifest: &LocalManifest,
+    python_env: &PythonEnvironment,
+    config: &Config,
+) -> HuakResult<Vec<String>> {
+    let artifacts = artifacts
+        .iter()
+        .filter(|it| artifact_kind(it).is_some())
+        .collect::<Vec<_>>();
+    if artifacts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let workspace = config.workspace();
+    let expected = expected_contents(workspace.root(), manifest, &workspace.build_contents())?;
+    let mut problems = Vec::new();
+
+    for (artifact, names) in artifacts
+        .iter()
+        .zip(archive_contents(python_env, &artifacts)?)
+    {
+        let (expected, missing): (Vec<PathBuf>, BTreeSet<PathBuf>) =
+            if artifact_kind(artifact) == Some(ArtifactKind::Wheel) {
+                let names = names.iter().map(PathBuf::from).collect::<BTreeSet<_>>();
+                let missing = expected
+                    .wheel
+                    .iter()
+                    .filter(|(_, it)| !names.contains(it))
+                    .map(|(it, _)| it.clone())
+                    .collect();
+
+                (
+                    expected.wheel.iter().map(|(it, _)| it.clone()).collect(),
+                    missing,
+                )
+            } else {
+                // An sdist's files are in a `{name}-{version}` directory.
+                let names = names
+                    .iter()
+                    .filter_map(|it| it.split_once('/').map(|(_, it)| PathBuf::from(it)))
+                    .collect::<BTreeSet<_>>();
+                let missing = expected
+                    .sdist
+                    .iter()
+                    .filter(|it| !names.contains(*it))
+                    .cloned()
+                    .collect();
+
+                (expected.sdist.clone(), missing)
+            };
+
+        let file_name = artifact
+            .file_name()
+            .map(|it| it.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        problems.extend(
+            describe_missing(&expected, &missing)
+                .into_iter()
+                .map(|it| format!("{file_name} is missing {it}")),
+        );
+    }
+
+    Ok(problems)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ArtifactKind {
+    Wheel,
+    Sdist,
+}
+
+fn artifact_kind(path: &Path) -> Option<ArtifactKind> {
+    let name = path.file_name()?.to_string_lossy();
+
+    if name.ends_with(".whl") {
+        Some(ArtifactKind::Wheel)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".zip") {
+        Some(ArtifactKind::Sdist)
+    } else {
+        None
+    }
+}
+
+/// Get the files the artifacts built from the workspace at `root` are expected to contain: the
+/// project's package (and its `pyproject.toml` and the `filter`'s included files for the
+/// sdist), without the `filter`'s excluded files.
+///
+/// Only files tracked by git are expected when the workspace is in a git repository. Bytecode
+/// and `__pycache__` directories are never expected.
+fn expected_contents(
+    root: &Path,
+    manifest: &LocalManifest,
+    filter: &FileFilter,
+) -> HuakResult<ExpectedContents> {
+    let files = match tracked_files(root) {
+        Some(tracked) => {
+            let root = dunce::canonicalize(root)?;
+            tracked
+                .into_iter()
+                .filter(|it| it.is_file())
+                .filter_map(|it| it.strip_prefix(&root).ok().map(Path::to_path_buf))
+                .collect()
+        }
+        None => source_files(root)?,
+    };
+    let include = patterns(&filter.include)?;
+    let exclude = patterns(&filter.exclude)?;
+    let package_dir = manifest
+        .manifest_data()
+        .project_name()
+        .map(|it| importable_package_name(&it))
+        .transpose()?
+        .and_then(|name| {
+            [PathBuf::from("src").join(&name), PathBuf::from(&name)]
+                .into_iter()
+                .find(|it| root.join(it).is_dir())
+        });
+
+    let files = files
+        .into_iter()
+        .filter(|it| !is_build_excluded(it) && !matches_any(&exclude, it))
+        .collect::<Vec<_>>();
+    let in_package = |path: &Path| package_dir.as_ref().is_some_and(|it| path.starts_with(it));
+
+    let sdist = files
+        .iter()
+        .filter(|it| {
+            in_package(it) || it.as_os_str() == "pyproject.toml" || matches_any(&include, it)
+        })
+        .cloned()
+        .collect();
+    let wheel = match package_dir.as_ref().and_then(|it| it.parent()) {
+        Some(parent) => files
+            .iter()
+            .filter(|it| in_package(it))
+            .filter_map(|it| Some((it.clone(), it.strip_prefix(parent).ok()?.to_path_buf())))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    Ok(ExpectedContents { sdist, wheel })
+}
+
+/// Check if a file is one builds leave out, like bytecode.
+fn is_build_excluded(path: &Path) -> bool {
+    path.components().any(|it| it.as_os_str() == "__pycache__")
+        || path
+            .extension()
+            .is_some_and(|it| it == "pyc" || it == "pyo")
+}
+
+/// Get the files in each of the `artifacts` with the `PythonEnvironment`'s Python.
+fn archive_contents(
+    python_env: &PythonEnvironment,
+    artifacts: &[&PathBuf],
+) -> HuakResult<Vec<Vec<String>>> {
+    let output = Command::new(python_env.python_path())
+        .args(["-c", LIST_ARCHIVES_SCRIPT])
+        .args(artifacts)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(Error::InternalError(format!(
+            "failed to read the built artifacts: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+/// Describe the `missing` files of the `expected` ones, naming a directory instead of its files
+/// when all of them are missing.
+fn describe_missing(expected: &[PathBuf], missing: &BTreeSet<PathBuf>) -> Vec<String> {
+    let mut described = BTreeSet::new();
+
+    for path in missing {
+        let mut description = path.display().to_string();
+
+        for dir in path
+            .ancestors()
+            .skip(1)
+            .take_while(|it| !it.as_os_str().is_empty())
+        {
+            let files = expected.iter().filter(|it| it.starts_with(dir)).count();
+            if !expected
+                .iter()
+                .filter(|it| it.starts_with(dir))
+                .all(|it| missing.contains(it))
+            {
+                break;
+            }
+
+            description = format!(
+                "{}/ ({files} file{})",
+                dir.display(),
+                if files == 1 { "" } else { "s" }
+            );
+        }
+
+        described.insert(description);
+    }
+
+    described.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_expected_contents() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for it in [
+            "pyproject.toml",
+            "README.md",
+            "src/mock_project/__init__.py",
+            "src/mock_project/templates/index.html",
+            "src/mock_project/fixtures/data.json",
+            "src/mock_project/__pycache__/__init__.cpython-311.pyc",
+            "tests/test_version.py",
+        ] {
+            let path = root.join(it);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        std::fs::write(
+            root.join("pyproject.toml"),
+            "[project]\nname = \"mock-project\"\nversion = \"0.0.1\"\n",
+        )
+        .unwrap();
+        let manifest = LocalManifest::new(root.join("pyproject.toml")).unwrap();
+        let filter = FileFilter {
+            include: vec!["tests".to_string()],
+            exclude: vec!["src/mock_project/fixtures".to_string()],
+        };
+
+        let expected = expected_contents(root, &manifest, &filter).unwrap();
+
+        assert_eq!(
+            expected.sdist,
+            [
+                "pyproject.toml",
+                "src/mock_project/__init__.py",
+                "src/mock_project/templates/index.html",
+                "tests/test_version.py",
+            ]
+            .map(PathBuf::from)
+        );
+        assert_eq!(
+            expected.wheel,
+            [
+                ("src/mock_project/__init__.py", "mock_project/__init__.py"),
+                (
+                    "src/mock_project/templates/index.html",
+                    "mock_project/templates/index.html"
+                ),
+            ]
+            .map(|(a, b)| (PathBuf::from(a), PathBuf::from(b)))
+        );
+    }
+
+    #[test]
+    fn test_describe_missing() {
+        let expected = [
+            "pkg/__init__.py",
+            "pkg/templates/a.html",
+            "pkg/templates/b.html",
+            "pkg/static/app.css",
+            "pkg/static/app.js",
+        ]
+        .map(PathBuf::from);
+        let missing = [
+            "pkg/templates/a.html",
+            "pkg/templates/b.html",
+            "pkg/static/app.js",
+        ]
+        .map(PathBuf::from)
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            describe_missing(&expected, &missing),
+            ["pkg/static/app.js", "pkg/templates/ (2 files)"]
+        );
+    }
+}
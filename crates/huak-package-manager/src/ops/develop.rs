@@ -0,0 +1,324 @@
+use super::install_project_dependencies;
+use crate::{dependency_iter, Config, Error, HuakResult, InstallOptions, PythonEnvironment};
+use huak_pyproject_toml::{canonical_name, PyProjectToml};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use termcolor::Color;
+
+/// The comment at the top of the overrides file.
+const OVERRIDES_HEADER: &str =
+    "# Local checkouts `huak develop` installs in place of the project's dependencies.
+# This file is specific to your machine and isn't committed.
+";
+
+/// Local checkouts installed editable in place of dependencies, keyed by canonical name.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct DependencyOverrides {
+    #[serde(default)]
+    overrides: BTreeMap<String, PathBuf>,
+}
+
+/// Get the path of the workspace's developer-local dependency overrides.
+fn overrides_file_path(root: &Path) -> PathBuf {
+    root.join(".huak").join("overrides.toml")
+}
+
+/// Install the project at `path` editable in place of the dependency `name`, without changing
+/// the manifest. The override is recorded in `.huak/overrides.toml` so it can be reapplied.
+pub fn add_dependency_override(
+    name: &str,
+    path: &Path,
+    options: &InstallOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let path = config.cwd.join(path);
+    let Ok(path) = dunce::canonicalize(&path) else {
+        return Err(Error::InvalidDependencyOverride(format!(
+            "{} doesn't exist",
+            path.display()
+        )));
+    };
+    check_override_source(name, &path)?;
+
+    let python_env = workspace.resolve_python_environment()?;
+    python_env.install_editable(&path, options, config)?;
+
+    let mut overrides = read_overrides(workspace.root())?;
+    overrides
+        .overrides
+        .insert(canonical_name(name), path.clone());
+    write_overrides(workspace.root(), &overrides, config)?;
+
+    config.terminal().print_custom(
+        "Overriding",
+        format!("{name} with {} (editable)", path.display()),
+        Color::Green,
+        true,
+    )
+}
+
+/// Print the active dependency overrides.
+pub fn list_dependency_overrides(config: &Config) -> HuakResult<()> {
+    let overrides = read_overrides(config.workspace().root())?;
+    let mut terminal = config.terminal();
+
+    if overrides.overrides.is_empty() {
+        return terminal.print_custom("Overrides", "none are active", Color::Green, true);
+    }
+
+    for (name, path) in &overrides.overrides {
+        if path.exists() {
+            terminal.print_custom(name, path.display(), Color::Green, true)?;
+        } else {
+            terminal.print_custom(
+                name,
+                format!("{} (missing)", path.display()),
+                Color::Yellow,
+                true,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the override of the dependency `name`, reinstalling the version the manifest declares.
+/// Packages the manifest doesn't declare (like transitive dependencies) are reinstalled by name.
+pub fn remove_dependency_override(
+    name: &str,
+    options: &InstallOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let mut overrides = read_overrides(workspace.root())?;
+    if overrides.overrides.remove(&canonical_name(name)).is_none() {
+        return Err(Error::DependencyOverrideNotFound {
+            name: name.to_string(),
+        });
+    }
+
+    let manifest = workspace.current_local_manifest()?;
+    let python_env = workspace.resolve_python_environment()?;
+    let mut declared = manifest
+        .manifest_data()
+        .project_dependencies()
+        .unwrap_or_default();
+    declared.extend(
+        manifest
+            .manifest_data()
+            .project_optional_dependencies()
+            .unwrap_or_default()
+            .into_values()
+            .flatten(),
+    );
+    let requirement = dependency_iter(&declared)
+        .find(|it| canonical_name(it.name()) == canonical_name(name))
+        .map_or_else(|| name.to_string(), |it| it.to_string());
+
+    python_env.uninstall_packages(&[name], options, config)?;
+    install_project_dependencies(
+        &python_env,
+        std::slice::from_ref(&requirement),
+        &manifest,
+        options,
+        config,
+    )?;
+    write_overrides(workspace.root(), &overrides, config)?;
+
+    config.terminal().print_custom(
+        "Removed",
+        format!("the override of {name} (reinstalled {requirement})"),
+        Color::Green,
+        true,
+    )
+}
+
+/// Install the active overrides editable again after the `python_env`'s packages were installed
+/// from the manifest, which replaces them.
+pub(super) fn reapply_dependency_overrides(
+    python_env: &PythonEnvironment,
+    options: &InstallOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    let overrides = read_overrides(config.workspace().root())?;
+    let mut terminal = config.terminal();
+
+    for (name, path) in &overrides.overrides {
+        if !path.exists() {
+            terminal.print_warning(format!(
+                "the override of {name} wasn't reapplied because {} doesn't exist (remove it with `huak develop remove {name}`)",
+                path.display()
+            ))?;
+            continue;
+        }
+
+        python_env.install_editable(path, options, config)?;
+        terminal.print_custom(
+            "Reapplied",
+            format!("the override of {name} with {}", path.display()),
+            Color::Green,
+            true,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Get a warning naming the active dependency overrides in the workspace at `root`, if there are
+/// any.
+pub(super) fn active_overrides_warning(root: &Path) -> HuakResult<Option<String>> {
+    let overrides = read_overrides(root)?;
+
+    if overrides.overrides.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(format!(
+        "dependency overrides are active, so the environment doesn't match the manifest: {} (see `huak develop list`)",
+        overrides
+            .overrides
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ")
+    )))
+}
+
+/// Check that `path` is a Python project, and that it's the project `name` if it says.
+fn check_override_source(name: &str, path: &Path) -> HuakResult<()> {
+    let pyproject = path.join("pyproject.toml");
+
+    if !pyproject.exists() && !path.join("setup.py").exists() {
+        return Err(Error::InvalidDependencyOverride(format!(
+            "{} isn't a Python project (it has no pyproject.toml or setup.py)",
+            path.display()
+        )));
+    }
+
+    if let Some(it) = PyProjectToml::read_toml(&pyproject)
+        .ok()
+        .and_then(|it| it.project_name())
+    {
+        if canonical_name(&it) != canonical_name(name) {
+            return Err(Error::InvalidDependencyOverride(format!(
+                "{} is the project {it}, not {name}",
+                path.display()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn read_overrides(root: &Path) -> HuakResult<DependencyOverrides> {
+    match std::fs::read_to_string(overrides_file_path(root)) {
+        Ok(it) => Ok(toml::from_str(&it)?),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(DependencyOverrides::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write the `overrides`, removing the file once none are left. The file is kept out of git
+/// with a `.gitignore` next to it, since `.huak` also holds files that are committed.
+fn write_overrides(
+    root: &Path,
+    overrides: &DependencyOverrides,
+    config: &Config,
+) -> HuakResult<()> {
+    let path = overrides_file_path(root);
+
+    if overrides.overrides.is_empty() {
+        return if path.exists() {
+            config.remove_path(&path)
+        } else {
+            Ok(())
+        };
+    }
+
+    let dir = root.join(".huak");
+    if !config.dry_run {
+        std::fs::create_dir_all(&dir)?;
+    }
+
+    let gitignore = dir.join(".gitignore");
+    let ignored = std::fs::read_to_string(&gitignore).unwrap_or_default();
+    if !ignored.lines().any(|it| it.trim() == "overrides.toml") {
+        let separator = if ignored.is_empty() || ignored.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        config.write_file(&gitignore, &format!("{ignored}{separator}overrides.toml\n"))?;
+    }
+
+    config.write_file(
+        &path,
+        &format!("{OVERRIDES_HEADER}{}", toml::to_string(overrides)?),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TerminalOptions, Verbosity};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_overrides() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let config = Config {
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        std::fs::create_dir(root.join(".huak")).unwrap();
+        std::fs::write(root.join(".huak").join(".gitignore"), "cache").unwrap();
+        let mut overrides = DependencyOverrides::default();
+        overrides
+            .overrides
+            .insert("requests".to_string(), root.join("requests-fork"));
+
+        write_overrides(root, &overrides, &config).unwrap();
+
+        assert_eq!(read_overrides(root).unwrap(), overrides);
+        assert_eq!(
+            std::fs::read_to_string(root.join(".huak").join(".gitignore")).unwrap(),
+            "cache\noverrides.toml\n"
+        );
+        assert!(active_overrides_warning(root)
+            .unwrap()
+            .is_some_and(|it| it.contains("requests")));
+
+        // The file is removed once the last override is.
+        write_overrides(root, &DependencyOverrides::default(), &config).unwrap();
+        assert!(!overrides_file_path(root).exists());
+        assert!(active_overrides_warning(root).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_check_override_source() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+
+        assert!(matches!(
+            check_override_source("requests", path),
+            Err(Error::InvalidDependencyOverride(..))
+        ));
+
+        std::fs::write(
+            path.join("pyproject.toml"),
+            "[project]\nname = \"Requests\"\n",
+        )
+        .unwrap();
+        assert!(check_override_source("requests", path).is_ok());
+        assert!(check_override_source("httpx", path).is_err());
+    }
+}
@@ -0,0 +1,308 @@
+use super::init::configured_groups;
+use crate::{directory_is_venv, Config, Error, HuakResult, PythonEnvironment};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use termcolor::Color;
+
+/// The file in a virtual environment recording what Huak built it with.
+const ENVIRONMENT_INFO_FILE_NAME: &str = "huak-env.json";
+
+/// What Huak built a project's environment with.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct EnvironmentInfo {
+    /// The optional dependency groups installed.
+    groups: Vec<String>,
+}
+
+/// Create the project's virtual environment with the `Config`'s `VenvOptions`.
+pub fn create_python_environment(config: &Config) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let mut terminal = config.terminal();
+
+    match workspace.current_python_environment() {
+        Ok(it) => {
+            return terminal.print_warning(format!(
+                "{} already exists (remove it to create it again)",
+                it.root().display()
+            ))
+        }
+        Err(Error::PythonEnvironmentNotFound) => (),
+        Err(e) => return Err(e),
+    }
+
+    match workspace.new_python_environment() {
+        Ok(it) => terminal.print_custom("Created", it.root().display(), Color::Green, true),
+        // Nothing is created during a dry run.
+        Err(Error::PythonEnvironmentNotFound) if config.dry_run => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Print the project's environments (the default one, then those configured in
+/// `[tool.huak.envs]`) with the groups each was built with. Environments built with other groups
+/// than are configured for them are highlighted.
+pub fn list_python_environments(config: &Config) -> HuakResult<()> {
+    let mut terminal = config.terminal();
+
+    for (name, config, path) in project_environments(config)? {
+        let label = name.as_deref().unwrap_or("default");
+        if !directory_is_venv(&path) {
+            terminal.print_custom(
+                label,
+                format!("{} (not created)", path.display()),
+                Color::Yellow,
+                true,
+            )?;
+            continue;
+        }
+
+        let Some(recorded) = recorded_groups(&path) else {
+            terminal.print_custom(label, path.display(), Color::Green, true)?;
+            continue;
+        };
+        match groups_drift(&config, &recorded)? {
+            Some(drift) => terminal.print_custom(
+                label,
+                format!("{} ({drift})", path.display()),
+                Color::Yellow,
+                true,
+            )?,
+            None => terminal.print_custom(
+                label,
+                format!("{} (groups: {})", path.display(), format_groups(&recorded)),
+                Color::Green,
+                true,
+            )?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the `Config`'s environment: a named one, or the project's default one.
+pub fn remove_python_environment(config: &Config) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let path = workspace.root().join(workspace.venv_name()?);
+
+    if !directory_is_venv(&path) {
+        return Err(Error::PythonEnvironmentNotFound);
+    }
+
+    config.remove_path(&path)?;
+
+    if config.dry_run {
+        return Ok(());
+    }
+
+    config
+        .terminal()
+        .print_custom("Removed", path.display(), Color::Green, true)
+}
+
+/// Record the optional dependency `groups` installed to the `python_env`, so changes to the
+/// groups configured for it can be detected.
+pub(super) fn record_environment_groups(
+    python_env: &PythonEnvironment,
+    groups: &[String],
+    config: &Config,
+) -> HuakResult<()> {
+    // The system's Python installation isn't Huak's to annotate.
+    if config.system_python {
+        return Ok(());
+    }
+
+    let mut groups = groups.to_vec();
+    groups.sort();
+    groups.dedup();
+    let info = serde_json::to_string_pretty(&EnvironmentInfo { groups })?;
+
+    config.write_file(
+        python_env.root().join(ENVIRONMENT_INFO_FILE_NAME),
+        &format!("{info}\n"),
+    )
+}
+
+/// Get a problem for each of the project's environments that was built with other groups than
+/// are configured for it.
+pub(super) fn environment_drift(config: &Config) -> HuakResult<Vec<String>> {
+    let mut problems = Vec::new();
+
+    for (name, config, path) in project_environments(config)? {
+        let Some(recorded) = recorded_groups(&path) else {
+            continue;
+        };
+        let Some(drift) = groups_drift(&config, &recorded)? else {
+            continue;
+        };
+        let (remove, init) = match name {
+            Some(it) => (
+                format!("huak env remove {it}"),
+                format!("huak init --env {it}"),
+            ),
+            None => ("huak env remove".to_string(), "huak init".to_string()),
+        };
+
+        problems.push(format!(
+            "{} was {drift} (recreate it with `{remove}` and `{init}`)",
+            path.display()
+        ));
+    }
+
+    Ok(problems)
+}
+
+/// Get the project's environments: the default one, then those configured in
+/// `[tool.huak.envs]`, each with a `Config` using it and its path.
+fn project_environments(config: &Config) -> HuakResult<Vec<(Option<String>, Config, PathBuf)>> {
+    let workspace = config.workspace();
+    let names = std::iter::once(None).chain(workspace.named_environments()?.into_keys().map(Some));
+
+    names
+        .map(|name| {
+            let config = Config {
+                env_name: name.clone(),
+                ..config.clone()
+            };
+            let path = workspace.root().join(config.workspace().venv_name()?);
+
+            Ok((name, config, path))
+        })
+        .collect()
+}
+
+/// Get the groups recorded for the environment at `path`, if it was built by Huak.
+fn recorded_groups(path: &Path) -> Option<Vec<String>> {
+    let info = std::fs::read_to_string(path.join(ENVIRONMENT_INFO_FILE_NAME)).ok()?;
+
+    serde_json::from_str::<EnvironmentInfo>(&info)
+        .ok()
+        .map(|it| it.groups)
+}
+
+/// Describe how the `recorded` groups differ from the groups configured for the `Config`'s
+/// environment. Environments without configured groups can't drift.
+fn groups_drift(config: &Config, recorded: &[String]) -> HuakResult<Option<String>> {
+    let manifest = config.workspace().current_local_manifest()?;
+    let Some((mut configured, source)) = configured_groups(config, manifest.manifest_data())?
+    else {
+        return Ok(None);
+    };
+    configured.sort();
+    configured.dedup();
+
+    if configured == recorded {
+        return Ok(None);
+    }
+
+    Ok(Some(format!(
+        "built with groups {} but {source} is {}",
+        format_groups(recorded),
+        format_groups(&configured)
+    )))
+}
+
+fn format_groups(groups: &[String]) -> String {
+    if groups.is_empty() {
+        "none".to_string()
+    } else {
+        groups.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TerminalOptions, VenvOptions, Verbosity};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_create_python_environment() {
+        let dir = tempdir().unwrap();
+        let workspace_root = dir.path().to_path_buf();
+        let cwd = workspace_root.clone();
+        let terminal_options = TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+        let config = Config {
+            workspace_root,
+            cwd,
+            terminal_options,
+            venv_options: VenvOptions {
+                system_site_packages: true,
+                prompt: Some("custom".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        create_python_environment(&config).unwrap();
+
+        let cfg = std::fs::read_to_string(dir.path().join(".venv").join("pyvenv.cfg")).unwrap();
+
+        assert!(cfg.contains("include-system-site-packages = true"));
+        assert!(cfg.contains("prompt = 'custom'"));
+    }
+
+    #[test]
+    fn test_environment_drift() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            r#"[project]
+name = "mock-project"
+version = "0.1.0"
+
+[project.optional-dependencies]
+docs = ["sphinx"]
+test = ["pytest"]
+
+[tool.huak.envs.docs]
+groups = ["docs"]
+
+[tool.huak.envs.test]
+groups = ["test"]
+"#,
+        )
+        .unwrap();
+        for (name, groups) in [(".venv-docs", r#"["docs"]"#), (".venv-test", r#"["docs"]"#)] {
+            let venv = root.join(name);
+            std::fs::create_dir(&venv).unwrap();
+            std::fs::write(venv.join("pyvenv.cfg"), "").unwrap();
+            std::fs::write(
+                venv.join(ENVIRONMENT_INFO_FILE_NAME),
+                format!(r#"{{"groups": {groups}}}"#),
+            )
+            .unwrap();
+        }
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root.clone(),
+            env_name: Some("docs".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.workspace().venv_name().unwrap(),
+            ".venv-docs".to_string()
+        );
+        assert_eq!(
+            environment_drift(&config).unwrap(),
+            [format!(
+                "{} was built with groups docs but [tool.huak.envs.test] groups is test \
+                (recreate it with `huak env remove test` and `huak init --env test`)",
+                root.join(".venv-test").display()
+            )]
+        );
+
+        let config = Config {
+            env_name: Some("lint".to_string()),
+            ..config
+        };
+        assert!(matches!(
+            config.workspace().venv_name(),
+            Err(Error::EnvironmentNotConfigured { .. })
+        ));
+    }
+}
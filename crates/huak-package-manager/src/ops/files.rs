@@ -0,0 +1,253 @@
+use crate::{directory_is_venv, HuakResult};
+use glob::{MatchOptions, Pattern};
+use std::path::{Path, PathBuf};
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Glob patterns choosing which of the workspace's Python files a tool runs on.
+///
+/// Patterns are relative to the workspace root. A pattern matching a directory matches
+/// everything in it.
+#[derive(Clone, Debug, Default)]
+pub struct FileFilter {
+    /// Only use files matching these patterns. Every Python file is used if there are none.
+    pub include: Vec<String>,
+    /// Skip files matching these patterns, even if they're included.
+    pub exclude: Vec<String>,
+}
+
+impl FileFilter {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Get the Python files under `root` the `FileFilter` selects, relative to `root`.
+    ///
+    /// Files ignored by the root's .gitignore and virtual environments are never selected.
+    pub(crate) fn files(&self, root: &Path) -> HuakResult<Vec<PathBuf>> {
+        let include = patterns(&self.include)?;
+        let exclude = patterns(&self.exclude)?;
+        let mut files = Vec::new();
+        walk(
+            root,
+            Path::new(""),
+            &gitignore_patterns(root),
+            true,
+            &mut files,
+        )?;
+
+        files.retain(|it| {
+            (include.is_empty() || matches_any(&include, it)) && !matches_any(&exclude, it)
+        });
+        files.sort();
+
+        Ok(files)
+    }
+
+    /// Get the paths to run a tool on from `root`: the selected files, or `.` without a filter.
+    pub(crate) fn paths(&self, root: &Path) -> HuakResult<Vec<PathBuf>> {
+        if self.is_empty() {
+            return Ok(vec![PathBuf::from(".")]);
+        }
+
+        self.files(root)
+    }
+}
+
+/// Get every file under `root` that isn't ignored, relative to `root`.
+pub(super) fn source_files(root: &Path) -> HuakResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    walk(
+        root,
+        Path::new(""),
+        &gitignore_patterns(root),
+        false,
+        &mut files,
+    )?;
+    files.sort();
+
+    Ok(files)
+}
+
+pub(super) fn patterns(values: &[String]) -> HuakResult<Vec<Pattern>> {
+    values
+        .iter()
+        .map(|it| {
+            Ok(Pattern::new(
+                it.trim_start_matches("./").trim_end_matches('/'),
+            )?)
+        })
+        .collect()
+}
+
+/// Check if any of the `patterns` match the `path` or one of its directories.
+pub(super) fn matches_any(patterns: &[Pattern], path: &Path) -> bool {
+    path.ancestors()
+        .filter(|it| !it.as_os_str().is_empty())
+        .any(|it| {
+            patterns
+                .iter()
+                .any(|p| p.matches_path_with(it, MATCH_OPTIONS))
+        })
+}
+
+/// Collect the files (or only the Python files) in `root.join(dir)` that aren't ignored,
+/// relative to `root`.
+fn walk(
+    root: &Path,
+    dir: &Path,
+    ignored: &[IgnorePattern],
+    python_only: bool,
+    files: &mut Vec<PathBuf>,
+) -> HuakResult<()> {
+    for entry in std::fs::read_dir(root.join(dir))?.filter_map(Result::ok) {
+        let path = dir.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            if entry.file_name() != ".git"
+                && !directory_is_venv(entry.path())
+                && !is_ignored(ignored, &path, true)
+            {
+                walk(root, &path, ignored, python_only, files)?;
+            }
+        } else if (!python_only || path.extension().is_some_and(|it| it == "py" || it == "pyi"))
+            && !is_ignored(ignored, &path, false)
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// A pattern from a .gitignore file.
+struct IgnorePattern {
+    pattern: Pattern,
+    /// Patterns with a `/` before their end only match paths from the root.
+    anchored: bool,
+    /// Patterns ending with a `/` only match directories.
+    dir_only: bool,
+}
+
+/// Get the patterns from the .gitignore at `root`. Negated patterns aren't supported.
+fn gitignore_patterns(root: &Path) -> Vec<IgnorePattern> {
+    let Ok(contents) = std::fs::read_to_string(root.join(".gitignore")) else {
+        return Vec::new();
+    };
+
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|it| !it.is_empty() && !it.starts_with('#') && !it.starts_with('!'))
+        .filter_map(|it| {
+            let dir_only = it.ends_with('/');
+            let it = it.trim_end_matches('/');
+            let anchored = it.contains('/');
+
+            Some(IgnorePattern {
+                pattern: Pattern::new(it.trim_start_matches('/')).ok()?,
+                anchored,
+                dir_only,
+            })
+        })
+        .collect()
+}
+
+fn is_ignored(patterns: &[IgnorePattern], path: &Path, is_dir: bool) -> bool {
+    patterns.iter().any(|it| {
+        if it.dir_only && !is_dir {
+            return false;
+        }
+
+        if it.anchored {
+            it.pattern.matches_path_with(path, MATCH_OPTIONS)
+        } else {
+            path.file_name()
+                .and_then(|it| it.to_str())
+                .is_some_and(|name| it.pattern.matches_with(name, MATCH_OPTIONS))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_file_filter_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for it in [
+            "src/pkg/__init__.py",
+            "src/pkg/api_pb2.py",
+            "src/pkg/core.py",
+            "src/pkg/core.pyi",
+            "src/pkg/data.json",
+            "tests/test_core.py",
+            "build/lib/pkg/core.py",
+            ".venv/lib/site.py",
+        ] {
+            let path = root.join(it);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        std::fs::write(root.join(".venv").join("pyvenv.cfg"), "").unwrap();
+        std::fs::write(root.join(".gitignore"), "build/\n*_pb2.py\n").unwrap();
+
+        let files = |include: &[&str], exclude: &[&str]| {
+            let filter = FileFilter {
+                include: include.iter().map(ToString::to_string).collect(),
+                exclude: exclude.iter().map(ToString::to_string).collect(),
+            };
+            filter
+                .files(root)
+                .unwrap()
+                .into_iter()
+                .map(|it| it.display().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            files(&[], &[]),
+            [
+                "src/pkg/__init__.py",
+                "src/pkg/core.py",
+                "src/pkg/core.pyi",
+                "tests/test_core.py"
+            ]
+        );
+        assert_eq!(
+            files(&["src"], &["**/*.pyi"]),
+            ["src/pkg/__init__.py", "src/pkg/core.py"]
+        );
+        assert_eq!(files(&["**/core.py"], &[]), ["src/pkg/core.py"]);
+        // Files that are both included and excluded are excluded.
+        assert!(files(&["tests/*.py"], &["tests"]).is_empty());
+    }
+
+    #[test]
+    fn test_file_filter_paths_non_ascii_nested_workspace() {
+        let dir = tempdir().unwrap();
+        let root = (0..20).fold(dir.path().join("проект"), |it, _| {
+            it.join("вложенная-папка")
+        });
+        let path = root.join("src").join("пакет").join("ядро.py");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "").unwrap();
+        let filter = FileFilter {
+            include: vec!["src/пакет".to_string()],
+            exclude: Vec::new(),
+        };
+
+        assert_eq!(
+            filter.paths(&root).unwrap(),
+            [PathBuf::from("src").join("пакет").join("ядро.py")]
+        );
+    }
+}
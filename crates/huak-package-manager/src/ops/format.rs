@@ -1,31 +1,44 @@
 use crate::{Config, Dependency, HuakResult, InstallOptions};
-use std::{process::Command, str::FromStr};
+use std::{collections::HashMap, process::Command, str::FromStr};
 
-use super::add_venv_to_command;
+use super::{
+    add_venv_to_command, install_locked_tool, operation_env, ruff_cache_key, tool_values,
+    FileFilter, ResultCache,
+};
 
 pub struct FormatOptions {
     /// A values vector of format options typically used for passing on arguments.
     pub values: Option<Vec<String>>,
+    /// The files to format. Every Python file in the workspace is formatted by default.
+    pub files: FileFilter,
+    /// Environment variables for the tool's subprocesses, merged over `[tool.huak.fmt] env`.
+    pub env: HashMap<String, String>,
+    /// Format every file, even those unchanged since they were last formatted.
+    pub no_cache: bool,
     pub install_options: InstallOptions,
 }
 
 pub fn format_project(config: &Config, options: &FormatOptions) -> HuakResult<()> {
     let workspace = config.workspace();
-    let mut manifest = workspace.current_local_manifest()?;
+    let paths = options.files.paths(workspace.root())?;
+
+    if paths.is_empty() {
+        return config
+            .operation_terminal("fmt")
+            .print_warning("no Python files match --include and --exclude");
+    }
+
     let python_env = workspace.resolve_python_environment()?;
 
-    // Install `ruff` it isn't already installed.
+    // Install `ruff` at its pinned version.
     let format_deps = [Dependency::from_str("ruff")?];
 
-    let new_format_deps = format_deps
-        .iter()
-        .filter(|dep| !python_env.contains_module(dep.name()).unwrap_or_default())
-        .collect::<Vec<_>>();
-
-    if !new_format_deps.is_empty() {
-        python_env.install_packages(&new_format_deps, &options.install_options, config)?;
+    for dep in &format_deps {
+        install_locked_tool(dep, &python_env, &options.install_options, config)?;
     }
 
+    let mut manifest = workspace.current_local_manifest()?;
+
     // Add the installed `ruff` package to the manifest file if not already there.
     let new_format_deps = format_deps
         .iter()
@@ -49,30 +62,72 @@ pub fn format_project(config: &Config, options: &FormatOptions) -> HuakResult<()
         }
     }
 
-    manifest.write_file()?;
+    config.write_manifest(&manifest)?;
+
+    // Skip the files that haven't changed since they were last formatted.
+    let files = options.files.files(workspace.root())?;
+    let mut cache = if options.no_cache {
+        ResultCache::disabled()
+    } else {
+        let key_args = options
+            .values
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .filter(|it| *it != "--check")
+            .collect::<Vec<_>>();
+        let key = ruff_cache_key(&python_env, workspace.root(), &key_args)?;
+        ResultCache::open("fmt", key, workspace.root(), config)
+    };
+    let changed = cache.changed(workspace.root(), &files);
+    cache.print_summary(files.len(), "fmt", config)?;
+    if changed.is_empty() && !files.is_empty() {
+        return Ok(());
+    }
+    let paths = if cache.hits() == 0 { paths } else { changed };
 
     // Run `ruff` for formatting imports and the rest of the Python code in the workspace.
     // NOTE: This needs to be refactored https://github.com/cnpryer/huak/issues/784, https://github.com/cnpryer/huak/issues/718
-    let mut terminal = config.terminal();
+    let env = operation_env("fmt", &options.env, config)?;
+    let mut terminal = config.operation_terminal("fmt");
     let mut cmd = Command::new(python_env.python_path());
     let mut ruff_cmd = Command::new(python_env.python_path());
-    let mut ruff_args = vec!["-m", "ruff", "check", ".", "--select", "I", "--fix"];
+    let mut ruff_args = vec!["--select", "I", "--fix"];
     add_venv_to_command(&mut cmd, &python_env)?;
     add_venv_to_command(&mut ruff_cmd, &python_env)?;
-    let mut args = vec!["-m", "ruff", "format", "."];
-    if let Some(v) = options.values.as_ref() {
-        args.extend(v.iter().map(String::as_str));
-        if v.contains(&"--check".to_string()) {
-            terminal.print_warning(
-                    "this check will exit early if imports aren't sorted (see https://github.com/cnpryer/huak/issues/510)",
-                )?;
-            ruff_args.retain(|item| *item != "--fix");
-        }
+    let values = tool_values(
+        options.values.as_deref().unwrap_or_default(),
+        workspace.root(),
+        config,
+    );
+    let check = values.iter().any(|it| it == "--check" || it == "--diff");
+    if check {
+        terminal.print_warning(
+            "this check will exit early if imports aren't sorted (see https://github.com/cnpryer/huak/issues/510)",
+        )?;
+        ruff_args.retain(|item| *item != "--fix");
+    }
+    ruff_cmd
+        .args(["-m", "ruff", "check"])
+        .args(&paths)
+        .args(ruff_args)
+        .envs(&env)
+        .current_dir(workspace.root());
+    cmd.args(["-m", "ruff", "format"])
+        .args(&paths)
+        .args(&values)
+        .envs(&env)
+        .current_dir(workspace.root());
+    if check {
+        terminal.run_command(&mut ruff_cmd)?;
+        terminal.run_command(&mut cmd)?;
+    } else {
+        // Formatting rewrites the project's code.
+        config.run_effect("fmt", &mut ruff_cmd)?;
+        config.run_effect("fmt", &mut cmd)?;
     }
-    ruff_cmd.args(ruff_args).current_dir(workspace.root());
-    terminal.run_command(&mut ruff_cmd)?;
-    cmd.args(args).current_dir(workspace.root());
-    terminal.run_command(&mut cmd)
+
+    cache.save(workspace.root(), &files, config)
 }
 
 #[cfg(test)]
@@ -112,7 +167,10 @@ def fn( ):
         std::fs::write(&fmt_filepath, pre_fmt_str).unwrap();
         let options = FormatOptions {
             values: None,
-            install_options: InstallOptions { values: None },
+            files: FileFilter::default(),
+            env: HashMap::new(),
+            no_cache: false,
+            install_options: InstallOptions::default(),
         };
 
         format_project(&config, &options).unwrap();
@@ -126,4 +184,45 @@ def fn( ):
 "
         );
     }
+
+    #[test]
+    fn test_format_project_dry_run() {
+        let dir = tempdir().unwrap();
+        copy_dir(
+            &dev_resources_dir().join("mock-project"),
+            &dir.path().join("mock-project"),
+            &CopyDirOptions::default(),
+        )
+        .unwrap();
+        let workspace_root = dir.path().join("mock-project");
+        let config = Config {
+            cwd: workspace_root.clone(),
+            workspace_root,
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        config.workspace().resolve_python_environment().unwrap();
+        let config = Config {
+            dry_run: true,
+            ..config
+        };
+        let ws = config.workspace();
+        let fmt_filepath = ws.root().join("src").join("mock_project").join("fmt_me.py");
+        let pre_fmt_str = "def fn( ):\n    pass";
+        std::fs::write(&fmt_filepath, pre_fmt_str).unwrap();
+        let options = FormatOptions {
+            values: None,
+            files: FileFilter::default(),
+            env: HashMap::new(),
+            no_cache: true,
+            install_options: InstallOptions::default(),
+        };
+
+        format_project(&config, &options).unwrap();
+
+        assert_eq!(std::fs::read_to_string(&fmt_filepath).unwrap(), pre_fmt_str);
+    }
 }
@@ -0,0 +1,114 @@
+use crate::{read_history, Config, DependencyChange, Error, HuakResult};
+use huak_pyproject_toml::canonical_name;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::io::Write;
+
+lazy_static! {
+    static ref DATE: Regex = Regex::new(r"^\d{4}-\d{2}-\d{2}(T\d{2}:\d{2}:\d{2}Z)?$").unwrap();
+}
+
+pub struct HistoryOptions {
+    /// Only show changes made on or after a date (`YYYY-MM-DD`) or UTC timestamp.
+    pub since: Option<String>,
+    /// Only show changes to a package.
+    pub package: Option<String>,
+    /// Print the raw JSON records instead.
+    pub json: bool,
+}
+
+/// Print the changes huak has made to the project's dependencies, oldest first.
+pub fn display_history(config: &Config, options: &HistoryOptions) -> HuakResult<()> {
+    if let Some(it) = options.since.as_ref().filter(|it| !DATE.is_match(it)) {
+        return Err(Error::InvalidDate(it.clone()));
+    }
+
+    let package = options.package.as_deref().map(canonical_name);
+    let mut stdout = std::io::stdout();
+
+    for (mut entry, line) in read_history(config)? {
+        // Timestamps are all UTC with the same format, so they sort like strings.
+        if options
+            .since
+            .as_ref()
+            .is_some_and(|it| entry.timestamp.as_str() < it.as_str())
+        {
+            continue;
+        }
+
+        if let Some(package) = package.as_ref() {
+            entry
+                .changes
+                .retain(|it| canonical_name(&it.name) == *package);
+
+            if entry.changes.is_empty() {
+                continue;
+            }
+        }
+
+        if options.json {
+            writeln!(stdout, "{line}")?;
+            continue;
+        }
+
+        let user = entry.user.map(|it| format!(" by {it}")).unwrap_or_default();
+        writeln!(stdout, "{} huak {}{user}", entry.timestamp, entry.command)?;
+
+        for it in &entry.changes {
+            writeln!(stdout, "  {}", format_change(it))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Format a `DependencyChange` like `~ requests==2.31.0 -> requests==2.32.0 (installed 2.32.0)`.
+fn format_change(change: &DependencyChange) -> String {
+    let mut s = match (&change.before, &change.after) {
+        (None, Some(after)) => format!("+ {after}"),
+        (Some(before), None) => format!("- {before}"),
+        (Some(before), Some(after)) => format!("~ {before} -> {after}"),
+        (None, None) => format!("~ {}", change.name),
+    };
+
+    if let Some(it) = change.group.as_ref() {
+        s = format!("{s} [{it}]");
+    }
+    if let Some(it) = change.installed.as_ref() {
+        s = format!("{s} (installed {it})");
+    }
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_change() {
+        let change = |before: Option<&str>, after: Option<&str>| DependencyChange {
+            name: "requests".to_string(),
+            group: None,
+            before: before.map(ToString::to_string),
+            after: after.map(ToString::to_string),
+            installed: after.map(|_| "2.32.0".to_string()),
+        };
+
+        assert_eq!(
+            format_change(&change(None, Some("requests"))),
+            "+ requests (installed 2.32.0)"
+        );
+        assert_eq!(
+            format_change(&change(Some("requests==2.31.0"), None)),
+            "- requests==2.31.0"
+        );
+        assert_eq!(
+            format_change(&DependencyChange {
+                group: Some("dev".to_string()),
+                ..change(Some("requests==2.31.0"), Some("requests==2.32.0"))
+            }),
+            "~ requests==2.31.0 -> requests==2.32.0 [dev] (installed 2.32.0)"
+        );
+    }
+}
@@ -1,61 +1,86 @@
-use toml_edit::{Item, Table};
-
-use super::init_git;
+use super::{
+    env::record_environment_groups, init_git, install_project_dependencies,
+    new::initial_commit_signature, project_dependencies, reapply_dependency_overrides,
+    scaffold::commit_template_files, unsatisfied_dependencies, write_template_files, ExistingFiles,
+    ProjectTemplate,
+};
 use crate::{
-    default_package_entrypoint_string, directory_is_venv, importable_package_name,
-    last_path_component, Config, Dependency, Error, HuakResult, InstallOptions, LocalManifest,
-    WorkspaceOptions,
+    dependency_iter, directory_is_venv, last_path_component, similar_names, Config, Error,
+    HuakResult, InstallOptions, LocalManifest, WorkspaceOptions,
 };
-use std::{path::PathBuf, str::FromStr};
+use huak_pyproject_toml::PyProjectToml;
+use std::path::PathBuf;
+use termcolor::Color;
+use toml_edit::Item;
 
 pub fn init_app_project(config: &Config, options: &WorkspaceOptions) -> HuakResult<()> {
-    init_lib_project(config, options)?;
+    init_project(ProjectTemplate::App, config, options)
+}
 
-    let workspace = config.workspace();
-    let mut manifest = workspace.current_local_manifest()?;
+pub fn init_lib_project(config: &Config, options: &WorkspaceOptions) -> HuakResult<()> {
+    init_project(ProjectTemplate::Lib, config, options)
+}
 
-    let Some(name) = manifest.manifest_data().project_name() else {
-        return Err(Error::InternalError("missing project name".to_string()));
-    };
-    let as_dep = Dependency::from_str(&name)?;
-    let _entry_point = default_package_entrypoint_string(&importable_package_name(as_dep.name())?);
+pub fn init_bare_project(config: &Config, options: &WorkspaceOptions) -> HuakResult<()> {
+    init_project(ProjectTemplate::Bare, config, options)
+}
 
-    if let Some(table) = manifest.manifest_data_mut().project_table_mut() {
-        let scripts = &mut table["scripts"];
+pub fn init_cli_project(config: &Config, options: &WorkspaceOptions) -> HuakResult<()> {
+    init_project(ProjectTemplate::Cli, config, options)
+}
 
-        if scripts.is_none() {
-            *scripts = Item::Table(Table::new());
-        }
+/// Initialize a project in an existing directory. Only the manifest (and .gitignore) is generated
+/// since the directory is expected to already contain the project's code.
+///
+/// An existing manifest is only overwritten with `options.force`. Each file that's overwritten
+/// is backed up to `<name>.bak` first.
+fn init_project(
+    template: ProjectTemplate,
+    config: &Config,
+    options: &WorkspaceOptions,
+) -> HuakResult<()> {
+    let workspace = config.workspace();
 
-        let importable = importable_package_name(&name)?;
-        scripts[name] = toml_edit::value(format!("{importable}.main:main"));
+    // Error if a manifest file already exists.
+    if workspace.current_local_manifest().is_ok() && !options.force {
+        return Err(Error::ManifestFileFound);
     }
 
-    manifest.write_file()
-}
+    let signature = initial_commit_signature(&config.workspace_root, options)?;
+    let name = last_path_component(&config.workspace_root)?;
+    let files = template.files(&name, options.uses_git, false)?;
 
-pub fn init_lib_project(config: &Config, options: &WorkspaceOptions) -> HuakResult<()> {
-    let workspace = config.workspace();
+    if options.uses_git && !config.dry_run {
+        init_git(&config.workspace_root)?;
+    }
 
-    // Create a manifest file or error if one already exists.
-    let mut manifest = match workspace.current_local_manifest() {
-        Ok(_) => return Err(Error::ManifestFileFound),
-        Err(_) => LocalManifest::template(workspace.root().join("pyproject.toml")),
+    let existing = if options.force {
+        ExistingFiles::Backup
+    } else {
+        ExistingFiles::Error
     };
 
-    if options.uses_git {
-        init_git(&config.workspace_root)?;
+    let written = write_template_files(&config.workspace_root, &files, existing, config)?;
+
+    match (options.initial_commit.as_deref(), signature) {
+        (Some(message), Some(signature)) => commit_template_files(
+            &config.workspace_root,
+            &written,
+            message,
+            &signature,
+            config,
+        ),
+        _ => Ok(()),
     }
-
-    let name = last_path_component(&config.workspace_root)?;
-    manifest.manifest_data_mut().set_project_name(&name);
-    manifest.write_file()
 }
 
 // TODO(cnpryer): Remove current huak install ops
+/// Install the project's dependencies. Without `optional_dependencies` the default groups are
+/// installed along with the required dependencies, or every group if there are no defaults.
 pub fn init_python_env(
     manifest: Option<PathBuf>,
     optional_dependencies: Option<Vec<String>>,
+    no_default_groups: bool,
     force: bool,
     options: &InstallOptions,
     config: &Config,
@@ -68,7 +93,9 @@ pub fn init_python_env(
         .file_name()
         .is_some_and(|it| !it.eq_ignore_ascii_case("pyproject.toml"))
     {
-        return Err(Error::ManifestFileNotSupported(manifest_path));
+        return Err(Error::ManifestFileNotSupported {
+            path: manifest_path,
+        });
     }
 
     manifest_path.set_file_name("pyproject.toml");
@@ -79,48 +106,81 @@ pub fn init_python_env(
             .print_warning("a manifest file could not be resolved");
     };
 
+    // Projects managed by Poetry have their dependencies read from `[tool.poetry]`.
+    let project_deps = project_dependencies(&manifest)?;
     let mut dependencies = Vec::new();
+    // The groups installed, recorded in the environment.
+    let mut groups = Vec::new();
+    let default_groups = if optional_dependencies.is_some() {
+        None
+    } else if no_default_groups {
+        Some((Vec::new(), "--no-default-groups".to_string()))
+    } else {
+        configured_groups(config, manifest.manifest_data())?
+    };
 
     if let Some(gs) = optional_dependencies {
         // If the group "required" is passed and isn't a valid optional dependency group
         // then install just the required dependencies.
         // TODO(cnpryer): Refactor/move
-        if manifest
-            .manifest_data()
-            .project_optional_dependency_groups()
-            .map_or(false, |it| it.iter().any(|s| s == "required"))
-        {
-            if let Some(reqs) = manifest.manifest_data().project_dependencies() {
-                dependencies.extend(reqs);
-            }
-        } else if let Some(optional_deps) = manifest.manifest_data().project_optional_dependencies()
-        {
+        if project_deps.optional.contains_key("required") {
+            dependencies.extend(project_deps.required);
+        } else if !project_deps.optional.is_empty() {
             for g in gs {
                 // TODO(cnpryer): Perf
-                if let Some(deps) = optional_deps.get(&g.to_string()) {
+                if let Some(deps) = project_deps.optional.get(&g.to_string()) {
                     dependencies.extend(deps.iter().cloned());
+                    groups.push(g);
+                } else {
+                    let mut message = format!("optional dependency group {g} not found");
+                    let similar = similar_names(&g, project_deps.optional.keys());
+                    if !similar.is_empty() {
+                        message = format!("{message}; did you mean {}?", similar.join(" or "));
+                    }
+                    config.terminal().print_warning(message)?;
                 }
             }
         }
+    } else if let Some((gs, source)) = default_groups {
+        dependencies.extend(project_deps.required);
+
+        for g in &gs {
+            let Some(deps) = project_deps.optional.get(g) else {
+                let mut available = project_deps.optional.keys().cloned().collect::<Vec<_>>();
+                available.sort();
+                return Err(Error::OptionalDependencyGroupNotFound {
+                    group: g.clone(),
+                    origin: Some(source.to_string()),
+                    available,
+                    suggestions: Vec::new(),
+                });
+            };
+            dependencies.extend(deps.iter().cloned());
+        }
+
+        config.terminal().print_custom(
+            "Groups",
+            format!(
+                "{} (from {source})",
+                if gs.is_empty() {
+                    "none".to_string()
+                } else {
+                    gs.join(", ")
+                }
+            ),
+            Color::Cyan,
+            true,
+        )?;
+        groups = gs;
     } else {
         // If no groups are passed then install all dependencies listed in the manifest file
         // including the optional dependencies.
-        if let Some(reqs) = manifest.manifest_data().project_dependencies() {
-            dependencies.extend(reqs);
-        }
+        dependencies.extend(project_deps.required);
 
         // TODO(cnpryer): Install optional as opt-in
-        if let Some(groups) = manifest
-            .manifest_data()
-            .project_optional_dependency_groups()
-        {
-            for key in groups {
-                if let Some(g) = manifest.manifest_data().project_optional_dependencies() {
-                    if let Some(it) = g.get(&key) {
-                        dependencies.extend(it.iter().cloned());
-                    }
-                }
-            }
+        for (g, deps) in project_deps.optional {
+            dependencies.extend(deps);
+            groups.push(g);
         }
     }
 
@@ -134,11 +194,11 @@ pub fn init_python_env(
     if force {
         // Remove the current Python virtual environment if one exists.
         match ws.current_python_environment() {
-            Ok(it) if directory_is_venv(it.root()) => std::fs::remove_dir_all(it.root())?,
+            Ok(it) if directory_is_venv(it.root()) => config.remove_path(it.root())?,
             // TODO(cnpryer): This might be a clippy bug.
             #[allow(clippy::no_effect)]
             Ok(_)
-            | Err(Error::PythonEnvironmentNotFound | Error::UnsupportedPythonEnvironment(_)) => {
+            | Err(Error::PythonEnvironmentNotFound | Error::UnsupportedPythonEnvironment { .. }) => {
                 ();
             }
             Err(e) => return Err(e),
@@ -146,17 +206,93 @@ pub fn init_python_env(
     }
 
     let python_env = ws.resolve_python_environment()?;
-    python_env.install_packages(&dependencies, options, config)
+    let deps = dependency_iter(&dependencies).collect::<Vec<_>>();
+    let unsatisfied = unsatisfied_dependencies(&python_env, &deps, config)?;
+
+    if unsatisfied.is_empty() {
+        record_environment_groups(&python_env, &groups, config)?;
+        return config.terminal().print_custom(
+            "Skipped",
+            "already initialized, the environment satisfies the project's dependencies",
+            Color::Green,
+            true,
+        );
+    }
+
+    let unsatisfied = unsatisfied
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    install_project_dependencies(&python_env, &unsatisfied, &manifest, options, config)?;
+    reapply_dependency_overrides(&python_env, options, config)?;
+    record_environment_groups(&python_env, &groups, config)
+}
+
+/// The environment variable that overrides `[tool.huak] default-groups`, as a comma-separated
+/// list. Set it to an empty string to only install the required dependencies.
+const DEFAULT_GROUPS_VAR: &str = "HUAK_DEFAULT_GROUPS";
+
+/// Get the optional dependency groups to install to the `Config`'s environment when none are
+/// passed, and where they're configured: a named environment's groups, or the default groups.
+pub(super) fn configured_groups(
+    config: &Config,
+    data: &PyProjectToml,
+) -> HuakResult<Option<(Vec<String>, String)>> {
+    if let Some(name) = config.env_name.as_ref() {
+        let Some(groups) = config.workspace().named_environments()?.remove(name) else {
+            return Err(Error::EnvironmentNotConfigured { name: name.clone() });
+        };
+
+        return Ok(Some((groups, format!("[tool.huak.envs.{name}] groups"))));
+    }
+
+    Ok(default_groups(data, std::env::var(DEFAULT_GROUPS_VAR).ok())
+        .map(|(groups, source)| (groups, source.to_string())))
+}
+
+/// Get the optional dependency groups to install when none are passed, and where they're
+/// configured. `var` is the value of `HUAK_DEFAULT_GROUPS`, which takes precedence over the
+/// manifest.
+///
+/// ```toml
+/// [tool.huak]
+/// default-groups = ["dev"]
+/// ```
+fn default_groups(
+    data: &PyProjectToml,
+    var: Option<String>,
+) -> Option<(Vec<String>, &'static str)> {
+    if let Some(var) = var {
+        let groups = var
+            .split(',')
+            .map(str::trim)
+            .filter(|it| !it.is_empty())
+            .map(ToString::to_string)
+            .collect();
+
+        return Some((groups, DEFAULT_GROUPS_VAR));
+    }
+
+    let groups = data
+        .huak_table()
+        .and_then(|it| it.get("default-groups"))
+        .and_then(Item::as_array)?
+        .iter()
+        .filter_map(|it| it.as_str().map(ToString::to_string))
+        .collect();
+
+    Some((groups, "[tool.huak] default-groups"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        copy_dir, default_pyproject_toml_contents, initialize_venv, CopyDirOptions, Package,
-        TerminalOptions, Verbosity,
+        copy_dir, default_pyproject_toml_contents, initialize_venv, venv_executables_dir_path,
+        CopyDirOptions, Package, TerminalOptions, Verbosity,
     };
     use huak_dev::dev_resources_dir;
+    use std::str::FromStr;
     use tempfile::tempdir;
 
     #[test]
@@ -177,7 +313,9 @@ mod tests {
         };
         let options = WorkspaceOptions {
             uses_git: false,
+            force: false,
             values: None,
+            initial_commit: None,
         };
         init_lib_project(&config, &options).unwrap();
 
@@ -190,6 +328,94 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_init_project_existing_manifest() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("mock-project")).unwrap();
+        let workspace_root = dir.path().join("mock-project");
+        let cwd = workspace_root.clone();
+        let terminal_options = TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+        let config = Config {
+            workspace_root: workspace_root.clone(),
+            cwd,
+            terminal_options,
+            ..Default::default()
+        };
+        let mut options = WorkspaceOptions {
+            uses_git: false,
+            force: false,
+            values: None,
+            initial_commit: None,
+        };
+        let existing = "[project]\nname = \"existing\"\n";
+        std::fs::write(workspace_root.join("pyproject.toml"), existing).unwrap();
+
+        assert!(matches!(
+            init_bare_project(&config, &options),
+            Err(Error::ManifestFileFound)
+        ));
+        assert!(!workspace_root.join("pyproject.toml.bak").exists());
+
+        options.force = true;
+        init_bare_project(&config, &options).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(workspace_root.join("pyproject.toml.bak")).unwrap(),
+            existing
+        );
+        assert_eq!(
+            std::fs::read_to_string(workspace_root.join("pyproject.toml")).unwrap(),
+            default_pyproject_toml_contents("mock-project")
+        );
+    }
+
+    #[test]
+    fn test_init_project_force_backs_up_files() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("mock-project")).unwrap();
+        let workspace_root = dir.path().join("mock-project");
+        let config = Config {
+            workspace_root: workspace_root.clone(),
+            cwd: workspace_root.clone(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let options = WorkspaceOptions {
+            uses_git: false,
+            force: true,
+            values: None,
+            initial_commit: None,
+        };
+        let package = workspace_root.join("src").join("mock_project");
+        std::fs::create_dir_all(&package).unwrap();
+        std::fs::write(workspace_root.join("pyproject.toml"), "[project]\n").unwrap();
+        std::fs::write(package.join("cli.py"), "print('mine')\n").unwrap();
+
+        init_cli_project(&config, &options).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(workspace_root.join("pyproject.toml.bak")).unwrap(),
+            "[project]\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(package.join("cli.py.bak")).unwrap(),
+            "print('mine')\n"
+        );
+        assert_ne!(
+            std::fs::read_to_string(package.join("cli.py")).unwrap(),
+            "print('mine')\n"
+        );
+        // Files that didn't exist aren't backed up.
+        assert!(package.join("__main__.py").exists());
+        assert!(!package.join("__main__.py.bak").exists());
+    }
+
     #[test]
     fn test_init_app_project() {
         let dir = tempdir().unwrap();
@@ -208,7 +434,9 @@ mod tests {
         };
         let options = WorkspaceOptions {
             uses_git: false,
+            force: false,
             values: None,
+            initial_commit: None,
         };
 
         init_app_project(&config, &options).unwrap();
@@ -257,17 +485,62 @@ mock-project = "mock_project.main:main"
         };
         let ws = config.workspace();
         initialize_venv(ws.root().join(".venv"), &ws.environment()).unwrap();
-        let options = InstallOptions { values: None };
+        let options = InstallOptions::default();
         let venv = ws.resolve_python_environment().unwrap();
         let test_package = Package::from_str("click==8.1.3").unwrap();
         let had_package = venv.contains_package(&test_package);
 
-        init_python_env(None, None, true, &options, &config).unwrap();
+        init_python_env(None, None, false, true, &options, &config).unwrap();
 
         assert!(!had_package);
         assert!(venv.contains_package(&test_package));
     }
 
+    #[test]
+    fn test_init_python_env_satisfied() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let venv = root.join(".venv");
+        std::fs::create_dir_all(venv_executables_dir_path(&venv)).unwrap();
+        std::fs::write(venv.join("pyvenv.cfg"), "version = 3.11.0\n").unwrap();
+        let site_packages = venv.join("lib").join("python3.11").join("site-packages");
+        for it in ["click-8.1.3.dist-info", "ruff-0.1.0.dist-info"] {
+            std::fs::create_dir_all(site_packages.join(it)).unwrap();
+        }
+        std::fs::write(
+            root.join("pyproject.toml"),
+            r#"[project]
+name = "mock-project"
+version = "0.0.1"
+dependencies = ["click==8.1.3"]
+
+[project.optional-dependencies]
+dev = ["ruff>=0.1"]
+"#,
+        )
+        .unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root,
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // The mock environment has no Python, so running the installer would fail.
+        init_python_env(
+            None,
+            None,
+            false,
+            false,
+            &InstallOptions::default(),
+            &config,
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_install_project_optional_dependencies() {
         let dir = tempdir().unwrap();
@@ -291,13 +564,14 @@ mock-project = "mock_project.main:main"
         };
         let ws = config.workspace();
         initialize_venv(ws.root().join(".venv"), &ws.environment()).unwrap();
-        let options = InstallOptions { values: None };
+        let options = InstallOptions::default();
         let venv = ws.resolve_python_environment().unwrap();
         let had_package = venv.contains_module("pytest").unwrap();
 
         init_python_env(
             None,
             Some(vec![String::from("dev")]),
+            false,
             true,
             &options,
             &config,
@@ -307,4 +581,87 @@ mock-project = "mock_project.main:main"
         assert!(!had_package);
         assert!(venv.contains_module("pytest").unwrap());
     }
+
+    #[test]
+    fn test_default_groups() {
+        let data = PyProjectToml::from_str(
+            r#"[project]
+name = "mock-project"
+
+[tool.huak]
+default-groups = ["dev", "test"]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            default_groups(&data, None),
+            Some((
+                vec!["dev".to_string(), "test".to_string()],
+                "[tool.huak] default-groups"
+            ))
+        );
+        assert_eq!(
+            default_groups(&data, Some(" docs, ".to_string())),
+            Some((vec!["docs".to_string()], DEFAULT_GROUPS_VAR))
+        );
+        assert_eq!(
+            default_groups(&data, Some(String::new())),
+            Some((Vec::new(), DEFAULT_GROUPS_VAR))
+        );
+        assert_eq!(
+            default_groups(
+                &PyProjectToml::from_str("[project]\nname = \"a\"\n").unwrap(),
+                None
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_init_python_env_unknown_default_group() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            r#"[project]
+name = "mock-project"
+version = "0.0.1"
+dependencies = ["click==8.1.3"]
+
+[project.optional-dependencies]
+dev = ["ruff>=0.1"]
+test = ["pytest"]
+
+[tool.huak]
+default-groups = ["dev", "docs"]
+"#,
+        )
+        .unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root,
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let Err(e @ Error::OptionalDependencyGroupNotFound { .. }) = init_python_env(
+            None,
+            None,
+            false,
+            false,
+            &InstallOptions::default(),
+            &config,
+        ) else {
+            panic!("expected the unknown default group to error");
+        };
+        assert_eq!(
+            e.to_string(),
+            "an optional dependency group could not be found: \
+            docs (from [tool.huak] default-groups; available: dev, test)"
+        );
+    }
 }
@@ -1,15 +1,21 @@
 use huak_python_manager::{RequestedVersion, Version};
 use huak_toolchain::{Channel, LocalTool, LocalToolchain};
 use pep508_rs::Requirement;
+use std::{path::Path, time::Instant};
+use termcolor::Color;
 
-use super::toolchain::{add_tool_to_toolchain, install_minimal_toolchain};
-use crate::{Config, Error, HuakResult};
+use super::toolchain::{add_tool_to_toolchain, install_minimal_toolchain, tool_install_command};
+use super::{
+    format_elapsed, install_project_dependencies, project_dependencies, unsatisfied_dependencies,
+};
+use crate::{Config, Dependency, Error, HuakResult, InstallOptions, PythonEnvironment};
 
 // TODO(cnpryer): https://github.com/cnpryer/huak/issues/850
 pub fn install(
     package: &Requirement,
     python_version: Option<RequestedVersion>,
     _package_index_url: &str,
+    options: &InstallOptions,
     config: &Config,
 ) -> HuakResult<()> {
     // TODO(cnpryer): Since we're treating the bin dir as a toolchain that'd mean Huak home is
@@ -18,11 +24,46 @@ pub fn install(
         return Err(Error::HuakHomeNotFound);
     };
 
+    // The install can be planned once the toolchain it installs to exists.
+    if config.plan.is_some() {
+        if let Ok(venv) = PythonEnvironment::new(home.join(".venv")) {
+            let tool = LocalTool::from_spec(package.name.clone(), package.to_string());
+            return config.run_effect(
+                "install",
+                &mut tool_install_command(&tool, &venv, options, config)?,
+            );
+        }
+    }
+
+    if config.dry_run {
+        return config.terminal().print_custom(
+            "Would install",
+            format!("{package} to {}", home.join("bin").display()),
+            Color::Yellow,
+            true,
+        );
+    }
+
+    let start = Instant::now();
+
     // TODO(cnpryer): Smarter installs
+    // An installed tool is only reinstalled if its environment doesn't satisfy the requirement.
     if home.join("bin").join(&package.name).exists() {
-        return config
-            .terminal()
-            .print_warning(format!("'{}' is already installed", &package.name));
+        let satisfied = match PythonEnvironment::new(home.join(".venv")) {
+            Ok(it) => {
+                unsatisfied_dependencies(&it, &[Dependency::from(package)], config)?.is_empty()
+            }
+            Err(_) => true,
+        };
+
+        if satisfied {
+            return config.terminal().print_custom(
+                "Skipped",
+                format!("{package} is already installed, nothing to do"),
+                Color::Green,
+                true,
+            );
+        }
     }
 
     if !home.join("bin").exists() {
@@ -47,5 +88,67 @@ pub fn install(
     let bin = LocalToolchain::new(home);
     let package = LocalTool::from_spec(package.name.clone(), package.to_string());
 
-    add_tool_to_toolchain(&package, &bin, config)
+    add_tool_to_toolchain(&package, &bin, options, config)?;
+
+    config.terminal().print_summary(format!(
+        "installed {} in {}",
+        package.name,
+        format_elapsed(start.elapsed())
+    ))
+}
+
+/// Install the `package`, or the project's required dependencies without one, into the `target`
+/// directory instead of the project's environment. This is useful for vendoring dependencies
+/// into a deployment bundle, like an AWS Lambda package.
+pub fn install_to_target(
+    package: Option<&Requirement>,
+    target: &Path,
+    options: &InstallOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let target = config.cwd.join(target);
+
+    if !config.dry_run {
+        std::fs::create_dir_all(&target)?;
+    }
+
+    let target = dunce::canonicalize(&target).unwrap_or(target);
+    let options = InstallOptions {
+        target: Some(target.clone()),
+        ..options.clone()
+    };
+
+    config
+        .terminal()
+        .print_custom("Target", target.display().to_string(), Color::Cyan, true)?;
+
+    let start = Instant::now();
+    let python_env = workspace.resolve_python_environment()?;
+
+    let installed = if let Some(package) = package {
+        python_env.install_packages(&[package], &options, config)?;
+        package.name.clone()
+    } else {
+        let manifest = workspace.current_local_manifest()?;
+        let dependencies = project_dependencies(&manifest)?.required;
+
+        if dependencies.is_empty() {
+            return config.terminal().print_custom(
+                "Skipped",
+                "the project has no dependencies to install",
+                Color::Green,
+                true,
+            );
+        }
+
+        install_project_dependencies(&python_env, &dependencies, &manifest, &options, config)?;
+        format!("{} dependencies", dependencies.len())
+    };
+
+    config.terminal().print_summary(format!(
+        "installed {installed} to {} in {}",
+        target.display(),
+        format_elapsed(start.elapsed())
+    ))
 }
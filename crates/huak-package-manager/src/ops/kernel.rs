@@ -0,0 +1,310 @@
+use crate::{Config, Dependency, Error, HuakResult, InstallOptions};
+use huak_pyproject_toml::{canonical_name, PyProjectToml};
+use serde_json::{json, Value};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+use termcolor::Color;
+
+/// The key in a kernel spec's metadata that marks it as registered by Huak.
+const KERNEL_METADATA_KEY: &str = "huak";
+
+pub struct KernelInstallOptions {
+    pub install_options: InstallOptions,
+}
+
+/// A Jupyter kernel registered by Huak for a project.
+#[derive(Debug, PartialEq, Eq)]
+struct HuakKernel {
+    /// The kernel's name (its directory in the kernels directory).
+    name: String,
+    /// The root of the project the kernel was registered for.
+    project: PathBuf,
+    /// The interpreter the kernel runs.
+    python: PathBuf,
+}
+
+impl HuakKernel {
+    /// Get why the kernel no longer points at its project, if it doesn't. Projects that were
+    /// moved, renamed, or lost their environment need their kernel installed again.
+    fn stale_reason(&self) -> Option<String> {
+        let Ok(manifest) = PyProjectToml::read_toml(self.project.join("pyproject.toml")) else {
+            return Some("the project was moved or removed".to_string());
+        };
+
+        if let Some(name) = manifest.project_name() {
+            if canonical_name(&name) != self.name {
+                return Some(format!("the project was renamed to {name}"));
+            }
+        }
+
+        if !self.python.exists() {
+            return Some("the project's environment was removed".to_string());
+        }
+
+        None
+    }
+}
+
+/// Register the project's environment as a Jupyter kernel named after the project.
+///
+/// `ipykernel` is installed to the environment if it's missing and added to the project's
+/// kernel group (`notebooks` unless `[tool.huak] kernel-group` is set), keeping it out of the
+/// project's main dependencies.
+pub fn install_kernel(config: &Config, options: &KernelInstallOptions) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let mut manifest = workspace.current_local_manifest()?;
+    let project_name = manifest
+        .manifest_data()
+        .project_name()
+        .ok_or_else(|| Error::InvalidMetadata("[project] has no name".to_string()))?;
+    let name = canonical_name(&project_name);
+    let python_env = workspace.resolve_python_environment()?;
+
+    let kernel_dep = Dependency::from_str("ipykernel")?;
+    if !python_env
+        .installed_versions()?
+        .contains_key(&canonical_name(kernel_dep.name()))
+    {
+        python_env.install_packages(&[&kernel_dep], &options.install_options, config)?;
+    }
+
+    // Add the installed `ipykernel` package to the kernel group if it isn't already there.
+    if !manifest
+        .manifest_data()
+        .contains_project_dependency_any(kernel_dep.name())
+    {
+        let group = workspace.kernel_group();
+
+        for pkg in python_env
+            .installed_packages()?
+            .iter()
+            .filter(|pkg| pkg.name() == kernel_dep.name())
+        {
+            manifest
+                .manifest_data_mut()
+                .add_project_optional_dependency(&pkg.to_string(), &group);
+        }
+
+        manifest.manifest_data_mut().formatted();
+        config.write_manifest(&manifest)?;
+    }
+
+    let mut cmd = Command::new(python_env.python_path());
+    cmd.args(["-m", "ipykernel", "install", "--user", "--name", &name])
+        .args(["--display-name", &format!("Python ({project_name})")])
+        .current_dir(workspace.root());
+    config.run_effect("register", &mut cmd)?;
+
+    if config.dry_run {
+        return Ok(());
+    }
+
+    let spec = kernels_dir()?.join(&name);
+    mark_kernel(&spec, workspace.root())?;
+
+    config.terminal().print_custom(
+        "Registered",
+        format!("kernel {name} ({})", spec.display()),
+        Color::Green,
+        true,
+    )
+}
+
+/// Unregister a kernel registered by Huak, defaulting to the project's.
+pub fn remove_kernel(name: Option<&str>, config: &Config) -> HuakResult<()> {
+    let name = match name {
+        Some(it) => it.to_string(),
+        None => config
+            .workspace()
+            .current_local_manifest()?
+            .manifest_data()
+            .project_name()
+            .map(|it| canonical_name(&it))
+            .ok_or_else(|| Error::InvalidMetadata("[project] has no name".to_string()))?,
+    };
+    let spec = kernels_dir()?.join(&name);
+
+    if !spec.join("kernel.json").exists() {
+        return Err(Error::KernelNotFound { name });
+    }
+
+    if read_kernel(&spec).is_none() {
+        return Err(Error::UnmanagedKernel { name });
+    }
+
+    config.remove_path(&spec)?;
+
+    config
+        .terminal()
+        .print_custom("Removed", format!("kernel {name}"), Color::Green, true)
+}
+
+/// List the kernels registered by Huak with their projects, flagging stale ones.
+pub fn list_kernels(config: &Config) -> HuakResult<()> {
+    let dir = kernels_dir()?;
+    let kernels = huak_kernels(&dir);
+    let mut terminal = config.terminal();
+
+    if kernels.is_empty() {
+        return terminal.print_custom("Empty", dir.display(), Color::Yellow, true);
+    }
+
+    for kernel in kernels {
+        let (message, color) = match kernel.stale_reason() {
+            Some(reason) => (
+                format!("{} (stale: {reason})", kernel.project.display()),
+                Color::Yellow,
+            ),
+            None => (kernel.project.display().to_string(), Color::Green),
+        };
+        terminal.print_custom(&kernel.name, message, color, true)?;
+    }
+
+    Ok(())
+}
+
+/// Get the directory Jupyter reads the user's kernel specs from, like `--user` installs do.
+fn kernels_dir() -> HuakResult<PathBuf> {
+    let data_dir = if let Ok(it) = std::env::var("JUPYTER_DATA_DIR") {
+        Some(PathBuf::from(it))
+    } else if cfg!(windows) {
+        std::env::var("APPDATA")
+            .ok()
+            .map(|it| PathBuf::from(it).join("jupyter"))
+    } else if cfg!(target_os = "macos") {
+        huak_home::sys::home_dir().map(|it| it.join("Library").join("Jupyter"))
+    } else if let Ok(it) = std::env::var("XDG_DATA_HOME") {
+        Some(PathBuf::from(it).join("jupyter"))
+    } else {
+        huak_home::sys::home_dir().map(|it| it.join(".local").join("share").join("jupyter"))
+    };
+
+    data_dir
+        .map(|it| it.join("kernels"))
+        .ok_or(Error::JupyterDataDirNotFound)
+}
+
+/// Record the project a kernel spec was registered for in its kernel.json's metadata.
+fn mark_kernel(spec: &Path, project: &Path) -> HuakResult<()> {
+    let path = spec.join("kernel.json");
+    let mut data: Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+
+    let Some(object) = data.as_object_mut() else {
+        return Err(Error::InternalError(format!(
+            "a kernel spec isn't an object: {}",
+            path.display()
+        )));
+    };
+
+    let metadata = object.entry("metadata").or_insert_with(|| json!({}));
+    if let Some(it) = metadata.as_object_mut() {
+        it.insert(
+            KERNEL_METADATA_KEY.to_string(),
+            json!({ "project": project }),
+        );
+    }
+
+    std::fs::write(path, serde_json::to_string_pretty(&data)? + "\n")?;
+
+    Ok(())
+}
+
+/// Read a kernel spec registered by Huak. Specs Huak didn't register are `None`.
+fn read_kernel(spec: &Path) -> Option<HuakKernel> {
+    let data: Value =
+        serde_json::from_str(&std::fs::read_to_string(spec.join("kernel.json")).ok()?).ok()?;
+    let project = data
+        .get("metadata")?
+        .get(KERNEL_METADATA_KEY)?
+        .get("project")?
+        .as_str()?;
+    let python = data.get("argv")?.get(0)?.as_str()?;
+
+    Some(HuakKernel {
+        name: spec.file_name()?.to_string_lossy().to_string(),
+        project: PathBuf::from(project),
+        python: PathBuf::from(python),
+    })
+}
+
+/// Get the kernels registered by Huak in the kernels `dir`, sorted by name.
+fn huak_kernels(dir: &Path) -> Vec<HuakKernel> {
+    let mut kernels = std::fs::read_dir(dir)
+        .map(|it| {
+            it.filter_map(Result::ok)
+                .filter_map(|it| read_kernel(&it.path()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    kernels.sort_by(|a, b| a.name.cmp(&b.name));
+
+    kernels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_spec(dir: &Path, name: &str, data: &Value) {
+        let spec = dir.join(name);
+        std::fs::create_dir_all(&spec).unwrap();
+        std::fs::write(spec.join("kernel.json"), data.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_huak_kernels() {
+        let dir = tempdir().unwrap();
+        let kernels = dir.path().join("kernels");
+        let project = dir.path().join("mock-project");
+        let python = project.join(".venv").join("bin").join("python");
+        std::fs::create_dir_all(python.parent().unwrap()).unwrap();
+        std::fs::write(&python, "").unwrap();
+        std::fs::write(
+            project.join("pyproject.toml"),
+            "[project]\nname = \"mock-project\"\n",
+        )
+        .unwrap();
+
+        let spec = |python: &Path| {
+            json!({
+                "argv": [python, "-m", "ipykernel_launcher", "-f", "{connection_file}"],
+                "display_name": "Python",
+                "language": "python",
+            })
+        };
+        write_spec(&kernels, "mock-project", &spec(&python));
+        mark_kernel(&kernels.join("mock-project"), &project).unwrap();
+        write_spec(&kernels, "moved-project", &spec(&python));
+        mark_kernel(&kernels.join("moved-project"), &dir.path().join("gone")).unwrap();
+        write_spec(&kernels, "python3", &spec(Path::new("/usr/bin/python3")));
+
+        let found = huak_kernels(&kernels);
+
+        assert_eq!(
+            found.iter().map(|it| it.name.as_str()).collect::<Vec<_>>(),
+            ["mock-project", "moved-project"]
+        );
+        assert_eq!(found[0].project, project);
+        assert_eq!(found[0].python, python);
+        assert_eq!(found[0].stale_reason(), None);
+        assert_eq!(
+            found[1].stale_reason(),
+            Some("the project was moved or removed".to_string())
+        );
+
+        std::fs::write(
+            project.join("pyproject.toml"),
+            "[project]\nname = \"new-name\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            found[0].stale_reason(),
+            Some("the project was renamed to new-name".to_string())
+        );
+    }
+}
@@ -1,34 +1,82 @@
-use super::add_venv_to_command;
-use crate::{Config, Dependency, HuakResult, InstallOptions};
-use std::{process::Command, str::FromStr};
+use super::{
+    add_venv_to_command, install_locked_tool, operation_env, ruff_cache_key, tool_values,
+    FileFilter, ResultCache,
+};
+use crate::{
+    git::uncommitted_paths,
+    schema::{SchemaMarker, LINT_BASELINE_SCHEMA},
+    Config, Dependency, Error, HuakResult, InstallOptions, SubprocessError,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+use termcolor::Color;
 
 pub struct LintOptions {
     /// A values vector of lint options typically used for passing on arguments.
     pub values: Option<Vec<String>>,
+    /// Comma-separated rule codes to enable (`ruff`'s `--select`).
+    pub select: Option<String>,
+    /// Comma-separated rule codes to disable (`ruff`'s `--ignore`).
+    pub ignore: Option<String>,
+    /// The files to lint. Every Python file in the workspace is linted by default.
+    pub files: FileFilter,
     pub include_types: bool,
+    /// A file of known `ruff` violations. Only violations that aren't in it fail the lint.
+    pub baseline: Option<PathBuf>,
+    /// Write the current `ruff` violations to the `baseline` instead of comparing against it.
+    pub write_baseline: bool,
+    /// Also write the `ruff` violations to this file as a SARIF 2.1.0 log, for code scanning.
+    pub sarif: Option<PathBuf>,
+    /// Environment variables for the tool's subprocesses, merged over `[tool.huak.lint] env`.
+    pub env: HashMap<String, String>,
+    /// Lint every file with `ruff`, even those unchanged since they last passed.
+    pub no_cache: bool,
     pub install_options: InstallOptions,
 }
 
 pub fn lint_project(config: &Config, options: &LintOptions) -> HuakResult<()> {
+    // Validate rule selections before doing any work.
+    let select = options
+        .select
+        .as_deref()
+        .map(parse_rule_codes)
+        .transpose()?;
+    let ignore = options
+        .ignore
+        .as_deref()
+        .map(parse_rule_codes)
+        .transpose()?;
+
     let workspace = config.workspace();
-    let mut manifest = workspace.current_local_manifest()?;
+    let paths = options.files.paths(workspace.root())?;
+
+    if paths.is_empty() {
+        return config
+            .operation_terminal("lint")
+            .print_warning("no Python files match --include and --exclude");
+    }
+
     let python_env = workspace.resolve_python_environment()?;
 
-    // Install `ruff` if it isn't already installed.
+    // Install `ruff` at its pinned version.
     let ruff_dep = Dependency::from_str("ruff")?;
     let mut lint_deps = vec![ruff_dep.clone()];
-    if !python_env.contains_module("ruff")? {
-        python_env.install_packages(&[&ruff_dep], &options.install_options, config)?;
-    }
+    install_locked_tool(&ruff_dep, &python_env, &options.install_options, config)?;
 
-    let mut terminal = config.terminal();
+    let env = operation_env("lint", &options.env, config)?;
+    let mut terminal = config.operation_terminal("lint");
 
     if options.include_types {
-        // Install `mypy` if it isn't already installed.
+        // Install `mypy` at its pinned version.
         let mypy_dep = Dependency::from_str("mypy")?;
-        if !python_env.contains_module("mypy")? {
-            python_env.install_packages(&[&mypy_dep], &options.install_options, config)?;
-        }
+        install_locked_tool(&mypy_dep, &python_env, &options.install_options, config)?;
 
         // Keep track of the fact that `mypy` is a needed lint dep.
         lint_deps.push(mypy_dep);
@@ -37,22 +85,92 @@ pub fn lint_project(config: &Config, options: &LintOptions) -> HuakResult<()> {
         let mut mypy_cmd = Command::new(python_env.python_path());
         add_venv_to_command(&mut mypy_cmd, &python_env)?;
         mypy_cmd
-            .args(vec!["-m", "mypy", ".", "--exclude", &python_env.name()?])
+            .args(["-m", "mypy"])
+            .args(&paths)
+            .args(["--exclude", &python_env.name()?])
+            .envs(&env)
             .current_dir(workspace.root());
         terminal.run_command(&mut mypy_cmd)?;
     }
 
     // Run `ruff`.
-    let mut cmd = Command::new(python_env.python_path());
-    let mut args = vec!["-m", "ruff", "check", "."];
-    if let Some(v) = options.values.as_ref() {
-        args.extend(v.iter().map(String::as_str));
+    let mut args = Vec::new();
+    if let Some(it) = select.as_ref() {
+        args.extend(["--select", it]);
+    }
+    if let Some(it) = ignore.as_ref() {
+        args.extend(["--ignore", it]);
+    }
+    let values = tool_values(
+        options.values.as_deref().unwrap_or_default(),
+        workspace.root(),
+        config,
+    );
+    args.extend(values.iter().map(String::as_str));
+
+    // Skip the files `ruff` passed that haven't changed since. The baseline and SARIF log need
+    // every file's violations.
+    let files = options.files.files(workspace.root())?;
+    let mut cache = if options.no_cache || options.baseline.is_some() || options.sarif.is_some() {
+        ResultCache::disabled()
+    } else {
+        let key = ruff_cache_key(&python_env, workspace.root(), &args)?;
+        ResultCache::open("lint", key, workspace.root(), config)
+    };
+    let changed = cache.changed(workspace.root(), &files);
+    cache.print_summary(files.len(), "lint", config)?;
+    let ruff_paths = if cache.hits() == 0 { paths } else { changed };
+
+    let ruff_command = || -> HuakResult<Command> {
+        let mut cmd = Command::new(python_env.python_path());
+        add_venv_to_command(&mut cmd, &python_env)?;
+        cmd.args(["-m", "ruff", "check"])
+            .args(&ruff_paths)
+            .args(&args)
+            .envs(&env)
+            .current_dir(workspace.root());
+        Ok(cmd)
+    };
+
+    // The SARIF log is written before the lint can fail, so violations are always reported.
+    if let Some(it) = options.sarif.as_ref() {
+        let path = config.cwd.join(it);
+        let found = ruff_violations(&mut ruff_command()?)?;
+        let contents = serde_json::to_string_pretty(&sarif_log(&found, workspace.root()))?;
+        config.write_file(&path, &format!("{contents}\n"))?;
+        terminal.print_custom(
+            "Wrote",
+            format!("{} violations to {}", found.len(), path.display()),
+            Color::Green,
+            true,
+        )?;
+    }
+
+    if !ruff_paths.is_empty() {
+        let mut cmd = ruff_command()?;
+        if let Some(it) = options.baseline.as_ref() {
+            lint_with_baseline(
+                &mut cmd,
+                &config.cwd.join(it),
+                workspace.root(),
+                options.write_baseline,
+                config,
+            )?;
+        } else if options
+            .values
+            .as_ref()
+            .is_some_and(|it| it.iter().any(|v| v == "--fix"))
+        {
+            // Fixing lints changes the project's code.
+            config.run_effect("lint", &mut cmd)?;
+        } else {
+            terminal.run_command(&mut cmd)?;
+        }
+        cache.save(workspace.root(), &files, config)?;
     }
-    add_venv_to_command(&mut cmd, &python_env)?;
-    cmd.args(args).current_dir(workspace.root());
-    terminal.run_command(&mut cmd)?;
 
     // Add installed lint deps (potentially both `mypy` and `ruff`) to manifest file if not already there.
+    let mut manifest = workspace.current_local_manifest()?;
     let new_lint_deps = lint_deps
         .iter()
         .filter(|dep| {
@@ -76,11 +194,477 @@ pub fn lint_project(config: &Config, options: &LintOptions) -> HuakResult<()> {
     }
 
     manifest.manifest_data_mut().formatted();
-    manifest.write_file()?;
+    config.write_manifest(&manifest)?;
 
     Ok(())
 }
 
+pub struct FixOptions {
+    /// A values vector of fix options typically used for passing on arguments.
+    pub values: Option<Vec<String>>,
+    /// Print the changes the fixes would make instead of making them.
+    pub diff: bool,
+    /// Fix files with uncommitted changes.
+    pub allow_dirty: bool,
+    /// Environment variables for the tool's subprocesses, merged over `[tool.huak.lint] env`.
+    pub env: HashMap<String, String>,
+    pub install_options: InstallOptions,
+}
+
+/// Apply `ruff`'s fixes to the project's Python code, or print them as a diff if `options.diff`
+/// is `true`.
+///
+/// Files with uncommitted changes aren't fixed unless `options.allow_dirty` is `true`, so fixes
+/// can always be reviewed and reverted with git.
+pub fn fix_project(config: &Config, options: &FixOptions) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let paths = FileFilter::default().paths(workspace.root())?;
+
+    if paths.is_empty() {
+        return config
+            .operation_terminal("lint")
+            .print_warning("no Python files to fix");
+    }
+
+    let python_env = workspace.resolve_python_environment()?;
+    let ruff_dep = Dependency::from_str("ruff")?;
+    install_locked_tool(&ruff_dep, &python_env, &options.install_options, config)?;
+
+    let env = operation_env("lint", &options.env, config)?;
+    let values = tool_values(
+        options.values.as_deref().unwrap_or_default(),
+        workspace.root(),
+        config,
+    );
+    let ruff_command = |args: &[&str]| -> HuakResult<Command> {
+        let mut cmd = Command::new(python_env.python_path());
+        add_venv_to_command(&mut cmd, &python_env)?;
+        cmd.args(["-m", "ruff", "check"])
+            .args(&paths)
+            .args(args)
+            .args(&values)
+            .envs(&env)
+            .current_dir(workspace.root());
+        Ok(cmd)
+    };
+
+    let unsafe_fixes = values.iter().any(|it| it == "--unsafe-fixes");
+    let fixable = ruff_violations(&mut ruff_command(&[])?)?
+        .into_iter()
+        .filter(|it| it.is_fixable(unsafe_fixes))
+        .collect::<Vec<_>>();
+    let summary = fix_summary(&fixable);
+    let mut terminal = config.terminal();
+
+    if options.diff {
+        let output = ruff_command(&["--fix", "--diff"])?.output()?;
+
+        // `ruff` exits with 1 when there's something to fix.
+        if !output.status.success() && output.status.code() != Some(1) {
+            std::io::stderr().write_all(&output.stderr)?;
+            return Err(Error::SubprocessFailure(SubprocessError::new(
+                output.status,
+            )));
+        }
+
+        print_fix_diff(&String::from_utf8_lossy(&output.stdout), config)?;
+
+        return match summary {
+            Some(it) => terminal.print_custom("Would fix", it, Color::Yellow, true),
+            None => terminal.print_custom("Success", "nothing to fix", Color::Green, true),
+        };
+    }
+
+    if !options.allow_dirty && !config.dry_run {
+        let mut files = fixable
+            .iter()
+            .map(|it| it.filename.clone())
+            .collect::<Vec<_>>();
+        files.dedup();
+        let dirty = uncommitted_paths(&files);
+
+        if !dirty.is_empty() {
+            return Err(Error::UncommittedChanges(
+                dirty
+                    .iter()
+                    .map(|it| {
+                        it.strip_prefix(workspace.root())
+                            .unwrap_or(it)
+                            .display()
+                            .to_string()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+        }
+    }
+
+    // Fixing lints changes the project's code.
+    let res = config.run_effect("lint", &mut ruff_command(&["--fix"])?);
+
+    if let Some(it) = summary.filter(|_| !config.dry_run) {
+        terminal.print_custom("Fixed", it, Color::Green, true)?;
+    }
+
+    res
+}
+
+/// Summarize the `fixable` violations like `4 violations (E711, F401) in 2 files`.
+fn fix_summary(fixable: &[RuffViolation]) -> Option<String> {
+    if fixable.is_empty() {
+        return None;
+    }
+
+    let mut codes = fixable
+        .iter()
+        .filter_map(|it| it.code.as_deref())
+        .collect::<Vec<_>>();
+    codes.sort_unstable();
+    codes.dedup();
+    let mut files = fixable.iter().map(|it| &it.filename).collect::<Vec<_>>();
+    files.dedup();
+
+    Some(format!(
+        "{} violation{} ({}) in {} file{}",
+        fixable.len(),
+        if fixable.len() == 1 { "" } else { "s" },
+        codes.join(", "),
+        files.len(),
+        if files.len() == 1 { "" } else { "s" },
+    ))
+}
+
+/// Print the hunks of a unified diff from `ruff --diff`, with a status for each file.
+fn print_fix_diff(diff: &str, config: &Config) -> HuakResult<()> {
+    let mut terminal = config.terminal();
+
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("--- ") {
+            terminal.print_custom("Would write", path, Color::Yellow, true)?;
+        } else if line.starts_with("@@") {
+            terminal.print_without_status(line, Color::Cyan)?;
+        } else if line.starts_with('-') {
+            terminal.print_without_status(line, Color::Red)?;
+        } else if line.starts_with('+') && !line.starts_with("+++ ") {
+            terminal.print_without_status(line, Color::Green)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A file of known lint violations, so linting can be adopted incrementally.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct LintBaseline {
+    #[serde(flatten)]
+    schema: SchemaMarker,
+    pub(crate) violations: Vec<BaselineViolation>,
+}
+
+/// A violation recorded in a `LintBaseline`. Locations aren't recorded so the baseline still
+/// applies after edits move the code around.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub(crate) struct BaselineViolation {
+    /// The path of the file relative to the project root.
+    file: String,
+    code: String,
+    message: String,
+}
+
+/// A violation from `ruff`'s JSON output.
+#[derive(Debug, Deserialize)]
+struct RuffViolation {
+    /// The rule code. Syntax errors don't have one.
+    code: Option<String>,
+    filename: PathBuf,
+    message: String,
+    location: RuffLocation,
+    end_location: Option<RuffLocation>,
+    /// The rule's documentation.
+    url: Option<String>,
+    /// The fix `ruff` can apply, if the violation is fixable.
+    fix: Option<RuffFix>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuffLocation {
+    row: usize,
+    column: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuffFix {
+    /// Whether the fix is safe to apply (like "safe" or "unsafe").
+    applicability: Option<String>,
+}
+
+impl RuffViolation {
+    /// Check if `ruff --fix` fixes the violation. Unsafe fixes are only applied with
+    /// `--unsafe-fixes`.
+    fn is_fixable(&self, unsafe_fixes: bool) -> bool {
+        self.fix.as_ref().is_some_and(|it| {
+            match it
+                .applicability
+                .as_deref()
+                .map(str::to_lowercase)
+                .as_deref()
+            {
+                Some("unsafe" | "suggested") => unsafe_fixes,
+                Some("display-only" | "displayonly" | "manual") => false,
+                _ => true,
+            }
+        })
+    }
+
+    fn to_baseline(&self, root: &Path) -> BaselineViolation {
+        let file = self.filename.strip_prefix(root).unwrap_or(&self.filename);
+
+        BaselineViolation {
+            file: file.to_string_lossy().replace('\\', "/"),
+            code: self.code.clone().unwrap_or_default(),
+            message: self.message.clone(),
+        }
+    }
+}
+
+/// Run `ruff` with JSON output and either write its violations to the `baseline` or fail if it
+/// found any violations that aren't in the `baseline`.
+fn lint_with_baseline(
+    cmd: &mut Command,
+    baseline: &Path,
+    root: &Path,
+    write_baseline: bool,
+    config: &Config,
+) -> HuakResult<()> {
+    let found = ruff_violations(cmd)?;
+    let mut terminal = config.terminal();
+
+    if write_baseline {
+        let mut violations = found
+            .iter()
+            .map(|it| it.to_baseline(root))
+            .collect::<Vec<_>>();
+        violations.sort();
+        write_lint_baseline(baseline, violations, config)?;
+
+        return terminal.print_custom(
+            "Wrote",
+            format!("{} violations to {}", found.len(), baseline.display()),
+            Color::Green,
+            true,
+        );
+    }
+
+    let known = read_lint_baseline(baseline, config)?.violations;
+    let new = new_violations(&found, &known, root);
+
+    for it in &new {
+        let file = it.filename.strip_prefix(root).unwrap_or(&it.filename);
+        terminal.print_error(format!(
+            "{}:{}:{}: {} {}",
+            file.display(),
+            it.location.row,
+            it.location.column,
+            it.code.as_deref().unwrap_or_default(),
+            it.message
+        ))?;
+    }
+
+    let fixed = known.len().saturating_sub(found.len() - new.len());
+    if fixed > 0 {
+        terminal.print_custom(
+            "Hint",
+            format!("{fixed} baseline violations were fixed (pass --write-baseline to update it)"),
+            Color::Cyan,
+            true,
+        )?;
+    }
+
+    if !new.is_empty() {
+        return Err(Error::NewLintViolations(new.len()));
+    }
+
+    terminal.print_custom(
+        "Success",
+        format!("no new violations ({} in the baseline)", found.len()),
+        Color::Green,
+        true,
+    )
+}
+
+/// Run a `ruff check` command with JSON output and get the violations it reports, sorted by
+/// location.
+fn ruff_violations(cmd: &mut Command) -> HuakResult<Vec<RuffViolation>> {
+    cmd.arg("--output-format=json");
+    let output = cmd.output()?;
+
+    // `ruff` exits with 1 when it finds violations.
+    if !output.status.success() && output.status.code() != Some(1) {
+        std::io::stderr().write_all(&output.stderr)?;
+        return Err(Error::SubprocessFailure(SubprocessError::new(
+            output.status,
+        )));
+    }
+
+    let mut found = serde_json::from_slice::<Vec<RuffViolation>>(&output.stdout)?;
+    found.sort_by(|a, b| {
+        (&a.filename, a.location.row, a.location.column).cmp(&(
+            &b.filename,
+            b.location.row,
+            b.location.column,
+        ))
+    });
+
+    Ok(found)
+}
+
+/// Convert `ruff` violations to a SARIF 2.1.0 log, with file locations relative to the `root`.
+///
+/// `ruff` doesn't have severities, so syntax errors and E9 runtime errors are errors and every
+/// other violation is a warning.
+fn sarif_log(found: &[RuffViolation], root: &Path) -> Value {
+    let mut rules = found
+        .iter()
+        .filter_map(|it| Some((it.code.as_deref()?, it.url.as_deref())))
+        .collect::<Vec<_>>();
+    rules.sort_unstable();
+    rules.dedup_by_key(|it| it.0);
+
+    let rules = rules
+        .iter()
+        .map(|(id, url)| {
+            let mut rule = json!({ "id": id });
+            if let Some(it) = url {
+                rule["helpUri"] = json!(it);
+            }
+            rule
+        })
+        .collect::<Vec<_>>();
+
+    let results = found
+        .iter()
+        .map(|it| {
+            let file = it.filename.strip_prefix(root).unwrap_or(&it.filename);
+            let end = it.end_location.as_ref().unwrap_or(&it.location);
+            let level = match it.code.as_deref() {
+                None => "error",
+                Some(code) if code.starts_with("E9") => "error",
+                Some(_) => "warning",
+            };
+
+            json!({
+                "ruleId": it.code.as_deref().unwrap_or("syntax-error"),
+                "level": level,
+                "message": { "text": it.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": file.to_string_lossy().replace('\\', "/"),
+                            "uriBaseId": "%SRCROOT%",
+                        },
+                        "region": {
+                            "startLine": it.location.row,
+                            "startColumn": it.location.column,
+                            "endLine": end.row,
+                            "endColumn": end.column,
+                        },
+                    },
+                }],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "ruff",
+                    "informationUri": "https://docs.astral.sh/ruff/",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+pub(crate) fn read_lint_baseline(path: &Path, config: &Config) -> HuakResult<LintBaseline> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(it) => it,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Err(Error::LintBaselineNotFound {
+                path: path.to_path_buf(),
+            })
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    // The schema is checked first since a newer baseline might not parse. Baselines with an
+    // older schema are rewritten with the current one.
+    let outdated = LINT_BASELINE_SCHEMA.check(&serde_json::from_str(&contents)?, path, config)?;
+    let baseline = serde_json::from_str::<LintBaseline>(&contents)?;
+    if outdated {
+        write_lint_baseline(path, baseline.violations.clone(), config)?;
+        LINT_BASELINE_SCHEMA.print_migrated(path, config)?;
+    }
+
+    Ok(baseline)
+}
+
+fn write_lint_baseline(
+    path: &Path,
+    violations: Vec<BaselineViolation>,
+    config: &Config,
+) -> HuakResult<()> {
+    let baseline = LintBaseline {
+        schema: LINT_BASELINE_SCHEMA.marker(),
+        violations,
+    };
+    let contents = serde_json::to_string_pretty(&baseline)?;
+
+    config.write_file(path, &format!("{contents}\n"))
+}
+
+/// Get the `found` violations that aren't in the `known` baseline violations. A violation
+/// recorded once in the baseline only accounts for one of the same violations in a file.
+fn new_violations<'a>(
+    found: &'a [RuffViolation],
+    known: &[BaselineViolation],
+    root: &Path,
+) -> Vec<&'a RuffViolation> {
+    let mut counts = HashMap::new();
+    for it in known {
+        *counts.entry(it).or_insert(0_usize) += 1;
+    }
+
+    found
+        .iter()
+        .filter(|it| match counts.get_mut(&it.to_baseline(root)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
+
+/// Parse a comma-separated list of rule codes (like "E,F401") into the form `ruff` expects.
+fn parse_rule_codes(s: &str) -> HuakResult<String> {
+    let codes = s.split(',').map(str::trim).collect::<Vec<_>>();
+
+    if codes
+        .iter()
+        .any(|it| it.is_empty() || !it.chars().all(|c| c.is_ascii_alphanumeric()))
+    {
+        return Err(Error::InvalidRuleCodes(s.to_string()));
+    }
+
+    Ok(codes.join(","))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,8 +695,16 @@ mod tests {
         };
         let options = LintOptions {
             values: None,
+            select: None,
+            ignore: None,
+            files: FileFilter::default(),
             include_types: true,
-            install_options: InstallOptions { values: None },
+            baseline: None,
+            write_baseline: false,
+            sarif: None,
+            env: HashMap::new(),
+            no_cache: false,
+            install_options: InstallOptions::default(),
         };
 
         lint_project(&config, &options).unwrap();
@@ -143,8 +735,16 @@ mod tests {
         initialize_venv(ws.root().join(".venv"), &ws.environment()).unwrap();
         let options = LintOptions {
             values: Some(vec![String::from("--fix")]),
+            select: None,
+            ignore: None,
+            files: FileFilter::default(),
             include_types: true,
-            install_options: InstallOptions { values: None },
+            baseline: None,
+            write_baseline: false,
+            sarif: None,
+            env: HashMap::new(),
+            no_cache: false,
+            install_options: InstallOptions::default(),
         };
         let lint_fix_filepath = ws.root().join("src").join("mock_project").join("fix_me.py");
         let pre_fix_str = r"
@@ -168,4 +768,82 @@ def fn():
 
         assert_eq!(post_fix_str, expected);
     }
+
+    #[test]
+    fn test_parse_rule_codes() {
+        assert_eq!(parse_rule_codes("E,F401").unwrap(), "E,F401");
+        assert_eq!(parse_rule_codes(" E , W ").unwrap(), "E,W");
+        assert!(parse_rule_codes("").is_err());
+        assert!(parse_rule_codes("E,,F").is_err());
+        assert!(parse_rule_codes("E,F4 01").is_err());
+    }
+
+    #[test]
+    fn test_new_violations() {
+        let root = PathBuf::from("/project");
+        let found = serde_json::from_str::<Vec<RuffViolation>>(
+            r#"[
+  {"code": "F401", "filename": "/project/src/a.py", "message": "`os` imported but unused", "location": {"row": 1, "column": 8}},
+  {"code": "E501", "filename": "/project/src/a.py", "message": "Line too long (99 > 88)", "location": {"row": 4, "column": 89}},
+  {"code": "E501", "filename": "/project/src/a.py", "message": "Line too long (99 > 88)", "location": {"row": 9, "column": 89}},
+  {"code": null, "filename": "/project/src/b.py", "message": "SyntaxError: Unexpected token", "location": {"row": 2, "column": 1}}
+]"#,
+        )
+        .unwrap();
+        let known = vec![
+            found[0].to_baseline(&root),
+            found[1].to_baseline(&root),
+            BaselineViolation {
+                file: "src/c.py".to_string(),
+                code: "F841".to_string(),
+                message: "Local variable `x` is assigned to but never used".to_string(),
+            },
+        ];
+
+        assert_eq!(known[0].file, "src/a.py");
+        assert_eq!(
+            new_violations(&found, &known, &root)
+                .iter()
+                .map(|it| (it.location.row, it.code.as_deref()))
+                .collect::<Vec<_>>(),
+            [(9, Some("E501")), (2, None)]
+        );
+    }
+
+    #[test]
+    fn test_sarif_log() {
+        let root = PathBuf::from("/project");
+        let found = serde_json::from_str::<Vec<RuffViolation>>(
+            r#"[
+  {"code": "F401", "filename": "/project/src/a.py", "message": "`os` imported but unused", "location": {"row": 1, "column": 8}, "end_location": {"row": 1, "column": 10}, "url": "https://docs.astral.sh/ruff/rules/unused-import"},
+  {"code": "F401", "filename": "/project/src/b.py", "message": "`sys` imported but unused", "location": {"row": 2, "column": 8}, "end_location": {"row": 2, "column": 11}, "url": "https://docs.astral.sh/ruff/rules/unused-import"},
+  {"code": null, "filename": "/project/src/c.py", "message": "SyntaxError: Unexpected token", "location": {"row": 3, "column": 1}}
+]"#,
+        )
+        .unwrap();
+
+        let log = sarif_log(&found, &root);
+        let run = &log["runs"][0];
+
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            json!([{ "id": "F401", "helpUri": "https://docs.astral.sh/ruff/rules/unused-import" }])
+        );
+        assert_eq!(run["results"][0]["ruleId"], "F401");
+        assert_eq!(run["results"][0]["level"], "warning");
+        assert_eq!(
+            run["results"][0]["locations"][0]["physicalLocation"],
+            json!({
+                "artifactLocation": { "uri": "src/a.py", "uriBaseId": "%SRCROOT%" },
+                "region": { "startLine": 1, "startColumn": 8, "endLine": 1, "endColumn": 10 },
+            })
+        );
+        assert_eq!(run["results"][2]["ruleId"], "syntax-error");
+        assert_eq!(run["results"][2]["level"], "error");
+        assert_eq!(
+            run["results"][2]["locations"][0]["physicalLocation"]["region"]["endLine"],
+            3
+        );
+    }
 }
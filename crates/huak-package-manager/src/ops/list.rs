@@ -0,0 +1,376 @@
+use super::project_dependencies;
+use crate::{
+    dependency_iter, json_api_url, Config, DependencyGraph, Error, HuakResult, IndexClient,
+    PythonEnvironment, DEFAULT_INDEX_URL,
+};
+use huak_pyproject_toml::canonical_name;
+use pep440_rs::Version;
+use serde::Serialize;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+    io::Write,
+    str::FromStr,
+};
+
+/// How the installed packages are listed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ListFormat {
+    /// A table of each package's name, version, and kind.
+    #[default]
+    Columns,
+    /// `name==version` lines, like `pip freeze`.
+    Freeze,
+    /// A JSON document for scripts.
+    Json,
+}
+
+impl FromStr for ListFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "columns" => Ok(ListFormat::Columns),
+            "freeze" => Ok(ListFormat::Freeze),
+            "json" => Ok(ListFormat::Json),
+            _ => Err(Error::InvalidOutputFormat(format!(
+                "{s} (expected columns, freeze, or json)"
+            ))),
+        }
+    }
+}
+
+impl Display for ListFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListFormat::Columns => write!(f, "columns"),
+            ListFormat::Freeze => write!(f, "freeze"),
+            ListFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+pub struct ListOptions {
+    pub format: ListFormat,
+    /// Only list packages with newer versions on the package index, with their latest version.
+    pub outdated: bool,
+}
+
+/// Why a package is installed in the project's environment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageKind {
+    /// The project or one of the dependencies it declares (in any group).
+    Direct,
+    /// Required by a direct dependency.
+    Transitive,
+    /// Installed without being declared or required by anything declared.
+    Undeclared,
+}
+
+impl Display for PackageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackageKind::Direct => write!(f, "direct"),
+            PackageKind::Transitive => write!(f, "transitive"),
+            PackageKind::Undeclared => write!(f, "undeclared"),
+        }
+    }
+}
+
+/// A package installed in the project's environment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ListedPackage {
+    /// The PEP 503 normalized name.
+    pub name: String,
+    pub version: String,
+    pub kind: PackageKind,
+    /// The latest version on the package index, when listing outdated packages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest: Option<String>,
+}
+
+/// Print the packages installed in the project's environment, marking which the project
+/// declares, which they require, and which are undeclared.
+pub fn list_packages(config: &Config, options: &ListOptions) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let manifest = workspace.current_local_manifest()?;
+    let python_env = workspace.current_python_environment()?;
+
+    let dependencies = project_dependencies(&manifest)?;
+    let mut declared = dependency_iter(
+        dependencies
+            .required
+            .iter()
+            .chain(dependencies.optional.values().flatten()),
+    )
+    .map(|it| canonical_name(it.name()))
+    .collect::<BTreeSet<_>>();
+    if let Some(it) = manifest.manifest_data().project_name() {
+        declared.insert(canonical_name(&it));
+    }
+
+    let mut packages = listed_packages(
+        &python_env.installed_versions()?,
+        &declared,
+        &python_env.dependency_graph()?,
+    );
+
+    if options.outdated {
+        let mut latest = outdated_packages(&python_env, config)?;
+        packages.retain_mut(|it| {
+            it.latest = latest.remove(&it.name);
+            it.latest.is_some()
+        });
+    }
+
+    let mut stdout = std::io::stdout();
+    match options.format {
+        ListFormat::Columns => write!(stdout, "{}", format_columns(&packages))?,
+        ListFormat::Freeze => {
+            for it in &packages {
+                writeln!(stdout, "{}=={}", it.name, it.version)?;
+            }
+        }
+        ListFormat::Json => writeln!(stdout, "{}", serde_json::to_string_pretty(&packages)?)?,
+    }
+
+    Ok(())
+}
+
+/// Classify the `installed` packages by the `declared` names and what they require in the
+/// `graph`, sorted by name.
+fn listed_packages(
+    installed: &HashMap<String, Version>,
+    declared: &BTreeSet<String>,
+    graph: &DependencyGraph,
+) -> Vec<ListedPackage> {
+    let roots = declared.iter().collect::<Vec<_>>();
+    let required = graph.resolved(&roots);
+    let mut packages = installed
+        .iter()
+        .map(|(name, version)| ListedPackage {
+            name: canonical_name(name),
+            version: version.to_string(),
+            kind: if declared.contains(&canonical_name(name)) {
+                PackageKind::Direct
+            } else if required.contains(&canonical_name(name)) {
+                PackageKind::Transitive
+            } else {
+                PackageKind::Undeclared
+            },
+            latest: None,
+        })
+        .collect::<Vec<_>>();
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+    packages
+}
+
+/// Get the latest version of each package in the environment with a newer one on the package
+/// index.
+///
+/// The project's `[tool.huak.network] index-url` is used if it's set, and otherwise the index pip
+/// is configured with. Indexes without the Python Package
+/// Index's JSON API are asked through pip.
+pub(super) fn outdated_packages(
+    python_env: &PythonEnvironment,
+    config: &Config,
+) -> HuakResult<HashMap<String, String>> {
+    let mut settings = config.workspace().network_settings();
+    if settings.index_url == DEFAULT_INDEX_URL {
+        if let Some(index_url) = python_env.pip_index_url()? {
+            match json_api_url(&index_url) {
+                Some(it) => settings.index_url = it,
+                None => return pip_outdated_packages(python_env, &index_url),
+            }
+        }
+    }
+
+    let installed = python_env.installed_versions()?;
+    let names = installed.keys().collect::<Vec<_>>();
+    let latest = IndexClient::with_settings(&settings, config)?.latest_versions(&names)?;
+
+    Ok(installed
+        .iter()
+        .filter_map(|(name, version)| {
+            let name = canonical_name(name);
+            let latest = latest.get(&name).filter(|it| *it > version)?;
+            Some((name, latest.to_string()))
+        })
+        .collect())
+}
+
+/// Get the latest version of each package in the environment with a newer one on the
+/// `index_url`, asking pip.
+fn pip_outdated_packages(
+    python_env: &PythonEnvironment,
+    index_url: &str,
+) -> HuakResult<HashMap<String, String>> {
+    Ok(python_env
+        .outdated_versions(index_url)?
+        .into_iter()
+        .map(|(name, version)| (name, version.to_string()))
+        .collect())
+}
+
+/// Format the `packages` as aligned columns with a header.
+fn format_columns(packages: &[ListedPackage]) -> String {
+    let outdated = packages.iter().any(|it| it.latest.is_some());
+    let mut rows = vec![vec![
+        "Package".to_string(),
+        "Version".to_string(),
+        "Kind".to_string(),
+    ]];
+    if outdated {
+        rows[0].push("Latest".to_string());
+    }
+    for it in packages {
+        let mut row = vec![it.name.clone(), it.version.clone(), it.kind.to_string()];
+        if let Some(latest) = it.latest.as_ref() {
+            row.push(latest.clone());
+        }
+        rows.push(row);
+    }
+
+    let widths = (0..rows[0].len())
+        .map(|i| {
+            rows.iter()
+                .filter_map(|it| it.get(i))
+                .map(String::len)
+                .max()
+        })
+        .map(Option::unwrap_or_default)
+        .collect::<Vec<_>>();
+
+    let mut table = String::new();
+    for row in rows {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(it, width)| format!("{it:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{copy_dir, CopyDirOptions};
+    use huak_dev::dev_resources_dir;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_listed_packages() {
+        let installed = [
+            "my-project",
+            "Requests",
+            "urllib3",
+            "pytest",
+            "iniconfig",
+            "pip",
+        ]
+        .into_iter()
+        .map(|it| (it.to_string(), Version::from_str("1.0.0").unwrap()))
+        .collect::<HashMap<_, _>>();
+        let declared = BTreeSet::from(["my-project".to_string(), "pytest".to_string()]);
+        let graph = DependencyGraph::new(HashMap::from([
+            ("my-project".to_string(), vec!["requests".to_string()]),
+            ("requests".to_string(), vec!["urllib3".to_string()]),
+            ("urllib3".to_string(), Vec::new()),
+            ("pytest".to_string(), vec!["iniconfig".to_string()]),
+            ("iniconfig".to_string(), Vec::new()),
+            ("pip".to_string(), Vec::new()),
+        ]));
+
+        let packages = listed_packages(&installed, &declared, &graph);
+
+        assert_eq!(
+            packages
+                .iter()
+                .map(|it| (it.name.as_str(), it.kind))
+                .collect::<Vec<_>>(),
+            [
+                ("iniconfig", PackageKind::Transitive),
+                ("my-project", PackageKind::Direct),
+                ("pip", PackageKind::Undeclared),
+                ("pytest", PackageKind::Direct),
+                ("requests", PackageKind::Transitive),
+                ("urllib3", PackageKind::Transitive),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_outdated_packages_with_pip_index() {
+        let dir = tempdir().unwrap();
+        copy_dir(
+            &dev_resources_dir().join("mock-project"),
+            &dir.path().join("mock-project"),
+            &CopyDirOptions::default(),
+        )
+        .unwrap();
+        let workspace_root = dir.path().join("mock-project");
+        let cwd = workspace_root.clone();
+        let config = Config {
+            workspace_root,
+            cwd,
+            ..Default::default()
+        };
+        let python_env = config.workspace().resolve_python_environment().unwrap();
+
+        // A PEP 503 index in a directory, which pip can read but has no JSON API.
+        let index = dir.path().join("simple");
+        std::fs::create_dir_all(index.join("pip")).unwrap();
+        std::fs::write(
+            index.join("pip").join("index.html"),
+            r#"<a href="pip-99.0.0-py3-none-any.whl">pip-99.0.0-py3-none-any.whl</a>"#,
+        )
+        .unwrap();
+        let index_url = url::Url::from_directory_path(&index).unwrap();
+        std::fs::write(
+            python_env.root().join("pip.conf"),
+            format!("[global]\nindex-url = {index_url}\n"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            python_env.pip_index_url().unwrap().as_deref(),
+            Some(index_url.as_str())
+        );
+        assert_eq!(
+            outdated_packages(&python_env, &config)
+                .unwrap()
+                .get("pip")
+                .map(String::as_str),
+            Some("99.0.0")
+        );
+    }
+
+    #[test]
+    fn test_format_columns() {
+        let package = |name: &str, kind, latest: Option<&str>| ListedPackage {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            kind,
+            latest: latest.map(ToString::to_string),
+        };
+
+        assert_eq!(
+            format_columns(&[
+                package("requests", PackageKind::Direct, None),
+                package("idna", PackageKind::Transitive, None),
+            ]),
+            "Package   Version  Kind\nrequests  1.0.0    direct\nidna      1.0.0    transitive\n"
+        );
+        assert_eq!(
+            format_columns(&[package("requests", PackageKind::Direct, Some("2.0.0"))]),
+            "Package   Version  Kind    Latest\nrequests  1.0.0    direct  2.0.0\n"
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{metadata::metadata_problems, Config, Error, HuakResult};
+use huak_pyproject_toml::value_to_sanitized_string;
+use std::io::Write;
+use termcolor::Color;
+use toml_edit::{Array, Item, Table, Value};
+
+/// Print the pyproject.toml value at a dotted `key` (like `project.urls.Homepage`).
+pub fn get_metadata(key: &str, config: &Config) -> HuakResult<()> {
+    let path = metadata_key(key)?;
+    let manifest = config.workspace().current_local_manifest()?;
+    let mut item = manifest.manifest_data().doc.as_item();
+
+    for it in &path {
+        item = item
+            .get(it)
+            .ok_or_else(|| Error::InvalidMetadata(format!("{key} is not set")))?;
+    }
+
+    let value = match item {
+        Item::Value(it) => value_to_sanitized_string(it),
+        it => it.to_string().trim().to_string(),
+    };
+
+    std::io::stdout().write_all(format!("{value}\n").as_bytes())?;
+
+    Ok(())
+}
+
+/// Write a `value` to the pyproject.toml at a dotted `key`.
+///
+/// A key ending with `+=` (like `project.keywords+=cli`) appends the value to an array
+/// instead. Values starting with `[` or `{` are parsed as TOML; anything else is a string.
+pub fn set_metadata(key: &str, value: Option<&str>, config: &Config) -> HuakResult<()> {
+    let (key, value, append) = match key.split_once("+=") {
+        Some((key, it)) if value.is_none() && !it.is_empty() => (key, it, true),
+        Some((key, "")) => (
+            key,
+            value.ok_or_else(|| Error::InvalidMetadata(format!("{key} needs a value")))?,
+            true,
+        ),
+        Some(_) => {
+            return Err(Error::InvalidMetadata(format!(
+                "{key} can't be given another value"
+            )))
+        }
+        None => (
+            key,
+            value.ok_or_else(|| Error::InvalidMetadata(format!("{key} needs a value")))?,
+            false,
+        ),
+    };
+
+    let path = metadata_key(key)?;
+    let value = metadata_value(value)?;
+    let workspace = config.workspace();
+    let mut manifest = workspace.current_local_manifest()?;
+    let (last, parents) = path.split_last().expect("key should have segments");
+    let mut table =
+        manifest.manifest_data_mut().doc.as_table_mut() as &mut dyn toml_edit::TableLike;
+
+    for it in parents {
+        if table.get(it).is_none() {
+            table.insert(it, Item::Table(Table::new()));
+        }
+
+        table = table
+            .get_mut(it)
+            .and_then(Item::as_table_like_mut)
+            .ok_or_else(|| Error::InvalidMetadata(format!("{it} in {key} is not a table")))?;
+    }
+
+    if append {
+        match table.get_mut(last) {
+            Some(Item::Value(Value::Array(it))) => it.push_formatted(value),
+            Some(_) => {
+                return Err(Error::InvalidMetadata(format!(
+                    "{key} is not an array and can't be appended to"
+                )))
+            }
+            None => {
+                let mut array = Array::new();
+                array.push_formatted(value);
+                table.insert(last, Item::Value(Value::Array(array)));
+            }
+        }
+    } else {
+        table.insert(last, Item::Value(value));
+    }
+
+    let item = table.get(last).expect("value should be set");
+
+    if let Some(e) = metadata_problems(&path, item).into_iter().next() {
+        return Err(e);
+    }
+
+    config.write_manifest(&manifest)?;
+
+    config
+        .terminal()
+        .print_custom("Updated", key, Color::Green, true)
+}
+
+/// Split a dotted `key` into its segments.
+fn metadata_key(key: &str) -> HuakResult<Vec<&str>> {
+    let path = key.split('.').map(str::trim).collect::<Vec<_>>();
+
+    if path.iter().any(|it| it.is_empty()) {
+        return Err(Error::InvalidMetadata(format!("{key} is not a valid key")));
+    }
+
+    Ok(path)
+}
+
+fn metadata_value(value: &str) -> HuakResult<Value> {
+    if value.starts_with(['[', '{']) {
+        value
+            .parse::<Value>()
+            .map_err(|e| Error::InvalidMetadata(format!("{value} is not valid TOML ({e})")))
+    } else {
+        Ok(Value::from(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{copy_dir, CopyDirOptions, TerminalOptions, Verbosity};
+    use huak_dev::dev_resources_dir;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_set_metadata() {
+        let dir = tempdir().unwrap();
+        copy_dir(
+            &dev_resources_dir().join("mock-project"),
+            &dir.path().join("mock-project"),
+            &CopyDirOptions::default(),
+        )
+        .unwrap();
+        let workspace_root = dir.path().join("mock-project");
+        let cwd = workspace_root.clone();
+        let terminal_options = TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+        let config = Config {
+            workspace_root,
+            cwd,
+            terminal_options,
+            ..Default::default()
+        };
+
+        set_metadata(
+            "project.urls.Homepage",
+            Some("https://example.com"),
+            &config,
+        )
+        .unwrap();
+        set_metadata("project.keywords+=cli", None, &config).unwrap();
+        set_metadata("project.keywords+=", Some("python"), &config).unwrap();
+
+        assert!(matches!(
+            set_metadata("project.classifiers+=Typing :: Untyped", None, &config),
+            Err(Error::InvalidClassifier(_))
+        ));
+        assert!(matches!(
+            set_metadata("project.urls.Docs", Some("example.com"), &config),
+            Err(Error::InvalidUrl(_))
+        ));
+
+        let manifest = config.workspace().current_local_manifest().unwrap();
+        let project = manifest.manifest_data().project_table().unwrap();
+
+        assert_eq!(
+            project["urls"]["Homepage"].as_str(),
+            Some("https://example.com")
+        );
+        assert_eq!(
+            project["keywords"].to_string().trim(),
+            r#"["cli", "python"]"#
+        );
+        assert!(project.get("classifiers").is_none());
+        assert!(project["urls"].get("Docs").is_none());
+    }
+}
@@ -1,48 +1,137 @@
 mod activate;
 mod add;
 mod build;
+mod cache;
+mod changelog;
+mod check;
+mod checksums;
 mod clean;
+mod complete;
+mod contents;
+mod develop;
+mod env;
+mod files;
 mod format;
+mod history;
 mod init;
 mod install;
+mod kernel;
 mod lint;
+mod list;
+mod metadata;
+mod native;
 mod new;
+mod poetry;
 mod publish;
 mod python;
 mod remove;
+mod rename;
+mod report;
+mod result_cache;
 mod run;
+mod sbom;
+mod scaffold;
+mod script;
+mod setuptools;
 mod test;
+mod tool_lock;
 mod toolchain;
 mod update;
+mod vendor;
 mod version;
 
 use crate::{
-    default_python_gitignore, env_path_values, git_init, Error, HuakResult, PythonEnvironment,
+    dependency::dependency_not_found, dependency_iter, env_path_values, git_init, Config,
+    Dependency, Error, HuakResult, InstallOptions, LocalManifest, PythonEnvironment,
 };
-pub use activate::activate_python_environment;
+pub use activate::{activate_python_environment, ActivateOptions, ActivationShell};
 pub use add::{add_project_dependencies, add_project_optional_dependencies, AddOptions};
-pub use build::{build_project, BuildOptions};
+use build::CIBUILDWHEEL_OUTPUT_DIR;
+pub use build::{build_project, BuildArtifact, BuildOptions, BuildReport, OutputFormat};
+pub use cache::{clean_cache, list_cache};
+pub use changelog::{display_changelog_entry, verify_changelog};
+pub use check::{check_project, CheckOptions};
+use check::{policy_violations, warn_policy_violations};
 pub use clean::{clean_project, CleanOptions};
+pub use complete::{complete_dependencies, complete_groups, complete_run};
+use develop::{active_overrides_warning, reapply_dependency_overrides};
+pub use develop::{add_dependency_override, list_dependency_overrides, remove_dependency_override};
+use env::environment_drift;
+pub use env::{create_python_environment, list_python_environments, remove_python_environment};
+pub use files::FileFilter;
 pub use format::{format_project, FormatOptions};
-pub use init::{init_app_project, init_lib_project, init_python_env};
-pub use install::install;
-pub use lint::{lint_project, LintOptions};
-pub use new::{new_app_project, new_lib_project};
+pub use history::{display_history, HistoryOptions};
+use huak_pyproject_toml::canonical_name;
+pub use init::{
+    init_app_project, init_bare_project, init_cli_project, init_lib_project, init_python_env,
+};
+pub use install::{install, install_to_target};
+pub use kernel::{install_kernel, list_kernels, remove_kernel, KernelInstallOptions};
+use lint::read_lint_baseline;
+pub use lint::{fix_project, lint_project, FixOptions, LintOptions};
+pub use list::{list_packages, ListFormat, ListOptions, ListedPackage, PackageKind};
+pub use metadata::{get_metadata, set_metadata};
+pub use new::{new_app_project, new_bare_project, new_cli_project, new_lib_project};
+pub use poetry::migrate_poetry_project;
+use poetry::{ensure_not_poetry_managed, project_dependencies, ProjectDependencies};
 pub use publish::{publish_project, PublishOptions};
-pub use python::{install_python, list_python, use_python};
-pub use remove::{remove_project_dependencies, RemoveOptions};
-pub use run::run_command_str;
-use std::{path::PathBuf, process::Command};
-pub use test::{test_project, TestOptions};
+pub use python::{
+    check_python, install_python, list_python, use_python, ListPythonOptions, UsePythonOptions,
+};
+pub use remove::{
+    remove_project_dependencies, remove_project_optional_dependencies, RemoveOptions,
+};
+pub use rename::{rename_project, RenameOptions};
+pub use report::{generate_report, ReportFormat, ReportOptions, ReportSection};
+use result_cache::{ruff_cache_key, ResultCache};
+pub use run::{list_entry_point_scripts, list_run_targets, run_command_sequence, run_command_str};
+pub use sbom::{generate_sbom, SbomFormat, SbomOptions};
+pub use scaffold::ProjectTemplate;
+use scaffold::{write_template_files, ExistingFiles};
+pub use script::{add_script_dependencies, run_script};
+pub use setuptools::{init_from_setup_py, SetuptoolsConversionOptions};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+use test::TestCounts;
+pub use test::{test_project, ShardBy, TestOptions, TestShard};
+pub use tool_lock::upgrade_self_toolchain;
+use tool_lock::{install_locked_tool, tool_version_drift};
 pub use toolchain::{
     add_tool, install_toolchain, list_toolchains, remove_tool, run_tool, toolchain_info,
     uninstall_toolchain, update_toolchain, use_toolchain,
 };
 pub use update::{update_project_dependencies, UpdateOptions};
-pub use version::display_project_version;
+use vendor::distribution_name_version;
+pub use vendor::{sync_vendor_dir, verify_vendor_dir, VendorSyncOptions};
+pub use version::{
+    display_project_version, project_version_report, set_project_version, VersionOptions,
+    VersionReport,
+};
+use version::{project_version, resolve_dynamic_version};
 
 const DEFAULT_PYTHON_INIT_FILE_CONTENTS: &str = r#"__version__ = "0.0.1"
 "#;
+const DEFAULT_PYTHON_CLI_FILE_CONTENTS: &str = r#"import argparse
+
+
+def main(argv=None):
+    parser = argparse.ArgumentParser()
+    parser.add_argument("--version", action="store_true", help="print the version")
+    args = parser.parse_args(argv)
+
+    if args.version:
+        from . import __version__
+
+        print(__version__)
+
+
+if __name__ == "__main__":
+    main()
+"#;
 const DEFAULT_PYTHON_MAIN_FILE_CONTENTS: &str = r#"def main():
     print("Hello, World!")
 
@@ -56,7 +145,8 @@ if __name__ == "__main__":
 /// - Adds the virtual environment's executables directory path to the top of the command's
 ///   `PATH` environment variable.
 /// - Adds `VIRTUAL_ENV` environment variable to the command pointing at the virtual environment's
-///   root.
+///   root and unsets `PYTHONHOME`, like activating the environment does. System Python
+///   environments aren't virtual environments so this is skipped for them.
 fn add_venv_to_command(cmd: &mut Command, venv: &PythonEnvironment) -> HuakResult<()> {
     let mut paths = env_path_values().unwrap_or_default();
 
@@ -64,18 +154,298 @@ fn add_venv_to_command(cmd: &mut Command, venv: &PythonEnvironment) -> HuakResul
     cmd.env(
         "PATH",
         std::env::join_paths(paths).map_err(|e| Error::InternalError(e.to_string()))?,
-    )
-    .env("VIRTUAL_ENV", venv.root());
+    );
+
+    if !venv.is_system() {
+        cmd.env("VIRTUAL_ENV", venv.root()).env_remove("PYTHONHOME");
+    }
 
     Ok(())
 }
 
+/// Get the environment variables for an operation's subprocesses: the workspace's configured
+/// `[tool.huak.<operation>] env` with the `env` passed to the operation merged over it.
+///
+/// Values can reference the inherited environment with `${VAR}` or `${VAR:-default}`, and `$$`
+/// is a literal `$`.
+fn operation_env(
+    operation: &str,
+    env: &HashMap<String, String>,
+    config: &Config,
+) -> HuakResult<HashMap<String, String>> {
+    let mut merged = config.workspace().operation_env(operation);
+    merged.extend(env.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+    merged
+        .into_iter()
+        .map(|(key, value)| {
+            let value = interpolate_env(&key, &value, |it| std::env::var(it).ok())?;
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Interpolate the `${VAR}` references in the value of the environment variable `key`.
+///
+/// A reference to an undefined variable is an error unless it has a default (`${VAR:-default}`).
+fn interpolate_env<F>(key: &str, value: &str, lookup: F) -> HuakResult<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut interpolated = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(i) = rest.find('$') {
+        interpolated.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+
+        if let Some(it) = rest.strip_prefix('$') {
+            interpolated.push('$');
+            rest = it;
+            continue;
+        }
+
+        let Some(it) = rest.strip_prefix('{') else {
+            interpolated.push('$');
+            continue;
+        };
+        let Some(end) = it.find('}') else {
+            return Err(Error::InvalidEnvironmentValue(format!(
+                "{key} has an unclosed ${{ (use $$ for a literal $)"
+            )));
+        };
+        let (name, default) = match it[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&it[..end], None),
+        };
+
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(Error::InvalidEnvironmentValue(format!(
+                "{key} references an invalid variable name: ${{{}}}",
+                &it[..end]
+            )));
+        }
+
+        match lookup(name).or_else(|| default.map(ToString::to_string)) {
+            Some(it) => interpolated.push_str(&it),
+            None => {
+                return Err(Error::UndefinedEnvironmentVariable(format!(
+                    "{name} (referenced by {key}; use ${{{name}:-default}} for a default)"
+                )))
+            }
+        }
+
+        rest = &it[end + 1..];
+    }
+
+    interpolated.push_str(rest);
+
+    Ok(interpolated)
+}
+
+/// Format how long an operation took for its summary, like `3.4s` or `2m 5.0s`.
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs_f64();
+
+    if secs < 60.0 {
+        format!("{secs:.1}s")
+    } else {
+        format!("{}m {:.1}s", elapsed.as_secs() / 60, secs % 60.0)
+    }
+}
+
+/// Get the value of an option passed through to a tool in `args`, given as `<name> <value>` or
+/// `<name>=<value>` for any of its `names`.
+fn option_value<'a>(args: &'a [String], names: &[&str]) -> Option<&'a str> {
+    args.iter().enumerate().find_map(|(i, it)| {
+        names.iter().find_map(|name| {
+            if it == name {
+                args.get(i + 1).map(String::as_str)
+            } else {
+                it.strip_prefix(name).and_then(|it| it.strip_prefix('='))
+            }
+        })
+    })
+}
+
+/// Translate the relative paths in the `values` passed through to a tool so they mean the same
+/// thing when the tool runs from the workspace `root` as they did from the current directory.
+///
+/// A value is translated if it names a path that exists from the current directory. The value
+/// of a `--name=value` option is also translated if it names a file to write (it has an
+/// extension) in a directory that exists. The path of a pytest node ID like
+/// `test_a.py::test_b` is translated the same way.
+fn tool_values(values: &[String], root: &Path, config: &Config) -> Vec<String> {
+    let prefix = config.cwd.strip_prefix(root).unwrap_or(&config.cwd);
+    if prefix.as_os_str().is_empty() {
+        return values.to_vec();
+    }
+    let translate = |value: &str, new_file: bool| -> Option<String> {
+        let (path, node) = match value.split_once("::") {
+            Some((path, node)) => (path, Some(node)),
+            None => (value, None),
+        };
+        if path.is_empty() || path.starts_with('-') || Path::new(path).is_absolute() {
+            return None;
+        }
+        let full = config.cwd.join(path);
+        let exists = full.exists()
+            || (new_file
+                && Path::new(path).extension().is_some()
+                && full.parent().is_some_and(Path::is_dir));
+
+        exists.then(|| {
+            let mut it = prefix.join(path).display().to_string();
+            if let Some(node) = node {
+                it.push_str("::");
+                it.push_str(node);
+            }
+            it
+        })
+    };
+
+    values
+        .iter()
+        .map(|it| {
+            let translated = match it.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => {
+                    translate(value, true).map(|value| format!("{name}={value}"))
+                }
+                _ => translate(it, false),
+            };
+
+            translated.unwrap_or_else(|| it.clone())
+        })
+        .collect()
+}
+
+/// Install the project's `dependencies`, verifying any with hashes pinned in the manifest.
+fn install_project_dependencies(
+    python_env: &PythonEnvironment,
+    dependencies: &[String],
+    manifest: &LocalManifest,
+    options: &InstallOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    let pinned = manifest.manifest_data().dependency_hashes();
+    let mut hashed = Vec::new();
+
+    for mut dep in dependency_iter(dependencies) {
+        if let Some(it) = pinned.get(&canonical_name(dep.name())) {
+            dep.set_hashes(it.clone())?;
+            hashed.push(dep);
+        }
+    }
+
+    python_env.install_hashed_packages(&hashed, options, config)?;
+    python_env.install_packages(dependencies, options, config)
+}
+
+/// Install the project's dependencies from the optional dependency `groups` that the
+/// `python_env` doesn't satisfy, and its required dependencies unless `no_default_deps` is
+/// `true`. Nothing else the project depends on is installed.
+fn install_group_dependencies(
+    python_env: &PythonEnvironment,
+    groups: &[String],
+    no_default_deps: bool,
+    options: &InstallOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    let manifest = config.workspace().current_local_manifest()?;
+    let mut project_deps = project_dependencies(&manifest)?;
+    let mut dependencies = if no_default_deps {
+        Vec::new()
+    } else {
+        project_deps.required
+    };
+
+    for group in groups {
+        let Some(deps) = project_deps.optional.remove(group) else {
+            return Err(Error::OptionalDependencyGroupNotFound {
+                group: group.clone(),
+                origin: None,
+                available: Vec::new(),
+                suggestions: Vec::new(),
+            });
+        };
+        dependencies.extend(deps);
+    }
+
+    let deps = dependency_iter(&dependencies).collect::<Vec<_>>();
+    let unsatisfied = unsatisfied_dependencies(python_env, &deps, config)?
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    if unsatisfied.is_empty() {
+        return Ok(());
+    }
+
+    install_project_dependencies(python_env, &unsatisfied, &manifest, options, config)
+}
+
+/// Get the `dependencies` the `python_env` doesn't satisfy, checked against the installed
+/// packages' metadata without running the installer.
+fn unsatisfied_dependencies(
+    python_env: &PythonEnvironment,
+    dependencies: &[Dependency],
+    config: &Config,
+) -> HuakResult<Vec<Dependency>> {
+    let start = Instant::now();
+    let installed = python_env.installed_versions()?;
+    let unsatisfied = dependencies
+        .iter()
+        .filter(|it| !it.is_satisfied_by(&installed))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    config.terminal().print_profile(
+        format!(
+            "checked {} dependencies against installed metadata",
+            dependencies.len()
+        ),
+        start.elapsed(),
+    )?;
+
+    Ok(unsatisfied)
+}
+
+/// Get the requested `dependencies` that the project's manifest lists.
+///
+/// Names are matched by their canonical form. A name the manifest doesn't list is an error
+/// suggesting the manifest's closest dependencies.
+fn find_project_dependencies(
+    dependencies: &[String],
+    manifest: &LocalManifest,
+) -> HuakResult<Vec<Dependency>> {
+    let manifest_data = manifest.manifest_data();
+    let mut deps = Vec::new();
+
+    for dep in dependency_iter(dependencies) {
+        if !manifest_data.contains_project_dependency_any(dep.name()) {
+            let mut listed = manifest_data.project_dependencies().unwrap_or_default();
+
+            if let Some(it) = manifest_data.project_optional_dependencies() {
+                listed.extend(it.into_values().flatten());
+            }
+
+            let names = dependency_iter(listed).map(|it| it.name().to_string());
+
+            return Err(dependency_not_found(dep.name(), names));
+        }
+
+        deps.push(dep);
+    }
+
+    Ok(deps)
+}
+
 /// Create a workspace directory on the system.
 fn create_workspace<T: Into<PathBuf>>(path: T) -> HuakResult<()> {
     let root = path.into();
 
     if root.exists() {
-        return Err(Error::DirectoryExists(root));
+        return Err(Error::DirectoryExists { path: root });
     }
 
     std::fs::create_dir(root)?;
@@ -83,20 +453,325 @@ fn create_workspace<T: Into<PathBuf>>(path: T) -> HuakResult<()> {
     Ok(())
 }
 
-/// Initialize a directory for git.
+/// Initialize a directory for git if it isn't already a git repository.
 ///
-/// - Initializes git
-/// - Adds .gitignore if one doesn't already exist.
+/// The .gitignore is generated with the rest of the project's template files.
 fn init_git<T: Into<PathBuf>>(path: T) -> HuakResult<()> {
     let root = path.into();
 
     if !root.join(".git").exists() {
         git_init(&root)?;
     }
-    let gitignore_path = root.join(".gitignore");
-    if !gitignore_path.exists() {
-        std::fs::write(gitignore_path, default_python_gitignore())?;
-    }
 
     Ok(())
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::{TerminalOptions, Verbosity};
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::{tempdir, TempDir};
+
+    /// Create a project with a mock virtual environment whose Python exits with `code`.
+    ///
+    /// The tools each command needs are already "installed" so only the tool itself runs.
+    fn mock_project(code: u8) -> (TempDir, Config) {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let bin = root.join(".venv").join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(root.join(".venv").join("pyvenv.cfg"), "version = 3.11.0\n").unwrap();
+        for it in ["build", "mypy", "pytest", "ruff"] {
+            std::fs::write(bin.join(it), "").unwrap();
+        }
+        let python = bin.join("python");
+        std::fs::write(&python, format!("#!/bin/sh\nexit {code}\n")).unwrap();
+        std::fs::set_permissions(&python, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            r#"[project]
+name = "mock-project"
+version = "0.0.1"
+
+[project.optional-dependencies]
+dev = ["build", "mypy", "pytest", "ruff"]
+"#,
+        )
+        .unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root,
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        (dir, config)
+    }
+
+    fn exit_code<T: std::fmt::Debug>(res: HuakResult<T>) -> u8 {
+        let e = res.unwrap_err();
+        assert!(matches!(e, Error::SubprocessFailure(_)), "{e}");
+        e.exit_code()
+    }
+
+    #[test]
+    fn test_run_exit_code() {
+        let (_dir, config) = mock_project(0);
+
+        assert_eq!(exit_code(run_command_str("exit 3", &config)), 3);
+    }
+
+    #[test]
+    fn test_lint_exit_code() {
+        let (_dir, config) = mock_project(2);
+        let options = LintOptions {
+            values: None,
+            select: None,
+            ignore: None,
+            files: FileFilter::default(),
+            include_types: true,
+            baseline: None,
+            write_baseline: false,
+            sarif: None,
+            env: HashMap::new(),
+            no_cache: false,
+            install_options: InstallOptions::default(),
+        };
+
+        assert_eq!(exit_code(lint_project(&config, &options)), 2);
+    }
+
+    #[test]
+    fn test_test_exit_code() {
+        // pytest exits with 5 when no tests are collected.
+        let (_dir, config) = mock_project(5);
+        let options = TestOptions {
+            values: None,
+            install_options: InstallOptions::default(),
+            groups: Vec::new(),
+            no_default_deps: false,
+            shard: None,
+            shard_by: ShardBy::default(),
+            shuffle: false,
+            seed: None,
+            until_failure: false,
+            max_runs: None,
+            no_capture: false,
+            log_cli_level: None,
+            paths: Vec::new(),
+            env: HashMap::new(),
+        };
+
+        assert_eq!(exit_code(test_project(&config, &options)), 5);
+    }
+
+    #[test]
+    fn test_fmt_check_exit_code() {
+        let (_dir, config) = mock_project(1);
+        let options = FormatOptions {
+            values: Some(vec!["--check".to_string()]),
+            files: FileFilter::default(),
+            env: HashMap::new(),
+            no_cache: false,
+            install_options: InstallOptions::default(),
+        };
+
+        assert_eq!(exit_code(format_project(&config, &options)), 1);
+    }
+
+    #[test]
+    fn test_build_exit_code() {
+        let (_dir, config) = mock_project(4);
+        let options = BuildOptions {
+            values: None,
+            cibuildwheel: false,
+            env: HashMap::new(),
+            format: OutputFormat::default(),
+            checksums: false,
+            verify_contents: false,
+            from_clean: false,
+            allow_dirty: false,
+            install_options: InstallOptions::default(),
+        };
+
+        assert_eq!(exit_code(build_project(&config, &options)), 4);
+    }
+
+    #[test]
+    fn test_operation_env() {
+        let (_dir, config) = mock_project(0);
+        let root = config.workspace_root.clone();
+        std::fs::write(
+            root.join(".venv").join("bin").join("python"),
+            "#!/bin/sh\nexit ${EXIT_CODE:-0}\n",
+        )
+        .unwrap();
+        let mut manifest = std::fs::read_to_string(root.join("pyproject.toml")).unwrap();
+        manifest
+            .push_str("\n[tool.huak.test]\nenv = { EXIT_CODE = \"${HUAK_UNDEFINED_VAR:-3}\" }\n");
+        std::fs::write(root.join("pyproject.toml"), &manifest).unwrap();
+        let options = |env: &[(&str, &str)]| TestOptions {
+            values: None,
+            install_options: InstallOptions::default(),
+            groups: Vec::new(),
+            no_default_deps: false,
+            shard: None,
+            shard_by: ShardBy::default(),
+            shuffle: false,
+            seed: None,
+            until_failure: false,
+            max_runs: None,
+            no_capture: false,
+            log_cli_level: None,
+            paths: Vec::new(),
+            env: env
+                .iter()
+                .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+                .collect(),
+        };
+
+        assert_eq!(exit_code(test_project(&config, &options(&[]))), 3);
+        assert_eq!(
+            exit_code(test_project(&config, &options(&[("EXIT_CODE", "6")]))),
+            6
+        );
+        assert!(matches!(
+            test_project(&config, &options(&[("EXIT_CODE", "${HUAK_UNDEFINED_VAR}")])),
+            Err(Error::UndefinedEnvironmentVariable(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_millis(3420)), "3.4s");
+        assert_eq!(format_elapsed(Duration::from_millis(125_040)), "2m 5.0s");
+    }
+
+    #[test]
+    fn test_option_value() {
+        let args = ["-x", "--outdir", "out", "--junitxml=report.xml"].map(String::from);
+
+        assert_eq!(option_value(&args, &["-o", "--outdir"]), Some("out"));
+        assert_eq!(
+            option_value(&args, &["--junitxml", "--junit-xml"]),
+            Some("report.xml")
+        );
+        assert_eq!(option_value(&args, &["--output-dir"]), None);
+    }
+
+    #[test]
+    fn test_tool_values() {
+        let (_dir, mut config) = mock_project(0);
+        let root = config.workspace_root.clone();
+        std::fs::create_dir(root.join("tests")).unwrap();
+        std::fs::write(root.join("tests").join("test_a.py"), "").unwrap();
+        let values = [
+            "test_a.py::test_b",
+            "-k",
+            "slow",
+            "--junitxml=report.xml",
+            "--log-cli-level=INFO",
+            "missing.py",
+        ]
+        .map(String::from);
+
+        assert_eq!(tool_values(&values, &root, &config), values);
+
+        config.cwd = root.join("tests");
+
+        assert_eq!(
+            tool_values(&values, &root, &config),
+            [
+                "tests/test_a.py::test_b",
+                "-k",
+                "slow",
+                "--junitxml=tests/report.xml",
+                "--log-cli-level=INFO",
+                "missing.py",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_test_from_nested_dir() {
+        let (dir, mut config) = mock_project(0);
+        let root = config.workspace_root.clone();
+        let log = dir.path().join("python.log");
+        std::fs::write(
+            root.join(".venv").join("bin").join("python"),
+            format!(
+                "#!/bin/sh\n[ \"$2\" = pytest ] || exit 0\necho \"$(pwd) $*\" > {}\nexit 5\n",
+                log.display()
+            ),
+        )
+        .unwrap();
+        std::fs::create_dir(root.join("tests")).unwrap();
+        std::fs::write(root.join("tests").join("test_a.py"), "").unwrap();
+        config.cwd = root.join("tests");
+        let options = TestOptions {
+            values: Some(vec!["test_a.py".to_string()]),
+            install_options: InstallOptions::default(),
+            groups: Vec::new(),
+            no_default_deps: false,
+            shard: None,
+            shard_by: ShardBy::default(),
+            shuffle: false,
+            seed: None,
+            until_failure: false,
+            max_runs: None,
+            no_capture: false,
+            log_cli_level: None,
+            paths: Vec::new(),
+            env: HashMap::new(),
+        };
+
+        assert_eq!(exit_code(test_project(&config, &options)), 5);
+        let pytest = std::fs::read_to_string(log).unwrap();
+        assert!(
+            pytest.starts_with(&format!("{} ", root.display())),
+            "{pytest}"
+        );
+        assert!(pytest.contains(" tests/test_a.py"), "{pytest}");
+    }
+
+    #[test]
+    fn test_interpolate_env() {
+        let lookup = |it: &str| (it == "HOME").then(|| "/home/huak".to_string());
+
+        assert_eq!(
+            interpolate_env("KEY", "${HOME}/.cache:$$HOME:$HOME", lookup).unwrap(),
+            "/home/huak/.cache:$HOME:$HOME"
+        );
+        assert_eq!(
+            interpolate_env("KEY", "${MISSING:-}${MISSING:-a b}", lookup).unwrap(),
+            "a b"
+        );
+        assert!(matches!(
+            interpolate_env("KEY", "${MISSING}", lookup),
+            Err(Error::UndefinedEnvironmentVariable(_))
+        ));
+        for it in ["${HOME", "${}", "${A-B}"] {
+            assert!(matches!(
+                interpolate_env("KEY", it, lookup),
+                Err(Error::InvalidEnvironmentValue(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_error_exit_code() {
+        assert_eq!(
+            Error::SubprocessTimeout {
+                command: "pytest".to_string(),
+                seconds: 1
+            }
+            .exit_code(),
+            124
+        );
+        assert_eq!(Error::PythonNotFound.exit_code(), 1);
+    }
+}
@@ -0,0 +1,290 @@
+use crate::{
+    directory_is_venv, env_path_values, fs::maybe_exe, Error, HuakResult, PythonEnvironment,
+};
+use huak_pyproject_toml::{canonical_name, PyProjectToml};
+use pep440_rs::{Version, VersionSpecifiers};
+use std::{
+    env::consts::OS,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+/// The Python versions `huak build --cibuildwheel` can build wheels for.
+const CPYTHON_VERSIONS: [&str; 6] = ["3.8", "3.9", "3.10", "3.11", "3.12", "3.13"];
+
+/// Directories that never contain a project's extension sources.
+const SKIPPED_DIRS: [&str; 5] = [".git", "build", "dist", "target", "wheelhouse"];
+
+/// Why a project's extensions need a C compiler or a Rust toolchain to build.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct NativeBuild {
+    pub c: Vec<String>,
+    pub rust: Vec<String>,
+}
+
+impl NativeBuild {
+    /// Detect the native extensions a project builds from its build system and sources.
+    pub(crate) fn detect(data: &PyProjectToml, root: &Path) -> NativeBuild {
+        let build_system = data.get("build-system");
+        let requires = build_system
+            .and_then(|it| it.get("requires"))
+            .and_then(|it| it.as_array())
+            .map(|it| {
+                it.iter()
+                    .filter_map(|it| it.as_str())
+                    .map(|it| {
+                        let end = it
+                            .find(|c: char| !(c.is_alphanumeric() || "-_.".contains(c)))
+                            .unwrap_or(it.len());
+                        canonical_name(&it[..end])
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let backend = build_system
+            .and_then(|it| it.get("build-backend"))
+            .and_then(|it| it.as_str())
+            .unwrap_or_default();
+        let mut native = NativeBuild::default();
+
+        if backend.starts_with("maturin") || requires.iter().any(|it| it == "maturin") {
+            native.rust.push("the build backend is maturin".to_string());
+        }
+
+        if requires.iter().any(|it| it == "setuptools-rust") {
+            native
+                .rust
+                .push("setuptools-rust is a build requirement".to_string());
+        }
+
+        if std::fs::read_to_string(root.join("setup.py")).is_ok_and(|it| it.contains("ext_modules"))
+        {
+            native.c.push("setup.py defines ext_modules".to_string());
+        }
+
+        if data
+            .tool_table()
+            .and_then(|it| it.get("setuptools"))
+            .and_then(|it| it.get("ext-modules"))
+            .is_some()
+        {
+            native
+                .c
+                .push("[tool.setuptools] defines ext-modules".to_string());
+        }
+
+        if requires.iter().any(|it| it == "cython") {
+            native.c.push("Cython is a build requirement".to_string());
+        } else if let Some(it) = find_source(root, Path::new(""), "pyx") {
+            native
+                .c
+                .push(format!("{} is a Cython source", it.display()));
+        }
+
+        native
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.c.is_empty() && self.rust.is_empty()
+    }
+
+    /// Check the tools needed to build the extensions are installed.
+    pub(crate) fn check(&self, python_env: &PythonEnvironment) -> HuakResult<()> {
+        let mut problems = Vec::new();
+        let mut remediation = Vec::new();
+
+        if !self.c.is_empty() {
+            let mut missing = false;
+
+            if c_compiler().is_none() {
+                problems.push(format!("no C compiler was found ({})", self.c.join(", ")));
+                missing = true;
+            }
+
+            if !has_python_headers(python_env)? {
+                problems.push("the Python headers (Python.h) weren't found".to_string());
+                missing = true;
+            }
+
+            if missing {
+                remediation.push(c_remediation());
+            }
+        }
+
+        if !self.rust.is_empty() && find_executable("cargo").is_none() {
+            problems.push(format!(
+                "no Rust toolchain (cargo) was found ({})",
+                self.rust.join(", ")
+            ));
+            remediation.push("install Rust from https://rustup.rs");
+        }
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+
+        Err(Error::NativeBuildToolsMissing(format!(
+            "{}\n{}",
+            problems.join("\n"),
+            remediation.join("\n")
+        )))
+    }
+}
+
+/// Get the cibuildwheel build selector (`CIBW_BUILD`) for the Python versions satisfying
+/// `requires_python`, like `cp311-* cp312-*`.
+pub(crate) fn cibuildwheel_build_selector(requires_python: &VersionSpecifiers) -> Option<String> {
+    let identifiers = CPYTHON_VERSIONS
+        .iter()
+        .filter(|it| Version::from_str(it).is_ok_and(|version| requires_python.contains(&version)))
+        .map(|it| format!("cp{}-*", it.replace('.', "")))
+        .collect::<Vec<_>>();
+
+    if identifiers.is_empty() {
+        None
+    } else {
+        Some(identifiers.join(" "))
+    }
+}
+
+/// Find the first file with the `extension` in `root.join(dir)`, relative to `root`.
+fn find_source(root: &Path, dir: &Path, extension: &str) -> Option<PathBuf> {
+    let mut entries = std::fs::read_dir(root.join(dir))
+        .ok()?
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+    entries.sort_by_key(std::fs::DirEntry::file_name);
+
+    entries.into_iter().find_map(|entry| {
+        let path = dir.join(entry.file_name());
+
+        if !entry.file_type().is_ok_and(|it| it.is_dir()) {
+            return path
+                .extension()
+                .is_some_and(|it| it == extension)
+                .then_some(path);
+        }
+
+        let skipped = SKIPPED_DIRS.iter().any(|it| entry.file_name() == *it)
+            || directory_is_venv(entry.path());
+        if skipped {
+            None
+        } else {
+            find_source(root, &path, extension)
+        }
+    })
+}
+
+/// Find the C compiler named by `CC` or the platform's usual one.
+fn c_compiler() -> Option<PathBuf> {
+    if let Some(it) = std::env::var("CC")
+        .ok()
+        .and_then(|it| it.split_whitespace().next().map(ToString::to_string))
+    {
+        let path = PathBuf::from(&it);
+        return if path.is_absolute() {
+            path.exists().then_some(path)
+        } else {
+            find_executable(&it)
+        };
+    }
+
+    let compilers: &[&str] = if OS == "windows" {
+        &["cl", "gcc", "clang"]
+    } else {
+        &["cc", "gcc", "clang"]
+    };
+
+    compilers.iter().find_map(|it| find_executable(it))
+}
+
+fn find_executable(name: &str) -> Option<PathBuf> {
+    env_path_values()?
+        .into_iter()
+        .map(|it| maybe_exe(it.join(name)))
+        .find(|it| it.is_file())
+}
+
+fn has_python_headers(python_env: &PythonEnvironment) -> HuakResult<bool> {
+    let output = Command::new(python_env.python_path())
+        .args([
+            "-c",
+            "import os, sysconfig; \
+             print(os.path.exists(os.path.join(sysconfig.get_paths()['include'], 'Python.h')))",
+        ])
+        .output()?;
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim() == "True")
+}
+
+fn c_remediation() -> &'static str {
+    match OS {
+        "macos" => "install the Xcode Command Line Tools with `xcode-select --install`",
+        "windows" => "install the Microsoft C++ Build Tools from https://visualstudio.microsoft.com/visual-cpp-build-tools/",
+        _ => "install a C compiler and the Python headers with your package manager (like `apt install build-essential python3-dev` or `dnf install gcc python3-devel`)",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_native_build_detect() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let data = |contents: &str| contents.parse::<PyProjectToml>().unwrap();
+
+        assert!(NativeBuild::detect(&data("[project]\nname = \"pure\"\n"), root).is_empty());
+
+        assert_eq!(
+            NativeBuild::detect(
+                &data(
+                    r#"[build-system]
+requires = ["maturin>=1.0,<2.0"]
+build-backend = "maturin"
+"#
+                ),
+                root
+            ),
+            NativeBuild {
+                c: Vec::new(),
+                rust: vec!["the build backend is maturin".to_string()],
+            }
+        );
+
+        std::fs::write(
+            root.join("setup.py"),
+            "setup(ext_modules=[Extension(\"fast\", [\"fast.c\"])])",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("src").join("pkg")).unwrap();
+        std::fs::write(root.join("src").join("pkg").join("speedups.pyx"), "").unwrap();
+
+        assert_eq!(
+            NativeBuild::detect(&data("[build-system]\nrequires = [\"setuptools\"]\n"), root).c,
+            [
+                "setup.py defines ext_modules".to_string(),
+                format!(
+                    "{} is a Cython source",
+                    Path::new("src").join("pkg").join("speedups.pyx").display()
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cibuildwheel_build_selector() {
+        let selector =
+            |it: &str| cibuildwheel_build_selector(&VersionSpecifiers::from_str(it).unwrap());
+
+        assert_eq!(
+            selector(">=3.11"),
+            Some("cp311-* cp312-* cp313-*".to_string())
+        );
+        assert_eq!(selector(">=3.9,<3.11"), Some("cp39-* cp310-*".to_string()));
+        assert_eq!(selector("<3.0"), None);
+    }
+}
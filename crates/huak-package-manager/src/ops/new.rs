@@ -1,79 +1,82 @@
-use toml_edit::{Item, Table};
-
-use super::{create_workspace, init_git};
-use crate::{
-    default_package_test_file_contents, importable_package_name, last_path_component, Config,
-    Dependency, Error, HuakResult, LocalManifest, WorkspaceOptions,
+use super::{
+    create_workspace, init_git, scaffold::commit_template_files, write_template_files,
+    ExistingFiles, ProjectTemplate,
 };
-use std::str::FromStr;
+use crate::{git, last_path_component, Config, Error, HuakResult, WorkspaceOptions};
+use git2::Signature;
+use std::path::Path;
 
 pub fn new_app_project(config: &Config, options: &WorkspaceOptions) -> HuakResult<()> {
-    new_lib_project(config, options)?;
-
-    let workspace = config.workspace();
-    let mut manifest = workspace.current_local_manifest()?;
+    new_project(ProjectTemplate::App, config, options)
+}
 
-    let name = last_path_component(workspace.root().as_path())?;
-    let as_dep = Dependency::from_str(&name)?;
-    manifest.manifest_data_mut().set_project_name(&name);
+pub fn new_lib_project(config: &Config, options: &WorkspaceOptions) -> HuakResult<()> {
+    new_project(ProjectTemplate::Lib, config, options)
+}
 
-    let src_path = workspace.root().join("src");
-    let importable_name = importable_package_name(as_dep.name())?;
-    std::fs::write(
-        src_path.join(importable_name).join("main.py"),
-        super::DEFAULT_PYTHON_MAIN_FILE_CONTENTS,
-    )?;
+pub fn new_bare_project(config: &Config, options: &WorkspaceOptions) -> HuakResult<()> {
+    new_project(ProjectTemplate::Bare, config, options)
+}
 
-    if let Some(table) = manifest.manifest_data_mut().project_table_mut() {
-        let scripts = &mut table["scripts"];
+pub fn new_cli_project(config: &Config, options: &WorkspaceOptions) -> HuakResult<()> {
+    new_project(ProjectTemplate::Cli, config, options)
+}
 
-        if scripts.is_none() {
-            *scripts = Item::Table(Table::new());
-        }
+fn new_project(
+    template: ProjectTemplate,
+    config: &Config,
+    options: &WorkspaceOptions,
+) -> HuakResult<()> {
+    let workspace = config.workspace();
 
-        let importable = importable_package_name(&name)?;
-        scripts[name] = toml_edit::value(format!("{importable}.main:main"));
+    // Error if a project already exists.
+    if workspace.current_local_manifest().is_ok() {
+        return Err(Error::ProjectFound);
     }
 
-    manifest.write_file()
-}
+    // Fail before anything is written if there's no identity to commit as.
+    let signature = initial_commit_signature(workspace.root(), options)?;
 
-pub fn new_lib_project(config: &Config, options: &WorkspaceOptions) -> HuakResult<()> {
-    let workspace = config.workspace();
-
-    // Create a new manifest file or error if one exists.
-    let mut manifest = match workspace.current_local_manifest() {
-        Ok(_) => return Err(Error::ProjectFound),
-        Err(_) => LocalManifest::template(workspace.root().join("pyproject.toml")),
-    };
+    // A directory that already has files in it can only be used when forced, and then only
+    // the files it's missing are written.
+    if workspace.root().is_dir() {
+        if !options.force && std::fs::read_dir(workspace.root())?.next().is_some() {
+            return Err(Error::DirectoryNotEmpty {
+                path: workspace.root().clone(),
+            });
+        }
+    } else if !config.dry_run {
+        create_workspace(workspace.root())?;
+    }
 
-    create_workspace(workspace.root())?;
+    let name = last_path_component(workspace.root())?;
+    let files = template.files(&name, options.uses_git, true)?;
 
-    if options.uses_git {
+    if options.uses_git && !config.dry_run {
         init_git(workspace.root())?;
     }
 
-    let name = &last_path_component(&config.workspace_root)?;
-    manifest.manifest_data_mut().set_project_name(name);
-
-    manifest.manifest_data_mut().formatted();
-    manifest.write_file()?;
-    manifest.write_file()?;
-
-    let as_dep = Dependency::from_str(name)?;
-    let src_path = config.workspace_root.join("src");
-    let importable_name = importable_package_name(as_dep.name())?;
-    std::fs::create_dir_all(src_path.join(&importable_name))?;
-    std::fs::create_dir_all(config.workspace_root.join("tests"))?;
-    std::fs::write(
-        src_path.join(&importable_name).join("__init__.py"),
-        super::DEFAULT_PYTHON_INIT_FILE_CONTENTS,
-    )?;
-    std::fs::write(
-        config.workspace_root.join("tests").join("test_version.py"),
-        default_package_test_file_contents(&importable_name),
-    )
-    .map_err(Error::IOError)
+    let written = write_template_files(workspace.root(), &files, ExistingFiles::Skip, config)?;
+
+    match (options.initial_commit.as_deref(), signature) {
+        (Some(message), Some(signature)) => {
+            commit_template_files(workspace.root(), &written, message, &signature, config)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Get the identity to make the `--initial-commit` as in the repository at `root`, if one is
+/// requested.
+pub(super) fn initial_commit_signature(
+    root: &Path,
+    options: &WorkspaceOptions,
+) -> HuakResult<Option<Signature<'static>>> {
+    if options.uses_git && options.initial_commit.is_some() {
+        git::signature(root).map(Some)
+    } else {
+        Ok(None)
+    }
 }
 
 #[cfg(test)]
@@ -100,7 +103,9 @@ mod tests {
         };
         let options = WorkspaceOptions {
             uses_git: false,
+            force: false,
             values: None,
+            initial_commit: None,
         };
 
         new_lib_project(&config, &options).unwrap();
@@ -133,6 +138,56 @@ def test_version():
         assert_eq!(init_file, expected_init_file);
     }
 
+    #[test]
+    fn test_new_project_existing_directory() {
+        let dir = tempdir().unwrap();
+        let workspace_root = dir.path().join("mock-project");
+        let cwd = workspace_root.clone();
+        let terminal_options = TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+        let config = Config {
+            workspace_root: workspace_root.clone(),
+            cwd,
+            terminal_options,
+            ..Default::default()
+        };
+        let mut options = WorkspaceOptions {
+            uses_git: false,
+            force: false,
+            values: None,
+            initial_commit: None,
+        };
+        std::fs::create_dir(&workspace_root).unwrap();
+
+        new_bare_project(&config, &options).unwrap();
+        std::fs::remove_file(workspace_root.join("pyproject.toml")).unwrap();
+        std::fs::create_dir(workspace_root.join("tests")).unwrap();
+        std::fs::write(workspace_root.join("tests").join("test_version.py"), "").unwrap();
+
+        assert!(matches!(
+            new_lib_project(&config, &options),
+            Err(Error::DirectoryNotEmpty { .. })
+        ));
+        assert!(!workspace_root.join("pyproject.toml").exists());
+
+        options.force = true;
+        new_lib_project(&config, &options).unwrap();
+
+        assert!(workspace_root.join("pyproject.toml").exists());
+        assert!(workspace_root
+            .join("src")
+            .join("mock_project")
+            .join("__init__.py")
+            .exists());
+        assert!(
+            std::fs::read_to_string(workspace_root.join("tests").join("test_version.py"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_new_app_project() {
         let dir = tempdir().unwrap();
@@ -150,7 +205,9 @@ def test_version():
         };
         let options = WorkspaceOptions {
             uses_git: false,
+            force: false,
             values: None,
+            initial_commit: None,
         };
 
         new_app_project(&config, &options).unwrap();
@@ -182,4 +239,39 @@ if __name__ == "__main__":
         );
         assert_eq!(main_file, expected_main_file);
     }
+
+    #[test]
+    fn test_new_bare_project() {
+        let dir = tempdir().unwrap();
+        let workspace_root = dir.path().join("mock-project");
+        let cwd = workspace_root.clone();
+        let terminal_options = TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+        let config = Config {
+            workspace_root,
+            cwd,
+            terminal_options,
+            ..Default::default()
+        };
+        let options = WorkspaceOptions {
+            uses_git: false,
+            force: false,
+            values: None,
+            initial_commit: None,
+        };
+
+        new_bare_project(&config, &options).unwrap();
+
+        let ws = config.workspace();
+        let manifest = ws.current_local_manifest().unwrap();
+
+        assert_eq!(
+            manifest.manifest_data().project_name(),
+            Some(String::from("mock-project"))
+        );
+        assert!(!ws.root().join("src").exists());
+        assert!(!ws.root().join("tests").exists());
+    }
 }
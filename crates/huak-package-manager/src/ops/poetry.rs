@@ -0,0 +1,487 @@
+use crate::{Config, Error, HuakResult, LocalManifest};
+use huak_pyproject_toml::{canonical_name, PyProjectToml};
+use std::{collections::HashMap, fmt::Write, path::Path};
+use termcolor::Color;
+use toml_edit::{value, Array, Item, Table, TableLike};
+
+/// A project's dependencies as they're listed in PEP 621's `[project]` table.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct ProjectDependencies {
+    pub(crate) requires_python: Option<String>,
+    pub(crate) required: Vec<String>,
+    pub(crate) optional: HashMap<String, Vec<String>>,
+}
+
+/// Check if the project's dependencies are only listed in Poetry's `[tool.poetry]` tables.
+pub(crate) fn is_poetry_managed(data: &PyProjectToml) -> bool {
+    poetry_table(data).is_some_and(|it| it.contains_key("dependencies"))
+        && data.project_table().map_or(true, |it| {
+            !it.contains_key("dependencies") && !it.contains_key("optional-dependencies")
+        })
+}
+
+/// Fail for operations that write dependencies to `[project]` when the project's dependencies
+/// are managed by Poetry, since the tables would disagree.
+pub(crate) fn ensure_not_poetry_managed(manifest: &LocalManifest) -> HuakResult<()> {
+    if is_poetry_managed(manifest.manifest_data()) {
+        return Err(Error::PoetryManagedDependencies);
+    }
+
+    Ok(())
+}
+
+/// Get the project's dependencies from `[project]`, or from `[tool.poetry]` with Poetry's
+/// constraints translated to PEP 440 specifiers if the project is managed by Poetry.
+pub(crate) fn project_dependencies(manifest: &LocalManifest) -> HuakResult<ProjectDependencies> {
+    let data = manifest.manifest_data();
+
+    if is_poetry_managed(data) {
+        let root = manifest.path().parent().unwrap_or(Path::new("."));
+        return poetry_dependencies(data, root);
+    }
+
+    Ok(ProjectDependencies {
+        requires_python: data
+            .project_table()
+            .and_then(|it| it.get("requires-python"))
+            .and_then(Item::as_str)
+            .map(ToString::to_string),
+        required: data.project_dependencies().unwrap_or_default(),
+        optional: data.project_optional_dependencies().unwrap_or_default(),
+    })
+}
+
+/// Move the dependencies of a project managed by Poetry to PEP 621's `[project]` table.
+///
+/// The project's name, version, and description are copied too if `[project]` doesn't have
+/// them. Poetry's other tables (like its scripts) are left alone.
+pub fn migrate_poetry_project(config: &Config) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let mut manifest = workspace.current_local_manifest()?;
+
+    if !is_poetry_managed(manifest.manifest_data()) {
+        return config
+            .terminal()
+            .print_warning("the project's dependencies aren't managed by poetry");
+    }
+
+    let deps = project_dependencies(&manifest)?;
+    let data = manifest.manifest_data_mut();
+    let poetry = data
+        .tool_table()
+        .and_then(|it| it.get("poetry"))
+        .cloned()
+        .unwrap_or_default();
+    let Some(project) = data.project_table_or_insert_mut() else {
+        return Err(Error::InvalidMetadata(
+            "[project] isn't a table".to_string(),
+        ));
+    };
+
+    for key in ["name", "version", "description"] {
+        if let (false, Some(it)) = (project.contains_key(key), poetry.get(key)) {
+            project.insert(key, it.clone());
+        }
+    }
+    if let Some(it) = deps.requires_python.as_ref() {
+        project.insert("requires-python", value(it));
+    }
+    project.insert(
+        "dependencies",
+        value(deps.required.iter().map(String::as_str).collect::<Array>()),
+    );
+    if !deps.optional.is_empty() {
+        let mut groups = deps.optional.iter().collect::<Vec<_>>();
+        groups.sort();
+        let mut table = Table::new();
+        for (group, it) in groups {
+            table.insert(
+                group,
+                value(it.iter().map(String::as_str).collect::<Array>()),
+            );
+        }
+        project.insert("optional-dependencies", Item::Table(table));
+    }
+
+    if let Some(poetry) = data
+        .tool_table_mut()
+        .and_then(|it| it.get_mut("poetry"))
+        .and_then(Item::as_table_like_mut)
+    {
+        for key in ["dependencies", "dev-dependencies", "group", "extras"] {
+            poetry.remove(key);
+        }
+    }
+    data.formatted();
+
+    config.write_manifest(&manifest)?;
+
+    if config.dry_run {
+        return Ok(());
+    }
+
+    let count = deps.required.len() + deps.optional.values().map(Vec::len).sum::<usize>();
+    config.terminal().print_custom(
+        "Migrated",
+        format!("{count} dependencies from [tool.poetry] to [project]"),
+        Color::Green,
+        true,
+    )
+}
+
+fn poetry_table(data: &PyProjectToml) -> Option<&dyn TableLike> {
+    data.tool_table()
+        .and_then(|it| it.get("poetry"))
+        .and_then(Item::as_table_like)
+}
+
+/// Read the dependencies from `[tool.poetry.dependencies]`, its dependency groups, and its
+/// extras. Path dependencies are resolved relative to `root`.
+fn poetry_dependencies(data: &PyProjectToml, root: &Path) -> HuakResult<ProjectDependencies> {
+    let mut deps = ProjectDependencies::default();
+    let Some(poetry) = poetry_table(data) else {
+        return Ok(deps);
+    };
+    let mut optional = HashMap::new();
+
+    for (name, item) in table_items(poetry.get("dependencies")) {
+        if name == "python" {
+            let constraint = item.as_str().ok_or_else(|| {
+                Error::InvalidPoetryDependency("python must be a version constraint".to_string())
+            })?;
+            deps.requires_python = Some(poetry_specifiers(constraint)?);
+            continue;
+        }
+
+        let (requirement, is_optional) = poetry_requirement(name, item, root)?;
+        if is_optional {
+            optional.insert(canonical_name(name), requirement);
+        } else {
+            deps.required.push(requirement);
+        }
+    }
+
+    // Optional dependencies are only installable through the extras that list them.
+    for (extra, item) in table_items(poetry.get("extras")) {
+        let names = item
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|it| it.as_str());
+        let group = deps.optional.entry(extra.to_string()).or_default();
+        group.extend(names.filter_map(|it| optional.get(&canonical_name(it)).cloned()));
+    }
+
+    let groups = table_items(poetry.get("group"))
+        .map(|(group, it)| (group, it.get("dependencies")))
+        .chain(std::iter::once(("dev", poetry.get("dev-dependencies"))));
+    for (group, table) in groups {
+        for (name, item) in table_items(table) {
+            let (requirement, _) = poetry_requirement(name, item, root)?;
+            deps.optional
+                .entry(group.to_string())
+                .or_default()
+                .push(requirement);
+        }
+    }
+
+    Ok(deps)
+}
+
+fn table_items(item: Option<&Item>) -> impl Iterator<Item = (&str, &Item)> {
+    item.and_then(Item::as_table_like)
+        .into_iter()
+        .flat_map(TableLike::iter)
+}
+
+/// Get the PEP 508 requirement for a Poetry dependency and whether it's optional.
+fn poetry_requirement(name: &str, item: &Item, root: &Path) -> HuakResult<(String, bool)> {
+    if let Some(constraint) = item.as_str() {
+        return Ok((format!("{name}{}", poetry_specifiers(constraint)?), false));
+    }
+
+    let Some(table) = item.as_table_like() else {
+        return Err(Error::InvalidPoetryDependency(format!(
+            "{name} (multiple constraints aren't supported)"
+        )));
+    };
+    let get_str = |key| table.get(key).and_then(Item::as_str);
+
+    let mut requirement = name.to_string();
+    let extras = table
+        .get("extras")
+        .and_then(Item::as_array)
+        .map(|it| it.iter().filter_map(|it| it.as_str()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    if !extras.is_empty() {
+        let _ = write!(requirement, "[{}]", extras.join(","));
+    }
+
+    let url = if let Some(git) = get_str("git") {
+        let reference = get_str("rev").or(get_str("tag")).or(get_str("branch"));
+        Some(match reference {
+            Some(it) => format!("git+{git}@{it}"),
+            None => format!("git+{git}"),
+        })
+    } else if let Some(path) = get_str("path") {
+        Some(format!("file://{}", root.join(path).display()))
+    } else {
+        get_str("url").map(ToString::to_string)
+    };
+    match url {
+        Some(it) => {
+            let _ = write!(requirement, " @ {it}");
+        }
+        None => requirement.push_str(&poetry_specifiers(get_str("version").unwrap_or("*"))?),
+    }
+
+    let mut markers = Vec::new();
+    if let Some(it) = get_str("python") {
+        markers.push(python_markers(it)?);
+    }
+    if let Some(it) = get_str("markers") {
+        markers.push(it.to_string());
+    }
+    if !markers.is_empty() {
+        // A space is needed before the `;` of a requirement with a URL.
+        let _ = write!(requirement, " ; {}", markers.join(" and "));
+    }
+
+    let is_optional = table
+        .get("optional")
+        .and_then(Item::as_bool)
+        .unwrap_or_default();
+
+    Ok((requirement, is_optional))
+}
+
+/// Translate a Poetry version constraint (like `^1.2`, `~1.2.3`, or `>= 1.2, < 2`) to PEP 440
+/// specifiers. Any version (`*`) is an empty string.
+fn poetry_specifiers(constraint: &str) -> HuakResult<String> {
+    let invalid = || {
+        Error::InvalidPoetryDependency(format!(
+            "{constraint} (can't be written as PEP 440 specifiers)"
+        ))
+    };
+
+    if constraint.contains("||") {
+        return Err(invalid());
+    }
+
+    let mut specifiers = Vec::new();
+
+    for part in constraint_parts(constraint) {
+        if part == "*" {
+            continue;
+        }
+
+        let specifier = if let Some(version) = part.strip_prefix('^') {
+            let release = release(version).ok_or_else(invalid)?;
+            let index = release
+                .iter()
+                .position(|it| *it != 0)
+                .unwrap_or(release.len() - 1);
+            format!(">={version},<{}", upper_bound(&release, index))
+        } else if part.starts_with("~=") {
+            part
+        } else if let Some(version) = part.strip_prefix('~') {
+            let release = release(version).ok_or_else(invalid)?;
+            let index = usize::from(release.len() > 1);
+            format!(">={version},<{}", upper_bound(&release, index))
+        } else if ["==", "!=", ">=", "<=", ">", "<"]
+            .iter()
+            .any(|it| part.starts_with(it))
+        {
+            part
+        } else {
+            format!("=={}", part.trim_start_matches('='))
+        };
+
+        specifiers.push(specifier);
+    }
+
+    Ok(specifiers.join(","))
+}
+
+/// Split a constraint at its commas and spaces, keeping an operator with its version.
+fn constraint_parts(constraint: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+
+    for group in constraint.split(',') {
+        let mut pending = String::new();
+
+        for token in group.split_whitespace() {
+            pending.push_str(token);
+
+            if !token.chars().all(|it| "<>=!~^".contains(it)) {
+                parts.push(std::mem::take(&mut pending));
+            }
+        }
+
+        if !pending.is_empty() {
+            parts.push(pending);
+        }
+    }
+
+    parts
+}
+
+/// Get the numbers of a version's release segment, like `[1, 2, 3]` for `1.2.3b1`.
+fn release(version: &str) -> Option<Vec<u64>> {
+    let end = version
+        .find(|it: char| !it.is_ascii_digit() && it != '.')
+        .unwrap_or(version.len());
+
+    version[..end]
+        .trim_end_matches('.')
+        .split('.')
+        .map(|it| it.parse().ok())
+        .collect()
+}
+
+/// Bump the release number at `index` and zero the ones after it.
+fn upper_bound(release: &[u64], index: usize) -> String {
+    release
+        .iter()
+        .enumerate()
+        .map(|(i, it)| match i.cmp(&index) {
+            std::cmp::Ordering::Less => it.to_string(),
+            std::cmp::Ordering::Equal => (it + 1).to_string(),
+            std::cmp::Ordering::Greater => "0".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Translate a Poetry dependency's `python` constraint to environment markers.
+fn python_markers(constraint: &str) -> HuakResult<String> {
+    let specifiers = poetry_specifiers(constraint)?;
+
+    Ok(specifiers
+        .split(',')
+        .filter(|it| !it.is_empty())
+        .map(|it| {
+            let version = it.trim_start_matches(|c: char| "<>=!~".contains(c));
+            let operator = &it[..it.len() - version.len()];
+            format!("python_version {operator} \"{version}\"")
+        })
+        .collect::<Vec<_>>()
+        .join(" and "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TerminalOptions, Verbosity};
+    use std::str::FromStr;
+    use tempfile::tempdir;
+
+    const POETRY_PYPROJECT_TOML: &str = r#"[tool.poetry]
+name = "mock-project"
+version = "0.1.0"
+description = ""
+
+[tool.poetry.dependencies]
+python = "^3.8"
+click = "^8.1.3"
+requests = { version = "~2.31", extras = ["socks"] }
+tomli = { version = ">= 1.1, < 3", python = "<3.11" }
+pandas = { version = "*", optional = true }
+
+[tool.poetry.extras]
+data = ["pandas"]
+
+[tool.poetry.group.dev.dependencies]
+pytest = "7.4.*"
+"#;
+
+    #[test]
+    fn test_poetry_specifiers() {
+        for (constraint, specifiers) in [
+            ("^1.2.3", ">=1.2.3,<2.0.0"),
+            ("^1.2", ">=1.2,<2.0"),
+            ("^0.2.3", ">=0.2.3,<0.3.0"),
+            ("^0.0.3", ">=0.0.3,<0.0.4"),
+            ("^0", ">=0,<1"),
+            ("~1.2.3", ">=1.2.3,<1.3.0"),
+            ("~1", ">=1,<2"),
+            ("~=1.2", "~=1.2"),
+            ("1.2.*", "==1.2.*"),
+            ("1.2.3", "==1.2.3"),
+            (">= 1.2, < 1.5", ">=1.2,<1.5"),
+            (">=1.2 !=1.3", ">=1.2,!=1.3"),
+            ("*", ""),
+        ] {
+            assert_eq!(poetry_specifiers(constraint).unwrap(), specifiers);
+        }
+
+        assert!(matches!(
+            poetry_specifiers("^1.2 || ^2.0"),
+            Err(Error::InvalidPoetryDependency(_))
+        ));
+    }
+
+    #[test]
+    fn test_poetry_dependencies() {
+        let data = PyProjectToml::from_str(POETRY_PYPROJECT_TOML).unwrap();
+        let deps = poetry_dependencies(&data, Path::new("/project")).unwrap();
+
+        assert!(is_poetry_managed(&data));
+        assert_eq!(deps.requires_python.as_deref(), Some(">=3.8,<4.0"));
+        assert_eq!(
+            deps.required,
+            [
+                "click>=8.1.3,<9.0.0",
+                "requests[socks]>=2.31,<2.32",
+                "tomli>=1.1,<3 ; python_version < \"3.11\"",
+            ]
+        );
+        assert_eq!(
+            deps.optional,
+            HashMap::from([
+                ("data".to_string(), vec!["pandas".to_string()]),
+                ("dev".to_string(), vec!["pytest==7.4.*".to_string()]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_migrate_poetry_project() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::write(root.join("pyproject.toml"), POETRY_PYPROJECT_TOML).unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root,
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let ws = config.workspace();
+
+        assert!(matches!(
+            ensure_not_poetry_managed(&ws.current_local_manifest().unwrap()),
+            Err(Error::PoetryManagedDependencies)
+        ));
+
+        migrate_poetry_project(&config).unwrap();
+
+        let manifest = ws.current_local_manifest().unwrap();
+        let data = manifest.manifest_data();
+
+        assert!(!is_poetry_managed(data));
+        assert_eq!(data.project_name().as_deref(), Some("mock-project"));
+        assert_eq!(data.project_version().as_deref(), Some("0.1.0"));
+        assert_eq!(
+            project_dependencies(&manifest).unwrap(),
+            poetry_dependencies(
+                &PyProjectToml::from_str(POETRY_PYPROJECT_TOML).unwrap(),
+                ws.root()
+            )
+            .unwrap()
+        );
+        assert!(poetry_table(data).is_some_and(|it| !it.contains_key("dependencies")));
+    }
+}
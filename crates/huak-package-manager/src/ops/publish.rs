@@ -1,23 +1,62 @@
-use super::add_venv_to_command;
-use crate::{Config, Dependency, HuakResult, InstallOptions};
-use std::{process::Command, str::FromStr};
+use super::{
+    add_venv_to_command,
+    checksums::{check_artifact_checksums, is_checksum_file},
+    install_locked_tool, operation_env, option_value, project_version, verify_changelog,
+    CIBUILDWHEEL_OUTPUT_DIR,
+};
+use crate::{
+    git::{head_tags, working_tree_changes},
+    Config, Dependency, Error, HuakResult, InstallOptions, PythonEnvironment,
+};
+use pep440_rs::Version;
+use std::{collections::HashMap, path::Path, process::Command, str::FromStr};
+use termcolor::Color;
 
+/// The simple index of pypi.org.
+const PYPI_INDEX_URL: &str = "https://pypi.org/simple/";
+
+/// The simple index of test.pypi.org.
+const TEST_PYPI_INDEX_URL: &str = "https://test.pypi.org/simple/";
+
+#[allow(clippy::struct_excessive_bools)]
 pub struct PublishOptions {
     /// A values vector of publish options typically used for passing on arguments.
     pub values: Option<Vec<String>>,
+    /// Environment variables for the tool's subprocesses, merged over `[tool.huak.publish] env`.
+    pub env: HashMap<String, String>,
     pub install_options: InstallOptions,
+    /// Publish a version that's already on the index, uploading only the files that aren't.
+    pub skip_existing: bool,
+    /// Publish with uncommitted changes in the working tree.
+    pub allow_dirty: bool,
+    /// Publish from a HEAD that isn't tagged with the project's version.
+    pub allow_untagged: bool,
+    /// Publish without verifying the artifacts against their `SHA256SUMS`.
+    pub no_checksums: bool,
 }
 
+/// Publish the project's distributions with twine.
+///
+/// Before anything is uploaded the working tree must be clean, HEAD must be tagged with the
+/// project's version, the version can't already be on the index, and the artifacts must match
+/// their recorded checksums. Each check can be skipped with its `PublishOptions` flag.
 pub fn publish_project(config: &Config, options: &PublishOptions) -> HuakResult<()> {
     let workspace = config.workspace();
-    let mut manifest = workspace.current_local_manifest()?;
+
+    if workspace.requires_changelog() {
+        verify_changelog(config)?;
+    }
+
+    let version = project_version(config)?;
+    verify_git_state(&version, config, options)?;
+
     let python_env = workspace.resolve_python_environment()?;
+    verify_unpublished(&version, &python_env, config, options)?;
 
-    // Install `twine` if it isn't already installed.
+    // Install `twine` at its pinned version.
     let pub_dep = Dependency::from_str("twine")?;
-    if !python_env.contains_module(pub_dep.name())? {
-        python_env.install_packages(&[&pub_dep], &options.install_options, config)?;
-    }
+    install_locked_tool(&pub_dep, &python_env, &options.install_options, config)?;
+    let mut manifest = workspace.current_local_manifest()?;
 
     // Add the installed `twine` package to the manifest file if it isn't already there.
     if !manifest
@@ -36,15 +75,366 @@ pub fn publish_project(config: &Config, options: &PublishOptions) -> HuakResult<
     }
 
     manifest.manifest_data_mut().formatted();
-    manifest.write_file()?;
+    config.write_manifest(&manifest)?;
+
+    // Run `twine`, uploading the wheels from `huak build --cibuildwheel` too. Checksum files
+    // are left out.
+    let dist = upload_files(workspace.root(), "dist", None);
+    let wheelhouse = upload_files(workspace.root(), CIBUILDWHEEL_OUTPUT_DIR, Some("whl"));
+    verify_artifacts(&dist, &wheelhouse, config, options)?;
 
-    // Run `twine`.
     let mut cmd = Command::new(python_env.python_path());
-    let mut args = vec!["-m", "twine", "upload", "dist/*"];
+    let mut args = ["-m", "twine", "upload"].map(String::from).to_vec();
+    if dist.is_empty() && wheelhouse.is_empty() {
+        // Let twine report there's nothing to upload.
+        args.push("dist/*".to_string());
+    }
+    args.extend(dist);
+    args.extend(wheelhouse);
     if let Some(v) = options.values.as_ref() {
-        args.extend(v.iter().map(String::as_str));
+        args.extend(v.iter().cloned());
+    }
+    if options.skip_existing && !args.iter().any(|it| it == "--skip-existing") {
+        args.push("--skip-existing".to_string());
     }
     add_venv_to_command(&mut cmd, &python_env)?;
-    cmd.args(args).current_dir(workspace.root());
-    config.terminal().run_command(&mut cmd)
+    cmd.args(args)
+        .envs(operation_env("publish", &options.env, config)?)
+        .current_dir(workspace.root());
+    config.run_effect("publish", &mut cmd)
+}
+
+/// Get the files to upload from the `dir` relative to the `root`, like `dist/pkg-0.1.0.tar.gz`,
+/// optionally only ones with an `extension`.
+///
+/// The directory is read instead of globbed so a root with glob characters (like `[`) still
+/// works.
+fn upload_files(root: &Path, dir: &str, extension: Option<&str>) -> Vec<String> {
+    let mut files = std::fs::read_dir(root.join(dir))
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|it| it.path())
+                .filter(|it| {
+                    it.is_file()
+                        && !is_checksum_file(it)
+                        && extension.map_or(true, |ext| it.extension().is_some_and(|it| it == ext))
+                })
+                .filter_map(|it| {
+                    it.file_name()
+                        .map(|name| format!("{dir}/{}", name.to_string_lossy()))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    files.sort();
+
+    files
+}
+
+/// Verify the artifacts to upload against the `SHA256SUMS` of their directories, so one changed
+/// since it was built isn't published. Checksums are recorded for artifacts that don't have one.
+fn verify_artifacts(
+    dist: &[String],
+    wheelhouse: &[String],
+    config: &Config,
+    options: &PublishOptions,
+) -> HuakResult<()> {
+    let mut terminal = config.terminal();
+
+    if options.no_checksums {
+        return terminal.print_custom(
+            "Skipped",
+            "checking the artifacts' checksums (--no-checksums)",
+            Color::Yellow,
+            true,
+        );
+    }
+
+    let root = config.workspace().root().clone();
+    for (dir, files) in [("dist", dist), (CIBUILDWHEEL_OUTPUT_DIR, wheelhouse)] {
+        if files.is_empty() {
+            continue;
+        }
+
+        let verified = check_artifact_checksums(&root.join(dir), "publish", config)?;
+        if verified > 0 {
+            terminal.print_custom(
+                "Verified",
+                format!("the checksums of {verified} artifacts in {dir}"),
+                Color::Green,
+                true,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the working tree is clean and HEAD is tagged with the `version`.
+fn verify_git_state(version: &str, config: &Config, options: &PublishOptions) -> HuakResult<()> {
+    let root = config.workspace().root().clone();
+    let mut terminal = config.terminal();
+
+    if options.allow_dirty {
+        terminal.print_custom(
+            "Skipped",
+            "checking for uncommitted changes (--allow-dirty)",
+            Color::Yellow,
+            true,
+        )?;
+    } else if let Some(changes) = working_tree_changes(&root) {
+        if !changes.is_empty() {
+            let changes = changes
+                .iter()
+                .map(|it| it.display().to_string())
+                .collect::<Vec<_>>();
+            return Err(Error::UncommittedRelease(changes.join(", ")));
+        }
+        terminal.print_custom("Verified", "the working tree is clean", Color::Green, true)?;
+    }
+
+    if options.allow_untagged {
+        terminal.print_custom(
+            "Skipped",
+            format!("checking HEAD is tagged {version} (--allow-untagged)"),
+            Color::Yellow,
+            true,
+        )?;
+    } else if let Some(tags) = head_tags(&root) {
+        let Some(tag) = tags.iter().find(|it| is_version_tag(it, version)) else {
+            return Err(Error::UntaggedRelease(format!(
+                "expected a tag like v{version} (HEAD is tagged {})",
+                if tags.is_empty() {
+                    "with nothing".to_string()
+                } else {
+                    tags.join(", ")
+                }
+            )));
+        };
+        terminal.print_custom(
+            "Verified",
+            format!("HEAD is tagged {tag}"),
+            Color::Green,
+            true,
+        )?;
+    } else {
+        terminal.print_warning(
+            "the project isn't in a git repository, so its working tree and tags weren't checked",
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Check if a `tag` is for the `version`, like `1.4.0`, `v1.4.0`, or `my-package-v1.4.0`.
+fn is_version_tag(tag: &str, version: &str) -> bool {
+    tag.strip_suffix(version).is_some_and(|prefix| {
+        let prefix = prefix.strip_suffix('v').unwrap_or(prefix);
+        prefix.is_empty() || prefix.ends_with(['-', '/', '@'])
+    })
+}
+
+/// Check the `version` isn't already published to the index twine uploads to.
+fn verify_unpublished(
+    version: &str,
+    python_env: &PythonEnvironment,
+    config: &Config,
+    options: &PublishOptions,
+) -> HuakResult<()> {
+    let mut terminal = config.terminal();
+
+    if options.skip_existing {
+        return terminal.print_custom(
+            "Skipped",
+            "checking the index for the version (--skip-existing)",
+            Color::Yellow,
+            true,
+        );
+    }
+
+    let values = options.values.clone().unwrap_or_default();
+    let env = operation_env("publish", &options.env, config)?;
+    let var = |key: &str| env.get(key).cloned().or_else(|| std::env::var(key).ok());
+    let Some(index) = repository_index(
+        &values,
+        var("TWINE_REPOSITORY_URL").as_deref(),
+        var("TWINE_REPOSITORY").as_deref(),
+    ) else {
+        return terminal.print_warning(
+            "the versions published to the repository couldn't be checked (only PyPI and TestPyPI are known)",
+        );
+    };
+    let name = config
+        .workspace()
+        .current_local_manifest()?
+        .manifest_data()
+        .project_name()
+        .ok_or(Error::InternalError("missing project name".to_string()))?;
+    let Some(published) = published_versions(python_env, &name, index)? else {
+        return terminal.print_warning(format!(
+            "the versions published to {index} couldn't be read"
+        ));
+    };
+
+    // Versions are compared by PEP 440, so 1.4 is the same release as 1.4.0.
+    let parsed = Version::from_str(version).ok();
+    if published
+        .iter()
+        .any(|it| it == version || (parsed.is_some() && Version::from_str(it).ok() == parsed))
+    {
+        return Err(Error::VersionAlreadyPublished {
+            package: name,
+            version: version.to_string(),
+            repository: index.to_string(),
+        });
+    }
+
+    terminal.print_custom(
+        "Verified",
+        format!("{name} {version} isn't published to {index}"),
+        Color::Green,
+        true,
+    )
+}
+
+/// Get the simple index of the repository twine uploads to, from its `--repository-url` and
+/// `--repository` options or their environment variables. Only pypi.org and test.pypi.org are
+/// known, and pypi.org is twine's default.
+fn repository_index(
+    values: &[String],
+    url_var: Option<&str>,
+    repository_var: Option<&str>,
+) -> Option<&'static str> {
+    let url = option_value(values, &["--repository-url"]).or(url_var);
+    let repository = option_value(values, &["--repository", "-r"]).or(repository_var);
+
+    if let Some(url) = url {
+        return if url.contains("test.pypi.org") {
+            Some(TEST_PYPI_INDEX_URL)
+        } else if url.contains("pypi.org") {
+            Some(PYPI_INDEX_URL)
+        } else {
+            None
+        };
+    }
+
+    match repository {
+        Some("testpypi") => Some(TEST_PYPI_INDEX_URL),
+        Some("pypi") | None => Some(PYPI_INDEX_URL),
+        Some(_) => None,
+    }
+}
+
+/// Get the versions of the project `name` published to the `index` with `pip index`. `None` is
+/// returned if the index couldn't be read.
+fn published_versions(
+    python_env: &PythonEnvironment,
+    name: &str,
+    index: &str,
+) -> HuakResult<Option<Vec<String>>> {
+    let mut cmd = Command::new(python_env.python_path());
+    cmd.args(["-m", "pip", "index", "versions", name, "--pre"])
+        .args([
+            "--index-url",
+            index,
+            "--retries",
+            "1",
+            "--disable-pip-version-check",
+        ]);
+    let output = cmd.output()?;
+
+    if output.status.success() {
+        return Ok(Some(parse_index_versions(&String::from_utf8_lossy(
+            &output.stdout,
+        ))));
+    }
+
+    // A project that's never been published isn't on the index at all, but pip says the same
+    // when the index can't be reached, after warning about it.
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("No matching distribution")
+        && !stderr.contains("Retrying")
+        && !stderr.contains("Could not fetch")
+    {
+        return Ok(Some(Vec::new()));
+    }
+
+    Ok(None)
+}
+
+/// Read the versions from `pip index versions` output.
+fn parse_index_versions(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .find_map(|it| it.trim().strip_prefix("Available versions:"))
+        .map(|it| {
+            it.split(',')
+                .map(str::trim)
+                .filter(|it| !it.is_empty())
+                .map(ToString::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_version_tag() {
+        for tag in [
+            "1.4.0",
+            "v1.4.0",
+            "my-package-v1.4.0",
+            "my-package/1.4.0",
+            "pkg@1.4.0",
+        ] {
+            assert!(is_version_tag(tag, "1.4.0"), "{tag}");
+        }
+        for tag in ["1.4.0rc1", "v1.4.1", "v11.4.0", "release1.4.0"] {
+            assert!(!is_version_tag(tag, "1.4.0"), "{tag}");
+        }
+    }
+
+    #[test]
+    fn test_repository_index() {
+        let args = |it: &[&str]| it.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        assert_eq!(repository_index(&[], None, None), Some(PYPI_INDEX_URL));
+        assert_eq!(
+            repository_index(&args(&["-r", "testpypi"]), None, None),
+            Some(TEST_PYPI_INDEX_URL)
+        );
+        assert_eq!(
+            repository_index(
+                &args(&["--repository-url=https://test.pypi.org/legacy/"]),
+                None,
+                None
+            ),
+            Some(TEST_PYPI_INDEX_URL)
+        );
+        assert_eq!(
+            repository_index(&[], Some("https://upload.pypi.org/legacy/"), None),
+            Some(PYPI_INDEX_URL)
+        );
+        assert_eq!(repository_index(&[], None, Some("internal")), None);
+        assert_eq!(
+            repository_index(&[], Some("https://pypi.example.com/"), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_index_versions() {
+        let output = "huak (0.0.20a1)\nAvailable versions: 0.0.20a1, 0.0.19, 0.0.18\n  \
+            INSTALLED: 0.0.19\n  LATEST:    0.0.20a1\n";
+
+        assert_eq!(
+            parse_index_versions(output),
+            ["0.0.20a1", "0.0.19", "0.0.18"]
+        );
+        assert!(parse_index_versions("").is_empty());
+    }
 }
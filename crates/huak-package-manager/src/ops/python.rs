@@ -1,81 +1,271 @@
-use crate::{
-    active_python_env_path, directory_is_venv, venv_executables_dir_path, Config, Environment,
-    Error, HuakResult, PythonEnvironment,
-};
+use super::{cache::format_size, install_project_dependencies, reapply_dependency_overrides};
+use crate::{directory_is_venv, Config, Environment, Error, HuakResult, InstallOptions};
 use huak_home::huak_home_dir;
 use huak_python_manager::{
-    install_with_target, release_options_from_requested_version, resolve_release, RequestedVersion,
-    Strategy,
+    install_with_progress, release_options_from_requested_version, resolve_release,
+    RequestedVersion, Strategy, Version,
+};
+use serde::Serialize;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
 };
-use huak_toolchain::Channel;
-use std::{process::Command, str::FromStr};
 use termcolor::Color;
 
-pub fn list_python(config: &Config) -> HuakResult<()> {
+/// How often a download's progress is reported.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct ListPythonOptions {
+    /// Print the interpreters as JSON for scripts instead.
+    pub json: bool,
+}
+
+/// Print the Python interpreters found on `PATH`, newest first. Interpreters reached through
+/// several paths (like symlinks or shims) are listed once, with the first path found.
+///
+/// The interpreter behind the project's environment is marked as active, and the one its
+/// pinned version resolves to is marked as pinned.
+pub fn list_python(config: &Config, options: &ListPythonOptions) -> HuakResult<()> {
     let env = Environment::new();
+    let workspace = config.workspace();
+    let active = workspace
+        .current_python_environment()
+        .ok()
+        .and_then(|it| real_path(it.python_path()));
+    let pinned = workspace
+        .python_version_pin()
+        .and_then(|it| workspace.resolve_python_interpreter(&it).ok())
+        .and_then(|it| real_path(&it));
+    let pythons = listed_pythons(
+        env.interpreters()
+            .interpreters()
+            .iter()
+            .map(|it| (*it.version(), it.path().clone())),
+    );
 
-    // Print enumerated Python paths as they exist in the `PATH` environment variable.
-    env.python_paths().enumerate().for_each(|(i, path)| {
-        config
-            .terminal()
-            .print_custom(i + 1, path.display(), Color::Blue, false)
-            .ok();
-    });
+    if options.json {
+        let pythons = pythons
+            .iter()
+            .map(|it| PythonEntry {
+                version: it.version.to_string(),
+                path: &it.path,
+                active: active.as_ref() == Some(&it.real_path),
+                pinned: pinned.as_ref() == Some(&it.real_path),
+            })
+            .collect::<Vec<_>>();
+        writeln!(
+            std::io::stdout(),
+            "{}",
+            serde_json::to_string_pretty(&pythons)?
+        )?;
+        return Ok(());
+    }
+
+    let width = pythons
+        .iter()
+        .map(|it| it.version.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let mut terminal = config.terminal();
+
+    for (i, it) in pythons.iter().enumerate() {
+        let marks = [
+            (active.as_ref() == Some(&it.real_path), "active"),
+            (pinned.as_ref() == Some(&it.real_path), "pinned"),
+        ]
+        .into_iter()
+        .filter_map(|(is_marked, mark)| is_marked.then_some(mark))
+        .collect::<Vec<_>>();
+        let mut line = format!("{:<width$}  {}", it.version, it.path.display());
+        if !marks.is_empty() {
+            line = format!("{line} ({})", marks.join(", "));
+        }
+
+        terminal.print_custom(i + 1, line, Color::Blue, false)?;
+    }
 
     Ok(())
 }
 
-pub fn use_python(version: &RequestedVersion, config: &Config) -> HuakResult<()> {
-    let ws = config.workspace();
+/// A Python interpreter listed by `huak python list`.
+#[derive(Debug, PartialEq, Eq)]
+struct ListedPython {
+    version: Version,
+    path: PathBuf,
+    /// The path with symlinks resolved, identifying the interpreter.
+    real_path: PathBuf,
+}
 
-    let Some(path) = ws
-        .resolve_local_toolchain(Some(&Channel::from_str(&version.to_string())?))
-        .ok()
-        .map(|it| PythonEnvironment::new(it.root().join(".venv")).expect("toolchain venv"))
-        .map(|it| it.python_path().clone()) // TODO(cnpryer): Perf
-        .or(
-            // TODO(cnpryer): Re-export `Interpreter` as public
-            // Get a path to an interpreter based on the version provided, excluding any activated Python environment.
-            Environment::resolve_python_interpreters()
-                .interpreters()
-                .iter()
-                .filter(|py| {
-                    !active_python_env_path().map_or(false, |it| {
-                        py.path().parent() == Some(&venv_executables_dir_path(it))
-                    })
-                })
-                .find(|py| version.matches_version(py.version()))
-                .map(|py| py.path().clone()), // TODO(cnpryer): Perf
-        )
-    else {
-        return Err(Error::PythonNotFound);
-    };
+/// A Python interpreter printed by `huak python list --json`.
+#[derive(Serialize)]
+struct PythonEntry<'a> {
+    version: String,
+    path: &'a Path,
+    /// The interpreter behind the project's environment.
+    active: bool,
+    /// The interpreter the project's pinned version resolves to.
+    pinned: bool,
+}
+
+/// Get the `pythons` (in `PATH` order) sorted by version, newest first, with each interpreter
+/// listed once by its real path. Interpreters with equal versions keep their order.
+fn listed_pythons(pythons: impl Iterator<Item = (Version, PathBuf)>) -> Vec<ListedPython> {
+    let mut listed = Vec::<ListedPython>::new();
+
+    for (version, path) in pythons {
+        let real_path = real_path(&path).unwrap_or_else(|| path.clone());
+
+        match listed.iter_mut().find(|it| it.real_path == real_path) {
+            // Aliases like python3 and python3.11 are named with less of the version.
+            Some(it) if version.patch.is_some() && it.version.patch.is_none() => {
+                it.version = version;
+            }
+            Some(_) => (),
+            None => listed.push(ListedPython {
+                version,
+                path,
+                real_path,
+            }),
+        }
+    }
+
+    listed.sort_by_key(|it| std::cmp::Reverse(it.version));
+    listed
+}
+
+/// Get the path with symlinks resolved.
+fn real_path(path: &Path) -> Option<PathBuf> {
+    dunce::canonicalize(path).ok()
+}
 
-    // Remove the current Python virtual environment if one exists.
+/// Print the path of the Python interpreter `huak python use` would use for the `version`,
+/// failing if there isn't one. A major.minor version matches any of its patch releases.
+pub fn check_python(version: &RequestedVersion, config: &Config) -> HuakResult<()> {
+    let path = config
+        .workspace()
+        .resolve_python_interpreter(version)
+        .map_err(|e| match e {
+            Error::PythonNotFound => Error::PythonVersionNotFound {
+                version: version.to_string(),
+            },
+            e => e,
+        })?;
+
+    writeln!(std::io::stdout(), "{}", path.display())?;
+
+    Ok(())
+}
+
+pub struct UsePythonOptions {
+    /// Recreate an existing virtual environment built with a different Python version and
+    /// reinstall the project's dependencies to it.
+    pub recreate: bool,
+    pub install_options: InstallOptions,
+}
+
+pub fn use_python(
+    version: &RequestedVersion,
+    config: &Config,
+    options: &UsePythonOptions,
+) -> HuakResult<()> {
     let workspace = config.workspace();
-    match workspace.current_python_environment() {
-        Ok(it) if directory_is_venv(it.root()) => std::fs::remove_dir_all(it.root())?,
-        // TODO(cnpryer): This might be a clippy bug.
-        #[allow(clippy::no_effect)]
-        Ok(_) | Err(Error::PythonEnvironmentNotFound | Error::UnsupportedPythonEnvironment(_)) => {
-            ();
+    let path = workspace.resolve_python_interpreter(version)?;
+
+    // Pin the version for the project so new environments are created with it.
+    if let Ok(mut manifest) = workspace.current_local_manifest() {
+        if manifest.manifest_data().python_version_pin() != Some(version.to_string()) {
+            manifest
+                .manifest_data_mut()
+                .set_python_version_pin(&version.to_string());
+            config.write_manifest(&manifest)?;
+        }
+    }
+
+    // Check the current Python virtual environment if one exists.
+    let current = match workspace.current_python_environment() {
+        Ok(it) if directory_is_venv(it.root()) => Some(it),
+        Ok(_)
+        | Err(Error::PythonEnvironmentNotFound | Error::UnsupportedPythonEnvironment { .. }) => {
+            None
         }
         Err(e) => return Err(e),
     };
 
+    if let Some(venv) = current.as_ref() {
+        if version.matches_version(venv.python_version()) {
+            return Ok(());
+        }
+
+        if !options.recreate {
+            return config.terminal().print_warning(format!(
+                "{} still uses Python {} (run `huak python use {version} --recreate` to \
+                recreate it with Python {version})",
+                venv.root().display(),
+                venv.python_version()
+            ));
+        }
+
+        config.remove_path(venv.root())?;
+    }
+
     // Create a new Python environment using the interpreter matching the version provided.
     let mut cmd = Command::new(path);
     cmd.args(["-m", "venv", ".venv"])
+        .args(config.venv_options.args())
         .current_dir(&config.workspace_root);
-    config.terminal().run_command(&mut cmd)
+    config.run_effect("venv", &mut cmd)?;
+
+    // Reinstall the project's dependencies (including its groups) to the recreated environment.
+    if current.is_some() {
+        reinstall_project_dependencies(config, &options.install_options)?;
+    }
+
+    Ok(())
 }
 
-pub fn install_python(version: RequestedVersion) -> HuakResult<()> {
+/// Install the dependencies declared by the current project's manifest, if there is one.
+fn reinstall_project_dependencies(
+    config: &Config,
+    install_options: &InstallOptions,
+) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let Ok(manifest) = workspace.current_local_manifest() else {
+        return Ok(());
+    };
+
+    let mut deps = manifest
+        .manifest_data()
+        .project_dependencies()
+        .unwrap_or_default();
+
+    if let Some(optional_deps) = manifest.manifest_data().project_optional_dependencies() {
+        deps.extend(optional_deps.into_values().flatten());
+    }
+
+    deps.sort();
+    deps.dedup();
+
+    if deps.is_empty() {
+        return Ok(());
+    }
+
+    let python_env = workspace.current_python_environment()?;
+    install_project_dependencies(&python_env, &deps, &manifest, install_options, config)?;
+    reapply_dependency_overrides(&python_env, install_options, config)
+}
+
+/// Install the Python release best matching the `version` to Huak's toolchains, reporting the
+/// download's progress every couple of seconds.
+pub fn install_python(version: RequestedVersion, config: &Config) -> HuakResult<()> {
     // Use default selection strategy to find the best match for the requested version.
     let strategy = Strategy::Selection(release_options_from_requested_version(version)?);
 
     let Some(release) = resolve_release(&strategy) else {
-        return Err(Error::PythonReleaseNotFound(strategy.to_string()));
+        return Err(Error::PythonReleaseNotFound {
+            version: strategy.to_string(),
+        });
     };
 
     // Always install to Huak's toolchain.
@@ -88,38 +278,195 @@ pub fn install_python(version: RequestedVersion) -> HuakResult<()> {
         return Err(Error::HuakHomeNotFound);
     };
 
-    install_with_target(&release, target).map_err(|e| Error::PythonInstallError(e.to_string()))
+    let mut terminal = config.terminal();
+    terminal.print_custom(
+        "Downloading",
+        format!("{} {}", release.kind, release.version),
+        Color::Green,
+        true,
+    )?;
+    let mut last_report = Instant::now();
+    install_with_progress(&release, &target, |it| {
+        if last_report.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        last_report = Instant::now();
+        let total = it
+            .total
+            .map(|it| format!(" of {}", format_size(it)))
+            .unwrap_or_default();
+        terminal
+            .print_custom(
+                "Downloaded",
+                format!("{}{total}", format_size(it.downloaded)),
+                Color::Cyan,
+                true,
+            )
+            .ok();
+    })
+    .map_err(|e| Error::PythonInstallError(e.to_string()))?;
+
+    terminal.print_custom("Installed", target.display(), Color::Green, true)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{TerminalOptions, Verbosity};
+    use crate::{PythonEnvironment, TerminalOptions, Verbosity};
+    use std::str::FromStr;
     use tempfile::tempdir;
 
-    #[test]
-    fn test_use_python() {
-        let dir = tempdir().unwrap();
+    fn latest_requested_version() -> RequestedVersion {
         let interpreters = Environment::resolve_python_interpreters();
         let version = interpreters.latest().unwrap().version();
-        let version = RequestedVersion {
+
+        RequestedVersion {
             major: version.major,
             minor: version.minor,
             patch: None,
-        };
-        let workspace_root = dir.path().to_path_buf();
-        let cwd = workspace_root.clone();
+        }
+    }
+
+    fn test_config(workspace_root: &Path) -> Config {
         let terminal_options = TerminalOptions {
             verbosity: Verbosity::Quiet,
             ..Default::default()
         };
-        let config = Config {
-            workspace_root,
-            cwd,
+
+        Config {
+            workspace_root: workspace_root.to_path_buf(),
+            cwd: workspace_root.to_path_buf(),
             terminal_options,
             ..Default::default()
+        }
+    }
+
+    /// Initialize a venv and rewrite its pyvenv.cfg to look like it was built with another Python.
+    fn mismatched_venv(version: &RequestedVersion, config: &Config) -> PathBuf {
+        let options = UsePythonOptions {
+            recreate: true,
+            install_options: InstallOptions::default(),
         };
+        use_python(version, config, &options).unwrap();
+        let root = config.workspace_root.join(".venv");
+        let cfg = root.join("pyvenv.cfg");
+        let contents = std::fs::read_to_string(&cfg)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                if line.trim_start().starts_with("version") {
+                    "version = 3.0.0"
+                } else {
+                    line
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(cfg, contents).unwrap();
+
+        root
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_listed_pythons() {
+        let dir = tempdir().unwrap();
+        let bin = dir.path();
+        for name in ["python3.10", "python3.12"] {
+            std::fs::write(bin.join(name), "").unwrap();
+        }
+        std::os::unix::fs::symlink(bin.join("python3.12"), bin.join("python3")).unwrap();
+        let pythons = [
+            (Version::from_str("3.12").unwrap(), bin.join("python3")),
+            (Version::from_str("3.10").unwrap(), bin.join("python3.10")),
+            (Version::from_str("3.12.1").unwrap(), bin.join("python3.12")),
+            (Version::from_str("3.10").unwrap(), bin.join("python3.10")),
+        ];
+
+        let listed = listed_pythons(pythons.into_iter())
+            .into_iter()
+            .map(|it| (it.version.to_string(), it.path))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            listed,
+            [
+                ("3.12.1".to_string(), bin.join("python3")),
+                ("3.10".to_string(), bin.join("python3.10")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_python() {
+        let dir = tempdir().unwrap();
+        let config = test_config(dir.path());
+
+        check_python(&latest_requested_version(), &config).unwrap();
+        assert!(matches!(
+            check_python(&RequestedVersion::from_str("2.1").unwrap(), &config),
+            Err(Error::PythonVersionNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_use_python() {
+        let dir = tempdir().unwrap();
+        let version = latest_requested_version();
+        let config = test_config(dir.path());
+        let options = UsePythonOptions {
+            recreate: true,
+            install_options: InstallOptions::default(),
+        };
+
+        use_python(&version, &config, &options).unwrap();
+    }
+
+    #[test]
+    fn test_use_python_recreates_mismatched_venv() {
+        let dir = tempdir().unwrap();
+        let version = latest_requested_version();
+        let config = test_config(dir.path());
+        let root = mismatched_venv(&version, &config);
+        let options = UsePythonOptions {
+            recreate: true,
+            install_options: InstallOptions::default(),
+        };
+
+        use_python(&version, &config, &options).unwrap();
+
+        let venv = PythonEnvironment::new(root).unwrap();
+
+        assert!(version.matches_version(venv.python_version()));
+    }
+
+    #[test]
+    fn test_use_python_keeps_mismatched_venv() {
+        let dir = tempdir().unwrap();
+        let version = latest_requested_version();
+        let config = test_config(dir.path());
+        let root = mismatched_venv(&version, &config);
+        std::fs::write(
+            dir.path().join("pyproject.toml"),
+            "[project]\nname = \"mock-project\"\n",
+        )
+        .unwrap();
+        let options = UsePythonOptions {
+            recreate: false,
+            install_options: InstallOptions::default(),
+        };
+
+        use_python(&version, &config, &options).unwrap();
+
+        let venv = PythonEnvironment::new(root).unwrap();
 
-        use_python(&version, &config).unwrap();
+        assert_eq!(*venv.python_version(), Version::new(3, 0, 0));
+        assert_eq!(
+            config
+                .workspace()
+                .python_version_pin()
+                .map(|it| it.to_string()),
+            Some(version.to_string())
+        );
     }
 }
@@ -1,6 +1,23 @@
-use crate::{dependency_iter, Config, Error, HuakResult, InstallOptions};
+use super::{ensure_not_poetry_managed, find_project_dependencies};
+use crate::{
+    dependency::dependency_not_found,
+    dependency_iter,
+    history::{record_dependency_changes, DependencySnapshot},
+    similar_names, Config, Dependency, DependencyGraph, Error, HuakResult, InstallOptions,
+    LocalManifest,
+};
+use huak_pyproject_toml::{canonical_name, PyProjectToml};
+use std::{collections::BTreeSet, str::FromStr};
+use termcolor::Color;
+
+/// Packages that are never uninstalled because the environment itself needs them.
+const PROTECTED_PACKAGES: [&str; 3] = ["pip", "setuptools", "wheel"];
 
 pub struct RemoveOptions {
+    /// Remove an optional dependency group and all of its dependencies.
+    pub purge_group: bool,
+    /// Keep the manifest change even if uninstalling the dependencies fails.
+    pub keep_manifest: bool,
     pub install_options: InstallOptions,
 }
 
@@ -11,15 +28,12 @@ pub fn remove_project_dependencies(
 ) -> HuakResult<()> {
     let workspace = config.workspace();
     let mut manifest = workspace.current_local_manifest()?;
+    ensure_not_poetry_managed(&manifest)?;
+    let before = DependencySnapshot::new(manifest.manifest_data());
+    let original = manifest.manifest_data().to_string();
 
-    // Collect any dependencies to remove from the manifest file.
-    let deps = dependency_iter(dependencies)
-        .filter(|dep| {
-            manifest
-                .manifest_data()
-                .contains_project_dependency_any(dep.name())
-        })
-        .collect::<Vec<_>>();
+    // Collect the dependencies to remove from the manifest file.
+    let deps = find_project_dependencies(dependencies, &manifest)?;
 
     if deps.is_empty() {
         return Ok(());
@@ -32,7 +46,8 @@ pub fn remove_project_dependencies(
     for dep in &deps {
         manifest
             .manifest_data_mut()
-            .remove_project_dependency(dep.name());
+            .remove_project_dependency(dep.name())
+            .remove_dependency_hashes(dep.name());
 
         if let Some(groups) = optional_groups.as_ref() {
             for g in groups {
@@ -44,13 +59,269 @@ pub fn remove_project_dependencies(
     }
 
     manifest.manifest_data_mut().formatted();
-    manifest.write_file()?;
+    config.write_manifest(&manifest)?;
 
     // Uninstall the dependencies from the Python environment if an environment is found.
-    match workspace.current_python_environment() {
+    let uninstalled = match workspace.current_python_environment() {
         Ok(it) => it.uninstall_packages(&deps, &options.install_options, config),
         Err(Error::PythonEnvironmentNotFound) => Ok(()),
         Err(e) => Err(e),
+    };
+    restore_manifest_on_error(uninstalled, &manifest, &original, options, config)?;
+    record_dependency_changes("remove", &before, &manifest, None, config);
+
+    Ok(())
+}
+
+/// Remove `dependencies` from an optional dependency `group`.
+///
+/// Without `dependencies`, `purge_group` removes the whole group. Removed packages are
+/// uninstalled from the Python environment unless the project or another installed package
+/// still requires them.
+pub fn remove_project_optional_dependencies(
+    dependencies: &[String],
+    group: &str,
+    config: &Config,
+    options: &RemoveOptions,
+) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let mut manifest = workspace.current_local_manifest()?;
+    ensure_not_poetry_managed(&manifest)?;
+    let before = DependencySnapshot::new(manifest.manifest_data());
+    let original = manifest.manifest_data().to_string();
+
+    let Some(members) = manifest
+        .manifest_data()
+        .project_optional_dependencies()
+        .and_then(|mut it| it.remove(group))
+    else {
+        let groups = manifest
+            .manifest_data()
+            .project_optional_dependency_groups()
+            .unwrap_or_default();
+        return Err(Error::OptionalDependencyGroupNotFound {
+            group: group.to_string(),
+            origin: None,
+            available: Vec::new(),
+            suggestions: similar_names(group, &groups),
+        });
+    };
+
+    let removed = if dependencies.is_empty() {
+        if !options.purge_group {
+            return Ok(());
+        }
+
+        manifest
+            .manifest_data_mut()
+            .remove_project_optional_dependency_group(group);
+        dependency_iter(&members).collect::<Vec<_>>()
+    } else {
+        let member_names = dependency_iter(&members)
+            .map(|it| canonical_name(it.name()))
+            .collect::<Vec<_>>();
+        let mut deps = Vec::new();
+
+        for dep in dependency_iter(dependencies) {
+            if !member_names.contains(&canonical_name(dep.name())) {
+                return Err(dependency_not_found(dep.name(), &member_names));
+            }
+
+            manifest
+                .manifest_data_mut()
+                .remove_project_optional_dependency(dep.name(), group);
+            deps.push(dep);
+        }
+
+        deps
+    };
+
+    // Hashes are only kept for dependencies the manifest still lists.
+    for dep in &removed {
+        let data = manifest.manifest_data();
+        if !data.contains_project_dependency_any(dep.name())
+            && !data.contains_project_optional_dependency_any(dep.name())
+        {
+            manifest
+                .manifest_data_mut()
+                .remove_dependency_hashes(dep.name());
+        }
+    }
+
+    manifest.manifest_data_mut().formatted();
+    config.write_manifest(&manifest)?;
+
+    let uninstalled = uninstall_removed_packages(&removed, &manifest, options, config);
+    restore_manifest_on_error(uninstalled, &manifest, &original, options, config)?;
+    record_dependency_changes("remove", &before, &manifest, None, config);
+
+    Ok(())
+}
+
+/// Uninstall the packages `removed` from an optional dependency group unless the project or
+/// another installed package still requires them.
+fn uninstall_removed_packages(
+    removed: &[Dependency],
+    manifest: &LocalManifest,
+    options: &RemoveOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    let python_env = match config.workspace().current_python_environment() {
+        Ok(it) => it,
+        Err(Error::PythonEnvironmentNotFound) => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    let removed = removed
+        .iter()
+        .map(|it| it.name().to_string())
+        .collect::<Vec<_>>();
+    let plan = UninstallPlan::new(
+        &removed,
+        &listed_dependencies(manifest),
+        &python_env.dependency_graph()?,
+    );
+    let mut terminal = config.terminal();
+
+    for (name, reason) in &plan.retained {
+        terminal.print_custom("Kept", format!("{name} ({reason})"), Color::Cyan, true)?;
+    }
+
+    if plan.uninstall.is_empty() {
+        return Ok(());
+    }
+
+    python_env.uninstall_packages(&plan.uninstall, &options.install_options, config)?;
+
+    if config.dry_run {
+        return Ok(());
+    }
+
+    terminal.print_custom("Uninstalled", plan.uninstall.join(", "), Color::Green, true)
+}
+
+/// Restore the manifest to its `original` contents if uninstalling the removed dependencies
+/// failed, so it doesn't list different dependencies than the environment has installed.
+/// `--keep-manifest` keeps the change instead.
+fn restore_manifest_on_error(
+    uninstalled: HuakResult<()>,
+    manifest: &LocalManifest,
+    original: &str,
+    options: &RemoveOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    let Err(e) = uninstalled else {
+        return Ok(());
+    };
+    if options.keep_manifest || config.dry_run {
+        return Err(e);
+    }
+
+    PyProjectToml::from_str(original)?.write_toml(manifest.path())?;
+    config.terminal().print_custom(
+        "Restored",
+        format!(
+            "{} since uninstalling failed (pass --keep-manifest to keep the change)",
+            manifest.path().display()
+        ),
+        Color::Yellow,
+        true,
+    )?;
+
+    Err(e)
+}
+
+/// Get the names of the dependencies the manifest lists with where they're listed.
+fn listed_dependencies(manifest: &LocalManifest) -> Vec<(String, String)> {
+    let data = manifest.manifest_data();
+    let mut listed = dependency_iter(data.project_dependencies().unwrap_or_default())
+        .map(|it| {
+            (
+                it.name().to_string(),
+                "the project's dependencies".to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut groups = data
+        .project_optional_dependencies()
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<Vec<_>>();
+    groups.sort();
+
+    for (group, deps) in groups {
+        listed.extend(
+            dependency_iter(deps).map(|it| (it.name().to_string(), format!("the {group} group"))),
+        );
+    }
+
+    listed
+}
+
+/// The installed packages left unneeded by removing dependencies from the manifest.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct UninstallPlan {
+    /// The removed dependencies and the packages they pulled in that nothing else requires.
+    uninstall: Vec<String>,
+    /// The removed dependencies that are still required, with the reason why.
+    retained: Vec<(String, String)>,
+}
+
+impl UninstallPlan {
+    /// Plan the uninstall of the `removed` dependencies given the dependencies the manifest
+    /// still `listed` and the `DependencyGraph` of the installed packages.
+    fn new(removed: &[String], listed: &[(String, String)], graph: &DependencyGraph) -> Self {
+        let installed = graph.packages().collect::<BTreeSet<_>>();
+        let listed_names = listed
+            .iter()
+            .map(|(it, _)| canonical_name(it))
+            .collect::<Vec<_>>();
+
+        let mut uninstall = graph
+            .resolved(removed)
+            .into_iter()
+            .filter(|it| {
+                installed.contains(it.as_str())
+                    && !listed_names.contains(it)
+                    && !PROTECTED_PACKAGES.contains(&it.as_str())
+            })
+            .collect::<BTreeSet<_>>();
+
+        // Everything else that's installed keeps the packages it requires.
+        let others = installed
+            .iter()
+            .filter(|it| !uninstall.contains(**it))
+            .copied()
+            .collect::<Vec<_>>();
+        let required = graph.resolved(&others);
+        uninstall.retain(|it| !required.contains(it));
+
+        let mut retained = Vec::new();
+        for name in removed {
+            let canonical = canonical_name(name);
+
+            if let Some((_, location)) = listed
+                .iter()
+                .find(|(it, _)| canonical_name(it) == canonical)
+            {
+                retained.push((name.clone(), format!("still listed in {location}")));
+            } else if installed.contains(canonical.as_str()) && !uninstall.contains(&canonical) {
+                let reason = others
+                    .iter()
+                    .find(|it| **it != canonical && graph.resolved(&[it]).contains(&canonical))
+                    .map_or_else(
+                        || "required by the environment".to_string(),
+                        |it| format!("required by {it}"),
+                    );
+                retained.push((name.clone(), reason));
+            }
+        }
+
+        UninstallPlan {
+            uninstall: uninstall.into_iter().collect(),
+            retained,
+        }
     }
 }
 
@@ -61,7 +332,7 @@ mod tests {
         copy_dir, initialize_venv, CopyDirOptions, Dependency, Package, TerminalOptions, Verbosity,
     };
     use huak_dev::dev_resources_dir;
-    use std::str::FromStr;
+    use std::{collections::HashMap, str::FromStr};
     use tempfile::tempdir;
 
     #[test]
@@ -86,7 +357,9 @@ mod tests {
             ..Default::default()
         };
         let options = RemoveOptions {
-            install_options: InstallOptions { values: None },
+            purge_group: false,
+            keep_manifest: false,
+            install_options: InstallOptions::default(),
         };
         let ws = config.workspace();
         initialize_venv(ws.root().join(".venv"), &ws.environment()).unwrap();
@@ -138,7 +411,9 @@ mod tests {
             ..Default::default()
         };
         let options = RemoveOptions {
-            install_options: InstallOptions { values: None },
+            purge_group: false,
+            keep_manifest: false,
+            install_options: InstallOptions::default(),
         };
         let ws = config.workspace();
         initialize_venv(ws.root().join(".venv"), &ws.environment()).unwrap();
@@ -168,4 +443,91 @@ mod tests {
         assert!(!venv_contains_package);
         assert!(!toml_contains_package);
     }
+
+    #[test]
+    fn test_restore_manifest_on_error() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("pyproject.toml");
+        std::fs::write(
+            &path,
+            "[project]\nname = \"test\"\ndependencies = [\"click\"]\n",
+        )
+        .unwrap();
+        let config = Config {
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut options = RemoveOptions {
+            purge_group: false,
+            keep_manifest: false,
+            install_options: InstallOptions::default(),
+        };
+        let mut manifest = LocalManifest::new(&path).unwrap();
+        let original = manifest.manifest_data().to_string();
+        manifest
+            .manifest_data_mut()
+            .remove_project_dependency("click");
+        config.write_manifest(&manifest).unwrap();
+
+        // Simulate the uninstall failing after the manifest was edited.
+        let failed = || Err(Error::PythonNotFound);
+
+        options.keep_manifest = true;
+        assert!(
+            restore_manifest_on_error(failed(), &manifest, &original, &options, &config).is_err()
+        );
+        assert_ne!(std::fs::read_to_string(&path).unwrap(), original);
+
+        options.keep_manifest = false;
+        assert!(
+            restore_manifest_on_error(failed(), &manifest, &original, &options, &config).is_err()
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_uninstall_plan() {
+        let graph = DependencyGraph::new(HashMap::from([
+            (
+                "sphinx".to_string(),
+                vec!["jinja2".to_string(), "babel".to_string()],
+            ),
+            ("furo".to_string(), vec!["sphinx".to_string()]),
+            ("jinja2".to_string(), vec!["markupsafe".to_string()]),
+            ("markupsafe".to_string(), vec![]),
+            ("babel".to_string(), vec!["setuptools".to_string()]),
+            ("setuptools".to_string(), vec![]),
+            ("flask".to_string(), vec!["jinja2".to_string()]),
+            ("requests".to_string(), vec![]),
+            ("ipython".to_string(), vec!["babel".to_string()]),
+        ]));
+        let removed = ["furo", "Sphinx", "babel", "requests", "flask"].map(ToString::to_string);
+        let listed = [
+            ("requests", "the project's dependencies"),
+            ("flask", "the web group"),
+        ]
+        .map(|(name, location)| (name.to_string(), location.to_string()));
+
+        assert_eq!(
+            UninstallPlan::new(&removed, &listed, &graph),
+            UninstallPlan {
+                // jinja2 is required by flask, babel by the unlisted ipython.
+                uninstall: vec!["furo".to_string(), "sphinx".to_string()],
+                retained: vec![
+                    ("babel".to_string(), "required by ipython".to_string()),
+                    (
+                        "requests".to_string(),
+                        "still listed in the project's dependencies".to_string()
+                    ),
+                    (
+                        "flask".to_string(),
+                        "still listed in the web group".to_string()
+                    ),
+                ],
+            }
+        );
+    }
 }
@@ -0,0 +1,419 @@
+use super::{scaffold::is_python_identifier, FileFilter};
+use crate::{
+    importable_package_name, Config, Error, HuakResult, InstallOptions, PythonEnvironment,
+};
+use huak_pyproject_toml::canonical_name;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{path::Path, process::Command};
+use termcolor::Color;
+use toml_edit::{Item, Table};
+
+lazy_static! {
+    static ref PROJECT_NAME_REGEX: Regex =
+        Regex::new("(?i)^([a-z0-9]|[a-z0-9][a-z0-9._-]*[a-z0-9])$").expect("project name regex");
+}
+
+/// The `[project]` tables with entry points referencing the project's package.
+const SCRIPT_TABLES: [&str; 2] = ["scripts", "gui-scripts"];
+
+pub struct RenameOptions {
+    /// Replace a stale install of the project in its virtual environment.
+    pub reinstall: bool,
+    pub install_options: InstallOptions,
+}
+
+/// Rename the project to `name`.
+///
+/// The `[project]` name, the package directory, imports in the project's Python files, and
+/// `[project.scripts]` entry points are all renamed. Every change is planned before any is
+/// made, so nothing is modified if one would conflict.
+pub fn rename_project(name: &str, config: &Config, options: &RenameOptions) -> HuakResult<()> {
+    if !PROJECT_NAME_REGEX.is_match(name) {
+        return Err(Error::InvalidProjectName(format!(
+            "{name} (names can only have letters, numbers, '.', '-', and '_', and must start \
+            and end with a letter or number)"
+        )));
+    }
+
+    let new_importable = importable_package_name(name)?;
+    if !is_python_identifier(&new_importable) {
+        return Err(Error::InvalidProjectName(format!(
+            "{name} ({new_importable} isn't an importable Python identifier)"
+        )));
+    }
+
+    let workspace = config.workspace();
+    let mut manifest = workspace.current_local_manifest()?;
+    let root = manifest
+        .path()
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or(Error::InternalError(
+            "failed to establish parent directory".to_string(),
+        ))?;
+    let Some(old_name) = manifest.manifest_data().project_name() else {
+        return Err(Error::InvalidMetadata(
+            "the project doesn't have a name".to_string(),
+        ));
+    };
+
+    if old_name == name {
+        return Err(Error::RenameConflict(format!(
+            "the project is already named {name}"
+        )));
+    }
+
+    let old_importable = importable_package_name(&old_name)?;
+    let python_env = workspace
+        .current_python_environment()
+        .ok()
+        .filter(|it| !it.is_system());
+
+    for it in name_collisions(name, &new_importable, python_env.as_ref()) {
+        config.terminal().print_warning(it)?;
+    }
+
+    // Plan every change so nothing is modified if one conflicts.
+    manifest.manifest_data_mut().set_project_name(name);
+    if let Some(table) = manifest.manifest_data_mut().project_table_mut() {
+        for it in SCRIPT_TABLES {
+            if let Some(scripts) = table.get_mut(it).and_then(Item::as_table_mut) {
+                rename_scripts(scripts, &old_name, name, &old_importable, &new_importable)?;
+            }
+        }
+    }
+
+    let mut package_move = None;
+    let mut writes = Vec::new();
+
+    if old_importable != new_importable {
+        if let Some(from) = [root.join("src"), root.clone()]
+            .into_iter()
+            .map(|it| it.join(&old_importable))
+            .find(|it| it.is_dir())
+        {
+            let to = from.with_file_name(&new_importable);
+            if to.exists() {
+                return Err(Error::DirectoryExists { path: to });
+            }
+            package_move = Some((from, to));
+        }
+
+        let pattern = Regex::new(&format!(r"(^|[^\w.]){}\b", regex::escape(&old_importable)))?;
+        for file in FileFilter::default().files(&root)? {
+            let path = root.join(file);
+            let contents = std::fs::read_to_string(&path)?;
+            let renamed = pattern.replace_all(&contents, format!("${{1}}{new_importable}"));
+
+            if renamed != contents {
+                writes.push((path, renamed.to_string()));
+            }
+        }
+    }
+
+    // Files are written before the package directory is moved so they're found where they are.
+    for (path, contents) in &writes {
+        config.write_file(path, contents)?;
+    }
+
+    if let Some((from, to)) = package_move.as_ref() {
+        config.move_path(from, to)?;
+    }
+
+    config.write_manifest(&manifest)?;
+
+    if !config.dry_run {
+        config.terminal().print_custom(
+            "Renamed",
+            format!("{old_name} -> {name}"),
+            Color::Green,
+            true,
+        )?;
+    }
+
+    if let Some(python_env) = python_env.as_ref() {
+        if is_installed(python_env, &old_name) {
+            replace_install(python_env, &old_name, &root, config, options)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Rename the entry points in a scripts table that reference the old name or package.
+fn rename_scripts(
+    scripts: &mut Table,
+    old_name: &str,
+    new_name: &str,
+    old_importable: &str,
+    new_importable: &str,
+) -> HuakResult<()> {
+    if scripts.contains_key(old_name) && scripts.contains_key(new_name) {
+        return Err(Error::RenameConflict(format!(
+            "a script named {new_name} already exists"
+        )));
+    }
+
+    let entries = scripts
+        .iter()
+        .map(|(key, it)| (key.to_string(), it.clone()))
+        .collect::<Vec<_>>();
+    scripts.clear();
+
+    for (key, mut item) in entries {
+        let key = if key == old_name {
+            new_name.to_string()
+        } else {
+            key
+        };
+
+        if let Some(value) = item.as_str() {
+            let is_package = value
+                .strip_prefix(old_importable)
+                .is_some_and(|it| it.is_empty() || it.starts_with(['.', ':']));
+
+            if is_package {
+                item = toml_edit::value(format!(
+                    "{new_importable}{}",
+                    &value[old_importable.len()..]
+                ));
+            }
+        }
+
+        scripts.insert(&key, item);
+    }
+
+    Ok(())
+}
+
+/// Get warnings for a name that's also a standard library module or a project on the
+/// package index.
+fn name_collisions(
+    name: &str,
+    importable_name: &str,
+    python_env: Option<&PythonEnvironment>,
+) -> Vec<String> {
+    let Some(python_env) = python_env else {
+        return Vec::new();
+    };
+    let mut warnings = Vec::new();
+
+    let is_stdlib = Command::new(python_env.python_path())
+        .args([
+            "-c",
+            "import sys; \
+             names = getattr(sys, 'stdlib_module_names', sys.builtin_module_names); \
+             print(sys.argv[1] in names)",
+            importable_name,
+        ])
+        .output()
+        .is_ok_and(|it| String::from_utf8_lossy(&it.stdout).trim() == "True");
+    if is_stdlib {
+        warnings.push(format!(
+            "{importable_name} is a standard library module and importing the project may \
+            import it instead"
+        ));
+    }
+
+    let on_index = Command::new(python_env.python_path())
+        .args(["-m", "pip", "index", "versions", name])
+        .args([
+            "--retries",
+            "0",
+            "--timeout",
+            "5",
+            "--disable-pip-version-check",
+        ])
+        .output()
+        .is_ok_and(|it| it.status.success());
+    if on_index {
+        warnings.push(format!(
+            "a project named {name} already exists on the package index"
+        ));
+    }
+
+    warnings
+}
+
+/// Check if a distribution named `name` is installed to the `PythonEnvironment`.
+fn is_installed(python_env: &PythonEnvironment, name: &str) -> bool {
+    let Ok(entries) = std::fs::read_dir(python_env.site_packages_dir_path()) else {
+        return false;
+    };
+    let name = canonical_name(name);
+
+    entries.filter_map(Result::ok).any(|entry| {
+        entry
+            .file_name()
+            .to_str()
+            .and_then(|it| it.strip_suffix(".dist-info"))
+            .and_then(|it| it.rsplit_once('-'))
+            .is_some_and(|(it, _)| canonical_name(it) == name)
+    })
+}
+
+/// Replace the install of the project under its old name, or warn that it's stale.
+fn replace_install(
+    python_env: &PythonEnvironment,
+    old_name: &str,
+    root: &Path,
+    config: &Config,
+    options: &RenameOptions,
+) -> HuakResult<()> {
+    if !options.reinstall {
+        return config.terminal().print_warning(format!(
+            "{} still has {old_name} installed and its editable install is stale (pass \
+            --reinstall to reinstall the project, or run `huak run pip uninstall -y {old_name}` \
+            and `huak run pip install -e .`)",
+            python_env.root().display()
+        ));
+    }
+
+    python_env.uninstall_packages(&[old_name], &options.install_options, config)?;
+    python_env.install_editable(root, &options.install_options, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TerminalOptions, Verbosity};
+    use tempfile::tempdir;
+
+    fn test_config(root: &Path, dry_run: bool) -> Config {
+        Config {
+            workspace_root: root.to_path_buf(),
+            cwd: root.to_path_buf(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            dry_run,
+            ..Default::default()
+        }
+    }
+
+    fn mock_project(root: &Path) {
+        for (path, contents) in [
+            (
+                "pyproject.toml",
+                r#"[project]
+name = "old-name"
+version = "0.0.1"
+
+[project.scripts]
+old-name = "old_name.cli:main"
+other = "tools:main"
+"#,
+            ),
+            ("src/old_name/__init__.py", "__version__ = \"0.0.1\"\n"),
+            (
+                "src/old_name/__main__.py",
+                "from old_name.cli import main\n\nmain()\n",
+            ),
+            (
+                "src/old_name/cli.py",
+                "import bold_name\nimport old_name.api\n",
+            ),
+            (
+                "tests/test_version.py",
+                "from old_name import __version__\n",
+            ),
+        ] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+    }
+
+    fn options() -> RenameOptions {
+        RenameOptions {
+            reinstall: false,
+            install_options: InstallOptions::default(),
+        }
+    }
+
+    #[test]
+    fn test_rename_project() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        mock_project(root);
+
+        rename_project("new-name", &test_config(root, false), &options()).unwrap();
+
+        let read = |it: &str| std::fs::read_to_string(root.join(it)).unwrap();
+
+        assert!(!root.join("src").join("old_name").exists());
+        assert_eq!(
+            read("pyproject.toml"),
+            r#"[project]
+name = "new-name"
+version = "0.0.1"
+
+[project.scripts]
+new-name = "new_name.cli:main"
+other = "tools:main"
+"#
+        );
+        assert_eq!(
+            read("src/new_name/__main__.py"),
+            "from new_name.cli import main\n\nmain()\n"
+        );
+        assert_eq!(
+            read("src/new_name/cli.py"),
+            "import bold_name\nimport new_name.api\n"
+        );
+        assert_eq!(
+            read("tests/test_version.py"),
+            "from new_name import __version__\n"
+        );
+    }
+
+    #[test]
+    fn test_rename_project_conflicts() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        mock_project(root);
+        std::fs::create_dir_all(root.join("src").join("new_name")).unwrap();
+        let before = std::fs::read_to_string(root.join("pyproject.toml")).unwrap();
+        let config = test_config(root, false);
+
+        assert!(matches!(
+            rename_project("new-name", &config, &options()),
+            Err(Error::DirectoryExists { .. })
+        ));
+        assert!(matches!(
+            rename_project("old-name", &config, &options()),
+            Err(Error::RenameConflict(_))
+        ));
+        assert!(matches!(
+            rename_project("-new", &config, &options()),
+            Err(Error::InvalidProjectName(_))
+        ));
+        assert!(matches!(
+            rename_project("class", &config, &options()),
+            Err(Error::InvalidProjectName(_))
+        ));
+        assert_eq!(
+            std::fs::read_to_string(root.join("pyproject.toml")).unwrap(),
+            before
+        );
+        assert!(root.join("src").join("old_name").join("cli.py").exists());
+    }
+
+    #[test]
+    fn test_rename_project_dry_run() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        mock_project(root);
+
+        rename_project("new-name", &test_config(root, true), &options()).unwrap();
+
+        assert!(root.join("src").join("old_name").exists());
+        assert!(!root.join("src").join("new_name").exists());
+        assert_eq!(
+            std::fs::read_to_string(root.join("tests").join("test_version.py")).unwrap(),
+            "from old_name import __version__\n"
+        );
+    }
+}
@@ -0,0 +1,438 @@
+use super::{
+    policy_violations, project_dependencies, read_lint_baseline, ProjectDependencies, TestCounts,
+};
+use crate::{Config, Error, HuakResult};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Write as _},
+    io::Write,
+    path::PathBuf,
+    str::FromStr,
+};
+use termcolor::Color;
+
+/// The coverage report read when one isn't passed, relative to the workspace root.
+const COVERAGE_FILE_NAME: &str = "coverage.xml";
+
+lazy_static! {
+    /// The overall line rate of a Cobertura XML report, like the ones written by `coverage xml`.
+    static ref LINE_RATE: Regex =
+        Regex::new(r#"<coverage\s[^>]*\bline-rate="([0-9.]+)""#).unwrap();
+}
+
+pub struct ReportOptions {
+    /// The sections to include. Sections are always written in the same order.
+    pub sections: Vec<ReportSection>,
+    pub format: ReportFormat,
+    /// Write the report to the path instead of stdout.
+    pub output: Option<PathBuf>,
+    /// An XML report of the last test run, like the one written by `pytest --junitxml`.
+    pub junit: Option<PathBuf>,
+    /// A Cobertura XML coverage report. Defaults to the workspace's coverage.xml if it has one.
+    pub coverage: Option<PathBuf>,
+    /// A lint baseline written by `huak lint --write-baseline`.
+    pub lint_baseline: Option<PathBuf>,
+}
+
+/// A section of the health report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReportSection {
+    /// The number of required and optional dependencies.
+    Deps,
+    /// The project's `requires-python` and its environment's Python version.
+    Python,
+    /// The number of dependency policy violations (see `huak check --policies`).
+    Policy,
+    /// The number of violations in the lint baseline.
+    Lint,
+    /// The outcomes of the last test run.
+    Tests,
+    /// The line coverage of the last test run.
+    Coverage,
+}
+
+impl ReportSection {
+    pub const ALL: [ReportSection; 6] = [
+        ReportSection::Deps,
+        ReportSection::Python,
+        ReportSection::Policy,
+        ReportSection::Lint,
+        ReportSection::Tests,
+        ReportSection::Coverage,
+    ];
+}
+
+impl FromStr for ReportSection {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "deps" => Ok(ReportSection::Deps),
+            "python" => Ok(ReportSection::Python),
+            "policy" => Ok(ReportSection::Policy),
+            "lint" => Ok(ReportSection::Lint),
+            "tests" => Ok(ReportSection::Tests),
+            "coverage" => Ok(ReportSection::Coverage),
+            _ => Err(Error::InvalidReportSection(format!(
+                "{s} (expected deps, python, policy, lint, tests, or coverage)"
+            ))),
+        }
+    }
+}
+
+/// How the health report is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// A markdown table for committing or posting as a comment.
+    #[default]
+    Markdown,
+    /// A JSON document for scripts.
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markdown" => Ok(ReportFormat::Markdown),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(Error::InvalidOutputFormat(format!(
+                "{s} (expected markdown or json)"
+            ))),
+        }
+    }
+}
+
+impl Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportFormat::Markdown => write!(f, "markdown"),
+            ReportFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// The project's health. Sections that weren't requested are `None`, and requested sections
+/// without anything to report on (like tests without a report) are `Some(None)`, so they're
+/// written as `null`.
+#[allow(clippy::option_option)]
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct ProjectReport {
+    name: String,
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dependencies: Option<DependencyCounts>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    python: Option<PythonReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    policy_violations: Option<Option<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lint_baseline_violations: Option<Option<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tests: Option<Option<TestCounts>>,
+    /// The percentage of lines covered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    coverage: Option<Option<f64>>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+struct DependencyCounts {
+    required: usize,
+    /// The number of dependencies in each optional dependency group.
+    optional: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+struct PythonReport {
+    requires_python: Option<String>,
+    /// The Python version of the project's environment.
+    environment: Option<String>,
+}
+
+/// Write a health report of the project from its manifest, environment, and the reports left
+/// behind by its last test run and lint baseline.
+///
+/// The report only changes when the project does, so it can be committed and diffed.
+pub fn generate_report(config: &Config, options: &ReportOptions) -> HuakResult<()> {
+    let report = project_report(config, options)?;
+    let contents = match options.format {
+        ReportFormat::Markdown => report_markdown(&report),
+        ReportFormat::Json => format!("{}\n", serde_json::to_string_pretty(&report)?),
+    };
+
+    let Some(path) = options.output.as_ref() else {
+        let mut stdout = std::io::stdout();
+        write!(stdout, "{contents}")?;
+        return Ok(());
+    };
+
+    let path = config.cwd.join(path);
+    config.write_file(&path, &contents)?;
+
+    if config.dry_run {
+        return Ok(());
+    }
+
+    config
+        .terminal()
+        .print_custom("Wrote", path.display(), Color::Green, true)
+}
+
+fn project_report(config: &Config, options: &ReportOptions) -> HuakResult<ProjectReport> {
+    let workspace = config.workspace();
+    let manifest = workspace.current_local_manifest()?;
+    let python_env = workspace.current_python_environment().ok();
+    let deps = project_dependencies(&manifest)?;
+    let mut sections = if options.sections.is_empty() {
+        ReportSection::ALL.to_vec()
+    } else {
+        options.sections.clone()
+    };
+    sections.sort();
+    sections.dedup();
+
+    let mut report = ProjectReport {
+        name: manifest.manifest_data().project_name().unwrap_or_default(),
+        version: manifest.manifest_data().project_version(),
+        ..Default::default()
+    };
+
+    for section in sections {
+        match section {
+            ReportSection::Deps => report.dependencies = Some(dependency_counts(&deps)),
+            ReportSection::Python => {
+                report.python = Some(PythonReport {
+                    requires_python: deps.requires_python.clone(),
+                    environment: python_env
+                        .as_ref()
+                        .map(|it| it.python_version().to_string()),
+                });
+            }
+            ReportSection::Policy => {
+                report.policy_violations = Some(match python_env.as_ref() {
+                    Some(it) => Some(policy_violations(&manifest, it, config)?.len()),
+                    None => None,
+                });
+            }
+            ReportSection::Lint => {
+                report.lint_baseline_violations = Some(match options.lint_baseline.as_ref() {
+                    Some(it) => Some(
+                        read_lint_baseline(&config.cwd.join(it), config)?
+                            .violations
+                            .len(),
+                    ),
+                    None => None,
+                });
+            }
+            ReportSection::Tests => {
+                report.tests = Some(
+                    options
+                        .junit
+                        .as_ref()
+                        .and_then(|it| std::fs::read_to_string(config.cwd.join(it)).ok())
+                        .and_then(|it| TestCounts::from_junit_xml(&it)),
+                );
+            }
+            ReportSection::Coverage => {
+                let path = options.coverage.as_ref().map_or_else(
+                    || workspace.root().join(COVERAGE_FILE_NAME),
+                    |it| config.cwd.join(it),
+                );
+                report.coverage = Some(
+                    std::fs::read_to_string(path)
+                        .ok()
+                        .and_then(|it| coverage_percentage(&it)),
+                );
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn dependency_counts(deps: &ProjectDependencies) -> DependencyCounts {
+    DependencyCounts {
+        required: deps.required.len(),
+        optional: deps
+            .optional
+            .iter()
+            .map(|(group, it)| (group.clone(), it.len()))
+            .collect(),
+    }
+}
+
+/// Get the percentage of lines covered from a Cobertura XML report, rounded to a tenth.
+fn coverage_percentage(xml: &str) -> Option<f64> {
+    let rate = LINE_RATE
+        .captures(xml)?
+        .get(1)?
+        .as_str()
+        .parse::<f64>()
+        .ok()?;
+
+    Some((rate * 1000.0).round() / 10.0)
+}
+
+fn report_markdown(report: &ProjectReport) -> String {
+    let mut title = report.name.clone();
+    if let Some(it) = report.version.as_ref() {
+        let _ = write!(title, " {it}");
+    }
+    let mut rows = Vec::new();
+
+    if let Some(it) = report.dependencies.as_ref() {
+        let optional = it.optional.values().sum::<usize>();
+        let mut row = format!("{} required, {optional} optional", it.required);
+        if !it.optional.is_empty() {
+            let groups = it
+                .optional
+                .iter()
+                .map(|(group, count)| format!("{group}: {count}"))
+                .collect::<Vec<_>>();
+            let _ = write!(row, " ({})", groups.join(", "));
+        }
+        rows.push(("Dependencies", row));
+    }
+
+    if let Some(it) = report.python.as_ref() {
+        let requires = it.requires_python.as_deref().unwrap_or("any version");
+        let environment = it.environment.as_deref().unwrap_or("none");
+        rows.push((
+            "Python",
+            format!("requires {requires}, environment {environment}"),
+        ));
+    }
+
+    if let Some(it) = report.policy_violations {
+        rows.push((
+            "Policy violations",
+            it.map_or("no environment".to_string(), |it| it.to_string()),
+        ));
+    }
+
+    if let Some(it) = report.lint_baseline_violations {
+        rows.push((
+            "Lint baseline",
+            it.map_or("no baseline".to_string(), |it| format!("{it} violations")),
+        ));
+    }
+
+    if let Some(it) = report.tests.as_ref() {
+        rows.push((
+            "Tests",
+            it.as_ref()
+                .map_or("no report".to_string(), ToString::to_string),
+        ));
+    }
+
+    if let Some(it) = report.coverage {
+        rows.push((
+            "Coverage",
+            it.map_or("no report".to_string(), |it| format!("{it:.1}%")),
+        ));
+    }
+
+    let mut contents = format!("## Project health: {title}\n\n| Check | Status |\n| --- | --- |\n");
+    for (check, status) in rows {
+        let _ = writeln!(contents, "| {check} | {status} |");
+    }
+
+    contents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TerminalOptions, Verbosity};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_generate_report() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            r#"[project]
+name = "mock-project"
+version = "0.1.0"
+requires-python = ">=3.8"
+dependencies = ["click"]
+
+[project.optional-dependencies]
+docs = ["mkdocs"]
+dev = ["pytest", "ruff"]
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("report.xml"),
+            r#"<testsuites><testsuite name="pytest" errors="0" failures="1" skipped="1" tests="5"></testsuite></testsuites>"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join(COVERAGE_FILE_NAME),
+            r#"<?xml version="1.0" ?><coverage version="7.4.0" line-rate="0.8526" branch-rate="0">"#,
+        )
+        .unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root.clone(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut options = ReportOptions {
+            sections: Vec::new(),
+            format: ReportFormat::Markdown,
+            output: Some(PathBuf::from("health.md")),
+            junit: Some(PathBuf::from("report.xml")),
+            coverage: None,
+            lint_baseline: None,
+        };
+
+        generate_report(&config, &options).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(root.join("health.md")).unwrap(),
+            "## Project health: mock-project 0.1.0
+
+| Check | Status |
+| --- | --- |
+| Dependencies | 1 required, 3 optional (dev: 2, docs: 1) |
+| Python | requires >=3.8, environment none |
+| Policy violations | no environment |
+| Lint baseline | no baseline |
+| Tests | 3 passed, 1 failed, 1 skipped |
+| Coverage | 85.3% |
+"
+        );
+
+        options.sections = vec![ReportSection::Tests, ReportSection::Deps];
+        options.format = ReportFormat::Json;
+        options.junit = None;
+        generate_report(&config, &options).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(root.join("health.md")).unwrap(),
+            r#"{
+  "name": "mock-project",
+  "version": "0.1.0",
+  "dependencies": {
+    "required": 1,
+    "optional": {
+      "dev": 2,
+      "docs": 1
+    }
+  },
+  "tests": null
+}
+"#
+        );
+    }
+}
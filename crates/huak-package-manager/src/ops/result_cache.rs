@@ -0,0 +1,229 @@
+use super::cache::cache_dir;
+use crate::{Config, HuakResult, PythonEnvironment};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use termcolor::Color;
+use toml_edit::Document;
+
+/// The files `ruff` reads its configuration from, relative to the workspace root.
+const RUFF_CONFIG_FILES: [&str; 2] = ["ruff.toml", ".ruff.toml"];
+
+/// The content hashes of the files a tool last passed (left formatted or found no violations
+/// in), so files that haven't changed since can be skipped.
+///
+/// The cache is kept in Huak's cache directory for each workspace and tool. It's cleared when
+/// its key (the tool's version, configuration, and arguments) changes.
+pub(super) struct ResultCache {
+    /// Where the cache is saved, or `None` if it's disabled.
+    path: Option<PathBuf>,
+    key: String,
+    files: BTreeMap<PathBuf, String>,
+    hits: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CachedResults {
+    key: String,
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl ResultCache {
+    /// Open the `tool`'s cache for the workspace at `root`. Results cached with a different
+    /// `key` are discarded. Caching is disabled without a Huak home directory.
+    pub(super) fn open(tool: &str, key: String, root: &Path, config: &Config) -> ResultCache {
+        let path = cache_dir(config).ok().map(|it| {
+            it.join("results").join(format!(
+                "{}-{tool}.json",
+                &hash(root.display().to_string().as_bytes())[..16]
+            ))
+        });
+        let files = path
+            .as_ref()
+            .and_then(|it| std::fs::read_to_string(it).ok())
+            .and_then(|it| serde_json::from_str::<CachedResults>(&it).ok())
+            .filter(|it| it.key == key)
+            .map(|it| it.files)
+            .unwrap_or_default();
+
+        ResultCache {
+            path,
+            key,
+            files,
+            hits: 0,
+        }
+    }
+
+    /// A `ResultCache` that never skips or saves anything.
+    pub(super) fn disabled() -> ResultCache {
+        ResultCache {
+            path: None,
+            key: String::new(),
+            files: BTreeMap::new(),
+            hits: 0,
+        }
+    }
+
+    /// Get the `files` (relative to `root`) that have changed since the tool passed them.
+    /// The rest are counted as cache hits.
+    pub(super) fn changed(&mut self, root: &Path, files: &[PathBuf]) -> Vec<PathBuf> {
+        if self.path.is_none() {
+            return files.to_vec();
+        }
+
+        let changed = files
+            .iter()
+            .filter(|it| {
+                let hash = file_hash(&root.join(it));
+                hash.is_none() || self.files.get(*it) != hash.as_ref()
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        self.hits = files.len() - changed.len();
+
+        changed
+    }
+
+    pub(super) fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Record the current contents of the `files` (relative to `root`) as passed by the tool and
+    /// save the cache.
+    pub(super) fn save(
+        &mut self,
+        root: &Path,
+        files: &[PathBuf],
+        config: &Config,
+    ) -> HuakResult<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        if config.dry_run || config.plan.is_some() {
+            return Ok(());
+        }
+
+        for it in files {
+            if let Some(hash) = file_hash(&root.join(it)) {
+                self.files.insert(it.clone(), hash);
+            }
+        }
+        self.files.retain(|it, _| root.join(it).exists());
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let results = CachedResults {
+            key: self.key.clone(),
+            files: self.files.clone(),
+        };
+        std::fs::write(path, serde_json::to_string(&results)?)?;
+
+        Ok(())
+    }
+
+    /// Print how many files were skipped because they're unchanged.
+    pub(super) fn print_summary(
+        &self,
+        total: usize,
+        operation: &str,
+        config: &Config,
+    ) -> HuakResult<()> {
+        if self.hits == 0 {
+            return Ok(());
+        }
+
+        config.operation_terminal(operation).print_custom(
+            "Cached",
+            format!(
+                "skipped {} of {total} files unchanged since they last passed (pass --no-cache \
+                to check them)",
+                self.hits
+            ),
+            Color::Green,
+            true,
+        )
+    }
+}
+
+/// Get the cache key for `ruff` run with the `args` in the workspace at `root`: its installed
+/// version, its configuration, and the arguments.
+pub(super) fn ruff_cache_key(
+    python_env: &PythonEnvironment,
+    root: &Path,
+    args: &[&str],
+) -> HuakResult<String> {
+    let version = python_env
+        .installed_versions()?
+        .get("ruff")
+        .map(ToString::to_string)
+        .unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    hasher.update(format!("ruff {version}"));
+    for it in RUFF_CONFIG_FILES {
+        hasher.update(b"\0");
+        hasher.update(std::fs::read(root.join(it)).unwrap_or_default());
+    }
+    let ruff_table = std::fs::read_to_string(root.join("pyproject.toml"))
+        .ok()
+        .and_then(|it| it.parse::<Document>().ok())
+        .and_then(|it| it.get("tool")?.get("ruff").map(ToString::to_string))
+        .unwrap_or_default();
+    hasher.update(b"\0");
+    hasher.update(ruff_table);
+    for it in args {
+        hasher.update(b"\0");
+        hasher.update(it);
+    }
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn file_hash(path: &Path) -> Option<String> {
+    std::fs::read(path).ok().map(|it| hash(&it))
+}
+
+fn hash(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_result_cache() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("project");
+        std::fs::create_dir(&root).unwrap();
+        let config = Config {
+            home: Some(dir.path().join("home")),
+            ..Default::default()
+        };
+        let files = [PathBuf::from("a.py"), PathBuf::from("b.py")];
+        for it in &files {
+            std::fs::write(root.join(it), "x = 1\n").unwrap();
+        }
+
+        let mut cache = ResultCache::open("lint", "key".to_string(), &root, &config);
+        assert_eq!(cache.changed(&root, &files), files);
+        cache.save(&root, &files, &config).unwrap();
+
+        std::fs::write(root.join("b.py"), "x = 2\n").unwrap();
+        let mut cache = ResultCache::open("lint", "key".to_string(), &root, &config);
+        assert_eq!(cache.changed(&root, &files), [PathBuf::from("b.py")]);
+        assert_eq!(cache.hits, 1);
+
+        // A new key, like from a new tool version, invalidates the cache.
+        let mut cache = ResultCache::open("lint", "new key".to_string(), &root, &config);
+        assert_eq!(cache.changed(&root, &files), files);
+
+        let mut cache = ResultCache::disabled();
+        assert_eq!(cache.changed(&root, &files), files);
+    }
+}
@@ -1,86 +1,378 @@
 use super::add_venv_to_command;
-use crate::{shell_name, sys::Terminal, Config, Error, HuakResult};
-use huak_pyproject_toml::{sanitize_str, value_to_sanitized_string};
-use std::{collections::HashMap, env::consts::OS, ffi::OsStr, ops::Deref, process::Command};
+use crate::{
+    env_path_values, shell_name, similar_names, venv_executables_dir_path,
+    workspace::find_venv_root, Config, Error, HuakResult, PythonEnvironment,
+};
+use huak_pyproject_toml::{sanitize_str, value_to_sanitized_string, PyProjectToml};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    env::consts::OS,
+    ffi::OsStr,
+    io::Write,
+    ops::Deref,
+    path::{Path, PathBuf},
+    process::Command,
+};
 use termcolor::Color;
 use toml_edit::{Array, ArrayOfTables, Formatted, InlineTable, Item, Table, Value};
 
+/// Shell builtins and keywords `huak run` leaves to the shell instead of resolving.
+#[cfg(unix)]
+const SHELL_BUILTINS: [&str; 35] = [
+    "alias", "bg", "break", "builtin", "case", "cd", "command", "continue", "declare", "echo",
+    "eval", "exec", "exit", "export", "false", "fg", "for", "function", "if", "jobs", "local",
+    "printf", "pwd", "read", "return", "set", "shift", "source", "test", "time", "trap", "true",
+    "type", "unset", "while",
+];
+#[cfg(windows)]
+const SHELL_BUILTINS: [&str; 22] = [
+    "assoc", "call", "cd", "chdir", "cls", "copy", "del", "dir", "echo", "erase", "exit", "for",
+    "if", "md", "mkdir", "move", "rd", "ren", "rmdir", "set", "start", "type",
+];
+
+/// The extensions Windows runs programs with when `PATHEXT` isn't set.
+const DEFAULT_PATHEXT: &str = ".COM;.EXE;.BAT;.CMD";
+
+/// The task `huak run` runs when it isn't given a command.
+const DEFAULT_TASK: &str = "default";
+
+/// Run a task from `[tool.huak.task]` or a command in the project's environment. Without
+/// either the `default` task is run, or the tasks and executables are listed if there isn't one.
 pub fn run_command_str(content: &str, config: &Config) -> HuakResult<()> {
     let ws = config.workspace();
     let manifest = ws.current_local_manifest()?;
+    let task_table = task_table(manifest.manifest_data());
+    let trimmed = content.trim();
 
-    // Get any run commands listed in [tool.huak.run]
-    let task_table = manifest
-        .manifest_data()
-        .tool_table()
-        .and_then(|it| it.get("huak"))
-        .and_then(Item::as_table)
-        .and_then(|it| it.get("task"))
-        .and_then(Item::as_table);
+    // Without a program run the default task, like `make` runs its first target.
+    if trimmed.is_empty() {
+        return match task_table.filter(|it| it.contains_key(DEFAULT_TASK)) {
+            Some(table) => TaskRunner::from_table(table.to_owned()).run(DEFAULT_TASK, config),
+            None => list_run_targets(config, false),
+        };
+    }
 
-    let trimmed = content.trim();
+    // If the program is in the task table then run the command from the task table.
+    if let Some(table) = task_table {
+        let task = trimmed.split(' ').next().unwrap_or_default();
 
-    let trimmed = if trimmed.is_empty() {
-        None
-    } else {
-        Some(trimmed)
+        if table.contains_key(task) {
+            return TaskRunner::from_table(table.to_owned()).run(task, config);
+        }
+    }
+
+    // Otherwise attempt to run the contents using the shell.
+    run_str(trimmed, config)
+}
+
+/// Run `--`-separated steps (like `lint -- test -- build`) in order with `run_command_str`,
+/// stopping at the first that fails unless `keep_going` is set. The first failure is returned
+/// after the failed steps are reported.
+pub fn run_command_sequence(args: &[String], keep_going: bool, config: &Config) -> HuakResult<()> {
+    let steps = command_sequence(args);
+    let mut terminal = config.terminal();
+    let mut failed = Vec::new();
+    let mut first_error = None;
+
+    for (i, step) in steps.iter().enumerate() {
+        terminal.print_custom(
+            "Running",
+            format!("{step} ({}/{})", i + 1, steps.len()),
+            Color::Green,
+            true,
+        )?;
+
+        if let Err(e) = run_command_str(step, config) {
+            terminal.print_custom(
+                "Failed",
+                format!("step {} of {}: {step}", i + 1, steps.len()),
+                Color::Red,
+                true,
+            )?;
+            failed.push(step.as_str());
+            first_error.get_or_insert(e);
+
+            if !keep_going {
+                break;
+            }
+        }
+    }
+
+    match first_error {
+        Some(e) => {
+            if keep_going {
+                terminal.print_custom(
+                    "Failed",
+                    format!(
+                        "{} of {} steps: {}",
+                        failed.len(),
+                        steps.len(),
+                        failed.join(", ")
+                    ),
+                    Color::Red,
+                    true,
+                )?;
+            }
+            Err(e)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Split `huak run` arguments into the commands separated by `--`. Empty steps are dropped.
+fn command_sequence(args: &[String]) -> Vec<String> {
+    args.split(|it| it == "--")
+        .map(|it| it.join(" "))
+        .filter(|it| !it.trim().is_empty())
+        .collect()
+}
+
+/// What `huak run` can run: the tasks from `[tool.huak.task]` and the executables in the
+/// project's virtual environment, each sorted by name.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+struct RunTargets {
+    tasks: Vec<RunTask>,
+    executables: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct RunTask {
+    name: String,
+    /// The command the task runs, as it would be typed.
+    command: String,
+}
+
+/// Print the tasks and executables `huak run` can run, as a list or as JSON.
+pub fn list_run_targets(config: &Config, json: bool) -> HuakResult<()> {
+    let manifest = config.workspace().current_local_manifest()?;
+    let mut tasks = task_table(manifest.manifest_data())
+        .map(|table| {
+            table
+                .iter()
+                .map(|(name, item)| RunTask {
+                    name: name.to_string(),
+                    command: task_command(item),
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    tasks.sort_by(|a, b| a.name.cmp(&b.name));
+    let targets = RunTargets {
+        tasks,
+        executables: venv_executables(config),
     };
+    let mut stdout = std::io::stdout();
 
-    // If there is a task table and there's no program provided just print any available commands
-    // from the task table.
-    // If there is a task table and the program is found in the task table then attempt to run
-    // the command with Huak by building a command from the contents provided.
-    if let Some(table) = task_table {
-        if trimmed.map_or(true, str::is_empty) {
-            return print_task_table(&mut config.terminal(), table);
-        };
+    if json {
+        writeln!(stdout, "{}", serde_json::to_string_pretty(&targets)?)?;
+        return Ok(());
+    }
 
-        // Try to get the program from the content provided.
-        let maybe_task = trimmed.as_ref().and_then(|it| it.split(' ').next());
+    if targets.tasks.is_empty() && targets.executables.is_empty() {
+        return Err(Error::InvalidProgram(
+            "no command was given and there are no tasks or executables to run".to_string(),
+        ));
+    }
 
-        // If the program is in the task table then run the command from the task table.
-        if maybe_task.map_or(false, |name| {
-            task_table.map_or(false, |table| table.contains_key(name))
-        }) {
-            let table = task_table.expect("task table");
-            let task = maybe_task.expect("task name");
-            return TaskRunner::from_table(table.to_owned()).run(task, config);
+    let width = targets
+        .tasks
+        .iter()
+        .map(|it| it.name.len())
+        .max()
+        .unwrap_or(0);
+
+    if !targets.tasks.is_empty() {
+        writeln!(stdout, "Tasks:")?;
+        for it in &targets.tasks {
+            writeln!(stdout, "  {:<width$}  {}", it.name, it.command)?;
         }
     }
 
-    // If a program is found or the contents still contain something to parse/run
-    // attempt to run the contents using the shell.
-    if let Some(s) = trimmed.filter(|it| !it.is_empty()) {
-        run_str(s, config)
-    } else {
-        Err(Error::InvalidProgram(
-            "could not resolve program".to_string(),
-        ))
+    if !targets.executables.is_empty() {
+        if !targets.tasks.is_empty() {
+            writeln!(stdout)?;
+        }
+        writeln!(stdout, "Executables:")?;
+        for it in &targets.executables {
+            writeln!(stdout, "  {it}")?;
+        }
     }
+
+    config.terminal().print_custom(
+        "Hint",
+        format!(
+            "run one with `huak run <name>` or add a `{DEFAULT_TASK}` task to run with `huak run`"
+        ),
+        Color::Cyan,
+        true,
+    )
 }
 
-fn print_task_table(terminal: &mut Terminal, table: &Table) -> HuakResult<()> {
-    let commands = table
-        .get_values()
-        .into_iter()
-        .flat_map(|(k, _)| k)
-        .map(ToString::to_string)
+/// Get the `[tool.huak.task]` table.
+pub(super) fn task_table(data: &PyProjectToml) -> Option<&Table> {
+    data.huak_table()
+        .and_then(|it| it.get("task"))
+        .and_then(Item::as_table)
+}
+
+/// Get the names of the executables in the project's virtual environment, sorted and without
+/// activation scripts or extensions.
+pub(super) fn venv_executables(config: &Config) -> Vec<String> {
+    find_venv_root(&config.cwd, config.workspace().root())
+        .map(|it| executables_in(&venv_executables_dir_path(it)))
+        .unwrap_or_default()
+}
+
+/// Get the names of the executables in a virtual environment's executables directory `dir`,
+/// sorted and without activation scripts or extensions.
+fn executables_in(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut executables = entries
+        .filter_map(Result::ok)
+        .filter_map(|it| it.file_name().into_string().ok())
+        .filter(|it| !it.to_lowercase().starts_with("activate") && !it.contains("__"))
+        .map(|it| {
+            it.strip_suffix(".exe")
+                .map(ToString::to_string)
+                .unwrap_or(it)
+        })
         .collect::<Vec<_>>();
+    executables.sort();
+    executables.dedup();
 
-    terminal.print_custom("Tasks", "", Color::Cyan, true)?;
+    executables
+}
 
-    for (i, command) in commands.iter().enumerate() {
-        terminal.print_custom(
-            format!("{:>5})", i + 1),
-            format!("{command:<16}"),
-            Color::Green,
-            true,
+/// Get the command a task runs, as it would be typed. A chain of tasks is listed by name.
+fn task_command(item: &Item) -> String {
+    let words = |item: &Item| match item {
+        Item::Value(Value::Array(_)) => item_as_args(item).map(|it| it.join(" ")),
+        _ => item.as_str().map(ToString::to_string),
+    };
+    let field = |key| match item {
+        Item::Table(table) => table.get(key).cloned(),
+        Item::Value(Value::InlineTable(table)) => table.get(key).cloned().map(Item::Value),
+        _ => None,
+    };
+
+    if let Some(it) = words(item) {
+        return it;
+    }
+    if let Some(it) = field("cmd").as_ref().and_then(words) {
+        return it;
+    }
+    if let Some(chain) = field("chain").as_ref().and_then(Item::as_array) {
+        let tasks = chain.iter().filter_map(Value::as_str).collect::<Vec<_>>();
+        return format!("chain: {}", tasks.join(", "));
+    }
+
+    let program = field("program").and_then(|it| it.as_str().map(ToString::to_string));
+    let args = field("args").as_ref().and_then(words);
+
+    program
+        .into_iter()
+        .chain(args)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A script an installed distribution declares in its `entry_points.txt`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct EntryPointScript {
+    name: String,
+    /// The distribution and version declaring the script (like `alembic 1.13.1`).
+    distribution: String,
+    /// The object the script runs (like `alembic.config:main`).
+    object: String,
+}
+
+/// Print the console and GUI scripts the distributions installed to the workspace's Python
+/// environment declare, sorted by name.
+pub fn list_entry_point_scripts(config: &Config) -> HuakResult<()> {
+    let python_env = config.workspace().current_python_environment()?;
+    let scripts = entry_point_scripts(python_env.site_packages_dir_path())?;
+
+    if scripts.is_empty() {
+        return config
+            .terminal()
+            .print_warning("no installed distributions declare scripts");
+    }
+
+    let width = scripts.iter().map(|it| it.name.len()).max().unwrap_or(0);
+    let mut stdout = std::io::stdout();
+
+    for it in scripts {
+        writeln!(
+            stdout,
+            "{:<width$}  {} ({})",
+            it.name, it.distribution, it.object
         )?;
     }
 
     Ok(())
 }
 
+/// Read the scripts declared by the `.dist-info` metadata in a `site-packages` directory.
+fn entry_point_scripts(site_packages: &Path) -> HuakResult<Vec<EntryPointScript>> {
+    let entries = match std::fs::read_dir(site_packages) {
+        Ok(it) => it,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut scripts = Vec::new();
+
+    for entry in entries.filter_map(Result::ok) {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(distribution) = file_name.strip_suffix(".dist-info") else {
+            continue;
+        };
+        let Ok(contents) = std::fs::read_to_string(entry.path().join("entry_points.txt")) else {
+            continue;
+        };
+        let distribution = distribution.replacen('-', " ", 1);
+
+        scripts.extend(
+            parse_entry_point_scripts(&contents)
+                .into_iter()
+                .map(|(name, object)| EntryPointScript {
+                    name,
+                    distribution: distribution.clone(),
+                    object,
+                }),
+        );
+    }
+
+    scripts.sort();
+
+    Ok(scripts)
+}
+
+/// Parse the `[console_scripts]` and `[gui_scripts]` of an `entry_points.txt` into names and
+/// the objects they run.
+fn parse_entry_point_scripts(contents: &str) -> Vec<(String, String)> {
+    let mut in_scripts = false;
+
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|it| !it.is_empty() && !it.starts_with(['#', ';']))
+        .filter_map(|line| {
+            if let Some(section) = line.strip_prefix('[').and_then(|it| it.strip_suffix(']')) {
+                in_scripts = matches!(section.trim(), "console_scripts" | "gui_scripts");
+                return None;
+            }
+
+            let (name, object) = line.split_once('=').filter(|_| in_scripts)?;
+
+            Some((name.trim().to_string(), object.trim().to_string()))
+        })
+        .collect()
+}
+
 struct TaskRunner {
     table: Table,
 }
@@ -378,11 +670,24 @@ fn run_str(s: &str, config: &Config) -> HuakResult<()> {
         _ => "-c",
     };
 
-    add_venv_to_command(&mut cmd, &config.workspace().current_python_environment()?)?;
+    let python_env = config.workspace().current_python_environment()?;
+    add_venv_to_command(&mut cmd, &python_env)?;
+
+    // Run the program the command starts with from where it resolves to, so the shell can't
+    // pick a different one. Builtins and anything that isn't a plain program name are left to
+    // the shell.
+    let s = s.trim_start();
+    let (program, rest) = s.split_at(s.find(char::is_whitespace).unwrap_or(s.len()));
+    let s = if is_program_name(program) && !SHELL_BUILTINS.contains(&program) {
+        let path = resolve_venv_program(program, &python_env, config)?;
+        format!("{}{rest}", shell_quote(&path.to_string_lossy()))
+    } else {
+        s.to_string()
+    };
 
-    cmd.args([flag, s]).current_dir(&config.cwd);
+    cmd.args([flag, &s]).current_dir(&config.cwd);
 
-    config.terminal().run_command(&mut cmd)
+    config.operation_terminal("run").run_command(&mut cmd)
 }
 
 fn run_program<I, S>(
@@ -395,19 +700,176 @@ where
     I: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
 {
-    let mut cmd = Command::new(program);
+    let python_env = config.workspace().current_python_environment()?;
+    let mut cmd = Command::new(resolve_venv_program(program, &python_env, config)?);
 
-    add_venv_to_command(&mut cmd, &config.workspace().current_python_environment()?)?;
+    add_venv_to_command(&mut cmd, &python_env)?;
 
     if let Some(env) = env {
         cmd.envs(env);
     }
 
     config
-        .terminal()
+        .operation_terminal("run")
         .run_command(cmd.args(args).current_dir(&config.cwd))
 }
 
+/// Get the directories to search for programs in order: the `PythonEnvironment`'s executables
+/// directory, then `PATH`.
+fn program_search_dirs(python_env: &PythonEnvironment) -> Vec<PathBuf> {
+    let venv_dir = python_env.executables_dir_path();
+
+    std::iter::once(venv_dir.clone())
+        .chain(
+            env_path_values()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|it| it != venv_dir),
+        )
+        .collect()
+}
+
+/// Resolve a `program` in the `PythonEnvironment`'s executables directory, then `PATH`. If it
+/// can't be found the error suggests the environment's similarly named console scripts.
+fn resolve_venv_program(
+    program: &str,
+    python_env: &PythonEnvironment,
+    config: &Config,
+) -> HuakResult<PathBuf> {
+    match resolve_program(program, &program_search_dirs(python_env), config) {
+        Err(Error::ProgramNotFound { .. }) => {
+            let mut scripts = entry_point_scripts(python_env.site_packages_dir_path())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|it| it.name)
+                .collect::<Vec<_>>();
+            scripts.extend(executables_in(python_env.executables_dir_path()));
+
+            Err(program_not_found(program, scripts))
+        }
+        result => result,
+    }
+}
+
+/// Get the error for a `program` that couldn't be found, suggesting up to three similarly named
+/// `scripts`.
+fn program_not_found<I>(program: &str, scripts: I) -> Error
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    Error::ProgramNotFound {
+        program: program.to_string(),
+        suggestions: similar_names(program, scripts),
+    }
+}
+
+/// Resolve a `program` to the first executable with its name in the `dirs`. Programs given as
+/// paths are used as they are.
+fn resolve_program(program: &str, dirs: &[PathBuf], config: &Config) -> HuakResult<PathBuf> {
+    if Path::new(program).components().count() > 1 {
+        return Ok(PathBuf::from(program));
+    }
+
+    let Some(path) = find_program(program, dirs, pathext().as_deref()) else {
+        return Err(Error::ProgramNotFound {
+            program: program.to_string(),
+            suggestions: Vec::new(),
+        });
+    };
+
+    config.terminal().print_verbose(
+        "Resolved",
+        format!("{program} to {}", path.display()),
+        Color::Cyan,
+        true,
+    )?;
+
+    Ok(path)
+}
+
+/// Find the first executable named like the `program` (see `executable_names`) in the `dirs`,
+/// trying each directory in order.
+fn find_program(program: &str, dirs: &[PathBuf], pathext: Option<&str>) -> Option<PathBuf> {
+    let names = executable_names(program, pathext);
+
+    dirs.iter().find_map(|dir| {
+        names
+            .iter()
+            .map(|it| dir.join(it))
+            .find(|it| is_executable(it))
+    })
+}
+
+/// Get the `PATHEXT` programs are resolved with. Only Windows has one.
+fn pathext() -> Option<String> {
+    cfg!(windows).then(|| std::env::var("PATHEXT").unwrap_or_else(|_| DEFAULT_PATHEXT.to_string()))
+}
+
+/// Get the file names a `program` can have, in the order they're searched for.
+///
+/// With a `pathext` (on Windows) each of its extensions is tried like `cmd.exe` does, so
+/// console scripts' `.exe` launchers and `.cmd` shims are found. A name that already ends
+/// with one of them is tried as it is first.
+fn executable_names(program: &str, pathext: Option<&str>) -> Vec<String> {
+    let Some(pathext) = pathext else {
+        return vec![program.to_string()];
+    };
+    let extensions = pathext
+        .split(';')
+        .map(str::trim)
+        .filter(|it| it.starts_with('.') && it.len() > 1)
+        .collect::<Vec<_>>();
+    let has_extension = Path::new(program)
+        .extension()
+        .and_then(|it| it.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|it| it[1..].eq_ignore_ascii_case(ext))
+        });
+
+    let mut names = extensions
+        .iter()
+        .map(|it| format!("{program}{}", it.to_ascii_lowercase()))
+        .collect::<Vec<_>>();
+    if has_extension {
+        names.insert(0, program.to_string());
+    } else {
+        names.push(program.to_string());
+    }
+
+    names
+}
+
+/// Check if a string is just a program's name (not a path, assignment, or shell syntax).
+fn is_program_name(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|it| it.is_alphanumeric() || matches!(it, '.' | '_' | '-' | '+'))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|it| it.is_file() && it.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(windows)]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Quote a string for the shell `huak run` uses.
+fn shell_quote(s: &str) -> String {
+    match OS {
+        "windows" => format!("\"{s}\""),
+        _ => format!("'{}'", s.replace('\'', r"'\''")),
+    }
+}
+
 fn item_as_args(item: &Item) -> Option<Vec<String>> {
     if let Item::Value(value) = item {
         match value {
@@ -464,6 +926,36 @@ mod tests {
     use huak_dev::dev_resources_dir;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_parse_entry_point_scripts() {
+        let contents = r"[console_scripts]
+alembic = alembic.config:main
+
+[gui_scripts]
+viewer=viewer.app:run [gui]
+
+[pytest11]
+cov = pytest_cov.plugin
+";
+
+        assert_eq!(
+            parse_entry_point_scripts(contents),
+            [
+                ("alembic".to_string(), "alembic.config:main".to_string()),
+                ("viewer".to_string(), "viewer.app:run [gui]".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_sequence() {
+        let args = ["lint", "--", "test", "-x", "--", "--", "build"]
+            .map(ToString::to_string)
+            .to_vec();
+
+        assert_eq!(command_sequence(&args), ["lint", "test -x", "build"]);
+    }
+
     #[test]
     fn test_run_command_str() {
         let dir = tempdir().unwrap();
@@ -501,4 +993,253 @@ mod tests {
         assert!(!venv_had_package);
         assert!(venv_contains_package);
     }
+
+    #[test]
+    fn test_run_command_str_without_command() {
+        let dir = tempdir().unwrap();
+        let config = Config {
+            workspace_root: dir.path().to_path_buf(),
+            cwd: dir.path().to_path_buf(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let manifest = "[project]\nname = \"mock-project\"\nversion = \"0.0.1\"\n";
+        std::fs::write(dir.path().join("pyproject.toml"), manifest).unwrap();
+
+        assert!(matches!(
+            run_command_str(" ", &config),
+            Err(Error::InvalidProgram(_))
+        ));
+
+        std::fs::write(
+            dir.path().join("pyproject.toml"),
+            format!("{manifest}\n[tool.huak.task]\nlint = \"ruff check .\"\n"),
+        )
+        .unwrap();
+
+        run_command_str("", &config).unwrap();
+    }
+
+    #[test]
+    fn test_task_command() {
+        let doc = r#"lint = "ruff check ."
+test = ["pytest", "-x"]
+docs = { cmd = "mkdocs build", env = { CI = "1" } }
+fmt = { program = "ruff", args = ["format", "."] }
+ci = { chain = ["lint", "test"] }
+"#
+        .parse::<toml_edit::Document>()
+        .unwrap();
+        let commands = doc
+            .iter()
+            .map(|(_, item)| task_command(item))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            commands,
+            [
+                "ruff check .",
+                "pytest -x",
+                "mkdocs build",
+                "ruff format .",
+                "chain: lint, test"
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_program() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let venv = dir.path().join("bin");
+        let system = dir.path().join("system");
+        for (dir, name, mode) in [
+            (&venv, "tool", 0o755),
+            (&venv, "data", 0o644),
+            (&system, "tool", 0o755),
+            (&system, "data", 0o755),
+        ] {
+            std::fs::create_dir_all(dir).unwrap();
+            std::fs::write(dir.join(name), "").unwrap();
+            std::fs::set_permissions(dir.join(name), std::fs::Permissions::from_mode(mode))
+                .unwrap();
+        }
+        let dirs = [venv.clone(), system.clone()];
+        let config = Config {
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(
+            resolve_program("tool", &dirs, &config).unwrap(),
+            venv.join("tool")
+        );
+        // Files that aren't executable are skipped.
+        assert_eq!(
+            resolve_program("data", &dirs, &config).unwrap(),
+            system.join("data")
+        );
+        assert_eq!(
+            resolve_program("./tool", &dirs, &config).unwrap(),
+            PathBuf::from("./tool")
+        );
+        assert!(matches!(
+            resolve_program("missing", &dirs, &config),
+            Err(Error::ProgramNotFound { .. })
+        ));
+        assert_eq!(
+            program_not_found("pytets", ["pytest", "py.test", "python", "pip"]).to_string(),
+            "a program could not be found in the virtual environment or PATH: pytets; \
+            did you mean pytest?"
+        );
+        assert_eq!(
+            program_not_found("pip4", ["pip", "pip3", "pytest"]).to_string(),
+            "a program could not be found in the virtual environment or PATH: pip4; \
+            did you mean pip or pip3?"
+        );
+        assert!(matches!(
+            program_not_found("deploy", ["pytest", "ruff"]),
+            Error::ProgramNotFound { suggestions, .. } if suggestions.is_empty()
+        ));
+        assert!(is_program_name("python3.11"));
+        assert!(!is_program_name("FOO=bar"));
+        assert!(!is_program_name("./tool"));
+        assert_eq!(shell_quote("/it's"), r"'/it'\''s'");
+    }
+
+    #[test]
+    fn test_executable_names() {
+        assert_eq!(executable_names("pip", None), ["pip"]);
+        assert_eq!(
+            executable_names("pip", Some(".COM;.EXE;;.CMD")),
+            ["pip.com", "pip.exe", "pip.cmd", "pip"]
+        );
+        assert_eq!(
+            executable_names("pip.EXE", Some(".EXE;.CMD")),
+            ["pip.EXE", "pip.EXE.exe", "pip.EXE.cmd"]
+        );
+        assert_eq!(
+            executable_names("python3.11", Some(".EXE")),
+            ["python3.11.exe", "python3.11"]
+        );
+    }
+
+    #[test]
+    fn test_find_program_pathext() {
+        let dir = tempdir().unwrap();
+        let scripts = dir.path().join(".venv").join("Scripts");
+        let bin = dir.path().join("bin");
+        std::fs::create_dir_all(&scripts).unwrap();
+        std::fs::create_dir_all(&bin).unwrap();
+        let stub = |path: PathBuf| {
+            std::fs::write(&path, "").unwrap();
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+            }
+            path
+        };
+
+        let dirs = [scripts.clone()];
+
+        // A console script's `.exe` launcher, like pip writes to `Scripts`.
+        let exe = stub(scripts.join("black.exe"));
+        assert_eq!(
+            find_program("black", &dirs, Some(DEFAULT_PATHEXT)),
+            Some(exe.clone())
+        );
+        assert_eq!(find_program("black", &dirs, None), None);
+
+        // Extensions are tried in the order `PATHEXT` lists them.
+        let cmd = stub(scripts.join("black.cmd"));
+        assert_eq!(
+            find_program("black", &dirs, Some(".EXE;.CMD")),
+            Some(exe.clone())
+        );
+        assert_eq!(find_program("black", &dirs, Some(".CMD;.EXE")), Some(cmd));
+
+        // An earlier directory wins over an earlier extension.
+        let bat = stub(bin.join("black.bat"));
+        assert_eq!(
+            find_program(
+                "black",
+                &[bin.clone(), scripts.clone()],
+                Some(DEFAULT_PATHEXT)
+            ),
+            Some(bat)
+        );
+        assert_eq!(
+            find_program("black", &[scripts, bin], Some(DEFAULT_PATHEXT)),
+            Some(exe)
+        );
+    }
+
+    /// Create a project with a virtual environment in `dir` for `huak run` to run in.
+    #[cfg(windows)]
+    fn windows_project(dir: &Path) -> Config {
+        let config = Config {
+            workspace_root: dir.to_path_buf(),
+            cwd: dir.to_path_buf(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        std::fs::write(
+            dir.join("pyproject.toml"),
+            "[project]\nname = \"mock-project\"\nversion = \"0.0.1\"\n",
+        )
+        .unwrap();
+        crate::ops::create_python_environment(&config).unwrap();
+
+        config
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_run_pip_windows() {
+        let dir = tempdir().unwrap();
+        let config = windows_project(dir.path());
+
+        run_command_str("pip --version", &config).unwrap();
+
+        let scripts = dir.path().join(".venv").join("Scripts");
+        assert_eq!(
+            resolve_program("pip", &[scripts.clone()], &config).unwrap(),
+            scripts.join("pip.exe")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_run_console_script_windows() {
+        let dir = tempdir().unwrap();
+        let config = windows_project(dir.path());
+        let scripts = dir.path().join(".venv").join("Scripts");
+        // A console script that fails unless it's run as if the environment were activated.
+        std::fs::write(
+            scripts.join("check-venv.cmd"),
+            "@echo off\r\nif not defined VIRTUAL_ENV exit /b 3\r\nif defined PYTHONHOME exit /b 4\r\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("pyproject.toml"),
+            "[project]\nname = \"mock-project\"\nversion = \"0.0.1\"\n\n\
+             [tool.huak.task]\ncheck = { program = \"check-venv\" }\n",
+        )
+        .unwrap();
+
+        run_command_str("check", &config).unwrap();
+        run_command_str("check-venv", &config).unwrap();
+    }
 }
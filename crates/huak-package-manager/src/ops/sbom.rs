@@ -0,0 +1,757 @@
+use super::project_dependencies;
+use crate::{dependency_iter, history::utc_timestamp, Config, Error, HuakResult};
+use huak_pyproject_toml::{canonical_name, PyProjectToml};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::SystemTime,
+};
+use termcolor::Color;
+use toml_edit::Item;
+
+/// The version of the `CycloneDX` specification SBOMs are written with.
+const CYCLONEDX_SPEC_VERSION: &str = "1.5";
+
+/// The SPDX specification version SBOMs are written with.
+const SPDX_VERSION: &str = "SPDX-2.3";
+
+pub struct SbomOptions {
+    pub format: SbomFormat,
+    /// Write the SBOM to the path instead of stdout.
+    pub output: Option<PathBuf>,
+}
+
+/// The specification an SBOM is written with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SbomFormat {
+    /// A `CycloneDX` JSON document.
+    #[default]
+    CycloneDxJson,
+    /// An SPDX JSON document.
+    SpdxJson,
+}
+
+impl FromStr for SbomFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cyclonedx-json" => Ok(SbomFormat::CycloneDxJson),
+            "spdx-json" => Ok(SbomFormat::SpdxJson),
+            _ => Err(Error::InvalidOutputFormat(format!(
+                "{s} (expected cyclonedx-json or spdx-json)"
+            ))),
+        }
+    }
+}
+
+impl Display for SbomFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SbomFormat::CycloneDxJson => write!(f, "cyclonedx-json"),
+            SbomFormat::SpdxJson => write!(f, "spdx-json"),
+        }
+    }
+}
+
+/// A distribution in an SBOM, read from its core metadata.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Component {
+    /// The distribution's canonical name.
+    name: String,
+    version: String,
+    license: Option<License>,
+    /// `sha256:<digest>` hashes of the distribution's archives.
+    hashes: Vec<String>,
+    /// The canonical names of the installed distributions it requires.
+    requires: Vec<String>,
+}
+
+impl Component {
+    /// Get the component's package URL, like `pkg:pypi/django@4.2.0`.
+    fn purl(&self) -> String {
+        format!("pkg:pypi/{}@{}", self.name, self.version)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum License {
+    /// An SPDX license expression, like `MIT OR Apache-2.0`.
+    Expression(String),
+    /// A free-form license name.
+    Name(String),
+}
+
+/// Write a software bill of materials for the project and the distributions installed in its
+/// Python environment.
+///
+/// The project is the root component and depends on the distributions it declares. Hashes are
+/// included for dependencies pinned with `[tool.huak.hashes]`.
+pub fn generate_sbom(config: &Config, options: &SbomOptions) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let manifest = workspace.current_local_manifest()?;
+    let python_env = workspace.current_python_environment()?;
+    let mut components = installed_components(python_env.site_packages_dir_path())?;
+    let root = project_component(manifest.manifest_data(), &components)?;
+    let deps = project_dependencies(&manifest)?;
+    let declared = dependency_iter(deps.required.iter().chain(deps.optional.values().flatten()))
+        .map(|it| canonical_name(it.name()))
+        .collect::<Vec<_>>();
+    let hashes = manifest.manifest_data().dependency_hashes();
+
+    // An editable install of the project is the root component rather than a dependency.
+    components.retain(|it| it.name != root.name);
+
+    for it in &mut components {
+        it.hashes = hashes.get(&it.name).cloned().unwrap_or_default();
+        it.requires.retain(|name| *name != root.name);
+    }
+
+    let root = Component {
+        requires: declared
+            .into_iter()
+            .filter(|name| components.iter().any(|it| it.name == *name))
+            .collect(),
+        ..root
+    };
+    let timestamp = utc_timestamp(SystemTime::now());
+    let document = match options.format {
+        SbomFormat::CycloneDxJson => cyclonedx_document(&root, &components, &timestamp),
+        SbomFormat::SpdxJson => spdx_document(&root, &components, &timestamp),
+    };
+    let contents = format!("{}\n", serde_json::to_string_pretty(&document)?);
+
+    let Some(path) = options.output.as_ref() else {
+        let mut stdout = std::io::stdout();
+        write!(stdout, "{contents}")?;
+        return Ok(());
+    };
+
+    let path = config.cwd.join(path);
+    config.write_file(&path, &contents)?;
+
+    if config.dry_run {
+        return Ok(());
+    }
+
+    config
+        .terminal()
+        .print_custom("Wrote", path.display(), Color::Green, true)
+}
+
+/// Get the project's component from its manifest. Its requirements are left empty.
+fn project_component(data: &PyProjectToml, installed: &[Component]) -> HuakResult<Component> {
+    let name = data
+        .project_name()
+        .map(|it| canonical_name(&it))
+        .ok_or_else(|| Error::InvalidMetadata("[project] has no name".to_string()))?;
+    // A dynamic version is read from the project's installed metadata if it's installed.
+    let version = data
+        .project_version()
+        .or_else(|| {
+            installed
+                .iter()
+                .find(|it| it.name == name)
+                .map(|it| it.version.clone())
+        })
+        .unwrap_or_else(|| "0.0.0".to_string());
+    let license = data
+        .project_table()
+        .and_then(|it| it.get("license"))
+        .and_then(project_license);
+
+    Ok(Component {
+        name,
+        version,
+        license,
+        ..Default::default()
+    })
+}
+
+/// Get the license from a `[project] license` value, either a PEP 639 expression or a table
+/// with the license's `text`.
+fn project_license(item: &Item) -> Option<License> {
+    if let Some(it) = item.as_str() {
+        return Some(License::Expression(it.trim().to_string()));
+    }
+
+    item.as_table_like()
+        .and_then(|it| it.get("text"))
+        .and_then(Item::as_str)
+        .and_then(license_name)
+}
+
+/// Read the components of the distributions installed in a `site-packages` directory, sorted
+/// by name.
+fn installed_components(site_packages: &Path) -> HuakResult<Vec<Component>> {
+    let entries = match std::fs::read_dir(site_packages) {
+        Ok(it) => it,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut components = BTreeMap::new();
+
+    for entry in entries.filter_map(Result::ok) {
+        if !entry.file_name().to_string_lossy().ends_with(".dist-info") {
+            continue;
+        }
+        let Ok(contents) = std::fs::read_to_string(entry.path().join("METADATA")) else {
+            continue;
+        };
+        if let Some(it) = parse_metadata(&contents) {
+            components.insert(it.name.clone(), it);
+        }
+    }
+
+    // Only installed distributions can be depended on.
+    let installed = components.keys().cloned().collect::<Vec<_>>();
+    for it in components.values_mut() {
+        it.requires.retain(|name| installed.contains(name));
+    }
+
+    Ok(components.into_values().collect())
+}
+
+/// Parse a distribution's core metadata into a `Component`.
+///
+/// Requirements only needed for an extra are left out. Other markers aren't evaluated, since
+/// a requirement that's installed was most likely installed for the distribution.
+fn parse_metadata(contents: &str) -> Option<Component> {
+    let mut fields = HashMap::<&str, Vec<&str>>::new();
+
+    // The headers end at the first blank line, where the description starts. Folded lines
+    // continue the previous header.
+    for line in contents.lines().take_while(|it| !it.trim().is_empty()) {
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            fields.entry(key.trim()).or_default().push(value.trim());
+        }
+    }
+
+    let field = |key| fields.get(key).and_then(|it| it.first()).copied();
+    let name = canonical_name(field("Name")?);
+    let version = field("Version")?.to_string();
+    let license = field("License-Expression")
+        .map(|it| License::Expression(it.to_string()))
+        .or_else(|| field("License").and_then(license_name))
+        .or_else(|| {
+            fields
+                .get("Classifier")?
+                .iter()
+                .find_map(|it| it.strip_prefix("License ::"))
+                .and_then(|it| it.rsplit("::").next())
+                .and_then(license_name)
+        });
+    let mut requires = fields
+        .get("Requires-Dist")
+        .into_iter()
+        .flatten()
+        .filter(|it| {
+            it.split_once(';')
+                .map_or(true, |(_, marker)| !marker.contains("extra"))
+        })
+        .filter_map(|it| {
+            let end = it
+                .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+                .unwrap_or(it.len());
+            Some(canonical_name(&it[..end])).filter(|it| !it.is_empty())
+        })
+        .collect::<Vec<_>>();
+    requires.sort();
+    requires.dedup();
+
+    Some(Component {
+        name,
+        version,
+        license,
+        hashes: Vec::new(),
+        requires,
+    })
+}
+
+/// Get a free-form license name, leaving out placeholders and full license texts.
+fn license_name(s: &str) -> Option<License> {
+    let s = s.trim();
+
+    if s.is_empty() || s.eq_ignore_ascii_case("UNKNOWN") || s.contains('\n') || s.len() > 100 {
+        return None;
+    }
+
+    Some(License::Name(s.to_string()))
+}
+
+/// Get a UUID (in version 4's format) for a document, derived from its contents and when it
+/// was written.
+fn document_uuid(root: &Component, components: &[Component], timestamp: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(root.purl());
+    for it in components {
+        hasher.update(it.purl());
+    }
+    hasher.update(timestamp);
+    hasher.update(
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |it| it.as_nanos())
+            .to_le_bytes(),
+    );
+    let mut bytes = hasher.finalize()[..16].to_vec();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+fn cyclonedx_document(root: &Component, components: &[Component], timestamp: &str) -> Value {
+    let component = |it: &Component, kind: &str| {
+        let mut value = json!({
+            "type": kind,
+            "bom-ref": it.purl(),
+            "name": it.name,
+            "version": it.version,
+            "purl": it.purl(),
+        });
+        match &it.license {
+            Some(License::Expression(expression)) => {
+                value["licenses"] = json!([{ "expression": expression }]);
+            }
+            Some(License::Name(name)) => {
+                value["licenses"] = json!([{ "license": { "name": name } }]);
+            }
+            None => (),
+        }
+        let hashes = it
+            .hashes
+            .iter()
+            .filter_map(|it| it.strip_prefix("sha256:"))
+            .map(|it| json!({ "alg": "SHA-256", "content": it }))
+            .collect::<Vec<_>>();
+        if !hashes.is_empty() {
+            value["hashes"] = Value::Array(hashes);
+        }
+        value
+    };
+    let purls = components
+        .iter()
+        .map(|it| (it.name.as_str(), it.purl()))
+        .collect::<HashMap<_, _>>();
+    let dependencies = std::iter::once(root)
+        .chain(components)
+        .map(|it| {
+            json!({
+                "ref": it.purl(),
+                "dependsOn": it.requires.iter().map(|name| &purls[name.as_str()]).collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": CYCLONEDX_SPEC_VERSION,
+        "serialNumber": format!("urn:uuid:{}", document_uuid(root, components, timestamp)),
+        "version": 1,
+        "metadata": {
+            "timestamp": timestamp,
+            "component": component(root, "application"),
+        },
+        "components": components.iter().map(|it| component(it, "library")).collect::<Vec<_>>(),
+        "dependencies": dependencies,
+    })
+}
+
+fn spdx_document(root: &Component, components: &[Component], timestamp: &str) -> Value {
+    let spdx_id = |it: &Component| format!("SPDXRef-Package-{}", it.name);
+    let root_id = format!("SPDXRef-Project-{}", root.name);
+    let package = |it: &Component, id: String| {
+        let declared = match &it.license {
+            Some(License::Expression(it)) => it.as_str(),
+            Some(License::Name(_)) | None => "NOASSERTION",
+        };
+        let mut value = json!({
+            "SPDXID": id,
+            "name": it.name,
+            "versionInfo": it.version,
+            "downloadLocation": "NOASSERTION",
+            "filesAnalyzed": false,
+            "licenseConcluded": "NOASSERTION",
+            "licenseDeclared": declared,
+            "externalRefs": [{
+                "referenceCategory": "PACKAGE-MANAGER",
+                "referenceType": "purl",
+                "referenceLocator": it.purl(),
+            }],
+        });
+        let checksums = it
+            .hashes
+            .iter()
+            .filter_map(|it| it.strip_prefix("sha256:"))
+            .map(|it| json!({ "algorithm": "SHA256", "checksumValue": it }))
+            .collect::<Vec<_>>();
+        if !checksums.is_empty() {
+            value["checksums"] = Value::Array(checksums);
+        }
+        value
+    };
+    let packages = std::iter::once(package(root, root_id.clone()))
+        .chain(components.iter().map(|it| package(it, spdx_id(it))))
+        .collect::<Vec<_>>();
+    let relationships = std::iter::once(json!({
+        "spdxElementId": "SPDXRef-DOCUMENT",
+        "relationshipType": "DESCRIBES",
+        "relatedSpdxElement": root_id,
+    }))
+    .chain(
+        std::iter::once((root_id.clone(), root))
+            .chain(components.iter().map(|it| (spdx_id(it), it)))
+            .flat_map(|(id, it)| {
+                it.requires.iter().map(move |name| {
+                    json!({
+                        "spdxElementId": id,
+                        "relationshipType": "DEPENDS_ON",
+                        "relatedSpdxElement": format!("SPDXRef-Package-{name}"),
+                    })
+                })
+            }),
+    )
+    .collect::<Vec<_>>();
+
+    json!({
+        "spdxVersion": SPDX_VERSION,
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": format!("{}-{}", root.name, root.version),
+        "documentNamespace": format!(
+            "https://spdx.org/spdxdocs/{}-{}-{}",
+            root.name,
+            root.version,
+            document_uuid(root, components, timestamp)
+        ),
+        "creationInfo": {
+            "created": timestamp,
+            "creators": ["Tool: huak"],
+        },
+        "packages": packages,
+        "relationships": relationships,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+    use std::collections::HashSet;
+    use tempfile::tempdir;
+
+    /// Write a fixture `site-packages` directory with `requests` and its dependencies.
+    fn site_packages(dir: &Path) -> PathBuf {
+        let site_packages = dir.join("site-packages");
+        let distributions = [
+            (
+                "requests-2.31.0",
+                "Metadata-Version: 2.1\nName: requests\nVersion: 2.31.0\nLicense: Apache 2.0\n\
+                 Requires-Dist: charset-normalizer (<4,>=2)\nRequires-Dist: urllib3<3,>=1.21.1\n\
+                 Requires-Dist: PySocks!=1.5.7,>=1.5.6; extra == \"socks\"\n\nRequests docs.\n",
+            ),
+            (
+                "charset_normalizer-3.3.2",
+                "Metadata-Version: 2.1\nName: charset-normalizer\nVersion: 3.3.2\n\
+                 Classifier: License :: OSI Approved :: MIT License\n",
+            ),
+            (
+                "urllib3-2.2.1",
+                "Metadata-Version: 2.3\nName: urllib3\nVersion: 2.2.1\n\
+                 License-Expression: MIT\nRequires-Dist: brotli>=1.0.9; extra == 'brotli'\n",
+            ),
+        ];
+
+        for (dist_info, metadata) in distributions {
+            let path = site_packages.join(format!("{dist_info}.dist-info"));
+            std::fs::create_dir_all(&path).unwrap();
+            std::fs::write(path.join("METADATA"), metadata).unwrap();
+        }
+
+        site_packages
+    }
+
+    fn fixture() -> (Component, Vec<Component>) {
+        let dir = tempdir().unwrap();
+        let mut components = installed_components(&site_packages(dir.path())).unwrap();
+        components[1].hashes = vec![format!("sha256:{}", "a".repeat(64))];
+        let data = PyProjectToml::from_str(
+            "[project]\nname = \"My_Project\"\nversion = \"0.1.0\"\nlicense = \"MIT\"\n",
+        )
+        .unwrap();
+        let root = Component {
+            requires: vec!["requests".to_string()],
+            ..project_component(&data, &components).unwrap()
+        };
+
+        (root, components)
+    }
+
+    #[test]
+    fn test_installed_components() {
+        let (root, components) = fixture();
+
+        assert_eq!(root.name, "my-project");
+        assert_eq!(root.license, Some(License::Expression("MIT".to_string())));
+        assert_eq!(
+            components
+                .iter()
+                .map(|it| (it.purl(), it.license.clone(), it.requires.clone()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "pkg:pypi/charset-normalizer@3.3.2".to_string(),
+                    Some(License::Name("MIT License".to_string())),
+                    Vec::new()
+                ),
+                (
+                    "pkg:pypi/requests@2.31.0".to_string(),
+                    Some(License::Name("Apache 2.0".to_string())),
+                    vec!["charset-normalizer".to_string(), "urllib3".to_string()]
+                ),
+                (
+                    "pkg:pypi/urllib3@2.2.1".to_string(),
+                    Some(License::Expression("MIT".to_string())),
+                    Vec::new()
+                ),
+            ]
+        );
+    }
+
+    /// Check a document has what the `CycloneDX` 1.5 JSON schema requires: the required
+    /// properties, the `serialNumber` and `date-time` formats, the enumerated values, and unique
+    /// `bom-ref`s that every dependency refers to.
+    fn assert_valid_cyclonedx(document: &Value) {
+        let serial_number = Regex::new(
+            "^urn:uuid:[0-9a-f]{8}-[0-9a-f]{4}-[1-5][0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$",
+        )
+        .unwrap();
+        let date_time = Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?Z$").unwrap();
+        let hash = Regex::new(
+            "^([a-fA-F0-9]{32}|[a-fA-F0-9]{40}|[a-fA-F0-9]{64}|[a-fA-F0-9]{96}|[a-fA-F0-9]{128})$",
+        )
+        .unwrap();
+        let component_types = [
+            "application",
+            "framework",
+            "library",
+            "container",
+            "platform",
+            "operating-system",
+            "device",
+            "device-driver",
+            "firmware",
+            "file",
+            "machine-learning-model",
+            "data",
+        ];
+        let document = document.as_object().unwrap();
+
+        assert!(document.keys().all(|it| [
+            "$schema",
+            "bomFormat",
+            "specVersion",
+            "serialNumber",
+            "version",
+            "metadata",
+            "components",
+            "dependencies",
+        ]
+        .contains(&it.as_str())));
+        assert_eq!(document["bomFormat"], "CycloneDX");
+        assert!(document["specVersion"].is_string());
+        assert!(serial_number.is_match(document["serialNumber"].as_str().unwrap()));
+        assert!(document["version"].as_u64().unwrap() >= 1);
+        assert!(date_time.is_match(document["metadata"]["timestamp"].as_str().unwrap()));
+
+        let components = std::iter::once(&document["metadata"]["component"])
+            .chain(document["components"].as_array().unwrap())
+            .collect::<Vec<_>>();
+        let mut refs = HashSet::new();
+        for it in &components {
+            assert!(component_types.contains(&it["type"].as_str().unwrap()));
+            assert!(it["name"].is_string());
+            assert!(refs.insert(it["bom-ref"].as_str().unwrap()));
+            for license in it["licenses"].as_array().into_iter().flatten() {
+                let license = license.as_object().unwrap();
+                assert_eq!(license.len(), 1);
+                match license.keys().next().unwrap().as_str() {
+                    "expression" => assert!(license["expression"].is_string()),
+                    "license" => assert!(
+                        license["license"]["id"].is_string()
+                            || license["license"]["name"].is_string()
+                    ),
+                    key => panic!("unexpected license property {key}"),
+                }
+            }
+            for it in it["hashes"].as_array().into_iter().flatten() {
+                assert!(["MD5", "SHA-1", "SHA-256", "SHA-384", "SHA-512"]
+                    .contains(&it["alg"].as_str().unwrap()));
+                assert!(hash.is_match(it["content"].as_str().unwrap()));
+            }
+        }
+
+        let mut dependencies = HashSet::new();
+        for it in document["dependencies"].as_array().unwrap() {
+            let dependency = it["ref"].as_str().unwrap();
+            assert!(refs.contains(dependency));
+            assert!(dependencies.insert(dependency));
+            for it in it["dependsOn"].as_array().unwrap() {
+                assert!(refs.contains(it.as_str().unwrap()));
+            }
+        }
+    }
+
+    /// Check a document has what the SPDX 2.3 JSON schema requires: the required properties,
+    /// the `SPDXID` and `created` formats, the enumerated values, and relationships between
+    /// elements the document has.
+    fn assert_valid_spdx(document: &Value) {
+        let spdx_id = Regex::new("^SPDXRef-[a-zA-Z0-9.-]+$").unwrap();
+        let created = Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}Z$").unwrap();
+
+        assert_eq!(document["spdxVersion"], "SPDX-2.3");
+        assert_eq!(document["dataLicense"], "CC0-1.0");
+        assert_eq!(document["SPDXID"], "SPDXRef-DOCUMENT");
+        assert!(document["name"].is_string());
+        let namespace = url::Url::parse(document["documentNamespace"].as_str().unwrap()).unwrap();
+        assert!(namespace.fragment().is_none());
+        assert!(created.is_match(document["creationInfo"]["created"].as_str().unwrap()));
+        let creators = document["creationInfo"]["creators"].as_array().unwrap();
+        assert!(!creators.is_empty());
+        assert!(creators
+            .iter()
+            .all(|it| ["Tool: ", "Organization: ", "Person: "]
+                .iter()
+                .any(|prefix| it.as_str().unwrap().starts_with(prefix))));
+
+        let mut ids = HashSet::from(["SPDXRef-DOCUMENT"]);
+        for it in document["packages"].as_array().unwrap() {
+            let id = it["SPDXID"].as_str().unwrap();
+            assert!(spdx_id.is_match(id));
+            assert!(ids.insert(id));
+            assert!(it["name"].is_string());
+            assert!(it["downloadLocation"].is_string());
+            assert!(it["filesAnalyzed"].is_boolean());
+            for it in it["checksums"].as_array().into_iter().flatten() {
+                assert!(["SHA1", "SHA224", "SHA256", "SHA384", "SHA512", "MD5"]
+                    .contains(&it["algorithm"].as_str().unwrap()));
+                assert!(it["checksumValue"].is_string());
+            }
+            for it in it["externalRefs"].as_array().into_iter().flatten() {
+                assert!(["SECURITY", "PACKAGE-MANAGER", "PERSISTENT-ID", "OTHER"]
+                    .contains(&it["referenceCategory"].as_str().unwrap()));
+                assert!(it["referenceType"].is_string());
+                assert!(it["referenceLocator"].is_string());
+            }
+        }
+
+        for it in document["relationships"].as_array().unwrap() {
+            assert!(ids.contains(it["spdxElementId"].as_str().unwrap()));
+            assert!(ids.contains(it["relatedSpdxElement"].as_str().unwrap()));
+            assert!(["DESCRIBES", "DEPENDS_ON"].contains(&it["relationshipType"].as_str().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_cyclonedx_document() {
+        let (root, components) = fixture();
+        let timestamp = utc_timestamp(SystemTime::now());
+        let document = cyclonedx_document(&root, &components, &timestamp);
+
+        assert_valid_cyclonedx(&document);
+        assert_eq!(document["specVersion"], "1.5");
+        assert_eq!(document["metadata"]["timestamp"], timestamp);
+        // The serial number is a version 4 UUID.
+        assert_eq!(document["serialNumber"].as_str().unwrap()[23..24], *"4");
+        assert_eq!(
+            document["metadata"]["component"],
+            json!({
+                "type": "application",
+                "bom-ref": "pkg:pypi/my-project@0.1.0",
+                "name": "my-project",
+                "version": "0.1.0",
+                "purl": "pkg:pypi/my-project@0.1.0",
+                "licenses": [{ "expression": "MIT" }],
+            })
+        );
+        assert_eq!(
+            document["components"][1]["hashes"],
+            json!([{ "alg": "SHA-256", "content": "a".repeat(64) }])
+        );
+        assert_eq!(
+            document["dependencies"][0],
+            json!({ "ref": "pkg:pypi/my-project@0.1.0", "dependsOn": ["pkg:pypi/requests@2.31.0"] })
+        );
+
+        // A project without any installed dependencies, like in a fresh environment.
+        let root = Component {
+            requires: Vec::new(),
+            ..root
+        };
+        assert_valid_cyclonedx(&cyclonedx_document(&root, &[], &timestamp));
+    }
+
+    #[test]
+    fn test_spdx_document() {
+        let (root, components) = fixture();
+        let timestamp = utc_timestamp(SystemTime::now());
+        let document = spdx_document(&root, &components, &timestamp);
+
+        assert_valid_spdx(&document);
+        assert_eq!(document["creationInfo"]["created"], timestamp);
+        assert!(document["documentNamespace"]
+            .as_str()
+            .unwrap()
+            .starts_with("https://spdx.org/spdxdocs/my-project-0.1.0-"));
+
+        let packages = document["packages"].as_array().unwrap();
+
+        assert_eq!(packages.len(), 4);
+        assert_eq!(packages[0]["licenseDeclared"], "MIT");
+        assert_eq!(packages[2]["licenseDeclared"], "NOASSERTION");
+        assert_eq!(
+            document["relationships"][1],
+            json!({
+                "spdxElementId": "SPDXRef-Project-my-project",
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": "SPDXRef-Package-requests",
+            })
+        );
+
+        // A project without any installed dependencies, like in a fresh environment.
+        let root = Component {
+            requires: Vec::new(),
+            ..root
+        };
+        assert_valid_spdx(&spdx_document(&root, &[], &timestamp));
+    }
+
+    #[test]
+    fn test_sbom_format_from_str() {
+        assert_eq!(
+            SbomFormat::from_str("spdx-json").unwrap(),
+            SbomFormat::SpdxJson
+        );
+        assert!(matches!(
+            SbomFormat::from_str("cyclonedx-xml"),
+            Err(Error::InvalidOutputFormat(_))
+        ));
+    }
+}
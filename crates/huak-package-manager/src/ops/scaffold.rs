@@ -0,0 +1,446 @@
+use toml_edit::{Item, Table};
+
+use super::{
+    DEFAULT_PYTHON_CLI_FILE_CONTENTS, DEFAULT_PYTHON_INIT_FILE_CONTENTS,
+    DEFAULT_PYTHON_MAIN_FILE_CONTENTS,
+};
+use crate::{
+    default_package_entrypoint_string, default_package_test_file_contents,
+    default_python_gitignore, git::commit_paths, importable_package_name, Config, Dependency,
+    Error, HuakResult, LocalManifest,
+};
+use git2::Signature;
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use termcolor::Color;
+
+/// Python's reserved keywords, which can't be used as module names.
+const PYTHON_KEYWORDS: [&str; 35] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// The templates Huak can generate projects from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProjectTemplate {
+    /// A package with a `main` entrypoint registered as a project script.
+    App,
+    /// A package with an argument-parsing `cli` module registered as a project script.
+    Cli,
+    /// A package with tests.
+    #[default]
+    Lib,
+    /// Only the manifest file. No package or tests are generated.
+    Bare,
+}
+
+impl ProjectTemplate {
+    /// Get the files the `ProjectTemplate` generates for a project named `name`.
+    ///
+    /// Package files (src/ and tests/) are only included if `include_package` is `true`. The
+    /// modules a CLI's entry point needs are always included.
+    pub(crate) fn files(
+        self,
+        name: &str,
+        uses_git: bool,
+        include_package: bool,
+    ) -> HuakResult<Vec<TemplateFile>> {
+        let importable_name = importable_package_name(Dependency::from_str(name)?.name())?;
+        let entry_point = self.entry_point(&importable_name)?;
+        let mut files = vec![TemplateFile::new(
+            "pyproject.toml",
+            manifest_contents(name, entry_point.as_deref()),
+        )];
+
+        if uses_git {
+            files.push(TemplateFile::new(".gitignore", default_python_gitignore()).keep_existing());
+        }
+
+        let package_path = PathBuf::from("src").join(&importable_name);
+
+        if include_package && self != ProjectTemplate::Bare {
+            files.push(TemplateFile::new(
+                package_path.join("__init__.py"),
+                DEFAULT_PYTHON_INIT_FILE_CONTENTS,
+            ));
+            files.push(TemplateFile::new(
+                PathBuf::from("tests").join("test_version.py"),
+                default_package_test_file_contents(&importable_name),
+            ));
+
+            if self == ProjectTemplate::App {
+                files.push(TemplateFile::new(
+                    package_path.join("main.py"),
+                    DEFAULT_PYTHON_MAIN_FILE_CONTENTS,
+                ));
+            }
+        }
+
+        if self == ProjectTemplate::Cli {
+            files.push(TemplateFile::new(
+                package_path.join("cli.py"),
+                DEFAULT_PYTHON_CLI_FILE_CONTENTS,
+            ));
+            files.push(TemplateFile::new(
+                package_path.join("__main__.py"),
+                format!("from {importable_name}.cli import main\n\nmain()\n"),
+            ));
+        }
+
+        Ok(files)
+    }
+
+    /// Get the `[project.scripts]` entry point for the `ProjectTemplate` if it has one.
+    fn entry_point(self, importable_name: &str) -> HuakResult<Option<String>> {
+        match self {
+            ProjectTemplate::App => Ok(Some(default_package_entrypoint_string(importable_name))),
+            ProjectTemplate::Cli => {
+                if !is_python_identifier(importable_name) {
+                    return Err(Error::InvalidEntryPoint(importable_name.to_string()));
+                }
+
+                Ok(Some(format!("{importable_name}.cli:main")))
+            }
+            ProjectTemplate::Lib | ProjectTemplate::Bare => Ok(None),
+        }
+    }
+}
+
+/// Render the pyproject.toml for a project, registering its entry point as a script.
+fn manifest_contents(name: &str, entry_point: Option<&str>) -> String {
+    let mut manifest = LocalManifest::template("pyproject.toml");
+    manifest.manifest_data_mut().set_project_name(name);
+
+    if let Some(entry_point) = entry_point {
+        if let Some(table) = manifest.manifest_data_mut().project_table_mut() {
+            let scripts = &mut table["scripts"];
+
+            if scripts.is_none() {
+                *scripts = Item::Table(Table::new());
+            }
+
+            scripts[name] = toml_edit::value(entry_point);
+        }
+    }
+
+    manifest.manifest_data_mut().formatted();
+    manifest.manifest_data().to_string()
+}
+
+/// Check if a name can be imported as a Python module.
+pub(crate) fn is_python_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|it| it.is_ascii_alphabetic() || it == '_')
+        && chars.all(|it| it.is_ascii_alphanumeric() || it == '_')
+        && !PYTHON_KEYWORDS.contains(&name)
+}
+
+/// A file generated for a project from a `ProjectTemplate`.
+pub(crate) struct TemplateFile {
+    /// The path to the file relative to the workspace root.
+    path: PathBuf,
+    contents: String,
+    /// Leave an existing file alone instead of treating it as a conflict.
+    keep_existing: bool,
+}
+
+impl TemplateFile {
+    fn new<T: Into<PathBuf>, U: Into<String>>(path: T, contents: U) -> TemplateFile {
+        TemplateFile {
+            path: path.into(),
+            contents: contents.into(),
+            keep_existing: false,
+        }
+    }
+
+    fn keep_existing(mut self) -> TemplateFile {
+        self.keep_existing = true;
+        self
+    }
+}
+
+/// What to do with `TemplateFile`s that would overwrite an existing file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ExistingFiles {
+    /// Write nothing and error.
+    Error,
+    /// Back up the existing files to `<name>.bak` and overwrite them.
+    Backup,
+    /// Leave the existing files alone and report them as skipped.
+    Skip,
+}
+
+/// Write `TemplateFile`s to the workspace at `root`, reporting each file created. Returns the
+/// paths (relative to `root`) of the files written.
+pub(crate) fn write_template_files(
+    root: &Path,
+    files: &[TemplateFile],
+    existing: ExistingFiles,
+    config: &Config,
+) -> HuakResult<Vec<PathBuf>> {
+    if existing == ExistingFiles::Error {
+        if let Some(it) = files
+            .iter()
+            .find(|it| !it.keep_existing && root.join(&it.path).exists())
+        {
+            return Err(Error::FileExists {
+                path: root.join(&it.path),
+            });
+        }
+    }
+
+    let mut terminal = config.terminal();
+    let mut written = Vec::new();
+
+    for file in files {
+        let path = root.join(&file.path);
+
+        if file.keep_existing && path.exists() {
+            continue;
+        }
+
+        if existing == ExistingFiles::Skip && path.exists() {
+            terminal.print_custom(
+                "Skipped",
+                format!("{} (already exists)", file.path.display()),
+                Color::Yellow,
+                true,
+            )?;
+            continue;
+        }
+
+        if existing == ExistingFiles::Backup && path.exists() {
+            backup_file(&path, config)?;
+        }
+
+        if config.dry_run {
+            terminal.print_custom("Would create", file.path.display(), Color::Yellow, true)?;
+            continue;
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, &file.contents)?;
+
+        terminal.print_custom("Created", file.path.display(), Color::Green, true)?;
+        written.push(file.path.clone());
+    }
+
+    Ok(written)
+}
+
+/// Copy the file at `path` to `<name>.bak` next to it, replacing an earlier backup.
+pub(crate) fn backup_file(path: &Path, config: &Config) -> HuakResult<()> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    let backup = path.with_file_name(name);
+    let mut terminal = config.terminal();
+
+    if config.dry_run {
+        return terminal.print_custom(
+            "Would back up",
+            format!("{} to {}", path.display(), backup.display()),
+            Color::Yellow,
+            true,
+        );
+    }
+
+    std::fs::copy(path, &backup)?;
+
+    terminal.print_custom(
+        "Backed up",
+        format!("{} to {}", path.display(), backup.display()),
+        Color::Green,
+        true,
+    )
+}
+
+/// Commit the template `files` written to the git repository at `root` with the `message`
+/// (`--initial-commit`).
+pub(crate) fn commit_template_files(
+    root: &Path,
+    files: &[PathBuf],
+    message: &str,
+    signature: &Signature,
+    config: &Config,
+) -> HuakResult<()> {
+    let mut terminal = config.terminal();
+
+    if config.dry_run {
+        return terminal.print_custom(
+            "Would commit",
+            format!("the generated files ({message})"),
+            Color::Yellow,
+            true,
+        );
+    }
+    if files.is_empty() {
+        return terminal.print_warning("no files were generated, so nothing was committed");
+    }
+
+    let id = commit_paths(root, files, message, signature)?;
+
+    terminal.print_custom(
+        "Committed",
+        format!("{} {message}", &id.to_string()[..7]),
+        Color::Green,
+        true,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TerminalOptions, Verbosity};
+    use tempfile::tempdir;
+
+    fn file_paths(files: &[TemplateFile]) -> Vec<PathBuf> {
+        files.iter().map(|it| it.path.clone()).collect()
+    }
+
+    #[test]
+    fn test_template_files() {
+        let package_path = PathBuf::from("src").join("mock_project");
+        let lib = ProjectTemplate::Lib
+            .files("mock-project", true, true)
+            .unwrap();
+        let app = ProjectTemplate::App
+            .files("mock-project", false, true)
+            .unwrap();
+        let bare = ProjectTemplate::Bare
+            .files("mock-project", true, true)
+            .unwrap();
+
+        assert_eq!(
+            file_paths(&lib),
+            vec![
+                PathBuf::from("pyproject.toml"),
+                PathBuf::from(".gitignore"),
+                package_path.join("__init__.py"),
+                PathBuf::from("tests").join("test_version.py"),
+            ]
+        );
+        assert_eq!(
+            file_paths(&app),
+            vec![
+                PathBuf::from("pyproject.toml"),
+                package_path.join("__init__.py"),
+                PathBuf::from("tests").join("test_version.py"),
+                package_path.join("main.py"),
+            ]
+        );
+        assert_eq!(
+            file_paths(&bare),
+            vec![PathBuf::from("pyproject.toml"), PathBuf::from(".gitignore")]
+        );
+    }
+
+    #[test]
+    fn test_cli_template_files() {
+        let package_path = PathBuf::from("src").join("mock_project");
+        let new = ProjectTemplate::Cli
+            .files("mock-project", false, true)
+            .unwrap();
+        let init = ProjectTemplate::Cli
+            .files("mock-project", false, false)
+            .unwrap();
+
+        assert_eq!(
+            file_paths(&new),
+            vec![
+                PathBuf::from("pyproject.toml"),
+                package_path.join("__init__.py"),
+                PathBuf::from("tests").join("test_version.py"),
+                package_path.join("cli.py"),
+                package_path.join("__main__.py"),
+            ]
+        );
+        assert_eq!(
+            file_paths(&init),
+            vec![
+                PathBuf::from("pyproject.toml"),
+                package_path.join("cli.py"),
+                package_path.join("__main__.py"),
+            ]
+        );
+        assert!(new[0]
+            .contents
+            .contains("mock-project = \"mock_project.cli:main\""));
+    }
+
+    #[test]
+    fn test_cli_template_invalid_entry_point() {
+        assert!(matches!(
+            ProjectTemplate::Cli.files("1project", false, true),
+            Err(Error::InvalidEntryPoint(_))
+        ));
+        assert!(matches!(
+            ProjectTemplate::Cli.files("import", false, true),
+            Err(Error::InvalidEntryPoint(_))
+        ));
+        assert!(ProjectTemplate::Lib.files("import", false, true).is_ok());
+    }
+
+    #[test]
+    fn test_write_template_files_conflicts() {
+        let dir = tempdir().unwrap();
+        let config = Config {
+            workspace_root: dir.path().to_path_buf(),
+            cwd: dir.path().to_path_buf(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let files = ProjectTemplate::Bare
+            .files("mock-project", true, false)
+            .unwrap();
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "").unwrap();
+
+        assert!(matches!(
+            write_template_files(dir.path(), &files, ExistingFiles::Error, &config),
+            Err(Error::FileExists { .. })
+        ));
+        assert!(std::fs::read_to_string(dir.path().join("pyproject.toml"))
+            .unwrap()
+            .is_empty());
+
+        assert!(
+            write_template_files(dir.path(), &files, ExistingFiles::Skip, &config)
+                .unwrap()
+                .is_empty()
+        );
+
+        assert!(std::fs::read_to_string(dir.path().join("pyproject.toml"))
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            write_template_files(dir.path(), &files, ExistingFiles::Backup, &config).unwrap(),
+            [PathBuf::from("pyproject.toml")]
+        );
+
+        assert!(!std::fs::read_to_string(dir.path().join("pyproject.toml"))
+            .unwrap()
+            .is_empty());
+        assert!(
+            std::fs::read_to_string(dir.path().join("pyproject.toml.bak"))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!dir.path().join(".gitignore.bak").exists());
+        assert!(std::fs::read_to_string(dir.path().join(".gitignore"))
+            .unwrap()
+            .is_empty());
+    }
+}
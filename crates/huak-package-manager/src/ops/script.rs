@@ -0,0 +1,182 @@
+use super::{
+    add_venv_to_command,
+    cache::{script_environments_dir, ScriptEnvironmentInfo, SCRIPT_ENVIRONMENT_INFO_FILE_NAME},
+};
+use crate::{
+    dependency_iter, Config, Dependency, Environment, Error, HuakResult, InstallOptions,
+    PythonEnvironment, ScriptMetadata,
+};
+use pep440_rs::{Version, VersionSpecifiers};
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+use termcolor::Color;
+
+/// Run a Python script in an environment with the dependencies from its inline metadata.
+///
+/// Environments are cached in Huak's home directory by interpreter and requirements, so
+/// scripts with the same requirements share one. No project is needed.
+pub fn run_script(path: &Path, args: &[String], config: &Config) -> HuakResult<()> {
+    let path = config.cwd.join(path);
+    let metadata = ScriptMetadata::new(&path)?;
+    let dependencies = metadata.dependencies()?;
+    let requires_python = metadata.requires_python()?;
+    let interpreter = script_interpreter(requires_python.as_ref())?;
+    let mut requirements = dependencies
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    requirements.sort();
+
+    let dir =
+        script_environments_dir(config)?.join(script_environment_key(&interpreter, &requirements));
+    let venv = dir.join(".venv");
+
+    if !venv.exists() {
+        if let Err(e) = create_script_environment(&venv, &interpreter, &dependencies, config) {
+            config.remove_path(&dir)?;
+            return Err(e);
+        }
+
+        // Nothing was created to run the script with if this is a dry run.
+        if config.dry_run {
+            return Ok(());
+        }
+    }
+
+    record_script_environment(&dir, &path, &interpreter, &requirements, &metadata)?;
+
+    let python_env = PythonEnvironment::new(venv)?;
+    let mut cmd = Command::new(python_env.python_path());
+    add_venv_to_command(&mut cmd, &python_env)?;
+    cmd.arg(&path).args(args).current_dir(&config.cwd);
+
+    config.operation_terminal("run").run_command(&mut cmd)
+}
+
+/// Add `dependencies` to a script's inline metadata, creating the metadata block if the script
+/// doesn't have one.
+pub fn add_script_dependencies(
+    path: &Path,
+    dependencies: &[String],
+    config: &Config,
+) -> HuakResult<()> {
+    let mut metadata = ScriptMetadata::new(config.cwd.join(path))?;
+    metadata.add_dependencies(&dependency_iter(dependencies).collect::<Vec<_>>())?;
+
+    config.write_script(&metadata)?;
+
+    if config.dry_run {
+        return Ok(());
+    }
+
+    config
+        .terminal()
+        .print_custom("Updated", path.display(), Color::Green, true)
+}
+
+fn create_script_environment(
+    venv: &Path,
+    interpreter: &Path,
+    dependencies: &[Dependency],
+    config: &Config,
+) -> HuakResult<()> {
+    let mut cmd = Command::new(interpreter);
+    cmd.args(["-m", "venv"]).arg(venv);
+    config.run_effect("venv", &mut cmd)?;
+
+    if config.dry_run || dependencies.is_empty() {
+        return Ok(());
+    }
+
+    PythonEnvironment::new(venv)?.install_packages(dependencies, &InstallOptions::default(), config)
+}
+
+/// Record the script using a cached environment so it can be listed with `huak cache list`.
+fn record_script_environment(
+    dir: &Path,
+    script: &Path,
+    interpreter: &Path,
+    requirements: &[String],
+    metadata: &ScriptMetadata,
+) -> HuakResult<()> {
+    let path = dir.join(SCRIPT_ENVIRONMENT_INFO_FILE_NAME);
+    let mut info = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|it| serde_json::from_str::<ScriptEnvironmentInfo>(&it).ok())
+        .unwrap_or_default();
+    let script = dunce::canonicalize(script).unwrap_or(script.to_path_buf());
+
+    if info.scripts.contains(&script) && info.python == interpreter {
+        return Ok(());
+    }
+
+    if !info.scripts.contains(&script) {
+        info.scripts.push(script);
+    }
+    info.python = interpreter.to_path_buf();
+    info.requires_python = metadata.requires_python()?.map(|it| it.to_string());
+    info.dependencies = requirements.to_vec();
+
+    std::fs::write(path, serde_json::to_string_pretty(&info)?)?;
+
+    Ok(())
+}
+
+/// Get the newest Python interpreter satisfying a script's `requires-python`.
+fn script_interpreter(requires_python: Option<&VersionSpecifiers>) -> HuakResult<PathBuf> {
+    let env = Environment::new();
+
+    env.interpreters()
+        .interpreters()
+        .iter()
+        .filter(|it| {
+            requires_python.map_or(true, |specifiers| {
+                Version::from_str(&it.version().to_string())
+                    .is_ok_and(|version| specifiers.contains(&version))
+            })
+        })
+        .max()
+        .map(|it| it.path().clone())
+        .ok_or(Error::PythonNotFound)
+}
+
+/// Get the name of the cached environment for an interpreter and sorted requirements.
+fn script_environment_key(interpreter: &Path, requirements: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(interpreter.display().to_string());
+    for it in requirements {
+        hasher.update(b"\n");
+        hasher.update(it);
+    }
+
+    hex::encode(hasher.finalize())[..16].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_environment_key() {
+        let interpreter = Path::new("/usr/bin/python3.11");
+        let key = script_environment_key(interpreter, &["requests<3".to_string()]);
+
+        assert_eq!(key.len(), 16);
+        assert_eq!(
+            key,
+            script_environment_key(interpreter, &["requests<3".to_string()])
+        );
+        assert_ne!(key, script_environment_key(interpreter, &[]));
+        assert_ne!(
+            key,
+            script_environment_key(
+                Path::new("/usr/bin/python3.12"),
+                &["requests<3".to_string()]
+            )
+        );
+    }
+}
@@ -0,0 +1,768 @@
+use super::{add_venv_to_command, scaffold::backup_file};
+use crate::{Config, Dependency, Environment, Error, HuakResult};
+use serde_json::{Map, Value};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+use termcolor::Color;
+use toml_edit::{value, Array, Document, InlineTable, Item, Table};
+
+/// Print the `setup()` call's keyword arguments in a setup.py as JSON without running it.
+///
+/// Arguments that are literals (or names assigned a literal at the top of the module) are read
+/// with `ast.literal_eval`. Anything computed by code is reported as dynamic instead.
+const READ_SETUP_PY_SCRIPT: &str = r#"
+import ast, json, sys
+
+tree = ast.parse(open(sys.argv[1], encoding="utf-8").read())
+names = {}
+for node in tree.body:
+    if isinstance(node, ast.Assign) and len(node.targets) == 1 and isinstance(node.targets[0], ast.Name):
+        try:
+            names[node.targets[0].id] = ast.literal_eval(node.value)
+        except Exception:
+            pass
+
+calls = [
+    node for node in ast.walk(tree)
+    if isinstance(node, ast.Call)
+    and getattr(node.func, "id", getattr(node.func, "attr", None)) == "setup"
+]
+values, dynamic = {}, {}
+for keyword in calls[0].keywords if calls else []:
+    if keyword.arg is None:
+        dynamic["**"] = "keyword arguments are unpacked from an expression"
+    elif isinstance(keyword.value, ast.Name) and keyword.value.id in names:
+        values[keyword.arg] = names[keyword.value.id]
+    else:
+        try:
+            values[keyword.arg] = ast.literal_eval(keyword.value)
+        except Exception:
+            dynamic[keyword.arg] = "its value is computed by code"
+
+print(json.dumps(
+    {"found": bool(calls), "values": values, "dynamic": dynamic},
+    default=lambda it: sorted(it) if isinstance(it, (set, frozenset)) else str(it),
+))
+"#;
+
+/// The `setup()` arguments converted to `[project]` fields.
+const CONVERTED_KEYS: [&str; 10] = [
+    "name",
+    "version",
+    "description",
+    "author",
+    "author_email",
+    "python_requires",
+    "classifiers",
+    "install_requires",
+    "extras_require",
+    "entry_points",
+];
+
+/// The `[metadata]` keys in setup.cfg that hold lists.
+const CFG_LIST_KEYS: [&str; 3] = ["classifiers", "keywords", "platforms"];
+
+pub struct SetuptoolsConversionOptions {
+    /// Replace an existing `[project]` table (it's backed up first).
+    pub force: bool,
+    /// Remove setup.py and setup.cfg once the converted project installs.
+    pub remove_legacy: bool,
+}
+
+/// A setting from setup.py or setup.cfg that wasn't converted, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Skipped {
+    key: String,
+    reason: String,
+}
+
+impl Skipped {
+    fn new(key: impl Into<String>, reason: impl Into<String>) -> Skipped {
+        Skipped {
+            key: key.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// The `setup()` arguments read from setup.cfg and setup.py.
+#[derive(Debug, Default, PartialEq)]
+struct LegacySetup {
+    values: BTreeMap<String, Value>,
+    skipped: Vec<Skipped>,
+}
+
+impl LegacySetup {
+    /// Add the `other` setup's arguments, overriding any already set like setup.py's
+    /// arguments override setup.cfg's.
+    fn extend(&mut self, other: LegacySetup) {
+        self.values.extend(other.values);
+        self.skipped.extend(other.skipped);
+    }
+}
+
+/// Initialize the project's pyproject.toml from the metadata in its setup.cfg and setup.py.
+///
+/// setup.cfg is parsed and setup.py is read without running it, so metadata computed by code
+/// isn't converted. What isn't converted is listed with the reason after the conversion.
+/// The legacy files are left in place unless `options.remove_legacy` is set, in which case
+/// they're removed once the project installs from the new pyproject.toml.
+pub fn init_from_setup_py(
+    config: &Config,
+    options: &SetuptoolsConversionOptions,
+) -> HuakResult<()> {
+    let root = &config.workspace_root;
+    let legacy = legacy_setup_files(root);
+    if legacy.is_empty() {
+        return Err(Error::LegacySetupNotFound { path: root.clone() });
+    }
+
+    let mut setup = LegacySetup::default();
+    for path in &legacy {
+        if path.ends_with("setup.cfg") {
+            setup.extend(parse_setup_cfg(&std::fs::read_to_string(path)?));
+        } else {
+            setup.extend(read_setup_py(path)?);
+        }
+    }
+
+    let manifest_path = root.join("pyproject.toml");
+    let mut doc = if manifest_path.exists() {
+        std::fs::read_to_string(&manifest_path)?.parse::<Document>()?
+    } else {
+        Document::new()
+    };
+    if doc.contains_key("project") {
+        if !options.force {
+            return Err(Error::ManifestFileFound);
+        }
+        backup_file(&manifest_path, config)?;
+    }
+
+    let (project, dynamic, mut skipped) = convert(&setup.values)?;
+    setup.skipped.append(&mut skipped);
+
+    if !doc.contains_key("build-system") {
+        let mut build_system = Table::new();
+        build_system.insert("requires", value(Array::from_iter(["setuptools>=61.0"])));
+        build_system.insert("build-backend", value("setuptools.build_meta"));
+        doc.insert("build-system", Item::Table(build_system));
+    }
+    doc.insert("project", Item::Table(project));
+    if let Some(dynamic) = dynamic {
+        let mut setuptools = Table::new();
+        setuptools.set_implicit(true);
+        setuptools.insert("dynamic", Item::Table(dynamic));
+        let mut tool = Table::new();
+        tool.set_implicit(true);
+        tool.insert("setuptools", Item::Table(setuptools));
+        doc.insert("tool", Item::Table(tool));
+    }
+
+    config.write_file(&manifest_path, &doc.to_string())?;
+
+    let mut terminal = config.terminal();
+    if !config.dry_run {
+        terminal.print_custom(
+            "Converted",
+            format!(
+                "{} to {}",
+                legacy
+                    .iter()
+                    .filter_map(|it| it.file_name())
+                    .map(|it| it.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" and "),
+                manifest_path.display()
+            ),
+            Color::Green,
+            true,
+        )?;
+    }
+    for it in &setup.skipped {
+        terminal.print_custom(
+            "Skipped",
+            format!("{}: {}", it.key, it.reason),
+            Color::Yellow,
+            true,
+        )?;
+    }
+
+    if !options.remove_legacy {
+        return Ok(());
+    }
+
+    // Settings that weren't converted would be lost with the files they're in.
+    if !setup.skipped.is_empty() {
+        return terminal.print_warning(format!(
+            "kept the legacy files since {} setting(s) weren't converted (convert them by \
+            hand and remove the files)",
+            setup.skipped.len()
+        ));
+    }
+
+    install_converted_project(root, config)?;
+    for it in legacy {
+        config.remove_path(it)?;
+    }
+
+    Ok(())
+}
+
+/// Install the project at `root` to its environment to check it builds from its converted
+/// pyproject.toml.
+fn install_converted_project(root: &Path, config: &Config) -> HuakResult<()> {
+    let python_env = config.workspace().resolve_python_environment()?;
+    let mut cmd = Command::new(python_env.python_path());
+    cmd.args(["-m", "pip", "install"])
+        .arg(root)
+        .current_dir(root);
+    add_venv_to_command(&mut cmd, &python_env)?;
+
+    config.run_effect("install", &mut cmd)
+}
+
+/// Parse the `setup()` arguments a setup.cfg declares.
+fn parse_setup_cfg(contents: &str) -> LegacySetup {
+    let mut setup = LegacySetup::default();
+
+    for (section, entries) in parse_ini(contents) {
+        match section.as_str() {
+            "metadata" | "options" => {
+                for (key, it) in entries {
+                    let key = key.replace('-', "_");
+                    let it = if CFG_LIST_KEYS.contains(&key.as_str()) {
+                        Value::from(cfg_list(&it, true))
+                    } else if key == "install_requires" {
+                        Value::from(cfg_list(&it, false))
+                    } else {
+                        Value::from(it)
+                    };
+                    setup.values.insert(key, it);
+                }
+            }
+            "options.extras_require" | "options.entry_points" => {
+                let key = section.trim_start_matches("options.").to_string();
+                let groups = entries
+                    .into_iter()
+                    .map(|(group, it)| (group, Value::from(cfg_list(&it, false))))
+                    .collect::<Map<_, _>>();
+                setup.values.insert(key, Value::Object(groups));
+            }
+            it if it.starts_with("options.") => setup.skipped.push(Skipped::new(
+                format!("[{it}]"),
+                "setuptools configuration isn't converted (move it to [tool.setuptools])",
+            )),
+            it => setup.skipped.push(Skipped::new(
+                format!("[{it}]"),
+                "another tool's configuration isn't converted",
+            )),
+        }
+    }
+
+    setup
+}
+
+/// Parse an INI file (like `configparser` does) into its sections' entries. Values continue on
+/// indented lines.
+fn parse_ini(contents: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut sections = Vec::<(String, Vec<(String, String)>)>::new();
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = trimmed
+            .strip_prefix('[')
+            .and_then(|it| it.strip_suffix(']'))
+        {
+            sections.push((name.trim().to_string(), Vec::new()));
+            continue;
+        }
+        let Some((_, entries)) = sections.last_mut() else {
+            continue;
+        };
+
+        let is_continuation = line.starts_with(char::is_whitespace) || trimmed.is_empty();
+        match entries.last_mut() {
+            Some((_, it)) if is_continuation => {
+                it.push('\n');
+                it.push_str(trimmed);
+            }
+            _ => {
+                let Some(split) = trimmed.find(['=', ':']) else {
+                    continue;
+                };
+                entries.push((
+                    trimmed[..split].trim().to_string(),
+                    trimmed[split + 1..].trim().to_string(),
+                ));
+            }
+        }
+    }
+
+    sections
+}
+
+/// Split a setup.cfg list value into its lines, or its comma-separated items if it's one line
+/// and `commas` is set.
+fn cfg_list(value: &str, commas: bool) -> Vec<String> {
+    let separator = if commas && !value.trim().contains('\n') {
+        ','
+    } else {
+        '\n'
+    };
+
+    value
+        .split(separator)
+        .map(str::trim)
+        .filter(|it| !it.is_empty() && !it.starts_with('#'))
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Read the `setup()` arguments from the setup.py at `path` without running it.
+fn read_setup_py(path: &Path) -> HuakResult<LegacySetup> {
+    let env = Environment::new();
+    let python = env.python_paths().next().ok_or(Error::PythonNotFound)?;
+    let output = Command::new(python)
+        .args(["-c", READ_SETUP_PY_SCRIPT])
+        .arg(path)
+        .output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::InvalidSetupPy(
+            stderr
+                .lines()
+                .map(str::trim)
+                .rfind(|it| !it.is_empty())
+                .unwrap_or("it couldn't be parsed")
+                .to_string(),
+        ));
+    }
+
+    let read = serde_json::from_slice::<Value>(&output.stdout)?;
+    if read["found"] != Value::Bool(true) {
+        return Err(Error::InvalidSetupPy("it doesn't call setup()".to_string()));
+    }
+
+    Ok(LegacySetup {
+        values: read["values"]
+            .as_object()
+            .map(|it| it.clone().into_iter().collect())
+            .unwrap_or_default(),
+        skipped: read["dynamic"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(key, reason)| Skipped::new(key.clone(), reason.as_str().unwrap_or_default()))
+            .collect(),
+    })
+}
+
+/// Convert `setup()` arguments to a `[project]` table and, for versions read from an
+/// attribute, a `[tool.setuptools.dynamic]` table. The arguments that weren't converted are
+/// returned with the reason.
+fn convert(values: &BTreeMap<String, Value>) -> HuakResult<(Table, Option<Table>, Vec<Skipped>)> {
+    let mut project = Table::new();
+    let mut dynamic = None;
+    let mut skipped = Vec::new();
+
+    let Some(name) = values.get("name").and_then(Value::as_str) else {
+        return Err(Error::InvalidSetupPy(
+            "the project's name isn't a literal".to_string(),
+        ));
+    };
+    project.insert("name", value(name));
+
+    // Convert the arguments in the order their fields are usually written in.
+    let mut arguments = values.iter().collect::<Vec<_>>();
+    arguments.sort_by_key(|(key, _)| {
+        CONVERTED_KEYS
+            .iter()
+            .position(|it| it == key)
+            .unwrap_or(CONVERTED_KEYS.len())
+    });
+
+    for (key, it) in arguments {
+        match (key.as_str(), it) {
+            ("name" | "author_email", _) => (),
+            ("version", Value::String(version)) => {
+                if let Some(attr) = version.strip_prefix("attr:") {
+                    project.insert("dynamic", value(Array::from_iter(["version"])));
+                    let mut table = Table::new();
+                    let mut version = InlineTable::new();
+                    version.insert("attr", attr.trim().into());
+                    table.insert("version", value(version));
+                    dynamic = Some(table);
+                } else {
+                    project.insert("version", value(version.as_str()));
+                }
+            }
+            ("description", Value::String(description)) => {
+                project.insert("description", value(description.as_str()));
+            }
+            ("python_requires", Value::String(specifiers)) => {
+                project.insert("requires-python", value(specifiers.as_str()));
+            }
+            ("author", Value::String(author)) => {
+                project.insert(
+                    "authors",
+                    value(authors(
+                        author,
+                        values.get("author_email").and_then(Value::as_str),
+                    )),
+                );
+            }
+            ("classifiers", Value::Array(classifiers)) => {
+                project.insert("classifiers", value(strings(classifiers)));
+            }
+            ("install_requires", it) => {
+                let requirements = requirements(it, key, &mut skipped);
+                project.insert("dependencies", value(requirements));
+            }
+            ("extras_require", Value::Object(extras)) => {
+                let mut groups = Table::new();
+                for (extra, it) in extras {
+                    if extra.contains(':') {
+                        skipped.push(Skipped::new(
+                            format!("extras_require.{extra}"),
+                            "markers in extra names aren't supported (add them to each \
+                            requirement)",
+                        ));
+                        continue;
+                    }
+                    let key = format!("extras_require.{extra}");
+                    groups.insert(extra, value(requirements(it, &key, &mut skipped)));
+                }
+                project.insert("optional-dependencies", Item::Table(groups));
+            }
+            ("entry_points", it) => {
+                convert_entry_points(it, &mut project, &mut skipped);
+            }
+            _ if is_directive(it) => skipped.push(Skipped::new(
+                key.as_str(),
+                format!(
+                    "it's read from `{}` when building",
+                    it.as_str().unwrap_or_default()
+                ),
+            )),
+            _ => skipped.push(Skipped::new(
+                key.as_str(),
+                "it has no [project] field (configure it in [tool.setuptools] if it's still \
+                needed)",
+            )),
+        }
+    }
+
+    // An email is still an author without a name to pair it with.
+    if !values.contains_key("author") {
+        if let Some(email) = values.get("author_email").and_then(Value::as_str) {
+            project.insert("authors", value(authors("", Some(email))));
+        }
+    }
+
+    Ok((project, dynamic, skipped))
+}
+
+/// Check if a setup.cfg value is read from somewhere else (like `file: README.md`).
+fn is_directive(value: &Value) -> bool {
+    value
+        .as_str()
+        .is_some_and(|it| it.starts_with("file:") || it.starts_with("attr:"))
+}
+
+/// Pair the comma-separated `names` with the `emails` as PEP 621 authors.
+fn authors(names: &str, emails: Option<&str>) -> Array {
+    let names = names
+        .split(',')
+        .map(str::trim)
+        .filter(|it| !it.is_empty())
+        .collect::<Vec<_>>();
+    let emails = emails
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|it| !it.is_empty())
+        .collect::<Vec<_>>();
+
+    (0..names.len().max(emails.len()))
+        .map(|i| {
+            let mut author = InlineTable::new();
+            if let Some(it) = names.get(i) {
+                author.insert("name", (*it).into());
+            }
+            if let Some(it) = emails.get(i) {
+                author.insert("email", (*it).into());
+            }
+            author
+        })
+        .collect()
+}
+
+/// Get the strings in a JSON array.
+fn strings(values: &[Value]) -> Array {
+    values.iter().filter_map(Value::as_str).collect()
+}
+
+/// Get the valid PEP 508 requirements from a list (or newline-separated string) of them. Any
+/// that don't parse are skipped under `key`.
+fn requirements(value: &Value, key: &str, skipped: &mut Vec<Skipped>) -> Array {
+    let values = match value {
+        Value::String(it) => cfg_list(it, false),
+        Value::Array(it) => it
+            .iter()
+            .filter_map(Value::as_str)
+            .map(ToString::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    values
+        .into_iter()
+        .filter(|it| {
+            let valid = Dependency::from_str(it).is_ok();
+            if !valid {
+                skipped.push(Skipped::new(
+                    key,
+                    format!("`{it}` isn't a valid requirement"),
+                ));
+            }
+            valid
+        })
+        .collect()
+}
+
+/// Convert `entry_points` (a table of groups, or an INI string of them) to `[project.scripts]`,
+/// `[project.gui-scripts]`, and `[project.entry-points]`.
+fn convert_entry_points(entry_points: &Value, project: &mut Table, skipped: &mut Vec<Skipped>) {
+    let groups = match entry_points {
+        Value::Object(it) => it
+            .iter()
+            .map(|(group, it)| {
+                let entries = match it {
+                    Value::String(it) => cfg_list(it, false),
+                    Value::Array(it) => it
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(ToString::to_string)
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                (group.clone(), entries)
+            })
+            .collect::<Vec<_>>(),
+        Value::String(it) => parse_ini(it)
+            .into_iter()
+            .map(|(group, entries)| {
+                let entries = entries
+                    .into_iter()
+                    .map(|(name, target)| format!("{name} = {target}"))
+                    .collect();
+                (group, entries)
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let mut other = Table::new();
+
+    for (group, entries) in groups {
+        let mut table = Table::new();
+        for entry in entries {
+            let Some((name, target)) = entry.split_once('=') else {
+                skipped.push(Skipped::new(
+                    format!("entry_points.{group}"),
+                    format!("`{entry}` isn't a `name = module:attr` entry point"),
+                ));
+                continue;
+            };
+            table.insert(name.trim(), value(target.trim()));
+        }
+
+        match group.as_str() {
+            "console_scripts" => {
+                project.insert("scripts", Item::Table(table));
+            }
+            "gui_scripts" => {
+                project.insert("gui-scripts", Item::Table(table));
+            }
+            _ => {
+                other.insert(&group, Item::Table(table));
+            }
+        }
+    }
+
+    if !other.is_empty() {
+        other.set_implicit(true);
+        project.insert("entry-points", Item::Table(other));
+    }
+}
+
+/// Get the legacy setuptools files in `root`, in the order their arguments are read.
+fn legacy_setup_files(root: &Path) -> Vec<PathBuf> {
+    ["setup.cfg", "setup.py"]
+        .into_iter()
+        .map(|it| root.join(it))
+        .filter(|it| it.exists())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TerminalOptions, Verbosity};
+    use tempfile::tempdir;
+
+    const SETUP_CFG: &str = r"[metadata]
+name = mock-project
+version = attr: mock_project.__version__
+description = A mock project.
+author = Jane Doe, John Doe
+author_email = jane@example.com, john@example.com
+long-description = file: README.md
+classifiers =
+    Programming Language :: Python :: 3
+    License :: OSI Approved :: MIT License
+
+[options]
+packages = find:
+python_requires = >=3.8
+install_requires =
+    click>=8
+    requests[socks] ~= 2.31
+
+[options.extras_require]
+test =
+    pytest
+    coverage
+
+[options.entry_points]
+console_scripts =
+    mock = mock_project.cli:main
+
+[options.packages.find]
+where = src
+
+[flake8]
+max-line-length = 88
+";
+
+    const SETUP_PY: &str = r#"from setuptools import setup
+
+REQUIRES = ["click>=8"]
+
+setup(
+    name="mock-project",
+    version="0.1.0",
+    install_requires=REQUIRES,
+    extras_require={"dev": ["pytest"]},
+    entry_points={"console_scripts": ["mock = mock_project.cli:main"]},
+    long_description=open("README.md").read(),
+)
+"#;
+
+    #[test]
+    fn test_parse_setup_cfg() {
+        let setup = parse_setup_cfg(SETUP_CFG);
+
+        assert_eq!(setup.values["name"], "mock-project");
+        assert_eq!(
+            setup.values["classifiers"],
+            serde_json::json!([
+                "Programming Language :: Python :: 3",
+                "License :: OSI Approved :: MIT License"
+            ])
+        );
+        assert_eq!(
+            setup.values["install_requires"],
+            serde_json::json!(["click>=8", "requests[socks] ~= 2.31"])
+        );
+        assert_eq!(
+            setup.values["entry_points"],
+            serde_json::json!({"console_scripts": ["mock = mock_project.cli:main"]})
+        );
+        assert_eq!(
+            setup
+                .skipped
+                .iter()
+                .map(|it| it.key.as_str())
+                .collect::<Vec<_>>(),
+            ["[options.packages.find]", "[flake8]"]
+        );
+    }
+
+    #[test]
+    fn test_convert() {
+        let setup = parse_setup_cfg(SETUP_CFG);
+        let (project, dynamic, skipped) = convert(&setup.values).unwrap();
+        let mut doc = Document::new();
+        doc.insert("project", Item::Table(project));
+
+        assert_eq!(
+            doc.to_string(),
+            r#"[project]
+name = "mock-project"
+dynamic = ["version"]
+description = "A mock project."
+authors = [{ name = "Jane Doe", email = "jane@example.com" }, { name = "John Doe", email = "john@example.com" }]
+requires-python = ">=3.8"
+classifiers = ["Programming Language :: Python :: 3", "License :: OSI Approved :: MIT License"]
+dependencies = ["click>=8", "requests[socks] ~= 2.31"]
+
+[project.optional-dependencies]
+test = ["pytest", "coverage"]
+
+[project.scripts]
+mock = "mock_project.cli:main"
+"#
+        );
+        assert_eq!(
+            dynamic.unwrap().to_string(),
+            "version = { attr = \"mock_project.__version__\" }\n"
+        );
+        assert_eq!(
+            skipped.iter().map(|it| it.key.as_str()).collect::<Vec<_>>(),
+            ["long_description", "packages"]
+        );
+    }
+
+    #[test]
+    fn test_init_from_setup_py() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::write(root.join("setup.py"), SETUP_PY).unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root.clone(),
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let options = SetuptoolsConversionOptions {
+            force: false,
+            remove_legacy: true,
+        };
+
+        init_from_setup_py(&config, &options).unwrap();
+
+        let manifest = config.workspace().current_local_manifest().unwrap();
+        let data = manifest.manifest_data();
+
+        assert_eq!(data.project_name().as_deref(), Some("mock-project"));
+        assert_eq!(data.project_version().as_deref(), Some("0.1.0"));
+        // The long description is computed by code, so the legacy files are kept.
+        assert!(root.join("setup.py").exists());
+        assert!(matches!(
+            init_from_setup_py(&config, &options),
+            Err(Error::ManifestFileFound)
+        ));
+    }
+}
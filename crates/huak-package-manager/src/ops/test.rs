@@ -1,24 +1,163 @@
-use super::add_venv_to_command;
-use crate::{Config, Dependency, HuakResult, InstallOptions};
-use std::{process::Command, str::FromStr};
+use super::{
+    add_venv_to_command, format_elapsed, install_group_dependencies, install_locked_tool,
+    operation_env, option_value, tool_values,
+};
+use crate::{
+    sys::Terminal, Config, Dependency, Error, HuakResult, InstallOptions, PythonEnvironment,
+    SubprocessError, Verbosity,
+};
+use huak_pyproject_toml::canonical_name;
+use regex::Regex;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    fmt::Display,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+use termcolor::Color;
 
+/// The file pytest-split's `--store-durations` records test durations to.
+const TEST_DURATIONS_FILE_NAME: &str = ".test_durations";
+
+/// The pytest plugin `--shuffle` randomizes the test order with.
+const PYTEST_RANDOMLY: &str = "pytest-randomly";
+
+/// pytest's exit code when a run was interrupted, like with Ctrl-C.
+const PYTEST_INTERRUPTED: i32 = 2;
+
+#[allow(clippy::struct_excessive_bools)]
 pub struct TestOptions {
     /// A values vector of test options typically used for passing on arguments.
     pub values: Option<Vec<String>>,
     pub install_options: InstallOptions,
+    /// Install the project's dependencies from these optional dependency groups before testing.
+    pub groups: Vec<String>,
+    /// Don't install the project's required dependencies along with `groups`.
+    pub no_default_deps: bool,
+    /// Only run one shard of the collected tests.
+    pub shard: Option<TestShard>,
+    /// How the collected tests are partitioned across shards.
+    pub shard_by: ShardBy,
+    /// Run the tests in a random order with pytest-randomly.
+    pub shuffle: bool,
+    /// The seed to shuffle the tests with. A random seed is used by default.
+    pub seed: Option<u64>,
+    /// Rerun the tests until they fail.
+    pub until_failure: bool,
+    /// The most runs to make with `until_failure`. Runs are unlimited by default.
+    pub max_runs: Option<usize>,
+    /// Directories to discover tests in, relative to the current directory. Defaults to the
+    /// workspace's `[tool.huak.test] paths`.
+    pub paths: Vec<PathBuf>,
+    /// Environment variables for the tool's subprocesses, merged over `[tool.huak.test] env`.
+    pub env: HashMap<String, String>,
+    /// Show the tests' output as it's printed instead of capturing it (pytest's `-s`).
+    pub no_capture: bool,
+    /// Show log records at this level or above as they're emitted (pytest's `--log-cli-level`).
+    pub log_cli_level: Option<String>,
+}
+
+/// One of `total` disjoint subsets of a test suite, like `2/4`. Shards are numbered from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TestShard {
+    pub index: usize,
+    pub total: usize,
+}
+
+impl FromStr for TestShard {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidTestShard(format!("{s} (expected INDEX/TOTAL like 2/4)"));
+        let (index, total) = s.split_once('/').ok_or_else(invalid)?;
+        let index = index.trim().parse::<usize>().map_err(|_| invalid())?;
+        let total = total.trim().parse::<usize>().map_err(|_| invalid())?;
+
+        if index == 0 || index > total {
+            return Err(invalid());
+        }
+
+        Ok(TestShard { index, total })
+    }
+}
+
+impl Display for TestShard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.total)
+    }
+}
+
+/// How tests are partitioned across shards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShardBy {
+    /// Partition by a stable hash of each test's node ID.
+    #[default]
+    Hash,
+    /// Balance the durations recorded by a previous `--store-durations` run (pytest-split).
+    Duration,
+}
+
+impl FromStr for ShardBy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hash" => Ok(ShardBy::Hash),
+            "duration" => Ok(ShardBy::Duration),
+            _ => Err(Error::InvalidTestShard(format!(
+                "{s} (expected hash or duration)"
+            ))),
+        }
+    }
+}
+
+impl Display for ShardBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShardBy::Hash => write!(f, "hash"),
+            ShardBy::Duration => write!(f, "duration"),
+        }
+    }
 }
 
 pub fn test_project(config: &Config, options: &TestOptions) -> HuakResult<()> {
     let workspace = config.workspace();
-    let mut manifest = workspace.current_local_manifest()?;
     let python_env = workspace.resolve_python_environment()?;
 
-    // Install `pytest` if it isn't already installed.
-    let test_dep = Dependency::from_str("pytest")?;
-    if !python_env.contains_module(test_dep.name())? {
-        python_env.install_packages(&[&test_dep], &options.install_options, config)?;
+    // Shuffling needs pytest-randomly, which is left to the project to depend on.
+    if options.shuffle
+        && !python_env
+            .installed_versions()?
+            .contains_key(PYTEST_RANDOMLY)
+    {
+        return Err(Error::PythonModuleNotFound {
+            module: PYTEST_RANDOMLY.to_string(),
+            hint: Some(format!(
+                "needed for --shuffle; add it with `huak add {PYTEST_RANDOMLY} --group dev`"
+            )),
+        });
+    }
+
+    if !options.groups.is_empty() {
+        install_group_dependencies(
+            &python_env,
+            &options.groups,
+            options.no_default_deps,
+            &options.install_options,
+            config,
+        )?;
     }
 
+    // Install `pytest` at its pinned version.
+    let test_dep = Dependency::from_str("pytest")?;
+    install_locked_tool(&test_dep, &python_env, &options.install_options, config)?;
+    let mut manifest = workspace.current_local_manifest()?;
+
     // Add the installed `pytest` package to the manifest file if it isn't already there.
     if !manifest
         .manifest_data()
@@ -27,7 +166,7 @@ pub fn test_project(config: &Config, options: &TestOptions) -> HuakResult<()> {
         for pkg in python_env
             .installed_packages()?
             .iter()
-            .filter(|pkg| pkg.name() == test_dep.name())
+            .filter(|pkg| canonical_name(pkg.name()) == canonical_name(test_dep.name()))
         {
             manifest
                 .manifest_data_mut()
@@ -36,33 +175,574 @@ pub fn test_project(config: &Config, options: &TestOptions) -> HuakResult<()> {
     }
 
     manifest.manifest_data_mut().formatted();
-    manifest.write_file()?;
+    config.write_manifest(&manifest)?;
 
     // Run `pytest` with the package directory added to the command's `PYTHONPATH`.
-    let mut cmd = Command::new(python_env.python_path());
-    add_venv_to_command(&mut cmd, &python_env)?;
     let python_path = if workspace.root().join("src").exists() {
         workspace.root().join("src")
     } else {
         workspace.root().clone()
     };
-    let mut args = vec!["-m", "pytest"];
-    if let Some(v) = options.values.as_ref() {
-        args.extend(v.iter().map(String::as_str));
+    let paths = test_paths(options, config)?;
+    let env = operation_env("test", &options.env, config)?;
+    let mut args = vec!["-m".to_string(), "pytest".to_string()];
+    if config.terminal_options.verbosity == Verbosity::Quiet {
+        args.push("-q".to_string());
+    }
+    args.extend(output_args(options));
+    // pytest runs from the workspace root, where its configuration is found.
+    let values = tool_values(
+        options.values.as_deref().unwrap_or_default(),
+        workspace.root(),
+        config,
+    );
+    args.extend(values.iter().cloned());
+
+    let mut path_args = Vec::new();
+    if let Some(shard) = options.shard {
+        let tests = collect_tests(
+            &python_env,
+            &python_path,
+            &paths,
+            &env,
+            &values,
+            workspace.root(),
+        )?;
+        let durations = match options.shard_by {
+            ShardBy::Hash => None,
+            ShardBy::Duration => test_durations(workspace.root(), config)?,
+        };
+        let selected = shard_tests(&tests, shard, durations.as_ref());
+        let mut terminal = config.terminal();
+
+        if selected.is_empty() {
+            return terminal.print_warning(format!("shard {shard} has no tests to run"));
+        }
+
+        terminal.print_custom(
+            "Sharding",
+            format!(
+                "running {} of {} tests (shard {shard} by {})",
+                selected.len(),
+                tests.len(),
+                options.shard_by
+            ),
+            Color::Green,
+            true,
+        )?;
+
+        // pytest-json-report includes pytest-metadata's values in its report, so shards'
+        // reports can be stitched back together.
+        if args.iter().any(|it| it.starts_with("--json-report")) {
+            args.extend(
+                [
+                    "--metadata",
+                    "shard",
+                    &shard.to_string(),
+                    "--metadata",
+                    "shard_by",
+                    &options.shard_by.to_string(),
+                ]
+                .map(String::from),
+            );
+        }
+
+        args.extend(selected);
+    } else {
+        // Paths are passed as they are so ones that aren't UTF-8 aren't mangled.
+        path_args = paths;
+    }
+
+    // Read the counts for the summary from pytest's XML report, reusing one that's requested.
+    let report_dir = tempfile::tempdir()?;
+    let mut report_arg = None;
+    let report = if let Some(it) = option_value(&args, &["--junitxml", "--junit-xml"]) {
+        workspace.root().join(it)
+    } else {
+        let it = report_dir.path().join("report.xml");
+        let mut arg = OsString::from("--junitxml=");
+        arg.push(&it);
+        report_arg = Some(arg);
+        it
+    };
+
+    let start = Instant::now();
+    let mut terminal = config.operation_terminal("test");
+    let runs = run_tests(options, &mut terminal, |seed_args| {
+        let mut cmd = Command::new(python_env.python_path());
+        add_venv_to_command(&mut cmd, &python_env)?;
+        cmd.args(&args)
+            .args(seed_args)
+            .args(&report_arg)
+            .args(&path_args)
+            .env("PYTHONPATH", &python_path)
+            .envs(&env)
+            .current_dir(workspace.root());
+        Ok(cmd)
+    })?;
+
+    let elapsed = format_elapsed(start.elapsed());
+    let summary = if options.until_failure {
+        format!("{runs} runs passed in {elapsed}")
+    } else if let Some(counts) = std::fs::read_to_string(report)
+        .ok()
+        .and_then(|it| TestCounts::from_junit_xml(&it))
+    {
+        format!("{counts} in {elapsed}")
+    } else {
+        format!("tests passed in {elapsed}")
+    };
+
+    terminal.print_summary(summary)
+}
+
+/// Run the tests with the pytest `command` made for each run's seed arguments, returning how many
+/// runs passed.
+///
+/// With `until_failure` the tests are rerun until a run fails, `max_runs` pass, or a run is
+/// interrupted. An interrupted run stops the hunt without being reported as a failure.
+fn run_tests<F>(options: &TestOptions, terminal: &mut Terminal, mut command: F) -> HuakResult<usize>
+where
+    F: FnMut(&[String]) -> HuakResult<Command>,
+{
+    let runs = if options.until_failure {
+        options.max_runs.unwrap_or(usize::MAX)
+    } else {
+        1
+    };
+
+    for run in 1..=runs {
+        // Each run is shuffled with a new seed unless one is given, to shake out more flakes.
+        let seed = options
+            .shuffle
+            .then(|| options.seed.unwrap_or_else(random_seed));
+        let mut seed_args = Vec::new();
+        if let Some(seed) = seed {
+            seed_args.extend(["-p".to_string(), "randomly".to_string()]);
+            seed_args.push(format!("--randomly-seed={seed}"));
+            terminal.print_custom(
+                "Shuffling",
+                format!("tests with seed {seed}"),
+                Color::Cyan,
+                true,
+            )?;
+        }
+
+        let res = terminal.run_command(&mut command(&seed_args)?);
+        let interrupted = matches!(
+            &res,
+            Err(Error::SubprocessFailure(e))
+                if e.code() == Some(PYTEST_INTERRUPTED) || e.interrupted()
+        );
+
+        if res.is_err() && options.until_failure {
+            let (title, color) = if interrupted {
+                ("Interrupted", Color::Yellow)
+            } else {
+                ("Failed", Color::Red)
+            };
+            terminal.print_custom(
+                title,
+                format!("on run {run} after {} passing runs", run - 1),
+                color,
+                true,
+            )?;
+        }
+        if let (Err(_), Some(seed), false) = (&res, seed, interrupted) {
+            terminal.print_custom(
+                "Hint",
+                format!("rerun the tests in the same order with `huak test --shuffle {seed}`"),
+                Color::Cyan,
+                true,
+            )?;
+        }
+        res?;
+
+        // Each run is reported as it passes so an interrupted hunt still shows how far it got.
+        if options.until_failure {
+            let of = options
+                .max_runs
+                .map(|it| format!(" of {it}"))
+                .unwrap_or_default();
+            terminal.print_custom("Passed", format!("run {run}{of}"), Color::Green, true)?;
+        }
+    }
+
+    Ok(runs)
+}
+
+/// Get a seed for pytest-randomly, which takes seeds up to 2^32 - 1.
+fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |it| it.subsec_nanos());
+
+    u64::from(nanos ^ std::process::id().rotate_left(16))
+}
+
+/// The outcomes of a test run.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct TestCounts {
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+}
+
+impl TestCounts {
+    /// Read the counts from the `<testsuite>` elements of an XML report, like the ones written
+    /// by pytest's `--junitxml`.
+    pub(crate) fn from_junit_xml(xml: &str) -> Option<TestCounts> {
+        let suite = Regex::new(r"<testsuite\s[^>]*>").ok()?;
+        let attribute = Regex::new(r#"\b(tests|failures|errors|skipped)="(\d+)""#).ok()?;
+        let mut counts = HashMap::<&str, usize>::new();
+
+        for it in suite.find_iter(xml) {
+            for captures in attribute.captures_iter(it.as_str()) {
+                let (_, [key, value]) = captures.extract();
+                *counts.entry(key).or_default() += value.parse::<usize>().ok()?;
+            }
+        }
+
+        let tests = *counts.get("tests")?;
+        let failed = counts.get("failures").unwrap_or(&0) + counts.get("errors").unwrap_or(&0);
+        let skipped = *counts.get("skipped").unwrap_or(&0);
+
+        Some(TestCounts {
+            passed: tests.saturating_sub(failed + skipped),
+            failed,
+            skipped,
+        })
+    }
+}
+
+impl Display for TestCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} passed", self.passed)?;
+
+        if self.failed > 0 {
+            write!(f, ", {} failed", self.failed)?;
+        }
+        if self.skipped > 0 {
+            write!(f, ", {} skipped", self.skipped)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the directories to discover tests in, checking each exists in the workspace.
+///
+/// Paths from the `TestOptions` are relative to the current directory and configured paths
+/// are relative to the workspace root.
+///
+/// ```toml
+/// [tool.huak.test]
+/// paths = ["tests", "integration"]
+/// ```
+fn test_paths(options: &TestOptions, config: &Config) -> HuakResult<Vec<PathBuf>> {
+    let workspace = config.workspace();
+    let paths = if options.paths.is_empty() {
+        workspace
+            .test_paths()
+            .into_iter()
+            .map(|it| workspace.root().join(it))
+            .collect::<Vec<_>>()
+    } else {
+        options.paths.iter().map(|it| config.cwd.join(it)).collect()
+    };
+    let root = dunce::canonicalize(workspace.root())?;
+
+    paths
+        .into_iter()
+        .map(|it| {
+            let Ok(path) = dunce::canonicalize(&it) else {
+                return Err(Error::TestPathNotFound { path: it });
+            };
+
+            if !path.starts_with(&root) {
+                return Err(Error::DirectoryOutsideWorkspace { path });
+            }
+
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Get pytest's arguments for how the `TestOptions` show the tests' output. They're passed
+/// before the `TestOptions`' values so explicitly passed capture and logging options win.
+fn output_args(options: &TestOptions) -> Vec<String> {
+    let mut args = Vec::new();
+    if options.no_capture {
+        args.push("--capture=no".to_string());
     }
-    cmd.args(args)
+    if let Some(it) = options.log_cli_level.as_ref() {
+        args.push(format!("--log-cli-level={it}"));
+    }
+
+    args
+}
+
+/// Get the node IDs of the tests pytest collects from the `paths` with the `values` passed
+/// through to it, running it from the workspace `root`.
+fn collect_tests(
+    python_env: &PythonEnvironment,
+    python_path: &Path,
+    paths: &[PathBuf],
+    env: &HashMap<String, String>,
+    values: &[String],
+    root: &Path,
+) -> HuakResult<Vec<String>> {
+    let mut cmd = Command::new(python_env.python_path());
+    add_venv_to_command(&mut cmd, python_env)?;
+    cmd.args(["-m", "pytest", "--collect-only", "-q"])
+        .args(values)
+        .args(paths)
         .env("PYTHONPATH", python_path)
-        .current_dir(&config.cwd);
-    config.terminal().run_command(&mut cmd)
+        .envs(env)
+        .current_dir(root);
+
+    let output = cmd.output()?;
+
+    // pytest exits with 5 when no tests are collected.
+    if !output.status.success() && output.status.code() != Some(5) {
+        std::io::stderr().write_all(&output.stdout)?;
+        std::io::stderr().write_all(&output.stderr)?;
+        return Err(Error::SubprocessFailure(SubprocessError::new(
+            output.status,
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .take_while(|it| !it.trim().is_empty())
+        .filter(|it| it.contains("::"))
+        .map(ToString::to_string)
+        .collect())
+}
+
+/// Read the test durations recorded by pytest-split's `--store-durations`.
+///
+/// Both pytest-split's current format (an object) and its older one (a list of pairs) are read.
+fn test_durations(root: &Path, config: &Config) -> HuakResult<Option<HashMap<String, f64>>> {
+    let path = root.join(TEST_DURATIONS_FILE_NAME);
+
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        config.terminal().print_warning(format!(
+            "test durations could not be found at {} (run `huak test -- --store-durations` with pytest-split); sharding by hash instead",
+            path.display()
+        ))?;
+        return Ok(None);
+    };
+
+    let durations = serde_json::from_str::<HashMap<String, f64>>(&contents).or_else(|_| {
+        serde_json::from_str::<Vec<(String, f64)>>(&contents).map(|it| it.into_iter().collect())
+    })?;
+
+    Ok(Some(durations))
+}
+
+/// Get the tests of a `TestShard`, in their collected order.
+///
+/// Without `durations` each test goes to the shard its node ID hashes to. With them, tests are
+/// assigned longest first to the shard with the least total duration so far. Tests without a
+/// recorded duration are weighted by the average.
+#[allow(clippy::cast_precision_loss)]
+fn shard_tests(
+    tests: &[String],
+    shard: TestShard,
+    durations: Option<&HashMap<String, f64>>,
+) -> Vec<String> {
+    let Some(durations) = durations else {
+        return tests
+            .iter()
+            .filter(|it| stable_hash(it) % shard.total as u64 == (shard.index - 1) as u64)
+            .cloned()
+            .collect();
+    };
+
+    let known = tests
+        .iter()
+        .filter_map(|it| durations.get(it))
+        .collect::<Vec<_>>();
+    let average = if known.is_empty() {
+        1.0
+    } else {
+        known.iter().copied().sum::<f64>() / known.len() as f64
+    };
+
+    let mut weighted = tests
+        .iter()
+        .map(|it| (it, durations.get(it).copied().unwrap_or(average)))
+        .collect::<Vec<_>>();
+    weighted.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    let mut loads = vec![0.0_f64; shard.total];
+    let mut selected = HashSet::new();
+
+    for (test, duration) in weighted {
+        let (i, _) = loads
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .expect("a shard");
+        loads[i] += duration;
+
+        if i == shard.index - 1 {
+            selected.insert(test);
+        }
+    }
+
+    tests
+        .iter()
+        .filter(|it| selected.contains(it))
+        .cloned()
+        .collect()
+}
+
+/// Hash a test's node ID the same way everywhere (FNV-1a) so every shard agrees on the split.
+fn stable_hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{copy_dir, initialize_venv, CopyDirOptions, TerminalOptions, Verbosity};
+    use crate::{
+        copy_dir, initialize_venv, venv_executables_dir_path, CopyDirOptions, TerminalOptions,
+        Verbosity,
+    };
     use huak_dev::dev_resources_dir;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_test_counts_from_junit_xml() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?><testsuites><testsuite name="pytest" errors="1" failures="0" skipped="2" tests="12" time="3.402"><testcase classname="tests.test_version" name="test_version" time="0.001" /></testsuite></testsuites>"#;
+        let counts = TestCounts::from_junit_xml(xml).unwrap();
+
+        assert_eq!(
+            counts,
+            TestCounts {
+                passed: 9,
+                failed: 1,
+                skipped: 2
+            }
+        );
+        assert_eq!(counts.to_string(), "9 passed, 1 failed, 2 skipped");
+        assert!(TestCounts::from_junit_xml("<testsuites></testsuites>").is_none());
+    }
+
+    #[test]
+    fn test_test_project_shuffle_without_plugin() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let venv = root.join(".venv");
+        std::fs::create_dir_all(venv_executables_dir_path(&venv)).unwrap();
+        std::fs::write(venv.join("pyvenv.cfg"), "version = 3.11.0\n").unwrap();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            "[project]\nname = \"mock-project\"\nversion = \"0.0.1\"\n",
+        )
+        .unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root,
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let options = TestOptions {
+            values: None,
+            install_options: InstallOptions::default(),
+            groups: Vec::new(),
+            no_default_deps: false,
+            shard: None,
+            shard_by: ShardBy::default(),
+            shuffle: true,
+            seed: Some(1234),
+            until_failure: false,
+            max_runs: None,
+            no_capture: false,
+            log_cli_level: None,
+            paths: Vec::new(),
+            env: HashMap::new(),
+        };
+
+        assert!(matches!(
+            test_project(&config, &options),
+            Err(Error::PythonModuleNotFound { module, .. }) if module == "pytest-randomly"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_tests_until_failure() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        // A mock python that counts its runs and exits with `$EXIT_CODE` from `$FAIL_ON` on.
+        let python = dir.path().join("python");
+        std::fs::write(
+            &python,
+            "#!/bin/sh\n\
+             runs=$(($(cat \"$RUNS\" 2>/dev/null || echo 0) + 1))\n\
+             echo $runs > \"$RUNS\"\n\
+             [ $runs -lt $FAIL_ON ] || exit $EXIT_CODE\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&python, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let options = |max_runs| TestOptions {
+            values: None,
+            install_options: InstallOptions::default(),
+            groups: Vec::new(),
+            no_default_deps: false,
+            shard: None,
+            shard_by: ShardBy::default(),
+            shuffle: false,
+            seed: None,
+            until_failure: true,
+            max_runs,
+            no_capture: false,
+            log_cli_level: None,
+            paths: Vec::new(),
+            env: HashMap::new(),
+        };
+        let mut terminal = Terminal::from_options(TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        });
+        let mut run = |name: &str, fail_on: usize, exit_code: i32, max_runs| {
+            let runs = dir.path().join(name);
+            let res = run_tests(&options(max_runs), &mut terminal, |_| {
+                let mut cmd = Command::new(&python);
+                cmd.env("RUNS", &runs)
+                    .env("FAIL_ON", fail_on.to_string())
+                    .env("EXIT_CODE", exit_code.to_string());
+                Ok(cmd)
+            });
+            let runs = std::fs::read_to_string(&runs).unwrap();
+            (res, runs.trim().parse::<usize>().unwrap())
+        };
+
+        let (res, runs) = run("failed", 3, 1, None);
+        assert!(matches!(res, Err(Error::SubprocessFailure(e)) if e.code() == Some(1)));
+        assert_eq!(runs, 3);
+
+        let (res, runs) = run("interrupted", 2, PYTEST_INTERRUPTED, None);
+        assert!(matches!(
+            res,
+            Err(Error::SubprocessFailure(e)) if e.code() == Some(PYTEST_INTERRUPTED)
+        ));
+        assert_eq!(runs, 2);
+
+        let (res, runs) = run("max-runs", 10, 1, Some(4));
+        assert_eq!(res.unwrap(), 4);
+        assert_eq!(runs, 4);
+    }
+
     #[test]
     fn test_test_project() {
         let dir = tempdir().unwrap();
@@ -88,9 +768,149 @@ mod tests {
         initialize_venv(ws.root().join(".venv"), &ws.environment()).unwrap();
         let options = TestOptions {
             values: None,
-            install_options: InstallOptions { values: None },
+            install_options: InstallOptions::default(),
+            groups: Vec::new(),
+            no_default_deps: false,
+            shard: None,
+            shard_by: ShardBy::default(),
+            shuffle: false,
+            seed: None,
+            until_failure: false,
+            max_runs: None,
+            no_capture: false,
+            log_cli_level: None,
+            paths: Vec::new(),
+            env: HashMap::new(),
         };
 
         test_project(&config, &options).unwrap();
     }
+
+    #[test]
+    fn test_test_paths() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("mock-project");
+        std::fs::create_dir_all(root.join("tests").join("unit")).unwrap();
+        std::fs::create_dir_all(root.join("integration")).unwrap();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            r#"[project]
+name = "mock-project"
+
+[tool.huak.test]
+paths = ["integration", "tests/unit"]
+"#,
+        )
+        .unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root.join("tests"),
+            ..Default::default()
+        };
+        let options = |paths: &[&str]| TestOptions {
+            values: None,
+            install_options: InstallOptions::default(),
+            groups: Vec::new(),
+            no_default_deps: false,
+            shard: None,
+            shard_by: ShardBy::default(),
+            shuffle: false,
+            seed: None,
+            until_failure: false,
+            max_runs: None,
+            no_capture: false,
+            log_cli_level: None,
+            paths: paths.iter().map(PathBuf::from).collect(),
+            env: HashMap::new(),
+        };
+        let root = root.canonicalize().unwrap();
+
+        assert_eq!(
+            test_paths(&options(&[]), &config).unwrap(),
+            [root.join("integration"), root.join("tests").join("unit")]
+        );
+        // Paths passed as options are relative to the current directory.
+        assert_eq!(
+            test_paths(&options(&["unit"]), &config).unwrap(),
+            [root.join("tests").join("unit")]
+        );
+        assert!(matches!(
+            test_paths(&options(&["missing"]), &config),
+            Err(Error::TestPathNotFound { .. })
+        ));
+        assert!(matches!(
+            test_paths(&options(&["../.."]), &config),
+            Err(Error::DirectoryOutsideWorkspace { .. })
+        ));
+    }
+
+    #[test]
+    fn test_output_args() {
+        let options = TestOptions {
+            values: None,
+            install_options: InstallOptions::default(),
+            groups: Vec::new(),
+            no_default_deps: false,
+            shard: None,
+            shard_by: ShardBy::default(),
+            shuffle: false,
+            seed: None,
+            until_failure: false,
+            max_runs: None,
+            paths: Vec::new(),
+            env: HashMap::new(),
+            no_capture: true,
+            log_cli_level: Some("DEBUG".to_string()),
+        };
+
+        assert_eq!(
+            output_args(&options),
+            ["--capture=no", "--log-cli-level=DEBUG"]
+        );
+    }
+
+    #[test]
+    fn test_test_shard_from_str() {
+        assert_eq!(
+            TestShard::from_str("2/4").unwrap(),
+            TestShard { index: 2, total: 4 }
+        );
+
+        for it in ["0/4", "5/4", "2", "a/4"] {
+            assert!(matches!(
+                TestShard::from_str(it),
+                Err(Error::InvalidTestShard(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_shard_tests() {
+        let tests = (0..20)
+            .map(|it| format!("tests/test_mock.py::test_{it}"))
+            .collect::<Vec<_>>();
+        let durations = HashMap::from([
+            (tests[0].clone(), 10.0),
+            (tests[1].clone(), 6.0),
+            (tests[2].clone(), 5.0),
+        ]);
+
+        for durations in [None, Some(&durations)] {
+            let shards = (1..=3)
+                .map(|index| shard_tests(&tests, TestShard { index, total: 3 }, durations))
+                .collect::<Vec<_>>();
+            let mut all = shards.concat();
+            all.sort();
+            let mut expected = tests.clone();
+            expected.sort();
+
+            assert_eq!(all, expected);
+            assert!(shards.iter().all(|it| !it.is_empty()));
+        }
+
+        // The longest test gets a shard to itself until the others catch up.
+        let first = shard_tests(&tests, TestShard { index: 1, total: 3 }, Some(&durations));
+        assert_eq!(first[0], tests[0]);
+        assert!(first.len() < 7);
+    }
 }
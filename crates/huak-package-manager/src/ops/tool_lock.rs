@@ -0,0 +1,245 @@
+use crate::{Config, Dependency, HuakResult, InstallOptions, LocalManifest, PythonEnvironment};
+use huak_pyproject_toml::canonical_name;
+use std::str::FromStr;
+use termcolor::Color;
+
+/// Install a tool Huak runs (like `ruff`) to the `PythonEnvironment` at the version pinned in the
+/// workspace's `[tool.huak.toolchain-lock]`.
+///
+/// A tool that isn't pinned yet is installed if it's missing and its installed version is
+/// pinned, so everyone working on the project runs the same version.
+pub(crate) fn install_locked_tool(
+    tool: &Dependency,
+    python_env: &PythonEnvironment,
+    install_options: &InstallOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    let name = canonical_name(tool.name());
+    let manifest = config.workspace().current_local_manifest().ok();
+    let pin = manifest
+        .as_ref()
+        .and_then(|it| it.manifest_data().tool_version_pins().remove(&name));
+    let installed = python_env.installed_versions()?.remove(&name);
+
+    match (pin.as_ref(), installed) {
+        (Some(pin), Some(it)) if pep440_rs::Version::from_str(pin).is_ok_and(|pin| pin == it) => {
+            return Ok(())
+        }
+        (Some(pin), _) => {
+            let dep = Dependency::from_str(&format!("{}=={pin}", tool.name()))?;
+            return python_env.install_packages(&[&dep], install_options, config);
+        }
+        (None, Some(_)) => (),
+        (None, None) => python_env.install_packages(&[tool], install_options, config)?,
+    }
+
+    let Some(mut manifest) = manifest else {
+        return Ok(());
+    };
+
+    // Nothing is installed for dry runs.
+    let Some(version) = python_env.installed_versions()?.remove(&name) else {
+        return Ok(());
+    };
+
+    manifest
+        .manifest_data_mut()
+        .set_tool_version_pin(&name, &version.to_string())
+        .formatted();
+    config.write_manifest(&manifest)
+}
+
+/// Upgrade the tools pinned in the workspace's `[tool.huak.toolchain-lock]` (or only `tool`) to
+/// their latest versions and move their pins forward.
+///
+/// Optional dependencies pinned to a tool's old version (like the `dev` dependencies Huak adds)
+/// are moved forward too.
+pub fn upgrade_self_toolchain(
+    tool: Option<&str>,
+    install_options: &InstallOptions,
+    config: &Config,
+) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let mut manifest = workspace.current_local_manifest()?;
+    let pins = manifest.manifest_data().tool_version_pins();
+    let mut terminal = config.terminal();
+
+    let mut tools = match tool {
+        Some(it) => vec![canonical_name(it)],
+        None => pins.keys().cloned().collect(),
+    };
+    tools.sort();
+
+    if tools.is_empty() {
+        return terminal.print_warning("no tools are pinned in [tool.huak.toolchain-lock]");
+    }
+
+    let python_env = workspace.resolve_python_environment()?;
+    python_env.update_packages(&tools, install_options, config)?;
+
+    // Nothing is upgraded for dry runs.
+    if config.dry_run {
+        return Ok(());
+    }
+
+    let installed = python_env.installed_versions()?;
+
+    for name in &tools {
+        let Some(version) = installed.get(name).map(ToString::to_string) else {
+            continue;
+        };
+
+        match pins.get(name) {
+            Some(old) if *old == version => {
+                terminal.print_custom(
+                    "Unchanged",
+                    format!("{name} {version}"),
+                    Color::Cyan,
+                    true,
+                )?;
+                continue;
+            }
+            Some(old) => {
+                terminal.print_custom(
+                    "Upgraded",
+                    format!("{name} {old} -> {version}"),
+                    Color::Green,
+                    true,
+                )?;
+                repin_optional_dependencies(&mut manifest, name, old, &version)?;
+            }
+            None => {
+                terminal.print_custom("Pinned", format!("{name} {version}"), Color::Green, true)?;
+            }
+        }
+
+        manifest
+            .manifest_data_mut()
+            .set_tool_version_pin(name, &version);
+    }
+
+    manifest.manifest_data_mut().formatted();
+    config.write_manifest(&manifest)
+}
+
+/// Replace the optional dependencies pinned to `name==old` with `name==new`.
+fn repin_optional_dependencies(
+    manifest: &mut LocalManifest,
+    name: &str,
+    old: &str,
+    new: &str,
+) -> HuakResult<()> {
+    let groups = manifest
+        .manifest_data()
+        .project_optional_dependencies()
+        .unwrap_or_default();
+
+    for (group, deps) in groups {
+        for it in deps {
+            let dep = Dependency::from_str(&it)?;
+
+            if canonical_name(dep.name()) != name
+                || it.replace(' ', "") != format!("{}=={old}", dep.name())
+            {
+                continue;
+            }
+
+            manifest
+                .manifest_data_mut()
+                .remove_project_optional_dependency(dep.name(), &group)
+                .add_project_optional_dependency(&format!("{}=={new}", dep.name()), &group);
+        }
+    }
+
+    Ok(())
+}
+
+/// Get a problem for each tool installed to the `PythonEnvironment` at a different version than
+/// the one pinned in `[tool.huak.toolchain-lock]`.
+pub(crate) fn tool_version_drift(
+    manifest: &LocalManifest,
+    python_env: &PythonEnvironment,
+) -> HuakResult<Vec<String>> {
+    let installed = python_env.installed_versions()?;
+    let mut pins = manifest
+        .manifest_data()
+        .tool_version_pins()
+        .into_iter()
+        .collect::<Vec<_>>();
+    pins.sort();
+
+    Ok(pins
+        .into_iter()
+        .filter_map(|(name, pin)| {
+            let version = installed.get(&name)?;
+
+            (pep440_rs::Version::from_str(&pin).ok().as_ref() != Some(version)).then(|| {
+                format!(
+                    "{name} {version} is installed but {pin} is pinned in [tool.huak.toolchain-lock] (reinstalled by the next command that runs it)"
+                )
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{venv_executables_dir_path, TerminalOptions, Verbosity};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_install_locked_tool() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let venv = root.join(".venv");
+        std::fs::create_dir_all(venv_executables_dir_path(&venv)).unwrap();
+        std::fs::write(venv.join("pyvenv.cfg"), "version = 3.11.0\n").unwrap();
+        let site_packages = venv.join("lib").join("python3.11").join("site-packages");
+        for it in ["ruff-0.4.2.dist-info", "pytest-8.2.0.dist-info"] {
+            std::fs::create_dir_all(site_packages.join(it)).unwrap();
+        }
+        std::fs::write(
+            root.join("pyproject.toml"),
+            r#"[project]
+name = "mock-project"
+version = "0.0.1"
+
+[tool.huak.toolchain-lock]
+pytest = "8.1.0"
+"#,
+        )
+        .unwrap();
+        let config = Config {
+            workspace_root: root.clone(),
+            cwd: root,
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let ws = config.workspace();
+        let python_env = PythonEnvironment::new(&venv).unwrap();
+
+        // The mock environment has no Python, so running the installer would fail.
+        install_locked_tool(
+            &Dependency::from_str("ruff").unwrap(),
+            &python_env,
+            &InstallOptions::default(),
+            &config,
+        )
+        .unwrap();
+
+        let manifest = ws.current_local_manifest().unwrap();
+
+        assert_eq!(
+            manifest.manifest_data().tool_version_pins().get("ruff"),
+            Some(&"0.4.2".to_string())
+        );
+        assert_eq!(
+            tool_version_drift(&manifest, &python_env).unwrap(),
+            ["pytest 8.2.0 is installed but 8.1.0 is pinned in [tool.huak.toolchain-lock] (reinstalled by the next command that runs it)"]
+        );
+    }
+}
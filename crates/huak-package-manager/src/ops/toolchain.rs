@@ -1,5 +1,6 @@
 use crate::{
-    fs::maybe_exe, sys::symlink_supported, Config, Error, HuakResult, PythonEnvironment, Verbosity,
+    fs::maybe_exe, sys::symlink_supported, Config, Error, HuakResult, InstallOptions,
+    PythonEnvironment, Verbosity,
 };
 use huak_home::huak_home_dir;
 use huak_python_manager::{
@@ -26,23 +27,37 @@ pub fn add_tool(tool: &LocalTool, channel: Option<&Channel>, config: &Config) ->
     // Resolve a toolchain if a channel is provided. Otherwise resolve the current.
     let toolchain = config.workspace().resolve_local_toolchain(channel)?;
 
-    add_tool_to_toolchain(tool, &toolchain, config)
+    add_tool_to_toolchain(tool, &toolchain, &InstallOptions::default(), config)
+}
+
+/// Build the command that installs the `tool` to a toolchain's `venv`.
+pub(crate) fn tool_install_command(
+    tool: &LocalTool,
+    venv: &PythonEnvironment,
+    options: &InstallOptions,
+    config: &Config,
+) -> HuakResult<Command> {
+    let mut cmd = Command::new(venv.python_path());
+    cmd.args(["-m", "pip", "install", tool.spec().unwrap_or(&tool.name)])
+        .args(options.args(config)?)
+        .envs(options.envs())
+        .current_dir(&config.cwd);
+
+    Ok(cmd)
 }
 
 // TODO(cnpryer): Refactor
 pub(crate) fn add_tool_to_toolchain(
     tool: &LocalTool,
     toolchain: &LocalToolchain,
+    options: &InstallOptions,
     config: &Config,
 ) -> HuakResult<()> {
-    let args = ["-m", "pip", "install", tool.spec().unwrap_or(&tool.name)];
     let venv = PythonEnvironment::new(toolchain.root().join(".venv"))?;
+    let mut cmd = tool_install_command(tool, &venv, options, config)?;
 
     let mut terminal = config.terminal();
 
-    let mut cmd = Command::new(venv.python_path());
-    let cmd = cmd.args(args).current_dir(&config.cwd);
-
     terminal.print_custom(
         "Updating",
         format!("adding {} to {}", &tool.name, toolchain.name()),
@@ -52,7 +67,7 @@ pub(crate) fn add_tool_to_toolchain(
 
     // TODO(cnpryer): Terminal work
     // terminal.set_verbosity(Verbosity::Quiet);
-    terminal.run_command(cmd)?;
+    terminal.run_command(&mut cmd)?;
 
     let Some(source) = venv.executable_module_path(&tool.name) else {
         return Err(Error::InternalError(format!(
@@ -106,7 +121,9 @@ pub fn install_toolchain(
     let ws = config.workspace();
 
     if let Ok(toolchain) = ws.resolve_local_toolchain(channel.as_ref()) {
-        return Err(Error::LocalToolchainExists(toolchain.root().clone()));
+        return Err(Error::LocalToolchainExists {
+            path: toolchain.root().clone(),
+        });
     }
 
     // If no target path is provided we always install to Huak's toolchain directory
@@ -121,7 +138,7 @@ pub fn install_toolchain(
     let path = parent.join(&channel_string);
 
     if path.exists() {
-        return Err(Error::LocalToolchainExists(path));
+        return Err(Error::LocalToolchainExists { path });
     }
 
     if let Err(e) = install(&path, channel, config) {
@@ -138,7 +155,7 @@ pub(crate) fn install(path: &PathBuf, channel: Channel, config: &Config) -> Huak
 
     let toolchain = match install_minimal_toolchain(path, channel, config) {
         Ok(it) => it,
-        Err(Error::LocalToolchainExists(_)) => {
+        Err(Error::LocalToolchainExists { .. }) => {
             return terminal
                 .print_warning(format!("Toolchain already exists at {}", path.display()))
         }
@@ -157,7 +174,10 @@ pub(crate) fn install(path: &PathBuf, channel: Channel, config: &Config) -> Huak
         terminal.run_command(&mut cmd)?;
 
         let Some(p) = venv.executable_module_path(name) else {
-            return Err(Error::PythonModuleNotFound(name.to_string()));
+            return Err(Error::PythonModuleNotFound {
+                module: name.to_string(),
+                hint: None,
+            });
         };
 
         if toolchain.register_tool(&p, name, false).is_err() {
@@ -195,7 +215,7 @@ pub(crate) fn install_minimal_toolchain(
 
     // If 'python' is already installed we don't install it.
     if py.exists() {
-        return Err(Error::LocalToolchainExists(path.clone()));
+        return Err(Error::LocalToolchainExists { path: path.clone() });
     }
 
     for p in [toolchain.bin(), toolchain.downloads()] {
@@ -204,9 +224,9 @@ pub(crate) fn install_minimal_toolchain(
 
     // Determine what Python release data to use for the install.
     let Some(release) = python_release_from_channel(toolchain.channel()) else {
-        return Err(Error::PythonReleaseNotFound(
-            toolchain.channel().to_string(),
-        ));
+        return Err(Error::PythonReleaseNotFound {
+            version: toolchain.channel().to_string(),
+        });
     };
 
     let msg = if matches!(toolchain.channel(), Channel::Default) {
@@ -545,7 +565,7 @@ pub fn use_toolchain(channel: &Channel, config: &Config) -> HuakResult<()> {
     let settings = home.join("toolchains").join("settings.toml");
     let mut db = SettingsDb::try_from(&settings).unwrap_or_default();
 
-    db.insert_scope(ws.root(), &toolchain.root().canonicalize()?)?;
+    db.insert_scope(ws.root(), &dunce::canonicalize(toolchain.root())?)?;
 
     Ok(db.save(settings)?)
 }
@@ -1,8 +1,48 @@
-use crate::{dependency_iter, Config, Dependency, HuakResult, InstallOptions};
-use std::str::FromStr;
+use super::{ensure_not_poetry_managed, find_project_dependencies, list::outdated_packages};
+use crate::{
+    dependency_iter,
+    history::{record_dependency_changes, DependencySnapshot},
+    Config, Dependency, Error, HuakResult, InstallOptions, PythonEnvironment,
+};
+use glob::Pattern;
+use huak_pyproject_toml::canonical_name;
+use pep440_rs::{Operator, Version, VersionSpecifiers};
+use pep508_rs::VersionOrUrl;
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+use termcolor::Color;
 
 pub struct UpdateOptions {
     pub install_options: InstallOptions,
+    /// Upgrade past the dependencies' current version specifiers.
+    pub latest: bool,
+    /// Upgrade unbounded dependencies past their installed major version.
+    pub allow_major: bool,
+}
+
+/// A dependency's PEP 503 normalized name and the constraint it was updated within.
+type Constrained = (String, Constraint);
+
+/// Why a dependency may be held back from its latest version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Constraint {
+    /// The dependency's declared version specifier has an upper bound.
+    Specifier(String),
+    /// The dependency's specifier is unbounded, so it's held to its installed major version.
+    Policy(u64),
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Constraint::Specifier(it) => {
+                write!(f, "constrained by specifier {it}; pass --latest to upgrade")
+            }
+            Constraint::Policy(it) => write!(
+                f,
+                "constrained by policy to major version {it}; pass --allow-major to upgrade"
+            ),
+        }
+    }
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -14,28 +54,43 @@ pub fn update_project_dependencies(
 ) -> HuakResult<()> {
     let workspace = config.workspace();
     let mut manifest = workspace.current_local_manifest()?;
-    let python_env = workspace.resolve_python_environment()?;
+    ensure_not_poetry_managed(&manifest)?;
+    let before = DependencySnapshot::new(manifest.manifest_data());
 
     // Collect dependencies to update if they are listed in the manifest file.
-    if let Some(it) = dependencies.as_ref() {
-        let deps = dependency_iter(it)
-            .filter_map(|dep| {
-                if manifest
-                    .manifest_data()
-                    .contains_project_dependency_any(dep.name())
-                {
-                    Some(dep)
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
+    let requested = dependencies
+        .as_deref()
+        .map(|it| find_project_dependencies(it, &manifest))
+        .transpose()?;
+
+    let python_env = workspace.resolve_python_environment()?;
+    let hashed = manifest.manifest_data().dependency_hashes();
+    let mut terminal = config.terminal();
+
+    if let Some(requested) = requested {
+        let mut deps = Vec::new();
+
+        for dep in requested {
+            if hashed.contains_key(&canonical_name(dep.name())) {
+                terminal.print_warning(format!("{} is pinned by hash", dep.name()))?;
+                continue;
+            }
+
+            deps.push(dep);
+        }
 
         if deps.is_empty() {
             return Ok(());
         }
 
+        let (deps, constrained) = upgrade_targets(
+            deps,
+            &python_env.installed_versions()?,
+            &allowed_major(config)?,
+            options,
+        )?;
         python_env.update_packages(&deps, &options.install_options, config)?;
+        report_held_back(&constrained, &python_env, config)?;
     } else {
         let mut deps = manifest
             .manifest_data()
@@ -58,7 +113,26 @@ pub fn update_project_dependencies(
 
         deps.dedup();
 
-        python_env.update_packages(&deps, &options.install_options, config)?;
+        let mut unhashed = Vec::new();
+
+        for dep in dependency_iter(deps) {
+            if hashed.contains_key(&canonical_name(dep.name())) {
+                terminal.print_warning(format!("{} is pinned by hash", dep.name()))?;
+            } else {
+                unhashed.push(dep);
+            }
+        }
+
+        if !unhashed.is_empty() {
+            let (unhashed, constrained) = upgrade_targets(
+                unhashed,
+                &python_env.installed_versions()?,
+                &allowed_major(config)?,
+                options,
+            )?;
+            python_env.update_packages(&unhashed, &options.install_options, config)?;
+            report_held_back(&constrained, &python_env, config)?;
+        }
     }
 
     let groups = manifest
@@ -97,7 +171,121 @@ pub fn update_project_dependencies(
     }
 
     manifest.manifest_data_mut().formatted();
-    manifest.write_file()?;
+    config.write_manifest(&manifest)?;
+    record_dependency_changes("update", &before, &manifest, Some(&python_env), config);
+
+    Ok(())
+}
+
+/// Get the `[tool.huak.update] allow-major` patterns.
+fn allowed_major(config: &Config) -> HuakResult<Vec<Pattern>> {
+    config
+        .workspace()
+        .update_allow_major()
+        .iter()
+        .map(|it| Ok(Pattern::new(&canonical_name(it))?))
+        .collect()
+}
+
+/// Get the requirements to upgrade the dependencies with, and the constraints on the
+/// dependencies that may be held back from their latest version.
+///
+/// Dependencies stay within their version specifiers unless `--latest` drops them. Unbounded
+/// dependencies are also held to their `installed` major version unless `--allow-major` is
+/// passed or they match an `allowed` pattern. Direct references are kept.
+fn upgrade_targets(
+    deps: Vec<Dependency>,
+    installed: &HashMap<String, Version>,
+    allowed: &[Pattern],
+    options: &UpdateOptions,
+) -> HuakResult<(Vec<Dependency>, Vec<Constrained>)> {
+    let mut targets = Vec::new();
+    let mut constrained = Vec::new();
+
+    for dep in deps {
+        let name = canonical_name(dep.name());
+        let mut requirement = dep.requirement().clone();
+        let specifiers = match requirement.version_or_url.as_ref() {
+            Some(VersionOrUrl::Url(_)) => {
+                targets.push(dep);
+                continue;
+            }
+            Some(VersionOrUrl::VersionSpecifier(it)) => it.clone(),
+            None => VersionSpecifiers::default(),
+        };
+
+        if options.latest {
+            requirement.version_or_url = None;
+        } else if specifiers.iter().any(|it| is_upper_bound(*it.operator())) {
+            constrained.push((name, Constraint::Specifier(specifiers.to_string())));
+        } else if let Some(major) = installed
+            .get(&name)
+            .and_then(|it| it.release.first().copied())
+            .filter(|_| !options.allow_major && !allowed.iter().any(|it| it.matches(&name)))
+        {
+            let bound = format!("<{}", major + 1);
+            let held = if specifiers.is_empty() {
+                bound
+            } else {
+                format!("{specifiers},{bound}")
+            };
+            requirement.version_or_url = Some(VersionOrUrl::VersionSpecifier(
+                VersionSpecifiers::from_str(&held)
+                    .map_err(|e| Error::InternalError(e.to_string()))?,
+            ));
+            constrained.push((name, Constraint::Policy(major)));
+        }
+
+        targets.push(Dependency::from(requirement));
+    }
+
+    Ok((targets, constrained))
+}
+
+/// Check if a specifier's `operator` keeps a dependency below some version.
+fn is_upper_bound(operator: Operator) -> bool {
+    matches!(
+        operator,
+        Operator::Equal
+            | Operator::EqualStar
+            | Operator::ExactEqual
+            | Operator::TildeEqual
+            | Operator::LessThan
+            | Operator::LessThanEqual
+    )
+}
+
+/// Print the `constrained` dependencies with newer versions on the package index than the ones
+/// they were updated to, and why they were held back.
+fn report_held_back(
+    constrained: &[Constrained],
+    python_env: &PythonEnvironment,
+    config: &Config,
+) -> HuakResult<()> {
+    if constrained.is_empty() {
+        return Ok(());
+    }
+
+    let mut terminal = config.terminal();
+    let mut latest = match outdated_packages(python_env, config) {
+        Ok(it) => it,
+        Err(Error::PackageIndexUnavailable(e)) => {
+            return terminal.print_warning(format!("couldn't check for held back packages: {e}"));
+        }
+        Err(e) => return Err(e),
+    };
+    let installed = python_env.installed_versions()?;
+
+    for (name, constraint) in constrained {
+        if let (Some(latest), Some(version)) = (latest.remove(name), installed.get(name)) {
+            terminal.print_custom(
+                "Held back",
+                format!("{name} {version} ({latest} available): {constraint}"),
+                Color::Yellow,
+                true,
+            )?;
+        }
+    }
 
     Ok(())
 }
@@ -133,7 +321,9 @@ mod tests {
         let ws = config.workspace();
         initialize_venv(ws.root().join(".venv"), &ws.environment()).unwrap();
         let options = UpdateOptions {
-            install_options: InstallOptions { values: None },
+            install_options: InstallOptions::default(),
+            latest: false,
+            allow_major: false,
         };
 
         update_project_dependencies(None, &config, &options).unwrap();
@@ -163,9 +353,88 @@ mod tests {
         let ws = config.workspace();
         initialize_venv(ws.root().join(".venv"), &ws.environment()).unwrap();
         let options = UpdateOptions {
-            install_options: InstallOptions { values: None },
+            install_options: InstallOptions::default(),
+            latest: false,
+            allow_major: false,
         };
 
         update_project_dependencies(None, &config, &options).unwrap();
     }
+
+    #[test]
+    fn test_upgrade_targets() {
+        let deps = dependency_iter([
+            "requests>=2,<3",
+            "black[jupyter]==23.1.0; python_version >= '3.8'",
+            "huak @ https://example.com/huak-0.0.1.tar.gz",
+            "pydantic>=1.8",
+            "internal-lib",
+            "click",
+        ])
+        .collect::<Vec<_>>();
+        let installed = [("pydantic", "1.10.13"), ("internal-lib", "3.2.0")]
+            .into_iter()
+            .map(|(name, version)| (name.to_string(), Version::from_str(version).unwrap()))
+            .collect::<HashMap<_, _>>();
+        let allowed = [Pattern::new("internal-*").unwrap()];
+        let mut options = UpdateOptions {
+            install_options: InstallOptions::default(),
+            latest: false,
+            allow_major: false,
+        };
+        let specifier = |it: &str| VersionSpecifiers::from_str(it).unwrap().to_string();
+
+        let (targets, constrained) =
+            upgrade_targets(deps.clone(), &installed, &allowed, &options).unwrap();
+
+        assert_eq!(
+            targets,
+            dependency_iter([
+                "requests>=2,<3",
+                "black[jupyter]==23.1.0; python_version >= '3.8'",
+                "huak @ https://example.com/huak-0.0.1.tar.gz",
+                "pydantic>=1.8,<2",
+                "internal-lib",
+                "click",
+            ])
+            .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            constrained,
+            [
+                (
+                    "requests".to_string(),
+                    Constraint::Specifier(specifier(">=2,<3"))
+                ),
+                (
+                    "black".to_string(),
+                    Constraint::Specifier(specifier("==23.1.0"))
+                ),
+                ("pydantic".to_string(), Constraint::Policy(1)),
+            ]
+        );
+
+        options.allow_major = true;
+        let (targets, constrained) =
+            upgrade_targets(deps.clone(), &installed, &allowed, &options).unwrap();
+
+        assert_eq!(targets[3], deps[3]);
+        assert_eq!(constrained.len(), 2);
+
+        options.latest = true;
+        let (targets, constrained) = upgrade_targets(deps, &installed, &[], &options).unwrap();
+
+        assert_eq!(
+            targets.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "requests",
+                "black[jupyter] ; python_version >= '3.8'",
+                "huak @ https://example.com/huak-0.0.1.tar.gz",
+                "pydantic",
+                "internal-lib",
+                "click",
+            ]
+        );
+        assert!(constrained.is_empty());
+    }
 }
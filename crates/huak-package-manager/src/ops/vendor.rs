@@ -0,0 +1,399 @@
+use crate::{dependency_iter, Config, Error, HuakResult, LocalManifest, PythonEnvironment};
+use huak_pyproject_toml::canonical_name;
+use pep440_rs::Version;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+use termcolor::Color;
+
+/// Vendored files larger than this are worth tracking with Git LFS.
+const LARGE_FILE_SIZE: u64 = 50 * 1024 * 1024;
+
+pub struct VendorSyncOptions {
+    /// Platforms to download wheels for (like `manylinux2014_x86_64`). Defaults to the current one.
+    pub platforms: Vec<String>,
+    /// Python versions to download wheels for (like `3.11`). Defaults to the current one.
+    pub python_versions: Vec<String>,
+}
+
+/// Download the project's dependencies into the vendor directory.
+///
+/// Dependencies are pinned to the versions installed in the project's environment so the
+/// vendor directory matches what's been tested. Without an environment only the declared
+/// dependencies are downloaded, along with whatever they resolve to.
+pub fn sync_vendor_dir(config: &Config, options: &VendorSyncOptions) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let manifest = workspace.current_local_manifest()?;
+    let python_env = workspace.resolve_python_environment()?;
+    let locked = locked_dependencies(&manifest, Some(&python_env))?;
+    let vendor_dir = workspace.vendor_dir();
+    let mut terminal = config.terminal();
+
+    if locked.is_empty() {
+        return terminal.print_warning("the project has no dependencies to vendor");
+    }
+
+    let requirements = locked
+        .iter()
+        .map(|(name, version)| match version {
+            Some(it) => format!("{name}=={it}"),
+            None => name.clone(),
+        })
+        .collect::<Vec<_>>();
+    // pip only needs to resolve anything if some of the dependencies aren't pinned.
+    let resolved = locked.values().all(Option::is_some);
+    let platforms = targets(&options.platforms);
+    let python_versions = targets(&options.python_versions);
+
+    if !config.dry_run {
+        std::fs::create_dir_all(&vendor_dir)?;
+    }
+
+    for platform in &platforms {
+        for python_version in &python_versions {
+            let mut cmd = Command::new(python_env.python_path());
+            cmd.args(["-m", "pip", "download", "--dest"])
+                .arg(&vendor_dir)
+                .args(&requirements)
+                .current_dir(&config.cwd);
+
+            if resolved {
+                cmd.arg("--no-deps");
+            }
+
+            // pip can only download for another target if it doesn't need to build anything.
+            if platform.is_some() || python_version.is_some() {
+                cmd.args(["--only-binary", ":all:"]);
+            } else {
+                cmd.arg("--prefer-binary");
+            }
+
+            if let Some(it) = platform {
+                cmd.args(["--platform", it]);
+            }
+
+            if let Some(it) = python_version {
+                cmd.args(["--python-version", it]);
+            }
+
+            config.run_effect("vendor", &mut cmd)?;
+        }
+    }
+
+    if config.dry_run {
+        return Ok(());
+    }
+
+    let files = distribution_files(&vendor_dir)?;
+
+    for file in &files {
+        let size = std::fs::metadata(&file.path)?.len();
+
+        if size > LARGE_FILE_SIZE {
+            terminal.print_warning(format!(
+                "{} is {} MiB",
+                file.path.display(),
+                size / 1024 / 1024
+            ))?;
+        }
+    }
+
+    if let Some(pattern) = lfs_pattern(&vendor_dir, workspace.root()) {
+        terminal.print_custom(
+            "Hint",
+            format!("track vendored files with Git LFS by adding `{pattern}` to .gitattributes"),
+            Color::Cyan,
+            true,
+        )?;
+    }
+
+    terminal.print_custom(
+        "Synced",
+        format!("{} files in {}", files.len(), vendor_dir.display()),
+        Color::Green,
+        true,
+    )
+}
+
+/// Check the vendor directory has every dependency of the project and nothing else.
+pub fn verify_vendor_dir(config: &Config) -> HuakResult<()> {
+    let workspace = config.workspace();
+    let manifest = workspace.current_local_manifest()?;
+    let vendor_dir = workspace.vendor_dir();
+
+    if !vendor_dir.exists() {
+        return Err(Error::VendorDirNotFound { path: vendor_dir });
+    }
+
+    let python_env = match workspace.current_python_environment() {
+        Ok(it) => Some(it),
+        Err(Error::PythonEnvironmentNotFound) => None,
+        Err(e) => return Err(e),
+    };
+    let locked = locked_dependencies(&manifest, python_env.as_ref())?;
+    let problems = vendor_problems(&vendor_dir, &locked)?;
+    let mut terminal = config.terminal();
+
+    for problem in &problems {
+        terminal.print_error(problem)?;
+    }
+
+    if !problems.is_empty() {
+        return Err(Error::VendorDirOutOfDate(problems.len()));
+    }
+
+    terminal.print_custom(
+        "Success",
+        format!(
+            "{} has all {} dependencies",
+            vendor_dir.display(),
+            locked.len()
+        ),
+        Color::Green,
+        true,
+    )
+}
+
+/// Get the canonical names of the project's dependencies and the versions they're locked to.
+///
+/// Declared dependencies pinned with `==` are locked to that version. If a `PythonEnvironment`
+/// is given, everything the declared dependencies resolve to is locked to its installed version.
+fn locked_dependencies(
+    manifest: &LocalManifest,
+    python_env: Option<&PythonEnvironment>,
+) -> HuakResult<BTreeMap<String, Option<String>>> {
+    let mut deps = manifest
+        .manifest_data()
+        .project_dependencies()
+        .unwrap_or_default();
+
+    if let Some(optional_deps) = manifest.manifest_data().project_optional_dependencies() {
+        deps.extend(optional_deps.into_values().flatten());
+    }
+
+    let mut locked = dependency_iter(deps)
+        .map(|it| (canonical_name(it.name()), it.pinned_version()))
+        .collect::<BTreeMap<_, _>>();
+
+    let Some(python_env) = python_env else {
+        return Ok(locked);
+    };
+
+    let installed = python_env
+        .installed_packages()?
+        .into_iter()
+        .map(|it| (canonical_name(it.name()), it.version().to_string()))
+        .collect::<BTreeMap<_, _>>();
+    let direct = locked.keys().cloned().collect::<Vec<_>>();
+
+    for name in python_env.dependency_graph()?.resolved(&direct) {
+        let version = installed.get(&name).cloned();
+        let entry = locked.entry(name).or_default();
+
+        if entry.is_none() {
+            *entry = version;
+        }
+    }
+
+    Ok(locked)
+}
+
+/// Get a description of each file missing from or extraneous to the vendor directory.
+fn vendor_problems(
+    vendor_dir: &Path,
+    locked: &BTreeMap<String, Option<String>>,
+) -> HuakResult<Vec<String>> {
+    let files = distribution_files(vendor_dir)?;
+    let matches = |file: &DistributionFile, name: &str, version: Option<&String>| {
+        file.name == name && version.map_or(true, |it| same_version(it, &file.version))
+    };
+    let mut problems = Vec::new();
+
+    for (name, version) in locked {
+        if !files.iter().any(|it| matches(it, name, version.as_ref())) {
+            problems.push(match version {
+                Some(it) => format!("{name}=={it} is missing"),
+                None => format!("{name} is missing"),
+            });
+        }
+    }
+
+    for file in &files {
+        if !locked
+            .iter()
+            .any(|(name, version)| matches(file, name, version.as_ref()))
+        {
+            problems.push(format!("{} is extraneous", file.path.display()));
+        }
+    }
+
+    Ok(problems)
+}
+
+/// A wheel or source distribution in the vendor directory.
+struct DistributionFile {
+    path: PathBuf,
+    /// The canonical name of the distribution.
+    name: String,
+    version: String,
+}
+
+/// Get the `DistributionFile`s in a directory. Anything that isn't a distribution is skipped.
+fn distribution_files(dir: &Path) -> HuakResult<Vec<DistributionFile>> {
+    let mut files = Vec::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some((name, version)) = path
+            .file_name()
+            .and_then(|it| it.to_str())
+            .and_then(distribution_name_version)
+        else {
+            continue;
+        };
+
+        files.push(DistributionFile {
+            path,
+            name,
+            version,
+        });
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(files)
+}
+
+/// Get the canonical name and version of a distribution from its file name.
+///
+/// Wheels are named `{name}-{version}(-{build})?-{python}-{abi}-{platform}.whl` and source
+/// distributions `{name}-{version}.tar.gz` (or `.zip`).
+pub(super) fn distribution_name_version(file_name: &str) -> Option<(String, String)> {
+    if let Some(stem) = file_name.strip_suffix(".whl") {
+        let mut parts = stem.split('-');
+        let name = parts.next()?;
+        let version = parts.next()?;
+
+        return Some((canonical_name(name), version.to_string()));
+    }
+
+    let stem = [".tar.gz", ".zip"]
+        .iter()
+        .find_map(|it| file_name.strip_suffix(it))?;
+    let (name, version) = stem.rsplit_once('-')?;
+
+    Some((canonical_name(name), version.to_string()))
+}
+
+fn same_version(a: &str, b: &str) -> bool {
+    match (Version::from_str(a), Version::from_str(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Get each requested target, or a single `None` for the current one.
+fn targets(values: &[String]) -> Vec<Option<&str>> {
+    if values.is_empty() {
+        vec![None]
+    } else {
+        values.iter().map(|it| Some(it.as_str())).collect()
+    }
+}
+
+/// Get the `.gitattributes` pattern tracking the vendor directory with Git LFS, if it isn't
+/// already tracked.
+fn lfs_pattern(vendor_dir: &Path, root: &Path) -> Option<String> {
+    let relative = vendor_dir.strip_prefix(root).unwrap_or(vendor_dir);
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    let tracked = std::fs::read_to_string(root.join(".gitattributes")).is_ok_and(|it| {
+        it.lines()
+            .any(|line| line.starts_with(relative.as_str()) && line.contains("filter=lfs"))
+    });
+
+    (!tracked).then(|| format!("{relative}/** filter=lfs diff=lfs merge=lfs -text"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{copy_dir, CopyDirOptions};
+    use huak_dev::dev_resources_dir;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_distribution_name_version() {
+        assert_eq!(
+            distribution_name_version("Jinja2-3.1.2-py3-none-any.whl"),
+            Some(("jinja2".to_string(), "3.1.2".to_string()))
+        );
+        assert_eq!(
+            distribution_name_version("left_pad-1.0.0.tar.gz"),
+            Some(("left-pad".to_string(), "1.0.0".to_string()))
+        );
+        assert_eq!(distribution_name_version(".gitkeep"), None);
+    }
+
+    #[test]
+    fn test_vendor_problems() {
+        let dir = tempdir().unwrap();
+        copy_dir(
+            &dev_resources_dir().join("mock-project"),
+            &dir.path().join("mock-project"),
+            &CopyDirOptions::default(),
+        )
+        .unwrap();
+        let root = dir.path().join("mock-project");
+        let manifest = LocalManifest::new(root.join("pyproject.toml")).unwrap();
+        let locked = locked_dependencies(&manifest, None).unwrap();
+        let vendor_dir = root.join("vendor");
+        std::fs::create_dir(&vendor_dir).unwrap();
+
+        for it in [
+            "click-8.1.7-py3-none-any.whl",
+            "pytest-7.4.3-py3-none-any.whl",
+            "pytest-7.4.2-py3-none-any.whl",
+            ".gitkeep",
+        ] {
+            std::fs::write(vendor_dir.join(it), "").unwrap();
+        }
+
+        assert_eq!(
+            vendor_problems(&vendor_dir, &locked).unwrap(),
+            [
+                "ruff is missing".to_string(),
+                format!(
+                    "{} is extraneous",
+                    vendor_dir.join("pytest-7.4.2-py3-none-any.whl").display()
+                ),
+            ]
+        );
+
+        std::fs::remove_file(vendor_dir.join("pytest-7.4.2-py3-none-any.whl")).unwrap();
+        std::fs::write(vendor_dir.join("ruff-0.1.6.tar.gz"), "").unwrap();
+
+        assert!(vendor_problems(&vendor_dir, &locked).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_lfs_pattern() {
+        let dir = tempdir().unwrap();
+        let vendor_dir = dir.path().join("vendor");
+
+        assert_eq!(
+            lfs_pattern(&vendor_dir, dir.path()).as_deref(),
+            Some("vendor/** filter=lfs diff=lfs merge=lfs -text")
+        );
+
+        std::fs::write(
+            dir.path().join(".gitattributes"),
+            "vendor/** filter=lfs diff=lfs merge=lfs -text\n",
+        )
+        .unwrap();
+
+        assert!(lfs_pattern(&vendor_dir, dir.path()).is_none());
+    }
+}
@@ -1,16 +1,322 @@
-use crate::{Config, Error, HuakResult};
+use super::{install_locked_tool, OutputFormat};
+use crate::{
+    Config, Dependency, Error, HuakResult, InstallOptions, LocalManifest, PythonEnvironment,
+    TerminalOptions, Verbosity,
+};
+use pep440_rs::Version;
+use serde::Serialize;
+use std::{process::Command, str::FromStr};
 use termcolor::Color;
 
+/// Print the version the project's build backend resolves, preferring the backend installed in
+/// the environment and falling back to an isolated build environment.
+const RESOLVE_VERSION_SCRIPT: &str = r#"import sys
+from build.util import project_wheel_metadata
+
+try:
+    metadata = project_wheel_metadata(sys.argv[1], isolated=False)
+except Exception:
+    metadata = project_wheel_metadata(sys.argv[1])
+
+print(metadata["Version"])
+"#;
+
 #[allow(clippy::module_name_repetitions)]
 pub fn display_project_version(config: &Config) -> HuakResult<()> {
+    let version = project_version(config)?;
+
+    config
+        .terminal()
+        .print_custom("version", version, Color::Green, false)
+}
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Default)]
+pub struct VersionOptions {
+    /// Check the project's version is newer than this PEP 440 version.
+    pub is_newer_than: Option<String>,
+    /// Check the project's version is a pre-release (or a development release).
+    pub is_prerelease: bool,
+    pub format: OutputFormat,
+}
+
+impl VersionOptions {
+    /// Check if any of the version checks were requested.
+    #[must_use]
+    pub fn has_checks(&self) -> bool {
+        self.is_newer_than.is_some() || self.is_prerelease
+    }
+}
+
+/// The project's version and the results of its requested checks.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct VersionReport {
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_newer_than: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_prerelease: Option<bool>,
+}
+
+impl VersionReport {
+    /// Check if every requested check passed.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.is_newer_than != Some(false) && self.is_prerelease != Some(false)
+    }
+}
+
+/// Check the project's version against the `VersionOptions`, like for gating release steps in
+/// scripts. Nothing is printed; use `VersionReport::passed` for the result.
+pub fn project_version_report(
+    config: &Config,
+    options: &VersionOptions,
+) -> HuakResult<VersionReport> {
+    // The comparison version is validated before a dynamic version is resolved.
+    let is_newer_than = options
+        .is_newer_than
+        .as_deref()
+        .map(parse_version)
+        .transpose()?;
+    let quiet = config.clone().with_terminal(TerminalOptions {
+        verbosity: Verbosity::Quiet,
+        ..config.terminal_options.clone()
+    });
+    let version = project_version(&quiet)?;
+
+    version_report(&version, is_newer_than.as_ref(), options.is_prerelease)
+}
+
+/// Check the `version` is newer than `is_newer_than` and a pre-release if `is_prerelease`.
+fn version_report(
+    version: &str,
+    is_newer_than: Option<&Version>,
+    is_prerelease: bool,
+) -> HuakResult<VersionReport> {
+    let parsed = parse_version(version)?;
+
+    Ok(VersionReport {
+        version: version.to_string(),
+        is_newer_than: is_newer_than.map(|it| parsed > *it),
+        is_prerelease: is_prerelease.then(|| parsed.any_prerelease()),
+    })
+}
+
+/// Parse a PEP 440 `version`.
+fn parse_version(version: &str) -> HuakResult<Version> {
+    Version::from_str(version).map_err(|e| Error::InvalidVersionString {
+        version: version.to_string(),
+        reason: e,
+    })
+}
+
+/// Get the project's version, asking its build backend if the version is dynamic.
+pub(crate) fn project_version(config: &Config) -> HuakResult<String> {
     let workspace = config.workspace();
-    let package = workspace.current_package()?;
+    let manifest = workspace.current_local_manifest()?;
+
+    if manifest.manifest_data().is_project_field_dynamic("version") {
+        let python_env = workspace.resolve_python_environment()?;
+        return resolve_dynamic_version(&manifest, &python_env, &InstallOptions::default(), config);
+    }
 
-    let Some(version) = package.manifest_data().project_version() else {
-        return Err(Error::PackageVersionNotFound);
+    manifest
+        .manifest_data()
+        .project_version()
+        .ok_or(Error::PackageVersionNotFound)
+}
+
+/// Ask the project's build backend for its dynamic version (like one derived from VCS tags
+/// by setuptools-scm).
+///
+/// `build` is installed to the `PythonEnvironment` at its pinned version.
+pub(crate) fn resolve_dynamic_version(
+    manifest: &LocalManifest,
+    python_env: &PythonEnvironment,
+    install_options: &InstallOptions,
+    config: &Config,
+) -> HuakResult<String> {
+    let root = manifest.path().parent().ok_or(Error::InternalError(
+        "failed to establish parent directory".to_string(),
+    ))?;
+
+    let build_dep = Dependency::from_str("build")?;
+    install_locked_tool(&build_dep, python_env, install_options, config)?;
+
+    let output = Command::new(python_env.python_path())
+        .args(["-c", RESOLVE_VERSION_SCRIPT])
+        .arg(root)
+        .current_dir(root)
+        .output()?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let version = stdout.lines().map(str::trim).rfind(|it| !it.is_empty());
+
+    match version {
+        Some(it) if output.status.success() => Ok(it.to_string()),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(Error::DynamicVersionUnresolved(
+                stderr
+                    .lines()
+                    .map(str::trim)
+                    .rfind(|it| !it.is_empty())
+                    .unwrap_or("no version was reported")
+                    .to_string(),
+            ))
+        }
+    }
+}
+
+/// Write a PEP 440 `version` to the project's pyproject.toml.
+pub fn set_project_version(version: &str, config: &Config) -> HuakResult<()> {
+    let version = parse_version(version)?.to_string();
+
+    let workspace = config.workspace();
+    let mut manifest = workspace.current_local_manifest()?;
+
+    if manifest.manifest_data().is_project_field_dynamic("version") {
+        return Err(Error::DynamicProjectVersion);
+    }
+
+    let previous = manifest.manifest_data().project_version();
+    manifest.manifest_data_mut().set_project_version(&version);
+    config.write_manifest(&manifest)?;
+
+    let message = match previous {
+        Some(it) => format!("version {it} -> {version}"),
+        None => format!("version {version}"),
     };
 
     config
         .terminal()
-        .print_custom("version", version, Color::Green, false)
+        .print_custom("Updated", message, Color::Green, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{copy_dir, initialize_venv, CopyDirOptions, TerminalOptions, Verbosity};
+    use huak_dev::dev_resources_dir;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_resolve_dynamic_version() {
+        let dir = tempdir().unwrap();
+        copy_dir(
+            &dev_resources_dir().join("mock-project"),
+            &dir.path().join("mock-project"),
+            &CopyDirOptions::default(),
+        )
+        .unwrap();
+        let workspace_root = dir.path().join("mock-project");
+        let cwd = workspace_root.clone();
+        let terminal_options = TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+        let config = Config {
+            workspace_root,
+            cwd,
+            terminal_options,
+            ..Default::default()
+        };
+        let ws = config.workspace();
+        let mut manifest = ws.current_local_manifest().unwrap();
+        manifest
+            .manifest_data_mut()
+            .project_table_mut()
+            .unwrap()
+            .remove("version");
+        manifest.manifest_data_mut().doc["project"]["dynamic"] =
+            toml_edit::value(toml_edit::Array::from_iter(["version"]));
+        manifest.manifest_data_mut().doc["tool"]["hatch"]["version"]["path"] =
+            toml_edit::value("src/mock_project/__init__.py");
+        manifest.write_file().unwrap();
+        std::fs::write(
+            ws.root()
+                .join("src")
+                .join("mock_project")
+                .join("__init__.py"),
+            "__version__ = \"1.2.3\"\n",
+        )
+        .unwrap();
+        initialize_venv(ws.root().join(".venv"), &ws.environment()).unwrap();
+        let python_env = ws.resolve_python_environment().unwrap();
+
+        let manifest = ws.current_local_manifest().unwrap();
+        let version =
+            resolve_dynamic_version(&manifest, &python_env, &InstallOptions::default(), &config)
+                .unwrap();
+
+        assert_eq!(version, "1.2.3");
+        assert!(matches!(
+            set_project_version("2.0.0", &config),
+            Err(Error::DynamicProjectVersion)
+        ));
+    }
+
+    #[test]
+    fn test_version_report() {
+        let version = |it: &str| Version::from_str(it).unwrap();
+        let report = version_report("1.4.0", Some(&version("1.3.9")), true).unwrap();
+
+        assert_eq!(report.is_newer_than, Some(true));
+        assert_eq!(report.is_prerelease, Some(false));
+        assert!(!report.passed());
+
+        let report = version_report("1.4.0rc1", Some(&version("1.4.0")), true).unwrap();
+
+        assert_eq!(report.is_newer_than, Some(false));
+        assert_eq!(report.is_prerelease, Some(true));
+
+        let report = version_report("1.4.0.dev2", None, true).unwrap();
+
+        assert_eq!(report.is_newer_than, None);
+        assert!(report.passed());
+        assert!(!version_report("1.4", Some(&version("1.4.0")), false)
+            .unwrap()
+            .passed());
+        assert!(matches!(
+            version_report("not-a-version", None, true),
+            Err(Error::InvalidVersionString { .. })
+        ));
+    }
+
+    #[test]
+    fn test_set_project_version() {
+        let dir = tempdir().unwrap();
+        copy_dir(
+            &dev_resources_dir().join("mock-project"),
+            &dir.path().join("mock-project"),
+            &CopyDirOptions::default(),
+        )
+        .unwrap();
+        let workspace_root = dir.path().join("mock-project");
+        let cwd = workspace_root.clone();
+        let terminal_options = TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+        let config = Config {
+            workspace_root,
+            cwd,
+            terminal_options,
+            ..Default::default()
+        };
+
+        set_project_version("2.0.0-RC1", &config).unwrap();
+
+        let manifest = config.workspace().current_local_manifest().unwrap();
+
+        assert_eq!(
+            manifest.manifest_data().project_version().unwrap(),
+            "2.0.0rc1"
+        );
+        assert!(matches!(
+            set_project_version("2.0.0.final.x", &config),
+            Err(Error::InvalidVersionString { .. })
+        ));
+    }
 }
@@ -62,8 +62,10 @@ impl Package {
         Ok(Self {
             id: PackageId {
                 name,
-                version: Version::from_str(&version)
-                    .map_err(|e| Error::InvalidVersionString(e.to_string()))?,
+                version: Version::from_str(&version).map_err(|e| Error::InvalidVersionString {
+                    version: version.clone(),
+                    reason: e.to_string(),
+                })?,
             },
             manifest_data: manifest.manifest_data().clone(),
         })
@@ -84,23 +86,27 @@ impl Package {
         // the name.
         let s = s.as_ref();
         let spec_str =
-            parse_version_specifiers_str(s).ok_or(Error::InvalidVersionString(s.to_string()))?;
+            parse_version_specifiers_str(s).ok_or_else(|| Error::InvalidVersionString {
+                version: s.to_string(),
+                reason: "expected a version specifier".to_string(),
+            })?;
         let name = s.strip_suffix(spec_str).unwrap_or(s).to_string();
         let version_specifiers = VersionSpecifiers::from_str(spec_str)?;
 
         // Since we only want to define `Package`s as having a specific `Version`,
         // a `Package` cannot be initialized with multiple `VersionSpecifier`s.
         if version_specifiers.len() > 1 {
-            return Err(Error::InvalidVersionString(format!(
-                "{s} can only contain one version specifier"
-            )));
+            return Err(Error::InvalidVersionString {
+                version: s.to_string(),
+                reason: "can only contain one version specifier".to_string(),
+            });
         }
         let version_specifer = version_specifiers.first().unwrap();
         if version_specifer.operator() != &Operator::Equal {
-            return Err(Error::InvalidVersionString(format!(
-                "{s} must contain {} specifier",
-                Operator::Equal
-            )));
+            return Err(Error::InvalidVersionString {
+                version: s.to_string(),
+                reason: format!("must contain {} specifier", Operator::Equal),
+            });
         }
 
         let id = PackageId {
@@ -0,0 +1,396 @@
+use crate::{Error, HuakResult};
+use huak_pyproject_toml::canonical_name;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt::Display,
+};
+use toml_edit::Item;
+
+/// Policies limiting the dependencies a project can pull in.
+///
+/// ```toml
+/// [tool.huak.policy]
+/// max-dependencies = 50
+/// max-transitive-per-direct = 10
+/// deny = ["left-pad"]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DependencyPolicy {
+    /// The maximum number of packages the project can resolve, including transitive packages.
+    pub max_dependencies: Option<usize>,
+    /// The maximum number of transitive packages any single direct dependency can pull in.
+    pub max_transitive_per_direct: Option<usize>,
+    /// Packages the project must not depend on, directly or transitively.
+    pub deny: Vec<String>,
+}
+
+impl DependencyPolicy {
+    /// Read a `DependencyPolicy` from a `[tool.huak.policy]` table.
+    pub fn from_item(item: &Item) -> HuakResult<DependencyPolicy> {
+        let Some(table) = item.as_table_like() else {
+            return Err(policy_error("policy must be a table"));
+        };
+
+        let limit = |key: &str| -> HuakResult<Option<usize>> {
+            table
+                .get(key)
+                .map(|it| {
+                    it.as_integer()
+                        .and_then(|it| usize::try_from(it).ok())
+                        .ok_or_else(|| {
+                            policy_error(&format!("{key} must be a non-negative integer"))
+                        })
+                })
+                .transpose()
+        };
+
+        let deny = match table.get("deny") {
+            Some(it) => it
+                .as_array()
+                .and_then(|it| {
+                    it.iter()
+                        .map(|it| it.as_str().map(canonical_name))
+                        .collect::<Option<Vec<_>>>()
+                })
+                .ok_or_else(|| policy_error("deny must be an array of package names"))?,
+            None => Vec::new(),
+        };
+
+        Ok(DependencyPolicy {
+            max_dependencies: limit("max-dependencies")?,
+            max_transitive_per_direct: limit("max-transitive-per-direct")?,
+            deny,
+        })
+    }
+
+    /// Check if the `DependencyPolicy` has nothing to enforce.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.max_dependencies.is_none()
+            && self.max_transitive_per_direct.is_none()
+            && self.deny.is_empty()
+    }
+
+    /// Get the `PolicyViolation`s of a project with `direct` dependencies resolved to `graph`.
+    #[must_use]
+    pub fn violations<T: AsRef<str>>(
+        &self,
+        graph: &DependencyGraph,
+        direct: &[T],
+    ) -> Vec<PolicyViolation> {
+        let mut direct = direct
+            .iter()
+            .map(|it| canonical_name(it.as_ref()))
+            .collect::<Vec<_>>();
+        direct.sort();
+        direct.dedup();
+
+        let chains = direct
+            .iter()
+            .map(|it| (it.as_str(), graph.chains(it)))
+            .collect::<BTreeMap<_, _>>();
+        let mut violations = Vec::new();
+
+        if let Some(max) = self.max_dependencies {
+            let resolved = chains
+                .iter()
+                .flat_map(|(name, chains)| {
+                    std::iter::once(*name).chain(chains.iter().map(|it| it[it.len() - 1].as_str()))
+                })
+                .collect::<HashSet<_>>();
+
+            if resolved.len() > max {
+                let mut contributions = chains
+                    .iter()
+                    .map(|(name, chains)| ((*name).to_string(), chains.len()))
+                    .collect::<Vec<_>>();
+                contributions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+                violations.push(PolicyViolation::TooManyDependencies {
+                    count: resolved.len(),
+                    max,
+                    contributions,
+                });
+            }
+        }
+
+        if let Some(max) = self.max_transitive_per_direct {
+            for (name, chains) in &chains {
+                if chains.len() > max {
+                    violations.push(PolicyViolation::TooManyTransitive {
+                        direct: (*name).to_string(),
+                        max,
+                        chains: chains.clone(),
+                    });
+                }
+            }
+        }
+
+        for denied in &self.deny {
+            let chain = if direct.contains(denied) {
+                Some(vec![denied.clone()])
+            } else {
+                chains
+                    .values()
+                    .flatten()
+                    .filter(|it| it.last() == Some(denied))
+                    .min_by_key(|it| it.len())
+                    .cloned()
+            };
+
+            if let Some(chain) = chain {
+                violations.push(PolicyViolation::Denied { chain });
+            }
+        }
+
+        violations
+    }
+}
+
+fn policy_error(message: &str) -> Error {
+    Error::HuakConfigurationError {
+        table: "tool.huak.policy".to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// The installed packages of an environment and the packages each of them requires.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DependencyGraph {
+    requirements: HashMap<String, Vec<String>>,
+}
+
+impl DependencyGraph {
+    #[must_use]
+    pub fn new(requirements: HashMap<String, Vec<String>>) -> DependencyGraph {
+        let requirements = requirements
+            .into_iter()
+            .map(|(name, requires)| {
+                let mut requires = requires
+                    .iter()
+                    .map(|it| canonical_name(it))
+                    .collect::<Vec<_>>();
+                requires.sort();
+                requires.dedup();
+                (canonical_name(&name), requires)
+            })
+            .collect();
+
+        DependencyGraph { requirements }
+    }
+
+    /// Get the names of the installed packages.
+    pub fn packages(&self) -> impl Iterator<Item = &str> {
+        self.requirements.keys().map(String::as_str)
+    }
+
+    /// Get the packages `roots` resolve to, including the `roots`.
+    #[must_use]
+    pub fn resolved<T: AsRef<str>>(&self, roots: &[T]) -> BTreeSet<String> {
+        let roots = roots
+            .iter()
+            .map(|it| canonical_name(it.as_ref()))
+            .collect::<Vec<_>>();
+        let transitive = roots
+            .iter()
+            .flat_map(|it| self.chains(it))
+            .filter_map(|mut it| it.pop())
+            .collect::<Vec<_>>();
+
+        roots.into_iter().chain(transitive).collect()
+    }
+
+    /// Get the shortest chain of requirements from `root` to each package it pulls in.
+    ///
+    /// Each chain starts with `root` and ends with the package pulled in.
+    fn chains<'a>(&'a self, root: &'a str) -> Vec<Vec<String>> {
+        let mut parents = HashMap::new();
+        let mut seen = HashSet::from([root]);
+        let mut queue = VecDeque::from([root]);
+        let mut order = Vec::new();
+
+        while let Some(name) = queue.pop_front() {
+            for it in self.requirements.get(name).into_iter().flatten() {
+                if seen.insert(it.as_str()) {
+                    parents.insert(it.as_str(), name);
+                    order.push(it.as_str());
+                    queue.push_back(it.as_str());
+                }
+            }
+        }
+
+        order
+            .into_iter()
+            .map(|name| {
+                let mut chain = vec![name.to_string()];
+                let mut current = name;
+                while let Some(parent) = parents.get(current) {
+                    chain.push((*parent).to_string());
+                    current = parent;
+                }
+                chain.reverse();
+                chain
+            })
+            .collect()
+    }
+}
+
+/// A way a project's dependencies break its `DependencyPolicy`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// The project resolves more packages than `max-dependencies` allows.
+    TooManyDependencies {
+        count: usize,
+        max: usize,
+        /// The number of transitive packages each direct dependency pulls in.
+        contributions: Vec<(String, usize)>,
+    },
+    /// A direct dependency pulls in more packages than `max-transitive-per-direct` allows.
+    TooManyTransitive {
+        direct: String,
+        max: usize,
+        chains: Vec<Vec<String>>,
+    },
+    /// A denied package is pulled in by the chain of requirements.
+    Denied { chain: Vec<String> },
+}
+
+impl Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyViolation::TooManyDependencies {
+                count,
+                max,
+                contributions,
+            } => {
+                write!(
+                    f,
+                    "the project resolves {count} dependencies (max-dependencies is {max})"
+                )?;
+                for (name, count) in contributions {
+                    write!(f, "\n  {name} pulls in {count} transitive packages")?;
+                }
+                Ok(())
+            }
+            PolicyViolation::TooManyTransitive {
+                direct,
+                max,
+                chains,
+            } => {
+                write!(
+                    f,
+                    "{direct} pulls in {} transitive packages (max-transitive-per-direct is {max})",
+                    chains.len()
+                )?;
+                for chain in chains {
+                    write!(f, "\n  {}", chain.join(" -> "))?;
+                }
+                Ok(())
+            }
+            PolicyViolation::Denied { chain } => write!(
+                f,
+                "{} is denied by policy\n  {}",
+                chain[chain.len() - 1],
+                chain.join(" -> ")
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_graph() -> DependencyGraph {
+        DependencyGraph::new(HashMap::from([
+            (
+                "Flask".to_string(),
+                vec!["Werkzeug".to_string(), "jinja2".to_string()],
+            ),
+            ("werkzeug".to_string(), vec!["MarkupSafe".to_string()]),
+            ("jinja2".to_string(), vec!["markupsafe".to_string()]),
+            ("markupsafe".to_string(), vec![]),
+            ("ruff".to_string(), vec![]),
+        ]))
+    }
+
+    #[test]
+    fn test_dependency_policy_from_item() {
+        let item = r#"
+max-dependencies = 5
+deny = ["Left_Pad"]
+"#
+        .parse::<toml_edit::Document>()
+        .unwrap();
+        let policy = DependencyPolicy::from_item(item.as_item()).unwrap();
+
+        assert_eq!(
+            policy,
+            DependencyPolicy {
+                max_dependencies: Some(5),
+                max_transitive_per_direct: None,
+                deny: vec!["left-pad".to_string()],
+            }
+        );
+
+        let item = "max-dependencies = -1"
+            .parse::<toml_edit::Document>()
+            .unwrap();
+
+        assert!(DependencyPolicy::from_item(item.as_item()).is_err());
+    }
+
+    #[test]
+    fn test_policy_violations() {
+        let graph = mock_graph();
+        let policy = DependencyPolicy {
+            max_dependencies: Some(4),
+            max_transitive_per_direct: Some(2),
+            deny: vec!["markupsafe".to_string(), "left-pad".to_string()],
+        };
+
+        assert_eq!(
+            policy.violations(&graph, &["flask", "ruff"]),
+            vec![
+                PolicyViolation::TooManyDependencies {
+                    count: 5,
+                    max: 4,
+                    contributions: vec![("flask".to_string(), 3), ("ruff".to_string(), 0)],
+                },
+                PolicyViolation::TooManyTransitive {
+                    direct: "flask".to_string(),
+                    max: 2,
+                    chains: vec![
+                        vec!["flask".to_string(), "jinja2".to_string()],
+                        vec!["flask".to_string(), "werkzeug".to_string()],
+                        vec![
+                            "flask".to_string(),
+                            "jinja2".to_string(),
+                            "markupsafe".to_string()
+                        ],
+                    ],
+                },
+                PolicyViolation::Denied {
+                    chain: vec![
+                        "flask".to_string(),
+                        "jinja2".to_string(),
+                        "markupsafe".to_string()
+                    ],
+                },
+            ]
+        );
+        assert!(DependencyPolicy::default()
+            .violations(&graph, &["flask"])
+            .is_empty());
+    }
+
+    #[test]
+    fn test_dependency_graph_resolved() {
+        assert_eq!(
+            mock_graph().resolved(&["Flask", "left_pad"]),
+            BTreeSet::from(
+                ["flask", "jinja2", "left-pad", "markupsafe", "werkzeug"].map(String::from)
+            )
+        );
+    }
+}
@@ -2,15 +2,18 @@ use crate::{
     environment::env_path_values,
     fs::{self, maybe_exe},
     package::Package,
-    sys, Config, Environment, Error, HuakResult,
+    sys, Config, Dependency, DependencyGraph, Environment, Error, HuakResult, Verbosity,
 };
+use huak_pyproject_toml::canonical_name;
 use huak_python_manager::Version;
+use serde::Deserialize;
 use std::{
     cmp::Ordering,
+    collections::HashMap,
     env::consts::OS,
     fmt::Display,
     fs::File,
-    io::{BufRead, BufReader},
+    io::{BufRead, BufReader, Write},
     path::{Path, PathBuf},
     process::Command,
     str::FromStr,
@@ -18,6 +21,50 @@ use std::{
 
 const DEFAULT_VENV_NAME: &str = ".venv";
 const VENV_CONFIG_FILE_NAME: &str = "pyvenv.cfg";
+/// Packages `pip freeze` leaves out since they're the environment's own tooling.
+const FREEZE_EXCLUDED_PACKAGES: [&str; 4] = ["distribute", "pip", "setuptools", "wheel"];
+/// Print the installed distributions and the distributions they require as JSON.
+///
+/// Requirements only needed for extras or other platforms are skipped.
+const DEPENDENCY_GRAPH_SCRIPT: &str = r#"
+import json
+from importlib import metadata
+
+try:
+    from packaging.requirements import Requirement
+except ImportError:
+    from pip._vendor.packaging.requirements import Requirement
+
+graph = {}
+for dist in metadata.distributions():
+    requires = []
+    for line in dist.requires or []:
+        req = Requirement(line)
+        if req.marker is None or req.marker.evaluate({"extra": ""}):
+            requires.append(req.name)
+    graph[dist.metadata["Name"]] = requires
+
+print(json.dumps(graph))
+"#;
+/// Print the install scheme of a Python interpreter's own installation as JSON.
+///
+/// The `EXTERNALLY-MANAGED` marker is ignored from within virtual environments (see PEP 668).
+const SYSTEM_ENVIRONMENT_SCRIPT: &str = r#"
+import json
+import os
+import sys
+import sysconfig
+
+marker = os.path.join(sysconfig.get_path("stdlib"), "EXTERNALLY-MANAGED")
+
+print(json.dumps({
+    "prefix": sys.prefix,
+    "scripts": sysconfig.get_path("scripts"),
+    "purelib": sysconfig.get_path("purelib"),
+    "version": "%d.%d.%d" % sys.version_info[:3],
+    "externally_managed": sys.prefix == sys.base_prefix and os.path.isfile(marker),
+}))
+"#;
 const VIRTUAL_ENV_ENV_VAR: &str = "VIRTUAL_ENV";
 const CONDA_ENV_ENV_VAR: &str = "CONDA_PREFIX";
 
@@ -71,6 +118,10 @@ pub struct PythonEnvironment {
     executables_dir_path: PathBuf,
     /// The site-packages directory contains all of the `PythonEnvironment`'s installed Python packages.
     site_packages_path: PathBuf,
+    /// Whether the `PythonEnvironment` is a Python installation rather than a virtual environment.
+    system: bool,
+    /// Whether the `PythonEnvironment` is marked as externally managed (see PEP 668).
+    externally_managed: bool,
 }
 
 impl PythonEnvironment {
@@ -80,7 +131,7 @@ impl PythonEnvironment {
 
         // TODO(cnpryer): `PythonEnvironment` shouldn't be exclusive to virtual environments.
         if !directory_is_venv(&path) {
-            return Err(Error::UnsupportedPythonEnvironment(path));
+            return Err(Error::UnsupportedPythonEnvironment { path });
         }
 
         let env = new_venv(path)?;
@@ -88,6 +139,39 @@ impl PythonEnvironment {
         Ok(env)
     }
 
+    /// Initialize a `PythonEnvironment` for the installation of the Python interpreter at
+    /// `python_path`.
+    ///
+    /// Packages are installed into the interpreter's own site-packages directory. This is
+    /// meant for environments like containers where a virtual environment isn't wanted.
+    pub fn system<T: Into<PathBuf>>(python_path: T) -> HuakResult<Self> {
+        let python_path = python_path.into();
+        let mut cmd = Command::new(&python_path);
+        cmd.args(["-c", SYSTEM_ENVIRONMENT_SCRIPT]);
+
+        let output = cmd.output()?;
+
+        if !output.status.success() {
+            return Err(Error::PythonNotFound);
+        }
+
+        let scheme: SystemScheme = serde_json::from_slice(&output.stdout)?;
+
+        let interpreter = Interpreter {
+            version: Version::from_str(&scheme.version)?,
+            path: python_path,
+        };
+
+        Ok(PythonEnvironment {
+            root: scheme.prefix,
+            interpreter,
+            executables_dir_path: scheme.scripts,
+            site_packages_path: scheme.purelib,
+            system: true,
+            externally_managed: scheme.externally_managed,
+        })
+    }
+
     /// Get a reference to the path to the `PythonEnvironment`.
     #[must_use]
     pub fn root(&self) -> &Path {
@@ -105,6 +189,12 @@ impl PythonEnvironment {
         self.interpreter.path()
     }
 
+    /// Get a reference to the `Version` of the `PythonEnvironment`'s Python `Interpreter`.
+    #[must_use]
+    pub fn python_version(&self) -> &Version {
+        self.interpreter.version()
+    }
+
     /// Get a reference to the `PythonEnvironment`'s executables directory path.
     #[must_use]
     pub fn executables_dir_path(&self) -> &PathBuf {
@@ -129,6 +219,39 @@ impl PythonEnvironment {
         &self.site_packages_path
     }
 
+    /// Check if the `PythonEnvironment` is a Python installation rather than a virtual
+    /// environment.
+    #[must_use]
+    pub fn is_system(&self) -> bool {
+        self.system
+    }
+
+    /// Check if the `PythonEnvironment` is marked as externally managed (see PEP 668).
+    #[must_use]
+    pub fn is_externally_managed(&self) -> bool {
+        self.externally_managed
+    }
+
+    /// Get a `Command` running pip for changes to the `PythonEnvironment`.
+    ///
+    /// Externally managed environments are only changed with `config.break_system_packages`.
+    fn pip_command(&self, config: &Config) -> HuakResult<Command> {
+        if self.externally_managed && !config.break_system_packages {
+            return Err(Error::ExternallyManagedEnvironment {
+                path: self.root.clone(),
+            });
+        }
+
+        let mut cmd = Command::new(self.python_path());
+        cmd.args(["-m", "pip"]);
+
+        if self.externally_managed {
+            cmd.env("PIP_BREAK_SYSTEM_PACKAGES", "1");
+        }
+
+        Ok(cmd)
+    }
+
     /// Install Python `Package`s to the `PythonEnvironment`.
     pub fn install_packages<T>(
         &self,
@@ -139,15 +262,68 @@ impl PythonEnvironment {
     where
         T: Display,
     {
-        let mut cmd = Command::new(self.python_path());
-        cmd.args(["-m", "pip", "install"])
-            .args(packages.iter().map(ToString::to_string));
+        let mut cmd = self.pip_command(config)?;
+        cmd.arg("install")
+            .args(packages.iter().map(ToString::to_string))
+            .args(options.args(config)?)
+            .envs(options.envs());
 
-        if let Some(v) = options.values.as_ref() {
-            cmd.args(v.iter().map(String::as_str));
+        config.run_effect("install", &mut cmd)
+    }
+
+    /// Install the project at `path` to the `PythonEnvironment` in editable mode.
+    pub fn install_editable(
+        &self,
+        path: &Path,
+        options: &InstallOptions,
+        config: &Config,
+    ) -> HuakResult<()> {
+        let mut cmd = self.pip_command(config)?;
+        cmd.args(["install", "-e"])
+            .arg(path)
+            .args(options.args(config)?)
+            .envs(options.envs());
+
+        config.run_effect("install", &mut cmd)
+    }
+
+    /// Install the `Dependency`s with hashes, verifying their distributions match.
+    ///
+    /// The installer requires every requirement to be hashed once any are, so the hashed
+    /// `Dependency`s are installed without their own dependencies. Install them again with
+    /// `install_packages` to resolve those.
+    pub fn install_hashed_packages(
+        &self,
+        dependencies: &[Dependency],
+        options: &InstallOptions,
+        config: &Config,
+    ) -> HuakResult<()> {
+        let requirements = dependencies
+            .iter()
+            .filter(|it| !it.hashes().is_empty())
+            .map(|it| {
+                let hashes = it.hashes().iter().map(|it| format!("--hash={it}"));
+                std::iter::once(it.to_string())
+                    .chain(hashes)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>();
+
+        if requirements.is_empty() {
+            return Ok(());
         }
 
-        config.terminal().run_command(&mut cmd)
+        let mut file = tempfile::NamedTempFile::new()?;
+        writeln!(file, "{}", requirements.join("\n"))?;
+
+        let mut cmd = self.pip_command(config)?;
+        cmd.args(["install", "--require-hashes", "--no-deps", "-r"])
+            .arg(file.path())
+            .args(options.args(config)?)
+            .envs(options.envs());
+
+        config.run_effect("install", &mut cmd)
     }
 
     /// Uninstall Python `Package`s from the `PythonEnvironment`.
@@ -160,8 +336,8 @@ impl PythonEnvironment {
     where
         T: Display,
     {
-        let mut cmd = Command::new(self.python_path());
-        cmd.args(["-m", "pip", "uninstall"])
+        let mut cmd = self.pip_command(config)?;
+        cmd.arg("uninstall")
             .args(packages.iter().map(ToString::to_string))
             .arg("-y");
 
@@ -169,7 +345,7 @@ impl PythonEnvironment {
             cmd.args(v.iter().map(String::as_str));
         }
 
-        config.terminal().run_command(&mut cmd)
+        config.run_effect("install", &mut cmd)
     }
 
     /// Update Python `Package`s installed in the `PythonEnvironment`.
@@ -182,15 +358,13 @@ impl PythonEnvironment {
     where
         T: Display,
     {
-        let mut cmd = Command::new(self.python_path());
-        cmd.args(["-m", "pip", "install", "--upgrade"])
-            .args(packages.iter().map(ToString::to_string));
-
-        if let Some(v) = options.values.as_ref() {
-            cmd.args(v.iter().map(String::as_str));
-        }
+        let mut cmd = self.pip_command(config)?;
+        cmd.args(["install", "--upgrade"])
+            .args(packages.iter().map(ToString::to_string))
+            .args(options.args(config)?)
+            .envs(options.envs());
 
-        config.terminal().run_command(&mut cmd)
+        config.run_effect("install", &mut cmd)
     }
 
     /// Check if the `PythonEnvironment` has a module installed in the executables directory.
@@ -217,23 +391,112 @@ impl PythonEnvironment {
         self.site_packages_dir_path().join(package.name()).exists()
     }
 
+    /// Get the versions of the packages installed in the `PythonEnvironment`, keyed by
+    /// canonical name.
+    ///
+    /// Versions are read from the names of the packages' `.dist-info` metadata directories,
+    /// which is much faster than asking the installer.
+    pub fn installed_versions(&self) -> HuakResult<HashMap<String, pep440_rs::Version>> {
+        let entries = match std::fs::read_dir(self.site_packages_dir_path()) {
+            Ok(it) => it,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(entries
+            .filter_map(Result::ok)
+            .filter_map(|it| {
+                let file_name = it.file_name().to_string_lossy().to_string();
+                let (name, version) = file_name.strip_suffix(".dist-info")?.rsplit_once('-')?;
+                let version = pep440_rs::Version::from_str(version).ok()?;
+
+                Some((canonical_name(name), version))
+            })
+            .collect())
+    }
+
     /// Get all of the `Package`s installed in the `PythonEnvironment`.
     pub fn installed_packages(&self) -> HuakResult<Vec<Package>> {
+        // `pip freeze` prints packages installed from a file or URL as direct references, so
+        // list every package as `name==version` and leave out the same tooling it does.
         let mut cmd = Command::new(self.python_path());
-        cmd.args(["-m", "pip", "freeze"]);
+        cmd.args(["-m", "pip", "list", "--format=freeze"]);
 
         let output = cmd.output()?;
-        let output = sys::parse_command_output(&output)?;
+        let output = sys::parse_command_output(&output);
         let mut packages = Vec::new();
         for line in output.split('\n') {
             if !line.is_empty() {
-                packages.push(Package::from_str(line)?);
+                let package = Package::from_str(line)?;
+                if !FREEZE_EXCLUDED_PACKAGES.contains(&canonical_name(package.name()).as_str()) {
+                    packages.push(package);
+                }
             }
         }
 
         Ok(packages)
     }
 
+    /// Get the `DependencyGraph` of the packages installed in the `PythonEnvironment`.
+    pub fn dependency_graph(&self) -> HuakResult<DependencyGraph> {
+        let mut cmd = Command::new(self.python_path());
+        cmd.args(["-c", DEPENDENCY_GRAPH_SCRIPT]);
+
+        let output = cmd.output()?;
+        let output = sys::parse_command_output(&output);
+
+        Ok(DependencyGraph::new(serde_json::from_str(&output)?))
+    }
+
+    /// Get the index pip is configured to list packages from (`PIP_INDEX_URL`, or an `index-url`
+    /// in pip's configuration files), if one is set.
+    pub fn pip_index_url(&self) -> HuakResult<Option<String>> {
+        let mut cmd = Command::new(self.python_path());
+        cmd.args(["-m", "pip", "config", "list"]);
+
+        let output = cmd.output()?;
+
+        Ok(parse_pip_index_url(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
+    /// Get the latest version of each installed package with a newer one on the `index_url`,
+    /// asking pip. This works with any PEP 503 index, not just ones with a JSON API.
+    pub fn outdated_versions(
+        &self,
+        index_url: &str,
+    ) -> HuakResult<HashMap<String, pep440_rs::Version>> {
+        let mut cmd = Command::new(self.python_path());
+        cmd.args([
+            "-m",
+            "pip",
+            "list",
+            "--outdated",
+            "--format=json",
+            "--disable-pip-version-check",
+            "--index-url",
+            index_url,
+        ]);
+
+        let output = cmd.output()?;
+        if !output.status.success() {
+            return Err(Error::PackageIndexUnavailable(format!(
+                "{index_url}: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let packages: Vec<PipOutdatedPackage> = serde_json::from_slice(&output.stdout)?;
+
+        Ok(packages
+            .into_iter()
+            .filter_map(|it| {
+                let version = pep440_rs::Version::from_str(&it.latest_version).ok()?;
+                Some((canonical_name(&it.name), version))
+            })
+            .collect())
+    }
+
     /// Check if the `PythonEnvironment` is already activated.
     #[must_use]
     pub fn active(&self) -> bool {
@@ -277,11 +540,38 @@ fn new_venv<T: Into<PathBuf>>(path: T) -> HuakResult<PythonEnvironment> {
         interpreter,
         executables_dir_path,
         site_packages_path,
+        system: false,
+        externally_managed: false,
     };
 
     Ok(venv)
 }
 
+/// A package in `pip list --outdated --format=json`.
+#[derive(Deserialize)]
+struct PipOutdatedPackage {
+    name: String,
+    latest_version: String,
+}
+
+/// Get the `index-url` `pip list` uses from the output of `pip config list`. The environment
+/// takes precedence over the `list` section, which takes precedence over the `global` one.
+fn parse_pip_index_url(config: &str) -> Option<String> {
+    let values = config
+        .lines()
+        .filter_map(|it| it.split_once('='))
+        .collect::<HashMap<_, _>>();
+
+    [":env:.index-url", "list.index-url", "global.index-url"]
+        .iter()
+        .find_map(|key| values.get(key))
+        .map(|it| {
+            it.trim()
+                .trim_matches(|c| c == '\'' || c == '"')
+                .to_string()
+        })
+}
+
 /// Helper for detecting virtual environment directories.
 pub fn directory_is_venv<T: Into<PathBuf>>(path: T) -> bool {
     path.into().join(VENV_CONFIG_FILE_NAME).exists()
@@ -299,10 +589,115 @@ pub fn venv_executables_dir_path<T: Into<PathBuf>>(root: T) -> PathBuf {
 }
 
 /// A struct used to configure Python `Package` installations.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct InstallOptions {
     /// A values vector of install options typically used for passing on arguments.
     pub values: Option<Vec<String>>,
+    /// Packages (comma-separated, or `:all:`) to only install from wheels.
+    pub only_binary: Option<String>,
+    /// Packages (comma-separated, or `:all:`) to never install from wheels.
+    pub no_binary: Option<String>,
+    /// Install only from the workspace's vendor directory instead of a package index.
+    pub offline: bool,
+    /// Print the resolver's decisions, like which candidates were considered and why each
+    /// version was chosen or rejected.
+    pub verbose_resolution: bool,
+    /// Install into this directory instead of the environment, replacing what's already there.
+    pub target: Option<PathBuf>,
+}
+
+impl InstallOptions {
+    /// Get the installer arguments for the `InstallOptions`.
+    ///
+    /// The same package can't be passed to both `only_binary` and `no_binary`. Offline installs
+    /// need the vendor directory to exist (see `huak vendor sync`).
+    pub(crate) fn args(&self, config: &Config) -> HuakResult<Vec<String>> {
+        if let (Some(only_binary), Some(no_binary)) = (&self.only_binary, &self.no_binary) {
+            let no_binary = binary_option_names(no_binary);
+
+            if let Some(it) = binary_option_names(only_binary)
+                .into_iter()
+                .find(|it| no_binary.contains(it))
+            {
+                return Err(Error::ConflictingBinaryOptions(it));
+            }
+        }
+
+        let mut args = Vec::new();
+
+        if let Some(it) = self.only_binary.as_ref() {
+            args.extend(["--only-binary".to_string(), it.clone()]);
+        }
+
+        if let Some(it) = self.no_binary.as_ref() {
+            args.extend(["--no-binary".to_string(), it.clone()]);
+        }
+
+        if self.offline {
+            let vendor_dir = config.workspace().vendor_dir();
+
+            if !vendor_dir.exists() {
+                return Err(Error::VendorDirNotFound { path: vendor_dir });
+            }
+
+            args.extend([
+                "--no-index".to_string(),
+                "--find-links".to_string(),
+                vendor_dir.display().to_string(),
+            ]);
+        }
+
+        if let Some(it) = self.target.as_ref() {
+            args.extend([
+                "--target".to_string(),
+                it.display().to_string(),
+                "--upgrade".to_string(),
+            ]);
+        }
+
+        if self.verbose_resolution {
+            args.push("--verbose".to_string());
+        } else if config.terminal_options.verbosity == Verbosity::Quiet {
+            args.push("--quiet".to_string());
+        }
+
+        if let Some(it) = self.values.as_ref() {
+            args.extend(it.iter().cloned());
+        }
+
+        Ok(args)
+    }
+
+    /// Get the installer environment variables for the `InstallOptions`.
+    ///
+    /// The installer only reports each step of its resolver when `PIP_RESOLVER_DEBUG` is set.
+    pub(crate) fn envs(&self) -> Vec<(&'static str, &'static str)> {
+        if self.verbose_resolution {
+            vec![("PIP_RESOLVER_DEBUG", "1")]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Get the normalized package names from an `--only-binary` or `--no-binary` value.
+fn binary_option_names(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|it| !it.is_empty() && *it != ":none:")
+        .map(canonical_name)
+        .collect()
+}
+
+/// The install scheme printed by `SYSTEM_ENVIRONMENT_SCRIPT`.
+#[derive(serde::Deserialize)]
+struct SystemScheme {
+    prefix: PathBuf,
+    scripts: PathBuf,
+    purelib: PathBuf,
+    version: String,
+    externally_managed: bool,
 }
 
 /// Python virtual environment configuration data (pyvenv.cfg).
@@ -500,6 +895,12 @@ pub fn default_venv_name() -> &'static str {
     DEFAULT_VENV_NAME
 }
 
+/// Get the name of the virtual environment for an environment configured in `[tool.huak.envs]`.
+#[must_use]
+pub fn named_venv_name(name: &str) -> String {
+    format!("{DEFAULT_VENV_NAME}-{name}")
+}
+
 /// Get an `Iterator` over available Python `Interpreter` paths parsed from the `PATH`
 /// environment variable (inspired by brettcannon/python-launcher).
 pub fn python_paths() -> impl Iterator<Item = (Option<Version>, PathBuf)> {
@@ -589,7 +990,7 @@ pub fn parse_python_version_from_command<T: Into<PathBuf>>(path: T) -> HuakResul
         "-c",
         "import sys;v=sys.version_info;print(v.major,v.minor,v.micro)",
     ]);
-    let output = sys::parse_command_output(&cmd.output()?)?
+    let output = sys::parse_command_output(&cmd.output()?)
         .replace(' ', ".")
         .replace(['\r', '\n'], "");
     let version = Version::from_str(&output).ok();
@@ -650,6 +1051,199 @@ mod tests {
         assert!(path.exists());
     }
 
+    #[test]
+    fn install_options_binary_args() {
+        let options = InstallOptions {
+            values: Some(vec!["--no-deps".to_string()]),
+            only_binary: Some(":all:".to_string()),
+            no_binary: Some("pyyaml".to_string()),
+            ..Default::default()
+        };
+        let config = Config::default();
+
+        assert_eq!(
+            options.args(&config).unwrap(),
+            [
+                "--only-binary",
+                ":all:",
+                "--no-binary",
+                "pyyaml",
+                "--no-deps"
+            ]
+        );
+
+        let options = InstallOptions {
+            only_binary: Some("numpy,PyYAML".to_string()),
+            no_binary: Some("pyyaml".to_string()),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            options.args(&config),
+            Err(Error::ConflictingBinaryOptions(it)) if it == "pyyaml"
+        ));
+    }
+
+    #[test]
+    fn install_options_offline_args() {
+        let dir = tempdir().unwrap();
+        let config = Config {
+            workspace_root: dir.path().to_path_buf(),
+            cwd: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let options = InstallOptions {
+            offline: true,
+            ..Default::default()
+        };
+        let vendor_dir = dir.path().join("vendor");
+
+        assert!(matches!(
+            options.args(&config),
+            Err(Error::VendorDirNotFound { path: it }) if it == vendor_dir
+        ));
+
+        std::fs::create_dir(&vendor_dir).unwrap();
+
+        assert_eq!(
+            options.args(&config).unwrap(),
+            [
+                "--no-index".to_string(),
+                "--find-links".to_string(),
+                vendor_dir.display().to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn install_options_verbose_resolution() {
+        let options = InstallOptions {
+            values: Some(vec!["--no-deps".to_string()]),
+            verbose_resolution: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            options.args(&Config::default()).unwrap(),
+            ["--verbose", "--no-deps"]
+        );
+        assert_eq!(options.envs(), [("PIP_RESOLVER_DEBUG", "1")]);
+        assert!(InstallOptions::default().envs().is_empty());
+    }
+
+    #[test]
+    fn install_options_target() {
+        let options = InstallOptions {
+            target: Some(PathBuf::from("build")),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            options.args(&Config::default()).unwrap(),
+            ["--target", "build", "--upgrade"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn system_python_environment() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // A mock interpreter printing the install scheme of an externally managed system Python.
+        let dir = tempdir().unwrap();
+        let prefix = dir.path().join("usr");
+        let python = prefix.join("bin").join("python3");
+        std::fs::create_dir_all(python.parent().unwrap()).unwrap();
+        let scheme = serde_json::json!({
+            "prefix": prefix,
+            "scripts": prefix.join("bin"),
+            "purelib": prefix.join("lib").join("python3.12").join("site-packages"),
+            "version": "3.12.1",
+            "externally_managed": true,
+        });
+        std::fs::write(&python, format!("#!/bin/sh\necho '{scheme}'\n")).unwrap();
+        std::fs::set_permissions(&python, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let python_env = PythonEnvironment::system(&python).unwrap();
+
+        assert!(python_env.is_system());
+        assert!(python_env.is_externally_managed());
+        assert_eq!(
+            python_env.python_version(),
+            &Version::from_str("3.12.1").unwrap()
+        );
+        assert!(python_env.executables_dir_path().is_absolute());
+        assert!(!directory_is_venv(python_env.root()));
+
+        let config = Config {
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            dry_run: true,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            python_env.install_packages(&["ruff"], &InstallOptions::default(), &config),
+            Err(Error::ExternallyManagedEnvironment { path }) if path == prefix
+        ));
+    }
+
+    #[test]
+    fn externally_managed_python_environment() {
+        let python_env = PythonEnvironment {
+            root: PathBuf::from("/usr"),
+            interpreter: Interpreter::new("/usr/bin/python3", Version::from_str("3.12.0").unwrap()),
+            executables_dir_path: PathBuf::from("/usr/bin"),
+            site_packages_path: PathBuf::from("/usr/lib/python3.12/site-packages"),
+            system: true,
+            externally_managed: true,
+        };
+        let config = Config {
+            terminal_options: TerminalOptions {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            dry_run: true,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            python_env.install_packages(&["ruff"], &InstallOptions::default(), &config),
+            Err(Error::ExternallyManagedEnvironment { .. })
+        ));
+
+        let config = Config {
+            break_system_packages: true,
+            ..config
+        };
+
+        assert!(python_env
+            .install_packages(&["ruff"], &InstallOptions::default(), &config)
+            .is_ok());
+    }
+
+    #[test]
+    fn pip_index_url() {
+        let config = "global.cert='/etc/ssl/certs/ca.pem'\n\
+                      global.index-url='https://pypi.example.com/simple'\n";
+        assert_eq!(
+            parse_pip_index_url(config).as_deref(),
+            Some("https://pypi.example.com/simple")
+        );
+
+        let config = format!(
+            "{config}list.index-url='https://list.example.com/simple'\n\
+             :env:.index-url='https://env.example.com/simple'\n"
+        );
+        assert_eq!(
+            parse_pip_index_url(&config).as_deref(),
+            Some("https://env.example.com/simple")
+        );
+        assert_eq!(parse_pip_index_url("global.cert='ca.pem'\n"), None);
+    }
+
     #[cfg(unix)]
     #[test]
     fn python_search() {
@@ -0,0 +1,267 @@
+use crate::{Config, Error, HuakResult};
+use pep440_rs::Version;
+use serde::{Deserialize, Serialize};
+use std::{path::Path, str::FromStr};
+use termcolor::Color;
+use toml_edit::{value, Item};
+
+/// A file format Huak writes, with the Huak version that introduced each of its schemas.
+pub(crate) struct FileSchema {
+    /// What the file is called in messages.
+    name: &'static str,
+    /// The Huak version that introduced each schema, starting with schema 1. Files written
+    /// before schemas were recorded have schema 0.
+    versions: &'static [&'static str],
+}
+
+/// The schemas of the dependency history (`.huak/history.jsonl`).
+pub(crate) const HISTORY_SCHEMA: FileSchema = FileSchema {
+    name: "dependency history",
+    versions: &["0.0.20a1"],
+};
+
+/// The schemas of lint baselines written by `huak lint --write-baseline`.
+pub(crate) const LINT_BASELINE_SCHEMA: FileSchema = FileSchema {
+    name: "lint baseline",
+    versions: &["0.0.20a1"],
+};
+
+/// The schema a file was written with, stored in the file itself.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SchemaMarker {
+    #[serde(default)]
+    pub(crate) schema: usize,
+    /// The oldest Huak version that can read the file.
+    #[serde(
+        rename = "huak-min-version",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) huak_min_version: Option<String>,
+}
+
+impl FileSchema {
+    fn current(&self) -> usize {
+        self.versions.len()
+    }
+
+    /// Get the `SchemaMarker` for a file written with the current schema.
+    pub(crate) fn marker(&self) -> SchemaMarker {
+        SchemaMarker {
+            schema: self.current(),
+            huak_min_version: self.versions.last().map(ToString::to_string),
+        }
+    }
+
+    /// Check that the file at `path` written with the `marker` can be read by this version of
+    /// Huak. Returns whether it has an older schema that should be migrated.
+    pub(crate) fn check(
+        &self,
+        marker: &SchemaMarker,
+        path: &Path,
+        config: &Config,
+    ) -> HuakResult<bool> {
+        let origin = path.display().to_string();
+
+        if let Some(it) = marker.huak_min_version.as_deref() {
+            require_huak_version(it, &origin, config)?;
+        }
+
+        if marker.schema > self.current() {
+            let required = marker.huak_min_version.as_ref().map_or_else(
+                || format!("newer than {}", self.versions[self.current() - 1]),
+                |it| format!(">= {it}"),
+            );
+
+            return Err(huak_version_too_old(origin, required, config));
+        }
+
+        Ok(marker.schema < self.current())
+    }
+
+    /// Print a note that the file at `path` was migrated to the current schema.
+    pub(crate) fn print_migrated(&self, path: &Path, config: &Config) -> HuakResult<()> {
+        config.terminal().print_custom(
+            "Migrated",
+            format!(
+                "{} {} to schema {}",
+                self.name,
+                path.display(),
+                self.current()
+            ),
+            Color::Green,
+            true,
+        )
+    }
+}
+
+/// Get the PEP 440 version of a Cargo package `version` like `0.0.20-alpha.1`, the way it's
+/// published to the Python Package Index (`0.0.20a1`).
+#[must_use]
+pub fn pep440_version(version: &str) -> Option<Version> {
+    let version = version
+        .replacen("-alpha.", "a", 1)
+        .replacen("-beta.", "b", 1)
+        .replacen("-rc.", "rc", 1);
+
+    Version::from_str(&version).ok()
+}
+
+/// Fail if the project requires a newer version of Huak with `huak-min-version`.
+///
+/// ```toml
+/// [tool.huak]
+/// huak-min-version = "0.0.21"
+/// ```
+pub fn check_huak_min_version(config: &Config) -> HuakResult<()> {
+    let Ok(manifest) = config.workspace().current_local_manifest() else {
+        return Ok(());
+    };
+    let Some(item) = manifest
+        .manifest_data()
+        .huak_table()
+        .and_then(|it| it.get("huak-min-version"))
+    else {
+        return Ok(());
+    };
+    let Some(required) = item.as_str() else {
+        return Err(invalid_min_version(item));
+    };
+
+    require_huak_version(required, "this project", config)
+}
+
+/// Fail unless this version of Huak is at least the `required` version. Without a known Huak
+/// version anything is allowed.
+fn require_huak_version(required: &str, origin: &str, config: &Config) -> HuakResult<()> {
+    let Ok(version) = Version::from_str(required) else {
+        return Err(invalid_min_version(&value(required)));
+    };
+
+    match config.huak_version.as_ref() {
+        Some(it) if *it < version => Err(huak_version_too_old(
+            origin.to_string(),
+            format!(">= {required}"),
+            config,
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn huak_version_too_old(origin: String, required: String, config: &Config) -> Error {
+    Error::HuakVersionTooOld {
+        origin,
+        required,
+        current: config
+            .huak_version
+            .as_ref()
+            .map_or_else(|| "an unknown version".to_string(), ToString::to_string),
+    }
+}
+
+fn invalid_min_version(item: &Item) -> Error {
+    Error::HuakConfigurationError {
+        table: "tool.huak".to_string(),
+        message: format!(
+            "huak-min-version must be a version like \"0.0.21\", found {}",
+            item.to_string().trim()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use huak_dev::dev_resources_dir;
+    use tempfile::tempdir;
+
+    fn test_config(huak_version: &str) -> Config {
+        Config {
+            huak_version: pep440_version(huak_version),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pep440_version() {
+        assert_eq!(
+            pep440_version("0.0.20-alpha.1").unwrap().to_string(),
+            "0.0.20a1"
+        );
+        assert_eq!(
+            pep440_version("0.1.0-rc.2").unwrap().to_string(),
+            "0.1.0rc2"
+        );
+        assert_eq!(pep440_version("0.1.0").unwrap().to_string(), "0.1.0");
+    }
+
+    #[test]
+    fn test_lint_baseline_schemas() {
+        let dir = dev_resources_dir().join("schemas").join("lint-baseline");
+        let config = test_config("0.0.20-alpha.1");
+        let check = |file: &str| {
+            let path = dir.join(file);
+            let marker =
+                serde_json::from_str::<SchemaMarker>(&std::fs::read_to_string(&path).unwrap())
+                    .unwrap();
+
+            LINT_BASELINE_SCHEMA.check(&marker, &path, &config)
+        };
+
+        // Baselines from before schemas were recorded are migrated.
+        assert!(check("0.json").unwrap());
+        assert!(!check("1.json").unwrap());
+        assert!(matches!(
+            check("future.json"),
+            Err(Error::HuakVersionTooOld { required, .. }) if required == ">= 99.0"
+        ));
+    }
+
+    #[test]
+    fn test_history_schemas() {
+        let dir = dev_resources_dir().join("schemas").join("history");
+        let config = test_config("0.0.20-alpha.1");
+
+        for (file, outdated) in [("0.jsonl", true), ("1.jsonl", false)] {
+            let path = dir.join(file);
+            for line in std::fs::read_to_string(&path).unwrap().lines() {
+                let marker = serde_json::from_str::<SchemaMarker>(line).unwrap();
+                assert_eq!(
+                    HISTORY_SCHEMA.check(&marker, &path, &config).unwrap(),
+                    outdated
+                );
+            }
+        }
+
+        let path = dir.join("future.jsonl");
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let marker = serde_json::from_str::<SchemaMarker>(contents.trim()).unwrap();
+        assert!(HISTORY_SCHEMA.check(&marker, &path, &config).is_err());
+    }
+
+    #[test]
+    fn test_check_huak_min_version() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("pyproject.toml"),
+            "[project]\nname = \"test\"\n\n[tool.huak]\nhuak-min-version = \"0.0.21\"\n",
+        )
+        .unwrap();
+        let config = |version: &str| Config {
+            cwd: dir.path().to_path_buf(),
+            workspace_root: dir.path().to_path_buf(),
+            ..test_config(version)
+        };
+
+        assert!(check_huak_min_version(&config("0.0.21")).is_ok());
+        assert!(check_huak_min_version(&config("0.1.0")).is_ok());
+        let Err(e) = check_huak_min_version(&config("0.0.20-alpha.1")) else {
+            panic!("an older huak should be rejected");
+        };
+        assert_eq!(
+            e.to_string(),
+            "this project requires huak >= 0.0.21, you have 0.0.20a1 (update it with `pip \
+            install --upgrade huak`)"
+        );
+    }
+}
@@ -0,0 +1,418 @@
+use crate::{Dependency, Error, HuakResult};
+use huak_pyproject_toml::canonical_name;
+use pep440_rs::VersionSpecifiers;
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use toml_edit::{Array, Document, Item, Value};
+
+const BLOCK_START: &str = "# /// script";
+const BLOCK_END: &str = "# ///";
+
+/// A Python script and its inline `# /// script` metadata block ([PEP 723](https://peps.python.org/pep-0723/)).
+///
+/// ```python
+/// # /// script
+/// # requires-python = ">=3.11"
+/// # dependencies = [
+/// #     "requests",
+/// # ]
+/// # ///
+/// ```
+#[derive(Debug)]
+pub struct ScriptMetadata {
+    path: PathBuf,
+    /// The lines of the script.
+    lines: Vec<String>,
+    /// The indices of the block's first and last line, if the script has a block.
+    block: Option<(usize, usize)>,
+    doc: Document,
+}
+
+impl ScriptMetadata {
+    /// Read the `ScriptMetadata` of the script at `path`.
+    pub fn new<T: Into<PathBuf>>(path: T) -> HuakResult<ScriptMetadata> {
+        let path = path.into();
+        let contents = std::fs::read_to_string(&path)?;
+
+        ScriptMetadata::parse(path, &contents)
+    }
+
+    fn parse(path: PathBuf, contents: &str) -> HuakResult<ScriptMetadata> {
+        let lines = contents
+            .lines()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        let error = |line: usize, message: String| {
+            Error::InvalidScriptMetadata(format!("{}:{}: {message}", path.display(), line + 1))
+        };
+
+        let Some(start) = lines.iter().position(|it| it.trim_end() == BLOCK_START) else {
+            return Ok(ScriptMetadata {
+                path,
+                lines,
+                block: None,
+                doc: Document::new(),
+            });
+        };
+
+        // The block ends with the last `# ///` before the first line that isn't part of it.
+        let mut end = None;
+        let mut next = start + 1;
+
+        while let Some(line) = lines.get(next).map(|it| it.trim_end()) {
+            if line != "#" && !line.starts_with("# ") {
+                break;
+            }
+            if line == BLOCK_END {
+                end = Some(next);
+            }
+            next += 1;
+        }
+
+        let Some(end) = end else {
+            return Err(match lines.get(next) {
+                Some(it) if it.starts_with('#') => error(
+                    next,
+                    "metadata lines must be `#` or start with `# `".to_string(),
+                ),
+                _ => error(
+                    start,
+                    format!("`{BLOCK_START}` is never closed with `{BLOCK_END}`"),
+                ),
+            });
+        };
+
+        if let Some(other) = lines
+            .iter()
+            .skip(end + 1)
+            .position(|it| it.trim_end() == BLOCK_START)
+        {
+            return Err(error(
+                end + 1 + other,
+                format!(
+                    "a script can only have one `{BLOCK_START}` block (the first starts on line {})",
+                    start + 1
+                ),
+            ));
+        }
+
+        let content = block_content(&lines[start + 1..end]);
+        let doc = content.parse::<Document>().map_err(|e| {
+            let line = e
+                .span()
+                .map_or(0, |it| content[..it.start].matches('\n').count());
+            error(start + 1 + line, e.message().trim().to_string())
+        })?;
+
+        let metadata = ScriptMetadata {
+            path,
+            lines,
+            block: Some((start, end)),
+            doc,
+        };
+        metadata.dependencies()?;
+        metadata.requires_python()?;
+
+        Ok(metadata)
+    }
+
+    /// Get the path to the script.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the script's `dependencies`.
+    pub fn dependencies(&self) -> HuakResult<Vec<Dependency>> {
+        let Some(item) = self.doc.get("dependencies") else {
+            return Ok(Vec::new());
+        };
+
+        let Some(array) = item.as_array() else {
+            return Err(self.key_error("dependencies", "dependencies must be an array of strings"));
+        };
+
+        array
+            .iter()
+            .map(|it| {
+                let Some(s) = it.as_str() else {
+                    return Err(
+                        self.key_error("dependencies", "dependencies must be an array of strings")
+                    );
+                };
+
+                Dependency::from_str(s).map_err(|e| self.value_error(s, &format!("{s} ({e})")))
+            })
+            .collect()
+    }
+
+    /// Get the script's `requires-python` constraint.
+    pub fn requires_python(&self) -> HuakResult<Option<VersionSpecifiers>> {
+        let Some(item) = self.doc.get("requires-python") else {
+            return Ok(None);
+        };
+
+        let Some(s) = item.as_str() else {
+            return Err(self.key_error("requires-python", "requires-python must be a string"));
+        };
+
+        VersionSpecifiers::from_str(s)
+            .map(Some)
+            .map_err(|e| self.value_error(s, &format!("{s} ({e})")))
+    }
+
+    /// Add `dependencies` to the script's metadata. Dependencies the script already has are
+    /// skipped.
+    pub fn add_dependencies(&mut self, dependencies: &[Dependency]) -> HuakResult<()> {
+        let existing = self
+            .dependencies()?
+            .iter()
+            .map(|it| canonical_name(it.name()))
+            .collect::<Vec<_>>();
+
+        let item = self
+            .doc
+            .entry("dependencies")
+            .or_insert(Item::Value(Value::Array(multiline_array())));
+        let array = item
+            .as_array_mut()
+            .expect("dependencies should be an array");
+        // New entries are put on their own lines if the array already spans lines.
+        let prefix = if array.is_empty() {
+            array
+                .trailing()
+                .as_str()
+                .filter(|it| it.contains('\n'))
+                .map(|_| "\n    ".to_string())
+        } else {
+            array
+                .get(0)
+                .and_then(|it| it.decor().prefix())
+                .and_then(|it| it.as_str())
+                .filter(|it| it.contains('\n'))
+                .map(ToString::to_string)
+        };
+
+        for dep in dependencies {
+            if existing.contains(&canonical_name(dep.name())) {
+                continue;
+            }
+
+            let mut value = Value::from(dep.to_string());
+            if let Some(it) = prefix.as_ref() {
+                value.decor_mut().set_prefix(it.as_str());
+            }
+            array.push_formatted(value);
+        }
+
+        Ok(())
+    }
+
+    /// Get the script's contents with its metadata block as it is now.
+    #[must_use]
+    pub fn contents(&self) -> String {
+        let mut block = vec![BLOCK_START.to_string()];
+        block.extend(self.doc.to_string().lines().map(|it| {
+            if it.is_empty() {
+                "#".to_string()
+            } else {
+                format!("# {it}")
+            }
+        }));
+        block.push(BLOCK_END.to_string());
+
+        let mut lines = self.lines.clone();
+
+        if let Some((start, end)) = self.block {
+            lines.splice(start..=end, block);
+        } else {
+            // Keep a shebang or encoding declaration first.
+            let at = lines
+                .iter()
+                .take(2)
+                .take_while(|it| {
+                    it.starts_with("#!") || (it.starts_with('#') && it.contains("coding"))
+                })
+                .count();
+            block.push(String::new());
+            lines.splice(at..at, block);
+        }
+
+        let mut contents = lines.join("\n");
+        contents.push('\n');
+        contents
+    }
+
+    /// Write the script with its metadata block as it is now.
+    pub fn write_file(&self) -> HuakResult<()> {
+        std::fs::write(&self.path, self.contents())?;
+
+        Ok(())
+    }
+
+    /// Get an error for the line a `key` of the block is set on.
+    fn key_error(&self, key: &str, message: &str) -> Error {
+        self.line_error(
+            |it| {
+                it.trim_start_matches('#')
+                    .trim_start()
+                    .strip_prefix(key)
+                    .is_some_and(|it| it.trim_start().starts_with('='))
+            },
+            message,
+        )
+    }
+
+    /// Get an error for the line a string `value` of the block is on.
+    fn value_error(&self, value: &str, message: &str) -> Error {
+        self.line_error(|it| it.contains(value), message)
+    }
+
+    fn line_error<F: Fn(&str) -> bool>(&self, matches: F, message: &str) -> Error {
+        let (start, end) = self.block.unwrap_or_default();
+        let line = (start + 1..end)
+            .find(|it| matches(&self.lines[*it]))
+            .unwrap_or(start);
+
+        Error::InvalidScriptMetadata(format!("{}:{}: {message}", self.path.display(), line + 1))
+    }
+}
+
+/// Get the TOML content of a block's lines with their `#` prefixes removed.
+fn block_content(lines: &[String]) -> String {
+    let mut content = lines
+        .iter()
+        .map(|it| {
+            let it = it.trim_end();
+            it.strip_prefix("# ").unwrap_or(it.trim_start_matches('#'))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    content.push('\n');
+    content
+}
+
+fn multiline_array() -> Array {
+    let mut array = Array::new();
+    array.set_trailing("\n");
+    array.set_trailing_comma(true);
+    array
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = r#"#!/usr/bin/env python
+# /// script
+# requires-python = ">=3.11"
+# dependencies = [
+#     "requests<3",
+# ]
+# ///
+
+import requests
+"#;
+
+    fn parse(contents: &str) -> HuakResult<ScriptMetadata> {
+        ScriptMetadata::parse(PathBuf::from("backfill.py"), contents)
+    }
+
+    #[test]
+    fn test_script_metadata() {
+        let metadata = parse(SCRIPT).unwrap();
+
+        assert_eq!(
+            metadata
+                .dependencies()
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["requests <3"]
+        );
+        assert_eq!(
+            metadata.requires_python().unwrap().unwrap().to_string(),
+            ">=3.11"
+        );
+        assert!(parse("import sys\n")
+            .unwrap()
+            .dependencies()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_script_metadata_errors() {
+        let error = |contents: &str| match parse(contents) {
+            Err(Error::InvalidScriptMetadata(it)) => it,
+            it => panic!("expected invalid metadata, got {it:?}"),
+        };
+
+        assert_eq!(
+            error("# /// script\n# dependencies = []\n\nimport sys\n"),
+            "backfill.py:1: `# /// script` is never closed with `# ///`"
+        );
+        assert_eq!(
+            error("# /// script\n#dependencies = []\n# ///\n"),
+            "backfill.py:2: metadata lines must be `#` or start with `# `"
+        );
+        assert!(error("# /// script\n# dependencies = [\n# ///\n").starts_with("backfill.py:3: "));
+        assert_eq!(
+            error("# /// script\n# requires-python = \">=3.11\"\n# dependencies = [1]\n# ///\n"),
+            "backfill.py:3: dependencies must be an array of strings"
+        );
+        assert!(
+            error("# /// script\n# dependencies = [\n#   \"requests>>2\",\n# ]\n# ///\n")
+                .starts_with("backfill.py:3: requests>>2")
+        );
+        assert!(error(&format!("{SCRIPT}\n# /// script\n# ///\n"))
+            .starts_with("backfill.py:11: a script can only have one"));
+    }
+
+    #[test]
+    fn test_script_metadata_add_dependencies() {
+        let mut metadata = parse(SCRIPT).unwrap();
+        metadata
+            .add_dependencies(&[
+                Dependency::from_str("Requests").unwrap(),
+                Dependency::from_str("rich>=13").unwrap(),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            metadata.contents(),
+            r#"#!/usr/bin/env python
+# /// script
+# requires-python = ">=3.11"
+# dependencies = [
+#     "requests<3",
+#     "rich >=13",
+# ]
+# ///
+
+import requests
+"#
+        );
+
+        let mut metadata = parse("#!/usr/bin/env python\nimport rich\n").unwrap();
+        metadata
+            .add_dependencies(&[Dependency::from_str("rich").unwrap()])
+            .unwrap();
+
+        assert_eq!(
+            metadata.contents(),
+            r#"#!/usr/bin/env python
+# /// script
+# dependencies = [
+#     "rich",
+# ]
+# ///
+
+import rich
+"#
+        );
+    }
+}
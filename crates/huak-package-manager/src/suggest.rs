@@ -0,0 +1,59 @@
+/// Get up to three `candidates` within a few edits of `value`, closest first.
+///
+/// Longer values allow more edits, so `instal` suggests `install` but `rn` doesn't suggest `fmt`.
+pub fn similar_names<I>(value: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let max_distance = (value.chars().count() / 3).max(1);
+    let mut similar = candidates
+        .into_iter()
+        .filter_map(|it| {
+            let it = it.as_ref();
+            let distance = edit_distance(value, it);
+            (distance <= max_distance && it != value).then(|| (distance, it.to_string()))
+        })
+        .collect::<Vec<_>>();
+    similar.sort();
+    similar.dedup_by(|a, b| a.1 == b.1);
+
+    similar.into_iter().take(3).map(|(_, it)| it).collect()
+}
+
+/// Get the number of single character edits needed to turn `a` into `b`.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_similar_names() {
+        let candidates = ["install", "init", "lint", "fmt", "fix"];
+
+        assert_eq!(similar_names("installl", candidates), ["install"]);
+        assert_eq!(similar_names("int", candidates), ["init", "lint"]);
+        assert_eq!(similar_names("fnt", candidates), ["fmt"]);
+        assert!(similar_names("publish", candidates).is_empty());
+        assert!(similar_names("init", candidates)
+            .iter()
+            .all(|it| it != "init"));
+    }
+}
@@ -2,17 +2,38 @@ use crate::error::HuakResult;
 use crate::Error;
 #[cfg(unix)]
 use std::os::unix::fs::symlink;
+#[cfg(unix)]
+use std::os::unix::process::ExitStatusExt;
 #[cfg(windows)]
 use std::os::windows::fs::symlink_file;
 use std::{
+    collections::VecDeque,
     fmt::Display,
-    io::Write,
-    path::Path,
-    process::{Command, ExitStatus},
+    io::{BufRead, BufReader, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 use tempfile::TempDir;
 use termcolor::{self, Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
+/// How long a subprocess can go without output before a heartbeat message is printed.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+/// How often a subprocess running with a timeout is polled.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// The number of captured output lines to report when a subprocess times out.
+const TIMEOUT_OUTPUT_TAIL_LEN: usize = 20;
+/// The number of captured output lines to report when a subprocess fails while quiet.
+const QUIET_OUTPUT_TAIL_LEN: usize = 50;
+/// The signal sent to interrupt a process, like with Ctrl-C.
+#[cfg(unix)]
+const SIGINT: i32 = 2;
+
 #[derive(Debug)]
 pub struct SubprocessError {
     status: ExitStatus,
@@ -28,6 +49,35 @@ impl SubprocessError {
     pub fn code(&self) -> Option<i32> {
         self.status.code()
     }
+
+    /// Check if the subprocess was killed by SIGINT, like with Ctrl-C.
+    #[must_use]
+    pub fn interrupted(&self) -> bool {
+        #[cfg(unix)]
+        let interrupted = self.status.signal() == Some(SIGINT);
+        #[cfg(not(unix))]
+        let interrupted = false;
+
+        interrupted
+    }
+
+    /// Get the exit code to exit with on behalf of the subprocess.
+    ///
+    /// Subprocesses killed by a signal get 128 plus the signal number like they would from a
+    /// shell. Codes that can't be represented fall back to 1.
+    #[must_use]
+    pub fn exit_code(&self) -> u8 {
+        #[cfg(unix)]
+        let code = self
+            .code()
+            .or_else(|| self.status.signal().map(|it| 128 + it));
+        #[cfg(not(unix))]
+        let code = self.code();
+
+        code.and_then(|it| u8::try_from(it).ok())
+            .filter(|it| *it != 0)
+            .unwrap_or(1)
+    }
 }
 
 impl Display for SubprocessError {
@@ -36,6 +86,8 @@ impl Display for SubprocessError {
     }
 }
 
+impl std::error::Error for SubprocessError {}
+
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub enum Verbosity {
     #[default]
@@ -60,6 +112,10 @@ impl Terminal {
             options: TerminalOptions {
                 verbosity: Verbosity::Verbose,
                 color_choice: ColorChoice::Auto,
+                timeout: None,
+                profile: false,
+                strict: false,
+                warnings: Arc::default(),
             },
             output: TerminalOut::Stream {
                 stderr: StandardStream::stderr(ColorChoice::Auto),
@@ -87,8 +143,13 @@ impl Terminal {
             .message_stderr_with_status(&"error", Some(&message), Color::Red, false)
     }
 
-    /// Prints a warning message.
+    /// Prints a warning message. Warnings are also recorded for `strict` `TerminalOptions` so
+    /// the command can fail because of them.
     pub fn print_warning<T: Display>(&mut self, message: T) -> HuakResult<()> {
+        if self.options.strict {
+            self.options.record_warning(message.to_string());
+        }
+
         match self.options.verbosity {
             Verbosity::Quiet => Ok(()),
             _ => self.print(&"warning", Some(&message), Color::Yellow, false),
@@ -110,6 +171,48 @@ impl Terminal {
         self.print(&title, Some(&message), color, justified)
     }
 
+    /// Prints a one-line summary of a finished operation. Summaries are printed even when the
+    /// `Verbosity` is `Quiet`, so scripts still get a confirmation.
+    pub fn print_summary<T: Display>(&mut self, message: T) -> HuakResult<()> {
+        self.output
+            .message_stderr_with_status(&"Finished", Some(&message), Color::Green, true)
+    }
+
+    /// Prints how long a step took when profiling.
+    pub fn print_profile<T: Display>(&mut self, message: T, elapsed: Duration) -> HuakResult<()> {
+        if !self.options.profile {
+            return Ok(());
+        }
+
+        self.output.message_stderr_with_status(
+            &"Profile",
+            Some(&format!(
+                "{message} in {:.1}ms",
+                elapsed.as_secs_f64() * 1000.0
+            )),
+            Color::Cyan,
+            true,
+        )
+    }
+
+    /// Prints a custom message only when the `Verbosity` is `Verbose`.
+    pub fn print_verbose<T, U>(
+        &mut self,
+        title: U,
+        message: T,
+        color: Color,
+        justified: bool,
+    ) -> HuakResult<()>
+    where
+        T: Display,
+        U: Display,
+    {
+        match self.options.verbosity {
+            Verbosity::Verbose => self.print(&title, Some(&message), color, justified),
+            _ => Ok(()),
+        }
+    }
+
     /// Prints a message without a status.
     pub fn print_without_status<T>(&mut self, message: T, color: Color) -> HuakResult<()>
     where
@@ -147,28 +250,58 @@ impl Terminal {
     }
 
     /// Run a command from the terminal's context.
+    ///
+    /// How much of the command's output is shown follows the terminal's `Verbosity`:
+    /// - Quiet: the output is captured and only its last lines are shown if the command fails.
+    /// - Normal: the command writes to the terminal directly.
+    /// - Verbose: the command line is printed before it's run.
     pub fn run_command(&mut self, cmd: &mut Command) -> HuakResult<()> {
-        // Allow `single_match_else` because `Quiet won't be the only handled `Verbosity`.
-        #[allow(clippy::single_match_else)]
+        let dir = cmd
+            .get_current_dir()
+            .map(|it| format!(" in {}", it.display()))
+            .unwrap_or_default();
+        self.print_verbose(
+            "Running",
+            format!("`{}`{dir}", command_display(cmd)),
+            Color::Cyan,
+            true,
+        )?;
+        let start = Instant::now();
+        let res = self.run_command_inner(cmd);
+        self.print_profile(format!("ran {}", command_display(cmd)), start.elapsed())?;
+
+        res
+    }
+
+    fn run_command_inner(&mut self, cmd: &mut Command) -> HuakResult<()> {
+        if let Some(timeout) = self.options.timeout {
+            let status = self.run_command_with_timeout(cmd, timeout)?;
+
+            if !status.success() {
+                return Err(Error::SubprocessFailure(SubprocessError::new(status)));
+            }
+
+            return Ok(());
+        }
+
         let status = match self.options.verbosity {
             Verbosity::Quiet => {
                 let output = cmd.output()?;
                 let status = output.status;
 
-                let stdout = trim_error_prefix(std::str::from_utf8(&output.stdout)?);
-                let stderr = trim_error_prefix(std::str::from_utf8(&output.stderr)?);
-
                 if !status.success() {
-                    if !stdout.is_empty() {
-                        self.print_error(stdout)?;
-                    }
-                    if !stderr.is_empty() {
-                        self.print_error(stderr)?;
+                    if let Some(it) = quiet_failure_output(&output.stdout, &output.stderr) {
+                        self.print_error(it)?;
                     }
                 }
 
                 status
             }
+            // Output going to a log (like in CI) is watched so that silent commands still show
+            // they're alive. At a terminal the command keeps it, so prompts and colors work.
+            _ if !std::io::stdout().is_terminal() && !std::io::stderr().is_terminal() => {
+                self.run_command_with_heartbeats(cmd)?
+            }
             _ => {
                 let mut child = cmd.spawn()?;
 
@@ -190,6 +323,426 @@ impl Terminal {
     }
 }
 
+impl Terminal {
+    /// Run a command, printing a heartbeat message whenever it has been silent for
+    /// `HEARTBEAT_INTERVAL`.
+    ///
+    /// The command's stdout and stderr are copied to the terminal's byte for byte as they
+    /// arrive. The command stays in the terminal's process group so interrupts still reach it.
+    fn run_command_with_heartbeats(&mut self, cmd: &mut Command) -> HuakResult<ExitStatus> {
+        let display = command_display(cmd);
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+        let mut child = cmd.spawn()?;
+        let last_output = Arc::new(Mutex::new(Instant::now()));
+        let stdout = child
+            .stdout
+            .take()
+            .map(|it| spawn_copier(it, std::io::stdout(), last_output.clone()));
+        let stderr = child
+            .stderr
+            .take()
+            .map(|it| spawn_copier(it, std::io::stderr(), last_output.clone()));
+
+        let start = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+
+            let silent = last_output
+                .lock()
+                .is_ok_and(|it| it.elapsed() >= HEARTBEAT_INTERVAL);
+            if silent {
+                self.print_custom(
+                    "Waiting",
+                    format!(
+                        "still running `{display}`, {}s elapsed",
+                        start.elapsed().as_secs()
+                    ),
+                    Color::Cyan,
+                    true,
+                )?;
+                if let Ok(mut it) = last_output.lock() {
+                    *it = Instant::now();
+                }
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        };
+
+        if let Some(it) = stdout {
+            it.join().ok();
+        }
+        if let Some(it) = stderr {
+            it.join().ok();
+        }
+
+        Ok(status)
+    }
+
+    /// Run a command, killing its process group if it runs longer than `timeout`.
+    ///
+    /// The command's output is captured line by line so that the last lines can be reported
+    /// if the timeout is exceeded. Unless the terminal is quiet the output is echoed as it
+    /// arrives, and a heartbeat message is printed whenever the command has been silent for
+    /// a while.
+    fn run_command_with_timeout(
+        &mut self,
+        cmd: &mut Command,
+        timeout: Duration,
+    ) -> HuakResult<ExitStatus> {
+        let display = command_display(cmd);
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+        // Spawn the command as the leader of a new process group so that anything it spawns
+        // can be killed with it.
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(cmd, 0);
+
+        let mut child = cmd.spawn()?;
+        let (sender, receiver) = mpsc::channel();
+        let readers = [
+            child
+                .stdout
+                .take()
+                .map(|it| spawn_line_reader(it, OutputStream::Stdout, sender.clone())),
+            child
+                .stderr
+                .take()
+                .map(|it| spawn_line_reader(it, OutputStream::Stderr, sender.clone())),
+        ];
+        drop(sender);
+
+        let quiet = self.options.verbosity == Verbosity::Quiet;
+        let start = Instant::now();
+        let mut last_output = start;
+        let mut tail = VecDeque::with_capacity(TIMEOUT_OUTPUT_TAIL_LEN);
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+
+            if start.elapsed() >= timeout {
+                kill_process_group(&mut child);
+
+                // Report whatever output made it through before the kill.
+                while let Ok((stream, line)) = receiver.try_recv() {
+                    record_line(&mut tail, quiet, stream, line);
+                }
+                if !tail.is_empty() {
+                    self.print_error(format!(
+                        "last {} lines of output from `{display}`:\n{}",
+                        tail.len(),
+                        Vec::from(tail).join("\n")
+                    ))?;
+                }
+
+                return Err(Error::SubprocessTimeout {
+                    command: display,
+                    seconds: timeout.as_secs(),
+                });
+            }
+
+            if last_output.elapsed() >= HEARTBEAT_INTERVAL {
+                self.print_custom(
+                    "Waiting",
+                    format!(
+                        "still running `{display}`, {}s elapsed",
+                        start.elapsed().as_secs()
+                    ),
+                    Color::Cyan,
+                    true,
+                )?;
+                last_output = Instant::now();
+            }
+
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok((stream, line)) => {
+                    last_output = Instant::now();
+                    record_line(&mut tail, quiet, stream, line);
+                }
+                // Once both streams are closed there's nothing left to wait on but the child.
+                Err(RecvTimeoutError::Disconnected) => thread::sleep(POLL_INTERVAL),
+                Err(RecvTimeoutError::Timeout) => (),
+            }
+        };
+
+        for reader in readers.into_iter().flatten() {
+            reader.join().ok();
+        }
+        for (stream, line) in receiver.try_iter() {
+            record_line(&mut tail, quiet, stream, line);
+        }
+
+        if quiet && !status.success() {
+            for line in tail {
+                self.print_error(trim_error_prefix(&line))?;
+            }
+        }
+
+        Ok(status)
+    }
+}
+
+/// The standard stream a line of subprocess output was read from.
+#[derive(Clone, Copy)]
+enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Echo a line of subprocess output (unless `quiet`) and keep it in the output `tail`.
+fn record_line(tail: &mut VecDeque<String>, quiet: bool, stream: OutputStream, line: String) {
+    if !quiet {
+        match stream {
+            OutputStream::Stdout => println!("{line}"),
+            OutputStream::Stderr => eprintln!("{line}"),
+        }
+    }
+    if tail.len() == TIMEOUT_OUTPUT_TAIL_LEN {
+        tail.pop_front();
+    }
+    tail.push_back(line);
+}
+
+/// Get the output to show for a command that failed while the terminal was quiet: the last
+/// `QUIET_OUTPUT_TAIL_LEN` lines of its stdout followed by its stderr.
+fn quiet_failure_output(stdout: &[u8], stderr: &[u8]) -> Option<String> {
+    // Tools don't always write UTF-8 (like on Windows code pages).
+    let (stdout, stderr) = (
+        String::from_utf8_lossy(stdout),
+        String::from_utf8_lossy(stderr),
+    );
+    let lines = [trim_error_prefix(&stdout), trim_error_prefix(&stderr)]
+        .into_iter()
+        .filter(|it| !it.is_empty())
+        .flat_map(str::lines)
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {
+        return None;
+    }
+
+    let omitted = lines.len().saturating_sub(QUIET_OUTPUT_TAIL_LEN);
+    let tail = lines[omitted..].join("\n");
+
+    if omitted == 0 {
+        Some(tail)
+    } else {
+        Some(format!("... {omitted} earlier lines omitted\n{tail}"))
+    }
+}
+
+/// A writer that flushes every write and records when the last one happened.
+struct ActivityWriter<W> {
+    inner: W,
+    last_write: Arc<Mutex<Instant>>,
+}
+
+impl<W: Write> Write for ActivityWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.inner.flush()?;
+        if let Ok(mut it) = self.last_write.lock() {
+            *it = Instant::now();
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Copy the bytes read from `stream` to `writer` unchanged from a separate thread, recording
+/// when output was last written in `last_write`. The thread returns the `writer`.
+fn spawn_copier<R, W>(
+    mut stream: R,
+    writer: W,
+    last_write: Arc<Mutex<Instant>>,
+) -> thread::JoinHandle<W>
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut writer = ActivityWriter {
+            inner: writer,
+            last_write,
+        };
+        std::io::copy(&mut stream, &mut writer).ok();
+
+        writer.inner
+    })
+}
+
+/// Forward each line read from `stream` to `sender` from a separate thread.
+fn spawn_line_reader<R: Read + Send + 'static>(
+    stream: R,
+    kind: OutputStream,
+    sender: Sender<(OutputStream, String)>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+        let mut buf = Vec::new();
+        while reader.read_until(b'\n', &mut buf).unwrap_or(0) > 0 {
+            let line = String::from_utf8_lossy(&buf).trim_end().to_string();
+            if sender.send((kind, line)).is_err() {
+                break;
+            }
+            buf.clear();
+        }
+    })
+}
+
+/// Kill a child process and any processes in its process group (or process tree on Windows).
+fn kill_process_group(child: &mut Child) {
+    let pid = child.id().to_string();
+
+    #[cfg(unix)]
+    let killed = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{pid}")])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    #[cfg(windows)]
+    let killed = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+
+    if !killed.is_ok_and(|it| it.success()) {
+        child.kill().ok();
+    }
+    child.wait().ok();
+}
+
+/// Get a readable representation of a `Command` for terminal messages.
+pub(crate) fn command_display(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|it| it.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A subprocess an operation would run, as data: its program and arguments, the changes to the
+/// environment it inherits, and the directory it runs in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannedCommand {
+    /// The operation the subprocess is run for (ex: "install").
+    pub operation: String,
+    pub program: String,
+    pub args: Vec<String>,
+    /// Variables set (`Some`) or removed (`None`) for the subprocess, sorted by name.
+    pub envs: Vec<(String, Option<String>)>,
+    /// The directory the subprocess runs in, resolved from huak's when it isn't set.
+    pub current_dir: Option<PathBuf>,
+}
+
+impl PlannedCommand {
+    #[must_use]
+    pub fn new(operation: &str, cmd: &Command) -> PlannedCommand {
+        let mut envs = cmd
+            .get_envs()
+            .map(|(key, value)| {
+                (
+                    key.to_string_lossy().to_string(),
+                    value.map(|it| it.to_string_lossy().to_string()),
+                )
+            })
+            .collect::<Vec<_>>();
+        envs.sort();
+
+        PlannedCommand {
+            operation: operation.to_string(),
+            program: cmd.get_program().to_string_lossy().to_string(),
+            args: cmd
+                .get_args()
+                .map(|it| it.to_string_lossy().to_string())
+                .collect(),
+            envs,
+            current_dir: cmd
+                .get_current_dir()
+                .map(Path::to_path_buf)
+                .or_else(|| std::env::current_dir().ok()),
+        }
+    }
+
+    /// Get the command line with arguments quoted for a POSIX shell where needed.
+    #[must_use]
+    pub fn command_line(&self) -> String {
+        std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|it| shell_quote(it))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl Display for PlannedCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.operation)?;
+        if let Some(it) = self.current_dir.as_ref() {
+            writeln!(f, "  cwd  {}", it.display())?;
+        }
+        for (key, value) in &self.envs {
+            match value {
+                Some(it) => writeln!(f, "  env  {key}={}", shell_quote(it))?,
+                None => writeln!(f, "  env  {key} (unset)")?,
+            }
+        }
+        write!(f, "  run  {}", self.command_line())
+    }
+}
+
+/// The subprocesses that would change the system, recorded in the order they'd run instead of
+/// running them (`--explain`). Clones share their records.
+#[derive(Clone, Debug, Default)]
+pub struct CommandPlan(Arc<Mutex<Vec<PlannedCommand>>>);
+
+impl CommandPlan {
+    pub fn record(&self, command: PlannedCommand) {
+        if let Ok(mut it) = self.0.lock() {
+            it.push(command);
+        }
+    }
+
+    #[must_use]
+    pub fn commands(&self) -> Vec<PlannedCommand> {
+        self.0.lock().map(|it| it.clone()).unwrap_or_default()
+    }
+}
+
+impl Display for CommandPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, it) in self.commands().iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{}. {it}", i + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Quote `value` for a POSIX shell if it has characters the shell would interpret.
+fn shell_quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|it| it.is_ascii_alphanumeric() || "-_./=:,+@%".contains(it))
+    {
+        return value.to_string();
+    }
+
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
 impl Default for Terminal {
     fn default() -> Self {
         Self::new()
@@ -200,6 +753,14 @@ impl Default for Terminal {
 pub struct TerminalOptions {
     pub verbosity: Verbosity,
     pub color_choice: ColorChoice,
+    /// How long a command run from the `Terminal` can take before it's killed.
+    pub timeout: Option<Duration>,
+    /// Print how long huak's steps and the commands it runs take.
+    pub profile: bool,
+    /// Record warnings so they can be promoted to errors (`--strict`).
+    pub strict: bool,
+    /// The warnings recorded by every `Terminal` created from these options (or their clones).
+    pub warnings: Arc<Mutex<Vec<String>>>,
 }
 
 impl TerminalOptions {
@@ -213,10 +774,30 @@ impl TerminalOptions {
         &self.color_choice
     }
 
+    #[must_use]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
     #[must_use]
     pub fn take(self) -> TerminalOptions {
         self
     }
+
+    /// Get the warnings recorded while `strict`, in the order they were printed.
+    #[must_use]
+    pub fn promoted_warnings(&self) -> Vec<String> {
+        self.warnings
+            .lock()
+            .map(|it| it.clone())
+            .unwrap_or_default()
+    }
+
+    fn record_warning(&self, warning: String) {
+        if let Ok(mut it) = self.warnings.lock() {
+            it.push(warning);
+        }
+    }
 }
 
 impl Default for TerminalOptions {
@@ -224,15 +805,20 @@ impl Default for TerminalOptions {
         Self {
             verbosity: Verbosity::default(),
             color_choice: ColorChoice::Auto,
+            timeout: None,
+            profile: false,
+            strict: false,
+            warnings: Arc::default(),
         }
     }
 }
 
-pub fn parse_command_output(output: &std::process::Output) -> HuakResult<String> {
-    let mut s = String::new();
-    s.push_str(std::str::from_utf8(&output.stdout)?);
-    s.push_str(std::str::from_utf8(&output.stderr)?);
-    Ok(s)
+/// Get a subprocess's stdout followed by its stderr, replacing anything that isn't UTF-8.
+#[must_use]
+pub fn parse_command_output(output: &std::process::Output) -> String {
+    let mut s = String::from_utf8_lossy(&output.stdout).into_owned();
+    s.push_str(&String::from_utf8_lossy(&output.stderr));
+    s
 }
 
 fn trim_error_prefix(msg: &str) -> &str {
@@ -339,6 +925,53 @@ pub fn shell_path() -> HuakResult<String> {
     Ok(std::env::var("COMSPEC")?)
 }
 
+/// Check if huak is running as root (effective uid 0), or as an administrator on Windows.
+#[cfg(unix)]
+#[must_use]
+pub fn is_root() -> bool {
+    // `/proc` isn't available on every unix, so fall back to asking `id`.
+    let euid = std::fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            status
+                .lines()
+                .find_map(|it| it.strip_prefix("Uid:"))
+                .and_then(|it| it.split_whitespace().nth(1).map(ToString::to_string))
+        })
+        .or_else(|| {
+            Command::new("id")
+                .arg("-u")
+                .output()
+                .ok()
+                .filter(|it| it.status.success())
+                .map(|it| String::from_utf8_lossy(&it.stdout).trim().to_string())
+        });
+
+    euid.is_some_and(|it| it == "0")
+}
+
+/// Check if huak is running as root (effective uid 0), or as an administrator on Windows.
+#[cfg(windows)]
+#[must_use]
+pub fn is_root() -> bool {
+    // `net session` is only allowed for administrators.
+    Command::new("net")
+        .arg("session")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|it| it.success())
+}
+
+/// Check if huak is running in a container, like with Docker, Podman, or Kubernetes.
+#[must_use]
+pub fn in_container() -> bool {
+    Path::new("/.dockerenv").exists()
+        || Path::new("/run/.containerenv").exists()
+        || std::env::var_os("container").is_some()
+        || std::env::var_os("KUBERNETES_SERVICE_HOST").is_some()
+}
+
 pub(crate) fn symlink_supported() -> bool {
     if cfg!(unix) {
         true
@@ -364,3 +997,153 @@ fn try_symlink<T: AsRef<Path>>(original: T, link: T) -> Result<(), Error> {
 
     Ok(err?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command_timeout() {
+        let mut terminal = Terminal::from_options(TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            timeout: Some(Duration::from_secs(1)),
+            ..Default::default()
+        });
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "echo started; exec /bin/sleep 30"]);
+        let start = Instant::now();
+
+        let res = terminal.run_command(&mut cmd);
+
+        assert!(matches!(
+            res,
+            Err(Error::SubprocessTimeout { seconds: 1, .. })
+        ));
+        assert!(start.elapsed() < Duration::from_secs(30));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command_within_timeout() {
+        let mut terminal = Terminal::from_options(TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        });
+        let mut ok = Command::new("/bin/sh");
+        ok.args(["-c", "echo done"]);
+        let mut fails = Command::new("/bin/sh");
+        fails.args(["-c", "exit 3"]);
+
+        terminal.run_command(&mut ok).unwrap();
+        let res = terminal.run_command(&mut fails);
+
+        assert!(
+            matches!(res, Err(Error::SubprocessFailure(e)) if e.code() == Some(3) && e.exit_code() == 3)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command_with_heartbeats() {
+        let mut terminal = Terminal::from_options(TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        });
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "echo started; /bin/sleep 1; exit 3"]);
+
+        let status = terminal.run_command_with_heartbeats(&mut cmd).unwrap();
+
+        assert_eq!(status.code(), Some(3));
+    }
+
+    #[test]
+    fn test_spawn_copier() {
+        let output = b"no newline \r\n\xff\x00 trailing  ".to_vec();
+        let before = Instant::now();
+        let last_write = Arc::new(Mutex::new(before));
+
+        let copied = spawn_copier(
+            std::io::Cursor::new(output.clone()),
+            Vec::new(),
+            last_write.clone(),
+        )
+        .join()
+        .unwrap();
+
+        assert_eq!(copied, output);
+        assert!(*last_write.lock().unwrap() > before);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_quiet_failure_output() {
+        let mut fails = Command::new("/bin/sh");
+        fails.args([
+            "-c",
+            "i=1; while [ $i -le 60 ]; do echo $i; i=$((i + 1)); done; echo broken >&2; exit 1",
+        ]);
+        let output = fails.output().unwrap();
+        let shown = quiet_failure_output(&output.stdout, &output.stderr).unwrap();
+        let lines = shown.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), QUIET_OUTPUT_TAIL_LEN + 1);
+        assert_eq!(lines[0], "... 11 earlier lines omitted");
+        assert_eq!(lines[1], "12");
+        assert_eq!(lines.last(), Some(&"broken"));
+        assert_eq!(quiet_failure_output(b"", b""), None);
+
+        let mut terminal = Terminal::from_options(TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        });
+        let res = terminal.run_command(&mut fails);
+
+        assert!(matches!(res, Err(Error::SubprocessFailure(e)) if e.code() == Some(1)));
+    }
+
+    #[test]
+    fn test_strict_warnings() {
+        let options = TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            strict: true,
+            ..Default::default()
+        };
+        Terminal::from_options(options.clone())
+            .print_warning("first")
+            .unwrap();
+        Terminal::from_options(options.clone())
+            .print_warning("second")
+            .unwrap();
+
+        assert_eq!(options.promoted_warnings(), ["first", "second"]);
+
+        let options = TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+        Terminal::from_options(options.clone())
+            .print_warning("ignored")
+            .unwrap();
+
+        assert!(options.promoted_warnings().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_subprocess_error_exit_code() {
+        let mut terminal = Terminal::from_options(TerminalOptions {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        });
+        let mut killed = Command::new("/bin/sh");
+        killed.args(["-c", "kill -9 $$"]);
+        let res = terminal.run_command(&mut killed);
+
+        assert!(
+            matches!(res, Err(Error::SubprocessFailure(e)) if e.code().is_none() && e.exit_code() == 137)
+        );
+    }
+}
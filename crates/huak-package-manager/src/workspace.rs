@@ -3,12 +3,19 @@ use crate::{
     environment::Environment,
     fs,
     manifest::LocalManifest,
-    python_environment::{default_venv_name, venv_config_file_name},
-    Config, Error, HuakResult, PythonEnvironment,
+    ops::FileFilter,
+    python_environment::{
+        active_python_env_path, default_venv_name, directory_is_venv, named_venv_name,
+        venv_config_file_name, venv_executables_dir_path,
+    },
+    Config, DependencyPolicy, Error, HuakResult, NetworkSettings, PythonEnvironment,
 };
+use huak_python_manager::RequestedVersion;
 use huak_toolchain::{Channel, LocalToolchain, LocalToolchainResolver, SettingsDb};
 use huak_workspace::{resolve_first, PathMarker};
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
+use std::time::Duration;
 use std::{path::PathBuf, process::Command};
 use toml_edit::Item;
 
@@ -72,7 +79,7 @@ impl Workspace {
         if path.exists() {
             LocalManifest::new(path)
         } else {
-            Err(Error::ManifestFileFound)
+            Err(Error::ManifestFileNotFound)
         }
     }
 
@@ -92,15 +99,134 @@ impl Workspace {
 
     /// Get the current `PythonEnvironment`. The current `PythonEnvironment` is one
     /// found by its configuration file or `Interpreter` nearest baseed on `Config` data.
+    ///
+    /// If the `Config` is set to use the system Python then the resolved Python interpreter's
+    /// own installation is used instead.
     pub fn current_python_environment(&self) -> HuakResult<PythonEnvironment> {
+        if self.config.system_python {
+            return PythonEnvironment::system(self.resolve_python_path()?);
+        }
+
+        // A named environment is always the one at the root, even if another is activated.
+        if self.config.env_name.is_some() {
+            let path = self.root.join(self.venv_name()?);
+            if !directory_is_venv(&path) {
+                return Err(Error::PythonEnvironmentNotFound);
+            }
+            return PythonEnvironment::new(path);
+        }
+
         let path = find_venv_root(&self.config.cwd, &self.root)?;
         let py_env = PythonEnvironment::new(path)?;
 
         Ok(py_env)
     }
 
-    /// Create a `PythonEnvironment` for the `Workspace`.
-    fn new_python_environment(&self) -> HuakResult<PythonEnvironment> {
+    /// Create a `PythonEnvironment` for the `Workspace` with the `Config`'s `VenvOptions`.
+    pub fn new_python_environment(&self) -> HuakResult<PythonEnvironment> {
+        let python_path = self.resolve_python_path()?;
+
+        // Set the name and path of the `PythonEnvironment. Note that we currently only
+        // support virtual environments.
+        let name = self.venv_name()?;
+        let path = self.root.join(&name);
+
+        // Create the `PythonEnvironment`. This uses the `venv` module distributed with Python.
+        // Note that this will fail on systems with minimal Python distributions.
+        let mut cmd = Command::new(python_path);
+        cmd.args(["-m", "venv", &name])
+            .args(self.config.venv_options.args())
+            .current_dir(&self.root);
+
+        if let Err(e) = self.config.run_effect("venv", &mut cmd) {
+            self.config.terminal().print_warning(
+                "a virtual environment could not be created (use --system or \
+                HUAK_SYSTEM_PYTHON=1 to use the Python interpreter directly)",
+            )?;
+            return Err(e);
+        }
+
+        // Nothing was created to use if this is a dry run.
+        if self.config.dry_run {
+            return Err(Error::PythonEnvironmentNotFound);
+        }
+
+        let python_env = PythonEnvironment::new(path)?;
+
+        Ok(python_env)
+    }
+
+    /// Get the name of the `Workspace`'s virtual environment: the default one, or the one for the
+    /// `Config`'s named environment.
+    pub fn venv_name(&self) -> HuakResult<String> {
+        let Some(name) = self.config.env_name.as_ref() else {
+            return Ok(default_venv_name().to_string());
+        };
+
+        if !self.named_environments()?.contains_key(name) {
+            return Err(Error::EnvironmentNotConfigured { name: name.clone() });
+        }
+
+        Ok(named_venv_name(name))
+    }
+
+    /// Get the environments configured in `[tool.huak.envs]` with the optional dependency
+    /// groups they're built with.
+    ///
+    /// ```toml
+    /// [tool.huak.envs.docs]
+    /// groups = ["docs"]
+    /// ```
+    pub fn named_environments(&self) -> HuakResult<BTreeMap<String, Vec<String>>> {
+        let manifest = self.current_local_manifest()?;
+        let Some(envs) = manifest
+            .manifest_data()
+            .huak_table()
+            .and_then(|it| it.get("envs"))
+        else {
+            return Ok(BTreeMap::new());
+        };
+        let configuration_error = |message: String| Error::HuakConfigurationError {
+            table: "tool.huak.envs".to_string(),
+            message,
+        };
+        let Some(envs) = envs.as_table_like() else {
+            return Err(configuration_error("expected a table".to_string()));
+        };
+
+        envs.iter()
+            .map(|(name, it)| {
+                let groups = it
+                    .get("groups")
+                    .map(|it| {
+                        it.as_array()
+                            .map(|it| {
+                                it.iter()
+                                    .filter_map(|it| it.as_str().map(ToString::to_string))
+                                    .collect::<Vec<_>>()
+                            })
+                            .ok_or_else(|| {
+                                configuration_error(format!(
+                                    "{name}.groups must be an array of group names"
+                                ))
+                            })
+                    })
+                    .transpose()?
+                    .unwrap_or_default();
+
+                Ok((name.to_string(), groups))
+            })
+            .collect()
+    }
+
+    /// Resolve the path to the Python interpreter to create `PythonEnvironment`s with.
+    ///
+    /// An interpreter matching the `Workspace`'s pinned Python version is required if one is pinned.
+    fn resolve_python_path(&self) -> HuakResult<PathBuf> {
+        if let Some(version) = self.python_version_pin() {
+            return self.resolve_python_interpreter(&version);
+        }
+
         // Get a snapshot of the environment.
         let env = self.environment();
         // Include toolchain installations when resolving for a Python interpreter to use.
@@ -121,21 +247,47 @@ impl Workspace {
             return Err(Error::PythonNotFound);
         };
 
-        // Set the name and path of the `PythonEnvironment. Note that we currently only
-        // support virtual environments.
-        let name = default_venv_name();
-        let path = self.root.join(name);
-
-        // Create the `PythonEnvironment`. This uses the `venv` module distributed with Python.
-        // Note that this will fail on systems with minimal Python distributions.
-        let args = ["-m", "venv", name];
-        let mut cmd = Command::new(python_path);
-        cmd.args(args).current_dir(&self.root);
-        self.config.terminal().run_command(&mut cmd)?;
+        Ok(python_path)
+    }
 
-        let python_env = PythonEnvironment::new(path)?;
+    /// Resolve the path to a Python interpreter matching the `version`.
+    ///
+    /// A toolchain for the version is preferred. Otherwise an interpreter from the `Environment`
+    /// is used, excluding any activated Python environment.
+    pub fn resolve_python_interpreter(&self, version: &RequestedVersion) -> HuakResult<PathBuf> {
+        self.resolve_local_toolchain(Some(&Channel::from_str(&version.to_string())?))
+            .ok()
+            .and_then(|it| PythonEnvironment::new(it.root().join(".venv")).ok())
+            .map(|it| it.python_path().clone()) // TODO(cnpryer): Perf
+            .or_else(|| {
+                // TODO(cnpryer): Re-export `Interpreter` as public
+                Environment::resolve_python_interpreters()
+                    .interpreters()
+                    .iter()
+                    .filter(|py| {
+                        !active_python_env_path().is_some_and(|it| {
+                            py.path().parent() == Some(&venv_executables_dir_path(it))
+                        })
+                    })
+                    .find(|py| version.matches_version(py.version()))
+                    .map(|py| py.path().clone()) // TODO(cnpryer): Perf
+            })
+            .ok_or(Error::PythonNotFound)
+    }
 
-        Ok(python_env)
+    /// Get the Python version pinned for the `Workspace` with `huak python use`.
+    ///
+    /// ```toml
+    /// [tool.huak]
+    /// python = "3.11"
+    /// ```
+    #[must_use]
+    pub fn python_version_pin(&self) -> Option<RequestedVersion> {
+        self.current_local_manifest()
+            .ok()?
+            .manifest_data()
+            .python_version_pin()
+            .and_then(|it| RequestedVersion::from_str(&it).ok())
     }
 
     /// Get the current toolchain. The current toolchain is found by:
@@ -149,14 +301,571 @@ impl Workspace {
 
         Ok(it)
     }
+
+    /// Get the directory build artifacts are written to. Relative paths are resolved from
+    /// the `Workspace` root. Defaults to `dist`.
+    ///
+    /// ```toml
+    /// [tool.huak]
+    /// dist-dir = "build/dist"
+    /// ```
+    #[must_use]
+    pub fn dist_dir(&self) -> PathBuf {
+        let configured = self.current_local_manifest().ok().and_then(|it| {
+            it.manifest_data()
+                .huak_table()
+                .and_then(|it| it.get("dist-dir"))
+                .and_then(Item::as_str)
+                .map(PathBuf::from)
+        });
+
+        self.root
+            .join(configured.unwrap_or_else(|| PathBuf::from("dist")))
+    }
+
+    /// Get the directory vendored dependencies are downloaded to. Relative paths are resolved
+    /// from the `Workspace` root. Defaults to `vendor`.
+    ///
+    /// ```toml
+    /// [tool.huak]
+    /// vendor-dir = "third_party/wheels"
+    /// ```
+    #[must_use]
+    pub fn vendor_dir(&self) -> PathBuf {
+        let configured = self.current_local_manifest().ok().and_then(|it| {
+            it.manifest_data()
+                .huak_table()
+                .and_then(|it| it.get("vendor-dir"))
+                .and_then(Item::as_str)
+                .map(PathBuf::from)
+        });
+
+        self.root
+            .join(configured.unwrap_or_else(|| PathBuf::from("vendor")))
+    }
+
+    /// Get the optional dependency group `huak kernel install` adds `ipykernel` to.
+    ///
+    /// ```toml
+    /// [tool.huak]
+    /// kernel-group = "jupyter"
+    /// ```
+    ///
+    /// Defaults to `notebooks`.
+    #[must_use]
+    pub fn kernel_group(&self) -> String {
+        self.current_local_manifest()
+            .ok()
+            .and_then(|it| {
+                it.manifest_data()
+                    .huak_table()
+                    .and_then(|it| it.get("kernel-group"))
+                    .and_then(Item::as_str)
+                    .map(ToString::to_string)
+            })
+            .unwrap_or_else(|| "notebooks".to_string())
+    }
+
+    /// Check if the project's changelog must be verified before it's published.
+    ///
+    /// ```toml
+    /// [tool.huak]
+    /// require-changelog = true
+    /// ```
+    #[must_use]
+    pub fn requires_changelog(&self) -> bool {
+        self.current_local_manifest().ok().is_some_and(|it| {
+            it.manifest_data()
+                .huak_table()
+                .and_then(|it| it.get("require-changelog"))
+                .and_then(Item::as_bool)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Check if warnings should fail commands run in the workspace, like `--strict`.
+    ///
+    /// ```toml
+    /// [tool.huak]
+    /// strict = true
+    /// ```
+    #[must_use]
+    pub fn is_strict(&self) -> bool {
+        self.current_local_manifest().ok().is_some_and(|it| {
+            it.manifest_data()
+                .huak_table()
+                .and_then(|it| it.get("strict"))
+                .and_then(Item::as_bool)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Get the directories configured to discover tests in, relative to the `Workspace` root.
+    ///
+    /// ```toml
+    /// [tool.huak.test]
+    /// paths = ["tests", "integration"]
+    /// ```
+    #[must_use]
+    pub fn test_paths(&self) -> Vec<PathBuf> {
+        let Ok(manifest) = self.current_local_manifest() else {
+            return Vec::new();
+        };
+
+        manifest
+            .manifest_data()
+            .huak_table()
+            .and_then(|it| it.get("test"))
+            .and_then(|it| it.get("paths"))
+            .and_then(Item::as_array)
+            .map(|it| {
+                it.iter()
+                    .filter_map(|it| it.as_str().map(PathBuf::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the files the built artifacts are expected to contain beyond the project's package
+    /// (`include`) and the files they leave out on purpose (`exclude`), as globs relative to the
+    /// `Workspace` root.
+    ///
+    /// ```toml
+    /// [tool.huak.build]
+    /// include = ["tests", "docs/*.md"]
+    /// exclude = ["src/my_project/fixtures"]
+    /// ```
+    #[must_use]
+    pub fn build_contents(&self) -> FileFilter {
+        let Ok(manifest) = self.current_local_manifest() else {
+            return FileFilter::default();
+        };
+        let table = manifest
+            .manifest_data()
+            .huak_table()
+            .and_then(|it| it.get("build"))
+            .cloned();
+        let patterns = |key: &str| -> Vec<String> {
+            table
+                .as_ref()
+                .and_then(|it| it.get(key))
+                .and_then(Item::as_array)
+                .map(|it| {
+                    it.iter()
+                        .filter_map(|it| it.as_str().map(ToString::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        FileFilter {
+            include: patterns("include"),
+            exclude: patterns("exclude"),
+        }
+    }
+
+    /// Get the patterns of dependencies `huak update` may upgrade past their installed major
+    /// version.
+    ///
+    /// ```toml
+    /// [tool.huak.update]
+    /// allow-major = ["internal-*"]
+    /// ```
+    #[must_use]
+    pub fn update_allow_major(&self) -> Vec<String> {
+        let Ok(manifest) = self.current_local_manifest() else {
+            return Vec::new();
+        };
+
+        manifest
+            .manifest_data()
+            .huak_table()
+            .and_then(|it| it.get("update"))
+            .and_then(|it| it.get("allow-major"))
+            .and_then(Item::as_array)
+            .map(|it| {
+                it.iter()
+                    .filter_map(|it| it.as_str().map(ToString::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the `NetworkSettings` for reaching the package index. A relative `cert` is resolved
+    /// from the `Workspace` root.
+    ///
+    /// ```toml
+    /// [tool.huak.network]
+    /// index-url = "https://pypi.example.com/pypi"
+    /// proxy = "http://proxy.example.com:3128"
+    /// cert = "certs/ca.pem"
+    /// ```
+    #[must_use]
+    pub fn network_settings(&self) -> NetworkSettings {
+        let mut settings = NetworkSettings::default();
+        let Ok(manifest) = self.current_local_manifest() else {
+            return settings;
+        };
+        let Some(table) = manifest
+            .manifest_data()
+            .huak_table()
+            .and_then(|it| it.get("network"))
+        else {
+            return settings;
+        };
+        let value = |key: &str| {
+            table
+                .get(key)
+                .and_then(Item::as_str)
+                .map(ToString::to_string)
+        };
+
+        if let Some(it) = value("index-url") {
+            settings.index_url = it;
+        }
+        settings.proxy = value("proxy");
+        settings.cert = value("cert").map(|it| self.root.join(it));
+
+        settings
+    }
+
+    /// Get the environment variables configured for an operation's subprocesses. Values are
+    /// returned uninterpolated.
+    ///
+    /// ```toml
+    /// [tool.huak.test]
+    /// env = { PYTEST_ADDOPTS = "-p no:cacheprovider" }
+    /// ```
+    #[must_use]
+    pub fn operation_env(&self, operation: &str) -> HashMap<String, String> {
+        let Ok(manifest) = self.current_local_manifest() else {
+            return HashMap::new();
+        };
+
+        manifest
+            .manifest_data()
+            .huak_table()
+            .and_then(|it| it.get(operation))
+            .and_then(|it| it.get("env"))
+            .and_then(Item::as_table_like)
+            .map(|it| {
+                it.iter()
+                    .filter_map(|(key, item)| {
+                        let value = item.as_value()?;
+                        let value = value.as_str().map_or_else(
+                            || value.to_string().trim().to_string(),
+                            ToString::to_string,
+                        );
+                        Some((key.to_string(), value))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the timeout configured for an operation's subprocesses.
+    ///
+    /// ```toml
+    /// [tool.huak.timeouts]
+    /// install = 600
+    /// test = 1800
+    /// ```
+    #[must_use]
+    pub fn operation_timeout(&self, operation: &str) -> Option<Duration> {
+        let manifest = self.current_local_manifest().ok()?;
+
+        manifest
+            .manifest_data()
+            .huak_table()
+            .and_then(|it| it.get("timeouts"))
+            .and_then(|it| it.get(operation))
+            .and_then(Item::as_integer)
+            .and_then(|it| u64::try_from(it).ok())
+            .map(Duration::from_secs)
+    }
+
+    /// Get the defaults configured for a command's options, in the order they're listed. `args`
+    /// is the default for the command's trailing arguments. The `env` table (and `paths` for
+    /// `test`) are configured separately and aren't included.
+    ///
+    /// With a `profile` its `[tool.huak.profile.<name>.<command>]` defaults are merged over the
+    /// command's, so options it doesn't set keep the project's defaults.
+    ///
+    /// ```toml
+    /// [tool.huak.lint]
+    /// no-types = true
+    /// select = "E,F"
+    /// args = ["--show-fixes"]
+    ///
+    /// [tool.huak.profile.ci.lint]
+    /// args = ["--output-format", "github"]
+    /// ```
+    pub fn command_defaults(
+        &self,
+        command: &str,
+        profile: Option<&str>,
+    ) -> HuakResult<Vec<(String, CommandDefault)>> {
+        let Ok(manifest) = self.current_local_manifest() else {
+            return match profile {
+                Some(it) => profile_table(None, it).map(|_| Vec::new()),
+                None => Ok(Vec::new()),
+            };
+        };
+        let huak = manifest.manifest_data().huak_table();
+        let mut defaults = match huak
+            .and_then(|it| it.get(command))
+            .and_then(Item::as_table_like)
+        {
+            Some(table) => table_defaults(table, command, &format!("tool.huak.{command}"))?,
+            None => Vec::new(),
+        };
+
+        if let Some(name) = profile {
+            let table = profile_table(huak, name)?;
+            let Some(table) = table.get(command).and_then(Item::as_table_like) else {
+                return Ok(defaults);
+            };
+
+            for (key, default) in table_defaults(
+                table,
+                command,
+                &format!("tool.huak.profile.{name}.{command}"),
+            )? {
+                let id = key.replace('_', "-");
+                match defaults.iter_mut().find(|it| it.0.replace('_', "-") == id) {
+                    Some(it) => *it = (key, default),
+                    None => defaults.push((key, default)),
+                }
+            }
+        }
+
+        Ok(defaults)
+    }
+
+    /// Get the defaults a profile sets for the global options (like `quiet`), in the order
+    /// they're listed. The profile's tables are its commands' defaults (see `command_defaults`).
+    ///
+    /// ```toml
+    /// [tool.huak.profile.ci]
+    /// quiet = true
+    /// strict = true
+    /// ```
+    pub fn profile_defaults(&self, profile: &str) -> HuakResult<Vec<(String, CommandDefault)>> {
+        let manifest = self.current_local_manifest().ok();
+        let table = profile_table(
+            manifest
+                .as_ref()
+                .and_then(|it| it.manifest_data().huak_table()),
+            profile,
+        )?;
+
+        table
+            .iter()
+            .filter(|(_, item)| !item.is_table_like())
+            .map(|(key, item)| {
+                let default = item
+                    .as_value()
+                    .and_then(CommandDefault::from_value)
+                    .ok_or_else(|| Error::HuakConfigurationError {
+                        table: format!("tool.huak.profile.{profile}"),
+                        message: format!("{key} must be a boolean, string, number, or array"),
+                    })?;
+                Ok((key.to_string(), default))
+            })
+            .collect()
+    }
+
+    /// Get the names of the commands a profile sets defaults for: its tables, in the order
+    /// they're listed.
+    pub fn profile_commands(&self, profile: &str) -> HuakResult<Vec<String>> {
+        let manifest = self.current_local_manifest().ok();
+        let table = profile_table(
+            manifest
+                .as_ref()
+                .and_then(|it| it.manifest_data().huak_table()),
+            profile,
+        )?;
+
+        Ok(table
+            .iter()
+            .filter(|(_, item)| item.is_table_like())
+            .map(|(key, _)| key.to_string())
+            .collect())
+    }
+
+    /// Get the `DependencyPolicy` configured for the `Workspace`.
+    ///
+    /// ```toml
+    /// [tool.huak.policy]
+    /// max-dependencies = 50
+    /// ```
+    pub fn dependency_policy(&self) -> HuakResult<DependencyPolicy> {
+        let manifest = self.current_local_manifest()?;
+
+        manifest
+            .manifest_data()
+            .huak_table()
+            .and_then(|it| it.get("policy"))
+            .map_or_else(
+                || Ok(DependencyPolicy::default()),
+                DependencyPolicy::from_item,
+            )
+    }
+}
+
+/// A default for one of a command's options, configured in `[tool.huak.<command>]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommandDefault {
+    /// Whether a flag (like `--no-types`) is on.
+    Flag(bool),
+    /// The values of an option, or of the trailing arguments.
+    Values(Vec<String>),
+}
+
+impl CommandDefault {
+    fn from_value(value: &toml_edit::Value) -> Option<CommandDefault> {
+        let scalar = |value: &toml_edit::Value| match value {
+            toml_edit::Value::String(it) => Some(it.value().clone()),
+            toml_edit::Value::Integer(it) => Some(it.value().to_string()),
+            toml_edit::Value::Float(it) => Some(it.value().to_string()),
+            _ => None,
+        };
+
+        match value {
+            toml_edit::Value::Boolean(it) => Some(CommandDefault::Flag(*it.value())),
+            toml_edit::Value::Array(it) => it
+                .iter()
+                .map(scalar)
+                .collect::<Option<Vec<_>>>()
+                .map(CommandDefault::Values),
+            it => scalar(it).map(|it| CommandDefault::Values(vec![it])),
+        }
+    }
+}
+
+/// Display the default as it's written in TOML. A single value is written as a string.
+impl std::fmt::Display for CommandDefault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandDefault::Flag(it) => write!(f, "{it}"),
+            CommandDefault::Values(it) if it.len() == 1 => {
+                write!(f, "{}", toml_edit::Value::from(it[0].as_str()))
+            }
+            CommandDefault::Values(it) => write!(
+                f,
+                "{}",
+                it.iter().map(String::as_str).collect::<toml_edit::Array>()
+            ),
+        }
+    }
+}
+
+/// Get the `[tool.huak.profile.<name>]` table, or an error listing the profiles there are.
+fn profile_table<'a>(
+    huak: Option<&'a toml_edit::Table>,
+    name: &str,
+) -> HuakResult<&'a dyn toml_edit::TableLike> {
+    let profiles = huak
+        .and_then(|it| it.get("profile"))
+        .and_then(Item::as_table_like);
+
+    if let Some(it) = profiles
+        .and_then(|it| it.get(name))
+        .and_then(Item::as_table_like)
+    {
+        return Ok(it);
+    }
+
+    let names = profiles
+        .map(|it| {
+            it.iter()
+                .filter(|(_, item)| item.is_table_like())
+                .map(|(key, _)| key.to_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    Err(Error::ProfileNotFound {
+        name: name.to_string(),
+        available: names,
+    })
+}
+
+/// Get the defaults for the `command`'s options in a `table`. `name` is the table's name in
+/// error messages.
+fn table_defaults(
+    table: &dyn toml_edit::TableLike,
+    command: &str,
+    name: &str,
+) -> HuakResult<Vec<(String, CommandDefault)>> {
+    table
+        .iter()
+        .filter(|(key, item)| {
+            *key != "env"
+                && !(command == "test" && *key == "paths")
+                && !(command == "update" && *key == "allow-major" && item.is_array())
+        })
+        .map(|(key, item)| {
+            let default = item
+                .as_value()
+                .and_then(CommandDefault::from_value)
+                .ok_or_else(|| Error::HuakConfigurationError {
+                    table: name.to_string(),
+                    message: format!("{key} must be a boolean, string, number, or array"),
+                })?;
+            Ok((key.to_string(), default))
+        })
+        .collect()
 }
 
 /// A struct used to configure options for `Workspace`s.
 pub struct WorkspaceOptions {
     /// Inidcate the `Workspace` should use git.
     pub uses_git: bool,
+    /// Overwrite existing files when generating the `Workspace`.
+    pub force: bool,
     /// Trailing argument values.
     pub values: Option<Vec<String>>,
+    /// Commit the generated files with this message once the git repository is initialized.
+    pub initial_commit: Option<String>,
+}
+
+/// Options for creating virtual environments with Python's `venv` module.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, Default)]
+pub struct VenvOptions {
+    /// Give the environment access to the system site-packages directory.
+    pub system_site_packages: bool,
+    /// The prompt shown while the environment is activated (defaults to its directory name).
+    pub prompt: Option<String>,
+    /// Copy the Python executable instead of symlinking it.
+    pub copies: bool,
+    /// Symlink the Python executable instead of copying it.
+    pub symlinks: bool,
+}
+
+impl VenvOptions {
+    /// Get the `venv` arguments for the options. No options leaves `venv`'s defaults.
+    #[must_use]
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        if self.system_site_packages {
+            args.push("--system-site-packages".to_string());
+        }
+
+        if let Some(it) = self.prompt.as_ref() {
+            args.extend(["--prompt".to_string(), it.clone()]);
+        }
+
+        if self.copies {
+            args.push("--copies".to_string());
+        } else if self.symlinks {
+            args.push("--symlinks".to_string());
+        }
+
+        args
+    }
 }
 
 /// Search for a Python virtual environment.
@@ -64,10 +64,10 @@
 
 pub use error::Error;
 use pep508_rs::Requirement;
-use std::{collections::HashMap, fmt::Display, path::Path, str::FromStr};
+use std::{collections::HashMap, fmt::Display, io::Write, path::Path, str::FromStr};
 use toml_edit::{Array, Document, Formatted, Item, Table, Value};
+pub use utils::{canonical_name, sanitize_str, value_to_sanitized_string};
 use utils::{format_array, format_table};
-pub use utils::{sanitize_str, value_to_sanitized_string};
 
 mod error;
 mod utils;
@@ -130,6 +130,14 @@ impl PyProjectToml {
         self.get_mut("project").and_then(Item::as_table_mut)
     }
 
+    /// Get the `[project]` table, adding an empty one if the document doesn't have one.
+    pub fn project_table_or_insert_mut(&mut self) -> Option<&mut Table> {
+        self.doc
+            .entry("project")
+            .or_insert(Item::Table(Table::new()))
+            .as_table_mut()
+    }
+
     // TODO(cnpryer): Tablelike or section(?)
     #[must_use]
     pub fn tool_table(&self) -> Option<&Table> {
@@ -141,6 +149,160 @@ impl PyProjectToml {
         self.get_mut("tool").and_then(Item::as_table_mut)
     }
 
+    /// Get Huak's `[tool.huak]` table.
+    #[must_use]
+    pub fn huak_table(&self) -> Option<&Table> {
+        self.tool_table()
+            .and_then(|it| it.get("huak"))
+            .and_then(Item::as_table)
+    }
+
+    /// Get the hashes pinned for dependencies in `[tool.huak.hashes]`.
+    ///
+    /// ```toml
+    /// [tool.huak.hashes]
+    /// requests = ["sha256:..."]
+    /// ```
+    #[must_use]
+    pub fn dependency_hashes(&self) -> HashMap<String, Vec<String>> {
+        let Some(table) = self
+            .huak_table()
+            .and_then(|it| it.get("hashes"))
+            .and_then(Item::as_table_like)
+        else {
+            return HashMap::new();
+        };
+
+        table
+            .iter()
+            .filter_map(|(name, it)| {
+                let hashes = it
+                    .as_array()?
+                    .iter()
+                    .filter_map(|it| it.as_str().map(ToString::to_string))
+                    .collect();
+                Some((canonical_name(name), hashes))
+            })
+            .collect()
+    }
+
+    pub fn set_dependency_hashes(&mut self, dependency: &str, hashes: &[String]) -> &mut Self {
+        if self.doc.get("tool").is_none() {
+            self.doc["tool"] = implicit_table();
+        }
+
+        if self.doc["tool"].get("huak").is_none() {
+            self.doc["tool"]["huak"] = implicit_table();
+        }
+
+        self.remove_dependency_hashes(dependency);
+
+        let item = &mut self.doc["tool"]["huak"]["hashes"];
+
+        if item.is_none() {
+            *item = Item::Table(Table::new());
+        }
+
+        item[dependency] = Item::Value(Value::Array(hashes.iter().collect()));
+
+        self
+    }
+
+    pub fn remove_dependency_hashes(&mut self, dependency: &str) -> &mut Self {
+        if let Some(table) = self
+            .tool_table_mut()
+            .and_then(|it| it.get_mut("huak"))
+            .and_then(|it| it.get_mut("hashes"))
+            .and_then(Item::as_table_like_mut)
+        {
+            let dependency = canonical_name(dependency);
+            let names = table
+                .iter()
+                .filter(|(name, _)| canonical_name(name) == dependency)
+                .map(|(name, _)| name.to_string())
+                .collect::<Vec<_>>();
+
+            for name in names {
+                table.remove(&name);
+            }
+        }
+
+        self
+    }
+
+    /// Get the versions pinned for the tools Huak installs (like `ruff`) in
+    /// `[tool.huak.toolchain-lock]`, keyed by canonical name.
+    ///
+    /// ```toml
+    /// [tool.huak.toolchain-lock]
+    /// ruff = "0.4.2"
+    /// ```
+    #[must_use]
+    pub fn tool_version_pins(&self) -> HashMap<String, String> {
+        let Some(table) = self
+            .huak_table()
+            .and_then(|it| it.get("toolchain-lock"))
+            .and_then(Item::as_table_like)
+        else {
+            return HashMap::new();
+        };
+
+        table
+            .iter()
+            .filter_map(|(name, it)| Some((canonical_name(name), it.as_str()?.to_string())))
+            .collect()
+    }
+
+    pub fn set_tool_version_pin(&mut self, tool: &str, version: &str) -> &mut Self {
+        if self.doc.get("tool").is_none() {
+            self.doc["tool"] = implicit_table();
+        }
+
+        if self.doc["tool"].get("huak").is_none() {
+            self.doc["tool"]["huak"] = implicit_table();
+        }
+
+        let item = &mut self.doc["tool"]["huak"]["toolchain-lock"];
+
+        if item.is_none() {
+            *item = Item::Table(Table::new());
+        }
+
+        item[canonical_name(tool).as_str()] =
+            Item::Value(Value::String(Formatted::new(version.to_string())));
+
+        self
+    }
+
+    /// Get the Python version pinned in `[tool.huak]`.
+    ///
+    /// ```toml
+    /// [tool.huak]
+    /// python = "3.11"
+    /// ```
+    #[must_use]
+    pub fn python_version_pin(&self) -> Option<String> {
+        self.huak_table()
+            .and_then(|it| it.get("python"))
+            .and_then(Item::as_str)
+            .map(ToString::to_string)
+    }
+
+    pub fn set_python_version_pin(&mut self, version: &str) -> &mut Self {
+        if self.doc.get("tool").is_none() {
+            self.doc["tool"] = implicit_table();
+        }
+
+        if self.doc["tool"].get("huak").is_none() {
+            self.doc["tool"]["huak"] = Item::Table(Table::new());
+        }
+
+        self.doc["tool"]["huak"]["python"] =
+            Item::Value(Value::String(Formatted::new(version.to_string())));
+
+        self
+    }
+
     #[must_use]
     pub fn project_name(&self) -> Option<String> {
         self.project_table()
@@ -168,6 +330,15 @@ impl PyProjectToml {
         self
     }
 
+    /// Check if a `[project]` field is listed in `dynamic`, so it's provided by the build backend.
+    #[must_use]
+    pub fn is_project_field_dynamic(&self, field: &str) -> bool {
+        self.project_table()
+            .and_then(|it| it.get("dynamic"))
+            .and_then(Item::as_array)
+            .is_some_and(|it| it.iter().any(|it| it.as_str() == Some(field)))
+    }
+
     #[must_use]
     pub fn project_description(&self) -> Option<String> {
         self.project_table()
@@ -295,6 +466,15 @@ impl PyProjectToml {
         self
     }
 
+    /// Remove an optional dependency group and every dependency in it.
+    pub fn remove_project_optional_dependency_group(&mut self, group: &str) -> &mut Self {
+        if let Some(table) = self.project_optional_dependencies_mut() {
+            table.remove(group);
+        }
+
+        self
+    }
+
     #[must_use]
     pub fn contains_project_optional_dependency_any(&self, dependency: &str) -> bool {
         let Some(keys) = self.project_optional_dependency_groups() else {
@@ -338,8 +518,44 @@ fn format_pyproject_toml(pyproject_toml: &mut PyProjectToml) -> &mut PyProjectTo
 }
 
 /// Save the `PyProjectToml` to a filepath.
+///
+/// The TOML is written to a temporary file next to `path` that's then renamed over it, so an
+/// interrupted write never leaves a partially written file behind.
 fn write_pyproject_toml<T: AsRef<Path>>(toml: &PyProjectToml, path: T) -> Result<(), Error> {
-    Ok(std::fs::write(path, toml.to_string())?)
+    let path = path.as_ref();
+    let contents = toml.to_string();
+    let file_name = path
+        .file_name()
+        .map(|it| it.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()));
+
+    let written =
+        write_synced(&temp_path, &contents, path).and_then(|()| std::fs::rename(&temp_path, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+
+    Ok(written?)
+}
+
+/// Write `contents` to `path` and flush it to disk, keeping the permissions of the file at
+/// `original` if there is one.
+fn write_synced(path: &Path, contents: &str, original: &Path) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    file.write_all(contents.as_bytes())?;
+    if let Ok(metadata) = std::fs::metadata(original) {
+        file.set_permissions(metadata.permissions())?;
+    }
+
+    file.sync_all()
+}
+
+/// Create a table that's only written if it has non-table entries.
+fn implicit_table() -> Item {
+    let mut table = Table::new();
+    table.set_implicit(true);
+    Item::Table(table)
 }
 
 // TODO(cnpryer): If contains requirement
@@ -377,12 +593,15 @@ fn remove_array_dependency(item: &mut Item, dependency: &str) {
     }
 }
 
+/// Check if a requirement string is for the `dependency`, comparing canonical names.
 fn matches_dependency(s: &str, dependency: &str) -> bool {
     let Ok(req) = Requirement::from_str(dependency) else {
         return false;
     };
 
-    Requirement::from_str(s).map_or(false, |it| it.name == req.name)
+    Requirement::from_str(s).map_or(false, |it| {
+        canonical_name(&it.name) == canonical_name(&req.name)
+    })
 }
 
 impl FromStr for PyProjectToml {
@@ -506,6 +725,8 @@ strip = true
         let pyproject_toml = PyProjectToml::read_toml(workspace.join("pyproject.toml")).unwrap();
 
         assert_eq!(&pyproject_toml.to_string(), content);
+        // The temporary file the TOML was written to was renamed into place.
+        assert_eq!(std::fs::read_dir(&workspace).unwrap().count(), 1);
     }
 
     #[test]
@@ -764,4 +985,250 @@ hello-world = "python -c 'print(\"hello, world.\")'"
 members = ["projects/*"]
 "#
     }
+
+    #[test]
+    fn test_dependency_hashes() {
+        let mut pyproject_toml = PyProjectToml::from_str(
+            r#"[project]
+name = "mock-project"
+"#,
+        )
+        .unwrap();
+        let hashes = vec!["sha256:abc".to_string()];
+
+        pyproject_toml.set_dependency_hashes("requests", &hashes);
+
+        assert_eq!(
+            pyproject_toml.to_string(),
+            r#"[project]
+name = "mock-project"
+
+[tool.huak.hashes]
+requests = ["sha256:abc"]
+"#
+        );
+        assert_eq!(
+            pyproject_toml.dependency_hashes(),
+            HashMap::from([("requests".to_string(), hashes)])
+        );
+
+        pyproject_toml.remove_dependency_hashes("requests");
+
+        assert!(pyproject_toml.dependency_hashes().is_empty());
+    }
+
+    #[test]
+    fn test_tool_version_pins() {
+        let mut pyproject_toml = PyProjectToml::from_str(
+            r#"[project]
+name = "mock-project"
+"#,
+        )
+        .unwrap();
+
+        assert!(pyproject_toml.tool_version_pins().is_empty());
+
+        pyproject_toml
+            .set_tool_version_pin("ruff", "0.1.0")
+            .set_tool_version_pin("Ruff", "0.4.2")
+            .set_tool_version_pin("pytest", "8.2.0");
+
+        assert_eq!(
+            pyproject_toml.to_string(),
+            r#"[project]
+name = "mock-project"
+
+[tool.huak.toolchain-lock]
+ruff = "0.4.2"
+pytest = "8.2.0"
+"#
+        );
+        assert_eq!(
+            pyproject_toml.tool_version_pins(),
+            HashMap::from([
+                ("ruff".to_string(), "0.4.2".to_string()),
+                ("pytest".to_string(), "8.2.0".to_string())
+            ])
+        );
+    }
+
+    #[test]
+    fn test_remove_project_optional_dependency_group() {
+        let mut pyproject_toml = PyProjectToml::from_str(
+            r#"[project]
+name = "mock-project"
+
+[project.optional-dependencies]
+docs = ["sphinx", "furo"]
+test = ["pytest"]
+"#,
+        )
+        .unwrap();
+
+        pyproject_toml
+            .remove_project_optional_dependency_group("docs")
+            .remove_project_optional_dependency_group("missing");
+
+        assert_eq!(
+            pyproject_toml.to_string(),
+            r#"[project]
+name = "mock-project"
+
+[project.optional-dependencies]
+test = ["pytest"]
+"#
+        );
+    }
+
+    #[test]
+    fn test_python_version_pin() {
+        let mut pyproject_toml = PyProjectToml::from_str(
+            r#"[project]
+name = "mock-project"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(pyproject_toml.python_version_pin(), None);
+
+        pyproject_toml.set_python_version_pin("3.11");
+        pyproject_toml.set_python_version_pin("3.12");
+
+        assert_eq!(
+            pyproject_toml.to_string(),
+            r#"[project]
+name = "mock-project"
+
+[tool.huak]
+python = "3.12"
+"#
+        );
+        assert_eq!(
+            pyproject_toml.python_version_pin(),
+            Some("3.12".to_string())
+        );
+    }
+
+    #[test]
+    fn test_is_project_field_dynamic() {
+        let pyproject_toml = PyProjectToml::from_str(
+            r#"[project]
+name = "mock-project"
+dynamic = ["version", "readme"]
+"#,
+        )
+        .unwrap();
+
+        assert!(pyproject_toml.is_project_field_dynamic("version"));
+        assert!(!pyproject_toml.is_project_field_dynamic("description"));
+        assert!(!PyProjectToml::default().is_project_field_dynamic("version"));
+    }
+
+    /// Generate spellings of a package name with different separators and casing.
+    fn name_variants(name: &str, count: usize) -> Vec<String> {
+        const SEPARATORS: [&str; 6] = ["-", "_", ".", "--", "_.", "-_."];
+        let mut seed = 0x2545_f491_u64;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            usize::try_from(seed % 1024).unwrap()
+        };
+
+        (0..count)
+            .map(|_| {
+                name.split(['-', '_', '.'])
+                    .map(|part| {
+                        part.chars()
+                            .map(|c| {
+                                if next() % 2 == 0 {
+                                    c.to_ascii_uppercase()
+                                } else {
+                                    c.to_ascii_lowercase()
+                                }
+                            })
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(SEPARATORS[next() % SEPARATORS.len()])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_canonical_name() {
+        assert_eq!(canonical_name("zope.interface"), "zope-interface");
+        assert_eq!(canonical_name("Flask_SQLAlchemy"), "flask-sqlalchemy");
+        assert_eq!(canonical_name("typing__extensions"), "typing-extensions");
+        assert_eq!(canonical_name("a-_.b"), "a-b");
+        assert_ne!(
+            canonical_name("flask-sqlalchemy"),
+            canonical_name("flasksqlalchemy")
+        );
+
+        for name in [
+            "zope.interface",
+            "Flask_SQLAlchemy",
+            "typing-extensions",
+            "ruff",
+        ] {
+            for variant in name_variants(name, 64) {
+                assert_eq!(canonical_name(&variant), canonical_name(name), "{variant}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_dependency_name_variants() {
+        let mut pyproject_toml = PyProjectToml::from_str(
+            r#"[project]
+name = "mock-project"
+dependencies = ["zope.interface>=6", "Flask_SQLAlchemy==3.1.1"]
+
+[project.optional-dependencies]
+dev = ["typing-extensions"]
+"#,
+        )
+        .unwrap();
+
+        for variant in name_variants("zope.interface", 32)
+            .into_iter()
+            .chain(name_variants("Flask_SQLAlchemy", 32))
+        {
+            assert!(
+                pyproject_toml.contains_project_dependency(&variant),
+                "{variant}"
+            );
+        }
+        for variant in name_variants("typing-extensions", 32) {
+            assert!(
+                pyproject_toml.contains_project_optional_dependency(&variant, "dev"),
+                "{variant}"
+            );
+        }
+        assert!(!pyproject_toml.contains_project_dependency_any("zope-interfaces"));
+
+        pyproject_toml
+            .remove_project_dependency("ZOPE_Interface")
+            .remove_project_optional_dependency("Typing.Extensions", "dev")
+            .set_dependency_hashes("Flask_SQLAlchemy", &["sha256:abc".to_string()])
+            .set_dependency_hashes("flask-sqlalchemy", &["sha256:def".to_string()]);
+
+        assert_eq!(
+            pyproject_toml.project_dependencies(),
+            Some(vec!["Flask_SQLAlchemy==3.1.1".to_string()])
+        );
+        assert!(!pyproject_toml.contains_project_dependency_any("typing-extensions"));
+        assert_eq!(
+            pyproject_toml.dependency_hashes(),
+            HashMap::from([(
+                "flask-sqlalchemy".to_string(),
+                vec!["sha256:def".to_string()]
+            )])
+        );
+
+        pyproject_toml.remove_dependency_hashes("FLASK.sqlalchemy");
+
+        assert!(pyproject_toml.dependency_hashes().is_empty());
+    }
 }
@@ -3,7 +3,8 @@ use toml_edit::{Array, RawString, Table, Value};
 #[must_use]
 pub fn value_to_sanitized_string(value: &Value) -> String {
     match value {
-        Value::String(string) => sanitize_str(string.value()),
+        // The value is already unquoted, and a quote can end it (like a marker's `"3.11"`).
+        Value::String(string) => string.value().trim().to_string(),
         _ => value.to_string(),
     }
 }
@@ -17,6 +18,27 @@ pub fn sanitize_str(s: &str) -> String {
         .to_string()
 }
 
+/// Normalize a package name so different spellings of it can be compared (see PEP 503).
+///
+/// The name is lowercased and each run of `-`, `_`, and `.` is replaced with a single `-`.
+#[must_use]
+pub fn canonical_name(name: &str) -> String {
+    let mut canonical = String::with_capacity(name.len());
+
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            // Every `-` written so far ends a run, so this only collapses the current one.
+            if !canonical.ends_with('-') {
+                canonical.push('-');
+            }
+        } else {
+            canonical.extend(c.to_lowercase());
+        }
+    }
+
+    canonical
+}
+
 pub fn format_table(table: &mut Table) {
     for array in table.iter_mut().filter_map(|(_, v)| v.as_array_mut()) {
         format_array(array);
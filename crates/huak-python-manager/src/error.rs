@@ -6,6 +6,10 @@ use thiserror::Error as ThisError;
 #[allow(clippy::enum_variant_names)]
 #[derive(ThisError, Debug)]
 pub enum Error {
+    #[error("a download was interrupted: {0}")]
+    DownloadInterrupted(String),
+    #[error("a problem with io occurred: {0}")]
+    IOError(#[from] std::io::Error),
     #[error("a release option is invalid: {0}")]
     InvalidReleaseOption(String),
     #[error("a version is invalid: {0}")]
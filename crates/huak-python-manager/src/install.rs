@@ -1,9 +1,34 @@
 use crate::{error::Error, releases::Release};
+use reqwest::{blocking::Client, header::RANGE, StatusCode};
 use sha2::{Digest, Sha256};
-use std::path::{Path, PathBuf};
+use std::{
+    ffi::OsString,
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 use tar::Archive;
 use zstd::stream::read::Decoder;
 
+/// The most times a download is attempted before giving up.
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+/// How long connecting to the download server can take.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a download can go without receiving any data before the attempt fails.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How much of a release's archive has been downloaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// The bytes downloaded so far, including any resumed from an earlier attempt.
+    pub downloaded: u64,
+    /// The size of the archive, if the server reported it.
+    pub total: Option<u64>,
+}
+
 /// An API for interacting with the release directory.
 ///
 /// Python Standalone Builds is a source for Python distributions. The location of the installed
@@ -109,18 +134,147 @@ impl PythonReleaseDir {
 }
 
 pub fn install_with_target<T: Into<PathBuf>>(release: &Release, target: T) -> Result<(), Error> {
-    let buffer = download_release(release)?;
-    validate_checksum(&buffer, release.checksum)?;
+    install_with_progress(release, target, |_| {})
+}
+
+/// Install the `release` to the `target` directory, calling `progress` as its archive downloads.
+///
+/// The archive is downloaded to `<target>.part`. An interrupted download is retried, resuming
+/// from what was already written with an HTTP range request, and so is running the install
+/// again. The archive is only unpacked once its checksum is verified, and it's unpacked to
+/// `<target>.tmp` before being moved to `target`, so `target` never holds a partial install.
+pub fn install_with_progress<T: Into<PathBuf>>(
+    release: &Release,
+    target: T,
+    mut progress: impl FnMut(DownloadProgress),
+) -> Result<(), Error> {
+    let target = target.into();
+    let part = with_suffix(&target, ".part");
+    let unpacked = with_suffix(&target, ".tmp");
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    download_release_to(release.url, &part, &mut progress)?;
+    if let Err(e) = validate_checksum(File::open(&part)?, release.checksum) {
+        // A corrupt download can't be resumed, so the next attempt starts over.
+        std::fs::remove_file(&part)?;
+        return Err(e);
+    }
 
+    if unpacked.exists() {
+        std::fs::remove_dir_all(&unpacked)?;
+    }
     // TODO(cnpryer): Support more archive formats.
-    let decoded =
-        Decoder::with_buffer(buffer.as_slice()).map_err(|e| Error::ZstdError(e.to_string()))?;
+    let decoded = Decoder::new(File::open(&part)?).map_err(|e| Error::ZstdError(e.to_string()))?;
+    Archive::new(decoded)
+        .unpack(&unpacked)
+        .map_err(|e| Error::TarError(e.to_string()))?;
+
+    if target.exists() {
+        std::fs::remove_dir_all(&target)?;
+    }
+    std::fs::rename(&unpacked, &target)?;
+    std::fs::remove_file(&part)?;
+
+    Ok(())
+}
 
-    let mut archive = Archive::new(decoded);
+/// Get the `path` with a `suffix` appended to its file name.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Download the file at the `url` to the `path`, resuming from what the `path` already has.
+/// Interrupted and stalled downloads are retried (resuming each time) up to `DOWNLOAD_ATTEMPTS`
+/// times.
+pub(crate) fn download_release_to(
+    url: &str,
+    path: &Path,
+    progress: &mut impl FnMut(DownloadProgress),
+) -> Result<(), Error> {
+    // The blocking client's timeout applies to waiting for the response and to each read of the
+    // body rather than the whole download, so large archives only fail when the data stops.
+    let client = Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(READ_TIMEOUT)
+        .build()?;
+
+    download_with_retries(&client, url, path, progress)
+}
 
-    archive
-        .unpack(target.into())
-        .map_err(|e| Error::TarError(e.to_string()))
+fn download_with_retries(
+    client: &Client,
+    url: &str,
+    path: &Path,
+    progress: &mut impl FnMut(DownloadProgress),
+) -> Result<(), Error> {
+    let mut attempt = 1;
+
+    loop {
+        match download_attempt(client, url, path, progress) {
+            Err(Error::ReqwestError(_) | Error::IOError(_) | Error::DownloadInterrupted(_))
+                if attempt < DOWNLOAD_ATTEMPTS =>
+            {
+                std::thread::sleep(Duration::from_millis(500 * u64::from(attempt)));
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+fn download_attempt(
+    client: &Client,
+    url: &str,
+    path: &Path,
+    progress: &mut impl FnMut(DownloadProgress),
+) -> Result<(), Error> {
+    let offset = path.metadata().map_or(0, |it| it.len());
+    let mut request = client.get(url);
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={offset}-"));
+    }
+    let mut response = request.send()?;
+
+    // Servers that don't support ranges send the whole file again.
+    let (mut file, mut downloaded) = match response.status() {
+        StatusCode::PARTIAL_CONTENT => (OpenOptions::new().append(true).open(path)?, offset),
+        // The file was already complete. Its checksum decides if it's usable.
+        StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => return Ok(()),
+        it if it.is_success() => (File::create(path)?, 0),
+        it if it.is_server_error() => {
+            return Err(Error::DownloadInterrupted(format!("{url} ({it})")))
+        }
+        it => {
+            return Err(Error::RequestError(format!(
+                "failed to download file from {url} ({it})"
+            )))
+        }
+    };
+    let total = response.content_length().map(|it| it + downloaded);
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        let read = response.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])?;
+        downloaded += read as u64;
+        progress(DownloadProgress { downloaded, total });
+    }
+
+    if total.is_some_and(|it| downloaded < it) {
+        return Err(Error::DownloadInterrupted(format!(
+            "{url} ({downloaded} of {} bytes)",
+            total.unwrap_or_default()
+        )));
+    }
+
+    Ok(())
 }
 
 pub(crate) fn download_release(release: &Release) -> Result<Vec<u8>, Error> {
@@ -139,9 +293,9 @@ pub(crate) fn download_release(release: &Release) -> Result<Vec<u8>, Error> {
     Ok(contents)
 }
 
-pub(crate) fn validate_checksum(bytes: &[u8], checksum: &str) -> Result<(), Error> {
+pub(crate) fn validate_checksum(mut reader: impl Read, checksum: &str) -> Result<(), Error> {
     let mut hasher = Sha256::new();
-    hasher.update(bytes);
+    std::io::copy(&mut reader, &mut hasher)?;
 
     if hex::encode(hasher.finalize()).eq_ignore_ascii_case(checksum) {
         Ok(())
@@ -155,9 +309,169 @@ pub(crate) fn validate_checksum(bytes: &[u8], checksum: &str) -> Result<(), Erro
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{fs::File, io::Write};
+    use crate::Version;
+    use std::{
+        fs::File,
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+    };
     use tempfile::TempDir;
 
+    /// Serve the `body`, cutting the first response off halfway and answering range requests
+    /// with the rest. The cut off connection is left open without sending anything more if
+    /// `stall` is `true`. Returns the URL and the `Range` headers the requests had.
+    fn serve_interrupted(
+        body: Vec<u8>,
+        stall: bool,
+    ) -> (String, std::sync::mpsc::Receiver<Option<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/python.tar.zst", listener.local_addr().unwrap());
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        std::thread::spawn(move || {
+            let mut stalled = Vec::new();
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let range = BufReader::new(stream.try_clone().unwrap())
+                    .lines()
+                    .map(Result::unwrap)
+                    .take_while(|it| !it.is_empty())
+                    .find_map(|it| {
+                        it.to_ascii_lowercase()
+                            .strip_prefix("range: bytes=")
+                            .map(|it| it.trim_end_matches('-').to_string())
+                    });
+                sender.send(range.clone()).unwrap();
+
+                if let Some(start) = range {
+                    let start = start.parse::<usize>().unwrap();
+                    let rest = &body[start..];
+                    let head = format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {start}-{}/{}\r\nConnection: close\r\n\r\n",
+                        rest.len(),
+                        body.len() - 1,
+                        body.len()
+                    );
+                    stream.write_all(head.as_bytes()).unwrap();
+                    stream.write_all(rest).unwrap();
+                } else {
+                    let head = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    );
+                    stream.write_all(head.as_bytes()).unwrap();
+                    stream.write_all(&body[..body.len() / 2]).unwrap();
+                    if stall {
+                        stalled.push(stream);
+                    }
+                }
+            }
+        });
+
+        (url, receiver)
+    }
+
+    /// Get a zstd-compressed tarball with a `python/install/bin/python3` file.
+    fn release_archive() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let contents = vec![b'#'; 200_000];
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder
+            .append_data(
+                &mut header,
+                "python/install/bin/python3",
+                contents.as_slice(),
+            )
+            .unwrap();
+
+        zstd::encode_all(builder.into_inner().unwrap().as_slice(), 0).unwrap()
+    }
+
+    #[test]
+    fn test_install_with_progress_resumes() {
+        let archive = release_archive();
+        let checksum = hex::encode(Sha256::digest(&archive));
+        let (url, ranges) = serve_interrupted(archive.clone(), false);
+        let release = Release {
+            kind: "cpython",
+            version: Version::new(3, 12, 0),
+            os: "linux",
+            architecture: "x86_64",
+            build_configuration: "pgo+lto",
+            checksum: &checksum,
+            url: &url,
+        };
+        let dir = TempDir::new().unwrap();
+        let target = dir.path().join("huak-cpython-3.12.0");
+        let mut reported = Vec::new();
+
+        install_with_progress(&release, &target, |it| reported.push(it)).unwrap();
+
+        assert_eq!(
+            ranges.try_iter().collect::<Vec<_>>(),
+            [None, Some((archive.len() / 2).to_string())]
+        );
+        assert_eq!(
+            reported.last(),
+            Some(&DownloadProgress {
+                downloaded: archive.len() as u64,
+                total: Some(archive.len() as u64),
+            })
+        );
+        assert!(target
+            .join("python")
+            .join("install")
+            .join("bin")
+            .join("python3")
+            .exists());
+        assert!(!with_suffix(&target, ".part").exists());
+        assert!(!with_suffix(&target, ".tmp").exists());
+    }
+
+    #[test]
+    fn test_download_with_retries_stalled() {
+        let archive = release_archive();
+        let (url, ranges) = serve_interrupted(archive.clone(), true);
+        let client = Client::builder()
+            .timeout(Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("python.tar.zst");
+
+        download_with_retries(&client, &url, &path, &mut |_| {}).unwrap();
+
+        assert_eq!(
+            ranges.try_iter().collect::<Vec<_>>(),
+            [None, Some((archive.len() / 2).to_string())]
+        );
+        assert_eq!(std::fs::read(&path).unwrap(), archive);
+    }
+
+    #[test]
+    fn test_install_with_progress_bad_checksum() {
+        let archive = release_archive();
+        let (url, _) = serve_interrupted(archive, false);
+        let release = Release {
+            kind: "cpython",
+            version: Version::new(3, 12, 0),
+            os: "linux",
+            architecture: "x86_64",
+            build_configuration: "pgo+lto",
+            checksum: "00",
+            url: &url,
+        };
+        let dir = TempDir::new().unwrap();
+        let target = dir.path().join("huak-cpython-3.12.0");
+
+        assert!(install_with_progress(&release, &target, |_| {}).is_err());
+        assert!(!target.exists());
+        assert!(!with_suffix(&target, ".part").exists());
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_python_build_standalone_helper_unix() {